@@ -0,0 +1,60 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Benchmark for the string distance calculation of a full track matrix.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use helicon::string_distance::{normalize, StringDistanceBuffer, StringMetric};
+
+/// Number of tracks on each side of the track matrix.
+const TRACK_COUNT: usize = 40;
+
+/// Generate some track titles.
+fn track_titles(prefix: &str) -> Vec<String> {
+    (1..=TRACK_COUNT)
+        .map(|number| format!("{prefix} Étude No. {number} in C Minor, Op. 10 & Finale, The"))
+        .collect()
+}
+
+/// Compare all track titles of both sides against each other.
+fn bench_track_matrix(c: &mut Criterion) {
+    let lhs = track_titles("Chopin:");
+    let rhs = track_titles("Frédéric Chopin -");
+
+    for metric in [
+        StringMetric::Levenshtein,
+        StringMetric::JaroWinkler,
+        StringMetric::TokenSetRatio,
+    ] {
+        let _ = c.bench_function(&format!("{metric:?} (unnormalized)"), |b| {
+            b.iter(|| {
+                for lhs in &lhs {
+                    for rhs in &rhs {
+                        let _ = black_box(metric.between(lhs, rhs));
+                    }
+                }
+            });
+        });
+
+        let _ = c.bench_function(&format!("{metric:?} (normalized, shared buffer)"), |b| {
+            b.iter(|| {
+                let lhs: Vec<_> = lhs.iter().map(|value| normalize(value)).collect();
+                let rhs: Vec<_> = rhs.iter().map(|value| normalize(value)).collect();
+                let mut buffer = StringDistanceBuffer::default();
+                for lhs in &lhs {
+                    for rhs in &rhs {
+                        let _ = black_box(metric.between_normalized(lhs, rhs, &mut buffer));
+                    }
+                }
+            });
+        });
+    }
+}
+
+criterion_group!(benches, bench_track_matrix);
+criterion_main!(benches);
@@ -0,0 +1,224 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Submission of fingerprints to [AcoustID](https://acoustid.org).
+//!
+//! After a release has been imported, the Chromaprint fingerprints of the tracks can be submitted
+//! together with the MusicBrainz recording IDs of the matched tracks, so that the tracks can be
+//! identified by their fingerprint in the future.
+
+use crate::config::{AcoustIdConfig, NetworkConfig};
+use crate::network;
+use crate::track::TrackLike;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// URL of the AcoustID submission endpoint.
+const SUBMIT_URL: &str = "https://api.acoustid.org/v2/submit";
+
+/// Encountered when submitting fingerprints to AcoustID fails.
+#[derive(Error, Debug)]
+pub enum AcoustIdError {
+    /// The HTTP request failed.
+    #[error("AcoustID request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The response could not be parsed.
+    #[error("Invalid AcoustID response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+    /// The API returned an error.
+    #[error("AcoustID returned an error: {0}")]
+    Api(String),
+}
+
+/// A fingerprint of a track that is submitted to AcoustID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    /// The compressed Chromaprint fingerprint (base64-encoded).
+    pub fingerprint: String,
+    /// Duration of the track (in seconds).
+    pub duration: i64,
+    /// MusicBrainz ID of the recording that the track was matched to.
+    pub musicbrainz_recording_id: String,
+    /// File format of the track (e.g., `FLAC`).
+    pub file_format: Option<String>,
+    /// Bitrate of the track (in kbps).
+    pub bitrate: Option<u32>,
+}
+
+impl Submission {
+    /// Create a submission from a track that has been tagged.
+    ///
+    /// Returns `None` if the track has no fingerprint, length or MusicBrainz recording ID.
+    pub fn from_track<T: TrackLike + ?Sized>(track: &T) -> Option<Self> {
+        Some(Self {
+            fingerprint: track.acoustid_fingerprint()?.into_owned(),
+            duration: track.track_length()?.num_seconds(),
+            musicbrainz_recording_id: track.musicbrainz_recording_id()?.into_owned(),
+            file_format: track.codec().map(|codec| codec.to_uppercase()),
+            bitrate: track.bitrate(),
+        })
+        .filter(|submission| submission.duration > 0)
+    }
+}
+
+/// Build the form parameters of a submission request.
+fn submission_params(
+    client_key: &str,
+    user_key: &str,
+    submissions: &[Submission],
+) -> Vec<(String, String)> {
+    let mut params = vec![
+        ("client".to_string(), client_key.to_string()),
+        ("clientversion".to_string(), crate::PKG_VERSION.to_string()),
+        ("user".to_string(), user_key.to_string()),
+        ("format".to_string(), "json".to_string()),
+    ];
+    for (index, submission) in submissions.iter().enumerate() {
+        params.push((
+            format!("fingerprint.{index}"),
+            submission.fingerprint.clone(),
+        ));
+        params.push((format!("duration.{index}"), submission.duration.to_string()));
+        params.push((
+            format!("mbid.{index}"),
+            submission.musicbrainz_recording_id.clone(),
+        ));
+        if let Some(file_format) = &submission.file_format {
+            params.push((format!("fileformat.{index}"), file_format.clone()));
+        }
+        if let Some(bitrate) = submission.bitrate {
+            params.push((format!("bitrate.{index}"), bitrate.to_string()));
+        }
+    }
+    params
+}
+
+/// Error details in an AcoustID response.
+#[derive(Debug, Deserialize)]
+struct ResponseError {
+    /// The error message.
+    message: String,
+}
+
+/// Response of the AcoustID API.
+#[derive(Debug, Deserialize)]
+struct Response {
+    /// Either `ok` or `error`.
+    status: String,
+    /// Error details (if the status is `error`).
+    error: Option<ResponseError>,
+}
+
+/// Client for submitting fingerprints to AcoustID.
+#[derive(Debug, Clone)]
+pub struct AcoustIdClient {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The application API key.
+    client_key: String,
+    /// The API key of the user.
+    user_key: String,
+}
+
+impl AcoustIdClient {
+    /// Create a client from the configuration.
+    ///
+    /// Returns `None` if submission is disabled or if an API key is missing.
+    pub fn from_config(config: &AcoustIdConfig, network: &NetworkConfig) -> Option<Self> {
+        if !config.submit {
+            return None;
+        }
+
+        let (Some(client_key), Some(user_key)) = (&config.client_key, &config.user_key) else {
+            log::warn!("AcoustID submission is enabled, but the API keys are not configured");
+            return None;
+        };
+
+        Some(Self {
+            client: network::http_client(network)
+                .inspect_err(|err| log::warn!("Failed to create HTTP client for AcoustID: {err}"))
+                .ok()?,
+            client_key: client_key.clone(),
+            user_key: user_key.clone(),
+        })
+    }
+
+    /// Submit the fingerprints to AcoustID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the API returns an error.
+    pub async fn submit(&self, submissions: &[Submission]) -> Result<(), AcoustIdError> {
+        if submissions.is_empty() {
+            return Ok(());
+        }
+
+        let params = submission_params(&self.client_key, &self.user_key, submissions);
+        let body = self
+            .client
+            .post(SUBMIT_URL)
+            .form(&params)
+            .send()
+            .await?
+            .text()
+            .await?;
+        let response: Response = serde_json::from_str(&body)?;
+        if response.status == "ok" {
+            Ok(())
+        } else {
+            Err(AcoustIdError::Api(response.error.map_or_else(
+                || format!("unexpected status {}", response.status),
+                |error| error.message,
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission_params() {
+        let submissions = [
+            Submission {
+                fingerprint: "AQAAT0mUaEkSRZEG".to_string(),
+                duration: 195,
+                musicbrainz_recording_id: "b4b7a2ee-4d4b-4b9d-a4c8-e6b3a5b9e1a2".to_string(),
+                file_format: Some("FLAC".to_string()),
+                bitrate: None,
+            },
+            Submission {
+                fingerprint: "AQAAT0mkSiqiJEqS".to_string(),
+                duration: 241,
+                musicbrainz_recording_id: "0b8c2a0b-7c3a-4e0f-9f5b-3c1a2d4e5f60".to_string(),
+                file_format: None,
+                bitrate: Some(320),
+            },
+        ];
+
+        let params = submission_params("client", "user", &submissions);
+        let value = |key: &str| {
+            params
+                .iter()
+                .find(|(param, _)| param == key)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value("client"), Some("client"));
+        assert_eq!(value("user"), Some("user"));
+        assert_eq!(value("fingerprint.0"), Some("AQAAT0mUaEkSRZEG"));
+        assert_eq!(value("duration.0"), Some("195"));
+        assert_eq!(value("fileformat.0"), Some("FLAC"));
+        assert_eq!(value("bitrate.0"), None);
+        assert_eq!(
+            value("mbid.1"),
+            Some("0b8c2a0b-7c3a-4e0f-9f5b-3c1a2d4e5f60")
+        );
+        assert_eq!(value("bitrate.1"), Some("320"));
+    }
+}
@@ -12,7 +12,7 @@
 //!
 //! [fpcalc]: https://acoustid.org/chromaprint
 
-use super::{Analyzer, AnalyzerError};
+use super::{Analyzer, AnalyzerError, Samples};
 use crate::config::Config;
 use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
 
@@ -32,6 +32,9 @@ pub struct ChromaprintFingerprintAnalyzer {
     stream_size_max: usize,
     /// Current stream size that already was analyzed.
     stream_size: usize,
+    /// Stereo channels that the channels of the track are mixed into (if the samples need to be
+    /// downmixed to stereo).
+    downmix_channels: Option<Vec<DownmixChannel>>,
 }
 
 /// Analysis result of the Chromaprint analyzer.
@@ -48,11 +51,128 @@ impl ChromaprintFingerprintResult {
     pub fn fingerprint_string(&self) -> String {
         BASE64_URL_SAFE_NO_PAD.encode(&self.fingerprint)
     }
+
+    /// Create a result from an existing base64-encoded fingerprint (e.g., from the file tags).
+    ///
+    /// The analyzed duration of such a fingerprint is unknown and set to 0. Returns `None` if the
+    /// string is not a valid fingerprint.
+    pub fn from_fingerprint_string(value: &str) -> Option<Self> {
+        let fingerprint = BASE64_URL_SAFE_NO_PAD.decode(value.trim()).ok()?;
+        (!fingerprint.is_empty()).then_some(Self {
+            duration: 0,
+            fingerprint,
+        })
+    }
+
+    /// Returns `true` if the fingerprint does not contain any information, i.e., it is empty or
+    /// all of its items are the same (e.g., because the analyzed audio is silent).
+    ///
+    /// The compressed fingerprint consists of a 4-byte header (algorithm and item count) and the
+    /// packed 3-bit values of the XOR-ed differences between consecutive items, where each item
+    /// is terminated by a 0. If all items are the same, every item after the first one consists
+    /// of the terminating 0 only.
+    pub fn is_silent(&self) -> bool {
+        let Some((header, data)) = self.fingerprint.split_first_chunk::<4>() else {
+            return true;
+        };
+        let item_count = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+        if item_count < 2 {
+            return true;
+        }
+
+        let mut values = (0..data.len() * 8 / 3).map(|index| {
+            let bit_offset = index * 3;
+            let bits = u16::from(data[bit_offset / 8])
+                | data
+                    .get(bit_offset / 8 + 1)
+                    .map_or(0, |byte| u16::from(*byte) << 8);
+            (bits >> (bit_offset % 8)) & 0b111
+        });
+        // Skip the first item, which is encoded relative to 0.
+        if !values.any(|value| value == 0) {
+            return false;
+        }
+        (1..item_count).all(|_| values.next() == Some(0))
+    }
 }
 
 /// Maximum duration that will be analyzed.
 const MAX_DURATION: usize = 120;
 
+/// Maximum number of channels that are passed to the fingerprinter without downmixing.
+const MAX_CHANNELS: usize = 2;
+
+/// Stereo channel that a channel of the track is mixed into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DownmixChannel {
+    /// Mixed into the left channel.
+    Left,
+    /// Mixed into the right channel.
+    Right,
+    /// Mixed into both channels (e.g., the centre channel).
+    Both,
+    /// Not mixed into the stereo channels (i.e., low-frequency effects).
+    Dropped,
+}
+
+/// Map the channel layout of the track to the stereo channels.
+///
+/// The interleaved samples are in the order of the channel bits, so the position of each channel
+/// is taken from the layout instead of assuming a specific order.
+fn downmix_map(channels: Channels) -> Vec<DownmixChannel> {
+    channels
+        .iter()
+        .map(|channel| match channel {
+            Channels::FRONT_LEFT
+            | Channels::FRONT_LEFT_CENTRE
+            | Channels::FRONT_LEFT_WIDE
+            | Channels::FRONT_LEFT_HIGH
+            | Channels::REAR_LEFT
+            | Channels::REAR_LEFT_CENTRE
+            | Channels::SIDE_LEFT
+            | Channels::TOP_FRONT_LEFT
+            | Channels::TOP_REAR_LEFT => DownmixChannel::Left,
+            Channels::FRONT_RIGHT
+            | Channels::FRONT_RIGHT_CENTRE
+            | Channels::FRONT_RIGHT_WIDE
+            | Channels::FRONT_RIGHT_HIGH
+            | Channels::REAR_RIGHT
+            | Channels::REAR_RIGHT_CENTRE
+            | Channels::SIDE_RIGHT
+            | Channels::TOP_FRONT_RIGHT
+            | Channels::TOP_REAR_RIGHT => DownmixChannel::Right,
+            Channels::LFE1 | Channels::LFE2 => DownmixChannel::Dropped,
+            _ => DownmixChannel::Both,
+        })
+        .collect()
+}
+
+/// Downmix interleaved multi-channel samples to stereo.
+///
+/// Each channel is mixed into the stereo channels according to its position in the channel map.
+fn downmix_to_stereo(samples: &[i16], channel_map: &[DownmixChannel]) -> Vec<i16> {
+    samples
+        .chunks_exact(channel_map.len())
+        .flat_map(|frame| {
+            let (mut left, mut right) = (0i32, 0i32);
+            let (mut left_count, mut right_count) = (0i32, 0i32);
+            for (&sample, channel) in frame.iter().zip(channel_map) {
+                let sample = i32::from(sample);
+                if matches!(channel, DownmixChannel::Left | DownmixChannel::Both) {
+                    left += sample;
+                    left_count += 1;
+                }
+                if matches!(channel, DownmixChannel::Right | DownmixChannel::Both) {
+                    right += sample;
+                    right_count += 1;
+                }
+            }
+            [left / left_count.max(1), right / right_count.max(1)]
+                .map(|value| i16::try_from(value).unwrap_or_default())
+        })
+        .collect()
+}
+
 impl Analyzer for ChromaprintFingerprintAnalyzer {
     type Result = ChromaprintFingerprintResult;
 
@@ -60,11 +180,14 @@ impl Analyzer for ChromaprintFingerprintAnalyzer {
         let sample_rate = codec_params
             .sample_rate
             .ok_or(AnalyzerError::MissingSampleRate)?;
-        let channels = codec_params
+        let channel_layout = codec_params
             .channels
-            .map(Channels::count)
-            .and_then(|channel_count| u32::try_from(channel_count).ok())
+            .filter(|channels| channels.count() > 0)
             .ok_or(AnalyzerError::MissingAudioChannels)?;
+        let channel_count = channel_layout.count();
+        let downmix_channels = (channel_count > MAX_CHANNELS).then(|| downmix_map(channel_layout));
+        let channels = u32::try_from(channel_count.min(MAX_CHANNELS))
+            .map_err(|_err| AnalyzerError::MissingAudioChannels)?;
 
         let chromaprint_config = Configuration::preset_test2();
         let mut fingerprinter = Fingerprinter::new(&chromaprint_config);
@@ -76,15 +199,21 @@ impl Analyzer for ChromaprintFingerprintAnalyzer {
             fingerprinter,
             stream_size_max: MAX_DURATION * usize::try_from(sample_rate).unwrap(),
             stream_size: 0,
+            downmix_channels,
         };
         Ok(analyzer)
     }
 
-    fn feed(&mut self, samples: &[i16]) -> Result<(), AnalyzerError> {
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError> {
         let remaining = self.stream_size_max - self.stream_size;
-        let chunk_size = samples.len().min(remaining);
-        self.stream_size += chunk_size;
-        self.fingerprinter.consume(&samples[..chunk_size]);
+        let samples = samples.truncate(remaining).to_i16();
+        self.stream_size += samples.len();
+        match &self.downmix_channels {
+            Some(channel_map) => self
+                .fingerprinter
+                .consume(&downmix_to_stereo(&samples, channel_map)),
+            None => self.fingerprinter.consume(&samples),
+        }
         Ok(())
     }
 
@@ -92,6 +221,10 @@ impl Analyzer for ChromaprintFingerprintAnalyzer {
         self.stream_size >= self.stream_size_max
     }
 
+    fn max_samples_needed(&self) -> Option<usize> {
+        Some(self.stream_size_max.saturating_sub(self.stream_size))
+    }
+
     fn finalize(mut self) -> Result<Self::Result, AnalyzerError> {
         self.fingerprinter.finish();
         let raw_fingerprint = self.fingerprinter.fingerprint();
@@ -103,3 +236,101 @@ impl Analyzer for ChromaprintFingerprintAnalyzer {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_samples_needed() {
+        let mut codec_params = CodecParameters::new();
+        let _ = codec_params
+            .with_sample_rate(11025)
+            .with_channels(Channels::FRONT_LEFT | Channels::FRONT_RIGHT);
+        let mut analyzer =
+            ChromaprintFingerprintAnalyzer::initialize(&Config::default(), &codec_params).unwrap();
+        let stream_size_max = MAX_DURATION * 11025;
+        assert_eq!(analyzer.max_samples_needed(), Some(stream_size_max));
+
+        analyzer.feed(Samples::I16(&[0; 1000])).unwrap();
+        assert_eq!(analyzer.max_samples_needed(), Some(stream_size_max - 1000));
+        assert!(!analyzer.is_complete());
+
+        analyzer
+            .feed(Samples::I16(&vec![0; stream_size_max]))
+            .unwrap();
+        assert_eq!(analyzer.max_samples_needed(), Some(0));
+        assert!(analyzer.is_complete());
+    }
+
+    #[test]
+    fn test_is_silent() {
+        let compress = |items: &[u32]| ChromaprintFingerprintResult {
+            duration: 0,
+            fingerprint: FingerprintCompressor::from(&Configuration::preset_test2())
+                .compress(items),
+        };
+        assert!(compress(&[0x2c3a_1b4d; 100]).is_silent());
+        assert!(compress(&[0; 100]).is_silent());
+        assert!(!compress(&[0x2c3a_1b4d, 0x2c3a_1b4c, 0x0c3a_1b4c]).is_silent());
+        assert!(!compress(&[0, 0, 0, 1]).is_silent());
+        assert!(
+            ChromaprintFingerprintResult::from_fingerprint_string("AQAAAA")
+                .unwrap()
+                .is_silent()
+        );
+    }
+
+    #[test]
+    fn test_from_fingerprint_string() {
+        let result =
+            ChromaprintFingerprintResult::from_fingerprint_string("AQAAT0mUaEkSRZEG").unwrap();
+        assert_eq!(result.fingerprint_string(), "AQAAT0mUaEkSRZEG");
+        assert_eq!(result.duration, 0);
+        assert!(ChromaprintFingerprintResult::from_fingerprint_string("").is_none());
+        assert!(ChromaprintFingerprintResult::from_fingerprint_string("not base64!").is_none());
+    }
+
+    #[test]
+    fn test_downmix_to_stereo() {
+        // Two 5.1 frames (FL, FR, FC, LFE, SL, SR).
+        let samples = [
+            100, 200, 300, 1000, 500, 600, -100, -200, 0, 1000, -300, -400,
+        ];
+        let channel_map = downmix_map(
+            Channels::FRONT_LEFT
+                | Channels::FRONT_RIGHT
+                | Channels::FRONT_CENTRE
+                | Channels::LFE1
+                | Channels::REAR_LEFT
+                | Channels::REAR_RIGHT,
+        );
+        assert_eq!(
+            downmix_to_stereo(&samples, &channel_map),
+            vec![300, 366, -133, -200]
+        );
+
+        // A 5.0 frame without LFE (FL, FR, FC, SL, SR).
+        let channel_map = downmix_map(
+            Channels::FRONT_LEFT
+                | Channels::FRONT_RIGHT
+                | Channels::FRONT_CENTRE
+                | Channels::SIDE_LEFT
+                | Channels::SIDE_RIGHT,
+        );
+        assert_eq!(
+            channel_map,
+            vec![
+                DownmixChannel::Left,
+                DownmixChannel::Right,
+                DownmixChannel::Both,
+                DownmixChannel::Left,
+                DownmixChannel::Right,
+            ]
+        );
+        assert_eq!(
+            downmix_to_stereo(&[100, 200, 300, 500, 600], &channel_map),
+            vec![300, 366]
+        );
+    }
+}
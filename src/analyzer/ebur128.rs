@@ -10,21 +10,29 @@
 //!
 //! [ebur128]: https://en.wikipedia.org/wiki/EBU_R_128
 
-use super::{Analyzer, AnalyzerError};
-use crate::config::Config;
+use super::{Analyzer, AnalyzerError, Samples};
+use crate::config::{Config, PeakMode};
 
 use symphonia::core::audio::Channels;
 use symphonia::core::codecs::CodecParameters;
 
-use ebur128::{energy_to_loudness, EbuR128, Mode};
+use ebur128::{energy_to_loudness, Channel, EbuR128, Mode};
 
 /// ReplayGain 2.0 Reference Gain
 ///
-/// See the [ReplayGain 2.0 specification][rg2spec] for details.
+/// This is used if the reference loudness is unknown. See the [ReplayGain 2.0
+/// specification][rg2spec] for details.
 ///
 /// [rg2spec]: https://wiki.hydrogenaud.io/index.php?title=ReplayGain_2.0_specification#Reference_level
 const REPLAYGAIN2_REFERENCE_LUFS: f64 = -18.0;
 
+/// Reference loudness of the Opus `R128_*_GAIN` tags.
+///
+/// See [RFC 7845, Section 5.2.1][rfc7845] for details.
+///
+/// [rfc7845]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.2.1
+const R128_REFERENCE_LUFS: f64 = -23.0;
+
 /// EBU R128 Analyzer.
 #[derive(Debug)]
 pub struct EbuR128Analyzer {
@@ -34,6 +42,10 @@ pub struct EbuR128Analyzer {
     channels: u32,
     /// Chunk size in samples (usually 1s).
     chunk_size: usize,
+    /// Reference loudness for the gain calculation (in LUFS).
+    reference_lufs: f64,
+    /// How the peak is measured.
+    peak_mode: PeakMode,
 }
 
 /// Result of the EBU R 128 analysis.
@@ -48,12 +60,22 @@ pub struct EbuR128Result {
     pub gating_block_count: u64,
     /// Energy of the track (for album gain calculation).
     pub energy: f64,
+    /// Reference loudness that the gain is calculated for.
+    pub reference_lufs: f64,
 }
 
 impl EbuR128Result {
     /// Calculate ReplayGain 2.0 Track Gain.
     pub fn replaygain_track_gain(&self) -> f64 {
-        REPLAYGAIN2_REFERENCE_LUFS - self.average_lufs
+        self.reference_lufs - self.average_lufs
+    }
+
+    /// ReplayGain 2.0 Reference Loudness, formatted according to "Table 3: Metadata keys and value
+    /// formatting" in the ["Metadata format" section in the ReplayGain 2.0 specification][rgmeta].
+    ///
+    /// [rgmeta]: https://wiki.hydrogenaud.io/index.php?title=ReplayGain_2.0_specification#Metadata_format
+    pub fn replaygain_reference_loudness_string(&self) -> String {
+        replaygain_reference_loudness_string(self.reference_lufs)
     }
 
     /// ReplayGain 2.0 Track Gain, formatted according to "Table 3: Metadata keys and value
@@ -80,6 +102,8 @@ pub struct EbuR128AlbumResult {
     pub average_lufs: f64,
     /// Peak amplitude of the audio files on the album.
     pub peak: f64,
+    /// Reference loudness that the gain is calculated for.
+    pub reference_lufs: f64,
 }
 
 impl EbuR128AlbumResult {
@@ -90,13 +114,14 @@ impl EbuR128AlbumResult {
     pub fn from_iter<'a>(
         results: impl Iterator<Item = &'a EbuR128Result>,
     ) -> Option<EbuR128AlbumResult> {
-        let (album_peak, album_gating_block_count, album_energy) = results.fold(
-            (0f64, 0u64, 0f64),
-            |(album_peak, album_gating_block_count, album_energy), result| {
+        let (album_peak, album_gating_block_count, album_energy, reference_lufs) = results.fold(
+            (0f64, 0u64, 0f64, REPLAYGAIN2_REFERENCE_LUFS),
+            |(album_peak, album_gating_block_count, album_energy, _), result| {
                 (
                     album_peak.max(result.peak),
                     album_gating_block_count + result.gating_block_count,
                     album_energy + result.energy,
+                    result.reference_lufs,
                 )
             },
         );
@@ -112,12 +137,13 @@ impl EbuR128AlbumResult {
         Some(EbuR128AlbumResult {
             average_lufs: album_average_lufs,
             peak: album_peak,
+            reference_lufs,
         })
     }
 
     /// Calculate ReplayGain 2.0 Album Gain.
     pub fn replaygain_album_gain(&self) -> f64 {
-        REPLAYGAIN2_REFERENCE_LUFS - self.average_lufs
+        self.reference_lufs - self.average_lufs
     }
 
     /// ReplayGain 2.0 Album Gain, formatted according to "Table 3: Metadata keys and value
@@ -155,6 +181,39 @@ pub fn replaygain_peak_string(peak: f64) -> String {
     format!("{peak:.6}")
 }
 
+/// Format an [`f64`] as a ReplayGain 2.0 Reference Loudness Value according to "Table 3:
+/// Metadata keys and value formatting" in the ["Metadata format" section in the ReplayGain 2.0
+/// specification][rgmeta].
+///
+/// [rgmeta]: https://wiki.hydrogenaud.io/index.php?title=ReplayGain_2.0_specification#Metadata_format
+pub fn replaygain_reference_loudness_string(reference_lufs: f64) -> String {
+    format!("{reference_lufs:.2} LUFS")
+}
+
+/// Parse a ReplayGain 2.0 Gain or Reference Loudness Value (e.g., `-7.03 dB` or `-18.00 LUFS`).
+pub fn parse_replaygain_value(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Convert a ReplayGain 2.0 gain that was calculated for the given reference loudness (or
+/// -18 LUFS if unknown) into the value of an Opus `R128_*_GAIN` tag, i.e., a Q7.8 fixed point
+/// number relative to -23 LUFS (see [RFC 7845, Section 5.2.1][rfc7845]).
+///
+/// [rfc7845]: https://datatracker.ietf.org/doc/html/rfc7845#section-5.2.1
+#[expect(clippy::cast_possible_truncation)]
+pub fn r128_gain_from_replaygain(gain: f64, reference_lufs: Option<f64>) -> i16 {
+    let gain = gain + R128_REFERENCE_LUFS - reference_lufs.unwrap_or(REPLAYGAIN2_REFERENCE_LUFS);
+    (gain * 256.0)
+        .round()
+        .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16
+}
+
 /// Convert a dBFS value to a LUFS value.
 ///
 /// See the [ReplayGain 2.0 specification][normalization] for details.
@@ -175,17 +234,36 @@ fn ratio_to_dbfs(value: f64) -> f64 {
     20.0 * value.log10()
 }
 
+/// Map the channel layout of the track to the channels of the loudness analyzer.
+///
+/// Low-frequency effects channels are not taken into account for the loudness measurement (as
+/// specified in ITU-R BS.1770). Channels without a dedicated position are weighted like the centre
+/// channel.
+fn channel_map(channels: Channels) -> Vec<Channel> {
+    channels
+        .iter()
+        .map(|channel| match channel {
+            Channels::FRONT_LEFT => Channel::Left,
+            Channels::FRONT_RIGHT => Channel::Right,
+            Channels::LFE1 | Channels::LFE2 => Channel::Unused,
+            Channels::REAR_LEFT | Channels::SIDE_LEFT => Channel::LeftSurround,
+            Channels::REAR_RIGHT | Channels::SIDE_RIGHT => Channel::RightSurround,
+            _ => Channel::Center,
+        })
+        .collect()
+}
+
 impl Analyzer for EbuR128Analyzer {
     type Result = EbuR128Result;
 
-    fn initialize(_config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
+    fn initialize(config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
         let sample_rate = codec_params
             .sample_rate
             .ok_or(AnalyzerError::MissingSampleRate)?;
-        let channel_count = codec_params
+        let channel_layout = codec_params
             .channels
-            .map(Channels::count)
             .ok_or(AnalyzerError::MissingAudioChannels)?;
+        let channel_count = channel_layout.count();
 
         let channels =
             u32::try_from(channel_count).map_err(|_| AnalyzerError::MissingAudioChannels)?;
@@ -193,22 +271,35 @@ impl Analyzer for EbuR128Analyzer {
             .map_err(|_err| AnalyzerError::MissingSampleRate)?
             * channel_count;
 
-        let ebur128 = EbuR128::new(channels, sample_rate, Mode::all())?;
+        let mut ebur128 = EbuR128::new(channels, sample_rate, Mode::all())?;
+        ebur128.set_channel_map(&channel_map(channel_layout))?;
         let analyzer = Self {
             ebur128,
             channels,
             chunk_size,
+            reference_lufs: config.analyzers.ebur128.reference_lufs,
+            peak_mode: config.analyzers.ebur128.peak_mode,
         };
         Ok(analyzer)
     }
 
-    fn feed(&mut self, samples: &[i16]) -> Result<(), AnalyzerError> {
-        let samples: Vec<f32> = samples
-            .iter()
-            .map(|&sample| f32::from(sample) / f32::from(i16::MAX))
-            .collect();
-        for chunk in samples.chunks(self.chunk_size) {
-            self.ebur128.add_frames_f32(chunk)?;
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError> {
+        match samples {
+            Samples::I16(samples) => {
+                for chunk in samples.chunks(self.chunk_size) {
+                    self.ebur128.add_frames_i16(chunk)?;
+                }
+            }
+            Samples::I32(samples) => {
+                for chunk in samples.chunks(self.chunk_size) {
+                    self.ebur128.add_frames_i32(chunk)?;
+                }
+            }
+            Samples::F32(samples) => {
+                for chunk in samples.chunks(self.chunk_size) {
+                    self.ebur128.add_frames_f32(chunk)?;
+                }
+            }
         }
         Ok(self.ebur128.loudness_global().map(|_| ())?)
     }
@@ -220,7 +311,10 @@ impl Analyzer for EbuR128Analyzer {
     fn finalize(self) -> Result<Self::Result, AnalyzerError> {
         let average_lufs = self.ebur128.loudness_global()?;
         let peak = (0..self.channels)
-            .map(|channel_index| self.ebur128.sample_peak(channel_index))
+            .map(|channel_index| match self.peak_mode {
+                PeakMode::Sample => self.ebur128.sample_peak(channel_index),
+                PeakMode::True => self.ebur128.true_peak(channel_index),
+            })
             .try_fold(0.0f64, |a, b| b.map(|b| a.max(b)))?;
         let (gating_block_count, energy) =
             self.ebur128
@@ -233,6 +327,68 @@ impl Analyzer for EbuR128Analyzer {
             peak,
             gating_block_count,
             energy,
+            reference_lufs: self.reference_lufs,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_channel_map_surround() {
+        let channels = Channels::FRONT_LEFT
+            | Channels::FRONT_RIGHT
+            | Channels::FRONT_CENTRE
+            | Channels::LFE1
+            | Channels::REAR_LEFT
+            | Channels::REAR_RIGHT;
+        assert_eq!(
+            channel_map(channels),
+            vec![
+                Channel::Left,
+                Channel::Right,
+                Channel::Center,
+                Channel::Unused,
+                Channel::LeftSurround,
+                Channel::RightSurround,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_r128_gain_from_replaygain() {
+        assert_eq!(parse_replaygain_value("-7.00 dB"), Some(-7.0));
+        assert_eq!(parse_replaygain_value(" +1.5 dB"), Some(1.5));
+        assert_eq!(parse_replaygain_value("-18.00 LUFS"), Some(-18.0));
+        assert_eq!(parse_replaygain_value("loud"), None);
+
+        assert_eq!(r128_gain_from_replaygain(-7.0, None), -3072);
+        assert_eq!(r128_gain_from_replaygain(0.0, None), -1280);
+        assert_eq!(r128_gain_from_replaygain(-12.0, Some(-23.0)), -3072);
+        assert_eq!(r128_gain_from_replaygain(200.0, None), i16::MAX);
+    }
+
+    #[test]
+    fn test_reference_loudness() {
+        let results = [-20.0, -26.0].map(|average_lufs| EbuR128Result {
+            average_lufs,
+            peak: 0.5,
+            gating_block_count: 10,
+            energy: 10.0 * 10f64.powf((average_lufs + 0.691) / 10.0),
+            reference_lufs: -23.0,
+        });
+        assert_float_eq!(results[0].replaygain_track_gain(), -3.0, abs <= 0.000_1);
+        assert_eq!(
+            results[0].replaygain_reference_loudness_string(),
+            "-23.00 LUFS"
+        );
+
+        let album_result = EbuR128AlbumResult::from_iter(results.iter()).unwrap();
+        assert_float_eq!(album_result.reference_lufs, -23.0, abs <= 0.000_1);
+        assert!(album_result.replaygain_album_gain() > -3.0);
+        assert!(album_result.replaygain_album_gain() < 3.0);
+    }
+}
@@ -23,13 +23,21 @@ use symphonia::core::probe::Hint;
 
 mod chromaprint;
 mod ebur128;
+mod samples;
+mod tempo;
 mod track_length;
 
 use chromaprint::ChromaprintFingerprintAnalyzer;
 use ebur128::EbuR128Analyzer;
-use track_length::TrackLengthAnalyzer;
+use samples::InterleavedBuffer;
+use tempo::TempoAnalyzer;
+use track_length::{track_length_from_codec_params, TrackLengthAnalyzer};
 
-pub use ebur128::EbuR128AlbumResult;
+pub use chromaprint::ChromaprintFingerprintResult;
+pub use ebur128::{parse_replaygain_value, r128_gain_from_replaygain, EbuR128AlbumResult};
+pub use samples::Samples;
+pub use tempo::TempoResult;
+pub use track_length::estimate_bitrate;
 
 /// An error during analysis.
 #[derive(Error, Debug)]
@@ -70,10 +78,15 @@ where
 
     /// Initialize the analyzer.
     fn initialize(config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError>;
-    /// Feed samples into the analysis.
-    fn feed(&mut self, samples: &[i16]) -> Result<(), AnalyzerError>;
+    /// Feed interleaved samples into the analysis.
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError>;
     /// Returns `true` if the Analyzer is complete and does not need additional input.
     fn is_complete(&self) -> bool;
+    /// Returns the number of (interleaved) samples that the analyzer still needs, or `None` if it
+    /// needs all remaining samples of the file.
+    fn max_samples_needed(&self) -> Option<usize> {
+        self.is_complete().then_some(0)
+    }
     /// Finalize the analysis and return the result.
     fn finalize(self) -> Result<Self::Result, AnalyzerError>;
 }
@@ -94,6 +107,8 @@ enum CompoundAnalyzerItem {
     ChromaprintFingerprint(Box<ChromaprintFingerprintAnalyzer>),
     /// EBU R 128 Analyzer.
     EbuR128(Box<EbuR128Analyzer>),
+    /// Tempo Analyzer.
+    Tempo(Box<TempoAnalyzer>),
 }
 
 impl CompoundAnalyzerItem {
@@ -130,6 +145,13 @@ impl CompoundAnalyzerItem {
                     None
                 }
             },
+            AnalyzerType::Tempo => match TempoAnalyzer::initialize(config, codec_params) {
+                Ok(analyzer) => Some(Self::Tempo(Box::from(analyzer))),
+                Err(err) => {
+                    result.tempo = Some(Err(err));
+                    None
+                }
+            },
         }
     }
 
@@ -139,6 +161,17 @@ impl CompoundAnalyzerItem {
             Self::TrackLength(analyzer) => analyzer.is_complete(),
             Self::ChromaprintFingerprint(analyzer) => analyzer.is_complete(),
             Self::EbuR128(analyzer) => analyzer.is_complete(),
+            Self::Tempo(analyzer) => analyzer.is_complete(),
+        }
+    }
+
+    /// Returns the number of samples that the analyzer still needs.
+    fn max_samples_needed(&self) -> Option<usize> {
+        match self {
+            Self::TrackLength(analyzer) => analyzer.max_samples_needed(),
+            Self::ChromaprintFingerprint(analyzer) => analyzer.max_samples_needed(),
+            Self::EbuR128(analyzer) => analyzer.max_samples_needed(),
+            Self::Tempo(analyzer) => analyzer.max_samples_needed(),
         }
     }
 
@@ -146,7 +179,7 @@ impl CompoundAnalyzerItem {
     /// occurs.
     fn feed_or_assign_result(
         &mut self,
-        samples: &[i16],
+        samples: Samples<'_>,
         result: &mut CompoundAnalyzerResult,
     ) -> bool {
         match self {
@@ -171,6 +204,13 @@ impl CompoundAnalyzerItem {
                     false
                 }
             },
+            Self::Tempo(analyzer) => match analyzer.feed(samples) {
+                Ok(()) => true,
+                Err(err) => {
+                    result.tempo = Some(Err(err));
+                    false
+                }
+            },
         }
     }
 
@@ -189,6 +229,9 @@ impl CompoundAnalyzerItem {
             Self::EbuR128(analyzer) => {
                 result.ebur128 = Some(analyzer.finalize());
             }
+            Self::Tempo(analyzer) => {
+                result.tempo = Some(analyzer.finalize());
+            }
         }
         result
     }
@@ -204,16 +247,60 @@ pub struct CompoundAnalyzerResult {
         Option<Result<<ChromaprintFingerprintAnalyzer as Analyzer>::Result, AnalyzerError>>,
     /// Result of the EBU R 128 analysis.
     pub ebur128: Option<Result<<EbuR128Analyzer as Analyzer>::Result, AnalyzerError>>,
+    /// Result of the tempo analysis.
+    pub tempo: Option<Result<<TempoAnalyzer as Analyzer>::Result, AnalyzerError>>,
+    /// Technical properties of the audio stream.
+    pub technical_metadata: Option<TechnicalMetadata>,
 }
 
-impl Analyzer for CompoundAnalyzer {
-    type Result = CompoundAnalyzerResult;
+/// Technical properties of the audio stream, as determined when probing the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TechnicalMetadata {
+    /// Short name of the codec (e.g., `flac` or `mp3`).
+    pub codec: Option<String>,
+    /// Average bitrate in kbit/s.
+    pub bitrate: Option<u32>,
+    /// Sample rate in Hz.
+    pub sample_rate: Option<u32>,
+    /// Number of bits per sample.
+    pub bit_depth: Option<u32>,
+    /// Number of channels.
+    pub channels: Option<u32>,
+}
 
-    fn initialize(config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
+impl TechnicalMetadata {
+    /// Determine the technical metadata from the codec parameters.
+    ///
+    /// Symphonia does not provide the bitrate, so the average bitrate is calculated from the file
+    /// size and the track length instead.
+    fn from_codec_params(codec_params: &CodecParameters, file_size: Option<u64>) -> Self {
+        let bitrate = file_size
+            .zip(track_length_from_codec_params(codec_params))
+            .and_then(|(file_size, track_length)| estimate_bitrate(file_size, track_length));
+
+        Self {
+            codec: symphonia::default::get_codecs()
+                .get_codec(codec_params.codec)
+                .map(|descriptor| descriptor.short_name.to_string()),
+            bitrate,
+            sample_rate: codec_params.sample_rate,
+            bit_depth: codec_params.bits_per_sample,
+            channels: codec_params
+                .channels
+                .and_then(|channels| u32::try_from(channels.count()).ok()),
+        }
+    }
+}
+
+impl CompoundAnalyzer {
+    /// Initialize a compound analyzer that runs the analyzers of the given types.
+    fn with_analyzer_types(
+        config: &Config,
+        codec_params: &CodecParameters,
+        analyzer_types: &[AnalyzerType],
+    ) -> Self {
         let mut results = CompoundAnalyzerResult::default();
-        let analyzers = config
-            .analyzers
-            .enabled
+        let analyzers = analyzer_types
             .iter()
             .copied()
             .filter_map(|analyzer_type| {
@@ -226,12 +313,27 @@ impl Analyzer for CompoundAnalyzer {
             })
             .collect::<Vec<CompoundAnalyzerItem>>();
 
-        Ok(Self { analyzers, results })
+        Self { analyzers, results }
     }
+}
 
-    fn feed(&mut self, samples: &[i16]) -> Result<(), AnalyzerError> {
-        self.analyzers
-            .retain_mut(|analyzer| analyzer.feed_or_assign_result(samples, &mut self.results));
+impl Analyzer for CompoundAnalyzer {
+    type Result = CompoundAnalyzerResult;
+
+    fn initialize(config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
+        Ok(Self::with_analyzer_types(
+            config,
+            codec_params,
+            &config.analyzers.enabled,
+        ))
+    }
+
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError> {
+        // Analyzers that are already complete (e.g., because they only need the codec
+        // parameters) are skipped.
+        self.analyzers.retain_mut(|analyzer| {
+            analyzer.is_complete() || analyzer.feed_or_assign_result(samples, &mut self.results)
+        });
         Ok(())
     }
 
@@ -239,11 +341,21 @@ impl Analyzer for CompoundAnalyzer {
         self.analyzers.iter().all(CompoundAnalyzerItem::is_complete)
     }
 
-    fn finalize(self) -> Result<CompoundAnalyzerResult, AnalyzerError> {
+    fn max_samples_needed(&self) -> Option<usize> {
+        self.analyzers
+            .iter()
+            .map(CompoundAnalyzerItem::max_samples_needed)
+            .try_fold(0, |max, needed| needed.map(|needed| max.max(needed)))
+    }
+
+    fn finalize(mut self) -> Result<CompoundAnalyzerResult, AnalyzerError> {
+        // The results already contain the errors of analyzers that failed during initialization
+        // or while being fed, so these must not be discarded.
+        let results = std::mem::take(&mut self.results);
         Ok(self
             .analyzers
             .into_iter()
-            .fold(CompoundAnalyzerResult::default(), |results, analyzer| {
+            .fold(results, |results, analyzer| {
                 analyzer.finalize_and_assign_result(results)
             }))
     }
@@ -253,8 +365,8 @@ impl Analyzer for CompoundAnalyzer {
 struct AudioReader {
     /// Audio format reader.
     format: Box<dyn FormatReader>,
-    /// Audio decoder.
-    decoder: Box<dyn Decoder>,
+    /// Audio decoder (created lazily when the first packet is decoded).
+    decoder: Option<Box<dyn Decoder>>,
     /// Track ID.
     track_id: u32,
 }
@@ -288,17 +400,30 @@ impl AudioReader {
 
         let track_id = track.id;
 
-        let dec_opts: DecoderOptions = DecoderOptions::default();
-
-        let decoder = symphonia::default::get_codecs().make(&track.codec_params, &dec_opts)?;
-
         Ok(Self {
             format,
-            decoder,
+            decoder: None,
             track_id,
         })
     }
 
+    /// Get the decoder, or create it if it does not exist yet.
+    ///
+    /// The decoder is only created on demand, so that no decoder needs to be set up if all
+    /// information can be determined from the container metadata.
+    fn decoder(&mut self) -> Result<&mut Box<dyn Decoder>, SymphoniaError> {
+        if self.decoder.is_none() {
+            let codec_params = self
+                .codec_params()
+                .ok_or(SymphoniaError::Unsupported("no supported audio tracks"))?;
+            let dec_opts: DecoderOptions = DecoderOptions::default();
+            let decoder = symphonia::default::get_codecs().make(codec_params, &dec_opts)?;
+            self.decoder = Some(decoder);
+        }
+
+        Ok(self.decoder.as_mut().expect("decoder has been created"))
+    }
+
     /// Get the codec parameters.
     fn codec_params(&self) -> Option<&CodecParameters> {
         self.format
@@ -322,7 +447,8 @@ impl AudioReader {
 
             break Ok(packet);
         };
-        packet.and_then(|pkt| self.decoder.decode(&pkt))
+        let packet = packet?;
+        self.decoder()?.decode(&packet)
     }
 }
 
@@ -330,14 +456,70 @@ impl AudioReader {
 pub fn analyze(
     config: &Config,
     path: impl AsRef<Path>,
+) -> Result<CompoundAnalyzerResult, AnalyzerError> {
+    analyze_with_analyzer_types(config, path, &config.analyzers.enabled)
+}
+
+/// Run an analysis, but reuse an existing AcoustID fingerprint (e.g., from the file tags) instead
+/// of running the Chromaprint analyzer.
+///
+/// If the fingerprint is invalid, the Chromaprint analyzer is run as usual.
+pub fn analyze_with_fingerprint(
+    config: &Config,
+    path: impl AsRef<Path>,
+    fingerprint: &str,
+) -> Result<CompoundAnalyzerResult, AnalyzerError> {
+    let enabled = &config.analyzers.enabled;
+    if !enabled.contains(&AnalyzerType::ChromaprintFingerprint) {
+        return analyze(config, path);
+    }
+
+    let Some(fingerprint) = ChromaprintFingerprintResult::from_fingerprint_string(fingerprint)
+    else {
+        log::debug!(
+            "Ignoring invalid fingerprint of file: {}",
+            path.as_ref().display()
+        );
+        return analyze(config, path);
+    };
+
+    log::debug!(
+        "Reusing existing fingerprint of file: {}",
+        path.as_ref().display()
+    );
+    let analyzer_types = enabled
+        .iter()
+        .copied()
+        .filter(|analyzer_type| *analyzer_type != AnalyzerType::ChromaprintFingerprint)
+        .collect::<Vec<_>>();
+    let mut result = analyze_with_analyzer_types(config, path, &analyzer_types)?;
+    result.chromaprint_fingerprint = Some(Ok(fingerprint));
+    Ok(result)
+}
+
+/// Run an analysis with the analyzers of the given types.
+fn analyze_with_analyzer_types(
+    config: &Config,
+    path: impl AsRef<Path>,
+    analyzer_types: &[AnalyzerType],
 ) -> Result<CompoundAnalyzerResult, AnalyzerError> {
     log::debug!("Analyzing file: {}", path.as_ref().display());
     let mut reader = AudioReader::new(&path)?;
     let codec_params = reader
         .codec_params()
         .ok_or(AnalyzerError::NoSupportedAudioTracks)?;
-
-    let mut analyzer = CompoundAnalyzer::initialize(config, codec_params)?;
+    let file_size = std::fs::metadata(path.as_ref())
+        .ok()
+        .map(|metadata| metadata.len());
+    let technical_metadata = TechnicalMetadata::from_codec_params(codec_params, file_size);
+
+    let mut analyzer = CompoundAnalyzer::with_analyzer_types(config, codec_params, analyzer_types);
+    // If all results can be determined from the codec parameters (e.g., if only the track length
+    // was requested), the analyzer is already complete and nothing is decoded. Otherwise, decoding
+    // stops as soon as all analyzers have received the samples they need.
+    if !analyzer.is_complete() {
+        let _ = reader.decoder()?;
+    }
 
     let mut sample_buf = None;
     while !analyzer.is_complete() {
@@ -347,17 +529,111 @@ pub fn analyze(
             Err(_) => break,
         };
 
+        let buf = sample_buf.get_or_insert_with(|| InterleavedBuffer::new(&audio_buf));
+        let samples = buf.copy_interleaved_ref(audio_buf);
+        let samples = match analyzer.max_samples_needed() {
+            Some(needed) => samples.truncate(needed),
+            None => samples,
+        };
+        if !samples.is_empty() {
+            analyzer.feed(samples)?;
+        }
+    }
+
+    let mut result = analyzer.finalize()?;
+    result.technical_metadata = Some(technical_metadata);
+    Ok(result)
+}
+
+/// Decode the whole audio file and pass the interleaved samples to the callback.
+///
+/// The callback receives the sample rate, the number of channels, the bit depth of the source
+/// and the decoded samples (scaled to the full range of `i32`).
+pub fn decode_interleaved<F>(path: impl AsRef<Path>, mut callback: F) -> Result<(), AnalyzerError>
+where
+    F: FnMut(u32, usize, u32, &[i32]) -> Result<(), AnalyzerError>,
+{
+    log::debug!("Decoding file: {}", path.as_ref().display());
+    let mut reader = AudioReader::new(&path)?;
+    let bits_per_sample = reader
+        .codec_params()
+        .and_then(|codec_params| codec_params.bits_per_sample)
+        .unwrap_or(16);
+    let _ = reader.decoder()?;
+
+    let mut sample_buf = None;
+    loop {
+        let audio_buf = match reader.next_buffer() {
+            Ok(buffer) => buffer,
+            Err(SymphoniaError::DecodeError(err)) => Err(SymphoniaError::DecodeError(err))?,
+            Err(_) => break,
+        };
+
+        let spec = *audio_buf.spec();
         if sample_buf.is_none() {
-            let spec = *audio_buf.spec();
             let duration = audio_buf.capacity() as u64;
-            sample_buf = Some(SampleBuffer::<i16>::new(duration, spec));
+            sample_buf = Some(SampleBuffer::<i32>::new(duration, spec));
         }
 
         if let Some(buf) = &mut sample_buf {
             buf.copy_interleaved_ref(audio_buf);
-            analyzer.feed(buf.samples())?;
+            callback(
+                spec.rate,
+                spec.channels.count(),
+                bits_per_sample,
+                buf.samples(),
+            )?;
         }
     }
 
-    analyzer.finalize()
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::audio::Channels;
+
+    #[test]
+    fn test_compound_analyzer_keeps_feed_errors() {
+        let mut codec_params = CodecParameters::new();
+        let _ = codec_params
+            .with_sample_rate(8000)
+            .with_channels(Channels::FRONT_LEFT | Channels::FRONT_RIGHT);
+        let mut analyzer = CompoundAnalyzer::with_analyzer_types(
+            &Config::default(),
+            &codec_params,
+            &[AnalyzerType::TrackLength, AnalyzerType::EbuR128],
+        );
+
+        // An odd number of interleaved samples is not a whole number of stereo frames, so the
+        // EBU R 128 analyzer fails and is removed from the compound analyzer.
+        analyzer.feed(Samples::I16(&[0, 0, 0])).unwrap();
+        assert_eq!(analyzer.analyzers.len(), 1);
+
+        let result = analyzer.finalize().unwrap();
+        assert!(result.track_length.unwrap().is_ok());
+        assert!(result.ebur128.unwrap().is_err());
+    }
+
+    #[test]
+    fn test_analyze_surround() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/surround/track-5.1.wav"
+        );
+        let result = analyze(&Config::default(), path).unwrap();
+        assert_eq!(
+            result.track_length.unwrap().unwrap(),
+            chrono::TimeDelta::seconds(1)
+        );
+        assert!(result.chromaprint_fingerprint.unwrap().is_ok());
+        let ebur128 = result.ebur128.unwrap().unwrap();
+        assert!(ebur128.average_lufs.is_finite());
+        let technical_metadata = result.technical_metadata.unwrap();
+        assert_eq!(technical_metadata.sample_rate, Some(8000));
+        assert_eq!(technical_metadata.bit_depth, Some(16));
+        assert_eq!(technical_metadata.channels, Some(6));
+        assert!(technical_metadata.bitrate.is_some());
+    }
 }
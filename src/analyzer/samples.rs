@@ -0,0 +1,136 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Interleaved samples in different sample formats.
+
+use std::borrow::Cow;
+use symphonia::core::audio::{AudioBufferRef, SampleBuffer};
+use symphonia::core::conv::FromSample;
+
+/// Interleaved samples that are passed to the analyzers.
+///
+/// The samples keep the precision of the source, so that 24-bit and floating point sources are not
+/// reduced to 16 bits.
+#[derive(Debug, Clone, Copy)]
+pub enum Samples<'a> {
+    /// Signed 16-bit integer samples (used for sources with a bit depth of up to 16 bits).
+    I16(&'a [i16]),
+    /// Signed 32-bit integer samples (used for 24-bit and 32-bit integer sources).
+    I32(&'a [i32]),
+    /// 32-bit floating point samples (used for floating point sources).
+    F32(&'a [f32]),
+}
+
+impl<'a> Samples<'a> {
+    /// Number of (interleaved) samples.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::I16(samples) => samples.len(),
+            Self::I32(samples) => samples.len(),
+            Self::F32(samples) => samples.len(),
+        }
+    }
+
+    /// Returns `true` if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the first `len` samples (or all samples if there are less).
+    #[must_use]
+    pub fn truncate(self, len: usize) -> Self {
+        let len = len.min(self.len());
+        match self {
+            Self::I16(samples) => Self::I16(&samples[..len]),
+            Self::I32(samples) => Self::I32(&samples[..len]),
+            Self::F32(samples) => Self::F32(&samples[..len]),
+        }
+    }
+
+    /// Convert the samples to signed 16-bit integers.
+    pub fn to_i16(&self) -> Cow<'a, [i16]> {
+        match *self {
+            Self::I16(samples) => Cow::Borrowed(samples),
+            Self::I32(samples) => samples.iter().copied().map(i16::from_sample).collect(),
+            Self::F32(samples) => samples.iter().copied().map(i16::from_sample).collect(),
+        }
+    }
+}
+
+/// Buffer that converts decoded audio buffers into interleaved samples of a suitable format.
+#[allow(missing_debug_implementations)]
+pub enum InterleavedBuffer {
+    /// Buffer for sources with a bit depth of up to 16 bits.
+    I16(SampleBuffer<i16>),
+    /// Buffer for 24-bit and 32-bit integer sources.
+    I32(SampleBuffer<i32>),
+    /// Buffer for floating point sources.
+    F32(SampleBuffer<f32>),
+}
+
+impl InterleavedBuffer {
+    /// Create a buffer that matches the sample format and capacity of the given audio buffer.
+    pub fn new(audio_buf: &AudioBufferRef<'_>) -> Self {
+        let spec = *audio_buf.spec();
+        let duration = audio_buf.capacity() as u64;
+        match audio_buf {
+            AudioBufferRef::F32(_) | AudioBufferRef::F64(_) => {
+                Self::F32(SampleBuffer::new(duration, spec))
+            }
+            AudioBufferRef::U24(_)
+            | AudioBufferRef::S24(_)
+            | AudioBufferRef::U32(_)
+            | AudioBufferRef::S32(_) => Self::I32(SampleBuffer::new(duration, spec)),
+            AudioBufferRef::U8(_)
+            | AudioBufferRef::U16(_)
+            | AudioBufferRef::S8(_)
+            | AudioBufferRef::S16(_) => Self::I16(SampleBuffer::new(duration, spec)),
+        }
+    }
+
+    /// Copy the audio buffer into this buffer and return the interleaved samples.
+    pub fn copy_interleaved_ref(&mut self, audio_buf: AudioBufferRef<'_>) -> Samples<'_> {
+        match self {
+            Self::I16(buf) => {
+                buf.copy_interleaved_ref(audio_buf);
+                Samples::I16(buf.samples())
+            }
+            Self::I32(buf) => {
+                buf.copy_interleaved_ref(audio_buf);
+                Samples::I32(buf.samples())
+            }
+            Self::F32(buf) => {
+                buf.copy_interleaved_ref(audio_buf);
+                Samples::F32(buf.samples())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        let samples = Samples::F32(&[0.0, 0.5, 1.0]);
+        assert_eq!(samples.truncate(2).len(), 2);
+        assert_eq!(samples.truncate(5).len(), 3);
+        assert!(samples.truncate(0).is_empty());
+    }
+
+    #[test]
+    fn test_to_i16() {
+        assert_eq!(Samples::I16(&[1, -1]).to_i16().as_ref(), &[1, -1]);
+        assert_eq!(
+            Samples::I32(&[i32::MAX, 0, i32::MIN]).to_i16().as_ref(),
+            &[i16::MAX, 0, i16::MIN]
+        );
+        assert_eq!(Samples::F32(&[1.0, 0.0]).to_i16().as_ref(), &[i16::MAX, 0]);
+    }
+}
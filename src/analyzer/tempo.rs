@@ -0,0 +1,315 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Tempo (BPM) detection.
+//!
+//! The tempo is estimated from the autocorrelation of an onset envelope: the signal energy is
+//! measured in short blocks, the positive changes of the logarithmic energy are used as onset
+//! strength, and the beat period is the lag at which the onset envelope correlates best with
+//! itself.
+
+use super::{Analyzer, AnalyzerError, Samples};
+use crate::config::{BpmPrecision, Config};
+
+use symphonia::core::codecs::CodecParameters;
+use symphonia::core::conv::FromSample;
+
+/// Rate of the onset envelope (in Hz).
+const ENVELOPE_RATE: u32 = 200;
+
+/// Tempo Analyzer.
+#[derive(Debug)]
+pub struct TempoAnalyzer {
+    /// Rate of the onset envelope (in Hz), i.e. the sample rate divided by the block size.
+    envelope_rate: f64,
+    /// Number of interleaved samples per energy block.
+    block_size: usize,
+    /// Sum of the squared samples of the current block.
+    block_energy: f64,
+    /// Number of samples in the current block.
+    block_length: usize,
+    /// Energy of each completed block.
+    energies: Vec<f64>,
+    /// Number of interleaved samples that the analyzer still needs (if limited).
+    samples_needed: Option<usize>,
+    /// Lowest tempo that is detected (in BPM).
+    min_bpm: f64,
+    /// Highest tempo that is detected (in BPM).
+    max_bpm: f64,
+    /// Precision of the BPM value.
+    precision: BpmPrecision,
+}
+
+/// Tempo analysis result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TempoResult {
+    /// Detected tempo (in BPM).
+    pub bpm: f64,
+    /// Precision of the BPM value when it is written to the tags.
+    pub precision: BpmPrecision,
+}
+
+impl TempoResult {
+    /// Format the tempo for the `BPM` tag according to the configured precision.
+    pub fn bpm_string(&self) -> String {
+        match self.precision {
+            BpmPrecision::Integer => format!("{:.0}", self.bpm),
+            BpmPrecision::OneDecimal => format!("{:.1}", self.bpm),
+        }
+    }
+}
+
+impl TempoAnalyzer {
+    /// Add samples that have already been converted to floating point values.
+    fn feed_samples(&mut self, samples: impl Iterator<Item = f32>) {
+        for sample in samples {
+            self.block_energy += f64::from(sample).powi(2);
+            self.block_length += 1;
+            if self.block_length == self.block_size {
+                self.energies.push(self.block_energy);
+                self.block_energy = 0.0;
+                self.block_length = 0;
+            }
+        }
+    }
+
+    /// Calculate the onset envelope from the block energies.
+    ///
+    /// The mean is subtracted, so that the autocorrelation is not dominated by the overall onset
+    /// density.
+    fn onset_envelope(&self) -> Vec<f64> {
+        #[expect(clippy::cast_precision_loss)]
+        let block_size = self.block_size as f64;
+        let onsets = self
+            .energies
+            .windows(2)
+            .map(|pair| {
+                let previous = (pair[0] / block_size + 1e-10).ln();
+                let current = (pair[1] / block_size + 1e-10).ln();
+                (current - previous).max(0.0)
+            })
+            .collect::<Vec<f64>>();
+        #[expect(clippy::cast_precision_loss)]
+        let mean = onsets.iter().sum::<f64>() / onsets.len().max(1) as f64;
+        onsets.into_iter().map(|onset| onset - mean).collect()
+    }
+}
+
+/// Normalized autocorrelation of the envelope at the given lag.
+fn autocorrelation(envelope: &[f64], lag: usize) -> f64 {
+    let sum = envelope
+        .iter()
+        .zip(&envelope[lag..])
+        .map(|(a, b)| a * b)
+        .sum::<f64>();
+    #[expect(clippy::cast_precision_loss)]
+    let count = (envelope.len() - lag) as f64;
+    sum / count
+}
+
+impl Analyzer for TempoAnalyzer {
+    type Result = TempoResult;
+
+    fn initialize(config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
+        let sample_rate = codec_params
+            .sample_rate
+            .ok_or(AnalyzerError::MissingSampleRate)?;
+        let channel_count = codec_params
+            .channels
+            .ok_or(AnalyzerError::MissingAudioChannels)?
+            .count();
+        let tempo_config = &config.analyzers.tempo;
+        if tempo_config.min_bpm <= 0.0 || tempo_config.min_bpm >= tempo_config.max_bpm {
+            return Err(AnalyzerError::Custom("invalid BPM range"));
+        }
+
+        let block_frames = usize::try_from((sample_rate / ENVELOPE_RATE).max(1))
+            .map_err(|_| AnalyzerError::MissingSampleRate)?;
+        let samples_needed = (tempo_config.window > 0)
+            .then(|| usize::try_from(u64::from(tempo_config.window) * u64::from(sample_rate)))
+            .transpose()
+            .map_err(|_| AnalyzerError::MissingSampleRate)?
+            .map(|frames| frames * channel_count);
+        #[expect(clippy::cast_precision_loss)]
+        let envelope_rate = f64::from(sample_rate) / block_frames as f64;
+
+        Ok(Self {
+            envelope_rate,
+            block_size: block_frames * channel_count,
+            block_energy: 0.0,
+            block_length: 0,
+            energies: Vec::new(),
+            samples_needed,
+            min_bpm: tempo_config.min_bpm,
+            max_bpm: tempo_config.max_bpm,
+            precision: tempo_config.precision,
+        })
+    }
+
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError> {
+        let samples = match self.samples_needed {
+            Some(needed) => samples.truncate(needed),
+            None => samples,
+        };
+        if let Some(needed) = self.samples_needed.as_mut() {
+            *needed -= samples.len();
+        }
+        match samples {
+            Samples::I16(samples) => {
+                self.feed_samples(samples.iter().copied().map(f32::from_sample))
+            }
+            Samples::I32(samples) => {
+                self.feed_samples(samples.iter().copied().map(f32::from_sample))
+            }
+            Samples::F32(samples) => self.feed_samples(samples.iter().copied()),
+        }
+        Ok(())
+    }
+
+    fn is_complete(&self) -> bool {
+        self.samples_needed == Some(0)
+    }
+
+    fn max_samples_needed(&self) -> Option<usize> {
+        self.samples_needed
+    }
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn finalize(self) -> Result<Self::Result, AnalyzerError> {
+        let envelope = self.onset_envelope();
+        let min_lag = ((60.0 * self.envelope_rate / self.max_bpm).floor() as usize).max(1);
+        let max_lag = (60.0 * self.envelope_rate / self.min_bpm).ceil() as usize;
+        if envelope.len() < max_lag * 2 {
+            return Err(AnalyzerError::Custom("not enough audio for tempo analysis"));
+        }
+
+        // Energy envelopes correlate almost equally well at multiples of the beat period, so
+        // shorter lags are slightly preferred to avoid detecting half the tempo.
+        let weight =
+            |lag: usize| 1.0 - 0.1 * (lag - min_lag) as f64 / (max_lag - min_lag).max(1) as f64;
+        let (best_lag, best_value) = (min_lag..=max_lag)
+            .map(|lag| (lag, autocorrelation(&envelope, lag) * weight(lag)))
+            .fold((min_lag, f64::NEG_INFINITY), |best, current| {
+                if current.1 > best.1 {
+                    current
+                } else {
+                    best
+                }
+            });
+        if best_value <= 0.0 {
+            return Err(AnalyzerError::Custom("no periodic onsets found"));
+        }
+
+        // Refine the lag by fitting a parabola through the neighbouring values.
+        let offset = if best_lag > min_lag && best_lag < max_lag {
+            let previous = autocorrelation(&envelope, best_lag - 1);
+            let current = autocorrelation(&envelope, best_lag);
+            let next = autocorrelation(&envelope, best_lag + 1);
+            let denominator = previous - 2.0 * current + next;
+            if denominator.abs() > f64::EPSILON {
+                (0.5 * (previous - next) / denominator).clamp(-0.5, 0.5)
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+
+        let bpm = (60.0 * self.envelope_rate / (best_lag as f64 + offset))
+            .clamp(self.min_bpm, self.max_bpm);
+        Ok(TempoResult {
+            bpm,
+            precision: self.precision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::audio::Channels;
+
+    /// Generate a mono click track with the given tempo.
+    fn click_track(sample_rate: u32, bpm: f64, seconds: u32) -> Vec<f32> {
+        let length = usize::try_from(sample_rate * seconds).unwrap();
+        #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let period = (f64::from(sample_rate) * 60.0 / bpm).round() as usize;
+        let click_length = usize::try_from(sample_rate / 100).unwrap();
+        (0..length)
+            .map(|index| {
+                if index % period < click_length {
+                    if index % 2 == 0 {
+                        0.8
+                    } else {
+                        -0.8
+                    }
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    /// Run the analyzer on the given samples.
+    fn analyze(config: &Config, samples: &[f32]) -> Result<TempoResult, AnalyzerError> {
+        let mut codec_params = CodecParameters::new();
+        let _ = codec_params
+            .with_sample_rate(8000)
+            .with_channels(Channels::FRONT_LEFT);
+        let mut analyzer = TempoAnalyzer::initialize(config, &codec_params)?;
+        for chunk in samples.chunks(1024) {
+            if analyzer.is_complete() {
+                break;
+            }
+            analyzer.feed(Samples::F32(chunk))?;
+        }
+        analyzer.finalize()
+    }
+
+    #[test]
+    fn test_tempo_click_track() {
+        let config = Config::default();
+        let result = analyze(&config, &click_track(8000, 120.0, 20)).unwrap();
+        assert!((result.bpm - 120.0).abs() < 1.0, "{}", result.bpm);
+        assert_eq!(result.bpm_string(), "120");
+    }
+
+    #[test]
+    fn test_tempo_window_and_range() {
+        let mut config = Config::default();
+        config.analyzers.tempo.window = 1;
+        let result = analyze(&config, &click_track(8000, 120.0, 20));
+        assert!(matches!(result, Err(AnalyzerError::Custom(_))));
+
+        config.analyzers.tempo.min_bpm = 180.0;
+        config.analyzers.tempo.max_bpm = 60.0;
+        let result = analyze(&config, &click_track(8000, 120.0, 20));
+        assert!(matches!(
+            result,
+            Err(AnalyzerError::Custom("invalid BPM range"))
+        ));
+    }
+
+    #[test]
+    fn test_bpm_string() {
+        let result = TempoResult {
+            bpm: 127.46,
+            precision: BpmPrecision::OneDecimal,
+        };
+        assert_eq!(result.bpm_string(), "127.5");
+        let result = TempoResult {
+            bpm: 127.46,
+            precision: BpmPrecision::Integer,
+        };
+        assert_eq!(result.bpm_string(), "127");
+    }
+}
@@ -8,52 +8,100 @@
 
 //! Track Length analysis.
 
-use super::{Analyzer, AnalyzerError};
+use super::{Analyzer, AnalyzerError, Samples};
 use crate::config::Config;
 use chrono::TimeDelta;
 use float_eq::float_eq;
 
+use symphonia::core::audio::Channels;
 use symphonia::core::codecs::CodecParameters;
+use symphonia::core::units::TimeBase;
 
 /// Track Length Analyzer.
+///
+/// If the container or codec parameters contain the number of frames, the track length is
+/// determined during initialization and no samples need to be decoded. Otherwise, the decoded
+/// frames are counted.
 #[derive(Debug)]
 #[expect(missing_copy_implementations)]
 pub struct TrackLengthAnalyzer {
-    /// The track length (already determined during initialization).
-    track_length: TimeDelta,
+    /// The track length (if it was already determined during initialization).
+    track_length: Option<TimeDelta>,
+    /// Sample rate, used to calculate the track length from the number of decoded frames.
+    sample_rate: u32,
+    /// Number of channels, used to convert the number of interleaved samples to frames.
+    channel_count: usize,
+    /// Number of decoded frames.
+    frame_count: u64,
 }
 
 /// Number of nanoseconds per second.
 const NANOSECONDS_PER_SECOND: f64 = 1_000_000_000.0;
 
+/// Estimate the average bitrate (in kbit/s) from the file size and the track length.
+pub fn estimate_bitrate(file_size: u64, track_length: TimeDelta) -> Option<u32> {
+    let milliseconds = u64::try_from(track_length.num_milliseconds())
+        .ok()
+        .filter(|milliseconds| *milliseconds > 0)?;
+    // Bits per millisecond are equivalent to kilobits per second.
+    u32::try_from(file_size * 8 / milliseconds).ok()
+}
+
+/// Calculate the track length from the codec parameters without decoding the file.
+pub(super) fn track_length_from_codec_params(codec_params: &CodecParameters) -> Option<TimeDelta> {
+    let time_base = codec_params.time_base.or_else(|| {
+        codec_params
+            .sample_rate
+            .map(|sample_rate| TimeBase::new(1, sample_rate))
+    })?;
+    let time = time_base.calc_time(codec_params.n_frames?);
+    let secs = i64::try_from(time.seconds).ok()?;
+    let nanos = f64_to_u32((time.frac * NANOSECONDS_PER_SECOND).trunc())?;
+    TimeDelta::new(secs, nanos)
+}
+
 impl Analyzer for TrackLengthAnalyzer {
     type Result = TimeDelta;
 
     fn initialize(_config: &Config, codec_params: &CodecParameters) -> Result<Self, AnalyzerError> {
-        let track_length = codec_params
-            .time_base
-            .zip(codec_params.n_frames)
-            .map(|(time_base, n_frames)| time_base.calc_time(n_frames))
-            .and_then(|time| {
-                i64::try_from(time.seconds)
-                    .ok()
-                    .zip(f64_to_u32((time.frac * NANOSECONDS_PER_SECOND).trunc()))
-            })
-            .and_then(|(secs, nanos)| TimeDelta::new(secs, nanos))
-            .ok_or(AnalyzerError::Custom("Failed to calculate track length"))?;
-        Ok(Self { track_length })
+        let track_length = track_length_from_codec_params(codec_params);
+        let sample_rate = codec_params.sample_rate.unwrap_or_default();
+        let channel_count = codec_params.channels.map_or(0, Channels::count);
+        if track_length.is_none() && (sample_rate == 0 || channel_count == 0) {
+            return Err(AnalyzerError::Custom("Failed to calculate track length"));
+        }
+
+        Ok(Self {
+            track_length,
+            sample_rate,
+            channel_count,
+            frame_count: 0,
+        })
     }
 
-    fn feed(&mut self, _samples: &[i16]) -> Result<(), AnalyzerError> {
+    fn feed(&mut self, samples: Samples<'_>) -> Result<(), AnalyzerError> {
+        if self.track_length.is_none() {
+            self.frame_count += (samples.len() / self.channel_count) as u64;
+        }
         Ok(())
     }
 
     fn is_complete(&self) -> bool {
-        true
+        self.track_length.is_some()
     }
 
     fn finalize(self) -> Result<TimeDelta, AnalyzerError> {
-        Ok(self.track_length)
+        if let Some(track_length) = self.track_length {
+            return Ok(track_length);
+        }
+
+        let sample_rate = u64::from(self.sample_rate);
+        let secs = i64::try_from(self.frame_count / sample_rate).ok();
+        let nanos =
+            u32::try_from((self.frame_count % sample_rate) * 1_000_000_000 / sample_rate).ok();
+        secs.zip(nanos)
+            .and_then(|(secs, nanos)| TimeDelta::new(secs, nanos))
+            .ok_or(AnalyzerError::Custom("Failed to calculate track length"))
     }
 }
 
@@ -68,3 +116,43 @@ fn f64_to_u32(value: f64) -> Option<u32> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_bitrate() {
+        assert_eq!(
+            estimate_bitrate(4_000_000, TimeDelta::seconds(100)),
+            Some(320)
+        );
+        assert_eq!(estimate_bitrate(4_000_000, TimeDelta::zero()), None);
+    }
+
+    #[test]
+    fn test_track_length_from_codec_params() {
+        let mut codec_params = CodecParameters::new();
+        let _ = codec_params
+            .with_sample_rate(44100)
+            .with_channels(Channels::FRONT_LEFT | Channels::FRONT_RIGHT)
+            .with_n_frames(44100 * 3 + 22050);
+        let analyzer = TrackLengthAnalyzer::initialize(&Config::default(), &codec_params).unwrap();
+        assert!(analyzer.is_complete());
+        assert_eq!(analyzer.finalize().unwrap(), TimeDelta::milliseconds(3500));
+    }
+
+    #[test]
+    fn test_track_length_from_decoded_frames() {
+        let mut codec_params = CodecParameters::new();
+        let _ = codec_params
+            .with_sample_rate(4)
+            .with_channels(Channels::FRONT_LEFT | Channels::FRONT_RIGHT);
+        let mut analyzer =
+            TrackLengthAnalyzer::initialize(&Config::default(), &codec_params).unwrap();
+        assert!(!analyzer.is_complete());
+        analyzer.feed(Samples::I16(&[0; 8])).unwrap();
+        analyzer.feed(Samples::F32(&[0.0; 4])).unwrap();
+        assert_eq!(analyzer.finalize().unwrap(), TimeDelta::milliseconds(1500));
+    }
+}
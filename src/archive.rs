@@ -0,0 +1,113 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Extraction of album archives (e.g., ZIP files of Bandcamp purchases).
+//!
+//! Archives are extracted into a temporary directory, which is then scanned like any other album
+//! directory.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use thiserror::Error;
+
+/// Encountered when an archive cannot be extracted.
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    /// The archive or the temporary directory could not be accessed.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// The archive is invalid.
+    #[error("Invalid ZIP archive: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+/// Returns `true` if the file is an archive that can be extracted.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// An archive that was extracted into a temporary directory.
+///
+/// The directory is removed when this is dropped.
+#[derive(Debug)]
+pub struct ExtractedArchive {
+    /// Path of the archive.
+    path: PathBuf,
+    /// Temporary directory that the archive was extracted to.
+    output_dir: TempDir,
+}
+
+impl ExtractedArchive {
+    /// Path of the archive.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Directory that the archive was extracted to.
+    pub fn output_dir(&self) -> &Path {
+        self.output_dir.path()
+    }
+}
+
+/// Extract the archive into a new temporary directory.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or extracted.
+pub fn extract_to_temp_dir(archive_path: &Path) -> Result<ExtractedArchive, ArchiveError> {
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+    let output_dir = tempfile::Builder::new()
+        .prefix("helicon-archive-")
+        .tempdir()?;
+    archive.extract(output_dir.path())?;
+    log::info!(
+        "Extracted {} files from {} to {}",
+        archive.len(),
+        archive_path.display(),
+        output_dir.path().display()
+    );
+    Ok(ExtractedArchive {
+        path: archive_path.to_path_buf(),
+        output_dir,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn test_extract_to_temp_dir() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let archive_path = tmp_dir.path().join("Album.ZIP");
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        for name in ["01 - Track.flac", "cover.jpg"] {
+            writer
+                .start_file(name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(b"data").unwrap();
+        }
+        let _ = writer.finish().unwrap();
+
+        assert!(is_archive(&archive_path));
+        assert!(!is_archive(&tmp_dir.path().join("01 - Track.flac")));
+
+        let extracted_archive = extract_to_temp_dir(&archive_path).unwrap();
+        let output_dir = extracted_archive.output_dir().to_path_buf();
+        assert_eq!(extracted_archive.path(), archive_path);
+        assert!(output_dir.join("01 - Track.flac").is_file());
+        assert!(output_dir.join("cover.jpg").is_file());
+        drop(extracted_archive);
+        assert!(!output_dir.exists());
+    }
+}
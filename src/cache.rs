@@ -63,69 +63,140 @@ impl<T> CacheEnvelope<T> {
     }
 }
 
-/// Maximum age of a a cache entry after which it expires.
-const MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// Default maximum age of a cache entry after which it expires.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+/// Header of a cache envelope, used to read the creation time without the cached item.
+#[derive(Deserialize)]
+struct CacheEnvelopeHeader {
+    /// The date and time when the item was added to the cache.
+    created: DateTime<Utc>,
+}
+
+/// An item read from the cache, which may be expired.
+#[derive(Debug)]
+pub struct CacheEntry<T> {
+    /// The date and time when the item was added to the cache.
+    pub created: DateTime<Utc>,
+    /// The cached item.
+    pub item: T,
+}
+
+/// Information about an item in the cache.
+#[derive(Debug, Clone)]
+pub struct CacheEntryInfo {
+    /// Path of the cache file.
+    pub path: PathBuf,
+    /// The cache key (e.g., the MusicBrainz ID), derived from the file name.
+    pub key: String,
+    /// The date and time when the item was added to the cache (if the file is readable).
+    pub created: Option<DateTime<Utc>>,
+    /// Size of the cache file in bytes.
+    pub size: u64,
+}
+
+impl CacheEntryInfo {
+    /// Returns `true` if the entry is older than the given age (or its age is unknown).
+    pub fn is_older_than(&self, age: Duration) -> bool {
+        self.created
+            .is_none_or(|created| is_older_than(created, age))
+    }
+}
+
+/// Returns `true` if the given time is more than `age` ago.
+fn is_older_than(created: DateTime<Utc>, age: Duration) -> bool {
+    Utc::now()
+        .signed_duration_since(created)
+        .to_std()
+        .is_ok_and(|elapsed| elapsed > age)
+}
 
 /// Cache for MusicBrainz queries (to not use their API too much unnecessarily).
 #[derive(Debug, Clone)]
-pub struct Cache(BaseDirectories);
+pub struct Cache {
+    /// Base directories used to place the cache files.
+    base_dirs: BaseDirectories,
+    /// Maximum age of a cache entry after which it expires.
+    max_age: Duration,
+}
 
 impl Cache {
     /// Create a new cache struct.
     #[must_use]
     pub fn new(base_dirs: BaseDirectories) -> Self {
-        Self(base_dirs)
+        Self {
+            base_dirs,
+            max_age: DEFAULT_MAX_AGE,
+        }
     }
 
-    /// Get a JSON-deserializable item with the given path from the cache.
+    /// Set the maximum age of cache entries after which they expire.
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Returns `true` if the entry is older than the maximum age and needs to be revalidated.
+    pub fn is_expired<T>(&self, entry: &CacheEntry<T>) -> bool {
+        is_older_than(entry.created, self.max_age)
+    }
+
+    /// Get a JSON-deserializable item with the given path from the cache, regardless of its age.
     ///
     /// # Errors
     ///
     /// Returns an error if a cache miss occurred or the cache file could not be read or the
     /// deserialization failed.
-    pub fn get_item<'a, T: Cacheable<'a> + DeserializeOwned>(
+    pub fn get_entry<'a, T: Cacheable<'a> + DeserializeOwned>(
         &self,
         key: T::Key,
-    ) -> Result<T, CacheError> {
+    ) -> Result<CacheEntry<T>, CacheError> {
         let item_path = T::cache_path(key);
         let path = self
-            .0
+            .base_dirs
             .find_cache_file(&item_path)
             .ok_or(CacheError::CacheMiss)?;
-        let cache_age = path
-            .metadata()?
-            .modified()
-            .ok()
-            .and_then(|time| time.elapsed().ok())
-            .unwrap_or(Duration::MAX);
-        // TODO: Make this configurable.
-        if cache_age > MAX_AGE {
-            std::fs::remove_file(path)?;
-            return Err(CacheError::CacheMiss);
-        }
 
         let f = File::open(&path)?;
         let reader = BufReader::new(f);
         let envelope: CacheEnvelope<T> = serde_json::from_reader(reader)?;
+        Ok(CacheEntry {
+            created: envelope.created,
+            item: envelope.into_inner(),
+        })
+    }
 
-        let age = Utc::now().signed_duration_since(envelope.created);
-        // TODO: Make this configurable
-        if age.num_weeks() >= 2 {
+    /// Get a JSON-deserializable item with the given path from the cache.
+    ///
+    /// Expired items are removed from the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cache miss occurred or the cache file could not be read or the
+    /// deserialization failed.
+    pub fn get_item<'a, T: Cacheable<'a> + DeserializeOwned>(
+        &self,
+        key: T::Key,
+    ) -> Result<T, CacheError> {
+        let entry = self.get_entry::<T>(key)?;
+        let item_path = T::cache_path(key);
+        if self.is_expired(&entry) {
             log::debug!(
                 "Cache item {item_path} (created {created}) expired, removing it",
                 item_path = item_path.display(),
-                created = envelope.created
+                created = entry.created
             );
-            std::fs::remove_file(path)?;
+            let _ = self.remove_item::<T>(key)?;
             return Err(CacheError::CacheMiss);
         }
 
         log::debug!(
             "Cache item {item_path} (created {created}) is still valid",
             item_path = item_path.display(),
-            created = envelope.created
+            created = entry.created
         );
-        Ok(envelope.into_inner())
+        Ok(entry.item)
     }
 
     /// Insert a JSON-deserializable item with the given path into cache.
@@ -139,20 +210,89 @@ impl Cache {
         item: &T,
     ) -> Result<(), CacheError> {
         let item_path = T::cache_path(key);
-        let path = self.0.place_cache_file(item_path)?;
+        let path = self.base_dirs.place_cache_file(item_path)?;
         let f = File::create(path)?;
         let writer = BufWriter::new(f);
         let envelope = CacheEnvelope::new(item);
         Ok(serde_json::to_writer(writer, &envelope)?)
     }
 
+    /// Remove the item with the given key from the cache. Returns `false` if the item was not
+    /// cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file could not be removed.
+    pub fn remove_item<'a, T: Cacheable<'a>>(&self, key: T::Key) -> Result<bool, CacheError> {
+        let Some(path) = self.base_dirs.find_cache_file(T::cache_path(key)) else {
+            return Ok(false);
+        };
+        std::fs::remove_file(path)?;
+        Ok(true)
+    }
+
+    /// List all items of the given type in the cache, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache file metadata could not be read.
+    pub fn list_entries<'a, T: Cacheable<'a>>(&self) -> Result<Vec<CacheEntryInfo>, CacheError> {
+        let mut entries = self
+            .base_dirs
+            .list_cache_files(T::CACHE_DIRECTORY)
+            .into_iter()
+            .map(|path| {
+                let size = path.metadata()?.len();
+                let created = File::open(&path)
+                    .ok()
+                    .and_then(|f| {
+                        serde_json::from_reader::<_, CacheEnvelopeHeader>(BufReader::new(f)).ok()
+                    })
+                    .map(|header| header.created);
+                let key = path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned();
+                Ok(CacheEntryInfo {
+                    path,
+                    key,
+                    created,
+                    size,
+                })
+            })
+            .collect::<Result<Vec<_>, CacheError>>()?;
+        entries.sort_by_key(|entry| entry.created);
+        Ok(entries)
+    }
+
+    /// Remove all items of the given type that are older than `older_than` (or all items if it
+    /// is `None`). Returns the number of removed items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a cache file could not be removed.
+    pub fn purge<'a, T: Cacheable<'a>>(
+        &self,
+        older_than: Option<Duration>,
+    ) -> Result<usize, CacheError> {
+        let mut count = 0;
+        for entry in self.list_entries::<T>()? {
+            if older_than.is_none_or(|age| entry.is_older_than(age)) {
+                std::fs::remove_file(&entry.path)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
     /// Get a tuple `(item_count, total_size_in_bytes)` for items at given cache path.
     ///
     /// # Errors
     ///
     /// Returns an error if the cache file metadata could not be read.
     pub fn get_stats<'a, T: Cacheable<'a>>(&self) -> Result<(usize, u64), CacheError> {
-        let items = self.0.list_cache_files(T::CACHE_DIRECTORY);
+        let items = self.base_dirs.list_cache_files(T::CACHE_DIRECTORY);
         let item_count = items.len();
         let item_size = items
             .iter()
@@ -165,7 +305,7 @@ impl Cache {
 /// Marks an item as cacheable.
 pub trait Cacheable<'a> {
     /// Type of the cache key.
-    type Key;
+    type Key: Copy;
 
     /// Directory inside the cache where items of this type are stored.
     const CACHE_DIRECTORY: &'static str;
@@ -8,22 +8,176 @@
 
 //! Functions related to importing files.
 
-use crate::analyzer;
+use crate::analyzer::{self, EbuR128AlbumResult};
 use crate::config::AnalyzerType;
+use crate::scanner::is_supported_audio_file;
 use crate::util::FormattedDuration;
 use crate::Cache;
-use crate::Config;
+use crate::{Config, TaggedFile, TaggedFileCollection};
 use clap::Parser;
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
 
 /// Command line arguments for the `import` CLI command.
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Path of audio file to analyze.
+    /// Path of audio file to analyze (or of the album directory if `--album` is used).
     path: PathBuf,
     /// Use all analyzers (regardless of configuration).
     #[arg(short, long)]
     all: bool,
+    /// Analyze all audio files in the directory as an album and print the track and album
+    /// loudness.
+    #[arg(long)]
+    album: bool,
+    /// Write the analysis results (i.e., the AcoustID fingerprint, the ReplayGain values and the
+    /// BPM) into the tags of the files. No other tags are changed.
+    #[arg(long)]
+    write: bool,
+}
+
+/// Header of the loudness table in album mode.
+const ALBUM_TABLE_HEADER: [&str; 5] = ["File", "Length", "Loudness", "Gain", "Peak"];
+
+/// Format the rows as a table with left-aligned columns that are separated by two spaces.
+fn format_table(rows: &[[String; 5]]) -> Vec<String> {
+    let mut widths = ALBUM_TABLE_HEADER.map(UnicodeWidthStr::width);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
+
+    std::iter::once(ALBUM_TABLE_HEADER.map(ToString::to_string))
+        .chain(rows.iter().cloned())
+        .map(|row| {
+            row.iter()
+                .zip(widths)
+                .map(|(cell, width)| {
+                    format!("{cell}{}", " ".repeat(width.saturating_sub(cell.width())))
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Analyze all audio files in the directory and print the loudness of each track and of the whole
+/// album.
+fn run_album(config: &Config, path: &Path, write: bool) -> crate::Result<()> {
+    let mut config = config.clone();
+    for analyzer in [AnalyzerType::TrackLength, AnalyzerType::EbuR128] {
+        if !config.analyzers.enabled.contains(&analyzer) {
+            config.analyzers.enabled.push(analyzer);
+        }
+    }
+
+    let mut file_paths = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    file_paths.retain(|path| is_supported_audio_file(path));
+    file_paths.sort();
+
+    let mut rows = vec![];
+    let mut results = vec![];
+    let mut tracks = vec![];
+    for file_path in &file_paths {
+        let name = file_path
+            .file_name()
+            .unwrap_or(file_path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        let result = match analyzer::analyze(&config, file_path) {
+            Ok(result) => result,
+            Err(err) => {
+                eprintln!("Analysis of {} failed: {err}", file_path.display());
+                rows.push([name, "?:??".to_string(), "-".into(), "-".into(), "-".into()]);
+                continue;
+            }
+        };
+        let length = match result.track_length {
+            Some(Ok(track_length)) => track_length.formatted_duration(),
+            _ => "?:??".to_string(),
+        };
+        match &result.ebur128 {
+            Some(Ok(ebur128)) => {
+                rows.push([
+                    name,
+                    length,
+                    format!("{:.2} LUFS", ebur128.average_lufs),
+                    ebur128.replaygain_track_gain_string(),
+                    ebur128.replaygain_track_peak_string(),
+                ]);
+                results.push(ebur128.clone());
+            }
+            Some(Err(err)) => {
+                eprintln!(
+                    "EBU R 128 analysis of {} failed: {err}",
+                    file_path.display()
+                );
+                rows.push([name, length, "-".into(), "-".into(), "-".into()]);
+            }
+            None => rows.push([name, length, "-".into(), "-".into(), "-".into()]),
+        }
+        if write {
+            match TaggedFile::read_from_path(file_path, &config.tags) {
+                Ok(tagged_file) => tracks.push(tagged_file.with_analysis_results(Some(result))),
+                Err(err) => eprintln!("Failed to read {}: {err}", file_path.display()),
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        return Err(crate::Error::NoAlbumFound);
+    }
+
+    let album_result = (results.len() == rows.len())
+        .then(|| EbuR128AlbumResult::from_iter(results.iter()))
+        .flatten();
+    if album_result.is_none() {
+        eprintln!(
+            "Album loudness is not available, because {} of {} tracks could not be analyzed",
+            rows.len() - results.len(),
+            rows.len()
+        );
+    }
+    let album_row = album_result.map_or_else(
+        || {
+            [
+                "Album".to_string(),
+                String::new(),
+                "-".into(),
+                "-".into(),
+                "-".into(),
+            ]
+        },
+        |album| {
+            [
+                "Album".to_string(),
+                String::new(),
+                format!("{:.2} LUFS", album.average_lufs),
+                album.replaygain_album_gain_string(),
+                album.replaygain_album_peak_string(),
+            ]
+        },
+    );
+    rows.push(album_row);
+
+    for line in format_table(&rows) {
+        println!("{line}");
+    }
+
+    if write {
+        let mut track_collection = TaggedFileCollection::new(tracks)
+            .with_partial_album_gain(config.analyzers.partial_album_gain);
+        track_collection.assign_analyzed_tags(&config.tags.replay_gain);
+        track_collection.write_tags(&config)?;
+    }
+    Ok(())
 }
 
 /// Analyze a file.
@@ -34,19 +188,25 @@ pub struct Args {
 /// variant will be returned.
 pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result<()> {
     let path = args.path;
-    let result = if args.all {
+    let config = if args.all {
         let mut config = config.clone();
         config.analyzers.enabled = vec![
             AnalyzerType::TrackLength,
             AnalyzerType::ChromaprintFingerprint,
             AnalyzerType::EbuR128,
+            AnalyzerType::Tempo,
         ];
-        analyzer::analyze(&config, &path)?
+        Cow::Owned(config)
     } else {
-        analyzer::analyze(config, &path)?
+        Cow::Borrowed(config)
     };
+    if args.album {
+        return run_album(&config, &path, args.write);
+    }
 
-    if let Some(result) = result.track_length {
+    let result = analyzer::analyze(&config, &path)?;
+
+    if let Some(result) = &result.track_length {
         match result {
             Ok(track_length) => {
                 println!("Track Length: {}", track_length.formatted_duration());
@@ -55,7 +215,7 @@ pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result
         }
     }
 
-    if let Some(result) = result.chromaprint_fingerprint {
+    if let Some(result) = &result.chromaprint_fingerprint {
         match result {
             Ok(result) => {
                 println!("Duration: {}", result.duration);
@@ -65,15 +225,66 @@ pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result
         }
     }
 
-    if let Some(result) = result.ebur128 {
+    if let Some(result) = &result.ebur128 {
         match result {
             Ok(ebur128) => {
                 println!("Track Gain: {}", ebur128.replaygain_track_gain());
                 println!("Track Peak: {}", ebur128.peak);
+                println!(
+                    "Reference Loudness: {}",
+                    ebur128.replaygain_reference_loudness_string()
+                );
             }
             Err(err) => eprintln!("EBU R 128 analysis failed: {err}"),
         }
     }
 
+    if let Some(result) = &result.tempo {
+        match result {
+            Ok(tempo) => println!("BPM: {}", tempo.bpm_string()),
+            Err(err) => eprintln!("Tempo analysis failed: {err}"),
+        }
+    }
+
+    if args.write {
+        let mut tagged_file =
+            TaggedFile::read_from_path(&path, &config.tags)?.with_analysis_results(Some(result));
+        tagged_file.assign_analyzed_tags(&config.tags.replay_gain);
+        tagged_file.write_tags_preserving_mtime(config.paths.preserve_mtime)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let rows = [
+            [
+                "01 - Intro.flac".to_string(),
+                "1:05".to_string(),
+                "-14.20 LUFS".to_string(),
+                "-3.80 dB".to_string(),
+                "0.988525".to_string(),
+            ],
+            [
+                "Album".to_string(),
+                String::new(),
+                "-9.00 LUFS".to_string(),
+                "-9.00 dB".to_string(),
+                "1.000000".to_string(),
+            ],
+        ];
+        assert_eq!(
+            format_table(&rows),
+            vec![
+                "File             Length  Loudness     Gain      Peak",
+                "01 - Intro.flac  1:05    -14.20 LUFS  -3.80 dB  0.988525",
+                "Album                    -9.00 LUFS   -9.00 dB  1.000000",
+            ]
+        );
+    }
+}
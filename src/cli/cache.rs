@@ -6,26 +6,70 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-//! Module for the `config` CLI subcommand.
+//! Module for the `cache` CLI subcommand.
 
-use crate::cache::Cache;
+use crate::cache::{Cache, Cacheable};
 use crate::Config;
-use clap::Parser;
+use chrono::Utc;
+use clap::{Parser, Subcommand, ValueEnum};
 use musicbrainz_rs_nova::entity::{
     release::Release as MusicBrainzRelease, release_group::ReleaseGroup as MusicBrainzReleaseGroup,
     search::SearchResult as MusicBrainzSearchResult,
 };
+use std::time::Duration;
 
-/// Command line arguments for the `config` CLI command.
+/// Number of seconds per day.
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Command line arguments for the `cache` CLI command.
 #[derive(Parser, Debug)]
-pub struct Args;
+pub struct Args {
+    /// Action to perform (shows the cache usage by default).
+    #[command(subcommand)]
+    action: Option<Action>,
+}
 
-/// Run the `cache` command.
-pub fn run(_config: &Config, cache: Option<&Cache>, _args: Args) -> crate::Result<()> {
-    let Some(cache) = cache else {
-        return Err(crate::Error::CacheNotAvailable);
-    };
+/// Kind of cached items.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    /// MusicBrainz releases.
+    Releases,
+    /// MusicBrainz release groups.
+    ReleaseGroups,
+    /// MusicBrainz release search results.
+    Searches,
+}
+
+/// Supported cache actions.
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Show the current cache usage.
+    Stats,
+    /// List cached items, oldest first.
+    List {
+        /// Kind of items to list.
+        #[arg(value_enum, default_value_t = ItemKind::Releases)]
+        kind: ItemKind,
+        /// Only list items that are older than this number of days.
+        #[arg(long)]
+        older_than: Option<u64>,
+    },
+    /// Remove items from the cache.
+    Purge {
+        /// Kind of items to remove (all kinds if not specified).
+        #[arg(value_enum)]
+        kind: Option<ItemKind>,
+        /// Only remove items that are older than this number of days.
+        #[arg(long, conflicts_with = "release")]
+        older_than: Option<u64>,
+        /// Only remove the releases with these MusicBrainz IDs.
+        #[arg(long)]
+        release: Vec<String>,
+    },
+}
 
+/// Print the number and size of cached items.
+fn print_stats(cache: &Cache) -> crate::Result<()> {
     let (count, size) = cache.get_stats::<MusicBrainzRelease>()?;
     println!("Releases: {count} ({size:?} bytes)");
 
@@ -37,3 +81,90 @@ pub fn run(_config: &Config, cache: Option<&Cache>, _args: Args) -> crate::Resul
 
     Ok(())
 }
+
+/// Print the cached items of the given type, oldest first.
+fn list_entries<'a, T: Cacheable<'a>>(
+    cache: &Cache,
+    older_than: Option<Duration>,
+) -> crate::Result<()> {
+    let now = Utc::now();
+    for entry in cache
+        .list_entries::<T>()?
+        .into_iter()
+        .filter(|entry| older_than.is_none_or(|age| entry.is_older_than(age)))
+    {
+        let age = entry.created.map_or_else(
+            || "unknown age".to_string(),
+            |created| format!("{} days old", now.signed_duration_since(created).num_days()),
+        );
+        println!(
+            "{key} ({age}, {size} bytes)",
+            key = entry.key,
+            size = entry.size
+        );
+    }
+    Ok(())
+}
+
+/// Remove cached items of the given type and print the number of removed items.
+fn purge_entries<'a, T: Cacheable<'a>>(
+    cache: &Cache,
+    name: &str,
+    older_than: Option<Duration>,
+) -> crate::Result<()> {
+    let count = cache.purge::<T>(older_than)?;
+    println!("Removed {count} {name}");
+    Ok(())
+}
+
+/// Run the `cache` command.
+pub fn run(_config: &Config, cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let Some(cache) = cache else {
+        return Err(crate::Error::CacheNotAvailable);
+    };
+
+    match args.action.unwrap_or(Action::Stats) {
+        Action::Stats => print_stats(cache)?,
+        Action::List { kind, older_than } => {
+            let older_than = older_than.map(|days| Duration::from_secs(days * SECONDS_PER_DAY));
+            match kind {
+                ItemKind::Releases => list_entries::<MusicBrainzRelease>(cache, older_than)?,
+                ItemKind::ReleaseGroups => {
+                    list_entries::<MusicBrainzReleaseGroup>(cache, older_than)?;
+                }
+                ItemKind::Searches => {
+                    list_entries::<MusicBrainzSearchResult<MusicBrainzRelease>>(cache, older_than)?;
+                }
+            }
+        }
+        Action::Purge { release, .. } if !release.is_empty() => {
+            for release_id in &release {
+                if cache.remove_item::<MusicBrainzRelease>(release_id.as_str())? {
+                    println!("Removed release {release_id}");
+                } else {
+                    println!("Release {release_id} is not cached");
+                }
+            }
+        }
+        Action::Purge {
+            kind, older_than, ..
+        } => {
+            let older_than = older_than.map(|days| Duration::from_secs(days * SECONDS_PER_DAY));
+            if kind.is_none_or(|kind| kind == ItemKind::Releases) {
+                purge_entries::<MusicBrainzRelease>(cache, "releases", older_than)?;
+            }
+            if kind.is_none_or(|kind| kind == ItemKind::ReleaseGroups) {
+                purge_entries::<MusicBrainzReleaseGroup>(cache, "release groups", older_than)?;
+            }
+            if kind.is_none_or(|kind| kind == ItemKind::Searches) {
+                purge_entries::<MusicBrainzSearchResult<MusicBrainzRelease>>(
+                    cache,
+                    "release search results",
+                    older_than,
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
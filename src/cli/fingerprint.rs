@@ -0,0 +1,139 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Module for the `fingerprint` CLI subcommand.
+//!
+//! The output format is compatible with `fpcalc` from the Chromaprint project, so that helicon can
+//! be used as a drop-in replacement in existing scripts.
+
+use crate::analyzer;
+use crate::config::AnalyzerType;
+use crate::Cache;
+use crate::Config;
+use chrono::TimeDelta;
+use clap::Parser;
+use std::path::{Path, PathBuf};
+
+/// Command line arguments for the `fingerprint` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Paths of the audio files to fingerprint.
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// Print the result as JSON (one object per file).
+    #[arg(long)]
+    json: bool,
+}
+
+/// Fingerprint of an audio file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    /// Duration of the file.
+    duration: TimeDelta,
+    /// The base64-encoded, compressed fingerprint.
+    fingerprint: String,
+}
+
+impl Fingerprint {
+    /// Duration in seconds.
+    #[expect(clippy::cast_precision_loss)]
+    fn duration_secs(&self) -> f64 {
+        self.duration.num_milliseconds() as f64 / 1000.0
+    }
+
+    /// Format the fingerprint like `fpcalc` does by default.
+    fn to_plain(&self, path: &Path) -> String {
+        format!(
+            "FILE={path}\nDURATION={duration}\nFINGERPRINT={fingerprint}",
+            path = path.display(),
+            duration = self.duration.num_seconds(),
+            fingerprint = self.fingerprint,
+        )
+    }
+
+    /// Format the fingerprint like `fpcalc -json` does.
+    fn to_json(&self) -> String {
+        serde_json::json!({
+            "duration": (self.duration_secs() * 100.0).round() / 100.0,
+            "fingerprint": self.fingerprint,
+        })
+        .to_string()
+    }
+}
+
+/// Calculate the fingerprint of the file.
+fn fingerprint(config: &Config, path: &Path) -> crate::Result<Fingerprint> {
+    let result = analyzer::analyze(config, path)?;
+    let duration = result
+        .track_length
+        .transpose()?
+        .unwrap_or_else(TimeDelta::zero);
+    let fingerprint = result
+        .chromaprint_fingerprint
+        .transpose()?
+        .map(|result| result.fingerprint_string())
+        .unwrap_or_default();
+    Ok(Fingerprint {
+        duration,
+        fingerprint,
+    })
+}
+
+/// Print the Chromaprint fingerprints of the files in the same format as `fpcalc`.
+///
+/// # Errors
+///
+/// Returns the last error if any of the files could not be fingerprinted. Files that come after a
+/// failing file are still processed.
+pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let mut config = config.clone();
+    config.analyzers.enabled = vec![
+        AnalyzerType::TrackLength,
+        AnalyzerType::ChromaprintFingerprint,
+    ];
+
+    let mut last_error = None;
+    for (index, path) in args.paths.iter().enumerate() {
+        match fingerprint(&config, path) {
+            Ok(fingerprint) if args.json => println!("{}", fingerprint.to_json()),
+            Ok(fingerprint) => {
+                if index > 0 {
+                    println!();
+                }
+                println!("{}", fingerprint.to_plain(path));
+            }
+            Err(err) => {
+                eprintln!("ERROR: Could not fingerprint {}: {err}", path.display());
+                last_error = Some(err);
+            }
+        }
+    }
+
+    last_error.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fpcalc_format() {
+        let fingerprint = Fingerprint {
+            duration: TimeDelta::milliseconds(249_068),
+            fingerprint: "AQAAT0mUaEkSRZEG".to_string(),
+        };
+        assert_eq!(
+            fingerprint.to_plain(Path::new("/music/track.flac")),
+            "FILE=/music/track.flac\nDURATION=249\nFINGERPRINT=AQAAT0mUaEkSRZEG"
+        );
+        assert_eq!(
+            fingerprint.to_json(),
+            r#"{"duration":249.07,"fingerprint":"AQAAT0mUaEkSRZEG"}"#
+        );
+    }
+}
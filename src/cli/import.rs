@@ -9,45 +9,192 @@
 //! Functions related to importing files.
 
 use super::ui;
-use crate::media::MediaLike;
-use crate::musicbrainz::{MusicBrainzClient, MusicBrainzRelease};
+use crate::config::AnalyzerType;
+use crate::decisions::{Decision, DecisionKind, DecisionStore};
+use crate::distance::{Distance, ReleaseDistanceExplanation};
+use crate::import_session::{
+    CollisionRequest, CollisionResolution, ImportDecision, ImportEvent, ImportSession,
+    SelectionMode,
+};
+use crate::musicbrainz::{MusicBrainzClient, MusicBrainzId, MusicBrainzRelease};
+use crate::progress::ImportProgress;
+use crate::quarantine::{self, SkipReason};
 use crate::release::ReleaseLike;
 use crate::release_candidate::{ReleaseCandidate, ReleaseCandidateCollection};
-use crate::scanner::Scanner;
+use crate::scanner;
+use crate::stats::ImportStats;
+use crate::tag::TagKey;
 use crate::track::TrackLike;
 use crate::util::FormattedDuration;
 use crate::Cache;
 use crate::{Config, TaggedFileCollection};
 use clap::Parser;
 use futures::StreamExt;
+use inquire::{InquireError, Select, Text};
+use serde::Serialize;
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 /// Command line arguments for the `import` CLI command.
 #[derive(Parser, Debug)]
 pub struct Args {
     /// Path to import.
-    path: PathBuf,
+    #[arg(required_unless_present = "retry_quarantine")]
+    path: Option<PathBuf>,
+    /// Retry importing the albums in the quarantine directory.
+    #[arg(long, conflicts_with = "path")]
+    retry_quarantine: bool,
+    /// Write the per-field distance contributions of all release candidates to this file (as one
+    /// JSON object per album and line).
+    #[arg(long)]
+    explain: Option<PathBuf>,
+    /// Discard the progress of a previous, interrupted import run of the same path and start
+    /// from the first album.
+    #[arg(long)]
+    restart: bool,
+    /// Skip the search and use the release with this MusicBrainz ID (or URL) as the only
+    /// candidate. The path must contain a single album.
+    #[arg(long, value_name = "MBID or URL", value_parser = parse_release_id, conflicts_with = "retry_quarantine")]
+    release: Option<String>,
 }
 
-/// Result returned from the [`select_release()`] function.
-#[allow(clippy::large_enum_variant)]
-enum SelectionResult {
-    /// A candidate was selected and should be assigned to the track collection.
-    Selected(TaggedFileCollection, ReleaseCandidate<MusicBrainzRelease>),
-    /// Skip importing the track collection.
-    Skipped,
-    /// Quit import.
-    Quit,
+/// Parse a MusicBrainz Release ID or URL and return the release ID.
+fn parse_release_id(input: &str) -> Result<String, String> {
+    match MusicBrainzId::find(input) {
+        Some(MusicBrainzId::Release(id)) => Ok(id.into_owned()),
+        Some(id) => Err(format!(
+            "This is a MusicBrainz {} ID, not a release ID.",
+            id.entity_name()
+        )),
+        None => Err("Not a MusicBrainz ID or URL.".to_string()),
+    }
+}
+
+/// Distance explanation of a release candidate, written by the `--explain` option.
+#[derive(Serialize)]
+struct CandidateExplanation<'a> {
+    /// MusicBrainz ID of the release.
+    release_id: Option<Cow<'a, str>>,
+    /// Artist of the release.
+    artist: Option<Cow<'a, str>>,
+    /// Title of the release.
+    title: Option<Cow<'a, str>>,
+    /// Distance that is used for ranking the candidates (including the release preferences).
+    ranking_distance: f64,
+    /// Explanation of the distance between the files and the release.
+    similarity: ReleaseDistanceExplanation,
+}
+
+/// Distance explanations of all release candidates for an album.
+#[derive(Serialize)]
+struct AlbumExplanation<'a> {
+    /// Path of the album.
+    path: &'a Path,
+    /// The release candidates, ordered by distance.
+    candidates: Vec<CandidateExplanation<'a>>,
+}
+
+/// Write the distance explanations of all candidates as a single line of JSON.
+fn write_explanation(
+    config: &Config,
+    writer: &mut impl Write,
+    path: &Path,
+    candidates: &ReleaseCandidateCollection<MusicBrainzRelease>,
+) -> crate::Result<()> {
+    let explanation = AlbumExplanation {
+        path,
+        candidates: candidates
+            .iter()
+            .map(|candidate| CandidateExplanation {
+                release_id: candidate.release().musicbrainz_release_id(),
+                artist: candidate.release().release_artist(),
+                title: candidate.release().release_title(),
+                ranking_distance: candidate.ranking_distance(config).as_f64(),
+                similarity: candidate.similarity().explain(config),
+            })
+            .collect(),
+    };
+    serde_json::to_writer(&mut *writer, &explanation)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Bulk actions that the user chose for the remaining albums of an import run.
+#[derive(Debug, Default)]
+struct BulkApply {
+    /// Apply this release to the remaining albums in the given parent directory (e.g., the other
+    /// discs of a box set).
+    release: Option<(MusicBrainzRelease, PathBuf)>,
+    /// Automatically apply the best candidate if its similarity is at least
+    /// `user_interface.bulk_auto_apply_similarity`.
+    auto_apply_good_matches: bool,
+}
+
+impl BulkApply {
+    /// Apply the release to the remaining albums in the same parent directory as the album at the
+    /// given path.
+    fn apply_release_to_remaining(&mut self, path: &Path, release: MusicBrainzRelease) {
+        let parent = path.parent().unwrap_or(path).to_path_buf();
+        self.release = Some((release, parent));
+    }
+
+    /// Returns the release that should be applied to the album at the given path (if any).
+    fn release_for(&self, path: &Path) -> Option<&MusicBrainzRelease> {
+        self.release
+            .as_ref()
+            .filter(|(_, parent)| path.parent().unwrap_or(path) == parent)
+            .map(|(release, _)| release)
+    }
+}
+
+/// Returns `true` if the distance is small enough for the release to be applied by a bulk action.
+fn is_good_match(config: &Config, distance: &Distance) -> bool {
+    1.0 - distance.as_f64() >= config.user_interface.bulk_auto_apply_similarity
 }
 
 /// Select the release for the given track collection from the list of candidates.
+#[expect(clippy::too_many_arguments)]
 async fn select_release<'a>(
     config: &Config,
     musicbrainz: &'a MusicBrainzClient<'a>,
-    track_collection: TaggedFileCollection,
+    path: &Path,
+    mut track_collection: TaggedFileCollection,
     mut candidates: ReleaseCandidateCollection<MusicBrainzRelease>,
-) -> crate::Result<SelectionResult> {
+    local_release: Option<TaggedFileCollection>,
+    mut decisions: Option<&mut DecisionStore>,
+    bulk_apply: &mut BulkApply,
+) -> crate::Result<ImportDecision> {
+    let mut lookup_again = false;
+    if config.user_interface.resolve_duplicate_tracks && io::stdin().is_terminal() {
+        let duplicate_paths = select_duplicate_tracks_to_drop(&track_collection)?;
+        if !duplicate_paths.is_empty() {
+            log::info!("Dropping {} duplicate tracks", duplicate_paths.len());
+            track_collection = track_collection.without_tracks(&duplicate_paths);
+            lookup_again = true;
+        }
+    }
+    if config.user_interface.resolve_tag_conflicts
+        && io::stdin().is_terminal()
+        && resolve_tag_conflicts(&mut track_collection)?
+    {
+        lookup_again = true;
+    }
+    if lookup_again {
+        log::debug!("Looking up release candidates again with the resolved tracks and tag values");
+        candidates = ReleaseCandidateCollection::from(
+            musicbrainz
+                .find_releases_by_similarity(&track_collection)
+                .await?,
+        )
+        .with_min_similarity(config);
+        if let Some(decisions) = decisions.as_deref() {
+            candidates = candidates.with_decisions(decisions, config);
+        }
+    }
+
     println!(
         "Tagging: {artist} - {title} ({track_count} tracks)",
         artist = track_collection
@@ -58,8 +205,198 @@ async fn select_release<'a>(
             .unwrap_or("[unknown title]".into()),
         track_count = track_collection.release_track_count().unwrap_or(0),
     );
+    print_source_summary(path, &track_collection);
+    if candidates.hidden_count() > 0 {
+        println!(
+            "Hidden {hidden_count} candidates with a similarity below {min_similarity:.0}%",
+            hidden_count = candidates.hidden_count(),
+            min_similarity = config.lookup.min_similarity.unwrap_or_default() * 100.0,
+        );
+    }
+    print_album_gain_warning(config, &track_collection);
+
+    if let Some(release) = bulk_apply.release_for(path) {
+        let candidate =
+            ReleaseCandidate::with_base_release(release.clone(), &track_collection, config);
+        let distance = candidate.distance(config);
+        if is_good_match(config, &distance) {
+            println!("Applying release {} to the remaining albums", release.id);
+            return Ok(ImportDecision::Import(
+                track_collection.assign_tags(&candidate, config),
+                distance,
+            ));
+        }
+        println!(
+            "Not applying release {} because its similarity is below {:.0}%",
+            release.id,
+            config.user_interface.bulk_auto_apply_similarity * 100.0,
+        );
+    }
+
+    if bulk_apply.auto_apply_good_matches
+        && candidates
+            .iter()
+            .next()
+            .is_some_and(|candidate| is_good_match(config, &candidate.distance(config)))
+    {
+        let selected_candidate = candidates.select_index(0);
+        println!(
+            "Automatically applying release {} with a similarity of at least {:.0}%",
+            selected_candidate.release().id,
+            config.user_interface.bulk_auto_apply_similarity * 100.0,
+        );
+        let distance = selected_candidate.distance(config);
+        return Ok(ImportDecision::Import(
+            track_collection.assign_tags(&selected_candidate, config),
+            distance,
+        ));
+    }
+
+    if let Some(local_release) = local_release {
+        println!("Found release metadata in local files:");
+        let candidate =
+            ReleaseCandidate::with_base_release(local_release, &track_collection, config);
+        loop {
+            match ui::handle_candidate(config, &track_collection, &candidate, false)? {
+                // Bulk actions are not offered for local metadata, so they only apply the release
+                // to this album.
+                ui::HandleCandidateResult::Apply
+                | ui::HandleCandidateResult::ApplyToRemaining
+                | ui::HandleCandidateResult::ApplyAndAutoApplyRemaining => {
+                    let distance = candidate.distance(config);
+                    return Ok(ImportDecision::Import(
+                        track_collection.assign_tags(&candidate, config),
+                        distance,
+                    ));
+                }
+                ui::HandleCandidateResult::Skip => {
+                    return Ok(ImportDecision::Skip(SkipReason::SkippedByUser))
+                }
+                ui::HandleCandidateResult::Quit => return Ok(ImportDecision::Quit),
+                ui::HandleCandidateResult::BackToSelection => {
+                    log::debug!("Local metadata rejected, continuing with MusicBrainz candidates");
+                    break;
+                }
+                ui::HandleCandidateResult::Reject => {
+                    record_decision(
+                        decisions.as_deref_mut(),
+                        DecisionKind::Rejected,
+                        candidate.release(),
+                    );
+                    log::debug!("Local metadata rejected, continuing with MusicBrainz candidates");
+                    break;
+                }
+                ui::HandleCandidateResult::PrintTrackList => {
+                    ui::print_tracklist(config, &track_collection)?;
+                }
+                ui::HandleCandidateResult::ShowDetails
+                | ui::HandleCandidateResult::HideDetails
+                | ui::HandleCandidateResult::ShowTagDiff => (),
+            }
+        }
+    }
+
+    if config.decisions.auto_select
+        && candidates.iter().next().is_some_and(|candidate| {
+            decisions.as_deref().is_some_and(|decisions| {
+                decisions.latest_decision(candidate.release()) == Some(DecisionKind::Selected)
+            })
+        })
+    {
+        let selected_candidate = candidates.select_index(0);
+        println!(
+            "Automatically applying previously selected release {}",
+            selected_candidate.release().id
+        );
+        let distance = selected_candidate.distance(config);
+        return Ok(ImportDecision::Import(
+            track_collection.assign_tags(&selected_candidate, config),
+            distance,
+        ));
+    }
+
     let mut allow_autoselection = candidates.len() == 1;
+    if config.user_interface.select_release_group_first {
+        match musicbrainz
+            .find_release_groups_by_similarity(&track_collection)
+            .await
+        {
+            Ok(release_groups) if !release_groups.is_empty() => {
+                match ui::select_release_group(config, &release_groups)? {
+                    ui::ReleaseGroupSelectionResult::ReleaseGroup(release_group) => {
+                        let group_candidates = find_release_group_candidates(
+                            config,
+                            musicbrainz,
+                            &track_collection,
+                            release_group.id.clone(),
+                            decisions.as_deref(),
+                        )
+                        .await?;
+                        if group_candidates.len() == 0 {
+                            log::warn!(
+                                "Failed to retrieve any release from release group {}",
+                                release_group.id
+                            );
+                        } else {
+                            candidates = group_candidates;
+                            allow_autoselection = true;
+                        }
+                    }
+                    ui::ReleaseGroupSelectionResult::ShowAllReleases => (),
+                    ui::ReleaseGroupSelectionResult::Skipped => {
+                        return Ok(ImportDecision::Skip(SkipReason::SkippedByUser))
+                    }
+                    ui::ReleaseGroupSelectionResult::Quit => return Ok(ImportDecision::Quit),
+                }
+            }
+            Ok(_) => log::info!("No release groups found, showing all release candidates"),
+            Err(err) => log::warn!("Failed to search for release groups: {err}"),
+        }
+    }
+
+    // The interactive screen needs a terminal, and it does not select a single good candidate
+    // automatically, so the menus are used in these cases.
+    let mut use_interactive_ui = !config.user_interface.simple_ui
+        && io::stdin().is_terminal()
+        && io::stdout().is_terminal()
+        && !(allow_autoselection && ui::autoselected_candidate(config, &candidates).is_some());
     'select_candidate: loop {
+        if use_interactive_ui {
+            match ui::interactive_select(config, &track_collection, &candidates)? {
+                result @ (ui::InteractiveResult::Apply(candidate_index)
+                | ui::InteractiveResult::ApplyToRemaining(candidate_index)
+                | ui::InteractiveResult::ApplyAndAutoApplyRemaining(candidate_index)) => {
+                    let selected_candidate = candidates.select_index(candidate_index);
+                    record_decision(
+                        decisions.as_deref_mut(),
+                        DecisionKind::Selected,
+                        selected_candidate.release(),
+                    );
+                    match result {
+                        ui::InteractiveResult::ApplyToRemaining(_) => bulk_apply
+                            .apply_release_to_remaining(path, selected_candidate.release().clone()),
+                        ui::InteractiveResult::ApplyAndAutoApplyRemaining(_) => {
+                            bulk_apply.auto_apply_good_matches = true;
+                        }
+                        _ => (),
+                    }
+                    let distance = selected_candidate.distance(config);
+                    return Ok(ImportDecision::Import(
+                        track_collection.assign_tags(&selected_candidate, config),
+                        distance,
+                    ));
+                }
+                ui::InteractiveResult::Skip => {
+                    return Ok(ImportDecision::Skip(SkipReason::SkippedByUser))
+                }
+                ui::InteractiveResult::Quit => return Ok(ImportDecision::Quit),
+                ui::InteractiveResult::ShowMenu => {
+                    use_interactive_ui = false;
+                    allow_autoselection = false;
+                }
+            }
+        }
+
         let selected_candidate: &ReleaseCandidate<_> = loop {
             match ui::select_candidate(config, &candidates, allow_autoselection)? {
                 ui::ReleaseCandidateSelectionResult::Candidate(candidate) => break candidate,
@@ -90,7 +427,11 @@ async fn select_release<'a>(
                         .await;
                 }
                 ui::ReleaseCandidateSelectionResult::PrintTrackList => {
-                    print_tracklist(&track_collection);
+                    ui::print_tracklist(config, &track_collection)?;
+                    continue 'select_candidate;
+                }
+                ui::ReleaseCandidateSelectionResult::AddToMusicBrainz => {
+                    ui::open_release_editor(config, &track_collection);
                     continue 'select_candidate;
                 }
                 #[cfg(feature = "dev")]
@@ -142,70 +483,279 @@ async fn select_release<'a>(
                     continue 'select_candidate;
                 }
                 ui::ReleaseCandidateSelectionResult::Skipped => {
-                    return Ok(SelectionResult::Skipped)
+                    return Ok(ImportDecision::Skip(SkipReason::SkippedByUser))
                 }
-                ui::ReleaseCandidateSelectionResult::Quit => return Ok(SelectionResult::Quit),
+                ui::ReleaseCandidateSelectionResult::Quit => return Ok(ImportDecision::Quit),
             };
         };
         allow_autoselection = false;
 
-        match ui::handle_candidate(config, &track_collection, selected_candidate)? {
-            ui::HandleCandidateResult::Apply => {
-                let candidate_index = candidates.find_index(selected_candidate);
-                let selected_candidate = candidates.select_index(candidate_index);
-                return Ok(SelectionResult::Selected(
-                    track_collection,
-                    selected_candidate,
-                ));
-            }
-            ui::HandleCandidateResult::Skip => {
-                log::warn!("Skipping collection");
-                return Ok(SelectionResult::Skipped);
-            }
-            ui::HandleCandidateResult::BackToSelection => {
-                continue 'select_candidate;
-            }
-            ui::HandleCandidateResult::Quit => {
-                return Ok(SelectionResult::Quit);
-            }
-            ui::HandleCandidateResult::ShowDetails
-            | ui::HandleCandidateResult::HideDetails
-            | ui::HandleCandidateResult::PrintTrackList => {
-                unreachable!()
+        // The candidate is shown again until the user makes a decision.
+        loop {
+            match ui::handle_candidate(config, &track_collection, selected_candidate, true)? {
+                result @ (ui::HandleCandidateResult::Apply
+                | ui::HandleCandidateResult::ApplyToRemaining
+                | ui::HandleCandidateResult::ApplyAndAutoApplyRemaining) => {
+                    let candidate_index = candidates.find_index(selected_candidate);
+                    let selected_candidate = candidates.select_index(candidate_index);
+                    record_decision(
+                        decisions.as_deref_mut(),
+                        DecisionKind::Selected,
+                        selected_candidate.release(),
+                    );
+                    match result {
+                        ui::HandleCandidateResult::ApplyToRemaining => {
+                            bulk_apply.apply_release_to_remaining(
+                                path,
+                                selected_candidate.release().clone(),
+                            );
+                        }
+                        ui::HandleCandidateResult::ApplyAndAutoApplyRemaining => {
+                            bulk_apply.auto_apply_good_matches = true;
+                        }
+                        _ => (),
+                    }
+                    let distance = selected_candidate.distance(config);
+                    return Ok(ImportDecision::Import(
+                        track_collection.assign_tags(&selected_candidate, config),
+                        distance,
+                    ));
+                }
+                ui::HandleCandidateResult::Skip => {
+                    log::warn!("Skipping collection");
+                    return Ok(ImportDecision::Skip(SkipReason::SkippedByUser));
+                }
+                ui::HandleCandidateResult::Reject => {
+                    record_decision(
+                        decisions.as_deref_mut(),
+                        DecisionKind::Rejected,
+                        selected_candidate.release(),
+                    );
+                    continue 'select_candidate;
+                }
+                ui::HandleCandidateResult::BackToSelection => {
+                    continue 'select_candidate;
+                }
+                ui::HandleCandidateResult::Quit => {
+                    return Ok(ImportDecision::Quit);
+                }
+                ui::HandleCandidateResult::PrintTrackList => {
+                    ui::print_tracklist(config, &track_collection)?;
+                }
+                ui::HandleCandidateResult::ShowDetails
+                | ui::HandleCandidateResult::HideDetails
+                | ui::HandleCandidateResult::ShowTagDiff => (),
             }
         }
     }
 }
 
-/// Print Tracklist (for copy & paste into MusicBrainz).
-fn print_tracklist(release: &impl ReleaseLike) {
-    for (media_index, media) in release.media().enumerate() {
-        let format = media.media_format().unwrap_or_else(|| "Medium".into());
-        if media_index > 0 {
-            println!();
+/// Fetch the releases of the release group and rank them as candidates for the track collection.
+async fn find_release_group_candidates(
+    config: &Config,
+    musicbrainz: &MusicBrainzClient<'_>,
+    track_collection: &TaggedFileCollection,
+    release_group_id: String,
+    decisions: Option<&DecisionStore>,
+) -> crate::Result<ReleaseCandidateCollection<MusicBrainzRelease>> {
+    log::debug!("Fetching releases of release group {release_group_id}");
+    let candidates = musicbrainz
+        .find_releases_by_release_group_id(release_group_id)
+        .await?
+        .fold(
+            ReleaseCandidateCollection::from(Vec::new()),
+            |mut acc, result| async {
+                match result {
+                    Ok(release) => acc.add_release(release, track_collection, config),
+                    Err(err) => log::warn!("Failed to retrieve release: {err}"),
+                }
+                acc
+            },
+        )
+        .await;
+    Ok(match decisions {
+        Some(decisions) => candidates.with_decisions(decisions, config),
+        None => candidates,
+    })
+}
+
+/// Record a decision about the release (if the decision store is enabled).
+fn record_decision<T: ReleaseLike + ?Sized>(
+    decisions: Option<&mut DecisionStore>,
+    kind: DecisionKind,
+    release: &T,
+) {
+    let Some(decisions) = decisions else {
+        return;
+    };
+    let Some(decision) = Decision::new(kind, release) else {
+        return;
+    };
+    if let Err(err) = decisions.record(decision) {
+        log::warn!("Failed to record decision: {err}");
+    }
+}
+
+/// Print the source directory together with the number, formats and total length of the files.
+fn print_source_summary(path: &Path, track_collection: &TaggedFileCollection) {
+    if let Some(directory_group) = track_collection.directory_group() {
+        println!(
+            "Source: {path} (only {directory_group})",
+            path = path.display()
+        );
+    } else {
+        println!("Source: {path}", path = path.display());
+    }
+    println!(
+        "Files: {file_count} ({formats}), total length: {total_length}",
+        file_count = track_collection.tracks().count(),
+        formats = track_collection.format_summary(),
+        total_length = track_collection
+            .total_length()
+            .map_or_else(|| "?:??".to_string(), |length| length.formatted_duration()),
+    );
+}
+
+/// Ask the user how to resolve the collision if the album directory of a release is already used
+/// by a different release that was imported during this run.
+fn ask_album_collision(request: &CollisionRequest) -> crate::Result<CollisionResolution> {
+    println!(
+        "The album directory {} is already used by a different release.",
+        request.directory().display()
+    );
+    let suffixes = request.suffixes();
+    let mut options = suffixes
+        .iter()
+        .map(|suffix| format!("Append \"{suffix}\" to the directory name"))
+        .collect::<Vec<_>>();
+    options.push("Import into the same directory".to_string());
+    options.push("Skip".to_string());
+    let selected = match Select::new("How should the collision be resolved?", options).raw_prompt()
+    {
+        Ok(selected) => selected.index,
+        Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
+            return Ok(CollisionResolution::Skip)
         }
-        if let Some(title) = media.media_title() {
-            println!("{format} {index}: {title}", index = media_index + 1);
-        } else {
-            println!("{format} {index}", index = media_index + 1);
+        Err(err) => return Err(err.into()),
+    };
+    Ok(match suffixes.get(selected) {
+        Some(suffix) => CollisionResolution::AppendSuffix(suffix.clone()),
+        None if selected == suffixes.len() => CollisionResolution::SameDirectory,
+        None => CollisionResolution::Skip,
+    })
+}
+
+/// Ask the user which value to use for each lookup tag that has conflicting values across the
+/// tracks.
+///
+/// Returns `true` if any value was changed.
+fn resolve_tag_conflicts(track_collection: &mut TaggedFileCollection) -> crate::Result<bool> {
+    let mut changed = false;
+    for (key, values) in track_collection.lookup_tag_conflicts() {
+        let name = match key {
+            TagKey::Album => "album title".to_string(),
+            TagKey::AlbumArtist => "album artist".to_string(),
+            _ => key.to_string(),
+        };
+        println!("The tracks have different values for the {name}:");
+        let mut options = values
+            .iter()
+            .map(|value| format!("Use \"{value}\""))
+            .collect::<Vec<_>>();
+        options.push("Enter a different value".to_string());
+        options.push("Leave undecided".to_string());
+        let selected =
+            match Select::new("Which value should be used for the lookup?", options).raw_prompt() {
+                Ok(selected) => selected.index,
+                Err(InquireError::OperationCanceled) => continue,
+                Err(err) => return Err(err.into()),
+            };
+        let value = match values.get(selected) {
+            Some(value) => value.clone(),
+            None if selected == values.len() => {
+                match Text::new(&format!("Enter the {name}:")).prompt() {
+                    Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
+                    Ok(_) | Err(InquireError::OperationCanceled) => continue,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            None => continue,
+        };
+        track_collection.set_release_tag_value(&key, &value);
+        changed = true;
+    }
+    Ok(changed)
+}
+
+/// Ask the user which file to keep for each group of duplicate tracks, and return the paths of the
+/// files that should be dropped.
+///
+/// The dropped files are left untouched in the source directory.
+fn select_duplicate_tracks_to_drop(
+    track_collection: &TaggedFileCollection,
+) -> crate::Result<HashSet<PathBuf>> {
+    let mut dropped_paths = HashSet::new();
+    for paths in track_collection.find_duplicate_tracks() {
+        println!("The following files seem to contain the same track:");
+        for path in &paths {
+            println!(" * {}", path.display());
         }
-        for (track_index, track) in media.media_tracks().enumerate() {
-            let track_number = track
-                .track_number()
-                .unwrap_or_else(|| Cow::from(format!("{track_index}")));
-            let track_artist = track
-                .track_artist()
-                .unwrap_or_else(|| Cow::from("[unknown]"));
-            let track_title = track
-                .track_title()
-                .unwrap_or_else(|| Cow::from("[unnamed track]"));
-            let track_length = track.track_length().map_or_else(
-                || Cow::from("?:??"),
-                |length| Cow::from(length.formatted_duration()),
+        let mut options = paths
+            .iter()
+            .map(|path| {
+                format!(
+                    "Keep only \"{}\"",
+                    path.file_name()
+                        .unwrap_or(path.as_os_str())
+                        .to_string_lossy()
+                )
+            })
+            .collect::<Vec<_>>();
+        options.push("Keep all files".to_string());
+        let selected = match Select::new("Which file should be used?", options).raw_prompt() {
+            Ok(selected) => selected.index,
+            Err(InquireError::OperationCanceled) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if selected < paths.len() {
+            dropped_paths.extend(
+                paths
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, _)| *index != selected)
+                    .map(|(_, path)| path),
             );
-            println!("{track_number}. {track_title} - {track_artist} ({track_length})");
         }
     }
+    Ok(dropped_paths)
+}
+
+/// Print a warning if the album gain is missing or incomplete because the loudness analysis of
+/// some tracks failed.
+fn print_album_gain_warning(config: &Config, track_collection: &TaggedFileCollection) {
+    let failures = track_collection.album_gain_failures();
+    if failures.is_empty() || !config.analyzers.enabled.contains(&AnalyzerType::EbuR128) {
+        return;
+    }
+
+    let theme = &config.user_interface.theme;
+    let message = if track_collection.is_album_gain_partial() {
+        format!(
+            "Album gain is calculated without {} of {} tracks:",
+            failures.len(),
+            track_collection.tracks().count()
+        )
+    } else {
+        format!(
+            "Album gain is not available, because {} of {} tracks could not be analyzed:",
+            failures.len(),
+            track_collection.tracks().count()
+        )
+    };
+    println!("{}", theme.medium_distance_style().apply(message));
+    for (path, reason) in failures {
+        println!(" * {path}: {reason}", path = path.display());
+    }
 }
 
 /// Run an import.
@@ -215,56 +765,137 @@ fn print_tracklist(release: &impl ReleaseLike) {
 /// If the underlying [`walk_dir`] function encounters any form of I/O or other error, an error
 /// variant will be returned.
 pub async fn run(config: &Config, cache: Option<&Cache>, args: Args) -> crate::Result<()> {
-    let mut scanner = Scanner::scan(config.clone(), cache.cloned(), args.path);
-
-    let (importer_tx, mut importer_rx) = tokio::sync::mpsc::channel::<(
-        TaggedFileCollection,
-        ReleaseCandidate<MusicBrainzRelease>,
-    )>(20);
-    let cloned_config = config.clone();
-    let importer_handle = tokio::task::spawn(async move {
-        while let Some((track_collection, selected_candidate)) = importer_rx.recv().await {
-            let mut track_collection = track_collection.assign_tags(&selected_candidate);
-            if let Err(err) = track_collection.move_files(&cloned_config) {
-                log::error!("Failed to move files: {err}");
-                continue;
-            };
+    let path = if args.retry_quarantine {
+        let Some(quarantine_dir) = quarantine::quarantine_dir(config)? else {
+            eprintln!("No quarantine directory configured.");
+            return Ok(());
+        };
+        quarantine_dir
+    } else {
+        args.path
+            .expect("path is required unless retrying the quarantine")
+    };
+    if args.restart {
+        ImportProgress::open_default(&path)?.clear()?;
+    }
+    // The release is used as the only candidate for every album, so it must not be applied to
+    // multiple albums at once.
+    if args.release.is_some() && scanner::count_albums(config, path.clone(), 2) > 1 {
+        eprintln!(
+            "{} contains more than one album, but --release can only be used for a single album.",
+            path.display()
+        );
+        return Ok(());
+    }
+    let config = if args.release.is_some() {
+        let mut config = config.clone();
+        config.lookup.release_id = args.release;
+        Cow::Owned(config)
+    } else {
+        Cow::Borrowed(config)
+    };
+    let config = config.as_ref();
+    let stats = import_path(
+        config,
+        cache,
+        path,
+        &SelectionMode::Interactive,
+        args.explain.as_deref(),
+        true,
+    )
+    .await?;
+    ui::print_import_summary(config, &stats);
 
-            if let Err(err) = track_collection.write_tags() {
-                log::error!("Failed to write tags: {err}");
-            };
-        }
-    });
-
-    let musicbrainz = MusicBrainzClient::new(config, cache);
-    while let Some(result) = scanner.recv().await {
-        let (track_collection, candidates) = match result {
-            Ok(res) => res,
-            Err(err) => {
-                log::error!("Scan of {} failed: {}", err.path.display(), err.source);
+    Ok(())
+}
+
+/// Import all albums in the given path and return the statistics of the import run.
+///
+/// If `explain_path` is given, the distance explanations of all release candidates are written to
+/// that file (in interactive mode). If `resume` is `true`, the progress of the import run is
+/// recorded, and albums that were already imported or skipped in a previous, interrupted run of
+/// the same path are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the network configuration is invalid, if the selection of a release
+/// candidate fails or if the explanation file cannot be created.
+pub async fn import_path(
+    config: &Config,
+    cache: Option<&Cache>,
+    import_root: PathBuf,
+    mode: &SelectionMode,
+    explain_path: Option<&Path>,
+    resume: bool,
+) -> crate::Result<ImportStats> {
+    let mut explain_writer = explain_path
+        .map(File::create)
+        .transpose()?
+        .map(BufWriter::new);
+    let musicbrainz = MusicBrainzClient::new(config, cache)?;
+    let mut session = ImportSession::start(
+        config.clone(),
+        cache.cloned(),
+        import_root,
+        mode.clone(),
+        resume,
+    );
+
+    let mut decisions = config
+        .decisions
+        .enabled
+        .then(|| {
+            DecisionStore::open_default()
+                .inspect_err(|err| log::warn!("Decision store is not available: {err}"))
+                .ok()
+        })
+        .flatten();
+    let mut bulk_apply = BulkApply::default();
+    while let Some(event) = session.next().await {
+        let request = match event {
+            ImportEvent::Resumed { finished_count } => {
+                println!(
+                    "Resuming previous import run, skipping {finished_count} albums that were already processed."
+                );
                 continue;
             }
-        };
-        match select_release(config, &musicbrainz, track_collection, candidates).await? {
-            SelectionResult::Selected(track_collection, selected_candidate) => {
-                if let Err(err) = importer_tx
-                    .send((track_collection, selected_candidate))
-                    .await
-                {
-                    log::error!("Failed to send job to importer: {err}");
-                };
-            }
-            SelectionResult::Skipped => {
+            ImportEvent::NeedsDecision(request) => request,
+            ImportEvent::AlbumCollision(request) => {
+                let resolution = ask_album_collision(&request)?;
+                request.respond(resolution);
                 continue;
             }
-            SelectionResult::Quit => {
-                break;
-            }
+            _ => continue,
         };
-    }
 
-    drop(importer_tx);
-    importer_handle.await.unwrap();
+        let (album, responder) = request.into_parts();
+        // The decisions may have changed since the candidates were ranked by the import session
+        // (e.g., for the other discs of a box set).
+        let candidates = match decisions.as_ref() {
+            Some(decisions) => album.candidates.with_decisions(decisions, config),
+            None => album.candidates,
+        };
+        if let Some(writer) = explain_writer.as_mut() {
+            if let Err(err) = write_explanation(config, writer, &album.path, &candidates) {
+                log::error!("Failed to write distance explanation: {err}");
+            }
+        }
+        let decision = select_release(
+            config,
+            &musicbrainz,
+            &album.path,
+            album.track_collection,
+            candidates,
+            album.local_release,
+            decisions.as_mut(),
+            &mut bulk_apply,
+        )
+        .await?;
+        responder.respond(decision);
+    }
 
-    Ok(())
+    if let Some(mut writer) = explain_writer {
+        writer.flush()?;
+    }
+    session.finish().await
 }
@@ -11,10 +11,17 @@
 mod analyze;
 mod cache;
 mod config;
+mod fingerprint;
 mod import;
+mod replaygain;
+#[cfg(unix)]
+mod serve;
+mod tags;
 mod ui;
+mod undo;
+mod watch;
 
-use crate::{Cache, Config, PKG_NAME, PKG_VERSION, USER_AGENT};
+use crate::{Cache, Config, PKG_NAME, PKG_VERSION};
 use clap::{Parser, Subcommand};
 use log::LevelFilter;
 use simplelog::{ConfigBuilder as LogConfigBuilder, WriteLogger};
@@ -23,6 +30,7 @@ use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use xdg::BaseDirectories;
 
 /// Command line Arguments.
@@ -35,12 +43,16 @@ struct Args {
     /// Path to configuration file.
     #[arg(short, long, required = false)]
     config_path: Option<PathBuf>,
+    /// Name of the library whose configuration overrides (from the `[libraries.<name>]` table)
+    /// are used.
+    #[arg(short, long, required = false)]
+    library: Option<String>,
 }
 
 /// Supported CLI Commands.
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Show the current cache usage.
+    /// Show the current cache usage, list or purge cached items.
     Cache(cache::Args),
     /// Show your current configuration.
     Config(config::Args),
@@ -48,6 +60,20 @@ enum Commands {
     Import(import::Args),
     /// Analyze a file.
     Analyze(analyze::Args),
+    /// Print the Chromaprint fingerprints of files (compatible with `fpcalc`).
+    Fingerprint(fingerprint::Args),
+    /// Watch a directory and automatically import albums that are added to it.
+    Watch(watch::Args),
+    /// Revert an import by restoring the original paths and tags.
+    Undo(undo::Args),
+    /// Analyze the loudness of files and write ReplayGain tags without changing other metadata.
+    #[command(name = "replaygain")]
+    ReplayGain(replaygain::Args),
+    /// Get, set or delete individual tags in files without re-importing them.
+    Tags(tags::Args),
+    /// Serve the import pipeline over a local socket (JSON-RPC) for alternative user interfaces.
+    #[cfg(unix)]
+    Serve(serve::Args),
 }
 
 /// Append a numeric suffix (e.g., `.1`) to a path.
@@ -98,7 +124,7 @@ fn rotate_logfiles(base_path: impl AsRef<Path>) -> io::Result<()> {
 /// # Panics
 ///
 /// May panic if logging cannot be initialized.
-pub async fn main() -> crate::Result<()> {
+pub fn main() -> crate::Result<()> {
     let args = Args::parse();
 
     let base_dirs = BaseDirectories::with_prefix(PKG_NAME)?;
@@ -119,29 +145,48 @@ pub async fn main() -> crate::Result<()> {
     log::info!("Started {PKG_NAME} {PKG_VERSION}");
 
     // Load configuration
-    let config = base_dirs
+    let config_builder = base_dirs
         .find_config_files("config.toml")
         .map(Cow::from)
         .chain(args.config_path.iter().map(Cow::from))
         .fold(Config::builder().with_defaults(), |builder, path| {
             builder.with_file(path)
         })
-        .build()?;
+        .with_environment();
+    let config = match args.library {
+        Some(library) => config_builder.with_library(library),
+        None => config_builder,
+    }
+    .build()?;
+
+    // Disable colors if requested by the user or if the output is not a terminal.
+    if !ui::colors_enabled() {
+        crossterm::style::force_color_output(false);
+    }
 
     // Initialize cache
-    let cache = Cache::new(base_dirs);
-
-    // Set User-Agent header for MusicBrainz requests. This is mandatory to comply with
-    // MusicBrainz's API application identification rules.
-    //
-    // See this for details:
-    // - <https://musicbrainz.org/doc/MusicBrainz_API#Application_rate_limiting_and_identification>
-    musicbrainz_rs_nova::config::set_user_agent(USER_AGENT);
-
-    match args.command {
-        Commands::Import(cmd_args) => import::run(&config, Some(&cache), cmd_args).await,
-        Commands::Config(cmd_args) => config::run(&config, Some(&cache), cmd_args),
-        Commands::Cache(cmd_args) => cache::run(&config, Some(&cache), cmd_args),
-        Commands::Analyze(cmd_args) => analyze::run(&config, Some(&cache), cmd_args),
+    let cache =
+        Cache::new(base_dirs).with_max_age(Duration::from_secs(config.lookup.cache_max_age));
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(run_command(&config, &cache, args.command))
+}
+
+/// Run the subcommand.
+async fn run_command(config: &Config, cache: &Cache, command: Commands) -> crate::Result<()> {
+    match command {
+        Commands::Import(cmd_args) => import::run(config, Some(cache), cmd_args).await,
+        Commands::Config(cmd_args) => config::run(config, Some(cache), cmd_args),
+        Commands::Cache(cmd_args) => cache::run(config, Some(cache), cmd_args),
+        Commands::Analyze(cmd_args) => analyze::run(config, Some(cache), cmd_args),
+        Commands::Fingerprint(cmd_args) => fingerprint::run(config, Some(cache), cmd_args),
+        Commands::Watch(cmd_args) => watch::run(config, Some(cache), cmd_args).await,
+        Commands::Undo(cmd_args) => undo::run(config, Some(cache), cmd_args),
+        Commands::ReplayGain(cmd_args) => replaygain::run(config, Some(cache), cmd_args),
+        Commands::Tags(cmd_args) => tags::run(config, Some(cache), cmd_args),
+        #[cfg(unix)]
+        Commands::Serve(cmd_args) => serve::run(config, Some(cache), cmd_args).await,
     }
 }
@@ -0,0 +1,109 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Module for the `replaygain` CLI subcommand.
+
+use crate::analyzer;
+use crate::config::AnalyzerType;
+use crate::release::ReleaseLike;
+use crate::scanner::is_supported_audio_file;
+use crate::tag::TagKey;
+use crate::util::walk_dir;
+use crate::Cache;
+use crate::{Config, TaggedFile, TaggedFileCollection};
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Command line arguments for the `replaygain` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Path of the album directory (or directory of albums) to analyze.
+    path: PathBuf,
+    /// Only print the calculated values without writing any tags.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Analyze the loudness of all files in each directory and write the ReplayGain tags in-place.
+///
+/// Each directory that contains audio files is treated as an album. No other metadata is changed
+/// and no files are moved.
+///
+/// # Errors
+///
+/// Returns an error if the path cannot be read or if writing the tags fails.
+pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let mut config = config.clone();
+    config.analyzers.enabled = vec![AnalyzerType::EbuR128];
+
+    for (dir, _subdirs, files) in walk_dir(args.path)
+        .with_ignore_patterns(&config.paths.ignore_patterns)
+        .filter_map(Result::ok)
+    {
+        let tracks = files
+            .iter()
+            .filter(|path| is_supported_audio_file(path))
+            .filter_map(
+                |path| match TaggedFile::read_from_path(path, &config.tags) {
+                    Ok(tagged_file) => Some(tagged_file),
+                    Err(err) => {
+                        log::warn!("Failed to read {}: {err}", path.display());
+                        None
+                    }
+                },
+            )
+            .map(|tagged_file| {
+                let analysis_result = analyzer::analyze(&config, &tagged_file.path)
+                    .inspect_err(|err| {
+                        log::warn!("Analysis of {} failed: {err}", tagged_file.path.display());
+                    })
+                    .ok();
+                tagged_file.with_analysis_results(analysis_result)
+            })
+            .collect::<Vec<_>>();
+        if tracks.is_empty() {
+            continue;
+        }
+
+        let mut track_collection = TaggedFileCollection::new(tracks)
+            .with_partial_album_gain(config.analyzers.partial_album_gain);
+        track_collection.assign_replay_gain_tags(&config.tags.replay_gain);
+
+        println!("{}", dir.display());
+        println!(
+            "  Album Gain: {gain}, Album Peak: {peak}",
+            gain = track_collection
+                .replay_gain_album_gain_analyzed()
+                .unwrap_or("[unknown]".into()),
+            peak = track_collection
+                .replay_gain_album_peak_analyzed()
+                .unwrap_or("[unknown]".into()),
+        );
+        for (path, reason) in track_collection.album_gain_failures() {
+            println!("  Missing analysis for {}: {reason}", path.display());
+        }
+        for track in track_collection.tracks() {
+            let file_name = track.path.file_name().unwrap_or_default().to_string_lossy();
+            println!(
+                "  {file_name}: Track Gain: {gain}, Track Peak: {peak}",
+                gain = track
+                    .first_tag_value(&TagKey::ReplayGainTrackGain)
+                    .unwrap_or("[unknown]".into()),
+                peak = track
+                    .first_tag_value(&TagKey::ReplayGainTrackPeak)
+                    .unwrap_or("[unknown]".into()),
+            );
+        }
+
+        if !args.dry_run {
+            track_collection.write_tags(&config)?;
+        }
+    }
+
+    Ok(())
+}
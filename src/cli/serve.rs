@@ -0,0 +1,470 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Module for the `serve` CLI subcommand.
+//!
+//! The server exposes the import pipeline over a local Unix socket using JSON-RPC 2.0, so it is
+//! only available on Unix platforms. Each request and response is a single line of JSON, and
+//! notifications (requests without an `id`) are not answered. The albums are scanned and imported
+//! by an [`ImportSession`], which waits for a decision about each album before presenting the
+//! next one. The supported methods are:
+//!
+//! - `list_pending`: List the scanned albums that are waiting for a decision.
+//! - `get_candidates` (`{"album": <id>}`): List the release candidates of an album with their
+//!   similarity details.
+//! - `apply` (`{"album": <id>, "candidate": <index>}`): Apply a candidate and import the album in
+//!   the background.
+//! - `skip` (`{"album": <id>}`): Skip the album (and move it to the quarantine, if configured).
+
+use super::ui;
+use crate::import_session::{
+    CollisionResolution, DecisionRequest, ImportEvent, ImportSession, SelectionMode,
+};
+use crate::release::ReleaseLike;
+use crate::{Cache, Config, PKG_NAME};
+use clap::Parser;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+use xdg::BaseDirectories;
+
+/// File name of the default socket in the runtime directory.
+const SOCKET_FILE_NAME: &str = "helicon.sock";
+
+/// Invalid JSON was received by the server.
+const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// Command line arguments for the `serve` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Path to import.
+    path: PathBuf,
+    /// Path of the Unix socket to listen on (defaults to a socket in the XDG runtime directory).
+    #[arg(long)]
+    socket: Option<PathBuf>,
+}
+
+/// A JSON-RPC request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    /// Name of the method to call.
+    method: String,
+    /// Parameters of the method.
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC error.
+#[derive(Debug, Serialize, PartialEq)]
+struct RpcError {
+    /// Error code.
+    code: i64,
+    /// Human-readable error message.
+    message: String,
+}
+
+impl RpcError {
+    /// Create a new error.
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Parameters that refer to a single album.
+#[derive(Debug, Deserialize)]
+struct AlbumParams {
+    /// Identifier of the album (as returned by `list_pending`).
+    album: usize,
+}
+
+/// Parameters of the `apply` method.
+#[derive(Debug, Deserialize)]
+struct ApplyParams {
+    /// Identifier of the album (as returned by `list_pending`).
+    album: usize,
+    /// Index of the candidate (as returned by `get_candidates`).
+    candidate: usize,
+}
+
+/// An album that was scanned and waits for a decision.
+#[derive(Debug)]
+struct PendingAlbum {
+    /// Identifier of the album.
+    id: usize,
+    /// The decision request of the import session.
+    request: DecisionRequest,
+}
+
+/// State that is shared between all connections.
+#[derive(Debug)]
+struct ServerState {
+    /// The configuration.
+    config: Config,
+    /// Albums that are waiting for a decision.
+    pending: Vec<PendingAlbum>,
+    /// Identifier of the next scanned album.
+    next_id: usize,
+}
+
+impl ServerState {
+    /// Create a new, empty server state.
+    fn new(config: Config) -> Self {
+        Self {
+            config,
+            pending: vec![],
+            next_id: 0,
+        }
+    }
+
+    /// Add an album that needs a decision.
+    fn add_album(&mut self, request: DecisionRequest) {
+        log::info!(
+            "Album {} is pending ({})",
+            self.next_id,
+            request.album().path.display()
+        );
+        self.pending.push(PendingAlbum {
+            id: self.next_id,
+            request,
+        });
+        self.next_id += 1;
+    }
+
+    /// Remove the album with the given identifier from the pending albums.
+    fn take_album(&mut self, id: usize) -> Result<PendingAlbum, RpcError> {
+        self.pending
+            .iter()
+            .position(|album| album.id == id)
+            .map(|index| self.pending.remove(index))
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown album {id}")))
+    }
+
+    /// Get the album with the given identifier.
+    fn album(&self, id: usize) -> Result<&PendingAlbum, RpcError> {
+        self.pending
+            .iter()
+            .find(|album| album.id == id)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown album {id}")))
+    }
+
+    /// List the pending albums.
+    fn list_pending(&self) -> Value {
+        self.pending
+            .iter()
+            .map(|pending| {
+                let album = pending.request.album();
+                json!({
+                    "id": pending.id,
+                    "path": album.path,
+                    "artist": album.track_collection.release_artist(),
+                    "title": album.track_collection.release_title(),
+                    "track_count": album.track_collection.release_track_count(),
+                    "candidate_count": album.candidates.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// List the candidates of an album.
+    fn get_candidates(&self, params: &AlbumParams) -> Result<Value, RpcError> {
+        let album = self.album(params.album)?.request.album();
+        Ok(album
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| {
+                let release = candidate.release();
+                let similarity = candidate.similarity();
+                json!({
+                    "index": index,
+                    "release_id": release.musicbrainz_release_id(),
+                    "artist": release.release_artist(),
+                    "title": release.release_title(),
+                    "release_date": release.release_date(),
+                    "release_country": release.release_country(),
+                    "media_format": release.release_media_format(),
+                    "record_label": release.record_label(),
+                    "catalog_number": release.catalog_number(),
+                    "track_count": release.release_track_count(),
+                    "distance": candidate.distance(&self.config).as_f64(),
+                    "matched_tracks": similarity.track_assignment().matched_tracks().count(),
+                    "unmatched_tracks": similarity.track_assignment().unmatched_tracks().len(),
+                    "problems": similarity
+                        .problems()
+                        .map(|problem| problem.to_string())
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect())
+    }
+
+    /// Apply a candidate. The album is imported in the background by the import session.
+    fn apply(&mut self, params: &ApplyParams) -> Result<Value, RpcError> {
+        if params.candidate >= self.album(params.album)?.request.album().candidates.len() {
+            return Err(RpcError::new(
+                INVALID_PARAMS,
+                format!("Unknown candidate {}", params.candidate),
+            ));
+        }
+
+        let album = self.take_album(params.album)?;
+        album.request.apply_candidate(params.candidate);
+        Ok(Value::Null)
+    }
+
+    /// Skip the album.
+    fn skip(&mut self, params: &AlbumParams) -> Result<Value, RpcError> {
+        self.take_album(params.album)?.request.skip();
+        Ok(Value::Null)
+    }
+
+    /// Dispatch a request to the corresponding method.
+    fn handle_request(&mut self, request: Request) -> Result<Value, RpcError> {
+        /// Parse the request parameters.
+        fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+            serde_json::from_value(params)
+                .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+        }
+
+        match request.method.as_str() {
+            "list_pending" => Ok(self.list_pending()),
+            "get_candidates" => self.get_candidates(&params(request.params)?),
+            "apply" => self.apply(&params(request.params)?),
+            "skip" => self.skip(&params(request.params)?),
+            method => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method {method}"),
+            )),
+        }
+    }
+}
+
+/// Parse a single line and handle the request. Returns the serialized response, or `None` if the
+/// request is a notification.
+async fn handle_line(state: &Mutex<ServerState>, line: &str) -> Option<String> {
+    let (id, result) = match serde_json::from_str::<Value>(line) {
+        Err(err) => (
+            Value::Null,
+            Err(RpcError::new(PARSE_ERROR, err.to_string())),
+        ),
+        Ok(value) => {
+            // Requests without an identifier are notifications, which are never answered.
+            let id = value.get("id").cloned();
+            match serde_json::from_value::<Request>(value) {
+                Err(err) => (
+                    id.unwrap_or_default(),
+                    Err(RpcError::new(INVALID_REQUEST, err.to_string())),
+                ),
+                Ok(request) => {
+                    let result = state.lock().await.handle_request(request);
+                    (id?, result)
+                }
+            }
+        }
+    };
+
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    };
+    Some(response.to_string())
+}
+
+/// Handle all requests of a single client connection.
+async fn handle_connection(state: Arc<Mutex<ServerState>>, stream: UnixStream) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(mut response) = handle_line(&state, &line).await {
+            response.push('\n');
+            writer.write_all(response.as_bytes()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Log the outcome of an album that the import session reports.
+fn log_event(event: &ImportEvent) {
+    match event {
+        ImportEvent::Applied {
+            path,
+            imported: true,
+            ..
+        } => log::info!("Imported {}", path.display()),
+        ImportEvent::Applied {
+            path,
+            imported: false,
+            ..
+        } => log::error!("Import of {} failed", path.display()),
+        ImportEvent::Skipped { path, reason } => {
+            log::info!("Skipped {}: {reason}", path.display());
+        }
+        ImportEvent::Failed { path, error } => {
+            log::error!("Scan of {} failed: {error}", path.display());
+        }
+        _ => (),
+    }
+}
+
+/// Scan the given path and serve the import pipeline on a Unix socket until interrupted.
+///
+/// # Errors
+///
+/// Returns an error if the socket cannot be created.
+pub async fn run(config: &Config, cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let socket_path = match args.socket {
+        Some(path) => path,
+        None => {
+            let base_dirs = BaseDirectories::with_prefix(PKG_NAME)?;
+            base_dirs
+                .place_runtime_file(SOCKET_FILE_NAME)
+                .or_else(|_| base_dirs.place_state_file(SOCKET_FILE_NAME))?
+        }
+    };
+    match fs::remove_file(&socket_path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+        _ => (),
+    }
+    let listener = UnixListener::bind(&socket_path)?;
+    println!("Listening on {}", socket_path.display());
+
+    let state = Arc::new(Mutex::new(ServerState::new(config.clone())));
+    let mut session = ImportSession::start(
+        config.clone(),
+        cache.cloned(),
+        args.path,
+        SelectionMode::Interactive,
+        true,
+    );
+    let mut session_finished = false;
+
+    loop {
+        tokio::select! {
+            result = listener.accept() => {
+                let (stream, _addr) = result?;
+                let state = Arc::clone(&state);
+                let _connection = tokio::task::spawn(async move {
+                    if let Err(err) = handle_connection(state, stream).await {
+                        log::warn!("Connection failed: {err}");
+                    }
+                });
+            }
+            event = session.next(), if !session_finished => match event {
+                Some(ImportEvent::NeedsDecision(request)) => state.lock().await.add_album(request),
+                // Clients cannot be asked about collisions, so they are resolved like in
+                // automatic mode.
+                Some(ImportEvent::AlbumCollision(request)) => {
+                    request.respond(CollisionResolution::Disambiguate);
+                }
+                Some(event) => log_event(&event),
+                None => {
+                    log::info!("All albums have been processed");
+                    session_finished = true;
+                }
+            },
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    // Dropping the pending decision requests stops the import run.
+    state.lock().await.pending.clear();
+    fs::remove_file(&socket_path)?;
+    let stats = session.finish().await?;
+    ui::print_import_summary(config, &stats);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> Mutex<ServerState> {
+        Mutex::new(ServerState::new(Config::default()))
+    }
+
+    /// Handle a line and parse the response (if any).
+    fn handle(state: &Mutex<ServerState>, line: &str) -> Option<Value> {
+        futures::executor::block_on(handle_line(state, line))
+            .map(|response| serde_json::from_str(&response).unwrap())
+    }
+
+    #[test]
+    fn test_list_pending() {
+        let response = handle(
+            &state(),
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "list_pending"}"#,
+        );
+        assert_eq!(
+            response,
+            Some(json!({ "jsonrpc": "2.0", "id": 1, "result": [] }))
+        );
+    }
+
+    #[test]
+    fn test_notifications() {
+        let state = state();
+        assert_eq!(
+            handle(&state, r#"{"jsonrpc": "2.0", "method": "list_pending"}"#),
+            None
+        );
+        assert_eq!(
+            handle(&state, r#"{"jsonrpc": "2.0", "method": "foo"}"#),
+            None
+        );
+        assert_eq!(
+            handle(
+                &state,
+                r#"{"jsonrpc": "2.0", "id": null, "method": "list_pending"}"#
+            ),
+            Some(json!({ "jsonrpc": "2.0", "id": null, "result": [] }))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let state = state();
+        let error_code = |line: &str| handle(&state, line).unwrap()["error"]["code"].as_i64();
+
+        assert_eq!(error_code("{"), Some(PARSE_ERROR));
+        assert_eq!(error_code(r#"{"id": 1}"#), Some(INVALID_REQUEST));
+        assert_eq!(
+            error_code(r#"{"id": 1, "method": "foo"}"#),
+            Some(METHOD_NOT_FOUND)
+        );
+        assert_eq!(
+            error_code(r#"{"id": 1, "method": "get_candidates"}"#),
+            Some(INVALID_PARAMS)
+        );
+        assert_eq!(
+            error_code(r#"{"id": 1, "method": "skip", "params": {"album": 3}}"#),
+            Some(INVALID_PARAMS)
+        );
+    }
+}
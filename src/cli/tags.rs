@@ -0,0 +1,171 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Module for the `tags` CLI subcommand.
+
+use crate::tag::{read_tags_from_path, TagKey};
+use crate::Cache;
+use crate::Config;
+use clap::{Parser, Subcommand};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Command line arguments for the `tags` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Action to perform.
+    #[command(subcommand)]
+    action: Action,
+}
+
+/// Supported tag actions.
+#[derive(Subcommand, Debug)]
+enum Action {
+    /// Print the tag values of the given files.
+    Get {
+        /// Only print the value of this tag key (e.g., `album` or `album_artist`).
+        #[arg(short, long)]
+        key: Option<TagKey>,
+        /// Files to read.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Set a tag value in the given files.
+    Set {
+        /// The tag key to set (e.g., `album` or `album_artist`).
+        #[arg(short, long)]
+        key: TagKey,
+        /// The value to set. Can be given multiple times for multi-valued tags.
+        #[arg(short, long, required = true)]
+        value: Vec<String>,
+        /// Files to modify.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Remove a tag from the given files.
+    Delete {
+        /// The tag key to remove (e.g., `album` or `album_artist`).
+        #[arg(short, long)]
+        key: TagKey,
+        /// Files to modify.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+/// Print the tag values of a file.
+fn print_tags(config: &Config, path: &Path, key: Option<&TagKey>) -> crate::Result<()> {
+    let tags = read_tags_from_path(path, &config.tags)?;
+    println!("{}", path.display());
+    for tag in &tags {
+        let keys = key.map_or_else(|| tag.keys(), |key| vec![key.clone()]);
+        for key in &keys {
+            if let Some(value) = tag.get(key) {
+                println!("  {key}: {value}");
+            }
+        }
+        if key.is_none() {
+            for (name, value) in tag.unknown_fields() {
+                println!("  [{name}]: {value}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Set or remove the tag value in all tags of a file and write them.
+///
+/// Files without tags get the default tag of their format (e.g., an ID3v2 tag for MP3 files).
+///
+/// # Errors
+///
+/// Returns an error if none of the tags of the file can store the value.
+fn modify_tags(config: &Config, path: &Path, key: &TagKey, values: &[String]) -> crate::Result<()> {
+    let mut tags = read_tags_from_path(path, &config.tags)?;
+    for tag in &mut tags {
+        if values.is_empty() {
+            tag.clear(key);
+        } else {
+            let values = values
+                .iter()
+                .map(|value| Cow::from(value.as_str()))
+                .collect::<Vec<_>>();
+            tag.set_multiple(key, &values);
+        }
+    }
+    if !values.is_empty() && !tags.iter().any(|tag| tag.get(key).is_some()) {
+        return Err(crate::Error::TagNotWritable {
+            path: path.to_path_buf(),
+            key: key.clone(),
+        });
+    }
+    for tag in &mut tags {
+        tag.write(path)?;
+    }
+    Ok(())
+}
+
+/// Get, set or delete individual tags in files.
+///
+/// # Errors
+///
+/// Returns an error if any of the files cannot be read or written.
+pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    match args.action {
+        Action::Get { key, paths } => {
+            for path in &paths {
+                print_tags(config, path, key.as_ref())?;
+            }
+        }
+        Action::Set { key, value, paths } => {
+            for path in &paths {
+                modify_tags(config, path, &key, &value)?;
+                log::info!("Set {key} in {}", path.display());
+            }
+        }
+        Action::Delete { key, paths } => {
+            for path in &paths {
+                modify_tags(config, path, &key, &[])?;
+                log::info!("Removed {key} from {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_modify_tags_without_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.flac");
+        let _ = std::fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/media/untagged/track.flac"
+            ),
+            &path,
+        )
+        .unwrap();
+
+        let config = Config::default();
+        modify_tags(&config, &path, &TagKey::Album, &["Album".to_string()]).unwrap();
+        let tags = read_tags_from_path(&path, &config.tags).unwrap();
+        assert!(tags
+            .iter()
+            .any(|tag| tag.get(&TagKey::Album).as_deref() == Some("Album")));
+
+        modify_tags(&config, &path, &TagKey::Album, &[]).unwrap();
+        let tags = read_tags_from_path(&path, &config.tags).unwrap();
+        assert!(tags.iter().all(|tag| tag.get(&TagKey::Album).is_none()));
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Cover art thumbnails of release candidates.
+//!
+//! Thumbnails are fetched from the Cover Art Archive and rendered inline if the terminal supports
+//! the kitty graphics protocol, the iTerm2 inline images protocol or sixel graphics.
+
+use crate::config::{Config, CoverArtDisplay, NetworkConfig};
+use crate::network::{self, NetworkError};
+use crate::release::ReleaseLike;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use crossterm::terminal;
+use image::RgbImage;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+
+/// Maximum size of a chunk of base64-encoded image data in the kitty graphics protocol.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Number of levels per color channel of the sixel palette (i.e., a 6x6x6 color cube).
+const SIXEL_COLOR_LEVELS: u8 = 6;
+
+/// Size of a terminal cell in pixels that is assumed if the terminal does not report it.
+const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+
+/// Thumbnails that have already been fetched (or failed to fetch), by release ID.
+static THUMBNAILS: Mutex<BTreeMap<String, Option<Vec<u8>>>> = Mutex::new(BTreeMap::new());
+
+/// Terminal protocol for displaying images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageProtocol {
+    /// Kitty graphics protocol.
+    Kitty,
+    /// iTerm2 inline images protocol.
+    Iterm,
+    /// Sixel graphics.
+    Sixel,
+}
+
+/// Detect the image protocol that the terminal supports from the environment variables.
+///
+/// Terminal multiplexers are not supported, because they do not pass the escape sequences through
+/// to the terminal.
+fn detect_protocol(variable: impl Fn(&str) -> Option<String>) -> Option<ImageProtocol> {
+    if variable("TMUX").is_some() || variable("STY").is_some() {
+        return None;
+    }
+
+    let term = variable("TERM").unwrap_or_default();
+    let term_program = variable("TERM_PROGRAM").unwrap_or_default();
+    if term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || term_program == "ghostty"
+        || variable("KITTY_WINDOW_ID").is_some()
+    {
+        Some(ImageProtocol::Kitty)
+    } else if term_program == "iTerm.app"
+        || term_program == "WezTerm"
+        || variable("LC_TERMINAL").as_deref() == Some("iTerm2")
+    {
+        Some(ImageProtocol::Iterm)
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+        Some(ImageProtocol::Sixel)
+    } else {
+        None
+    }
+}
+
+/// Returns the image protocol that is used for the cover art (if any).
+fn image_protocol(config: &Config) -> Option<ImageProtocol> {
+    match config.user_interface.candidate_details.cover_art {
+        CoverArtDisplay::Off | CoverArtDisplay::Link => None,
+        CoverArtDisplay::Auto => io::stdout()
+            .is_terminal()
+            .then(|| detect_protocol(|name| env::var(name).ok()))
+            .flatten(),
+        CoverArtDisplay::Kitty => Some(ImageProtocol::Kitty),
+        CoverArtDisplay::Iterm => Some(ImageProtocol::Iterm),
+        CoverArtDisplay::Sixel => Some(ImageProtocol::Sixel),
+    }
+}
+
+/// URL of the page that shows the cover art of the release.
+pub fn cover_art_url(release: &impl ReleaseLike) -> Option<String> {
+    release
+        .musicbrainz_release_url()
+        .map(|url| format!("{url}/cover-art"))
+}
+
+/// Fetch the front cover thumbnail of the release from the Cover Art Archive.
+async fn fetch_thumbnail(
+    network: &NetworkConfig,
+    release_id: &str,
+) -> Result<Vec<u8>, NetworkError> {
+    let url = format!("https://coverartarchive.org/release/{release_id}/front-250");
+    let bytes = network::http_client(network)?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes.to_vec())
+}
+
+/// Get the front cover thumbnail of the release (from the cache, if it was fetched before).
+///
+/// Blocks until the thumbnail has been downloaded, so this must be called from a multi-threaded
+/// runtime.
+fn thumbnail(network: &NetworkConfig, release_id: &str) -> Option<Vec<u8>> {
+    if let Some(thumbnail) = THUMBNAILS.lock().unwrap().get(release_id) {
+        return thumbnail.clone();
+    }
+
+    let handle = tokio::runtime::Handle::try_current().ok()?;
+    let thumbnail =
+        tokio::task::block_in_place(|| handle.block_on(fetch_thumbnail(network, release_id)))
+            .inspect_err(|err| log::info!("Failed to fetch cover art of {release_id}: {err}"))
+            .ok();
+    let _unused = THUMBNAILS
+        .lock()
+        .unwrap()
+        .insert(release_id.to_string(), thumbnail.clone());
+    thumbnail
+}
+
+/// Write the image using the kitty graphics protocol, scaled to the given number of columns and
+/// rows (if any).
+fn write_kitty(
+    out: &mut impl Write,
+    image: &RgbImage,
+    cells: Option<(u16, u16)>,
+) -> io::Result<()> {
+    let data = BASE64_STANDARD.encode(image.as_raw());
+    let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect::<Vec<_>>();
+    let size = cells.map_or_else(String::new, |(columns, rows)| {
+        format!(",c={columns},r={rows}")
+    });
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=24,q=2,s={},v={}{size},m={more};",
+                image.width(),
+                image.height()
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={more};")?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    writeln!(out)
+}
+
+/// Write the image data (in any format supported by the terminal) using the iTerm2 inline images
+/// protocol, scaled to the given number of columns and rows (if any).
+fn write_iterm(out: &mut impl Write, data: &[u8], cells: Option<(u16, u16)>) -> io::Result<()> {
+    let dimensions = cells.map_or_else(String::new, |(columns, rows)| {
+        format!(";width={columns};height={rows}")
+    });
+    writeln!(
+        out,
+        "\x1b]1337;File=inline=1;size={size}{dimensions}:{data}\x07",
+        size = data.len(),
+        data = BASE64_STANDARD.encode(data)
+    )
+}
+
+/// Scale the image down so that it fits into the given number of columns and rows.
+///
+/// Sixel graphics cannot be scaled by the terminal, so the cell size in pixels is taken from the
+/// terminal (if it reports it).
+fn fit_to_cells(image: RgbImage, (columns, rows): (u16, u16)) -> RgbImage {
+    let (cell_width, cell_height) = terminal::window_size()
+        .ok()
+        .filter(|size| size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0)
+        .map_or(DEFAULT_CELL_SIZE, |size| {
+            (
+                u32::from(size.width / size.columns),
+                u32::from(size.height / size.rows),
+            )
+        });
+    let max_width = u32::from(columns) * cell_width;
+    let max_height = u32::from(rows) * cell_height;
+    if image.width() <= max_width && image.height() <= max_height {
+        return image;
+    }
+    image::DynamicImage::ImageRgb8(image)
+        .resize(max_width, max_height, image::imageops::FilterType::Triangle)
+        .to_rgb8()
+}
+
+/// Map a color to the index of the nearest color of the sixel palette.
+fn sixel_color_index(pixel: [u8; 3]) -> usize {
+    let max_level = u16::from(SIXEL_COLOR_LEVELS - 1);
+    pixel.into_iter().fold(0, |index, value| {
+        let level = (u16::from(value) * max_level + 127) / 255;
+        index * usize::from(SIXEL_COLOR_LEVELS) + usize::from(level)
+    })
+}
+
+/// Encode the image as sixel graphics with a fixed palette.
+fn encode_sixel(image: &RgbImage) -> String {
+    let levels = usize::from(SIXEL_COLOR_LEVELS);
+    let max_level = levels - 1;
+    let (width, height) = (image.width(), image.height());
+    let mut sixel = format!("\x1bPq\"1;1;{width};{height}");
+    for index in 0..levels.pow(3) {
+        let [red, green, blue] = [
+            index / levels / levels,
+            index / levels % levels,
+            index % levels,
+        ]
+        .map(|level| level * 100 / max_level);
+        let _ = write!(sixel, "#{index};2;{red};{green};{blue}");
+    }
+
+    for band_top in (0..height).step_by(6) {
+        let band_height = (height - band_top).min(6);
+        // The sixel bits of each column, for each color that is used in this band.
+        let mut colors: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for x in 0..width {
+            for row in 0..band_height {
+                let pixel = image.get_pixel(x, band_top + row).0;
+                let columns = colors
+                    .entry(sixel_color_index(pixel))
+                    .or_insert_with(|| vec![0; width as usize]);
+                columns[x as usize] |= 1 << row;
+            }
+        }
+
+        for (position, (index, columns)) in colors.into_iter().enumerate() {
+            if position > 0 {
+                sixel.push('$');
+            }
+            let _ = write!(sixel, "#{index}");
+            for run in columns.chunk_by(|a, b| a == b) {
+                let character = char::from(0x3F + run[0]);
+                if run.len() > 3 {
+                    let _ = write!(sixel, "!{}{character}", run.len());
+                } else {
+                    sixel.extend(std::iter::repeat_n(character, run.len()));
+                }
+            }
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+/// Returns the image protocol and the front cover thumbnail of the release if the thumbnail can
+/// be displayed.
+fn displayable_thumbnail(
+    config: &Config,
+    release: &impl ReleaseLike,
+) -> Option<(ImageProtocol, Vec<u8>)> {
+    let protocol = image_protocol(config)?;
+    let data = release
+        .musicbrainz_release_id()
+        .and_then(|release_id| thumbnail(&config.network, &release_id))?;
+    Some((protocol, data))
+}
+
+/// Returns `true` if the front cover thumbnail of the release can be displayed in the terminal.
+///
+/// Otherwise, a link to the cover art is shown instead (unless the cover art is disabled).
+pub fn can_display_cover_art(config: &Config, release: &impl ReleaseLike) -> bool {
+    displayable_thumbnail(config, release).is_some()
+}
+
+/// Write the front cover thumbnail of the release at the cursor position, scaled to the given
+/// number of columns and rows (if any).
+///
+/// Nothing is written if the thumbnail cannot be displayed, e.g., because the release does not
+/// have cover art.
+pub fn write_cover_art(
+    out: &mut impl Write,
+    config: &Config,
+    release: &impl ReleaseLike,
+    cells: Option<(u16, u16)>,
+) -> io::Result<()> {
+    let Some((protocol, data)) = displayable_thumbnail(config, release) else {
+        return Ok(());
+    };
+
+    match protocol {
+        ImageProtocol::Iterm => write_iterm(out, &data, cells),
+        ImageProtocol::Kitty | ImageProtocol::Sixel => match image::load_from_memory(&data) {
+            Ok(image) if protocol == ImageProtocol::Kitty => {
+                write_kitty(out, &image.to_rgb8(), cells)
+            }
+            Ok(image) => {
+                let image = image.to_rgb8();
+                let image = match cells {
+                    Some(cells) => fit_to_cells(image, cells),
+                    None => image,
+                };
+                writeln!(out, "{}", encode_sixel(&image))
+            }
+            Err(err) => {
+                log::warn!("Failed to decode cover art: {err}");
+                Ok(())
+            }
+        },
+    }
+}
+
+/// Remove all images that were displayed using the kitty graphics protocol.
+///
+/// Images of the other protocols are part of the terminal cells and are removed when the screen
+/// is cleared.
+pub fn clear_cover_art(out: &mut impl Write, config: &Config) -> io::Result<()> {
+    if image_protocol(config) == Some(ImageProtocol::Kitty) {
+        write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Print the front cover thumbnail of the release if the terminal supports images.
+///
+/// Nothing is printed if the thumbnail is not available, e.g., because the release does not have
+/// cover art.
+pub fn print_cover_art(config: &Config, release: &impl ReleaseLike) {
+    let mut stdout = io::stdout().lock();
+    let result = write_cover_art(&mut stdout, config, release, None);
+    if let Err(err) = result.and_then(|()| stdout.flush()) {
+        log::warn!("Failed to display cover art: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn test_detect_protocol() {
+        let detect = |variables: &[(&str, &str)]| {
+            detect_protocol(|name| {
+                variables
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            })
+        };
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty")]),
+            Some(ImageProtocol::Kitty)
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")]),
+            Some(ImageProtocol::Iterm)
+        );
+        assert_eq!(detect(&[("TERM", "foot")]), Some(ImageProtocol::Sixel));
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), None);
+        assert_eq!(
+            detect(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_write_kitty() {
+        let image = RgbImage::from_pixel(1, 1, Rgb([255, 0, 0]));
+        let mut out = Vec::new();
+        write_kitty(&mut out, &image, Some((24, 12))).unwrap();
+        assert_eq!(
+            out,
+            b"\x1b_Ga=T,f=24,q=2,s=1,v=1,c=24,r=12,m=0;/wAA\x1b\\\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_sixel() {
+        let image = RgbImage::from_fn(5, 7, |x, _| {
+            if x < 4 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        let sixel = encode_sixel(&image);
+        assert!(sixel.starts_with("\x1bPq\"1;1;5;7#0;2;0;0;0"));
+        assert!(sixel.ends_with("\x1b\\"));
+        // Red is color 180 and white is color 215 of the palette.
+        assert!(sixel.contains("#180;2;100;0;0"));
+        assert!(sixel.contains("#180!4~?$#215!4?~-#180!4@?$#215!4?@-"));
+    }
+}
@@ -8,36 +8,49 @@
 
 //! Show candidate details and select next action.
 
+use super::cover_art;
+use super::tracklist;
 use super::util::{self, LayoutItem, StyledContentList};
-use crate::config::{CandidateDetails, Config, UnmatchedTrackStyleConfig};
+use crate::config::{CandidateDetails, Config, CoverArtDisplay, UnmatchedTrackStyleConfig};
 use crate::distance::{Distance, UnmatchedTracksSource};
 use crate::media::MediaLike;
 use crate::pathformat::PathFormatterValues;
 use crate::release::ReleaseLike;
 use crate::release_candidate::ReleaseCandidate;
+use crate::tag::TagKey;
 use crate::track::{AnalyzedTrackMetadata, InvolvedPerson, TrackLike};
-use crate::util::{FormattedDuration, KeyedBinaryHeap};
-use crossterm::{
-    style::{ContentStyle, Stylize},
-    terminal,
+use crate::util::{
+    normalize_barcode, normalize_catalog_number, FormattedDuration, KeyedBinaryHeap,
 };
+use crate::TaggedFileCollection;
+use crossterm::{style::ContentStyle, terminal};
 use inquire::{InquireError, Select};
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashSet};
 use std::fmt;
+use std::io::{self, Write};
 use std::iter::Peekable;
 
 /// The result of a `handle_candidate` all.
 pub enum HandleCandidateResult {
     /// Apply the current candidate.
     Apply,
+    /// Apply the current candidate and also apply it to all remaining albums of the import run.
+    ApplyToRemaining,
+    /// Apply the current candidate and automatically apply good matches for the remaining albums
+    /// of the import run.
+    ApplyAndAutoApplyRemaining,
     /// Show more details about the current candidate.
     ShowDetails,
     /// Hide details about the current candidate.
     HideDetails,
-    /// Print the tracklist (for copy & paste into MusicBrainz).
+    /// Show all tag changes for every file.
+    ShowTagDiff,
+    /// Print the tracklist of the files (for copy & paste into MusicBrainz).
     PrintTrackList,
+    /// Reject the current candidate, so that it is ranked last in future runs.
+    Reject,
     /// Skip the release.
     Skip,
     /// Back to candidate selection.
@@ -53,9 +66,17 @@ impl fmt::Display for StyledHandleCandidateResult<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text = match &self.1 {
             HandleCandidateResult::Apply => "Apply candidate",
+            HandleCandidateResult::ApplyToRemaining => {
+                "Apply candidate to the remaining albums in the same directory"
+            }
+            HandleCandidateResult::ApplyAndAutoApplyRemaining => {
+                "Apply candidate and auto-apply good matches for remaining albums"
+            }
             HandleCandidateResult::ShowDetails => "Show details",
             HandleCandidateResult::HideDetails => "Hide details",
+            HandleCandidateResult::ShowTagDiff => "Show full tag diff",
             HandleCandidateResult::PrintTrackList => "Print Tracklist",
+            HandleCandidateResult::Reject => "Reject candidate (rank it last in future runs)",
             HandleCandidateResult::Skip => "Skip album",
             HandleCandidateResult::BackToSelection => "Back to candidate selection",
             HandleCandidateResult::Quit => "Quit",
@@ -86,7 +107,9 @@ impl From<StyledHandleCandidateResult<'_>> for HandleCandidateResult {
 }
 
 /// Print additional metadata.
+#[expect(clippy::too_many_arguments)]
 fn print_extra_metadata(
+    out: &mut impl Write,
     lhs: Option<Cow<'_, str>>,
     rhs: Option<Cow<'_, str>>,
     missing_str: &str,
@@ -94,7 +117,7 @@ fn print_extra_metadata(
     candidate_details_config: &CandidateDetails,
     max_width: usize,
     max_height: usize,
-) {
+) -> io::Result<()> {
     let (lhs_value, rhs_value) = util::string_diff_opt(
         lhs,
         rhs,
@@ -109,50 +132,151 @@ fn print_extra_metadata(
             .into(),
     );
     util::print_column_layout(
+        out,
         lhs,
         rhs,
         &candidate_details_config.tracklist_extra_indent,
         &candidate_details_config.tracklist_extra_separator,
         max_width,
         max_height,
+    )
+}
+
+/// Describe the technical properties of the track (e.g., `flac, 44100 Hz, 16 bit, 2 ch`).
+fn technical_metadata_description(track: &impl TrackLike) -> Option<String> {
+    let parts = [
+        track.codec().map(Cow::into_owned),
+        track
+            .sample_rate()
+            .map(|sample_rate| format!("{sample_rate} Hz")),
+        track
+            .bit_depth()
+            .map(|bit_depth| format!("{bit_depth} bit")),
+        track
+            .channel_count()
+            .map(|channels| format!("{channels} ch")),
+        track.bitrate().map(|bitrate| format!("{bitrate} kbps")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Combine the configured release artist and title style with the distance style.
+fn release_artist_and_title_style(
+    candidate_details_config: &CandidateDetails,
+    distance_style: ContentStyle,
+) -> ContentStyle {
+    let mut style = ContentStyle::from(&candidate_details_config.release_artist_and_title_style);
+    style.foreground_color = distance_style.foreground_color.or(style.foreground_color);
+    style.background_color = distance_style.background_color.or(style.background_color);
+    style.attributes.extend(distance_style.attributes);
+    style
+}
+
+/// Calculate maximum width of the terminal.
+fn max_terminal_width(config: &Config) -> usize {
+    let max_width = terminal::size().map_or(
+        config.user_interface.default_terminal_width,
+        |(cols, _rows)| usize::from(cols),
     );
+    config
+        .user_interface
+        .max_terminal_width
+        .map_or(max_width, |max| max_width.min(max))
+}
+
+/// Display every tag value that would change for each file if the candidate is applied.
+fn show_tag_diff<C: ReleaseLike>(
+    out: &mut impl Write,
+    config: &Config,
+    base_release: &TaggedFileCollection,
+    candidate: &ReleaseCandidate<C>,
+) -> io::Result<()> {
+    let candidate_details_config = &config.user_interface.candidate_details;
+    let max_width = max_terminal_width(config);
+    let preview = base_release.preview_tags(candidate, config);
+
+    let mut has_changes = false;
+    for new_track in preview.tracks() {
+        let Some(old_track) = base_release
+            .tracks()
+            .find(|track| track.path == new_track.path)
+        else {
+            continue;
+        };
+
+        let changes = TagKey::ALL
+            .iter()
+            .filter_map(|key| {
+                let old_values = old_track.tag_values(key).collect::<Vec<_>>();
+                let new_values = new_track.tag_values(key).collect::<Vec<_>>();
+                (old_values != new_values).then_some((key, old_values, new_values))
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            continue;
+        }
+
+        has_changes = true;
+        writeln!(
+            out,
+            "{}",
+            candidate_details_config
+                .disc_title_style
+                .apply(new_track.path.display().to_string())
+        )?;
+        for (key, old_values, new_values) in changes {
+            print_extra_metadata(
+                out,
+                (!old_values.is_empty()).then(|| Cow::from(old_values.join("; "))),
+                (!new_values.is_empty()).then(|| Cow::from(new_values.join("; "))),
+                "<none>",
+                &format!(" ({key})"),
+                candidate_details_config,
+                max_width,
+                candidate_details_config.tracklist_extra_line_limit,
+            )?;
+        }
+    }
+
+    if !has_changes {
+        writeln!(out, "No tags would be changed.")?;
+    }
+
+    Ok(())
 }
 
 /// Display details about the candidate.
 pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
+    out: &mut impl Write,
     config: &Config,
     base_release: &B,
     candidate: &ReleaseCandidate<C>,
     show_details: bool,
-) {
+) -> io::Result<()> {
     let candidate_details_config = &config.user_interface.candidate_details;
 
-    let distance_color = util::distance_color(&candidate.distance(config));
+    let distance_style = util::distance_style(config, &candidate.distance(config));
 
     let release = candidate.release();
     let release_artist_and_title = util::format_release_artist_and_title(release);
 
-    println!(
+    writeln!(
+        out,
         "{release_artist_and_title}",
         release_artist_and_title =
-            ContentStyle::from(&candidate_details_config.release_artist_and_title_style)
-                .with(distance_color)
+            release_artist_and_title_style(candidate_details_config, distance_style)
                 .apply(release_artist_and_title),
-    );
-    println!(
+    )?;
+    writeln!(
+        out,
         "Similarity: {similarity}",
-        similarity = util::format_similarity(&candidate.distance(config))
-    );
+        similarity = util::format_similarity(config, &candidate.distance(config))
+    )?;
 
-    // Calculate maximum width of the terminal.
-    let max_width = terminal::size().map_or(
-        config.user_interface.default_terminal_width,
-        |(cols, _rows)| usize::from(cols),
-    );
-    let max_width = config
-        .user_interface
-        .max_terminal_width
-        .map_or(max_width, |max| max_width.min(max));
+    let max_width = max_terminal_width(config);
 
     // Show release metadata
     let release_meta = [
@@ -180,18 +304,86 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
             text + " | " + item.as_ref()
         }
     });
-    println!(
+    writeln!(
+        out,
         "{}",
         candidate_details_config
             .release_meta_style
             .apply(release_meta)
-    );
+    )?;
+
+    if show_details {
+        for (name, raw_value, normalized_value) in [
+            (
+                "Catalog number",
+                release.catalog_number(),
+                release
+                    .catalog_number()
+                    .map(|value| normalize_catalog_number(&value)),
+            ),
+            (
+                "Barcode",
+                release.barcode(),
+                release.barcode().map(|value| normalize_barcode(&value)),
+            ),
+        ] {
+            if let (Some(raw_value), Some(normalized_value)) = (raw_value, normalized_value) {
+                if raw_value != normalized_value.as_str() {
+                    writeln!(
+                        out,
+                        "{}",
+                        candidate_details_config.release_meta_style.apply(format!(
+                            "{name}: {raw_value} (written as {normalized_value})"
+                        ))
+                    )?;
+                }
+            }
+        }
+    }
+
+    let release_events = release.release_events();
+    if show_details && release_events.len() > 1 {
+        let release_events = release_events
+            .iter()
+            .map(|event| {
+                format!(
+                    "{date} ({country})",
+                    date = event.date.as_deref().unwrap_or("unknown date"),
+                    country = event.country.as_deref().unwrap_or("unknown country")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            out,
+            "{}",
+            candidate_details_config
+                .release_meta_style
+                .apply(format!("Release events: {release_events}"))
+        )?;
+    }
 
     if let Some(mb_url) = release.musicbrainz_release_url() {
-        println!(
+        writeln!(
+            out,
             "{}",
             candidate_details_config.release_meta_style.apply(mb_url)
-        );
+        )?;
+    }
+
+    // The link is only shown as a fallback if the thumbnail cannot be displayed.
+    if candidate_details_config.cover_art != CoverArtDisplay::Off
+        && !cover_art::can_display_cover_art(config, release)
+    {
+        if let Some(cover_art_url) = cover_art::cover_art_url(release) {
+            writeln!(
+                out,
+                "{}",
+                candidate_details_config
+                    .release_meta_style
+                    .apply(format!("Cover art: {cover_art_url}"))
+            )?;
+        }
     }
 
     // Show the tracklist of matched and unmatched tracks.
@@ -208,10 +400,11 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
             format!("{format} {index}", index = media_index + 1)
         };
 
-        println!(
+        writeln!(
+            out,
             "{}",
             candidate_details_config.disc_title_style.apply(disc_title)
-        );
+        )?;
 
         for rhs_track in media.media_tracks() {
             let Some((lhs_track_index, track_similarity)) = matched_track_map.get(&rhs_track_index)
@@ -321,19 +514,48 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                 ));
             }
 
+            // Show where the values of the file were read from.
+            if show_details {
+                let provenance = [
+                    lhs_track
+                        .value_provenance(&TagKey::TrackTitle)
+                        .map(|provenance| format!("title: {provenance}")),
+                    (!track_similarity.track_length.is_equal())
+                        .then(|| lhs_track.track_length_provenance())
+                        .flatten()
+                        .map(|provenance| format!("length: {provenance}")),
+                    (!track_similarity.track_artist.is_equal())
+                        .then(|| lhs_track.value_provenance(&TagKey::Artist))
+                        .flatten()
+                        .map(|provenance| format!("artist: {provenance}")),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+                if !provenance.is_empty() {
+                    lhs.suffix = StyledContentList::from(
+                        candidate_details_config
+                            .value_provenance_style
+                            .apply(Cow::from(format!(" [{}]", provenance.join(", ")))),
+                    );
+                }
+            }
+
             // Finally, print the track title/number/length layout item.
             util::print_column_layout(
+                out,
                 lhs,
                 rhs,
                 &candidate_details_config.tracklist_indent,
                 &candidate_details_config.tracklist_separator,
                 max_width,
                 candidate_details_config.tracklist_title_line_limit,
-            );
+            )?;
 
             // Print the track artist (if different)
             if !track_similarity.track_artist.is_equal() {
                 print_extra_metadata(
+                    out,
                     lhs_track.track_artist(),
                     rhs_track.track_artist(),
                     "<unknown artist>",
@@ -341,7 +563,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                     candidate_details_config,
                     max_width,
                     candidate_details_config.tracklist_artist_line_limit,
-                );
+                )?;
             }
 
             if show_details {
@@ -364,6 +586,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
 
                         let unknown_string = format!("<unknown performer[{involvement}]>");
                         print_extra_metadata(
+                            out,
                             lhs_involvee,
                             rhs_involvee,
                             unknown_string.as_str(),
@@ -371,13 +594,14 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
                 // Print the MusicBrain Recording ID (if different)
                 if !track_similarity.musicbrainz_recording_id.is_equal() {
                     print_extra_metadata(
+                        out,
                         lhs_track.musicbrainz_recording_id(),
                         rhs_track.musicbrainz_recording_id(),
                         "<unknown id>",
@@ -385,7 +609,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                         candidate_details_config,
                         max_width,
                         candidate_details_config.tracklist_extra_line_limit,
-                    );
+                    )?;
                 }
 
                 // Print the AcoustID Fingerprint (if available/different)
@@ -395,6 +619,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                         .is_none_or(|f| f != fingerprint)
                     {
                         print_extra_metadata(
+                            out,
                             lhs_track.acoustid_fingerprint(),
                             Some(fingerprint),
                             "<unknown fingerprint>",
@@ -402,7 +627,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -410,6 +635,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                 if let Some(gain) = lhs_track.analyzed_metadata().replay_gain_track_gain() {
                     if lhs_track.replay_gain_track_gain().is_none_or(|g| g != gain) {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_gain(),
                             Some(gain),
                             "<unknown gain>",
@@ -417,7 +643,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -425,6 +651,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                 if let Some(peak) = lhs_track.analyzed_metadata().replay_gain_track_peak() {
                     if lhs_track.replay_gain_track_peak().is_none_or(|p| p != peak) {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_peak(),
                             Some(peak),
                             "<unknown peak>",
@@ -432,7 +659,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -443,6 +670,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                         .is_none_or(|l| l != range)
                     {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_range(),
                             Some(range),
                             "<unknown range>",
@@ -450,7 +678,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -458,6 +686,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                 if let Some(gain) = base_release.replay_gain_album_gain_analyzed() {
                     if lhs_track.replay_gain_album_gain().is_none_or(|g| g != gain) {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_gain(),
                             Some(gain),
                             "<unknown gain>",
@@ -465,7 +694,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -473,6 +702,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                 if let Some(peak) = base_release.replay_gain_album_peak_analyzed() {
                     if lhs_track.replay_gain_album_peak().is_none_or(|p| p != peak) {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_peak(),
                             Some(peak),
                             "<unknown peak>",
@@ -480,7 +710,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
 
@@ -491,6 +721,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                         .is_none_or(|l| l != range)
                     {
                         print_extra_metadata(
+                            out,
                             lhs_track.replay_gain_track_range(),
                             Some(range),
                             "<unknown range>",
@@ -498,9 +729,23 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
+                // Print the technical properties of the file (if available)
+                if let Some(description) = technical_metadata_description(*lhs_track) {
+                    print_extra_metadata(
+                        out,
+                        Some(Cow::from(description.as_str())),
+                        Some(Cow::from(description.as_str())),
+                        "<unknown format>",
+                        " (format)",
+                        candidate_details_config,
+                        max_width,
+                        candidate_details_config.tracklist_extra_line_limit,
+                    )?;
+                }
+
                 if let Some(path) = lhs_track.track_path() {
                     let old_path = path;
 
@@ -508,14 +753,17 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                         .with_release(base_release)
                         .with_release(release)
                         .with_media(media)
+                        .with_tags(*lhs_track)
                         .with_track(*lhs_track_index + 1, *lhs_track)
-                        .with_track(rhs_track_index + 1, rhs_track);
+                        .with_track(rhs_track_index + 1, rhs_track)
+                        .with_number_format(&config.tags.number_format);
                     let new_path = config
                         .paths
                         .format_path(&values, lhs_track.track_file_extension())
                         .ok();
                     if new_path.as_deref().is_none_or(|p| p != old_path) {
                         print_extra_metadata(
+                            out,
                             old_path.to_str().map(Cow::from),
                             new_path.as_deref().and_then(|x| x.to_str()).map(Cow::from),
                             "<unknown path>",
@@ -523,7 +771,7 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                             candidate_details_config,
                             max_width,
                             candidate_details_config.tracklist_extra_line_limit,
-                        );
+                        )?;
                     }
                 }
             }
@@ -546,18 +794,20 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                     unmatched_count = unmatched_track_indices.len(),
                     total_count = "??"
                 );
-                println!(
+                writeln!(
+                    out,
                     "{}",
                     candidate_details_config
                         .unmatched_tracks_residual
                         .headline_style
                         .apply(title)
-                );
+                )?;
                 print_unmatched_tracks(
+                    out,
                     base_release,
                     &unmatched_track_indices,
                     &candidate_details_config.unmatched_tracks_residual,
-                );
+                )?;
             }
             UnmatchedTracksSource::Right => {
                 let title = format!(
@@ -565,44 +815,79 @@ pub fn show_candidate<B: ReleaseLike, C: ReleaseLike>(
                     unmatched_count = unmatched_track_indices.len(),
                     total_count = rhs_track_index
                 );
-                println!(
+                writeln!(
+                    out,
                     "{}",
                     candidate_details_config
                         .unmatched_tracks_missing
                         .headline_style
                         .apply(title)
-                );
+                )?;
                 print_unmatched_tracks(
+                    out,
                     release,
                     &unmatched_track_indices,
                     &candidate_details_config.unmatched_tracks_missing,
-                );
+                )?;
             }
         }
     }
+
+    Ok(())
 }
 
 /// Prompt the user how to handle the candidate.
-pub fn handle_candidate<B: ReleaseLike, C: ReleaseLike>(
+///
+/// If `allow_bulk_actions` is `true`, the user can also choose to apply the candidate (or good
+/// matches) to the remaining albums of the import run.
+pub fn handle_candidate<C: ReleaseLike>(
     config: &Config,
-    base_release: &B,
+    base_release: &TaggedFileCollection,
     candidate: &ReleaseCandidate<C>,
+    allow_bulk_actions: bool,
 ) -> Result<HandleCandidateResult, InquireError> {
     let mut show_details = false;
+    let mut show_full_tag_diff = false;
+    let mut show_tracklist = false;
     loop {
-        show_candidate(config, base_release, candidate, show_details);
-        let options = vec![
-            HandleCandidateResult::Apply.into_styled(config),
+        let mut output = Vec::new();
+        show_candidate(&mut output, config, base_release, candidate, show_details)?;
+        if show_full_tag_diff {
+            writeln!(output)?;
+            show_tag_diff(&mut output, config, base_release, candidate)?;
+            show_full_tag_diff = false;
+        }
+        if show_tracklist {
+            writeln!(output)?;
+            tracklist::write_tracklist(&mut output, config, base_release)?;
+            show_tracklist = false;
+        }
+        util::print_paged(config, &output)?;
+        cover_art::print_cover_art(config, candidate.release());
+        let mut options = vec![HandleCandidateResult::Apply.into_styled(config)];
+        if allow_bulk_actions {
+            options.extend([
+                HandleCandidateResult::ApplyToRemaining.into_styled(config),
+                HandleCandidateResult::ApplyAndAutoApplyRemaining.into_styled(config),
+            ]);
+        }
+        options.extend([
             if show_details {
                 HandleCandidateResult::HideDetails.into_styled(config)
             } else {
                 HandleCandidateResult::ShowDetails.into_styled(config)
             },
+            HandleCandidateResult::ShowTagDiff.into_styled(config),
             HandleCandidateResult::PrintTrackList.into_styled(config),
+        ]);
+        if config.decisions.enabled && candidate.release().musicbrainz_release_id().is_some() {
+            options.push(HandleCandidateResult::Reject.into_styled(config));
+        }
+        options.extend([
             HandleCandidateResult::Skip.into_styled(config),
             HandleCandidateResult::BackToSelection.into_styled(config),
             HandleCandidateResult::Quit.into_styled(config),
-        ];
+        ]);
 
         break match Select::new("Select an option:", options).prompt() {
             Ok(StyledHandleCandidateResult(_, HandleCandidateResult::ShowDetails)) => {
@@ -613,6 +898,14 @@ pub fn handle_candidate<B: ReleaseLike, C: ReleaseLike>(
                 show_details = false;
                 continue;
             }
+            Ok(StyledHandleCandidateResult(_, HandleCandidateResult::ShowTagDiff)) => {
+                show_full_tag_diff = true;
+                continue;
+            }
+            Ok(StyledHandleCandidateResult(_, HandleCandidateResult::PrintTrackList)) => {
+                show_tracklist = true;
+                continue;
+            }
             Ok(option) => Ok(option.into()),
             Err(InquireError::OperationCanceled) => Ok(HandleCandidateResult::BackToSelection),
             Err(InquireError::OperationInterrupted) => Ok(HandleCandidateResult::Quit),
@@ -623,10 +916,11 @@ pub fn handle_candidate<B: ReleaseLike, C: ReleaseLike>(
 
 /// Print a list of unmatched tracks.
 fn print_unmatched_tracks(
+    out: &mut impl Write,
     release: &impl ReleaseLike,
     unmatched_track_indices: &HashSet<usize>,
     config: &UnmatchedTrackStyleConfig,
-) {
+) -> io::Result<()> {
     for (i, track) in release
         .release_tracks()
         .enumerate()
@@ -637,7 +931,8 @@ fn print_unmatched_tracks(
             .unwrap_or_else(|| format!("#{index}", index = i + 1).into());
         let track_title = track.track_title().unwrap_or_else(|| "".into());
 
-        println!(
+        writeln!(
+            out,
             "{prefix}{track_number}{track_number_suffix}{track_title}",
             prefix = config.prefix_style.apply(&config.prefix),
             track_number = config.track_number_style.apply(&track_number),
@@ -646,8 +941,10 @@ fn print_unmatched_tracks(
                     .track_number_style
                     .apply(if track_number.is_empty() { "" } else { ". " }),
             track_title = config.track_title_style.apply(track_title),
-        );
+        )?;
     }
+
+    Ok(())
 }
 
 /// Iterator struct that is returned by the [`match_involved_persons`] function.
@@ -0,0 +1,93 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Summary that is shown at the end of an import run.
+
+use super::util;
+use crate::stats::{ImportOutcome, ImportStats};
+use crate::util::FormattedDuration;
+use crate::Config;
+use chrono::TimeDelta;
+use crossterm::style::Stylize;
+
+/// Format a byte count in human-readable form (e.g., `12.3 MiB`).
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    #[expect(clippy::cast_precision_loss)]
+    let mut value = bytes as f64;
+    let mut unit_index = 0;
+    while value >= 1024.0 && unit_index < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{bytes} {unit}", unit = UNITS[unit_index])
+    } else {
+        format!("{value:.1} {unit}", unit = UNITS[unit_index])
+    }
+}
+
+/// Print the per-directory results and the overall statistics of an import run.
+pub fn print_import_summary(config: &Config, stats: &ImportStats) {
+    if stats.directories().next().is_none() {
+        println!("Nothing to import.");
+        return;
+    }
+
+    println!();
+    println!("{}", "Import summary".bold());
+    for (path, outcome) in stats.directories() {
+        let theme = &config.user_interface.theme;
+        let outcome = match outcome {
+            ImportOutcome::Imported => theme.low_distance_style().apply("imported"),
+            ImportOutcome::PartiallyImported => {
+                theme.medium_distance_style().apply("partially imported")
+            }
+            ImportOutcome::Skipped => theme.medium_distance_style().apply("skipped"),
+            ImportOutcome::Failed => theme.high_distance_style().apply("failed"),
+        };
+        println!(" * {path}: {outcome}", path = path.display());
+    }
+
+    println!();
+    println!(
+        "Imported: {imported}, partially imported: {partial}, skipped: {skipped}, failed: {failed}",
+        imported = stats.count(ImportOutcome::Imported),
+        partial = stats.count(ImportOutcome::PartiallyImported),
+        skipped = stats.count(ImportOutcome::Skipped),
+        failed = stats.count(ImportOutcome::Failed),
+    );
+    if let Some(distance) = stats.average_distance() {
+        println!(
+            "Average similarity: {similarity}",
+            similarity = util::format_similarity(config, &distance)
+        );
+    }
+    println!(
+        "Files moved: {files_moved} ({bytes})",
+        files_moved = stats.files_moved(),
+        bytes = format_bytes(stats.bytes_processed()),
+    );
+    let elapsed = TimeDelta::from_std(stats.elapsed()).unwrap_or(TimeDelta::MAX);
+    println!("Wall time: {}", elapsed.formatted_duration());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536 * 1024), "1.5 MiB");
+    }
+}
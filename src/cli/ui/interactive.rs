@@ -0,0 +1,464 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Keyboard-driven interactive screen for selecting and handling release candidates.
+
+use super::cover_art;
+use super::handle_candidate::show_candidate;
+use super::select_candidate::{candidate_order, next_sort_order, sort_order_name};
+use crate::config::CandidateSortOrder;
+use crate::release::ReleaseLike;
+use crate::release_candidate::ReleaseCandidateCollection;
+use crate::Config;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::Stylize,
+    terminal::{self, ClearType},
+    QueueableCommand,
+};
+use std::borrow::Cow;
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// Help text that is shown in the footer of the screen.
+const HELP_TEXT: &str = "[a]pply  [r] apply to remaining  [A] apply & auto-apply good matches  \
+    [d]etails  [o] sort  [g]roup  [s]kip  [m]enu  [q]uit  [1-9] candidate  [←/→] prev/next  \
+    [↑/↓] scroll";
+
+/// Number of columns of the cover art thumbnail at the right side of the screen.
+const COVER_ART_COLUMNS: u16 = 24;
+
+/// Number of rows of the cover art thumbnail at the right side of the screen.
+const COVER_ART_ROWS: u16 = 12;
+
+/// The result of the interactive screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractiveResult {
+    /// Apply the candidate with the given index.
+    Apply(usize),
+    /// Apply the candidate with the given index, and also to the remaining albums in the same
+    /// directory.
+    ApplyToRemaining(usize),
+    /// Apply the candidate with the given index, and automatically apply good matches for the
+    /// remaining albums.
+    ApplyAndAutoApplyRemaining(usize),
+    /// Skip the release.
+    Skip,
+    /// Quit the import.
+    Quit,
+    /// Fall back to the menu-based user interface.
+    ShowMenu,
+}
+
+/// Guard that switches the terminal to the alternate screen in raw mode and restores it when
+/// dropped.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Enter the alternate screen and enable raw mode.
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        let _ = stdout
+            .queue(terminal::EnterAlternateScreen)?
+            .queue(cursor::Hide)?;
+        stdout.flush()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = stdout
+            .queue(cursor::Show)
+            .and_then(|stdout| stdout.queue(terminal::LeaveAlternateScreen))
+            .and_then(|stdout| stdout.flush());
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// State of the interactive screen.
+#[derive(Debug)]
+struct ScreenState {
+    /// Position of the currently displayed candidate in the candidate order.
+    candidate_index: usize,
+    /// Number of candidates.
+    candidate_count: usize,
+    /// Indices of the candidates in the order in which they are displayed.
+    candidate_order: Vec<usize>,
+    /// Order of the candidates.
+    sort_order: CandidateSortOrder,
+    /// List candidates from the same release group next to each other.
+    group_by_release_group: bool,
+    /// Whether additional details are shown.
+    show_details: bool,
+    /// Index of the first visible line.
+    scroll_offset: usize,
+}
+
+impl ScreenState {
+    /// Select a different candidate and reset the scroll position.
+    fn select_candidate(&mut self, index: usize) {
+        if index < self.candidate_count {
+            self.candidate_index = index;
+            self.scroll_offset = 0;
+        }
+    }
+
+    /// Index of the currently displayed candidate in the candidate collection.
+    fn selected_candidate(&self) -> usize {
+        self.candidate_order
+            .get(self.candidate_index)
+            .copied()
+            .unwrap_or(self.candidate_index)
+    }
+
+    /// Replace the candidate order, keeping the currently displayed candidate selected.
+    fn set_candidate_order(&mut self, candidate_order: Vec<usize>) {
+        let selected_candidate = self.selected_candidate();
+        self.candidate_index = candidate_order
+            .iter()
+            .position(|index| *index == selected_candidate)
+            .unwrap_or(0);
+        self.candidate_order = candidate_order;
+    }
+
+    /// Handle a key press. Returns a result if the screen should be closed.
+    fn handle_key(&mut self, key: KeyEvent, page_height: usize) -> Option<InteractiveResult> {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(InteractiveResult::Quit);
+            }
+            KeyCode::Char('a') | KeyCode::Enter => {
+                return Some(InteractiveResult::Apply(self.selected_candidate()));
+            }
+            KeyCode::Char('r') => {
+                return Some(InteractiveResult::ApplyToRemaining(
+                    self.selected_candidate(),
+                ));
+            }
+            KeyCode::Char('A') => {
+                return Some(InteractiveResult::ApplyAndAutoApplyRemaining(
+                    self.selected_candidate(),
+                ));
+            }
+            KeyCode::Char('o') => self.sort_order = next_sort_order(self.sort_order),
+            KeyCode::Char('g') => self.group_by_release_group = !self.group_by_release_group,
+            KeyCode::Char('s') => return Some(InteractiveResult::Skip),
+            KeyCode::Char('q') => return Some(InteractiveResult::Quit),
+            KeyCode::Char('m') | KeyCode::Esc => return Some(InteractiveResult::ShowMenu),
+            KeyCode::Char('d') => {
+                self.show_details = !self.show_details;
+                self.scroll_offset = 0;
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c.to_digit(10).and_then(|digit| usize::try_from(digit).ok());
+                if let Some(index) = index {
+                    self.select_candidate(index - 1);
+                }
+            }
+            KeyCode::Left | KeyCode::Char('p') => {
+                self.select_candidate(self.candidate_index.saturating_sub(1));
+            }
+            KeyCode::Right | KeyCode::Char('n') => {
+                self.select_candidate(self.candidate_index + 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_offset += 1,
+            KeyCode::PageUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(page_height);
+            }
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_offset += page_height,
+            KeyCode::Home => self.scroll_offset = 0,
+            _ => (),
+        }
+
+        None
+    }
+}
+
+/// Truncate a line that may contain ANSI escape sequences to the given display width.
+///
+/// Escape sequences do not count towards the width. If the line is truncated, the style is reset
+/// at the end so that it does not leak into the following lines.
+fn truncate_line(line: &str, max_width: usize) -> Cow<'_, str> {
+    let mut width = 0;
+    let mut has_escape_sequences = false;
+    let mut chars = line.char_indices();
+    while let Some((index, c)) = chars.next() {
+        if c == '\x1b' {
+            // Skip the escape sequence (`ESC [ <parameters> <final byte>`).
+            has_escape_sequences = true;
+            if chars.next().is_some_and(|(_, c)| c == '[') {
+                for (_, c) in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+
+        width += c.width().unwrap_or(0);
+        if width > max_width {
+            let mut truncated = line[..index].to_string();
+            if has_escape_sequences {
+                truncated.push_str("\x1b[0m");
+            }
+            return Cow::Owned(truncated);
+        }
+    }
+
+    Cow::Borrowed(line)
+}
+
+/// Render the current state of the screen.
+///
+/// The lines are truncated to the text width, so that they do not overlap with the cover art.
+fn render(
+    stdout: &mut impl Write,
+    state: &mut ScreenState,
+    lines: &[&str],
+    (width, height): (u16, u16),
+    text_width: u16,
+) -> io::Result<()> {
+    let page_height = usize::from(height.saturating_sub(2));
+    state.scroll_offset = state
+        .scroll_offset
+        .min(lines.len().saturating_sub(page_height));
+
+    let _ = stdout
+        .queue(terminal::Clear(ClearType::All))?
+        .queue(cursor::MoveTo(0, 0))?;
+    let header = format!(
+        "Candidate {index}/{count}, sorted by {sort_order}{grouped}{details}",
+        index = state.candidate_index + 1,
+        count = state.candidate_count,
+        sort_order = sort_order_name(state.sort_order),
+        grouped = if state.group_by_release_group {
+            ", grouped by release group"
+        } else {
+            ""
+        },
+        details = if state.show_details { " (details)" } else { "" },
+    );
+    write!(
+        stdout,
+        "{}",
+        truncate_line(&header, usize::from(width))
+            .into_owned()
+            .bold()
+    )?;
+
+    for (row, line) in (1..).zip(lines.iter().skip(state.scroll_offset).take(page_height)) {
+        let _ = stdout.queue(cursor::MoveTo(0, row))?;
+        write!(stdout, "{}", truncate_line(line, usize::from(text_width)))?;
+    }
+
+    let _ = stdout.queue(cursor::MoveTo(0, height.saturating_sub(1)))?;
+    let help_text = HELP_TEXT
+        .chars()
+        .take(usize::from(width))
+        .collect::<String>();
+    write!(stdout, "{}", help_text.reverse())?;
+    stdout.flush()
+}
+
+/// Show the interactive candidate screen until the user selects an action.
+///
+/// # Errors
+///
+/// Returns an error if the terminal cannot be controlled.
+pub fn interactive_select<B: ReleaseLike, C: ReleaseLike>(
+    config: &Config,
+    base_release: &B,
+    candidates: &ReleaseCandidateCollection<C>,
+) -> io::Result<InteractiveResult> {
+    if candidates.len() == 0 {
+        return Ok(InteractiveResult::ShowMenu);
+    }
+
+    let _guard = TerminalGuard::enter()?;
+    let mut stdout = io::stdout();
+    let sort_order = config.user_interface.candidate_sort_order;
+    let group_by_release_group = config.user_interface.select_release_group_first;
+    let mut state = ScreenState {
+        candidate_index: 0,
+        candidate_count: candidates.len(),
+        candidate_order: candidate_order(candidates, sort_order, group_by_release_group),
+        sort_order,
+        group_by_release_group,
+        show_details: false,
+        scroll_offset: 0,
+    };
+
+    loop {
+        state.set_candidate_order(candidate_order(
+            candidates,
+            state.sort_order,
+            state.group_by_release_group,
+        ));
+        let Some(candidate) = candidates.iter().nth(state.selected_candidate()) else {
+            return Ok(InteractiveResult::ShowMenu);
+        };
+
+        let mut buffer = Vec::new();
+        show_candidate(
+            &mut buffer,
+            config,
+            base_release,
+            candidate,
+            state.show_details,
+        )?;
+        let content = String::from_utf8_lossy(&buffer);
+        let lines = content.lines().collect::<Vec<_>>();
+
+        let size = terminal::size()?;
+        let (width, height) = size;
+        let cover_art_cells = (width >= COVER_ART_COLUMNS * 3
+            && height > 2
+            && cover_art::can_display_cover_art(config, candidate.release()))
+        .then(|| {
+            (
+                COVER_ART_COLUMNS,
+                COVER_ART_ROWS.min(height.saturating_sub(2)),
+            )
+        });
+        let text_width = cover_art_cells.map_or(width, |(columns, _)| width - columns - 1);
+        cover_art::clear_cover_art(&mut stdout, config)?;
+        render(&mut stdout, &mut state, &lines, size, text_width)?;
+        if let Some(cells) = cover_art_cells {
+            let _ = stdout.queue(cursor::MoveTo(width - cells.0, 1))?;
+            cover_art::write_cover_art(&mut stdout, config, candidate.release(), Some(cells))?;
+            stdout.flush()?;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let page_height = usize::from(size.1.saturating_sub(2)).max(1);
+            if let Some(result) = state.handle_key(key, page_height) {
+                return Ok(result);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> ScreenState {
+        ScreenState {
+            candidate_index: 0,
+            candidate_count: 3,
+            candidate_order: vec![0, 1, 2],
+            sort_order: CandidateSortOrder::Similarity,
+            group_by_release_group: false,
+            show_details: false,
+            scroll_offset: 0,
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_handle_key_actions() {
+        let mut state = state();
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('a')), 10),
+            Some(InteractiveResult::Apply(0))
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('r')), 10),
+            Some(InteractiveResult::ApplyToRemaining(0))
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('A')), 10),
+            Some(InteractiveResult::ApplyAndAutoApplyRemaining(0))
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('s')), 10),
+            Some(InteractiveResult::Skip)
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('q')), 10),
+            Some(InteractiveResult::Quit)
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('m')), 10),
+            Some(InteractiveResult::ShowMenu)
+        );
+        assert_eq!(state.handle_key(key(KeyCode::Char('d')), 10), None);
+        assert!(state.show_details);
+    }
+
+    #[test]
+    fn test_handle_key_view_options() {
+        let mut state = state();
+        assert_eq!(state.handle_key(key(KeyCode::Char('o')), 10), None);
+        assert_eq!(state.sort_order, CandidateSortOrder::Year);
+        assert_eq!(state.handle_key(key(KeyCode::Char('g')), 10), None);
+        assert!(state.group_by_release_group);
+
+        state.set_candidate_order(vec![2, 0, 1]);
+        assert_eq!(state.candidate_index, 1);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('a')), 10),
+            Some(InteractiveResult::Apply(0))
+        );
+        assert_eq!(state.handle_key(key(KeyCode::Char('1')), 10), None);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('a')), 10),
+            Some(InteractiveResult::Apply(2))
+        );
+    }
+
+    #[test]
+    fn test_truncate_line() {
+        assert_eq!(truncate_line("Track 1", 10), "Track 1");
+        assert_eq!(truncate_line("Track 1", 5), "Track");
+        assert_eq!(
+            truncate_line("\x1b[1mTrack\x1b[0m 1", 5),
+            "\x1b[1mTrack\x1b[0m\x1b[0m"
+        );
+        assert_eq!(
+            truncate_line("\x1b[1mTrack\x1b[0m", 5),
+            "\x1b[1mTrack\x1b[0m"
+        );
+        assert_eq!(truncate_line("日本語", 5), "日本");
+    }
+
+    #[test]
+    fn test_handle_key_navigation() {
+        let mut state = state();
+        assert_eq!(state.handle_key(key(KeyCode::Char('3')), 10), None);
+        assert_eq!(state.candidate_index, 2);
+        assert_eq!(state.handle_key(key(KeyCode::Char('9')), 10), None);
+        assert_eq!(state.candidate_index, 2);
+        assert_eq!(state.handle_key(key(KeyCode::Right), 10), None);
+        assert_eq!(state.candidate_index, 2);
+        assert_eq!(state.handle_key(key(KeyCode::Left), 10), None);
+        assert_eq!(state.candidate_index, 1);
+
+        assert_eq!(state.handle_key(key(KeyCode::PageDown), 10), None);
+        assert_eq!(state.scroll_offset, 10);
+        assert_eq!(state.handle_key(key(KeyCode::Up), 10), None);
+        assert_eq!(state.scroll_offset, 9);
+        assert_eq!(state.handle_key(key(KeyCode::Char('2')), 10), None);
+        assert_eq!(state.scroll_offset, 0);
+    }
+}
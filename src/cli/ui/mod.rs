@@ -8,9 +8,21 @@
 
 //! User Interface (UI) utilities.
 
+mod cover_art;
 mod handle_candidate;
+mod import_summary;
+mod interactive;
 mod select_candidate;
+mod select_release_group;
+mod tracklist;
 mod util;
 
 pub use handle_candidate::{handle_candidate, HandleCandidateResult};
-pub use select_candidate::{select_candidate, ReleaseCandidateSelectionResult};
+pub use import_summary::print_import_summary;
+pub use interactive::{interactive_select, InteractiveResult};
+pub use select_candidate::{
+    autoselected_candidate, select_candidate, ReleaseCandidateSelectionResult,
+};
+pub use select_release_group::{select_release_group, ReleaseGroupSelectionResult};
+pub use tracklist::{open_release_editor, print_tracklist};
+pub use util::colors_enabled;
@@ -9,7 +9,7 @@
 //! Candidate Selection.
 
 use super::util;
-use crate::config::Config;
+use crate::config::{CandidateSortOrder, Config};
 use crate::musicbrainz::MusicBrainzId;
 use crate::release::ReleaseLike;
 use crate::release_candidate::{ReleaseCandidate, ReleaseCandidateCollection};
@@ -31,6 +31,8 @@ pub enum ReleaseCandidateSelectionResult<'a, T: ReleaseLike> {
     Skipped,
     /// Print the track list.
     PrintTrackList,
+    /// Open the MusicBrainz release editor seeded with the local release.
+    AddToMusicBrainz,
     /// Save release information to file (for debugging).
     #[cfg(feature = "dev")]
     DumpReleaseInfo,
@@ -44,8 +46,14 @@ enum ReleaseCandidateSelectionOption<'a, T: ReleaseLike> {
     Candidate(&'a ReleaseCandidate<T>),
     /// Enter a customer MusicBrainz release ID.
     EnterMusicBrainzId,
+    /// Change the sort order of the candidates to the given order.
+    ChangeSortOrder(CandidateSortOrder),
+    /// Enable or disable grouping the candidates by release group.
+    ToggleGrouping(bool),
     /// Print the track list.
     PrintTrackList,
+    /// Open the MusicBrainz release editor seeded with the local release.
+    AddToMusicBrainz,
     /// DumpReleaseInfo release for debugging.
     #[cfg(feature = "dev")]
     DumpReleaseInfo,
@@ -61,7 +69,12 @@ impl<T: ReleaseLike> Clone for ReleaseCandidateSelectionOption<'_, T> {
         match &self {
             Self::Candidate(candidate) => Self::Candidate(candidate),
             Self::EnterMusicBrainzId => Self::EnterMusicBrainzId,
+            Self::ChangeSortOrder(sort_order) => Self::ChangeSortOrder(*sort_order),
+            Self::ToggleGrouping(group_by_release_group) => {
+                Self::ToggleGrouping(*group_by_release_group)
+            }
             Self::PrintTrackList => Self::PrintTrackList,
+            Self::AddToMusicBrainz => Self::AddToMusicBrainz,
             #[cfg(feature = "dev")]
             Self::DumpReleaseInfo => Self::DumpReleaseInfo,
             Self::SkipItem => Self::SkipItem,
@@ -83,113 +96,111 @@ impl<T: ReleaseLike> Clone for StyledReleaseCandidateSelectionOption<'_, T> {
     }
 }
 
+/// Write a single line describing the candidate, followed by the similarity and additional
+/// details.
+fn write_candidate<'a, T: ReleaseLike>(
+    f: &mut fmt::Formatter<'_>,
+    config: &Config,
+    candidate: &'a ReleaseCandidate<T>,
+    details: impl Iterator<Item = Cow<'a, str>>,
+) -> fmt::Result {
+    let candidate_details = &config.user_interface.candidate_details;
+    let release_artist_and_title = util::format_release_artist_and_title(candidate.release());
+    let similarity_percentage = candidate_details
+        .candidate_similarity_style
+        .apply(Cow::from(util::format_similarity(
+            config,
+            &candidate.distance(config),
+        )));
+
+    let details = details.map(|detail| {
+        candidate_details
+            .candidate_disambiguation_style
+            .apply(detail)
+    });
+
+    let problems = candidate.similarity().problems().map(|problem| {
+        candidate_details
+            .candidate_problem_style
+            .apply(Cow::Owned(problem.to_string()))
+    });
+
+    let similarity = std::iter::once(similarity_percentage)
+        .chain(details)
+        .chain(problems)
+        .join(
+            &candidate_details
+                .candidate_similarity_separator_style
+                .apply(", ")
+                .to_string(),
+        );
+    write!(
+        f,
+        "{release_artist_and_title}{similarity_prefix}{similarity}{similarity_suffix}",
+        similarity_prefix = candidate_details
+            .candidate_similarity_prefix_style
+            .apply(&candidate_details.candidate_similarity_prefix),
+        similarity_suffix = candidate_details
+            .candidate_similarity_suffix_style
+            .apply(&candidate_details.candidate_similarity_suffix),
+    )
+}
+
 impl<T: ReleaseLike> fmt::Display for StyledReleaseCandidateSelectionOption<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let ReleaseCandidateSelectionOption::Candidate(candidate) = &self.1 {
-            let release_artist_and_title =
-                util::format_release_artist_and_title(candidate.release());
-            let similarity_percentage = self
-                .0
+        let text = match &self.1 {
+            ReleaseCandidateSelectionOption::Candidate(candidate) => {
+                let disambiguation = [
+                    candidate
+                        .release()
+                        .release_media_format()
+                        .map(|media_format| {
+                            let media_count = candidate.release().media().count();
+                            if media_count > 1 {
+                                Cow::from(format!("{media_count}x{media_format}"))
+                            } else {
+                                media_format
+                            }
+                        }),
+                    candidate.release().release_year(),
+                    candidate.release().release_country(),
+                ]
+                .into_iter()
+                .flatten();
+                return write_candidate(f, self.0, candidate, disambiguation);
+            }
+            ReleaseCandidateSelectionOption::EnterMusicBrainzId => {
+                Cow::from("Enter MusicBrainz ID")
+            }
+            ReleaseCandidateSelectionOption::ChangeSortOrder(sort_order) => {
+                Cow::from(format!("Sort by {}", sort_order_name(*sort_order)))
+            }
+            ReleaseCandidateSelectionOption::ToggleGrouping(true) => {
+                Cow::from("Group by Release Group")
+            }
+            ReleaseCandidateSelectionOption::ToggleGrouping(false) => {
+                Cow::from("Show All Releases")
+            }
+            ReleaseCandidateSelectionOption::SkipItem => Cow::from("Skip Item"),
+            ReleaseCandidateSelectionOption::PrintTrackList => Cow::from("Print Tracklist"),
+            ReleaseCandidateSelectionOption::AddToMusicBrainz => {
+                Cow::from("Add Release to MusicBrainz")
+            }
+            #[cfg(feature = "dev")]
+            ReleaseCandidateSelectionOption::DumpReleaseInfo => {
+                Cow::from("Dump Releases for Debugging")
+            }
+            ReleaseCandidateSelectionOption::Quit => Cow::from("Quit"),
+        };
+        write!(
+            f,
+            "{}",
+            self.0
                 .user_interface
                 .candidate_details
-                .candidate_similarity_style
-                .apply(Cow::from(util::format_similarity(
-                    &candidate.distance(self.0),
-                )));
-
-            let disambiguation = [
-                candidate
-                    .release()
-                    .release_media_format()
-                    .map(|media_format| {
-                        let media_count = candidate.release().media().count();
-                        if media_count > 1 {
-                            Cow::from(format!("{media_count}x{media_format}"))
-                        } else {
-                            media_format
-                        }
-                    }),
-                candidate.release().release_year(),
-                candidate.release().release_country(),
-            ]
-            .into_iter()
-            .flatten()
-            .map(|disambig| {
-                self.0
-                    .user_interface
-                    .candidate_details
-                    .candidate_disambiguation_style
-                    .apply(disambig)
-            });
-
-            let problems = candidate.similarity().problems().map(|problem| {
-                self.0
-                    .user_interface
-                    .candidate_details
-                    .candidate_problem_style
-                    .apply(Cow::Owned(problem.to_string()))
-            });
-
-            let similarity = std::iter::once(similarity_percentage)
-                .chain(disambiguation)
-                .chain(problems)
-                .join(
-                    &self
-                        .0
-                        .user_interface
-                        .candidate_details
-                        .candidate_similarity_separator_style
-                        .apply(", ")
-                        .to_string(),
-                );
-            write!(
-                f,
-                "{release_artist_and_title}{similarity_prefix}{similarity}{similarity_suffix}",
-                similarity_prefix = self
-                    .0
-                    .user_interface
-                    .candidate_details
-                    .candidate_similarity_prefix_style
-                    .apply(
-                        &self
-                            .0
-                            .user_interface
-                            .candidate_details
-                            .candidate_similarity_prefix
-                    ),
-                similarity_suffix = self
-                    .0
-                    .user_interface
-                    .candidate_details
-                    .candidate_similarity_suffix_style
-                    .apply(
-                        &self
-                            .0
-                            .user_interface
-                            .candidate_details
-                            .candidate_similarity_suffix
-                    ),
-            )
-        } else {
-            let text = match &self.1 {
-                ReleaseCandidateSelectionOption::EnterMusicBrainzId => "Enter MusicBrainz ID",
-                ReleaseCandidateSelectionOption::SkipItem => "Skip Item",
-                ReleaseCandidateSelectionOption::PrintTrackList => "Print Tracklist",
-                #[cfg(feature = "dev")]
-                ReleaseCandidateSelectionOption::DumpReleaseInfo => "Dump Releases for Debugging",
-                ReleaseCandidateSelectionOption::Quit => "Quit",
-                ReleaseCandidateSelectionOption::Candidate(_) => unreachable!(),
-            };
-            write!(
-                f,
-                "{}",
-                self.0
-                    .user_interface
-                    .candidate_details
-                    .action_style
-                    .apply(text)
-            )
-        }
+                .action_style
+                .apply(text)
+        )
     }
 }
 
@@ -208,65 +219,263 @@ impl<'a, T: ReleaseLike> From<StyledReleaseCandidateSelectionOption<'a, T>>
     }
 }
 
+/// How the candidates are presented, which can be changed by the user during the selection.
+#[derive(Debug, Clone, Copy)]
+struct ViewOptions {
+    /// Order of the candidates.
+    sort_order: CandidateSortOrder,
+    /// List candidates from the same release group next to each other.
+    group_by_release_group: bool,
+}
+
+/// Returns the name of the sort order that is displayed to the user.
+pub fn sort_order_name(sort_order: CandidateSortOrder) -> &'static str {
+    match sort_order {
+        CandidateSortOrder::Similarity => "Similarity",
+        CandidateSortOrder::Year => "Year",
+        CandidateSortOrder::Country => "Country",
+        CandidateSortOrder::Format => "Format",
+    }
+}
+
+/// Returns the sort order that the user can switch to from the given sort order.
+pub fn next_sort_order(sort_order: CandidateSortOrder) -> CandidateSortOrder {
+    match sort_order {
+        CandidateSortOrder::Similarity => CandidateSortOrder::Year,
+        CandidateSortOrder::Year => CandidateSortOrder::Country,
+        CandidateSortOrder::Country => CandidateSortOrder::Format,
+        CandidateSortOrder::Format => CandidateSortOrder::Similarity,
+    }
+}
+
+/// Sort the candidates in the given order.
+///
+/// Candidates with the same sort key stay ordered by similarity, and candidates without a value
+/// for the sort key are put last.
+fn sort_candidates<T: ReleaseLike>(
+    candidates: &ReleaseCandidateCollection<T>,
+    sort_order: CandidateSortOrder,
+) -> Vec<&ReleaseCandidate<T>> {
+    let mut sorted_candidates = candidates.iter().collect::<Vec<_>>();
+    sorted_candidates.sort_by_cached_key(|candidate| {
+        let release = candidate.release();
+        let value = match sort_order {
+            CandidateSortOrder::Similarity => None,
+            CandidateSortOrder::Year => release.release_year(),
+            CandidateSortOrder::Country => release.release_country(),
+            CandidateSortOrder::Format => release.release_media_format(),
+        }
+        .map(Cow::into_owned);
+        (
+            sort_order != CandidateSortOrder::Similarity && value.is_none(),
+            value,
+        )
+    });
+    sorted_candidates
+}
+
+/// Group the candidates by their MusicBrainz Release Group ID.
+///
+/// The order of the candidates is preserved, i.e., the groups are ordered by their first candidate.
+/// Candidates without a release group ID are put into a group of their own.
+fn group_candidates_by_release_group<'a, T: ReleaseLike>(
+    candidates: &[&'a ReleaseCandidate<T>],
+) -> Vec<Vec<&'a ReleaseCandidate<T>>> {
+    candidates
+        .iter()
+        .copied()
+        .fold(
+            Vec::<(Option<Cow<'_, str>>, Vec<&ReleaseCandidate<T>>)>::new(),
+            |mut groups, candidate| {
+                let release_group_id = candidate.release().musicbrainz_release_group_id();
+                if let Some((_, group_candidates)) = groups
+                    .iter_mut()
+                    .find(|(id, _)| id.is_some() && *id == release_group_id)
+                {
+                    group_candidates.push(candidate);
+                } else {
+                    groups.push((release_group_id, vec![candidate]));
+                }
+                groups
+            },
+        )
+        .into_iter()
+        .map(|(_, group_candidates)| group_candidates)
+        .collect()
+}
+
+/// Returns the candidates in the order in which they are presented to the user.
+///
+/// If `group_by_release_group` is set, candidates from the same release group are listed next to
+/// each other.
+fn ordered_candidates<T: ReleaseLike>(
+    candidates: &ReleaseCandidateCollection<T>,
+    sort_order: CandidateSortOrder,
+    group_by_release_group: bool,
+) -> Vec<&ReleaseCandidate<T>> {
+    let sorted_candidates = sort_candidates(candidates, sort_order);
+    if group_by_release_group {
+        group_candidates_by_release_group(&sorted_candidates)
+            .into_iter()
+            .flatten()
+            .collect()
+    } else {
+        sorted_candidates
+    }
+}
+
+/// Returns the indices of the candidates in the order in which they are presented to the user.
+///
+/// If `group_by_release_group` is set, candidates from the same release group are listed next to
+/// each other.
+pub fn candidate_order<T: ReleaseLike>(
+    candidates: &ReleaseCandidateCollection<T>,
+    sort_order: CandidateSortOrder,
+    group_by_release_group: bool,
+) -> Vec<usize> {
+    ordered_candidates(candidates, sort_order, group_by_release_group)
+        .into_iter()
+        .filter_map(|candidate| {
+            candidates
+                .iter()
+                .position(|other| std::ptr::eq(other, candidate))
+        })
+        .collect()
+}
+
+/// Returns the best candidate if it is close enough to be selected without asking the user.
+pub fn autoselected_candidate<'a, T: ReleaseLike>(
+    config: &Config,
+    candidates: &'a ReleaseCandidateCollection<T>,
+) -> Option<&'a ReleaseCandidate<T>> {
+    candidates
+        .iter()
+        .next()
+        .filter(|candidate| candidate.distance(config).as_f64() <= 0.05)
+}
+
 /// Present a selection of releases to the user, and loop until either a release was selected or
 /// the item is skipped. In the latter case, `None` is returned.
+///
+/// The user can change the sort order of the candidates and toggle the grouping by release group,
+/// which lists the candidates from the same release group next to each other.
 pub fn select_candidate<'a, T: ReleaseLike>(
     config: &'a Config,
     candidates: &'a ReleaseCandidateCollection<T>,
     allow_autoselection: bool,
 ) -> Result<ReleaseCandidateSelectionResult<'a, T>, InquireError> {
     if allow_autoselection {
-        if let Some(best_candidate) = candidates.iter().next() {
-            if best_candidate.distance(config).as_f64() <= 0.05 {
-                return Ok(ReleaseCandidateSelectionResult::Candidate(best_candidate));
+        if let Some(best_candidate) = autoselected_candidate(config, candidates) {
+            return Ok(ReleaseCandidateSelectionResult::Candidate(best_candidate));
+        }
+    }
+
+    let mut view_options = ViewOptions {
+        sort_order: config.user_interface.candidate_sort_order,
+        group_by_release_group: config.user_interface.select_release_group_first,
+    };
+    let prompt = match candidates.len() {
+        0 | 1 => "Select release candidate:".to_string(),
+        candidate_count => format!("Select one of {candidate_count} release candidates:"),
+    };
+    loop {
+        let options = ordered_candidates(
+            candidates,
+            view_options.sort_order,
+            view_options.group_by_release_group,
+        )
+        .into_iter()
+        .map(ReleaseCandidateSelectionOption::Candidate);
+        match select_option(config, &prompt, options, view_options)? {
+            SelectedOption::Result(result) => break Ok(result),
+            SelectedOption::ChangeSortOrder(sort_order) => view_options.sort_order = sort_order,
+            SelectedOption::ToggleGrouping(group_by_release_group) => {
+                view_options.group_by_release_group = group_by_release_group;
             }
         }
     }
+}
 
+/// The choice made by the user in [`select_option`].
+enum SelectedOption<'a, T: ReleaseLike> {
+    /// Change the sort order of the candidates.
+    ChangeSortOrder(CandidateSortOrder),
+    /// Enable or disable grouping the candidates by release group.
+    ToggleGrouping(bool),
+    /// Any other choice that is directly returned from [`select_candidate`].
+    Result(ReleaseCandidateSelectionResult<'a, T>),
+}
+
+/// Present the options followed by the additional actions to the user, and loop until a choice
+/// has been made.
+fn select_option<'a, T: ReleaseLike>(
+    config: &'a Config,
+    prompt: &str,
+    options: impl Iterator<Item = ReleaseCandidateSelectionOption<'a, T>>,
+    view_options: ViewOptions,
+) -> Result<SelectedOption<'a, T>, InquireError> {
     let additional_options = [
+        ReleaseCandidateSelectionOption::ChangeSortOrder(next_sort_order(view_options.sort_order)),
+        ReleaseCandidateSelectionOption::ToggleGrouping(!view_options.group_by_release_group),
         ReleaseCandidateSelectionOption::EnterMusicBrainzId,
         ReleaseCandidateSelectionOption::PrintTrackList,
+        ReleaseCandidateSelectionOption::AddToMusicBrainz,
         #[cfg(feature = "dev")]
         ReleaseCandidateSelectionOption::DumpReleaseInfo,
         ReleaseCandidateSelectionOption::SkipItem,
         ReleaseCandidateSelectionOption::Quit,
     ];
-    let options: Vec<StyledReleaseCandidateSelectionOption<'a, T>> = candidates
-        .iter()
-        .map(ReleaseCandidateSelectionOption::Candidate)
+    let options: Vec<StyledReleaseCandidateSelectionOption<'a, T>> = options
         .chain(additional_options)
         .map(|option| option.into_styled(config))
         .collect();
     loop {
-        let prompt = match candidates.len() {
-            0 | 1 => "Select release candidate:".to_string(),
-            candidate_count => format!("Select one of {candidate_count} release candidates:"),
-        };
-        match Select::new(&prompt, options.clone())
+        match Select::new(prompt, options.clone())
             .prompt()
             .map(ReleaseCandidateSelectionOption::from)
         {
             Ok(ReleaseCandidateSelectionOption::Candidate(candidate)) => {
-                break Ok(ReleaseCandidateSelectionResult::Candidate(candidate))
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::Candidate(candidate),
+                ))
+            }
+            Ok(ReleaseCandidateSelectionOption::ChangeSortOrder(sort_order)) => {
+                break Ok(SelectedOption::ChangeSortOrder(sort_order))
+            }
+            Ok(ReleaseCandidateSelectionOption::ToggleGrouping(group_by_release_group)) => {
+                break Ok(SelectedOption::ToggleGrouping(group_by_release_group))
             }
             Ok(ReleaseCandidateSelectionOption::PrintTrackList) => {
-                break Ok(ReleaseCandidateSelectionResult::PrintTrackList);
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::PrintTrackList,
+                ));
+            }
+            Ok(ReleaseCandidateSelectionOption::AddToMusicBrainz) => {
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::AddToMusicBrainz,
+                ));
             }
             Ok(ReleaseCandidateSelectionOption::EnterMusicBrainzId) => {
                 if let Some(option) = enter_musicbrainz_id() {
-                    break Ok(option);
+                    break Ok(SelectedOption::Result(option));
                 }
             }
             #[cfg(feature = "dev")]
             Ok(ReleaseCandidateSelectionOption::DumpReleaseInfo) => {
-                break Ok(ReleaseCandidateSelectionResult::DumpReleaseInfo);
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::DumpReleaseInfo,
+                ));
             }
             Ok(ReleaseCandidateSelectionOption::SkipItem)
             | Err(InquireError::OperationCanceled) => {
-                break Ok(ReleaseCandidateSelectionResult::Skipped)
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::Skipped,
+                ))
             }
             Ok(ReleaseCandidateSelectionOption::Quit) => {
-                break Ok(ReleaseCandidateSelectionResult::Quit)
+                break Ok(SelectedOption::Result(
+                    ReleaseCandidateSelectionResult::Quit,
+                ))
             }
             Err(err) => Err(err)?,
         }
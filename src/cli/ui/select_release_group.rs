@@ -0,0 +1,179 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Release Group Selection.
+
+use crate::config::Config;
+use crate::musicbrainz::MusicBrainzReleaseGroup;
+use inquire::{InquireError, Select};
+use itertools::Itertools;
+use musicbrainz_rs_nova::entity::release_group::ReleaseGroupPrimaryType;
+use std::borrow::Cow;
+use std::fmt;
+
+/// The choice made by the user when selecting a release group.
+#[derive(Debug, Clone, Copy)]
+pub enum ReleaseGroupSelectionResult<'a> {
+    /// Select a release from this release group.
+    ReleaseGroup(&'a MusicBrainzReleaseGroup),
+    /// Select from all release candidates instead of a single release group.
+    ShowAllReleases,
+    /// The item was skipped.
+    Skipped,
+    /// Quit (i.e., skip this item and all following).
+    Quit,
+}
+
+/// An option presented when selecting a release group.
+#[derive(Clone, Copy)]
+enum ReleaseGroupSelectionOption<'a> {
+    /// Select this release group.
+    ReleaseGroup(&'a MusicBrainzReleaseGroup),
+    /// Select from all release candidates instead.
+    ShowAllReleases,
+    /// Skip this item.
+    SkipItem,
+    /// Quit (i.e., skip this item and all following).
+    Quit,
+}
+
+/// A styled version of `ReleaseGroupSelectionOption` that is displayed to the user.
+#[derive(Clone, Copy)]
+struct StyledReleaseGroupSelectionOption<'a>(&'a Config, ReleaseGroupSelectionOption<'a>);
+
+/// Returns the name of the primary type of the release group that is displayed to the user.
+fn primary_type_name(primary_type: &ReleaseGroupPrimaryType) -> Option<&'static str> {
+    match primary_type {
+        ReleaseGroupPrimaryType::Album => Some("Album"),
+        ReleaseGroupPrimaryType::Single => Some("Single"),
+        ReleaseGroupPrimaryType::Ep => Some("EP"),
+        ReleaseGroupPrimaryType::Broadcast => Some("Broadcast"),
+        ReleaseGroupPrimaryType::Other => Some("Other"),
+        _ => None,
+    }
+}
+
+/// Write a single line describing the release group, followed by its type, the year of its first
+/// release and the number of releases.
+fn write_release_group(
+    f: &mut fmt::Formatter<'_>,
+    config: &Config,
+    release_group: &MusicBrainzReleaseGroup,
+) -> fmt::Result {
+    let candidate_details = &config.user_interface.candidate_details;
+    let artist = release_group
+        .artist_credit
+        .iter()
+        .flatten()
+        .fold(String::new(), |acc, artist| {
+            acc + &artist.name + artist.joinphrase.as_deref().unwrap_or_default()
+        });
+    let details = [
+        release_group
+            .primary_type
+            .as_ref()
+            .and_then(primary_type_name)
+            .map(Cow::from),
+        release_group
+            .first_release_date
+            .map(|date| Cow::from(date.format("%Y").to_string())),
+        release_group
+            .releases
+            .as_ref()
+            .map(|releases| match releases.len() {
+                1 => Cow::from("1 release"),
+                count => Cow::from(format!("{count} releases")),
+            }),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|detail| {
+        candidate_details
+            .candidate_disambiguation_style
+            .apply(detail)
+    })
+    .join(
+        &candidate_details
+            .candidate_similarity_separator_style
+            .apply(", ")
+            .to_string(),
+    );
+    if details.is_empty() {
+        return write!(f, "{artist} - {title}", title = release_group.title);
+    }
+    write!(
+        f,
+        "{artist} - {title}{prefix}{details}{suffix}",
+        title = release_group.title,
+        prefix = candidate_details
+            .candidate_similarity_prefix_style
+            .apply(&candidate_details.candidate_similarity_prefix),
+        suffix = candidate_details
+            .candidate_similarity_suffix_style
+            .apply(&candidate_details.candidate_similarity_suffix),
+    )
+}
+
+impl fmt::Display for StyledReleaseGroupSelectionOption<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self.1 {
+            ReleaseGroupSelectionOption::ReleaseGroup(release_group) => {
+                return write_release_group(f, self.0, release_group);
+            }
+            ReleaseGroupSelectionOption::ShowAllReleases => "Show All Releases",
+            ReleaseGroupSelectionOption::SkipItem => "Skip Item",
+            ReleaseGroupSelectionOption::Quit => "Quit",
+        };
+        write!(
+            f,
+            "{}",
+            self.0
+                .user_interface
+                .candidate_details
+                .action_style
+                .apply(text)
+        )
+    }
+}
+
+/// Present the release groups to the user and loop until either a release group was selected or
+/// the user decided to select from all release candidates, skip the item or quit.
+pub fn select_release_group<'a>(
+    config: &'a Config,
+    release_groups: &'a [MusicBrainzReleaseGroup],
+) -> Result<ReleaseGroupSelectionResult<'a>, InquireError> {
+    let prompt = match release_groups.len() {
+        0 | 1 => "Select release group:".to_string(),
+        group_count => format!("Select one of {group_count} release groups:"),
+    };
+    let options: Vec<StyledReleaseGroupSelectionOption<'a>> = release_groups
+        .iter()
+        .map(ReleaseGroupSelectionOption::ReleaseGroup)
+        .chain([
+            ReleaseGroupSelectionOption::ShowAllReleases,
+            ReleaseGroupSelectionOption::SkipItem,
+            ReleaseGroupSelectionOption::Quit,
+        ])
+        .map(|option| StyledReleaseGroupSelectionOption(config, option))
+        .collect();
+    match Select::new(&prompt, options).prompt() {
+        Ok(StyledReleaseGroupSelectionOption(
+            _,
+            ReleaseGroupSelectionOption::ReleaseGroup(release_group),
+        )) => Ok(ReleaseGroupSelectionResult::ReleaseGroup(release_group)),
+        Ok(StyledReleaseGroupSelectionOption(_, ReleaseGroupSelectionOption::ShowAllReleases)) => {
+            Ok(ReleaseGroupSelectionResult::ShowAllReleases)
+        }
+        Ok(StyledReleaseGroupSelectionOption(_, ReleaseGroupSelectionOption::SkipItem))
+        | Err(InquireError::OperationCanceled) => Ok(ReleaseGroupSelectionResult::Skipped),
+        Ok(StyledReleaseGroupSelectionOption(_, ReleaseGroupSelectionOption::Quit)) => {
+            Ok(ReleaseGroupSelectionResult::Quit)
+        }
+        Err(err) => Err(err),
+    }
+}
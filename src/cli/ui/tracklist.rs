@@ -0,0 +1,358 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Print tracklists for adding missing releases to MusicBrainz.
+//!
+//! The tracklist of each medium is printed in the format that the track parser of the MusicBrainz
+//! release editor understands (`number. title (m:ss)`), so that it can be copied and pasted as-is.
+//! Optionally, a URL that opens the release editor with all known values prefilled is printed as
+//! well.
+//!
+//! For releases with many tracks, the seed URL can get too long for some browsers, so the release
+//! editor can also be opened with an HTML form that POSTs the same data.
+
+use crate::media::MediaLike;
+use crate::release::ReleaseLike;
+use crate::track::TrackLike;
+use crate::util::FormattedDuration;
+use crate::Config;
+use reqwest::Url;
+use std::borrow::Cow;
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// URL of the MusicBrainz release editor.
+const RELEASE_EDITOR_URL: &str = "https://musicbrainz.org/release/add";
+
+/// Command that is used to open files if neither the configuration nor `$BROWSER` specify one.
+#[cfg(target_os = "macos")]
+const DEFAULT_BROWSER: &str = "open";
+/// Command that is used to open files if neither the configuration nor `$BROWSER` specify one.
+#[cfg(not(target_os = "macos"))]
+const DEFAULT_BROWSER: &str = "xdg-open";
+
+/// Time that the seed form file is kept after the browser command exited, so that browsers which
+/// hand the file over to a running instance still have time to load it.
+const SEED_FORM_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Write the tracklist of the release in the format of the MusicBrainz track parser.
+///
+/// Each medium is preceded by a header line and separated by an empty line, because the track
+/// parser has to be used separately for each medium.
+fn write_track_parser_format(
+    writer: &mut impl Write,
+    release: &impl ReleaseLike,
+) -> io::Result<()> {
+    for (media_index, media) in release.media().enumerate() {
+        let format = media.media_format().unwrap_or_else(|| "Medium".into());
+        if media_index > 0 {
+            writeln!(writer)?;
+        }
+        if let Some(title) = media.media_title() {
+            writeln!(writer, "{format} {index}: {title}", index = media_index + 1)?;
+        } else {
+            writeln!(writer, "{format} {index}", index = media_index + 1)?;
+        }
+        for (track_index, track) in media.media_tracks().enumerate() {
+            let track_number = track
+                .track_number()
+                .unwrap_or_else(|| Cow::from(format!("{}", track_index + 1)));
+            let track_title = track
+                .track_title()
+                .unwrap_or_else(|| Cow::from("[unnamed track]"));
+            let track_length = track.track_length().map_or_else(
+                || Cow::from("?:??"),
+                |length| Cow::from(length.formatted_duration()),
+            );
+            writeln!(writer, "{track_number}. {track_title} ({track_length})")?;
+        }
+    }
+    Ok(())
+}
+
+/// Build the parameters for seeding the MusicBrainz release editor with the data of the release.
+///
+/// See <https://musicbrainz.org/doc/Development/Release_Editor_Seeding> for the supported
+/// parameters.
+fn release_editor_seed_params(release: &impl ReleaseLike) -> Vec<(String, String)> {
+    let mut params: Vec<(String, String)> = vec![];
+    let mut push = |key: &str, value: Option<Cow<'_, str>>| {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            params.push((key.to_string(), value.into_owned()));
+        }
+    };
+
+    push("name", release.release_title());
+    push("artist_credit.names.0.name", release.release_artist());
+    push("barcode", release.barcode());
+    push("labels.0.name", release.record_label());
+    push("labels.0.catalog_number", release.catalog_number());
+    push("country", release.release_country());
+    push("status", release.release_status());
+    push("type", release.release_type());
+    if let Some(date) = release.release_date().or_else(|| release.release_year()) {
+        for (key, value) in ["date.year", "date.month", "date.day"]
+            .into_iter()
+            .zip(date.split('-'))
+        {
+            push(key, Some(Cow::from(value)));
+        }
+    }
+
+    for (media_index, media) in release.media().enumerate() {
+        let prefix = format!("mediums.{media_index}");
+        push(&format!("{prefix}.format"), media.media_format());
+        push(&format!("{prefix}.name"), media.media_title());
+        for (track_index, track) in media.media_tracks().enumerate() {
+            let prefix = format!("{prefix}.track.{track_index}");
+            push(&format!("{prefix}.number"), track.track_number());
+            push(&format!("{prefix}.name"), track.track_title());
+            push(
+                &format!("{prefix}.length"),
+                track
+                    .track_length()
+                    .map(|length| Cow::from(length.num_milliseconds().to_string())),
+            );
+            push(
+                &format!("{prefix}.artist_credit.names.0.name"),
+                track.track_artist(),
+            );
+        }
+    }
+
+    push(
+        "edit_note",
+        Some(Cow::from(format!(
+            "Seeded by {} {}",
+            crate::PKG_NAME,
+            crate::PKG_VERSION
+        ))),
+    );
+
+    params
+}
+
+/// Build a URL that opens the MusicBrainz release editor seeded with the data of the release.
+///
+/// # Panics
+///
+/// Panics if the release editor URL is invalid, which cannot happen.
+pub fn release_editor_seed_url(release: &impl ReleaseLike) -> Url {
+    Url::parse_with_params(RELEASE_EDITOR_URL, release_editor_seed_params(release))
+        .expect("release editor URL is valid")
+}
+
+/// Build an HTML page that POSTs the seed data of the release to the MusicBrainz release editor
+/// as soon as it is opened in a browser.
+fn release_editor_seed_form(release: &impl ReleaseLike) -> String {
+    let inputs = release_editor_seed_params(release)
+        .into_iter()
+        .map(|(key, value)| {
+            format!(
+                r#"<input type="hidden" name="{}" value="{}">"#,
+                handlebars::html_escape(&key),
+                handlebars::html_escape(&value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Add Release to MusicBrainz</title></head>
+<body onload="document.forms[0].submit()">
+<form action="{RELEASE_EDITOR_URL}" method="post" accept-charset="utf-8">
+{inputs}
+<button type="submit">Add Release to MusicBrainz</button>
+</form>
+</body>
+</html>
+"#
+    )
+}
+
+/// Determine the browser command from the configuration or the `$BROWSER` environment variable.
+fn browser_command(config: &Config) -> String {
+    config
+        .user_interface
+        .browser_command
+        .clone()
+        .or_else(|| {
+            env::var("BROWSER")
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_BROWSER.to_string())
+}
+
+/// Write the seed form of the release into a temporary file and open it in the browser.
+///
+/// The browser is started in the background, so that the import is not blocked until the browser
+/// exits. The temporary file is removed in the background after the browser command has exited
+/// (and the browser had time to load it).
+fn open_seed_form(config: &Config, release: &impl ReleaseLike) -> io::Result<()> {
+    let mut file = tempfile::Builder::new()
+        .prefix("helicon-release-seed-")
+        .suffix(".html")
+        .tempfile()?;
+    file.write_all(release_editor_seed_form(release).as_bytes())?;
+    let path = file.into_temp_path();
+
+    let command = browser_command(config);
+    let mut args = command.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| io::Error::other("empty browser command"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    println!("Opened release editor seed form {}", path.display());
+    let _ = thread::spawn(move || {
+        match child.wait() {
+            Ok(status) if !status.success() => {
+                log::warn!("{command:?} exited with {status}");
+            }
+            Ok(_) => (),
+            Err(err) => log::warn!("Failed to wait for {command:?}: {err}"),
+        }
+        thread::sleep(SEED_FORM_LIFETIME);
+        if let Err(err) = path.close() {
+            log::warn!("Failed to remove release editor seed form: {err}");
+        }
+    });
+    Ok(())
+}
+
+/// Open the MusicBrainz release editor seeded with the data of the release in the browser.
+///
+/// If the browser cannot be started, the seed URL is printed instead.
+pub fn open_release_editor(config: &Config, release: &impl ReleaseLike) {
+    if let Err(err) = open_seed_form(config, release) {
+        log::warn!("Failed to open release editor in browser: {err}");
+        println!(
+            "Add release to MusicBrainz: {}",
+            release_editor_seed_url(release)
+        );
+    }
+}
+
+/// Write the tracklist of the release (and the release editor seed URL, if enabled).
+pub fn write_tracklist(
+    writer: &mut impl Write,
+    config: &Config,
+    release: &impl ReleaseLike,
+) -> io::Result<()> {
+    write_track_parser_format(writer, release)?;
+    if config.user_interface.print_seed_url {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "Add release to MusicBrainz: {}",
+            release_editor_seed_url(release)
+        )?;
+    }
+    Ok(())
+}
+
+/// Print the tracklist of the release (and the release editor seed URL, if enabled).
+pub fn print_tracklist(config: &Config, release: &impl ReleaseLike) -> io::Result<()> {
+    write_tracklist(&mut io::stdout().lock(), config, release)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::FakeRelease;
+
+    /// Release with two media for testing.
+    fn release() -> FakeRelease {
+        serde_json::from_value(serde_json::json!({
+            "release_title": "Album",
+            "release_artist": "Artist",
+            "release_date": "2024-03-01",
+            "media": [
+                {
+                    "media_format": "CD",
+                    "media_tracks": [
+                        {"track_number": "1", "track_title": "Intro", "track_length": [65, 0]},
+                        {"track_title": "Song", "track_artist": "Guest", "track_length": [245, 0]},
+                    ],
+                },
+                {
+                    "media_format": "CD",
+                    "media_title": "Bonus",
+                    "media_tracks": [{"track_number": "1", "track_title": "Outro"}],
+                },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_write_track_parser_format() {
+        let mut output = Vec::new();
+        write_track_parser_format(&mut output, &release()).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "CD 1\n1. Intro (1:05)\n2. Song (4:05)\n\nCD 2: Bonus\n1. Outro (?:??)\n"
+        );
+    }
+
+    #[test]
+    fn test_release_editor_seed_url() {
+        let url = release_editor_seed_url(&release());
+        assert!(url.as_str().starts_with(RELEASE_EDITOR_URL));
+        let params = url.query_pairs().into_owned().collect::<Vec<_>>();
+        let value = |key: &str| {
+            params
+                .iter()
+                .find(|(param, _)| param == key)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value("name"), Some("Album"));
+        assert_eq!(value("artist_credit.names.0.name"), Some("Artist"));
+        assert_eq!(value("date.year"), Some("2024"));
+        assert_eq!(value("date.month"), Some("03"));
+        assert_eq!(value("date.day"), Some("01"));
+        assert_eq!(value("mediums.0.format"), Some("CD"));
+        assert_eq!(value("mediums.0.track.1.name"), Some("Song"));
+        assert_eq!(value("mediums.0.track.1.length"), Some("245000"));
+        assert_eq!(
+            value("mediums.0.track.1.artist_credit.names.0.name"),
+            Some("Guest")
+        );
+        assert_eq!(value("mediums.0.track.1.number"), None);
+        assert_eq!(value("mediums.1.name"), Some("Bonus"));
+        assert_eq!(value("mediums.1.track.0.length"), None);
+    }
+
+    #[test]
+    fn test_release_editor_seed_form() {
+        let release: FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": "Rock & \"Roll\"",
+            "media": [{"media_tracks": [{"track_title": "<Intro>"}]}],
+        }))
+        .unwrap();
+        let form = release_editor_seed_form(&release);
+        assert!(
+            form.contains(r#"<form action="https://musicbrainz.org/release/add" method="post""#)
+        );
+        assert!(form
+            .contains(r#"<input type="hidden" name="name" value="Rock &amp; &quot;Roll&quot;">"#));
+        assert!(form.contains(
+            r#"<input type="hidden" name="mediums.0.track.0.name" value="&lt;Intro&gt;">"#
+        ));
+    }
+}
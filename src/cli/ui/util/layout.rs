@@ -12,6 +12,7 @@ use super::styled_content::CharWidth;
 use super::StyledContentList;
 use crossterm::style::{ContentStyle, StyledContent};
 use std::borrow::Cow;
+use std::io::{self, Write};
 
 /// An item that can be passed to `print_column_layout`.
 pub struct LayoutItem<'a> {
@@ -122,13 +123,14 @@ impl<'a> LayoutItem<'a> {
 ///         [          ..lhs_content              ]           [          ..rhs_content              ]
 /// ```
 pub fn print_column_layout(
+    out: &mut impl Write,
     lhs: LayoutItem<'_>,
     rhs: LayoutItem<'_>,
     indent: &str,
     separator: &str,
     max_width: usize,
     max_height: usize,
-) {
+) -> io::Result<()> {
     let column_width = (max_width - indent.len() - separator.len()) / 2;
     let mut lhs_lines = lhs.into_split_lines(column_width, max_height);
     let mut rhs_lines = rhs.into_split_lines(column_width, max_height);
@@ -153,7 +155,7 @@ pub fn print_column_layout(
             .into();
 
     while let Some(line) = next_line {
-        println!("{line}");
+        writeln!(out, "{line}")?;
 
         let lhs_line: Option<StyledContentList<'_>> = lhs_lines.next();
         let rhs_line: Option<StyledContentList<'_>> = rhs_lines.next();
@@ -185,4 +187,6 @@ pub fn print_column_layout(
             next_line = None;
         }
     }
+
+    Ok(())
 }
@@ -10,13 +10,18 @@
 
 use crate::distance::Distance;
 use crate::release::ReleaseLike;
-use crossterm::style::{Color, Stylize};
+use crate::Config;
+use crossterm::style::{ContentStyle, Stylize};
 use std::borrow::Cow;
+use std::env;
+use std::io::{self, IsTerminal};
 
 mod layout;
+mod pager;
 mod styled_content;
 
 pub use layout::{print_column_layout, LayoutItem};
+pub use pager::print_paged;
 pub use styled_content::{string_diff_opt, StyledContentList};
 
 /// Format a distance as a similarity in percent, were 0% the the maximum distance and 100% the
@@ -25,24 +30,76 @@ pub fn as_similarity_percentage(distance: &Distance) -> f64 {
     (1.0 - distance.as_f64()) * 100.0
 }
 
-/// Get the color associate with the distance value.
-pub fn distance_color(distance: &Distance) -> Color {
-    let d = distance.as_f64();
-    if d <= 0.1 {
-        Color::Green
-    } else if d <= 0.5 {
-        Color::Yellow
-    } else {
-        Color::Red
+/// Coarse classification of a distance value that is used for styling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceLevel {
+    /// Low distance (good match).
+    Low,
+    /// Medium distance.
+    Medium,
+    /// High distance (bad match).
+    High,
+}
+
+impl DistanceLevel {
+    /// Classify the distance value.
+    pub fn from_distance(distance: &Distance) -> Self {
+        let d = distance.as_f64();
+        if d <= 0.1 {
+            DistanceLevel::Low
+        } else if d <= 0.5 {
+            DistanceLevel::Medium
+        } else {
+            DistanceLevel::High
+        }
+    }
+
+    /// Symbol that conveys the distance level without relying on colors.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            DistanceLevel::Low => "✓",
+            DistanceLevel::Medium => "~",
+            DistanceLevel::High => "✗",
+        }
     }
 }
 
-/// Format the similarity as colored percentage.
-pub fn format_similarity(distance: &Distance) -> String {
+/// Returns `true` if colored output should be used, i.e., if `NO_COLOR` is not set and the
+/// standard output is a terminal.
+pub fn colors_enabled() -> bool {
+    env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && io::stdout().is_terminal()
+}
+
+/// Get the style associated with the distance value.
+pub fn distance_style(config: &Config, distance: &Distance) -> ContentStyle {
+    let theme = &config.user_interface.theme;
+    match DistanceLevel::from_distance(distance) {
+        DistanceLevel::Low => theme.low_distance_style(),
+        DistanceLevel::Medium => theme.medium_distance_style(),
+        DistanceLevel::High => theme.high_distance_style(),
+    }
+}
+
+/// Format the similarity as styled percentage.
+///
+/// If colors are disabled (or if configured in the theme), a symbol is prepended so that the
+/// distance level can be distinguished without colors.
+pub fn format_similarity(config: &Config, distance: &Distance) -> String {
     let similarity = as_similarity_percentage(distance);
-    let color = distance_color(distance);
+    let style = distance_style(config, distance);
+    let show_symbols = config
+        .user_interface
+        .theme
+        .similarity_symbols
+        .unwrap_or_else(|| !colors_enabled());
 
-    format!("{similarity:.02}").with(color).to_string()
+    let text = if show_symbols {
+        let symbol = DistanceLevel::from_distance(distance).symbol();
+        format!("{symbol} {similarity:.02}")
+    } else {
+        format!("{similarity:.02}")
+    };
+    style.apply(text).to_string()
 }
 
 /// Format the release artist and title for the terminal.
@@ -0,0 +1,80 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Pager support for long output.
+
+use crate::Config;
+use crossterm::terminal;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Pager command that is used if neither the configuration nor `$PAGER` specify one.
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Number of lines that are reserved for the prompt that is shown after the output.
+const PROMPT_LINES: usize = 10;
+
+/// Determine the pager command from the configuration or the `$PAGER` environment variable.
+fn pager_command(config: &Config) -> String {
+    config
+        .user_interface
+        .pager_command
+        .clone()
+        .or_else(|| {
+            env::var("PAGER")
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+/// Pipe the output into the pager and wait until it exits.
+fn run_pager(command: &str, output: &[u8]) -> io::Result<()> {
+    let mut args = command.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| io::Error::other("empty pager command"))?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may exit before reading all input (e.g., if the user quits early).
+        match stdin.write_all(output) {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err),
+            _ => (),
+        }
+    }
+    let _ = child.wait()?;
+    Ok(())
+}
+
+/// Print the output, using a pager if it does not fit on the screen.
+///
+/// # Errors
+///
+/// Returns an error if writing to the standard output fails.
+pub fn print_paged(config: &Config, output: &[u8]) -> io::Result<()> {
+    let line_count = output.iter().filter(|&&byte| byte == b'\n').count();
+    let fits_on_screen = terminal::size().map_or(true, |(_cols, rows)| {
+        line_count + PROMPT_LINES <= usize::from(rows)
+    });
+
+    if config.user_interface.use_pager && !fits_on_screen && io::stdout().is_terminal() {
+        let command = pager_command(config);
+        match run_pager(&command, output) {
+            Ok(()) => return Ok(()),
+            Err(err) => log::warn!("Failed to run pager {command:?}: {err}"),
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(output)?;
+    stdout.flush()
+}
@@ -0,0 +1,57 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Module for the `undo` CLI subcommand.
+
+use crate::journal::ImportJournal;
+use crate::Cache;
+use crate::Config;
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Command line arguments for the `undo` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Album to revert, either its source directory or the directory it was imported to. Use
+    /// `last` to revert the most recent import.
+    #[arg(default_value = "last")]
+    album: String,
+}
+
+/// Run the `undo` command.
+///
+/// # Errors
+///
+/// Returns an error if the journal cannot be read or the files cannot be restored.
+pub fn run(config: &Config, _cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let path = if args.album == "last" {
+        None
+    } else {
+        Some(std::path::absolute(PathBuf::from(args.album))?)
+    };
+
+    let journal = ImportJournal::open_default()?;
+    let Some(entry) = journal.take_entry(path.as_deref())? else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    println!(
+        "Reverting import of {path} from {date}",
+        path = entry.source_path.display(),
+        date = entry.imported_at
+    );
+    if let Err(err) = entry.revert(&config.tags) {
+        // Keep the entry so that the undo can be retried after fixing the problem.
+        journal.append(&entry)?;
+        return Err(err);
+    }
+    println!("Restored {} tracks.", entry.tracks.len());
+
+    Ok(())
+}
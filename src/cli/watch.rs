@@ -0,0 +1,189 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Functions related to watching a drop folder.
+
+use super::import;
+use crate::distance::Distance;
+use crate::import_session::SelectionMode;
+use crate::stats::ImportOutcome;
+use crate::util;
+use crate::Cache;
+use crate::Config;
+use clap::Parser;
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Interval in which pending album folders are checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Command line arguments for the `watch` CLI command.
+#[derive(Parser, Debug)]
+pub struct Args {
+    /// Path to watch.
+    path: PathBuf,
+}
+
+/// Find the album folder (i.e., the direct child of the watched directory) that contains the path.
+fn album_dir_for_path(watch_root: &Path, path: &Path) -> Option<PathBuf> {
+    path.strip_prefix(watch_root)
+        .ok()?
+        .components()
+        .next()
+        .map(|component| watch_root.join(component))
+}
+
+/// Returns `true` if the directory contains files that indicate that it is still being copied.
+fn contains_incomplete_files(dir: &Path, incomplete_patterns: &[String]) -> bool {
+    util::walk_dir(dir.to_path_buf())
+        .filter_map(Result::ok)
+        .flat_map(|(_path, _dirs, files)| files)
+        .any(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    incomplete_patterns
+                        .iter()
+                        .any(|pattern| util::matches_file_pattern(pattern, name))
+                })
+        })
+}
+
+/// Watch a directory and automatically import album folders once they are complete.
+///
+/// An album folder is considered complete if there were no filesystem changes in it for the
+/// configured settle time and if it does not contain any files that match the configured
+/// incomplete patterns.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be watched.
+pub async fn run(config: &Config, cache: Option<&Cache>, args: Args) -> crate::Result<()> {
+    let watch_root = args.path;
+    let settle_time = Duration::from_secs(config.watch.settle_time);
+    let mode = SelectionMode::Automatic {
+        max_distance: Distance::from(config.watch.max_distance),
+    };
+
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                for path in event.paths {
+                    if event_tx.send(path).is_err() {
+                        log::debug!("Dropping filesystem event because the receiver was closed");
+                    }
+                }
+            }
+            Err(err) => log::warn!("Filesystem watcher error: {err}"),
+        })?;
+    watcher.watch(&watch_root, RecursiveMode::Recursive)?;
+    log::info!("Watching {}", watch_root.display());
+    println!("Watching {} (press Ctrl+C to stop)", watch_root.display());
+
+    // Album folders that already exist when starting are treated like new ones.
+    let mut pending_dirs: HashMap<PathBuf, Instant> = fs::read_dir(&watch_root)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .map(|path| (path, Instant::now()))
+        .collect();
+    let mut imported_dirs: HashMap<PathBuf, Instant> = HashMap::new();
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            Some(path) = event_rx.recv() => {
+                let Some(album_dir) = album_dir_for_path(&watch_root, &path) else {
+                    continue;
+                };
+
+                // Ignore the changes caused by importing the folder ourselves.
+                if imported_dirs
+                    .get(&album_dir)
+                    .is_some_and(|imported_at| imported_at.elapsed() < settle_time)
+                {
+                    continue;
+                }
+
+                let _ = pending_dirs.insert(album_dir, Instant::now());
+            }
+            () = tokio::time::sleep(POLL_INTERVAL) => (),
+            _ = &mut ctrl_c => break,
+        }
+
+        let settled_dirs = pending_dirs
+            .iter()
+            .filter(|(_, changed_at)| changed_at.elapsed() >= settle_time)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for album_dir in settled_dirs {
+            let _ = pending_dirs.remove(&album_dir);
+            if !album_dir.is_dir() {
+                continue;
+            }
+
+            if contains_incomplete_files(&album_dir, &config.watch.incomplete_patterns) {
+                log::info!(
+                    "Postponing import of {} because it is incomplete",
+                    album_dir.display()
+                );
+                let _ = pending_dirs.insert(album_dir, Instant::now());
+                continue;
+            }
+
+            log::info!("Importing {}", album_dir.display());
+            match import::import_path(config, cache, album_dir.clone(), &mode, None, false).await {
+                Ok(stats) => println!(
+                    "{path}: {imported} imported, {partial} partially imported, {skipped} skipped, \
+                     {failed} failed",
+                    path = album_dir.display(),
+                    imported = stats.count(ImportOutcome::Imported),
+                    partial = stats.count(ImportOutcome::PartiallyImported),
+                    skipped = stats.count(ImportOutcome::Skipped),
+                    failed = stats.count(ImportOutcome::Failed),
+                ),
+                Err(err) => log::error!("Import of {} failed: {err}", album_dir.display()),
+            }
+            let _ = imported_dirs.insert(album_dir, Instant::now());
+        }
+    }
+
+    log::info!("Stopped watching {}", watch_root.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_album_dir_for_path() {
+        let root = Path::new("/drop");
+        assert_eq!(
+            album_dir_for_path(root, Path::new("/drop/Album/CD1/01.flac")),
+            Some(PathBuf::from("/drop/Album"))
+        );
+        assert_eq!(album_dir_for_path(root, Path::new("/drop")), None);
+        assert_eq!(album_dir_for_path(root, Path::new("/other/Album")), None);
+    }
+
+    #[test]
+    fn test_contains_incomplete_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let patterns = vec!["*.part".to_string()];
+        fs::write(tmp_dir.path().join("01.flac"), "").unwrap();
+        assert!(!contains_incomplete_files(tmp_dir.path(), &patterns));
+        fs::write(tmp_dir.path().join("02.flac.part"), "").unwrap();
+        assert!(contains_incomplete_files(tmp_dir.path(), &patterns));
+    }
+}
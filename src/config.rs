@@ -8,10 +8,13 @@
 
 //! Configuration utils.
 
+use crate::distance::StringMetric;
 use crate::pathformat::PathFormatterValues;
 use crate::pathformat::PathTemplate;
+use crate::tag::TagKey;
 use expanduser::expanduser;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -45,6 +48,10 @@ pub struct TrackDistanceWeights {
     pub track_length: DistanceWeight,
     /// MusicBrainz Recording ID weight.
     pub musicbrainz_recording_id: DistanceWeight,
+    /// ISRC weight.
+    pub isrc: DistanceWeight,
+    /// MusicBrainz Artist ID weight.
+    pub musicbrainz_artist_id: DistanceWeight,
 }
 
 /// Weights  for release distance calculation.
@@ -65,10 +72,30 @@ pub struct ReleaseDistanceWeights {
     pub catalog_number: DistanceWeight,
     /// Barcode weight.
     pub barcode: DistanceWeight,
+    /// Release date weight (only the year is compared).
+    pub release_date: DistanceWeight,
+    /// Release country weight.
+    pub release_country: DistanceWeight,
+    /// Artist consistency weight (i.e., whether the track artists match the release artist on
+    /// both releases, which distinguishes compilations of various artists from albums by a single
+    /// artist).
+    pub artist_consistency: DistanceWeight,
     /// Overall track assignment weight.
     pub track_assignment: DistanceWeight,
 }
 
+/// Weights for the preferred release calculation.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PreferredReleaseWeights {
+    /// Release country weight.
+    pub country: DistanceWeight,
+    /// Media format weight.
+    pub media_format: DistanceWeight,
+    /// Release status weight.
+    pub status: DistanceWeight,
+}
+
 /// Weight configuration.
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct DistanceWeights {
@@ -76,18 +103,155 @@ pub struct DistanceWeights {
     pub track: TrackDistanceWeights,
     /// Weights for release distance calculation.
     pub release: ReleaseDistanceWeights,
+    /// Weights for the preferred release calculation.
+    pub preferred_release: PreferredReleaseWeights,
+}
+
+/// Algorithms used to compare the string fields of tracks and releases.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+pub struct StringMetricsConfig {
+    /// Metric for track titles.
+    pub track_title: StringMetric,
+    /// Metric for track artists.
+    pub track_artist: StringMetric,
+    /// Metric for release titles.
+    pub release_title: StringMetric,
+    /// Metric for release artists.
+    pub release_artist: StringMetric,
+    /// Metric for record labels.
+    pub record_label: StringMetric,
+}
+
+/// Preferred release properties that are used to rank candidates with similar metadata.
+///
+/// Each list is ordered by preference, i.e., the first item is the most preferred one. Empty lists
+/// are ignored.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct PreferredReleaseConfig {
+    /// Preferred release countries (e.g., `XW`, `US`, `DE`).
+    pub countries: Vec<String>,
+    /// Preferred media formats (e.g., `CD`, `Digital Media`).
+    pub media_formats: Vec<String>,
+    /// Preferred release statuses (e.g., `official`).
+    pub statuses: Vec<String>,
+    /// Weight of the preference compared to the metadata distance (which has a weight of 1.0).
+    pub weight: DistanceWeight,
+}
+
+/// Filters that are applied to search results before the full releases are fetched.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CandidateFilterConfig {
+    /// Exclude releases with these statuses (e.g., `bootleg`).
+    pub excluded_statuses: Vec<String>,
+    /// Exclude releases if all of their media have one of these formats (e.g., `Digital Media`
+    /// to exclude digital-only releases).
+    pub excluded_media_formats: Vec<String>,
+    /// Exclude releases from before this year.
+    pub min_year: Option<i32>,
+    /// Exclude releases from after this year.
+    pub max_year: Option<i32>,
+}
+
+/// Configuration for the assignment of tracks between the local files and a candidate release.
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize)]
+pub struct TrackAssignmentConfig {
+    /// Only compare tracks whose lengths differ by at most this number of seconds. Other pairs are
+    /// considered to be completely different. Tracks without a known length are always compared.
+    pub length_window: Option<u64>,
+    /// Only apply the `length_window` if one of the releases has at least this number of tracks
+    /// (e.g., for large box sets).
+    pub length_window_min_tracks: usize,
+}
+
+/// Strategy for searching release candidates on MusicBrainz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStrategy {
+    /// Search releases by the fields configured in [`SearchQueryConfig`].
+    Release,
+    /// Search releases by their title (and track count, if enabled) only.
+    TitleOnly,
+    /// Search recordings by the release artist and the track titles, and use the releases of the
+    /// matching recordings.
+    ArtistAndTrackTitles,
+}
+
+/// Configuration of the MusicBrainz release search query.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchQueryConfig {
+    /// Only search releases with the same number of tracks.
+    pub track_count: bool,
+    /// Search by the release artist (or the track artist if all tracks have the same one).
+    pub artist: bool,
+    /// Search by the release title.
+    pub release_title: bool,
+    /// Use fuzzy matching for artist names and titles, so that minor spelling differences are
+    /// tolerated.
+    pub fuzzy: bool,
+    /// Strategies that are tried in order if the release search does not yield any candidates.
+    pub fallbacks: Vec<SearchStrategy>,
+    /// If most files are tagged with MusicBrainz Recording or Track IDs, look up the releases
+    /// that contain most of these tracks directly instead of searching by metadata. The search by
+    /// metadata is still used if no such release is found.
+    pub identifiers: bool,
 }
 
 /// Configuration for MusicBrainz lookups.
-#[expect(missing_copy_implementations)]
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct LookupConfig {
     /// Number of concurrent connections to use.
     pub connection_limit: usize,
-    /// Do not fetch more than this number of candidate releases from MusicBrainz.
+    /// Number of candidate releases to fetch from MusicBrainz (per search result page if
+    /// `max_candidates` is set).
     ///
     /// Must be a number between 1 and 100.
     pub release_candidate_limit: u8,
+    /// Do not fetch more than this number of candidate releases in total. If this is larger than
+    /// `release_candidate_limit`, multiple search result pages are requested. If it is not set,
+    /// only a single page of `release_candidate_limit` releases is requested.
+    pub max_candidates: Option<usize>,
+    /// Rank the search results by their release title, release artist and track count first and
+    /// only fetch the full releases (including the tracklists) of this number of best-ranked
+    /// search results.
+    pub max_full_candidates: Option<usize>,
+    /// Stop requesting further search result pages once a candidate with a distance less than or
+    /// equal to this value has been found.
+    pub early_termination_distance: Option<f64>,
+    /// Do not present candidates whose similarity (i.e., 1 minus the distance) is below this value
+    /// (e.g., `0.4` to hide candidates with a similarity of less than 40%).
+    pub min_similarity: Option<f64>,
+    /// Offer release metadata from local files (e.g., `metadata.json` or `album.nfo`) in the album
+    /// directory as an additional candidate.
+    pub use_local_metadata: bool,
+    /// Continue matching the remaining tracks of an album if some of its files cannot be read.
+    /// Otherwise, the whole album fails.
+    pub skip_unreadable_files: bool,
+    /// Split album directories that contain files of several releases (e.g., a directory of
+    /// singles) by their album tags, and match each release separately.
+    pub split_releases: bool,
+    /// Maximum age of cached MusicBrainz responses (in seconds). Expired releases are fetched
+    /// again, but the cached version is still used if the request fails.
+    pub cache_max_age: u64,
+    /// Preferred release properties.
+    pub preferred_release: PreferredReleaseConfig,
+    /// Filters for candidate releases.
+    pub candidate_filter: CandidateFilterConfig,
+    /// Search query configuration.
+    pub search: SearchQueryConfig,
+    /// Track assignment configuration.
+    pub track_assignment: TrackAssignmentConfig,
+    /// MusicBrainz ID of a release that is used as the only candidate instead of searching (set
+    /// by `import --release`, not read from the configuration file).
+    #[serde(skip)]
+    pub release_id: Option<String>,
+}
+
+impl LookupConfig {
+    /// Maximum number of candidate releases that are fetched in total.
+    pub fn max_candidates(&self) -> usize {
+        self.max_candidates
+            .unwrap_or_else(|| usize::from(self.release_candidate_limit))
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -270,6 +434,8 @@ pub struct CandidateDetails {
     pub track_length_missing_style: TextStyleConfig,
     /// Changed value indicator style.
     pub changed_value_style: TextStyleConfig,
+    /// Style of the indicator where a value of the files was read from (e.g., the tag field).
+    pub value_provenance_style: TextStyleConfig,
     /// Styles for residual tracks.
     pub unmatched_tracks_residual: UnmatchedTrackStyleConfig,
     /// Styles for missing tracks.
@@ -296,6 +462,125 @@ pub struct CandidateDetails {
     pub candidate_similarity_suffix_style: TextStyleConfig,
     /// Styles for string diffs.
     pub string_diff_style: StringDiffStyleConfig,
+    /// How the cover art of a candidate is displayed.
+    pub cover_art: CoverArtDisplay,
+}
+
+/// Display mode for the cover art of release candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverArtDisplay {
+    /// Do not show the cover art.
+    Off,
+    /// Only show a link to the cover art.
+    Link,
+    /// Show a thumbnail if the terminal supports images, and a link otherwise.
+    Auto,
+    /// Always show a thumbnail using the kitty graphics protocol.
+    Kitty,
+    /// Always show a thumbnail using the iTerm2 inline images protocol.
+    Iterm,
+    /// Always show a thumbnail using sixel graphics.
+    Sixel,
+}
+
+/// Named color theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    /// Green, yellow and red distance colors.
+    Default,
+    /// Blue, yellow and magenta distance colors that can be distinguished with most forms of color
+    /// blindness.
+    ColorBlind,
+    /// No colors, only text attributes.
+    Monochrome,
+}
+
+impl ThemeName {
+    /// Returns the default styles for low, medium and high distances.
+    fn distance_styles(self) -> [crossterm::style::ContentStyle; 3] {
+        use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+
+        match self {
+            ThemeName::Default => [
+                ContentStyle::new().with(Color::Green),
+                ContentStyle::new().with(Color::Yellow),
+                ContentStyle::new().with(Color::Red),
+            ],
+            ThemeName::ColorBlind => [
+                ContentStyle::new().with(Color::Blue),
+                ContentStyle::new().with(Color::Yellow),
+                ContentStyle::new().with(Color::Magenta),
+            ],
+            ThemeName::Monochrome => [
+                ContentStyle::new().attribute(Attribute::Bold),
+                ContentStyle::new(),
+                ContentStyle::new().attribute(Attribute::Dim),
+            ],
+        }
+    }
+}
+
+/// Theme configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ThemeConfig {
+    /// The named theme that provides the default styles.
+    pub name: ThemeName,
+    /// Style override for low distances (i.e., good matches).
+    low_distance_style: Option<TextStyleConfig>,
+    /// Style override for medium distances.
+    medium_distance_style: Option<TextStyleConfig>,
+    /// Style override for high distances (i.e., bad matches).
+    high_distance_style: Option<TextStyleConfig>,
+    /// Show symbols (✓/~/✗) next to similarity values. If unset, symbols are only shown if colors
+    /// are disabled.
+    pub similarity_symbols: Option<bool>,
+}
+
+impl ThemeConfig {
+    /// Returns the style for low, medium or high distances (depending on the index), taking style
+    /// overrides into account.
+    fn distance_style(&self, index: usize) -> crossterm::style::ContentStyle {
+        let style_override = [
+            &self.low_distance_style,
+            &self.medium_distance_style,
+            &self.high_distance_style,
+        ][index];
+        style_override.as_ref().map_or_else(
+            || self.name.distance_styles()[index],
+            crossterm::style::ContentStyle::from,
+        )
+    }
+
+    /// Style for low distances (i.e., good matches).
+    pub fn low_distance_style(&self) -> crossterm::style::ContentStyle {
+        self.distance_style(0)
+    }
+
+    /// Style for medium distances.
+    pub fn medium_distance_style(&self) -> crossterm::style::ContentStyle {
+        self.distance_style(1)
+    }
+
+    /// Style for high distances (i.e., bad matches).
+    pub fn high_distance_style(&self) -> crossterm::style::ContentStyle {
+        self.distance_style(2)
+    }
+}
+
+/// Order in which the release candidates are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSortOrder {
+    /// Most similar candidates first.
+    Similarity,
+    /// Oldest releases first.
+    Year,
+    /// Alphabetically by release country.
+    Country,
+    /// Alphabetically by media format.
+    Format,
 }
 
 /// Configuration for the user interface.
@@ -306,10 +591,443 @@ pub struct UiConfig {
     /// Maximum terminal width to use. If the terminal is wider, this configured width will be
     /// used.
     pub max_terminal_width: Option<usize>,
+    /// Search for release groups and let the user select one first, and then pick the release
+    /// from the releases of that group.
+    pub select_release_group_first: bool,
+    /// Initial sort order of the release candidates (can be changed during selection).
+    pub candidate_sort_order: CandidateSortOrder,
+    /// Use the simple, prompt-based user interface instead of the interactive screen (e.g., for
+    /// dumb terminals).
+    pub simple_ui: bool,
+    /// Ask which value to use if the tracks of an album have different album titles or artists,
+    /// and look up the candidates again with the chosen values. Only used if the import runs in a
+    /// terminal.
+    pub resolve_tag_conflicts: bool,
+    /// Ask which file to keep if the same track appears multiple times in an album (e.g., `(1)`
+    /// copies), and look up the candidates again without the dropped files. Only used if the
+    /// import runs in a terminal.
+    pub resolve_duplicate_tracks: bool,
+    /// Show candidate details in a pager if they do not fit on the screen.
+    pub use_pager: bool,
+    /// Pager command (e.g., `less -R`). If unset, the `PAGER` environment variable is used.
+    pub pager_command: Option<String>,
+    /// Command for opening the MusicBrainz release editor (e.g., `firefox`). If unset, the
+    /// `BROWSER` environment variable or the platform's default opener is used.
+    pub browser_command: Option<String>,
+    /// Minimum similarity (e.g., `0.95` for 95%) of a release that is applied by a bulk action,
+    /// i.e., the best candidate after choosing to auto-apply good matches for the remaining
+    /// albums, or the release that was chosen to be applied to the remaining albums.
+    pub bulk_auto_apply_similarity: f64,
+    /// When printing the tracklist, also print a URL that opens the MusicBrainz release editor
+    /// prefilled with the release data.
+    pub print_seed_url: bool,
+    /// Color theme.
+    pub theme: ThemeConfig,
     /// Styles for the candidate details view.
     pub candidate_details: CandidateDetails,
 }
 
+/// Configuration for single-file albums with cue sheets.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CueConfig {
+    /// Split single-file albums into individual tracks using the accompanying cue sheet when
+    /// importing them.
+    ///
+    /// Single-file albums are always matched track by track. If this is disabled, the audio file
+    /// is imported as a whole and only receives the release-level tags.
+    pub split_files: bool,
+}
+
+/// Configuration for cleaning up the source directories after an import.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CleanupConfig {
+    /// Remove source directories that are empty after their files were moved into the library.
+    pub remove_empty_dirs: bool,
+    /// File name patterns (with `*` and `?` wildcards) of files that are not worth keeping.
+    ///
+    /// Directories that only contain such files are considered empty.
+    pub junk_patterns: Vec<String>,
+}
+
+/// How albums are put into the quarantine directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineMode {
+    /// Move the album directory into the quarantine directory.
+    Move,
+    /// Create a symbolic link to the album directory in the quarantine directory.
+    Symlink,
+}
+
+/// Configuration for albums that were skipped during an import.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuarantineConfig {
+    /// Directory that skipped albums are put into. Quarantine is disabled if not set.
+    pub path: Option<String>,
+    /// How albums are put into the quarantine directory.
+    pub mode: QuarantineMode,
+}
+
+/// Configuration for remembering the candidate decisions of the user.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DecisionsConfig {
+    /// Record the selected candidates (and the ones that the user explicitly rejected) and use them
+    /// to rank candidates in later runs. Disabled by default.
+    pub enabled: bool,
+    /// Weight of the past decisions compared to the metadata distance (which has a weight of
+    /// 1.0).
+    pub weight: DistanceWeight,
+    /// Automatically apply the best candidate if the user has selected that release before.
+    pub auto_select: bool,
+}
+
+/// Configuration for importing albums from archives.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArchiveConfig {
+    /// Treat each ZIP archive in the import path as an album and extract it into a temporary
+    /// directory for importing.
+    pub extract: bool,
+    /// Remove the archive after the album has been imported.
+    pub remove_after_import: bool,
+}
+
+/// Configuration for network access (e.g., MusicBrainz, AcoustID and Cover Art Archive requests).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NetworkConfig {
+    /// Proxy URL for all requests (e.g., `http://proxy:3128` or `socks5h://localhost:1080`).
+    pub proxy: Option<String>,
+    /// PEM file with additional CA certificates to trust (e.g., of a TLS-intercepting proxy).
+    pub ca_certificate: Option<PathBuf>,
+}
+
+/// Configuration for submitting fingerprints to AcoustID.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AcoustIdConfig {
+    /// Submit the fingerprints of imported tracks together with their MusicBrainz recording IDs.
+    pub submit: bool,
+    /// The application API key.
+    pub client_key: Option<String>,
+    /// The API key of the user (see <https://acoustid.org/api-key>).
+    pub user_key: Option<String>,
+}
+
+/// Service that provides tags for filling missing genres and moods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnrichmentProviderType {
+    /// Genre tags of the recordings and release groups on ListenBrainz.
+    ListenBrainz,
+    /// Top tags of the album on Last.fm (requires an API key).
+    LastFm,
+}
+
+/// Configuration for filling missing genres and moods from other services.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EnrichmentConfig {
+    /// Providers that are queried (in this order) until one of them returns tags.
+    pub providers: Vec<EnrichmentProviderType>,
+    /// Maximum number of genres that are assigned.
+    pub max_genres: usize,
+    /// Minimum weight of a tag relative to the most frequent tag (from 0.0 to 1.0).
+    pub min_tag_weight: f64,
+    /// Tags that are assigned as mood instead of genre.
+    pub mood_tags: Vec<String>,
+    /// The Last.fm API key (see <https://www.last.fm/api/account/create>).
+    pub lastfm_api_key: Option<String>,
+}
+
+/// External commands that are run on import events.
+///
+/// The commands are run with a shell, and the album is described by environment variables (e.g.,
+/// `HELICON_ALBUM_PATH`, `HELICON_RELEASE_ID` or `HELICON_SIMILARITY`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Commands that are run when a release candidate has been selected for an album.
+    pub on_album_matched: Vec<String>,
+    /// Commands that are run after the files of an album have been moved into the library.
+    pub post_move: Vec<String>,
+    /// Commands that are run after the tags of an album have been written (e.g., `mpc update`).
+    pub post_write: Vec<String>,
+}
+
+/// Configuration for adding imported releases to a MusicBrainz collection.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MusicBrainzCollectionConfig {
+    /// Add each imported release to the collection.
+    pub add_releases: bool,
+    /// Base URL of the MusicBrainz server (e.g., for a mirror or the beta server).
+    pub server_url: String,
+    /// MusicBrainz ID of the release collection (see the URL of the collection page).
+    pub collection_id: Option<String>,
+    /// OAuth access token of the user with the `collection` scope.
+    ///
+    /// Access tokens expire after an hour, so the refresh token should be configured as well.
+    pub access_token: Option<String>,
+    /// OAuth refresh token of the user, which is used to request a new access token when it is
+    /// missing or has expired.
+    pub refresh_token: Option<String>,
+    /// Client ID of the OAuth application that the tokens were issued for.
+    pub client_id: Option<String>,
+    /// Client secret of the OAuth application that the tokens were issued for.
+    pub client_secret: Option<String>,
+}
+
+/// Configuration for watching a drop folder.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WatchConfig {
+    /// Maximum distance of the best candidate for a release to be imported automatically.
+    /// Releases without a sufficiently similar candidate are skipped.
+    pub max_distance: f64,
+    /// Number of seconds without filesystem changes in an album folder before it is imported.
+    pub settle_time: u64,
+    /// File name patterns (with `*` and `?` wildcards) that indicate that an album folder is still
+    /// being copied (e.g., partial downloads or lock files).
+    pub incomplete_patterns: Vec<String>,
+}
+
+/// Configuration for classical music.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClassicalConfig {
+    /// Fill the work and movement tags from the work hierarchy on MusicBrainz.
+    pub enabled: bool,
+    /// Use "Work: Movement" as track title for movements.
+    pub work_movement_titles: bool,
+}
+
+/// Deserialize a list of tag keys from their names (e.g., `album_artist`).
+fn deserialize_tag_keys<'de, D>(deserializer: D) -> Result<Vec<TagKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|name| name.parse().map_err(de::Error::custom))
+        .collect()
+}
+
+/// How a new tag value is merged with the existing value when metadata is assigned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMergePolicy {
+    /// Replace the existing value (or remove it if there is no new value).
+    #[default]
+    Overwrite,
+    /// Only set the value if the tag does not have a value yet.
+    FillIfEmpty,
+    /// Add the new values to the existing values, skipping duplicates.
+    AppendMultivalue,
+}
+
+/// Deserialize a table of tag key names and merge policies (e.g., `genre = "append_multivalue"`).
+fn deserialize_merge_policies<'de, D>(
+    deserializer: D,
+) -> Result<Vec<(TagKey, TagMergePolicy)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, TagMergePolicy>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, policy)| {
+            name.parse()
+                .map(|key| (key, policy))
+                .map_err(de::Error::custom)
+        })
+        .collect()
+}
+
+/// Serialize the merge policies as a table of tag key names and merge policies.
+fn serialize_merge_policies<S>(
+    merge_policies: &[(TagKey, TagMergePolicy)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_map(
+        merge_policies
+            .iter()
+            .map(|(key, policy)| (key.to_string(), policy)),
+    )
+}
+
+/// Additional formats for writing the ReplayGain values.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReplayGainTagsConfig {
+    /// Also write the gains as `RVA2` frames to ID3v2.4 tags (in addition to the `TXXX` frames).
+    pub rva2: bool,
+    /// Also write the gains as `R128_TRACK_GAIN`/`R128_ALBUM_GAIN` tags to the Vorbis comments of
+    /// Opus files (other formats do not define these tags).
+    pub r128: bool,
+}
+
+/// Language and description of the ID3 comment frame (`COMM`) that is used for the comment.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Id3CommentConfig {
+    /// Three-letter ISO 639-2 language code (e.g., `eng`). Comments in other languages are still
+    /// read if there is no comment in this language.
+    pub language: String,
+    /// Description of the comment frame (MusicBrainz Picard uses an empty description).
+    pub description: String,
+}
+
+/// How ID3 popularimeter ratings (0–255) are mapped to stars (0–5).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RatingScale {
+    /// Linear mapping as used by MusicBrainz Picard (e.g., 3 stars are 153).
+    Linear,
+    /// Mapping used by Windows Media Player and many other players (e.g., 3 stars are 128).
+    WindowsMediaPlayer,
+}
+
+impl RatingScale {
+    /// Convert a popularimeter rating (1–255) to stars (1–5). Returns `None` for unrated (0).
+    #[must_use]
+    pub fn stars_from_popm(self, rating: u8) -> Option<u8> {
+        if rating == 0 {
+            return None;
+        }
+        let stars = match self {
+            Self::Linear => {
+                let stars = (u16::from(rating) * 5 + 127) / 255;
+                u8::try_from(stars).unwrap_or(5).max(1)
+            }
+            Self::WindowsMediaPlayer => match rating {
+                1..=31 => 1,
+                32..=95 => 2,
+                96..=159 => 3,
+                160..=223 => 4,
+                _ => 5,
+            },
+        };
+        Some(stars)
+    }
+
+    /// Convert stars (1–5) to a popularimeter rating (1–255).
+    #[must_use]
+    pub fn popm_from_stars(self, stars: u8) -> u8 {
+        let stars = stars.min(5);
+        match self {
+            Self::Linear => u8::try_from(u16::from(stars) * 255 / 5).unwrap_or(u8::MAX),
+            Self::WindowsMediaPlayer => [0, 1, 64, 128, 196, 255][usize::from(stars)],
+        }
+    }
+}
+
+/// Configuration for ratings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RatingConfig {
+    /// Email address that identifies the owner of the rating (ID3 `POPM` frames and Vorbis
+    /// `RATING:<email>` fields).
+    pub email: String,
+    /// How ID3 popularimeter ratings are mapped to stars.
+    pub scale: RatingScale,
+}
+
+/// Field names that are used for the total number of tracks and discs in Vorbis comments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VorbisTotalFields {
+    /// Write `TOTALTRACKS` and `TOTALDISCS` (as MusicBrainz Picard does).
+    #[default]
+    Totaltracks,
+    /// Write `TRACKTOTAL` and `DISCTOTAL`.
+    Tracktotal,
+    /// Write both variants.
+    Both,
+}
+
+/// Formatting of track and disc numbers.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct NumberFormatConfig {
+    /// Minimum number of digits of track and disc numbers, which are padded with leading zeros
+    /// (e.g., `2` for `04`). Use `0` to disable padding.
+    pub pad_width: usize,
+    /// Write the total to the track and disc number tags (e.g., `04/12` instead of `04`). ID3
+    /// tags always store the total this way if it is known.
+    pub include_total: bool,
+}
+
+impl NumberFormatConfig {
+    /// Pad the number with leading zeros.
+    ///
+    /// A total that is part of the value (e.g., `4/12`) is removed. Values that are not numeric
+    /// (e.g., vinyl track numbers like `A1`) are returned unchanged.
+    pub fn pad(&self, number: &str) -> String {
+        let number = number
+            .split_once('/')
+            .map_or(number, |(number, _)| number)
+            .trim();
+        match number.parse::<u32>() {
+            Ok(number) => format!("{number:0width$}", width = self.pad_width),
+            Err(_) => number.to_string(),
+        }
+    }
+
+    /// Format the number for writing it to a tag, optionally followed by the total.
+    pub fn format_tag_value(&self, number: &str, total: Option<&str>) -> String {
+        let number = self.pad(number);
+        match total {
+            Some(total) if self.include_total => format!("{number}/{}", self.pad(total)),
+            _ => number,
+        }
+    }
+}
+
+/// Configuration for tag assignment.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagsConfig {
+    /// Tag keys that are never overwritten or cleared (e.g., `comment` or `rating`).
+    #[serde(deserialize_with = "deserialize_tag_keys")]
+    pub skip: Vec<TagKey>,
+    /// Merge policies for individual tag keys (tags without a policy are overwritten).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_merge_policies",
+        serialize_with = "serialize_merge_policies"
+    )]
+    pub merge_policies: Vec<(TagKey, TagMergePolicy)>,
+    /// Patterns for deriving tag values from the file path if a file does not have tags (e.g.,
+    /// `%artist%/%album%/%track% - %title%`). The first matching pattern is used.
+    pub filename_patterns: Vec<String>,
+    /// Classical music configuration.
+    pub classical: ClassicalConfig,
+    /// Additional ReplayGain formats.
+    pub replay_gain: ReplayGainTagsConfig,
+    /// Formatting of track and disc numbers.
+    pub number_format: NumberFormatConfig,
+    /// Field names for the total number of tracks and discs in Vorbis comments (both variants
+    /// are always read).
+    pub vorbis_total_fields: VorbisTotalFields,
+    /// Also write the field names used by beets (e.g., `ALBUMARTIST_CREDIT` or
+    /// `MUSICBRAINZ_ALBUMTYPE`) to Vorbis comments, so that beets keeps working on the same
+    /// library. These fields are always read if the canonical fields are missing.
+    pub vorbis_beets_fields: bool,
+    /// Match the descriptions of ID3 user-defined text frames (`TXXX`) case-insensitively, so
+    /// that values written by other tools (e.g., `MUSICBRAINZ ALBUM ID`) are found.
+    pub id3_case_insensitive_descriptions: bool,
+    /// Comment frame (`COMM`) that is used for the comment in ID3 tags.
+    pub id3_comment: Id3CommentConfig,
+    /// Store the grouping in the `GRP1` frame of ID3 tags and also write the work title to the
+    /// `TIT1` frame, like iTunes does. The work title is always written to and read from
+    /// `TXXX:WORK` (like Picard does by default), where the grouping is stored in `TIT1` unless
+    /// this is enabled.
+    pub id3_itunes_grouping: bool,
+    /// Ratings (in stars from 0 to 5).
+    pub rating: RatingConfig,
+    /// Album artist of compilations by various artists (e.g., to use a localized name like
+    /// `Verschiedene Interpreten`). The artists of the individual tracks are kept.
+    pub various_artists: String,
+    /// Merge APEv2 tags into the primary tag of MP3 files (ID3v2) and remove them when writing, so
+    /// that the tags cannot become inconsistent.
+    pub strip_ape_tags: bool,
+}
+
 /// The main configuration struct.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AnalyzerConfig {
@@ -317,10 +1035,66 @@ pub struct AnalyzerConfig {
     pub enabled: Vec<AnalyzerType>,
     /// Number of parallel analyzer jobs (use 0 for the number of CPUs)
     pub num_parallel_jobs: usize,
+    /// Calculate the album gain from the tracks that were analyzed successfully if the analysis of
+    /// some tracks failed (instead of not writing any album gain).
+    pub partial_album_gain: bool,
+    /// Reuse the AcoustID fingerprints that are already stored in the file tags instead of
+    /// running the Chromaprint analyzer.
+    pub reuse_existing: bool,
+    /// EBU R 128 analyzer configuration.
+    pub ebur128: EbuR128Config,
+    /// Tempo analyzer configuration.
+    pub tempo: TempoConfig,
+}
+
+/// How the peak amplitude is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeakMode {
+    /// Maximum sample value.
+    Sample,
+    /// True peak (i.e., the maximum of the oversampled signal).
+    True,
+}
+
+/// Configuration for the EBU R 128 analyzer.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EbuR128Config {
+    /// Reference loudness (in LUFS) that the ReplayGain values are calculated for (e.g., -18 for
+    /// ReplayGain 2.0 or -23 for EBU R 128).
+    pub reference_lufs: f64,
+    /// How the ReplayGain peak values are measured.
+    pub peak_mode: PeakMode,
+}
+
+/// Precision of the BPM values that are written to the tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BpmPrecision {
+    /// Round to the nearest integer (e.g., `128`).
+    Integer,
+    /// Keep one decimal place (e.g., `127.9`).
+    OneDecimal,
+}
+
+/// Configuration for the tempo analyzer.
+#[expect(missing_copy_implementations)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TempoConfig {
+    /// Number of seconds from the start of the track that are analyzed (use 0 for the whole
+    /// track).
+    pub window: u32,
+    /// Lowest tempo that is detected (in BPM).
+    pub min_bpm: f64,
+    /// Highest tempo that is detected (in BPM).
+    pub max_bpm: f64,
+    /// Precision of the BPM value that is written to the tags.
+    pub precision: BpmPrecision,
 }
 
 /// Analyzer type.
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AnalyzerType {
     /// Track Length analyzer.
@@ -329,6 +1103,8 @@ pub enum AnalyzerType {
     ChromaprintFingerprint,
     /// EBU R 128 Loudness Analyzer
     EbuR128,
+    /// Tempo (BPM) Analyzer.
+    Tempo,
 }
 
 /// The path configuration struct.
@@ -336,35 +1112,105 @@ pub enum AnalyzerType {
 pub struct PathConfig {
     /// Location of the music library that files will be imported to.
     pub library_path: String,
+    /// Write a `release.json` file with the metadata of the matched release into each imported
+    /// album directory.
+    pub write_release_json: bool,
+    /// How files are transferred into the library.
+    pub transfer_mode: TransferMode,
+    /// What to do if a file already exists at the destination path.
+    pub collision_policy: CollisionPolicy,
+    /// What to do if different releases are formatted to the same album directory during an import
+    /// run.
+    pub album_collision_policy: AlbumCollisionPolicy,
+    /// Compare the SHA-256 checksums of each file before and after transferring it, and record the
+    /// checksum in the import journal.
+    pub verify_transfers: bool,
+    /// Permission bits of transferred files (e.g., `0o644`). If unset, the permissions are not
+    /// changed.
+    pub file_mode: Option<u32>,
+    /// Permission bits of the directories that contain transferred files (e.g., `0o755`). If
+    /// unset, the permissions are not changed.
+    pub dir_mode: Option<u32>,
+    /// Restore the modification time of files after writing their tags, so that tag changes do
+    /// not make backup tools treat the files as modified.
+    pub preserve_mtime: bool,
+    /// File name patterns (with `*` and `?` wildcards) of non-audio files (e.g., cover scans, rip
+    /// logs or cue sheets) that are transferred into the album directory along with the tracks.
+    pub attachment_patterns: Vec<String>,
+    /// Patterns (with `*` and `?` wildcards) of files and directories that are skipped when
+    /// scanning for audio files (e.g., `._*` or `*/demos/*`). Patterns that contain a `/` are
+    /// matched against the path relative to the scanned directory, all other patterns against the
+    /// file name. Additional patterns can be placed in a `.heliconignore` file in any directory.
+    pub ignore_patterns: Vec<String>,
     /// Formats for file paths.
     #[serde(flatten)]
     pub format: PathTemplate,
 }
 
+/// How files are transferred into the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferMode {
+    /// Move the files (copy and remove the source if the destination is on another filesystem).
+    Move,
+    /// Copy the files and keep the source files.
+    Copy,
+    /// Create hard links to the source files. Files whose tags change (or all files, if a file
+    /// mode is set) are copied instead, so that the source files are never modified.
+    Hardlink,
+    /// Create reflinks (copy-on-write clones) of the source files, or copy them if the filesystem
+    /// does not support reflinks.
+    Reflink,
+}
+
+/// What to do if a file already exists at the destination path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Leave the existing file alone and do not transfer the file.
+    Skip,
+    /// Replace the existing file.
+    Overwrite,
+    /// Append a numeric suffix (e.g., ` (1)`) to the destination file name.
+    Rename,
+}
+
+/// What to do if different releases are formatted to the same album directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumCollisionPolicy {
+    /// Import the release into the same directory anyway.
+    Ignore,
+    /// Ask the user how to resolve the collision (behaves like `disambiguate` if the import is not
+    /// interactive).
+    Ask,
+    /// Append the release year (or the MusicBrainz release ID if that is not sufficient) to the
+    /// album directory.
+    Disambiguate,
+    /// Do not import the release.
+    Skip,
+}
+
 impl PathConfig {
+    /// Returns the library path (with `~` expanded to the home directory).
+    pub fn library_path(&self) -> crate::Result<PathBuf> {
+        expanduser(&self.library_path).map_err(crate::Error::Io)
+    }
+
     /// Convenience method to format a path based on the current path configuration.
     pub fn format_path(
         &self,
         values: &PathFormatterValues<'_>,
         file_extension: Option<impl AsRef<str>>,
     ) -> crate::Result<PathBuf> {
-        let library_path = expanduser(&self.library_path).map_err(crate::Error::Io)?;
+        let library_path = self.library_path()?;
+        // We cannot use `PathBuf::set_extension(ext)` here, because if there already is an
+        // extension (e.g., if the track title contains a dot), that extension would be replaced
+        // instead of appended.
         self.format
             .formatter()
-            .format(values)
+            .format(values, file_extension.as_ref().map(AsRef::as_ref))
             .map(|path| library_path.join(path))
-            .map(|path| match file_extension {
-                Some(ext) => {
-                    // We cannot use `PathBuf::set_extension(ext)` here, because if there
-                    // already is an extension (e.g., if the track title contains a dot),
-                    // that extension would be replaced instead of appended.
-                    let mut path_with_ext = path.into_os_string();
-                    path_with_ext.push(".");
-                    path_with_ext.push(ext.as_ref());
-                    PathBuf::from(path_with_ext)
-                }
-                None => path,
-            })
             .map_err(crate::Error::TemplateFormattingFailed)
     }
 }
@@ -376,6 +1222,26 @@ pub struct PathTemplateConfig {
     pub album_format: String,
     /// Format for compilation file paths.
     pub compilation_format: String,
+    /// Options for sanitizing the formatted paths.
+    pub sanitize: PathSanitizationConfig,
+}
+
+/// Options for sanitizing formatted paths, so that they are valid on the target filesystem.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PathSanitizationConfig {
+    /// Replacement for characters that are not allowed in paths (may be empty to remove them).
+    pub replacement: String,
+    /// Maximum length of a single path component in bytes, including the file extension (use 0 for
+    /// no limit).
+    pub max_component_length: usize,
+    /// Maximum length of the formatted path in bytes, relative to the library path and without
+    /// the file extension (use 0 for no limit).
+    pub max_path_length: usize,
+    /// Make paths compatible with Microsoft Windows (i.e., also replace characters like `:` and
+    /// avoid trailing dots and reserved names like `CON`), even on other operating systems.
+    pub windows_compatible: bool,
+    /// Transliterate non-ASCII characters to ASCII (e.g., `Björk` becomes `Bjork`).
+    pub ascii_only: bool,
 }
 
 /// The main configuration struct.
@@ -389,8 +1255,34 @@ pub struct Config {
     pub lookup: LookupConfig,
     /// Weight configuration.
     pub weights: DistanceWeights,
+    /// String similarity metric configuration.
+    pub string_metrics: StringMetricsConfig,
     /// UI configuration.
     pub user_interface: UiConfig,
+    /// Cue sheet configuration.
+    pub cue: CueConfig,
+    /// Cleanup configuration.
+    pub cleanup: CleanupConfig,
+    /// Watch mode configuration.
+    pub watch: WatchConfig,
+    /// Quarantine configuration.
+    pub quarantine: QuarantineConfig,
+    /// Tag assignment configuration.
+    pub tags: TagsConfig,
+    /// Configuration for remembering candidate decisions.
+    pub decisions: DecisionsConfig,
+    /// AcoustID submission configuration.
+    pub acoustid: AcoustIdConfig,
+    /// MusicBrainz collection configuration.
+    pub musicbrainz_collection: MusicBrainzCollectionConfig,
+    /// Genre and mood enrichment configuration.
+    pub enrichment: EnrichmentConfig,
+    /// Import hook configuration.
+    pub hooks: HooksConfig,
+    /// Archive import configuration.
+    pub archives: ArchiveConfig,
+    /// Network configuration.
+    pub network: NetworkConfig,
 }
 
 impl Default for Config {
@@ -403,29 +1295,170 @@ impl Default for Config {
 mod builder {
     use super::{Config, ConfigError, DEFAULT_CONFIG};
     use config::{
-        builder::DefaultState, Config as BaseConfig, ConfigBuilder as BaseConfigBuilder, File,
-        FileFormat,
+        builder::DefaultState, Config as BaseConfig, ConfigBuilder as BaseConfigBuilder,
+        Environment, File, FileFormat, Map, Source, Value,
     };
-    use std::path::Path;
+    use expanduser::expanduser;
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
     /// Builder for the configuration object.
     #[derive(Debug)]
-    pub struct ConfigBuilder(BaseConfigBuilder<DefaultState>);
+    pub struct ConfigBuilder {
+        /// The underlying builder that merges the configuration sources.
+        builder: BaseConfigBuilder<DefaultState>,
+        /// Name of the library whose overrides (from the `[libraries.<name>]` table) are applied.
+        library: Option<String>,
+        /// Apply overrides from `HELICON_*` environment variables.
+        environment: Option<Environment>,
+    }
 
     impl Default for ConfigBuilder {
         fn default() -> Self {
-            Self(BaseConfig::builder())
+            Self {
+                builder: BaseConfig::builder(),
+                library: None,
+                environment: None,
+            }
         }
     }
 
+    /// Prefix of environment variables that override configuration values.
+    const ENVIRONMENT_PREFIX: &str = "HELICON";
+
+    /// Separator between the sections and keys in the names of environment variables (e.g.,
+    /// `HELICON_PATHS__LIBRARY_PATH` for `paths.library_path`).
+    const ENVIRONMENT_SEPARATOR: &str = "__";
+
+    /// Separator between the values of lists in environment variables (e.g.,
+    /// `HELICON_TAGS__SKIP=comment,rating`).
+    const ENVIRONMENT_LIST_SEPARATOR: &str = ",";
+
+    /// Get the dotted keys of all lists in the table (e.g., `tags.skip`).
+    fn list_keys(prefix: &str, table: &toml::Table) -> Vec<String> {
+        table
+            .iter()
+            .flat_map(|(key, value)| {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                match value {
+                    toml::Value::Table(table) => list_keys(&key, table),
+                    toml::Value::Array(_) => vec![key],
+                    _ => vec![],
+                }
+            })
+            .collect()
+    }
+
+    /// Create the source for the `HELICON_*` environment variables.
+    ///
+    /// All values are read as strings and only converted when the configuration is deserialized,
+    /// so that string values that look like numbers or booleans (e.g., `007`) are kept as they
+    /// are.
+    fn environment() -> Environment {
+        Environment::with_prefix(ENVIRONMENT_PREFIX)
+            .prefix_separator("_")
+            .separator(ENVIRONMENT_SEPARATOR)
+    }
+
+    /// Split the value of an environment variable at the list separator if the key is a list in
+    /// the default configuration.
+    fn split_list_value(
+        list_keys: &[String],
+        key: &str,
+        value: Value,
+    ) -> Result<Value, ConfigError> {
+        if !list_keys.iter().any(|list_key| list_key == key) {
+            return Ok(value);
+        }
+        let values = value
+            .into_string()?
+            .split(ENVIRONMENT_LIST_SEPARATOR)
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        Ok(Value::from(values))
+    }
+
+    /// Get the paths of the files that are included by a configuration file (using the `include`
+    /// key).
+    ///
+    /// Relative paths are resolved relative to the directory of the including file. Errors are
+    /// ignored here, because they are reported when the file itself is loaded.
+    fn included_files(path: &Path) -> Vec<PathBuf> {
+        let Some(table) = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| text.parse::<toml::Table>().ok())
+        else {
+            return vec![];
+        };
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        table
+            .get("include")
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|value| {
+                let include = value.as_str();
+                if include.is_none() {
+                    log::warn!("Ignoring invalid include {value} in {}", path.display());
+                }
+                include
+            })
+            .map(|include| base_dir.join(expanduser(include).unwrap_or_else(|_| include.into())))
+            .collect()
+    }
+
+    /// Flatten a table of overrides into pairs of dotted keys and (non-table) values, so that
+    /// overriding a single value in a section keeps the other values of that section.
+    fn flatten_overrides(prefix: &str, table: Map<String, Value>) -> Vec<(String, Value)> {
+        table
+            .into_iter()
+            .flat_map(|(key, value)| {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                match value.clone().into_table() {
+                    Ok(table) => flatten_overrides(&key, table),
+                    Err(_) => vec![(key, value)],
+                }
+            })
+            .collect()
+    }
+
     impl ConfigBuilder {
         /// Add a file to be loaded to the configuration builder. Files added later will override
         /// values from previous files.
-        pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Self {
-            log::debug!("Reading config from file: {}", path.as_ref().display());
-            self.0 = self
-                .0
-                .add_source(File::from(path.as_ref()).format(FileFormat::Toml));
+        ///
+        /// Files listed in the `include` key of the file are loaded before the file itself, so
+        /// that the file can override values from the included files.
+        pub fn with_file<P: AsRef<Path>>(self, path: P) -> Self {
+            self.with_file_and_includes(path.as_ref(), &mut Vec::new())
+        }
+
+        /// Add a file and (recursively) the files that it includes. The `parents` are the files
+        /// that are currently being included, which is used to detect include cycles.
+        fn with_file_and_includes(mut self, path: &Path, parents: &mut Vec<PathBuf>) -> Self {
+            let canonical_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+            if parents.contains(&canonical_path) {
+                log::warn!("Ignoring recursive include of {}", path.display());
+                return self;
+            }
+
+            parents.push(canonical_path);
+            for include in included_files(path) {
+                self = self.with_file_and_includes(&include, parents);
+            }
+            let _unused = parents.pop();
+
+            log::debug!("Reading config from file: {}", path.display());
+            self.builder = self
+                .builder
+                .add_source(File::from(path).format(FileFormat::Toml));
             self
         }
 
@@ -436,8 +1469,8 @@ mod builder {
                 "Reading config from string ({} bytes)",
                 value.as_ref().len()
             );
-            self.0 = self
-                .0
+            self.builder = self
+                .builder
                 .add_source(File::from_str(value.as_ref(), FileFormat::Toml));
             self
         }
@@ -448,9 +1481,70 @@ mod builder {
             self.with_str(DEFAULT_CONFIG)
         }
 
+        /// Apply the overrides for a library, i.e., the values from the `[libraries.<name>]`
+        /// table (e.g., `[libraries.lossless.paths]`), on top of all other configuration sources.
+        pub fn with_library<S: Into<String>>(mut self, name: S) -> Self {
+            self.library = Some(name.into());
+            self
+        }
+
+        /// Apply overrides from environment variables on top of all other configuration sources
+        /// (including the library overrides).
+        ///
+        /// The variable names consist of the `HELICON_` prefix and the upper-case key path,
+        /// separated by double underscores (e.g., `HELICON_PATHS__LIBRARY_PATH` or
+        /// `HELICON_LOOKUP__MAX_CANDIDATES`). Values of lists are separated by commas (e.g.,
+        /// `HELICON_TAGS__SKIP=comment,rating`).
+        pub fn with_environment(mut self) -> Self {
+            self.environment = Some(environment());
+            self
+        }
+
+        /// Apply overrides from the given variables instead of the environment variables of the
+        /// process.
+        #[cfg(test)]
+        pub fn with_environment_variables(mut self, variables: Map<String, String>) -> Self {
+            self.environment = Some(environment().source(Some(variables)));
+            self
+        }
+
         /// Actually load the configuration from the builder.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if a configuration source cannot be loaded, the configuration is
+        /// invalid or the selected library is not configured.
         pub fn build(self) -> Result<Config, ConfigError> {
-            Ok(self.0.build().unwrap().try_deserialize::<Config>()?)
+            let config = self.builder.build()?;
+
+            let mut overrides = match &self.library {
+                Some(name) => {
+                    flatten_overrides("", config.get_table(&format!("libraries.{name}"))?)
+                }
+                None => vec![],
+            };
+            if let Some(environment) = self.environment {
+                let default_config = DEFAULT_CONFIG.parse::<toml::Table>().unwrap_or_default();
+                let list_keys = list_keys("", &default_config);
+                for (key, value) in environment.collect()? {
+                    log::debug!("Overriding config value {key} from environment");
+                    let value = split_list_value(&list_keys, &key, value)?;
+                    overrides.push((key, value));
+                }
+            }
+
+            let config = if overrides.is_empty() {
+                config
+            } else {
+                overrides
+                    .into_iter()
+                    .try_fold(
+                        BaseConfig::builder().add_source(config),
+                        |builder, (key, value)| builder.set_override(key, value),
+                    )?
+                    .build()?
+            };
+            Ok(config.try_deserialize::<Config>()?)
         }
     }
 }
@@ -491,6 +1585,27 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_number_format() {
+        let number_format = NumberFormatConfig {
+            pad_width: 2,
+            include_total: false,
+        };
+        assert_eq!(number_format.pad("4"), "04");
+        assert_eq!(number_format.pad("4/12"), "04");
+        assert_eq!(number_format.pad("123"), "123");
+        assert_eq!(number_format.pad("A1"), "A1");
+        assert_eq!(number_format.format_tag_value("4", Some("12")), "04");
+
+        let number_format = NumberFormatConfig {
+            pad_width: 0,
+            include_total: true,
+        };
+        assert_eq!(number_format.pad("04"), "4");
+        assert_eq!(number_format.format_tag_value("4", Some("12")), "4/12");
+        assert_eq!(number_format.format_tag_value("4", None), "4");
+    }
+
     #[test]
     fn test_load_default() {
         let config = Config::load_default().unwrap();
@@ -504,4 +1619,146 @@ mod tests {
         let serialized = toml::to_string_pretty(&config).unwrap();
         println!("{serialized}");
     }
+
+    #[test]
+    fn test_build_with_includes() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp_dir.path().join("conf.d")).unwrap();
+        std::fs::write(
+            tmp_dir.path().join("conf.d/paths.toml"),
+            "include = [\"../config.toml\"]\n[paths]\nlibrary_path = \"/included\"\n[paths.sanitize]\nascii_only = true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            tmp_dir.path().join("config.toml"),
+            "include = [\"conf.d/paths.toml\"]\n[paths]\nlibrary_path = \"/main\"\n",
+        )
+        .unwrap();
+
+        let config = Config::builder()
+            .with_defaults()
+            .with_file(tmp_dir.path().join("config.toml"))
+            .build()
+            .unwrap();
+        assert_eq!(config.paths.library_path, "/main");
+        assert!(config.paths.sanitize.ascii_only);
+    }
+
+    #[test]
+    fn test_build_with_library() {
+        let builder = || {
+            Config::builder().with_defaults().with_str(
+                r#"
+                [libraries.lossless.paths]
+                library_path = "/music/lossless"
+
+                [libraries.lossless.lookup]
+                max_candidates = 3
+                "#,
+            )
+        };
+        let default_config = builder().build().unwrap();
+        let config = builder().with_library("lossless").build().unwrap();
+        assert_eq!(config.paths.library_path, "/music/lossless");
+        assert_eq!(config.paths.album_format, default_config.paths.album_format);
+        assert_eq!(config.lookup.max_candidates(), 3);
+        assert_eq!(
+            default_config.lookup.max_candidates(),
+            usize::from(default_config.lookup.release_candidate_limit)
+        );
+
+        assert!(builder().with_library("mp3").build().is_err());
+    }
+
+    #[test]
+    fn test_build_with_environment() {
+        let variables = [
+            ("HELICON_TAGS__VARIOUS_ARTISTS", "Verschiedene Interpreten"),
+            ("HELICON_LOOKUP__RELEASE_CANDIDATE_LIMIT", "7"),
+            ("HELICON_TAGS__SKIP", "comment,rating"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let config = Config::builder()
+            .with_defaults()
+            .with_str("[tags]\nvarious_artists = \"VA\"\n")
+            .with_environment_variables(variables)
+            .build()
+            .unwrap();
+        assert_eq!(config.tags.various_artists, "Verschiedene Interpreten");
+        assert_eq!(config.lookup.release_candidate_limit, 7);
+        assert_eq!(config.tags.skip, vec![TagKey::Comment, TagKey::Rating]);
+
+        // String values that look like numbers are not converted.
+        let variables = [
+            ("HELICON_TAGS__VARIOUS_ARTISTS", "007"),
+            ("HELICON_TAGS__SKIP", "comment"),
+            ("HELICON_USER_INTERFACE__SIMPLE_UI", "true"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let config = Config::builder()
+            .with_defaults()
+            .with_environment_variables(variables)
+            .build()
+            .unwrap();
+        assert_eq!(config.tags.various_artists, "007");
+        assert_eq!(config.tags.skip, vec![TagKey::Comment]);
+        assert!(config.user_interface.simple_ui);
+
+        let config = Config::builder().with_defaults().build().unwrap();
+        assert_eq!(config.tags.various_artists, "Various Artists");
+    }
+
+    #[test]
+    fn test_deserialize_tags_config() {
+        let config: TagsConfig = toml::from_str(
+            r#"
+            skip = ["comment", "Rating", "album_artist"]
+            filename_patterns = []
+
+            [merge_policies]
+            genre = "append_multivalue"
+            mood = "fill_if_empty"
+
+            [classical]
+            enabled = false
+            work_movement_titles = false
+
+            [replay_gain]
+            rva2 = false
+            r128 = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.skip,
+            vec![TagKey::Comment, TagKey::Rating, TagKey::AlbumArtist]
+        );
+        assert_eq!(
+            config.merge_policies,
+            vec![
+                (TagKey::Genre, TagMergePolicy::AppendMultivalue),
+                (TagKey::Mood, TagMergePolicy::FillIfEmpty),
+            ]
+        );
+
+        let result = toml::from_str::<TagsConfig>(
+            r#"
+            skip = ["no_such_key"]
+            filename_patterns = []
+
+            [classical]
+            enabled = false
+            work_movement_titles = false
+
+            [replay_gain]
+            rva2 = false
+            r128 = false
+            "#,
+        );
+        assert!(result.is_err());
+    }
 }
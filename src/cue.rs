@@ -0,0 +1,909 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Support for single-file albums that are described by a cue sheet.
+
+use crate::analyzer;
+use crate::tag::memory::MemoryTag;
+use crate::tag::TagKey;
+use std::fs;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Number of frames per second in cue sheet timestamps (`mm:ss:ff`).
+const FRAMES_PER_SECOND: u64 = 75;
+
+/// Tag keys that apply to the whole release, and are therefore written to the audio file of a
+/// single-file album that is imported without splitting it.
+pub const ALBUM_TAG_KEYS: [TagKey; 19] = [
+    TagKey::Album,
+    TagKey::AlbumArtist,
+    TagKey::AlbumArtistSortOrder,
+    TagKey::AlbumSortOrder,
+    TagKey::Asin,
+    TagKey::Barcode,
+    TagKey::CatalogNumber,
+    TagKey::Compilation,
+    TagKey::Genre,
+    TagKey::MusicBrainzReleaseArtistId,
+    TagKey::MusicBrainzReleaseGroupId,
+    TagKey::MusicBrainzReleaseId,
+    TagKey::RecordLabel,
+    TagKey::ReleaseCountry,
+    TagKey::ReleaseDate,
+    TagKey::ReleaseYear,
+    TagKey::ReleaseStatus,
+    TagKey::ReleaseType,
+    TagKey::Script,
+];
+
+/// Encountered when a cue sheet cannot be parsed or split.
+#[derive(Error, Debug)]
+pub enum CueError {
+    /// A line of the cue sheet is malformed.
+    #[error("Malformed cue sheet (line {0})")]
+    MalformedLine(usize),
+    /// A track does not have an `INDEX 01` entry.
+    #[error("Track {0} has no start index")]
+    MissingIndex(u32),
+    /// The cue sheet does not contain any tracks.
+    #[error("Cue sheet does not contain any tracks")]
+    NoTracks,
+    /// The tracks of the cue sheet are spread across multiple audio files.
+    #[error("Cue sheet references multiple audio files")]
+    MultipleFiles,
+    /// I/O Error.
+    #[error("Input/Output error ({:?})", .0)]
+    Io(#[from] io::Error),
+}
+
+/// A track from a cue sheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CueTrack {
+    /// Track number.
+    pub number: u32,
+    /// Track title.
+    pub title: Option<String>,
+    /// Track performer.
+    pub performer: Option<String>,
+    /// Track songwriter.
+    pub songwriter: Option<String>,
+    /// International Standard Recording Code.
+    pub isrc: Option<String>,
+    /// The audio file that contains this track.
+    pub file: Option<String>,
+    /// Start of the track (`INDEX 01`) in frames (1/75 seconds).
+    pub start_frames: u64,
+}
+
+/// A parsed cue sheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CueSheet {
+    /// Album title.
+    pub title: Option<String>,
+    /// Album performer.
+    pub performer: Option<String>,
+    /// Media catalog number.
+    pub catalog_number: Option<String>,
+    /// Release date (from `REM DATE`).
+    pub date: Option<String>,
+    /// Genre (from `REM GENRE`).
+    pub genre: Option<String>,
+    /// The tracks of the cue sheet.
+    pub tracks: Vec<CueTrack>,
+}
+
+/// Remove surrounding whitespace and quotes from a value.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Parse the file name from the arguments of a `FILE` command (e.g., `"foo bar.flac" WAVE`).
+fn parse_file_name(args: &str) -> String {
+    if let Some(quoted) = args.strip_prefix('"') {
+        if let Some((file_name, _)) = quoted.split_once('"') {
+            return file_name.to_string();
+        }
+    }
+
+    args.rsplit_once(char::is_whitespace)
+        .map_or(args, |(file_name, _)| file_name)
+        .trim()
+        .to_string()
+}
+
+/// Parse a cue sheet timestamp in the form `mm:ss:ff` and return the number of frames.
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let mut parts = value.trim().splitn(3, ':');
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let seconds = parts.next()?.parse::<u64>().ok()?;
+    let frames = parts.next()?.parse::<u64>().ok()?;
+    if seconds >= 60 || frames >= FRAMES_PER_SECOND {
+        return None;
+    }
+
+    Some((minutes * 60 + seconds) * FRAMES_PER_SECOND + frames)
+}
+
+impl CueSheet {
+    /// Read and parse the cue sheet from the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn read_from_path(path: impl AsRef<Path>) -> Result<Self, CueError> {
+        let data = fs::read(path)?;
+        // Cue sheets are frequently not encoded in UTF-8, so we need to be lenient here.
+        Self::parse(&String::from_utf8_lossy(&data))
+    }
+
+    /// Parse a cue sheet from a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cue sheet is malformed.
+    pub fn parse(input: &str) -> Result<Self, CueError> {
+        let mut sheet = CueSheet::default();
+        let mut current_file: Option<String> = None;
+        let mut current_track: Option<(CueTrack, Option<u64>)> = None;
+
+        for (line_index, line) in input.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = line.trim_start_matches('\u{feff}').trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (command, args) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(command, args)| (command, args.trim()));
+            match command.to_ascii_uppercase().as_str() {
+                "REM" => {
+                    let (key, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+                    match key.to_ascii_uppercase().as_str() {
+                        "DATE" => sheet.date = Some(unquote(value)),
+                        "GENRE" => sheet.genre = Some(unquote(value)),
+                        _ => (),
+                    }
+                }
+                "CATALOG" => sheet.catalog_number = Some(unquote(args)),
+                "FILE" => current_file = Some(parse_file_name(args)),
+                "TRACK" => {
+                    if let Some(track) = current_track.take() {
+                        sheet.tracks.push(finish_track(track)?);
+                    }
+                    let number = args
+                        .split_whitespace()
+                        .next()
+                        .and_then(|number| number.parse::<u32>().ok())
+                        .ok_or(CueError::MalformedLine(line_number))?;
+                    let track = CueTrack {
+                        number,
+                        file: current_file.clone(),
+                        ..Default::default()
+                    };
+                    current_track = Some((track, None));
+                }
+                "TITLE" => match &mut current_track {
+                    Some((track, _)) => track.title = Some(unquote(args)),
+                    None => sheet.title = Some(unquote(args)),
+                },
+                "PERFORMER" => match &mut current_track {
+                    Some((track, _)) => track.performer = Some(unquote(args)),
+                    None => sheet.performer = Some(unquote(args)),
+                },
+                "SONGWRITER" => {
+                    if let Some((track, _)) = &mut current_track {
+                        track.songwriter = Some(unquote(args));
+                    }
+                }
+                "ISRC" => {
+                    if let Some((track, _)) = &mut current_track {
+                        track.isrc = Some(unquote(args));
+                    }
+                }
+                "INDEX" => {
+                    let Some((_, start_frames)) = &mut current_track else {
+                        return Err(CueError::MalformedLine(line_number));
+                    };
+                    let mut parts = args.split_whitespace();
+                    let index = parts
+                        .next()
+                        .and_then(|index| index.parse::<u32>().ok())
+                        .ok_or(CueError::MalformedLine(line_number))?;
+                    let timestamp = parts
+                        .next()
+                        .and_then(parse_timestamp)
+                        .ok_or(CueError::MalformedLine(line_number))?;
+                    if index == 1 {
+                        *start_frames = Some(timestamp);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(track) = current_track.take() {
+            sheet.tracks.push(finish_track(track)?);
+        }
+
+        if sheet.tracks.is_empty() {
+            return Err(CueError::NoTracks);
+        }
+
+        Ok(sheet)
+    }
+
+    /// Returns the name of the audio file that contains all tracks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tracks are spread across multiple files or if no file is
+    /// referenced.
+    pub fn audio_file(&self) -> Result<&str, CueError> {
+        let mut files = self.tracks.iter().filter_map(|track| track.file.as_deref());
+        let file = files.next().ok_or(CueError::NoTracks)?;
+        if files.any(|other| other != file) {
+            return Err(CueError::MultipleFiles);
+        }
+
+        Ok(file)
+    }
+
+    /// Create an in-memory tag with the metadata of the given track.
+    pub fn track_tag(&self, track: &CueTrack) -> MemoryTag {
+        MemoryTag::new()
+            .with_value(TagKey::Album, self.title.as_deref())
+            .with_value(TagKey::AlbumArtist, self.performer.as_deref())
+            .with_value(TagKey::CatalogNumber, self.catalog_number.as_deref())
+            .with_value(TagKey::ReleaseDate, self.date.as_deref())
+            .with_value(TagKey::Genre, self.genre.as_deref())
+            .with_value(TagKey::TrackTitle, track.title.as_deref())
+            .with_value(
+                TagKey::Artist,
+                track.performer.as_deref().or(self.performer.as_deref()),
+            )
+            .with_value(TagKey::Composer, track.songwriter.as_deref())
+            .with_value(TagKey::Isrc, track.isrc.as_deref())
+            .with_value(TagKey::TrackNumber, Some(track.number.to_string()))
+            .with_value(TagKey::TotalTracks, Some(self.tracks.len().to_string()))
+    }
+
+    /// Returns the name of the file that the given track is split into.
+    ///
+    /// The name consists of the name of the audio file and the track number. The extension
+    /// depends on the format that the track is written in (see [`CueSheet::split_audio_file`]).
+    pub fn track_file_name(audio_path: &Path, track: &CueTrack) -> String {
+        let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = if is_flac(audio_path) { "flac" } else { "wav" };
+        format!("{stem} - {number:02}.{extension}", number = track.number)
+    }
+
+    /// Split the audio file into one file per track and write them to the output directory.
+    ///
+    /// FLAC files are split into (uncompressed) FLAC files, all other formats into WAV files. The
+    /// bit depth of the source file is preserved. The returned paths are in the same order as the
+    /// tracks of the cue sheet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the audio file cannot be decoded or if the output files cannot be
+    /// written.
+    pub fn split_audio_file(
+        &self,
+        audio_path: impl AsRef<Path>,
+        output_dir: impl AsRef<Path>,
+    ) -> crate::Result<Vec<PathBuf>> {
+        let audio_path = audio_path.as_ref();
+        fs::create_dir_all(output_dir.as_ref())?;
+        let output_paths: Vec<PathBuf> = self
+            .tracks
+            .iter()
+            .map(|track| {
+                output_dir
+                    .as_ref()
+                    .join(Self::track_file_name(audio_path, track))
+            })
+            .collect();
+        let write_flac = is_flac(audio_path);
+
+        let mut current_writer: Option<(usize, TrackWriter)> = None;
+        let mut position: u64 = 0;
+        analyzer::decode_interleaved(
+            audio_path,
+            |sample_rate, channels, bits_per_sample, samples| {
+                let track_starts: Vec<u64> = self
+                    .tracks
+                    .iter()
+                    .map(|track| track.start_frames * u64::from(sample_rate) / FRAMES_PER_SECOND)
+                    .collect();
+                let buffer_frames = samples.len() / channels;
+
+                let mut offset = 0;
+                while offset < buffer_frames {
+                    let current_position = position + offset as u64;
+                    let remaining_frames = buffer_frames - offset;
+                    let Some(track_index) = track_starts
+                        .iter()
+                        .rposition(|&start| start <= current_position)
+                    else {
+                        // Skip audio before the first track (e.g., a hidden pregap).
+                        let skipped_frames = usize::try_from(track_starts[0] - current_position)
+                            .map_or(remaining_frames, |frames| frames.min(remaining_frames));
+                        offset += skipped_frames;
+                        continue;
+                    };
+
+                    if current_writer.as_ref().map(|(index, _)| *index) != Some(track_index) {
+                        if let Some((_, writer)) = current_writer.take() {
+                            writer.finalize()?;
+                        }
+                        let path = &output_paths[track_index];
+                        let writer = if write_flac {
+                            TrackWriter::Flac(FlacWriter::create(
+                                path,
+                                sample_rate,
+                                channels,
+                                bits_per_sample,
+                            )?)
+                        } else {
+                            TrackWriter::Wav(WavWriter::create(
+                                path,
+                                sample_rate,
+                                channels,
+                                bits_per_sample,
+                            )?)
+                        };
+                        current_writer = Some((track_index, writer));
+                    }
+
+                    let frame_count = track_starts
+                        .get(track_index + 1)
+                        .and_then(|&next_start| usize::try_from(next_start - current_position).ok())
+                        .map_or(remaining_frames, |frames| frames.min(remaining_frames));
+                    if let Some((_, writer)) = &mut current_writer {
+                        writer.write_samples(
+                            &samples[offset * channels..(offset + frame_count) * channels],
+                        )?;
+                    }
+                    offset += frame_count;
+                }
+
+                position += buffer_frames as u64;
+                Ok(())
+            },
+        )?;
+
+        if let Some((_, writer)) = current_writer.take() {
+            writer.finalize()?;
+        }
+
+        Ok(output_paths)
+    }
+}
+
+/// Finish parsing the track by checking that the start index is present.
+fn finish_track((mut track, start_frames): (CueTrack, Option<u64>)) -> Result<CueTrack, CueError> {
+    track.start_frames = start_frames.ok_or(CueError::MissingIndex(track.number))?;
+    Ok(track)
+}
+
+/// Returns `true` if the path has a `.flac` extension.
+fn is_flac(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("flac"))
+}
+
+/// Convert a sample that is scaled to the full range of `i32` to the given bit depth.
+fn scale_sample(sample: i32, bits_per_sample: u32) -> i32 {
+    sample >> (32 - bits_per_sample)
+}
+
+/// Writer for the audio file of a split track.
+#[derive(Debug)]
+enum TrackWriter {
+    /// WAV file.
+    Wav(WavWriter),
+    /// FLAC file.
+    Flac(FlacWriter),
+}
+
+impl TrackWriter {
+    /// Append interleaved samples (scaled to the full range of `i32`).
+    fn write_samples(&mut self, samples: &[i32]) -> io::Result<()> {
+        match self {
+            Self::Wav(writer) => writer.write_samples(samples),
+            Self::Flac(writer) => writer.write_samples(samples),
+        }
+    }
+
+    /// Write the remaining data, update the header and flush the file.
+    fn finalize(self) -> io::Result<()> {
+        match self {
+            Self::Wav(writer) => writer.finalize(),
+            Self::Flac(writer) => writer.finalize(),
+        }
+    }
+}
+
+/// Minimal writer for PCM WAV files.
+#[derive(Debug)]
+struct WavWriter {
+    /// The underlying file writer.
+    writer: BufWriter<fs::File>,
+    /// Number of bits per sample (rounded up to whole bytes).
+    bits_per_sample: u32,
+    /// Number of bytes in the data chunk.
+    data_size: u32,
+}
+
+impl WavWriter {
+    /// Create the file and write the header.
+    fn create(
+        path: &Path,
+        sample_rate: u32,
+        channels: usize,
+        bits_per_sample: u32,
+    ) -> io::Result<Self> {
+        let channels = u16::try_from(channels).map_err(io::Error::other)?;
+        let bits_per_sample = bits_per_sample.div_ceil(8).clamp(1, 4) * 8;
+        let block_align = channels
+            .checked_mul(u16::try_from(bits_per_sample / 8).map_err(io::Error::other)?)
+            .ok_or_else(|| io::Error::other("Too many channels"))?;
+        let byte_rate = sample_rate * u32::from(block_align);
+
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(
+            &u16::try_from(bits_per_sample)
+                .map_err(io::Error::other)?
+                .to_le_bytes(),
+        )?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            bits_per_sample,
+            data_size: 0,
+        })
+    }
+
+    /// Append interleaved samples (scaled to the full range of `i32`) to the data chunk.
+    fn write_samples(&mut self, samples: &[i32]) -> io::Result<()> {
+        let bytes_per_sample = (self.bits_per_sample / 8) as usize;
+        for &sample in samples {
+            let mut sample = scale_sample(sample, self.bits_per_sample);
+            if bytes_per_sample == 1 {
+                // 8-bit WAV files use unsigned samples.
+                sample += 128;
+            }
+            self.writer
+                .write_all(&sample.to_le_bytes()[..bytes_per_sample])?;
+        }
+        self.data_size = u32::try_from(samples.len() * bytes_per_sample)
+            .ok()
+            .and_then(|size| self.data_size.checked_add(size))
+            .ok_or_else(|| io::Error::other("WAV file too large"))?;
+        Ok(())
+    }
+
+    /// Update the chunk sizes in the header and flush the file.
+    fn finalize(mut self) -> io::Result<()> {
+        let _ = self.writer.seek(SeekFrom::Start(4))?;
+        self.writer
+            .write_all(&(36 + self.data_size).to_le_bytes())?;
+        let _ = self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&self.data_size.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+/// Number of samples per channel in each FLAC frame.
+const FLAC_BLOCK_SIZE: usize = 4096;
+
+/// Offset of the STREAMINFO field that contains the total number of samples (and the sample
+/// rate, number of channels and bit depth).
+const FLAC_STREAM_PARAMETERS_OFFSET: u64 = 18;
+
+/// Lookup table for the CRC-8 of FLAC frame headers (polynomial `x^8 + x^2 + x + 1`).
+const FLAC_CRC8_TABLE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        #[expect(clippy::cast_possible_truncation)]
+        let mut crc = index as u8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x07
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Lookup table for the CRC-16 of FLAC frames (polynomial `x^16 + x^15 + x^2 + 1`).
+const FLAC_CRC16_TABLE: [u16; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        #[expect(clippy::cast_possible_truncation)]
+        let mut crc = (index as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ 0x8005
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Calculate the CRC-8 of a FLAC frame header.
+fn flac_crc8(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0, |crc, &byte| FLAC_CRC8_TABLE[usize::from(crc ^ byte)])
+}
+
+/// Calculate the CRC-16 of a FLAC frame.
+fn flac_crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (crc << 8) ^ FLAC_CRC16_TABLE[usize::from((crc >> 8) as u8 ^ byte)]
+    })
+}
+
+/// Append a number in the UTF-8-like variable-length encoding that FLAC uses for frame numbers.
+#[expect(clippy::cast_possible_truncation)]
+fn write_flac_coded_number(out: &mut Vec<u8>, value: u64) {
+    if value < 0x80 {
+        out.push(value as u8);
+        return;
+    }
+
+    // A number with `length` bytes can store `5 * length + 1` bits.
+    let mut length = 2;
+    while length < 7 && value >> (5 * length + 1) != 0 {
+        length += 1;
+    }
+    out.push(!(0xFF >> length) | (value >> (6 * (length - 1))) as u8);
+    for index in (0..length - 1).rev() {
+        out.push(0x80 | ((value >> (6 * index)) & 0x3F) as u8);
+    }
+}
+
+/// Writes bits to a byte buffer (most significant bit first).
+#[derive(Debug, Default)]
+struct BitWriter {
+    /// The completed bytes.
+    bytes: Vec<u8>,
+    /// Bits that have not been written to a byte yet.
+    accumulator: u64,
+    /// Number of valid bits in the accumulator.
+    bit_count: u32,
+}
+
+impl BitWriter {
+    /// Append the lowest `bits` bits of the value.
+    #[expect(clippy::cast_possible_truncation)]
+    fn write(&mut self, value: u32, bits: u32) {
+        let mask = u64::MAX >> (64 - bits);
+        self.accumulator = (self.accumulator << bits) | (u64::from(value) & mask);
+        self.bit_count += bits;
+        while self.bit_count >= 8 {
+            self.bit_count -= 8;
+            self.bytes.push((self.accumulator >> self.bit_count) as u8);
+        }
+    }
+
+    /// Pad the last byte with zero bits and return the bytes.
+    #[expect(clippy::cast_possible_truncation)]
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes
+                .push((self.accumulator << (8 - self.bit_count)) as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Minimal writer for FLAC files.
+///
+/// The samples are stored in verbatim subframes, i.e., without compression. This keeps the
+/// writer simple while producing files that any FLAC decoder can read and that can be re-encoded
+/// losslessly later.
+#[derive(Debug)]
+struct FlacWriter {
+    /// The underlying file writer.
+    writer: BufWriter<fs::File>,
+    /// Number of channels.
+    channels: usize,
+    /// Number of bits per sample.
+    bits_per_sample: u32,
+    /// Stream parameters as stored in the STREAMINFO block (without the total number of
+    /// samples).
+    stream_parameters: u64,
+    /// Interleaved samples that have not been written to a frame yet.
+    pending_samples: Vec<i32>,
+    /// Number of the next frame.
+    frame_number: u64,
+    /// Number of samples per channel that have been written.
+    total_samples: u64,
+}
+
+impl FlacWriter {
+    /// Create the file and write the stream header.
+    fn create(
+        path: &Path,
+        sample_rate: u32,
+        channels: usize,
+        bits_per_sample: u32,
+    ) -> io::Result<Self> {
+        if !(1..=8).contains(&channels) {
+            return Err(io::Error::other("FLAC supports at most 8 channels"));
+        }
+        let bits_per_sample = bits_per_sample.clamp(4, 32);
+        let stream_parameters = (u64::from(sample_rate) << 44)
+            | ((channels as u64 - 1) << 41)
+            | (u64::from(bits_per_sample - 1) << 36);
+        let block_size = u16::try_from(FLAC_BLOCK_SIZE).map_err(io::Error::other)?;
+
+        let mut writer = BufWriter::new(fs::File::create(path)?);
+        writer.write_all(b"fLaC")?;
+        // STREAMINFO block header (last metadata block, 34 bytes).
+        writer.write_all(&[0x80, 0, 0, 34])?;
+        writer.write_all(&block_size.to_be_bytes())?;
+        writer.write_all(&block_size.to_be_bytes())?;
+        // Minimum and maximum frame size (unknown).
+        writer.write_all(&[0; 6])?;
+        writer.write_all(&stream_parameters.to_be_bytes())?;
+        // MD5 signature of the audio data (unknown).
+        writer.write_all(&[0; 16])?;
+
+        Ok(Self {
+            writer,
+            channels,
+            bits_per_sample,
+            stream_parameters,
+            pending_samples: Vec::with_capacity(FLAC_BLOCK_SIZE * channels),
+            frame_number: 0,
+            total_samples: 0,
+        })
+    }
+
+    /// Append interleaved samples (scaled to the full range of `i32`).
+    fn write_samples(&mut self, samples: &[i32]) -> io::Result<()> {
+        let frame_length = FLAC_BLOCK_SIZE * self.channels;
+        for &sample in samples {
+            self.pending_samples
+                .push(scale_sample(sample, self.bits_per_sample));
+            if self.pending_samples.len() == frame_length {
+                self.write_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the pending samples as a single frame.
+    #[expect(clippy::cast_sign_loss)]
+    fn write_frame(&mut self) -> io::Result<()> {
+        let block_size = self.pending_samples.len() / self.channels;
+        let encoded_block_size = u16::try_from(block_size - 1).map_err(io::Error::other)?;
+        let channel_assignment = u8::try_from(self.channels - 1).map_err(io::Error::other)?;
+
+        // Frame header with fixed block size, sample rate and bit depth from STREAMINFO, and
+        // independently coded channels.
+        let mut frame = vec![0xFF, 0xF8, 0x70, channel_assignment << 4];
+        write_flac_coded_number(&mut frame, self.frame_number);
+        frame.extend_from_slice(&encoded_block_size.to_be_bytes());
+        frame.push(flac_crc8(&frame));
+
+        let mut bits = BitWriter::default();
+        for channel in 0..self.channels {
+            // Verbatim subframe without wasted bits.
+            bits.write(0b0000_0010, 8);
+            for sample in self
+                .pending_samples
+                .iter()
+                .skip(channel)
+                .step_by(self.channels)
+            {
+                bits.write(*sample as u32, self.bits_per_sample);
+            }
+        }
+        frame.extend(bits.finish());
+        frame.extend_from_slice(&flac_crc16(&frame).to_be_bytes());
+
+        self.writer.write_all(&frame)?;
+        self.frame_number += 1;
+        self.total_samples += block_size as u64;
+        self.pending_samples.clear();
+        Ok(())
+    }
+
+    /// Write the remaining samples, update the total number of samples in the header and flush
+    /// the file.
+    fn finalize(mut self) -> io::Result<()> {
+        if !self.pending_samples.is_empty() {
+            self.write_frame()?;
+        }
+        let _ = self
+            .writer
+            .seek(SeekFrom::Start(FLAC_STREAM_PARAMETERS_OFFSET))?;
+        self.writer
+            .write_all(&(self.stream_parameters | self.total_samples).to_be_bytes())?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::Tag;
+    use crate::Config;
+
+    const CUE_SHEET: &str = r#"REM GENRE Jazz
+REM DATE 1959
+PERFORMER "Miles Davis"
+TITLE "Kind of Blue"
+FILE "Miles Davis - Kind of Blue.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "So What"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Freddie Freeloader"
+    ISRC USSM15900112
+    INDEX 00 09:22:10
+    INDEX 01 09:24:00
+  TRACK 03 AUDIO
+    TITLE "Blue in Green"
+    PERFORMER "Miles Davis & Bill Evans"
+    INDEX 01 19:11:37
+"#;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("00:00:00"), Some(0));
+        assert_eq!(parse_timestamp("01:02:03"), Some((60 + 2) * 75 + 3));
+        assert_eq!(parse_timestamp("00:60:00"), None);
+        assert_eq!(parse_timestamp("00:00:75"), None);
+        assert_eq!(parse_timestamp("foo"), None);
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(parse_file_name(r#""foo bar.flac" WAVE"#), "foo bar.flac");
+        assert_eq!(parse_file_name("foo.flac WAVE"), "foo.flac");
+    }
+
+    #[test]
+    fn test_parse() {
+        let sheet = CueSheet::parse(CUE_SHEET).unwrap();
+        assert_eq!(sheet.title.as_deref(), Some("Kind of Blue"));
+        assert_eq!(sheet.performer.as_deref(), Some("Miles Davis"));
+        assert_eq!(sheet.date.as_deref(), Some("1959"));
+        assert_eq!(sheet.genre.as_deref(), Some("Jazz"));
+        assert_eq!(
+            sheet.audio_file().unwrap(),
+            "Miles Davis - Kind of Blue.flac"
+        );
+        assert_eq!(sheet.tracks.len(), 3);
+        assert_eq!(sheet.tracks[1].number, 2);
+        assert_eq!(sheet.tracks[1].title.as_deref(), Some("Freddie Freeloader"));
+        assert_eq!(sheet.tracks[1].isrc.as_deref(), Some("USSM15900112"));
+        assert_eq!(sheet.tracks[1].start_frames, (9 * 60 + 24) * 75);
+    }
+
+    #[test]
+    fn test_parse_missing_index() {
+        let result = CueSheet::parse("FILE \"foo.flac\" WAVE\nTRACK 01 AUDIO\nTITLE \"Foo\"\n");
+        assert!(matches!(result, Err(CueError::MissingIndex(1))));
+    }
+
+    #[test]
+    fn test_track_file_name() {
+        let sheet = CueSheet::parse(CUE_SHEET).unwrap();
+        assert_eq!(
+            CueSheet::track_file_name(Path::new("/music/Album.FLAC"), &sheet.tracks[1]),
+            "Album - 02.flac"
+        );
+        assert_eq!(
+            CueSheet::track_file_name(Path::new("/music/Album.ape"), &sheet.tracks[1]),
+            "Album - 02.wav"
+        );
+    }
+
+    #[test]
+    fn test_split_audio_file() {
+        let sheet = CueSheet::parse(concat!(
+            "FILE \"track.flac\" WAVE\n",
+            "TRACK 01 AUDIO\nINDEX 01 00:00:00\n",
+            "TRACK 02 AUDIO\nINDEX 01 00:00:30\n",
+        ))
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        for extension in ["flac", "wav"] {
+            let audio_path = PathBuf::from(format!(
+                "{}/tests/data/media/untagged/track.{extension}",
+                env!("CARGO_MANIFEST_DIR"),
+            ));
+            let output_dir = dir.path().join(extension);
+            let paths = sheet.split_audio_file(&audio_path, &output_dir).unwrap();
+            assert_eq!(
+                paths,
+                vec![
+                    output_dir.join(format!("track - 01.{extension}")),
+                    output_dir.join(format!("track - 02.{extension}")),
+                ]
+            );
+
+            // The source is one second of mono audio at 44.1 kHz, and the second track starts
+            // after 30 frames (0.4 seconds).
+            let frame_counts = paths
+                .iter()
+                .map(|path| {
+                    let mut frame_count = 0;
+                    analyzer::decode_interleaved(
+                        path,
+                        |sample_rate, channels, bits_per_sample, samples| {
+                            assert_eq!((sample_rate, channels, bits_per_sample), (44100, 1, 16));
+                            frame_count += samples.len();
+                            Ok(())
+                        },
+                    )
+                    .unwrap();
+                    frame_count
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(frame_counts, vec![17640, 26460]);
+            assert!(crate::tag::read_tags_from_path(&paths[0], &Config::default().tags).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_track_tag() {
+        let sheet = CueSheet::parse(CUE_SHEET).unwrap();
+        let tag = sheet.track_tag(&sheet.tracks[2]);
+        assert_eq!(tag.get(&TagKey::Album).as_deref(), Some("Kind of Blue"));
+        assert_eq!(
+            tag.get(&TagKey::AlbumArtist).as_deref(),
+            Some("Miles Davis")
+        );
+        assert_eq!(
+            tag.get(&TagKey::Artist).as_deref(),
+            Some("Miles Davis & Bill Evans")
+        );
+        assert_eq!(tag.get(&TagKey::TrackNumber).as_deref(), Some("3"));
+        assert_eq!(tag.get(&TagKey::TotalTracks).as_deref(), Some("3"));
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Persistent store of candidate decisions made by the user.
+//!
+//! When the user selects a release candidate, the selected release is recorded. Candidates are
+//! only recorded as rejected if the user explicitly rejects them. In later runs, these decisions
+//! are used to rank the candidates (e.g., to prefer the release country that the user usually
+//! picks for an artist, or to rank a rejected release last).
+
+use crate::distance::Distance;
+use crate::release::ReleaseLike;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use xdg::BaseDirectories;
+
+/// File name of the decision store in the state directory.
+const DECISIONS_FILE_NAME: &str = "decisions.jsonl";
+
+/// Kind of decision that the user made about a release candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    /// The release was selected.
+    Selected,
+    /// The release was explicitly rejected.
+    Rejected,
+}
+
+/// A single decision about a release candidate.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Decision {
+    /// Kind of the decision.
+    pub kind: DecisionKind,
+    /// MusicBrainz ID of the release.
+    pub release_id: String,
+    /// Artist of the release.
+    pub release_artist: Option<String>,
+    /// Country of the release.
+    pub release_country: Option<String>,
+    /// Time of the decision (RFC 3339).
+    pub decided_at: String,
+}
+
+impl Decision {
+    /// Create a new decision about the release that was made just now.
+    ///
+    /// Returns `None` if the release does not have a MusicBrainz ID.
+    pub fn new<T: ReleaseLike + ?Sized>(kind: DecisionKind, release: &T) -> Option<Self> {
+        Some(Self {
+            kind,
+            release_id: release.musicbrainz_release_id()?.into_owned(),
+            release_artist: release.release_artist().map(|artist| normalize(&artist)),
+            release_country: release.release_country().map(|country| normalize(&country)),
+            decided_at: chrono::Local::now().to_rfc3339(),
+        })
+    }
+}
+
+/// Normalize a value for comparison.
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+/// Store of past decisions, persisted as JSON lines.
+#[derive(Debug, Clone)]
+pub struct DecisionStore {
+    /// Path of the store file.
+    path: PathBuf,
+    /// The decisions, oldest first.
+    decisions: Vec<Decision>,
+}
+
+impl DecisionStore {
+    /// Load the decision store at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn load(path: PathBuf) -> crate::Result<Self> {
+        let decisions = match File::open(&path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+                .map(|line| Ok(serde_json::from_str(&line?)?))
+                .collect::<crate::Result<Vec<_>>>()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, decisions })
+    }
+
+    /// Load the decision store in the XDG state directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory cannot be determined or the store cannot be read.
+    pub fn open_default() -> crate::Result<Self> {
+        let path =
+            BaseDirectories::with_prefix(crate::PKG_NAME)?.place_state_file(DECISIONS_FILE_NAME)?;
+        Self::load(path)
+    }
+
+    /// Record a decision.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    pub fn record(&mut self, decision: Decision) -> crate::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut line = serde_json::to_vec(&decision)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        self.decisions.push(decision);
+        Ok(())
+    }
+
+    /// Returns the most recent decision about the release, if any.
+    pub fn latest_decision<T: ReleaseLike + ?Sized>(&self, release: &T) -> Option<DecisionKind> {
+        let release_id = release.musicbrainz_release_id()?;
+        self.decisions
+            .iter()
+            .rev()
+            .find(|decision| decision.release_id == release_id)
+            .map(|decision| decision.kind)
+    }
+
+    /// Returns the distance of the release to the past decisions.
+    ///
+    /// Releases that were selected before have the minimum distance and releases that were
+    /// rejected have the maximum distance. For other releases, the release country is compared to
+    /// the countries of the releases that were selected for the same artist. Returns `None` if
+    /// there are no relevant decisions or if the release country is unknown.
+    pub fn distance<T: ReleaseLike + ?Sized>(&self, release: &T) -> Option<Distance> {
+        match self.latest_decision(release) {
+            Some(DecisionKind::Selected) => return Some(Distance::MIN),
+            Some(DecisionKind::Rejected) => return Some(Distance::MAX),
+            None => (),
+        }
+
+        let artist = release.release_artist().map(|artist| normalize(&artist))?;
+        let country = release
+            .release_country()
+            .map(|country| normalize(&country))?;
+        let mut selected_countries = self
+            .decisions
+            .iter()
+            .filter(|decision| {
+                decision.kind == DecisionKind::Selected
+                    && decision.release_artist.as_ref() == Some(&artist)
+            })
+            .filter_map(|decision| decision.release_country.as_ref())
+            .peekable();
+        selected_countries.peek()?;
+
+        Some(Distance::from(
+            selected_countries.any(|selected| selected == &country),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::FakeRelease;
+
+    fn release(release_id: &str, artist: &str, country: &str) -> FakeRelease {
+        serde_json::from_value(serde_json::json!({
+            "musicbrainz_release_id": release_id,
+            "release_artist": artist,
+            "release_country": country,
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_decision_store() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("decisions.jsonl");
+        let mut store = DecisionStore::load(path.clone()).unwrap();
+
+        let selected = release("selected", "The Ahmad Jamal Trio", "US");
+        let bootleg = release("bootleg", "The Ahmad Jamal Trio", "XE");
+        assert_eq!(store.distance(&selected), None);
+
+        store
+            .record(Decision::new(DecisionKind::Selected, &selected).unwrap())
+            .unwrap();
+        store
+            .record(Decision::new(DecisionKind::Rejected, &bootleg).unwrap())
+            .unwrap();
+
+        let store = DecisionStore::load(path).unwrap();
+        assert_eq!(store.distance(&selected), Some(Distance::MIN));
+        assert_eq!(store.distance(&bootleg), Some(Distance::MAX));
+        assert_eq!(
+            store.distance(&release("other", "the ahmad jamal trio", "us")),
+            Some(Distance::MIN)
+        );
+        assert_eq!(
+            store.distance(&release("other", "The Ahmad Jamal Trio", "GB")),
+            Some(Distance::MAX)
+        );
+        assert_eq!(store.distance(&release("other", "Miles Davis", "US")), None);
+
+        let unknown_country: FakeRelease = serde_json::from_value(serde_json::json!({
+            "musicbrainz_release_id": "other",
+            "release_artist": "The Ahmad Jamal Trio",
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        assert_eq!(store.distance(&unknown_country), None);
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Functions for distance calculation between (partial) dates.
+
+use super::{Distance, DistanceBetween};
+use crate::util::parse_partial_date_from_str;
+use chrono::Datelike;
+
+/// Releases whose years differ by this number of years (or more) have the maximum distance.
+const MAX_YEAR_DIFFERENCE: i64 = 10;
+
+/// Parse the year from a partial date string (e.g., `YYYY-MM-DD`, `YYYY-MM` or `YYYY`).
+pub fn parse_year(value: &str) -> Option<i32> {
+    parse_partial_date_from_str(value).map(|date| date.year())
+}
+
+/// Calculate the distance between two years.
+pub fn between_years(lhs: i32, rhs: i32) -> Distance {
+    let diff = (i64::from(lhs) - i64::from(rhs))
+        .abs()
+        .min(MAX_YEAR_DIFFERENCE);
+    Distance::between(diff, MAX_YEAR_DIFFERENCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year("1962-01-17"), Some(1962));
+        assert_eq!(parse_year("1986-04"), Some(1986));
+        assert_eq!(parse_year("2001"), Some(2001));
+        assert_eq!(parse_year("foo"), None);
+    }
+
+    #[test]
+    fn test_between_years() {
+        assert!(between_years(1998, 1998).is_equality());
+        assert_float_eq!(between_years(1998, 2003).as_f64(), 0.5, abs <= 0.000_1);
+        assert_float_eq!(between_years(2003, 1998).as_f64(), 0.5, abs <= 0.000_1);
+        assert_float_eq!(between_years(1970, 2020).as_f64(), 1.0, abs <= 0.000_1);
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Structured explanations of distances (e.g., for tuning the configured weights).
+
+use super::WeightedDistance;
+use serde::Serialize;
+
+/// Contribution of a single field to the overall distance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldDistance {
+    /// Name of the field (e.g., `release_title`).
+    pub field: &'static str,
+    /// Distance between the two values, or `None` if the field is not taken into account.
+    pub distance: Option<f64>,
+    /// Weight of the field.
+    pub weight: Option<f64>,
+    /// Distance multiplied with the weight.
+    pub weighted_distance: Option<f64>,
+}
+
+impl FieldDistance {
+    /// Create the explanation from an (optional) weighted distance.
+    pub fn new(field: &'static str, distance: Option<&WeightedDistance<'_>>) -> Self {
+        Self {
+            field,
+            distance: distance.map(|distance| distance.base_distance().as_f64()),
+            weight: distance.map(WeightedDistance::weight),
+            weighted_distance: distance.map(WeightedDistance::as_f64),
+        }
+    }
+}
+
+/// Explanation of the distance between two matched tracks.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackDistanceExplanation {
+    /// Index of the track on the left hand side.
+    pub lhs_index: usize,
+    /// Index of the track on the right hand side.
+    pub rhs_index: usize,
+    /// Overall distance between the two tracks.
+    pub distance: f64,
+    /// Contributions of the individual fields.
+    pub fields: Vec<FieldDistance>,
+}
+
+/// Explanation of the distance between two releases.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseDistanceExplanation {
+    /// Overall distance between the two releases.
+    pub distance: f64,
+    /// Contributions of the individual fields (including the track assignment).
+    pub fields: Vec<FieldDistance>,
+    /// Explanations for the matched tracks.
+    pub matched_tracks: Vec<TrackDistanceExplanation>,
+    /// Indices of the tracks that could not be matched.
+    pub unmatched_tracks: Vec<usize>,
+}
@@ -14,15 +14,23 @@ use std::cmp;
 use std::fmt;
 use std::iter::Sum;
 
+mod date;
 mod difference;
+mod explain;
+mod preference;
 mod release;
 mod string;
 mod time;
 mod track;
 
 pub use difference::Difference;
+pub use explain::{FieldDistance, ReleaseDistanceExplanation, TrackDistanceExplanation};
+pub use preference::ReleasePreference;
 pub use release::{ReleaseSimilarity, UnmatchedTracksSource};
-pub use track::TrackSimilarity;
+#[cfg(feature = "bench")]
+pub use string::normalize;
+pub use string::{StringDistanceBuffer, StringMetric};
+pub use track::{NormalizedTrackStrings, TrackSimilarity};
 
 /// A distance in the range (0.0, 1.0) between two items.
 #[expect(missing_copy_implementations)]
@@ -95,6 +103,11 @@ impl WeightedDistance<'_> {
         self.weight
     }
 
+    /// Returns the distance without the weight.
+    pub fn base_distance(&self) -> &Distance {
+        &self.base_distance
+    }
+
     /// Returns the distance between the items as floating point number in the range 0 to 1,
     /// multiplied with the weight.
     pub fn as_f64(&self) -> f64 {
@@ -0,0 +1,140 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Preferred release heuristics that are independent of the metadata distance.
+
+use super::Distance;
+use crate::media::MediaLike;
+use crate::release::ReleaseLike;
+use crate::Config;
+use num::rational::Ratio;
+
+/// Calculate the distance of a value to the most preferred item in the list.
+///
+/// Returns `None` if the list of preferred values is empty. If the value is missing or not
+/// contained in the list, the maximum distance is returned.
+fn preference_distance<'a>(
+    preferred_values: &[String],
+    values: impl IntoIterator<Item = &'a str>,
+) -> Option<Distance> {
+    if preferred_values.is_empty() {
+        return None;
+    }
+
+    values
+        .into_iter()
+        .filter_map(|value| {
+            preferred_values
+                .iter()
+                .position(|preferred| preferred.trim().eq_ignore_ascii_case(value.trim()))
+        })
+        .min()
+        .map(|index| Distance::from(Ratio::new(index, preferred_values.len())))
+        .unwrap_or(Distance::MAX)
+        .into()
+}
+
+/// Result of checking a release against the preferred release configuration.
+#[derive(Debug, Clone)]
+pub struct ReleasePreference {
+    /// The distance of the release country to the preferred countries.
+    country: Option<Distance>,
+    /// The distance of the media formats to the preferred media formats.
+    media_format: Option<Distance>,
+    /// The distance of the release status to the preferred release statuses.
+    status: Option<Distance>,
+}
+
+impl ReleasePreference {
+    /// Check the release against the preferred release configuration.
+    pub fn detect<T>(config: &Config, release: &T) -> Self
+    where
+        T: ReleaseLike + ?Sized,
+    {
+        let preferred = &config.lookup.preferred_release;
+
+        let countries = release
+            .release_events()
+            .into_iter()
+            .filter_map(|event| event.country)
+            .collect::<Vec<_>>();
+        let media_formats = release
+            .media()
+            .filter_map(MediaLike::media_format)
+            .collect::<Vec<_>>();
+        let status = release.release_status();
+
+        Self {
+            country: preference_distance(&preferred.countries, countries.iter().map(AsRef::as_ref)),
+            media_format: preference_distance(
+                &preferred.media_formats,
+                media_formats.iter().map(AsRef::as_ref),
+            ),
+            status: preference_distance(&preferred.statuses, status.as_deref()),
+        }
+    }
+
+    /// Returns the overall preference distance, i.e., the minimum distance if the release matches
+    /// all most preferred properties.
+    ///
+    /// Returns `None` if no preferences are configured (or all of them have a weight of zero), so
+    /// that the ranking of the candidates is not affected.
+    pub fn total_distance(&self, config: &Config) -> Option<Distance> {
+        let weights = &config.weights.preferred_release;
+
+        let weighted_distances = [
+            self.country
+                .as_ref()
+                .map(|dist| dist.to_weighted(weights.country)),
+            self.media_format
+                .as_ref()
+                .map(|dist| dist.to_weighted(weights.media_format)),
+            self.status
+                .as_ref()
+                .map(|dist| dist.to_weighted(weights.status)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|dist| dist.weight() > 0.0)
+        .collect::<Vec<_>>();
+
+        if weighted_distances.is_empty() {
+            return None;
+        }
+
+        Some(weighted_distances.into_iter().sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_preference_distance() {
+        let preferred = ["XW".to_string(), "US".to_string()];
+        assert_eq!(preference_distance(&[], Some("XW")), None);
+        assert_eq!(
+            preference_distance(&preferred, Some("xw")),
+            Some(Distance::MIN)
+        );
+        assert_eq!(
+            preference_distance(&preferred, Some("DE")),
+            Some(Distance::MAX)
+        );
+        assert_eq!(preference_distance(&preferred, None), Some(Distance::MAX));
+        assert_float_eq!(
+            preference_distance(&preferred, ["DE", "US"])
+                .unwrap()
+                .as_f64(),
+            0.5,
+            abs <= 0.000_1
+        );
+    }
+}
@@ -8,11 +8,18 @@
 
 //! Functions for distance calculation between [`ReleaseLike`] objects.
 
-use super::TrackSimilarity;
-use super::{string, Difference, Distance, WeightedDistance};
+use super::{
+    date, string, Difference, Distance, FieldDistance, ReleaseDistanceExplanation,
+    TrackDistanceExplanation, WeightedDistance,
+};
+use super::{NormalizedTrackStrings, StringDistanceBuffer, TrackSimilarity};
 use crate::release::ReleaseLike;
 use crate::track::TrackLike;
+use crate::util::{normalize_barcode, normalize_catalog_number};
 use crate::Config;
+use chrono::TimeDelta;
+use rayon::prelude::*;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 use std::iter;
@@ -46,6 +53,35 @@ fn usize_to_f64(value: usize) -> Option<f64> {
     (value < (f64::MAX.trunc() as usize)).then_some(value as f64)
 }
 
+/// Returns the release year as a number, preferring the full release date if available.
+fn release_year_number<T: ReleaseLike + ?Sized>(release: &T) -> Option<i32> {
+    release
+        .release_date()
+        .as_deref()
+        .and_then(date::parse_year)
+        .or_else(|| release.release_year().as_deref().and_then(date::parse_year))
+}
+
+/// Returns the share of tracks whose artist does not contain the release artist, i.e., 0.0 for an
+/// album by a single artist and (close to) 1.0 for a compilation of various artists.
+///
+/// Returns `None` if the release artist or all track artists are missing.
+fn track_artist_mismatch_share<'a>(
+    release_artist: Option<&str>,
+    track_artists: impl Iterator<Item = Option<Cow<'a, str>>>,
+) -> Option<f64> {
+    let release_artist = string::normalize(release_artist?);
+    let (mismatch_count, track_count) =
+        track_artists
+            .flatten()
+            .fold((0, 0), |(mismatch_count, track_count), track_artist| {
+                let is_mismatch = !string::normalize(&track_artist).contains(&release_artist);
+                (mismatch_count + usize::from(is_mismatch), track_count + 1)
+            });
+    (track_count > 0)
+        .then(|| usize_to_f64(mismatch_count).unwrap() / usize_to_f64(track_count).unwrap())
+}
+
 /// The source of the unmatched tracks.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UnmatchedTracksSource {
@@ -55,6 +91,72 @@ pub enum UnmatchedTracksSource {
     Right,
 }
 
+/// Find the pairs of tracks with the same MusicBrainz ID, where each ID occurs at most once on each
+/// side.
+fn unique_id_pairs(lhs_ids: &[Option<String>], rhs_ids: &[Option<String>]) -> Vec<(usize, usize)> {
+    let positions = |ids: &[Option<String>], id: &String| {
+        ids.iter()
+            .enumerate()
+            .filter(|(_, other)| other.as_ref() == Some(id))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>()
+    };
+
+    lhs_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| {
+            let id = id.as_ref()?;
+            match (
+                positions(lhs_ids, id).as_slice(),
+                positions(rhs_ids, id).as_slice(),
+            ) {
+                ([_], [j]) => Some((i, *j)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Find the pairs of tracks that are tagged with the same MusicBrainz Track ID or, if it is
+/// unambiguous, the same MusicBrainz Recording ID.
+fn locked_track_pairs<T1, T2>(lhs: &[&T1], rhs: &[&T2]) -> Vec<(usize, usize)>
+where
+    T1: TrackLike,
+    T2: TrackLike,
+{
+    /// Normalize the ID for comparison.
+    fn normalize_id(id: Cow<'_, str>) -> Option<String> {
+        let id = id.trim().to_ascii_lowercase();
+        (!id.is_empty()).then_some(id)
+    }
+
+    let lhs_track_ids: Vec<_> = lhs
+        .iter()
+        .map(|track| track.musicbrainz_track_id().and_then(normalize_id))
+        .collect();
+    let rhs_track_ids: Vec<_> = rhs
+        .iter()
+        .map(|track| track.musicbrainz_track_id().and_then(normalize_id))
+        .collect();
+    let lhs_recording_ids: Vec<_> = lhs
+        .iter()
+        .map(|track| track.musicbrainz_recording_id().and_then(normalize_id))
+        .collect();
+    let rhs_recording_ids: Vec<_> = rhs
+        .iter()
+        .map(|track| track.musicbrainz_recording_id().and_then(normalize_id))
+        .collect();
+
+    let mut pairs = unique_id_pairs(&lhs_track_ids, &rhs_track_ids);
+    for (i, j) in unique_id_pairs(&lhs_recording_ids, &rhs_recording_ids) {
+        if pairs.iter().all(|&(lhs, rhs)| lhs != i && rhs != j) {
+            pairs.push((i, j));
+        }
+    }
+    pairs
+}
+
 /// A pair of tracks that are part of a [`TrackAssignment`].
 #[derive(Debug, Clone)]
 pub struct TrackMatchPair {
@@ -120,11 +222,15 @@ impl TrackAssignment {
 
     /// Compute the best match between two Iterators of [`TrackLike`] items and returns a
     /// [`TrackAssignment`] struct.
-    pub fn compute_from<'a>(
+    pub fn compute_from<'a, T1, T2>(
         config: &Config,
-        lhs: impl Iterator<Item = &'a (impl TrackLike + 'a)>,
-        rhs: impl Iterator<Item = &'a (impl TrackLike + 'a)>,
-    ) -> TrackAssignment {
+        lhs: impl Iterator<Item = &'a T1>,
+        rhs: impl Iterator<Item = &'a T2>,
+    ) -> TrackAssignment
+    where
+        T1: TrackLike + 'a,
+        T2: TrackLike + 'a,
+    {
         /// Since the `hungarian` crate operates on integers, we'll normalize the [`f64`] distances by
         /// multiplying them with this constant and truncating them, then divide by this constant
         /// afterwards.
@@ -149,31 +255,97 @@ impl TrackAssignment {
             };
         }
 
-        let track_similarity_matrix: Vec<TrackSimilarity> = lhs_tracks
+        // Normalize the strings of each track only once instead of once per track pair.
+        let lhs_strings: Vec<_> = lhs_tracks
             .iter()
-            .flat_map(|lhs_track| iter::repeat(lhs_track).zip(rhs_tracks.iter()))
-            .map(|(lhs_track, rhs_track)| TrackSimilarity::detect(*lhs_track, *rhs_track))
+            .map(|track| NormalizedTrackStrings::new(*track))
+            .collect();
+        let rhs_strings: Vec<_> = rhs_tracks
+            .iter()
+            .map(|track| NormalizedTrackStrings::new(*track))
             .collect();
         let track_distance_matrix_height = lhs_tracks.len(); // number of rows
         let track_distance_matrix_width = rhs_tracks.len(); // number of columns
-        let track_distance_matrix: Option<Vec<u64>> = track_similarity_matrix
-            .iter()
-            .map(|distance| {
-                f64_to_u64(
-                    (distance.total_distance(config).as_f64() * TRACK_DISTANCE_PRECISION_FACTOR)
+
+        // For huge releases (e.g., box sets), pairs of tracks with very different lengths can be
+        // skipped, because they will not be assigned to each other anyway.
+        let length_window = config
+            .lookup
+            .track_assignment
+            .length_window
+            .filter(|_| {
+                track_distance_matrix_height.max(track_distance_matrix_width)
+                    >= config.lookup.track_assignment.length_window_min_tracks
+            })
+            .and_then(|seconds| TimeDelta::try_seconds(i64::try_from(seconds).ok()?));
+        let is_within_length_window = |lhs_track: &T1, rhs_track: &T2| {
+            let Some(length_window) = length_window else {
+                return true;
+            };
+            match (lhs_track.track_length(), rhs_track.track_length()) {
+                (Some(lhs_length), Some(rhs_length)) => {
+                    (lhs_length - rhs_length).abs() <= length_window
+                }
+                _ => true,
+            }
+        };
+
+        // The matrix contains `None` for pairs that were skipped by the length window.
+        let (track_similarity_matrix, track_distance_matrix): (
+            Vec<Option<TrackSimilarity>>,
+            Vec<u64>,
+        ) = (0..track_distance_matrix_height * track_distance_matrix_width)
+            .into_par_iter()
+            .map_init(StringDistanceBuffer::default, |buffer, index| {
+                let (i, j) = (
+                    index / track_distance_matrix_width,
+                    index % track_distance_matrix_width,
+                );
+                if !is_within_length_window(lhs_tracks[i], rhs_tracks[j]) {
+                    return (None, f64_to_u64(TRACK_DISTANCE_PRECISION_FACTOR).unwrap());
+                }
+
+                let similarity = TrackSimilarity::detect_normalized(
+                    config,
+                    (lhs_tracks[i], &lhs_strings[i]),
+                    (rhs_tracks[j], &rhs_strings[j]),
+                    buffer,
+                );
+                let distance = f64_to_u64(
+                    (similarity.total_distance(config).as_f64() * TRACK_DISTANCE_PRECISION_FACTOR)
                         .trunc(),
                 )
+                .unwrap();
+                (Some(similarity), distance)
             })
-            .collect();
-        let track_distance_matrix = track_distance_matrix.unwrap();
+            .unzip();
         debug_assert_eq!(
             track_distance_matrix_height * track_distance_matrix_width,
             track_distance_matrix.len()
         );
 
+        // Tracks that are already tagged with the same MusicBrainz IDs are locked to each other by
+        // making all other assignments of these tracks as expensive as possible. The actual
+        // distances are still used for the resulting cost.
+        let locked_track_pairs = locked_track_pairs(&lhs_tracks, &rhs_tracks);
+        let assignment_cost_matrix = if locked_track_pairs.is_empty() {
+            Cow::Borrowed(&track_distance_matrix)
+        } else {
+            let max_cost = f64_to_u64(TRACK_DISTANCE_PRECISION_FACTOR).unwrap();
+            let mut cost_matrix = track_distance_matrix.clone();
+            for (i, j) in locked_track_pairs {
+                (0..track_distance_matrix_width)
+                    .for_each(|k| cost_matrix[i * track_distance_matrix_width + k] = max_cost);
+                (0..track_distance_matrix_height)
+                    .for_each(|k| cost_matrix[k * track_distance_matrix_width + j] = max_cost);
+                cost_matrix[i * track_distance_matrix_width + j] = 0;
+            }
+            Cow::Owned(cost_matrix)
+        };
+
         // Returns a Vec of with `track_distance_matrix_height` items.
         let assignment = hungarian::minimize(
-            &track_distance_matrix,
+            &assignment_cost_matrix,
             track_distance_matrix_height,
             track_distance_matrix_width,
         );
@@ -231,11 +403,15 @@ impl TrackAssignment {
             .enumerate()
             .for_each(|pair| match pair {
                 (i, Some(j)) => {
+                    let similarity = track_similarity_matrix[i * track_distance_matrix_width + j]
+                        .clone()
+                        .unwrap_or_else(|| {
+                            TrackSimilarity::detect(config, lhs_tracks[i], rhs_tracks[j])
+                        });
                     let matched_track = TrackMatchPair {
                         lhs: i,
                         rhs: j,
-                        similarity: track_similarity_matrix[i * track_distance_matrix_width + j]
-                            .clone(),
+                        similarity,
                     };
                     matched_tracks.push(matched_track);
                 }
@@ -315,6 +491,12 @@ pub struct ReleaseSimilarity {
     catalog_number: Difference,
     /// The distance between the two barcodes.
     barcode: Difference,
+    /// The distance between the two release years.
+    release_date: Difference,
+    /// The distance between the two release countries.
+    release_country: Difference,
+    /// The difference in how consistently the track artists match the release artist.
+    artist_consistency: Difference,
     /// The minimum distance mapping of tracks from the two releases.
     track_assignment: TrackAssignment,
 }
@@ -330,19 +512,74 @@ impl ReleaseSimilarity {
             record_label: Difference::Added,
             catalog_number: Difference::Added,
             barcode: Difference::Added,
+            release_date: Difference::Added,
+            release_country: Difference::Added,
+            artist_consistency: Difference::Added,
             track_assignment: TrackAssignment::new(track_count),
         }
     }
 
+    /// Estimate the distance between two releases by only comparing the release title, the
+    /// release artist and the number of tracks.
+    ///
+    /// This is much cheaper than [`Self::detect`] and does not need the tracklists, so that it can
+    /// be used to rank search results before the full releases are fetched.
+    pub fn estimate_distance<T1, T2>(config: &Config, lhs: &T1, rhs: &T2) -> Distance
+    where
+        T1: ReleaseLike + ?Sized,
+        T2: ReleaseLike + ?Sized,
+    {
+        let weights = &config.weights.release;
+        let metrics = &config.string_metrics;
+        let release_title =
+            Difference::between_options_fn(lhs.release_title(), rhs.release_title(), |lhs, rhs| {
+                metrics.release_title.between(&lhs, &rhs)
+            });
+        let release_artist = Difference::between_options_fn(
+            lhs.release_artist(),
+            rhs.release_artist(),
+            |lhs, rhs| metrics.release_artist.between(&lhs, &rhs),
+        );
+        let track_count = Difference::between_options_fn(
+            lhs.release_track_count(),
+            rhs.release_track_count(),
+            |lhs, rhs| Distance::from(lhs == rhs),
+        );
+
+        [
+            Some(
+                release_title
+                    .to_distance()
+                    .to_weighted(weights.release_title),
+            ),
+            release_artist
+                .to_distance_if_both_present()
+                .map(|dist| dist.to_weighted(weights.release_artist)),
+            track_count
+                .to_distance_if_both_present()
+                .map(|dist| dist.to_weighted(weights.track_assignment)),
+        ]
+        .into_iter()
+        .flatten()
+        .sum()
+    }
+
     /// Calculate the distance between two releases.
     pub fn detect<T1, T2>(config: &Config, lhs: &T1, rhs: &T2) -> Self
     where
         T1: ReleaseLike + ?Sized,
         T2: ReleaseLike + ?Sized,
     {
-        let release_title = Difference::between_options(lhs.release_title(), rhs.release_title());
-        let release_artist =
-            Difference::between_options(lhs.release_artist(), rhs.release_artist());
+        let metrics = &config.string_metrics;
+        let release_title =
+            Difference::between_options_fn(lhs.release_title(), rhs.release_title(), |lhs, rhs| {
+                metrics.release_title.between(&lhs, &rhs)
+            });
+        let release_artist = Difference::between_options_fn(
+            lhs.release_artist(),
+            rhs.release_artist(),
+            |lhs, rhs| metrics.release_artist.between(&lhs, &rhs),
+        );
         let musicbrainz_release_id = Difference::between_options_fn(
             lhs.musicbrainz_release_id(),
             rhs.musicbrainz_release_id(),
@@ -356,10 +593,49 @@ impl ReleaseSimilarity {
         );
         let media_format =
             Difference::between_options(lhs.release_media_format(), rhs.release_media_format());
-        let record_label = Difference::between_options(lhs.record_label(), rhs.record_label());
-        let catalog_number =
-            Difference::between_options(lhs.catalog_number(), rhs.catalog_number());
-        let barcode = Difference::between_options(lhs.barcode(), rhs.barcode());
+        let record_label =
+            Difference::between_options_fn(lhs.record_label(), rhs.record_label(), |lhs, rhs| {
+                metrics.record_label.between(&lhs, &rhs)
+            });
+        let catalog_number = Difference::between_options(
+            lhs.catalog_number()
+                .map(|value| Cow::from(normalize_catalog_number(&value))),
+            rhs.catalog_number()
+                .map(|value| Cow::from(normalize_catalog_number(&value))),
+        );
+        let barcode = Difference::between_options(
+            lhs.barcode()
+                .map(|value| Cow::from(normalize_barcode(&value))),
+            rhs.barcode()
+                .map(|value| Cow::from(normalize_barcode(&value))),
+        );
+        let release_date = Difference::between_options_fn(
+            release_year_number(lhs),
+            release_year_number(rhs),
+            date::between_years,
+        );
+        let release_country = Difference::between_options_fn(
+            lhs.release_country(),
+            rhs.release_country(),
+            |lhs, rhs| {
+                if string::is_nonempty_and_equal_trimmed(lhs.to_uppercase(), rhs.to_uppercase()) {
+                    Distance::MIN
+                } else {
+                    Distance::MAX
+                }
+            },
+        );
+        let artist_consistency = Difference::between_options_fn(
+            track_artist_mismatch_share(
+                lhs.release_artist().as_deref(),
+                lhs.release_tracks().map(TrackLike::track_artist),
+            ),
+            track_artist_mismatch_share(
+                rhs.release_artist().as_deref(),
+                rhs.release_tracks().map(TrackLike::track_artist),
+            ),
+            |lhs, rhs| Distance::from((lhs - rhs).abs()),
+        );
 
         let track_assignment =
             TrackAssignment::compute_from(config, lhs.release_tracks(), rhs.release_tracks());
@@ -371,43 +647,120 @@ impl ReleaseSimilarity {
             record_label,
             catalog_number,
             barcode,
+            release_date,
+            release_country,
+            artist_consistency,
             track_assignment,
         }
     }
 
-    /// Returns the overall distance of the two releases.
-    pub fn total_distance(&self, config: &Config) -> Distance {
+    /// Returns the weighted distances of the individual fields, or `None` for fields that are not
+    /// taken into account (e.g., because they are missing on one side).
+    pub fn weighted_distances(
+        &self,
+        config: &Config,
+    ) -> [(&'static str, Option<WeightedDistance<'_>>); 11] {
         let weights = &config.weights.release;
 
-        let track_assignment_distance = self.track_assignment.to_weighted_distance();
         [
-            self.release_title
-                .to_distance()
-                .to_weighted(weights.release_title)
-                .into(),
-            self.release_artist
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.release_artist)),
-            self.musicbrainz_release_id
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.musicbrainz_release_id)),
-            self.media_format
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.media_format)),
-            self.record_label
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.record_label)),
-            self.catalog_number
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.catalog_number)),
-            self.barcode
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.barcode)),
-            track_assignment_distance.into(),
+            (
+                "release_title",
+                self.release_title
+                    .to_distance()
+                    .to_weighted(weights.release_title)
+                    .into(),
+            ),
+            (
+                "release_artist",
+                self.release_artist
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.release_artist)),
+            ),
+            (
+                "musicbrainz_release_id",
+                self.musicbrainz_release_id
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.musicbrainz_release_id)),
+            ),
+            (
+                "media_format",
+                self.media_format
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.media_format)),
+            ),
+            (
+                "record_label",
+                self.record_label
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.record_label)),
+            ),
+            (
+                "catalog_number",
+                self.catalog_number
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.catalog_number)),
+            ),
+            (
+                "barcode",
+                self.barcode
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.barcode)),
+            ),
+            (
+                "release_date",
+                self.release_date
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.release_date)),
+            ),
+            (
+                "release_country",
+                self.release_country
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.release_country)),
+            ),
+            (
+                "artist_consistency",
+                self.artist_consistency
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.artist_consistency)),
+            ),
+            (
+                "track_assignment",
+                self.track_assignment.to_weighted_distance().into(),
+            ),
         ]
-        .into_iter()
-        .flatten()
-        .sum()
+    }
+
+    /// Returns the overall distance of the two releases.
+    pub fn total_distance(&self, config: &Config) -> Distance {
+        self.weighted_distances(config)
+            .into_iter()
+            .filter_map(|(_, distance)| distance)
+            .sum()
+    }
+
+    /// Returns the contributions of the individual fields and of the matched tracks to the overall
+    /// distance.
+    pub fn explain(&self, config: &Config) -> ReleaseDistanceExplanation {
+        ReleaseDistanceExplanation {
+            distance: self.total_distance(config).as_f64(),
+            fields: self
+                .weighted_distances(config)
+                .iter()
+                .map(|(field, distance)| FieldDistance::new(field, distance.as_ref()))
+                .collect(),
+            matched_tracks: self
+                .track_assignment
+                .matched_tracks()
+                .map(|pair| TrackDistanceExplanation {
+                    lhs_index: pair.lhs,
+                    rhs_index: pair.rhs,
+                    distance: pair.similarity.total_distance(config).as_f64(),
+                    fields: pair.similarity.explain(config),
+                })
+                .collect(),
+            unmatched_tracks: self.track_assignment.unmatched_tracks().to_vec(),
+        }
     }
 
     /// Get a reference to the [`TrackAssignment`] struct.
@@ -441,6 +794,15 @@ impl ReleaseSimilarity {
 
             None
         }))
+        .chain(iter::once_with(|| {
+            if let Difference::BothPresent(dist) = &self.artist_consistency {
+                if dist.as_f64() > 0.5 {
+                    return SimilarityProblem::ArtistMismatch.into();
+                }
+            }
+
+            None
+        }))
         .flatten()
     }
 }
@@ -454,6 +816,8 @@ pub enum SimilarityProblem {
     ResidualTracks(usize),
     /// The release ID is present on both releases, but it differs.
     WrongReleaseId,
+    /// One release is a compilation of various artists, but the other is not.
+    ArtistMismatch,
 }
 
 impl fmt::Display for SimilarityProblem {
@@ -462,6 +826,7 @@ impl fmt::Display for SimilarityProblem {
             Self::MissingTracks(count) => write!(f, "{count} missing tracks"),
             Self::ResidualTracks(count) => write!(f, "{count} residual tracks"),
             Self::WrongReleaseId => write!(f, "wrong id"),
+            Self::ArtistMismatch => write!(f, "various artists mismatch"),
         }
     }
 }
@@ -473,6 +838,42 @@ mod tests {
     use crate::util::FakeTrack;
     use float_eq::assert_float_eq;
 
+    #[test]
+    fn test_track_artist_mismatch_share() {
+        let track_artists = |artists: &[&'static str]| {
+            artists
+                .iter()
+                .map(|artist| Some(Cow::from(*artist)))
+                .collect::<Vec<_>>()
+        };
+
+        let share = track_artist_mismatch_share(
+            Some("The Beatles"),
+            track_artists(&["Beatles, The", "The Beatles feat. Billy Preston"]).into_iter(),
+        );
+        assert_float_eq!(share.unwrap(), 0.0, abs <= 0.000_1);
+
+        let share = track_artist_mismatch_share(
+            Some("Various Artists"),
+            track_artists(&["The Beatles", "The Rolling Stones", "The Kinks", "The Who"])
+                .into_iter(),
+        );
+        assert_float_eq!(share.unwrap(), 1.0, abs <= 0.000_1);
+
+        let share = track_artist_mismatch_share(
+            Some("The Beatles"),
+            track_artists(&["The Beatles", "The Rolling Stones", "The Kinks", "The Who"])
+                .into_iter(),
+        );
+        assert_float_eq!(share.unwrap(), 0.75, abs <= 0.000_1);
+
+        assert!(
+            track_artist_mismatch_share(None, track_artists(&["The Beatles"]).into_iter())
+                .is_none()
+        );
+        assert!(track_artist_mismatch_share(Some("The Beatles"), iter::once(None)).is_none());
+    }
+
     #[test]
     fn test_track_assignment_exact() {
         let tracks = [
@@ -522,6 +923,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_track_assignment_length_window() {
+        let lhs = [
+            FakeTrack::with_title_and_length("foo", 120),
+            FakeTrack::with_title_and_length("bar", 240),
+            FakeTrack::with_title_and_length("baz", 360),
+        ];
+        let rhs = [
+            FakeTrack::with_title_and_length("baz", 362),
+            FakeTrack::with_title_and_length("foo", 121),
+            FakeTrack::with_title_and_length("bar", 239),
+        ];
+
+        let mut config = Config::default();
+        let full_assignment = TrackAssignment::compute_from(&config, lhs.iter(), rhs.iter());
+
+        config.lookup.track_assignment.length_window = Some(10);
+        config.lookup.track_assignment.length_window_min_tracks = 0;
+        let assignment = TrackAssignment::compute_from(&config, lhs.iter(), rhs.iter());
+        assert_eq!(
+            assignment
+                .matched_tracks
+                .iter()
+                .map(|pair| (pair.lhs, pair.rhs))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2), (2, 0)]
+        );
+        assert_float_eq!(
+            assignment.to_distance().as_f64(),
+            full_assignment.to_distance().as_f64(),
+            abs <= 0.000_1
+        );
+    }
+
+    #[test]
+    fn test_track_assignment_locked_by_recording_id() {
+        // The titles suggest a different assignment than the recording IDs.
+        let lhs = [
+            FakeTrack::with_title_and_recording_id("foo", "recording-b"),
+            FakeTrack::with_title_and_recording_id("bar", "recording-a"),
+            FakeTrack::with_title("baz"),
+        ];
+        let rhs = [
+            FakeTrack::with_title_and_recording_id("foo", "RECORDING-A"),
+            FakeTrack::with_title_and_recording_id("bar", "recording-b"),
+            FakeTrack::with_title("baz"),
+        ];
+
+        let config = Config::default();
+        let assignment = TrackAssignment::compute_from(&config, lhs.iter(), rhs.iter());
+        assert_eq!(
+            assignment
+                .matched_tracks
+                .iter()
+                .map(|pair| (pair.lhs, pair.rhs))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0), (2, 2)]
+        );
+    }
+
     #[test]
     fn test_track_assignment_distinct() {
         let lhs = [FakeTrack::with_title("foo"), FakeTrack::with_title("bar")];
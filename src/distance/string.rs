@@ -7,17 +7,23 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! Functions for distance calculation between strings.
+//!
+//! When comparing many strings against each other (e.g., the titles of all tracks of two
+//! releases), the strings should be normalized once with [`normalize`] and compared with
+//! [`StringMetric::between_normalized`] using a shared [`StringDistanceBuffer`], so that neither
+//! the normalization nor the allocation of the Levenshtein matrix row is repeated for every pair.
 
 use super::Distance;
-use levenshtein::levenshtein;
+use serde::{Deserialize, Serialize};
 use std::cmp;
+use std::collections::BTreeSet;
 use unidecode::unidecode;
 
 /// Common suffixes that are stripped and added as a suffix during [`Self::normalize`].
 const SUFFIXES: [&str; 3] = [", the", ", a", ", an"];
 
 /// Normalize a string slice value for comparison.
-fn normalize(value: &str) -> String {
+pub fn normalize(value: &str) -> String {
     // Normalize all strings to ASCII lowercase.
     let mut value = unidecode(value);
     value.make_ascii_lowercase();
@@ -42,26 +48,154 @@ pub fn is_nonempty_and_equal_trimmed<T: AsRef<str>, S: AsRef<str>>(lhs: T, rhs:
     lhs == rhs && !lhs.is_empty()
 }
 
+/// Algorithm used to calculate the distance between two strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StringMetric {
+    /// Levenshtein edit distance, relative to the length of the longer string.
+    #[default]
+    Levenshtein,
+    /// Jaro-Winkler distance, which favors strings with a common prefix.
+    JaroWinkler,
+    /// Token set ratio, which ignores the order of words and words that only occur in one of the
+    /// strings (e.g., "Artist feat. Guest" vs. "Artist").
+    TokenSetRatio,
+}
+
+impl StringMetric {
+    /// Calculate the case- and whitespace-insensitive distance between two strings using this
+    /// metric, where 0.0 is minimum and 1.0 is the maximum distance.
+    pub fn between(self, lhs: &str, rhs: &str) -> Distance {
+        self.between_normalized(
+            &normalize(lhs),
+            &normalize(rhs),
+            &mut StringDistanceBuffer::default(),
+        )
+    }
+
+    /// Calculate the distance between two strings that have already been normalized with
+    /// [`normalize`], reusing the given buffer.
+    pub fn between_normalized(
+        self,
+        lhs: &str,
+        rhs: &str,
+        buffer: &mut StringDistanceBuffer,
+    ) -> Distance {
+        match self {
+            Self::Levenshtein => buffer.levenshtein_distance(lhs, rhs),
+            Self::JaroWinkler => jaro_winkler_distance(lhs, rhs),
+            Self::TokenSetRatio => token_set_distance(lhs, rhs, buffer),
+        }
+    }
+}
+
+/// Scratch space for the Levenshtein distance calculation that can be reused across comparisons.
+#[derive(Debug, Clone, Default)]
+pub struct StringDistanceBuffer {
+    /// Characters of the right-hand string.
+    rhs_chars: Vec<char>,
+    /// The current row of the edit distance matrix.
+    row: Vec<usize>,
+}
+
+impl StringDistanceBuffer {
+    /// Calculate the Levenshtein edit distance between two strings (in characters).
+    fn levenshtein(&mut self, lhs: &str, rhs: &str) -> usize {
+        self.rhs_chars.clear();
+        self.rhs_chars.extend(rhs.chars());
+        self.row.clear();
+        self.row.extend(0..=self.rhs_chars.len());
+
+        for (i, lhs_char) in lhs.chars().enumerate() {
+            // Value of the previous row in the previous column.
+            let mut diagonal = self.row[0];
+            self.row[0] = i + 1;
+            for (j, rhs_char) in self.rhs_chars.iter().enumerate() {
+                let above = self.row[j + 1];
+                let substitution_cost = usize::from(lhs_char != *rhs_char);
+                self.row[j + 1] = cmp::min(
+                    cmp::min(above, self.row[j]) + 1,
+                    diagonal + substitution_cost,
+                );
+                diagonal = above;
+            }
+        }
+
+        self.row[self.rhs_chars.len()]
+    }
+
+    /// Calculate the Levenshtein distance between two normalized strings, relative to the length
+    /// of the longer string.
+    #[expect(clippy::cast_precision_loss)]
+    fn levenshtein_distance(&mut self, lhs: &str, rhs: &str) -> Distance {
+        let max_possible_distance = cmp::max(lhs.len(), rhs.len());
+
+        // Special case: If both strings are empty after normalization, then the strings should be
+        // considered equal and we can exit early. Otherwise we would divide by zero later on.
+        if max_possible_distance == 0 {
+            return Distance::MIN;
+        }
+
+        let levenshtein_distance = self.levenshtein(lhs, rhs);
+
+        // FIXME: It's extremely unlikely, but this conversion to f64 is fallible. Hence, it should
+        // use f64::try_from(usize) instead, but unfortunately that doesn't exist.
+        Distance::from(levenshtein_distance as f64 / max_possible_distance as f64)
+    }
+}
+
 /// Calculate the case- and whitespace-insensitive distance between two strings, where 0.0 is
 /// minimum and 1.0 is the maximum distance.
-#[expect(clippy::cast_precision_loss)]
 pub fn between(lhs: &str, rhs: &str) -> Distance {
-    let lhs = normalize(lhs);
-    let rhs = normalize(rhs);
-
-    let max_possible_distance = cmp::max(lhs.len(), rhs.len());
+    StringMetric::default().between(lhs, rhs)
+}
 
-    // Special case: If both strings are empty after normalization, then the strings should be
-    // considered equal and we can exit early. Otherwise we would divide by zero later on.
-    if max_possible_distance == 0 {
+/// Calculate the Jaro-Winkler distance between two normalized strings.
+fn jaro_winkler_distance(lhs: &str, rhs: &str) -> Distance {
+    // Both strings being empty is treated as equality, like for the Levenshtein distance.
+    if lhs.is_empty() && rhs.is_empty() {
         return Distance::MIN;
     }
 
-    let levenshtein_distance = levenshtein(&lhs, &rhs);
+    Distance::from(1.0 - strsim::jaro_winkler(lhs, rhs))
+}
+
+/// Calculate the token set distance between two normalized strings.
+///
+/// Both strings are split into words, and the sorted intersection of both word sets is compared
+/// with the intersection plus the remaining words of either string. The smallest Levenshtein
+/// distance of these comparisons is returned, so that a string that contains all words of the
+/// other one is considered equal.
+fn token_set_distance(lhs: &str, rhs: &str, buffer: &mut StringDistanceBuffer) -> Distance {
+    let lhs_tokens: BTreeSet<&str> = lhs.split_whitespace().collect();
+    let rhs_tokens: BTreeSet<&str> = rhs.split_whitespace().collect();
+
+    let join = |tokens: Vec<&str>| tokens.join(" ");
+    let intersection = join(lhs_tokens.intersection(&rhs_tokens).copied().collect());
+    let with_remainder = |remainder: Vec<&str>| {
+        let remainder = join(remainder);
+        [intersection.as_str(), remainder.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let lhs_combined = with_remainder(lhs_tokens.difference(&rhs_tokens).copied().collect());
+    let rhs_combined = with_remainder(rhs_tokens.difference(&lhs_tokens).copied().collect());
 
-    // FIXME: It's extremely unlikely, but this conversion to f64 is fallible. Hence, it should use
-    // f64::try_from(usize) instead, but unfortunately that doesn't exist.
-    Distance::from(levenshtein_distance as f64 / max_possible_distance as f64)
+    let combined_distance = buffer.levenshtein_distance(&lhs_combined, &rhs_combined);
+    if intersection.is_empty() {
+        // Without common words, comparing against the (empty) intersection is meaningless.
+        return combined_distance;
+    }
+
+    cmp::min(
+        combined_distance,
+        cmp::min(
+            buffer.levenshtein_distance(&intersection, &lhs_combined),
+            buffer.levenshtein_distance(&intersection, &rhs_combined),
+        ),
+    )
 }
 
 #[cfg(test)]
@@ -117,4 +251,63 @@ mod tests {
         let distance = between("Foo & Bar, The", "The Foo and Bar");
         assert_float_eq!(distance.as_f64(), 0.0, abs <= 0.000_1);
     }
+
+    #[test]
+    fn test_string_distance_buffer_levenshtein() {
+        let mut buffer = StringDistanceBuffer::default();
+        assert_eq!(buffer.levenshtein("kitten", "sitting"), 3);
+        assert_eq!(buffer.levenshtein("", "abc"), 3);
+        assert_eq!(buffer.levenshtein("abc", ""), 3);
+        assert_eq!(buffer.levenshtein("flaw", "lawn"), 2);
+        assert_eq!(buffer.levenshtein("étude", "etude"), 1);
+        assert_eq!(buffer.levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_string_distance_between_normalized() {
+        let mut buffer = StringDistanceBuffer::default();
+        for metric in [
+            StringMetric::Levenshtein,
+            StringMetric::JaroWinkler,
+            StringMetric::TokenSetRatio,
+        ] {
+            for (lhs, rhs) in [("Foo", "foobar"), ("Foo & Bar, The", "the foo and bar")] {
+                assert_eq!(
+                    metric.between_normalized(&normalize(lhs), &normalize(rhs), &mut buffer),
+                    metric.between(lhs, rhs),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_metric_jaro_winkler() {
+        let metric = StringMetric::JaroWinkler;
+        assert_float_eq!(metric.between("Foo", "foo").as_f64(), 0.0, abs <= 0.000_1);
+        assert_float_eq!(metric.between("", "").as_f64(), 0.0, abs <= 0.000_1);
+        assert_float_eq!(metric.between("abc", "xyz").as_f64(), 1.0, abs <= 0.000_1);
+        assert!(
+            metric.between("Martha", "Marhta").as_f64()
+                < StringMetric::Levenshtein
+                    .between("Martha", "Marhta")
+                    .as_f64()
+        );
+    }
+
+    #[test]
+    fn test_string_metric_token_set_ratio() {
+        let metric = StringMetric::TokenSetRatio;
+        assert_float_eq!(
+            metric.between("Foo Bar", "bar foo").as_f64(),
+            0.0,
+            abs <= 0.000_1
+        );
+        assert_float_eq!(
+            metric.between("Artist", "Artist feat. Guest").as_f64(),
+            0.0,
+            abs <= 0.000_1
+        );
+        assert_float_eq!(metric.between("foo", "bar").as_f64(), 1.0, abs <= 0.000_1);
+        assert_float_eq!(metric.between("", "").as_f64(), 0.0, abs <= 0.000_1);
+    }
 }
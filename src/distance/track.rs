@@ -8,9 +8,43 @@
 
 //! Functions for distance calculation between [`ReleaseLike`] objects.
 
-use super::{string, Difference, Distance};
+use super::{string, Difference, Distance, FieldDistance, StringDistanceBuffer, WeightedDistance};
 use crate::track::TrackLike;
 use crate::Config;
+use num::rational::Ratio;
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+/// Normalized string fields and identifiers of a track.
+///
+/// When a track is compared to many other tracks, these are computed only once instead of
+/// normalizing the same strings for every comparison.
+#[derive(Debug, Clone, Default)]
+pub struct NormalizedTrackStrings {
+    /// The normalized track title.
+    track_title: Option<String>,
+    /// The normalized track artist.
+    track_artist: Option<String>,
+    /// The normalized ISRCs.
+    isrc: Option<BTreeSet<String>>,
+    /// The normalized MusicBrainz Artist IDs.
+    musicbrainz_artist_id: Option<BTreeSet<String>>,
+}
+
+impl NormalizedTrackStrings {
+    /// Normalize the string fields and identifiers of the track.
+    pub fn new<T: TrackLike + ?Sized>(track: &T) -> Self {
+        Self {
+            track_title: track.track_title().as_deref().map(string::normalize),
+            track_artist: track.track_artist().as_deref().map(string::normalize),
+            isrc: identifier_set(track.isrc(), normalize_isrc),
+            musicbrainz_artist_id: identifier_set(
+                track.musicbrainz_artist_id(),
+                str::to_ascii_lowercase,
+            ),
+        }
+    }
+}
 
 /// Result of a comparison between two tracks that represents how similar they are to each other.
 #[derive(Debug, Clone)]
@@ -25,6 +59,33 @@ pub struct TrackSimilarity {
     pub track_length: Difference,
     /// The distance between the two MusicBrainz Recording Ids.
     pub musicbrainz_recording_id: Difference,
+    /// The distance between the two sets of ISRCs.
+    pub isrc: Difference,
+    /// The distance between the two sets of MusicBrainz Artist IDs.
+    pub musicbrainz_artist_id: Difference,
+}
+
+/// Collect the non-empty values into a set after applying the normalization function, or return
+/// `None` if there are no values.
+fn identifier_set<'a>(
+    values: impl Iterator<Item = Cow<'a, str>>,
+    normalize: impl Fn(&str) -> String,
+) -> Option<BTreeSet<String>> {
+    let values: BTreeSet<String> = values
+        .map(|value| normalize(value.trim()))
+        .filter(|value| !value.is_empty())
+        .collect();
+    (!values.is_empty()).then_some(values)
+}
+
+/// Normalize an ISRC for comparison by removing separators (e.g., dashes) and converting it to
+/// uppercase.
+fn normalize_isrc(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
 }
 
 impl TrackSimilarity {
@@ -37,44 +98,119 @@ impl TrackSimilarity {
             track_number: Difference::Added,
             track_length: Difference::Added,
             musicbrainz_recording_id: Difference::Added,
+            isrc: Difference::Added,
+            musicbrainz_artist_id: Difference::Added,
         }
     }
 
-    /// Returns the overall distance of the two tracks.
-    pub fn total_distance(&self, config: &Config) -> Distance {
+    /// Returns the weighted distances of the individual fields, or `None` for fields that are not
+    /// taken into account (e.g., because they are missing on one side).
+    pub fn weighted_distances(
+        &self,
+        config: &Config,
+    ) -> [(&'static str, Option<WeightedDistance<'_>>); 7] {
         let weights = &config.weights.track;
 
         [
-            self.track_title
-                .to_distance()
-                .to_weighted(weights.track_title)
-                .into(),
-            self.track_artist
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.track_artist)),
-            self.track_number
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.track_number)),
-            self.track_length
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.track_length)),
-            self.musicbrainz_recording_id
-                .to_distance_if_both_present()
-                .map(|dist| dist.to_weighted(weights.musicbrainz_recording_id)),
+            (
+                "track_title",
+                self.track_title
+                    .to_distance()
+                    .to_weighted(weights.track_title)
+                    .into(),
+            ),
+            (
+                "track_artist",
+                self.track_artist
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.track_artist)),
+            ),
+            (
+                "track_number",
+                self.track_number
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.track_number)),
+            ),
+            (
+                "track_length",
+                self.track_length
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.track_length)),
+            ),
+            (
+                "musicbrainz_recording_id",
+                self.musicbrainz_recording_id
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.musicbrainz_recording_id)),
+            ),
+            (
+                "isrc",
+                self.isrc
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.isrc)),
+            ),
+            (
+                "musicbrainz_artist_id",
+                self.musicbrainz_artist_id
+                    .to_distance_if_both_present()
+                    .map(|dist| dist.to_weighted(weights.musicbrainz_artist_id)),
+            ),
         ]
-        .into_iter()
-        .flatten()
-        .sum()
+    }
+
+    /// Returns the overall distance of the two tracks.
+    pub fn total_distance(&self, config: &Config) -> Distance {
+        self.weighted_distances(config)
+            .into_iter()
+            .filter_map(|(_, distance)| distance)
+            .sum()
+    }
+
+    /// Returns the contributions of the individual fields to the overall distance.
+    pub fn explain(&self, config: &Config) -> Vec<FieldDistance> {
+        self.weighted_distances(config)
+            .iter()
+            .map(|(field, distance)| FieldDistance::new(field, distance.as_ref()))
+            .collect()
     }
 
     /// Calculate the distance between two releases.
-    pub fn detect<T1, T2>(lhs: &T1, rhs: &T2) -> Self
+    pub fn detect<T1, T2>(config: &Config, lhs: &T1, rhs: &T2) -> Self
+    where
+        T1: TrackLike + ?Sized,
+        T2: TrackLike + ?Sized,
+    {
+        Self::detect_normalized(
+            config,
+            (lhs, &NormalizedTrackStrings::new(lhs)),
+            (rhs, &NormalizedTrackStrings::new(rhs)),
+            &mut StringDistanceBuffer::default(),
+        )
+    }
+
+    /// Calculate the distance between two tracks, using the precomputed normalized strings of
+    /// both tracks and reusing the given buffer for the string distance calculation.
+    pub fn detect_normalized<T1, T2>(
+        config: &Config,
+        (lhs, lhs_strings): (&T1, &NormalizedTrackStrings),
+        (rhs, rhs_strings): (&T2, &NormalizedTrackStrings),
+        buffer: &mut StringDistanceBuffer,
+    ) -> Self
     where
         T1: TrackLike + ?Sized,
         T2: TrackLike + ?Sized,
     {
-        let track_title = Difference::between_options(lhs.track_title(), rhs.track_title());
-        let track_artist = Difference::between_options(lhs.track_artist(), rhs.track_artist());
+        let metrics = &config.string_metrics;
+        let track_title = Difference::between_options_fn(
+            lhs_strings.track_title.as_deref(),
+            rhs_strings.track_title.as_deref(),
+            |lhs, rhs| metrics.track_title.between_normalized(lhs, rhs, buffer),
+        );
+        let track_artist = Difference::between_options_fn(
+            lhs_strings.track_artist.as_deref(),
+            rhs_strings.track_artist.as_deref(),
+            |lhs, rhs| metrics.track_artist.between_normalized(lhs, rhs, buffer),
+        );
         let track_number = Difference::between_options(lhs.track_number(), rhs.track_number());
         let track_length = Difference::between_options(lhs.track_length(), rhs.track_length());
         let musicbrainz_recording_id = Difference::between_options_fn(
@@ -88,6 +224,23 @@ impl TrackSimilarity {
                 }
             },
         );
+        // A recording can have multiple ISRCs, so sharing any of them is considered a match.
+        let isrc = Difference::between_options_fn(
+            lhs_strings.isrc.as_ref(),
+            rhs_strings.isrc.as_ref(),
+            |lhs, rhs| Distance::from(!lhs.is_disjoint(rhs)),
+        );
+        // For tracks with multiple artists, the distance is the fraction of artists that only
+        // occur on one side.
+        let musicbrainz_artist_id = Difference::between_options_fn(
+            lhs_strings.musicbrainz_artist_id.as_ref(),
+            rhs_strings.musicbrainz_artist_id.as_ref(),
+            |lhs, rhs| {
+                let union_count = lhs.union(rhs).count();
+                let intersection_count = lhs.intersection(rhs).count();
+                Distance::from(Ratio::new(union_count - intersection_count, union_count))
+            },
+        );
 
         TrackSimilarity {
             track_title,
@@ -95,6 +248,8 @@ impl TrackSimilarity {
             track_number,
             track_length,
             musicbrainz_recording_id,
+            isrc,
+            musicbrainz_artist_id,
         }
     }
 }
@@ -103,6 +258,7 @@ impl TrackSimilarity {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::distance::StringMetric;
     use crate::util::FakeTrack;
     use float_eq::assert_float_eq;
 
@@ -110,7 +266,7 @@ mod tests {
     fn test_track_distance_title_exact() {
         let track = FakeTrack::with_title("foo");
         let config = Config::default();
-        let distance = TrackSimilarity::detect(&track, &track).total_distance(&config);
+        let distance = TrackSimilarity::detect(&config, &track, &track).total_distance(&config);
         assert_float_eq!(distance.as_f64(), 0.0, abs <= 0.000_1);
     }
 
@@ -119,7 +275,7 @@ mod tests {
         let track1 = FakeTrack::with_title("foo");
         let track2 = FakeTrack::with_title("bar");
         let config = Config::default();
-        let distance = TrackSimilarity::detect(&track1, &track2).total_distance(&config);
+        let distance = TrackSimilarity::detect(&config, &track1, &track2).total_distance(&config);
         assert_float_eq!(distance.as_f64(), 1.0, abs <= 0.000_1);
     }
 
@@ -128,7 +284,71 @@ mod tests {
         let track1 = FakeTrack::with_title("foo");
         let track2 = FakeTrack::with_title("barfoo");
         let config = Config::default();
-        let distance = TrackSimilarity::detect(&track1, &track2).total_distance(&config);
+        let distance = TrackSimilarity::detect(&config, &track1, &track2).total_distance(&config);
         assert_float_eq!(distance.as_f64(), 0.5, abs <= 0.000_1);
     }
+
+    #[test]
+    fn test_track_distance_explain() {
+        let track1 = FakeTrack::with_title("foo");
+        let track2 = FakeTrack::with_title("barfoo");
+        let config = Config::default();
+        let fields = TrackSimilarity::detect(&config, &track1, &track2).explain(&config);
+        assert_eq!(fields.len(), 7);
+        assert_eq!(fields[0].field, "track_title");
+        assert_float_eq!(fields[0].distance.unwrap(), 0.5, abs <= 0.000_1);
+        assert_float_eq!(
+            fields[0].weighted_distance.unwrap(),
+            0.5 * config.weights.track.track_title,
+            abs <= 0.000_1
+        );
+        assert!(fields[1].distance.is_none());
+    }
+
+    #[test]
+    fn test_track_distance_identifiers() {
+        let track1 = FakeTrack::with_title_and_identifiers(
+            "foo",
+            &["USRC17607839", "GBAYE0601498"],
+            &["artist-a", "artist-b"],
+        );
+        let track2 = FakeTrack::with_title_and_identifiers(
+            "foo",
+            &["us-rc1-76-07839"],
+            &["ARTIST-A", "artist-c"],
+        );
+        let config = Config::default();
+        let similarity = TrackSimilarity::detect(&config, &track1, &track2);
+        assert!(similarity.isrc.is_equal());
+        assert_float_eq!(
+            similarity
+                .musicbrainz_artist_id
+                .to_distance_if_both_present()
+                .unwrap()
+                .as_f64(),
+            2.0 / 3.0,
+            abs <= 0.000_1
+        );
+
+        let track3 = FakeTrack::with_title_and_identifiers("foo", &["GBAYE0000001"], &[]);
+        let similarity = TrackSimilarity::detect(&config, &track1, &track3);
+        assert_float_eq!(similarity.isrc.to_distance().as_f64(), 1.0, abs <= 0.000_1);
+        assert!(similarity
+            .musicbrainz_artist_id
+            .to_distance_if_both_present()
+            .is_none());
+    }
+
+    #[test]
+    fn test_track_distance_title_string_metric() {
+        let track1 = FakeTrack::with_title("Foo Bar");
+        let track2 = FakeTrack::with_title("Bar Foo");
+        let mut config = Config::default();
+        let distance = TrackSimilarity::detect(&config, &track1, &track2).total_distance(&config);
+        assert!(distance.as_f64() > 0.0);
+
+        config.string_metrics.track_title = StringMetric::TokenSetRatio;
+        let distance = TrackSimilarity::detect(&config, &track1, &track2).total_distance(&config);
+        assert_float_eq!(distance.as_f64(), 0.0, abs <= 0.000_1);
+    }
 }
@@ -0,0 +1,575 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Enrichment of the tags with metadata from other services.
+//!
+//! MusicBrainz does not have genres for many releases. After a candidate has been selected, the
+//! configured enrichment providers (e.g., the genre tags on [ListenBrainz][listenbrainz] or the
+//! top tags on [Last.fm][lastfm]) are queried in order until one of them returns tags, which are
+//! then used to fill the genre and mood of tracks that do not have one yet.
+//!
+//! [listenbrainz]: https://listenbrainz.org
+//! [lastfm]: https://www.last.fm
+
+use crate::config::{EnrichmentConfig, EnrichmentProviderType, NetworkConfig};
+use crate::network;
+use crate::release::ReleaseLike;
+use crate::tag::TagKey;
+use crate::track::TrackLike;
+use crate::TaggedFileCollection;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// URL of the ListenBrainz recording metadata endpoint.
+const LISTENBRAINZ_METADATA_URL: &str = "https://api.listenbrainz.org/1/metadata/recording/";
+
+/// URL of the Last.fm API.
+const LASTFM_API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Encountered when fetching tags from an enrichment provider fails.
+#[derive(Error, Debug)]
+pub enum EnrichmentError {
+    /// The HTTP request failed.
+    #[error("Enrichment request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The response could not be parsed.
+    #[error("Invalid enrichment response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+    /// The API returned an error.
+    #[error("Enrichment provider returned an error: {0}")]
+    Api(String),
+}
+
+/// Information about a release that is used to look up its tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnrichmentQuery {
+    /// Artist(s) primarily credited on the release.
+    pub release_artist: Option<String>,
+    /// Title of the release.
+    pub release_title: Option<String>,
+    /// MusicBrainz Recording IDs of the tracks.
+    pub musicbrainz_recording_ids: Vec<String>,
+}
+
+impl EnrichmentQuery {
+    /// Create a query from a tagged release.
+    pub fn from_release(release: &TaggedFileCollection) -> Self {
+        Self {
+            release_artist: release.release_artist().map(Cow::into_owned),
+            release_title: release.release_title().map(Cow::into_owned),
+            musicbrainz_recording_ids: release
+                .tracks()
+                .filter_map(TrackLike::musicbrainz_recording_id)
+                .map(Cow::into_owned)
+                .collect(),
+        }
+    }
+}
+
+/// A tag returned by an enrichment provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightedTag {
+    /// Name of the tag (e.g., `jazz`).
+    pub name: String,
+    /// How often the tag was applied.
+    pub count: u64,
+    /// Whether the tag may be used as genre (i.e., it is not known to be something else).
+    pub is_genre: bool,
+}
+
+/// A service that provides tags for a release.
+pub trait EnrichmentProvider {
+    /// Name of the provider (for log messages).
+    fn name(&self) -> &'static str;
+
+    /// Fetch the tags of the release.
+    async fn fetch_tags(
+        &self,
+        query: &EnrichmentQuery,
+    ) -> Result<Vec<WeightedTag>, EnrichmentError>;
+}
+
+/// Build an HTTP client for enrichment requests.
+fn http_client(network: &NetworkConfig) -> Option<reqwest::Client> {
+    network::http_client(network)
+        .inspect_err(|err| log::warn!("Failed to create HTTP client for enrichment: {err}"))
+        .ok()
+}
+
+/// Tag in a ListenBrainz metadata response.
+#[derive(Debug, Deserialize)]
+struct ListenBrainzTag {
+    /// Name of the tag.
+    tag: String,
+    /// Number of users that applied the tag.
+    count: u64,
+    /// MusicBrainz ID of the genre (only present if the tag is a genre).
+    genre_mbid: Option<String>,
+}
+
+/// Tags of a recording in a ListenBrainz metadata response.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ListenBrainzTags {
+    /// Tags of the recording.
+    recording: Vec<ListenBrainzTag>,
+    /// Tags of the release group of the recording.
+    release_group: Vec<ListenBrainzTag>,
+}
+
+/// Metadata of a recording in a ListenBrainz metadata response.
+#[derive(Debug, Deserialize)]
+struct ListenBrainzRecordingMetadata {
+    /// Tags of the recording and its release group.
+    #[serde(default)]
+    tag: ListenBrainzTags,
+}
+
+/// Parse the tags from a ListenBrainz recording metadata response.
+fn parse_listenbrainz_tags(body: &str) -> Result<Vec<WeightedTag>, EnrichmentError> {
+    let response: HashMap<String, ListenBrainzRecordingMetadata> = serde_json::from_str(body)?;
+    Ok(response
+        .into_values()
+        .flat_map(|metadata| {
+            metadata
+                .tag
+                .release_group
+                .into_iter()
+                .chain(metadata.tag.recording)
+        })
+        .map(|tag| WeightedTag {
+            name: tag.tag,
+            count: tag.count,
+            is_genre: tag.genre_mbid.is_some(),
+        })
+        .collect())
+}
+
+/// Provider for the genre tags of recordings and release groups on ListenBrainz.
+#[derive(Debug, Clone)]
+pub struct ListenBrainzProvider {
+    /// The HTTP client.
+    client: reqwest::Client,
+}
+
+impl EnrichmentProvider for ListenBrainzProvider {
+    fn name(&self) -> &'static str {
+        "ListenBrainz"
+    }
+
+    async fn fetch_tags(
+        &self,
+        query: &EnrichmentQuery,
+    ) -> Result<Vec<WeightedTag>, EnrichmentError> {
+        if query.musicbrainz_recording_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body = self
+            .client
+            .get(LISTENBRAINZ_METADATA_URL)
+            .query(&[
+                (
+                    "recording_mbids",
+                    query.musicbrainz_recording_ids.join(",").as_str(),
+                ),
+                ("inc", "tag"),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_listenbrainz_tags(&body)
+    }
+}
+
+/// Tag in a Last.fm top tags response.
+#[derive(Debug, Deserialize)]
+struct LastFmTag {
+    /// Name of the tag.
+    name: String,
+    /// Weight of the tag (from 0 to 100).
+    count: u64,
+}
+
+/// Top tags in a Last.fm response.
+#[derive(Debug, Deserialize)]
+struct LastFmTopTags {
+    /// The tags, ordered by weight.
+    #[serde(default)]
+    tag: Vec<LastFmTag>,
+}
+
+/// Response of the Last.fm `album.getTopTags` method.
+#[derive(Debug, Deserialize)]
+struct LastFmResponse {
+    /// The top tags (if the request was successful).
+    toptags: Option<LastFmTopTags>,
+    /// Error message (if the request failed).
+    message: Option<String>,
+}
+
+/// Parse the tags from a Last.fm `album.getTopTags` response.
+fn parse_lastfm_tags(body: &str) -> Result<Vec<WeightedTag>, EnrichmentError> {
+    let response: LastFmResponse = serde_json::from_str(body)?;
+    let Some(top_tags) = response.toptags else {
+        return Err(EnrichmentError::Api(
+            response
+                .message
+                .unwrap_or_else(|| "missing top tags".to_string()),
+        ));
+    };
+    Ok(top_tags
+        .tag
+        .into_iter()
+        .map(|tag| WeightedTag {
+            name: tag.name,
+            count: tag.count,
+            is_genre: true,
+        })
+        .collect())
+}
+
+/// Provider for the top tags of albums on Last.fm.
+#[derive(Debug, Clone)]
+pub struct LastFmProvider {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// The Last.fm API key.
+    api_key: String,
+}
+
+impl EnrichmentProvider for LastFmProvider {
+    fn name(&self) -> &'static str {
+        "Last.fm"
+    }
+
+    async fn fetch_tags(
+        &self,
+        query: &EnrichmentQuery,
+    ) -> Result<Vec<WeightedTag>, EnrichmentError> {
+        let (Some(artist), Some(album)) = (&query.release_artist, &query.release_title) else {
+            return Ok(Vec::new());
+        };
+
+        let body = self
+            .client
+            .get(LASTFM_API_URL)
+            .query(&[
+                ("method", "album.gettoptags"),
+                ("artist", artist.as_str()),
+                ("album", album.as_str()),
+                ("autocorrect", "1"),
+                ("api_key", self.api_key.as_str()),
+                ("format", "json"),
+            ])
+            .send()
+            .await?
+            .text()
+            .await?;
+        parse_lastfm_tags(&body)
+    }
+}
+
+/// An enrichment provider that is enabled in the configuration.
+#[derive(Debug, Clone)]
+enum EnrichmentProviderItem {
+    /// ListenBrainz provider.
+    ListenBrainz(ListenBrainzProvider),
+    /// Last.fm provider.
+    LastFm(LastFmProvider),
+}
+
+impl EnrichmentProviderItem {
+    /// Create the provider of the given type.
+    ///
+    /// Returns `None` if the provider is missing an API key.
+    fn from_config(
+        provider_type: EnrichmentProviderType,
+        config: &EnrichmentConfig,
+        network: &NetworkConfig,
+    ) -> Option<Self> {
+        match provider_type {
+            EnrichmentProviderType::ListenBrainz => {
+                Some(Self::ListenBrainz(ListenBrainzProvider {
+                    client: http_client(network)?,
+                }))
+            }
+            EnrichmentProviderType::LastFm => {
+                let Some(api_key) = &config.lastfm_api_key else {
+                    log::warn!("Last.fm enrichment is enabled, but the API key is not configured");
+                    return None;
+                };
+                Some(Self::LastFm(LastFmProvider {
+                    client: http_client(network)?,
+                    api_key: api_key.clone(),
+                }))
+            }
+        }
+    }
+
+    /// Name of the provider (for log messages).
+    fn name(&self) -> &'static str {
+        match self {
+            Self::ListenBrainz(provider) => provider.name(),
+            Self::LastFm(provider) => provider.name(),
+        }
+    }
+
+    /// Fetch the tags of the release.
+    async fn fetch_tags(
+        &self,
+        query: &EnrichmentQuery,
+    ) -> Result<Vec<WeightedTag>, EnrichmentError> {
+        match self {
+            Self::ListenBrainz(provider) => provider.fetch_tags(query).await,
+            Self::LastFm(provider) => provider.fetch_tags(query).await,
+        }
+    }
+}
+
+/// Genres and mood selected from the tags of an enrichment provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enrichment {
+    /// Genres, ordered by weight.
+    pub genres: Vec<String>,
+    /// Mood.
+    pub mood: Option<String>,
+}
+
+impl Enrichment {
+    /// Returns `true` if neither genres nor a mood were found.
+    pub fn is_empty(&self) -> bool {
+        self.genres.is_empty() && self.mood.is_none()
+    }
+
+    /// Assign the genres and mood to the tracks that do not have any yet.
+    pub fn assign_to(&self, release: &mut TaggedFileCollection) {
+        let genres = self
+            .genres
+            .iter()
+            .map(|genre| Cow::from(genre.as_str()))
+            .collect::<Vec<_>>();
+        for track in release.tracks_mut() {
+            if !genres.is_empty() && track.genre().next().is_none() {
+                track.set_tag_values(&TagKey::Genre, &genres);
+            }
+            if self.mood.is_some() && track.mood().is_none() {
+                track.set_tag_value(&TagKey::Mood, self.mood.as_deref().map(Cow::from));
+            }
+        }
+    }
+}
+
+/// Fills missing genres and moods from the configured enrichment providers.
+#[derive(Debug, Clone)]
+pub struct Enricher {
+    /// The enabled providers, in the order in which they are queried.
+    providers: Vec<EnrichmentProviderItem>,
+    /// Maximum number of genres.
+    max_genres: usize,
+    /// Minimum weight of a tag relative to the most frequent tag.
+    min_tag_weight: f64,
+    /// Tags that are used as mood instead of genre (lowercase).
+    mood_tags: Vec<String>,
+}
+
+impl Enricher {
+    /// Create an enricher from the configuration.
+    ///
+    /// Returns `None` if no provider is enabled.
+    pub fn from_config(config: &EnrichmentConfig, network: &NetworkConfig) -> Option<Self> {
+        let providers = config
+            .providers
+            .iter()
+            .filter_map(|provider_type| {
+                EnrichmentProviderItem::from_config(*provider_type, config, network)
+            })
+            .collect::<Vec<_>>();
+        if providers.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            providers,
+            max_genres: config.max_genres,
+            min_tag_weight: config.min_tag_weight,
+            mood_tags: config
+                .mood_tags
+                .iter()
+                .map(|tag| tag.to_lowercase())
+                .collect(),
+        })
+    }
+
+    /// Select the genres and mood from the tags of a provider.
+    ///
+    /// Tags with the same name are merged, and tags that were applied much less often than the
+    /// most frequent tag are ignored.
+    fn select(&self, tags: Vec<WeightedTag>) -> Enrichment {
+        let mut merged_tags: Vec<WeightedTag> = Vec::new();
+        for tag in tags {
+            let name = tag.name.trim().to_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+            match merged_tags.iter_mut().find(|merged| merged.name == name) {
+                Some(merged) => {
+                    merged.count += tag.count;
+                    merged.is_genre |= tag.is_genre;
+                }
+                None => merged_tags.push(WeightedTag { name, ..tag }),
+            }
+        }
+        merged_tags.sort_by(|a, b| b.count.cmp(&a.count));
+
+        let max_count = merged_tags.first().map_or(0, |tag| tag.count);
+        #[expect(clippy::cast_precision_loss)]
+        let is_relevant =
+            |tag: &WeightedTag| tag.count as f64 >= max_count as f64 * self.min_tag_weight;
+        let is_mood = |tag: &WeightedTag| self.mood_tags.contains(&tag.name);
+        Enrichment {
+            genres: merged_tags
+                .iter()
+                .filter(|tag| tag.is_genre && !is_mood(tag) && is_relevant(tag))
+                .take(self.max_genres)
+                .map(|tag| tag.name.clone())
+                .collect(),
+            mood: merged_tags
+                .iter()
+                .find(|tag| is_mood(tag) && is_relevant(tag))
+                .map(|tag| tag.name.clone()),
+        }
+    }
+
+    /// Fill the missing genres and moods of the release with the tags of the first provider that
+    /// returns any.
+    ///
+    /// Nothing is fetched if all tracks already have a genre (e.g., from MusicBrainz). Failing
+    /// providers are skipped.
+    pub async fn enrich(&self, release: &mut TaggedFileCollection) {
+        if release.tracks().all(|track| track.genre().next().is_some()) {
+            return;
+        }
+
+        let query = EnrichmentQuery::from_release(release);
+        for provider in &self.providers {
+            match provider.fetch_tags(&query).await {
+                Ok(tags) => {
+                    let enrichment = self.select(tags);
+                    if enrichment.is_empty() {
+                        continue;
+                    }
+                    log::info!(
+                        "Using genres {:?} and mood {:?} from {}",
+                        enrichment.genres,
+                        enrichment.mood,
+                        provider.name()
+                    );
+                    enrichment.assign_to(release);
+                    return;
+                }
+                Err(err) => log::warn!("Failed to fetch tags from {}: {err}", provider.name()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn test_parse_listenbrainz_tags() {
+        let body = r#"{
+            "8f3471b5-7e6a-48da-86a9-c1c07a0f47ae": {
+                "tag": {
+                    "recording": [{"count": 1, "tag": "hard bop", "genre_mbid": "b6f8ee2e-6a8a-4a36-a9b2-9f8a1ebc6b7e"}],
+                    "release_group": [
+                        {"count": 3, "tag": "jazz", "genre_mbid": "fd8fa2c8-4d5b-4d3e-a1c8-2a5b1e7a9f5e"},
+                        {"count": 2, "tag": "seen live"}
+                    ]
+                }
+            },
+            "a8d2c6f5-3b1e-4a7c-9f0d-2e4b6c8a0d1f": {}
+        }"#;
+        let tags = parse_listenbrainz_tags(body).unwrap();
+        assert_eq!(tags.len(), 3);
+        assert!(tags
+            .iter()
+            .any(|tag| tag.name == "jazz" && tag.count == 3 && tag.is_genre));
+        assert!(tags
+            .iter()
+            .any(|tag| tag.name == "seen live" && !tag.is_genre));
+    }
+
+    #[test]
+    fn test_parse_lastfm_tags() {
+        let body = r#"{"toptags": {"tag": [{"count": 100, "name": "Jazz"}, {"count": 12, "name": "chill"}], "@attr": {"artist": "Ahmad Jamal", "album": "At the Pershing"}}}"#;
+        let tags = parse_lastfm_tags(body).unwrap();
+        assert_eq!(
+            tags,
+            vec![
+                WeightedTag {
+                    name: "Jazz".to_string(),
+                    count: 100,
+                    is_genre: true
+                },
+                WeightedTag {
+                    name: "chill".to_string(),
+                    count: 12,
+                    is_genre: true
+                },
+            ]
+        );
+
+        let body = r#"{"error": 6, "message": "Album not found", "links": []}"#;
+        assert!(matches!(
+            parse_lastfm_tags(body),
+            Err(EnrichmentError::Api(message)) if message == "Album not found"
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let network = Config::default().network;
+        let mut config = Config::default().enrichment;
+        config.providers = vec![EnrichmentProviderType::ListenBrainz];
+        config.max_genres = 2;
+        config.min_tag_weight = 0.25;
+        let enricher = Enricher::from_config(&config, &network).unwrap();
+
+        let tag = |name: &str, count: u64, is_genre: bool| WeightedTag {
+            name: name.to_string(),
+            count,
+            is_genre,
+        };
+        let enrichment = enricher.select(vec![
+            tag("Jazz", 4, true),
+            tag("jazz", 4, true),
+            tag("hard bop", 5, true),
+            tag("seen live", 10, false),
+            tag("Relaxing", 3, false),
+            tag("bebop", 2, true),
+            tag("swing", 1, true),
+        ]);
+        assert_eq!(
+            enrichment,
+            Enrichment {
+                genres: vec!["jazz".to_string(), "hard bop".to_string()],
+                mood: Some("relaxing".to_string()),
+            }
+        );
+
+        assert!(enricher.select(Vec::new()).is_empty());
+    }
+}
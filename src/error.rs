@@ -33,8 +33,11 @@ pub enum ErrorType {
     #[error("File has unknown file type")]
     UnknownFileType,
     /// A MusicBrainz API request failed.
-    #[error("API request failed")]
-    Request(#[from] musicbrainz_rs_nova::Error),
+    #[error("API request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The HTTP client could not be created with the network configuration.
+    #[error("Network configuration error: {0}")]
+    Network(#[from] crate::network::NetworkError),
     /// A MusicBrainz API request failed.
     #[error("MusicBrainz lookup failed")]
     MusicBrainzLookupFailed(&'static str),
@@ -52,6 +55,38 @@ pub enum ErrorType {
     /// An error occurred while analyzing the audio track.
     #[error("Audio analysis failed: {0}")]
     AnalyzerError(#[from] crate::analyzer::AnalyzerError),
+    /// An error occurred while reading or splitting a cue sheet.
+    #[error("Cue sheet error: {0}")]
+    Cue(#[from] crate::cue::CueError),
+    /// An error occurred while watching the filesystem.
+    #[error("Filesystem watcher failed: {0}")]
+    Notify(#[from] notify::Error),
+    /// An error occurred while serializing or deserializing JSON.
+    #[error("JSON serialization failed: {0}")]
+    Json(#[from] serde_json::Error),
+    /// No album was found in the scanned directory.
+    #[error("No album found")]
+    NoAlbumFound,
+    /// A transferred file differs from the source file.
+    #[error("Checksum mismatch after transferring {0}")]
+    ChecksumMismatch(std::path::PathBuf),
+    /// Some files of the album could not be read.
+    #[error("{0} files could not be read")]
+    UnreadableFiles(usize),
+    /// The selected candidate does not exist.
+    #[error("Invalid candidate index {0}")]
+    InvalidCandidateIndex(usize),
+    /// A candidate was already applied to the tracks.
+    #[error("Candidate was already applied")]
+    CandidateAlreadyApplied,
+    /// None of the tags of a file can store the value of the tag key.
+    #[error("Cannot write {key} to {}", path.display())]
+    TagNotWritable {
+        /// Path of the file.
+        path: std::path::PathBuf,
+        /// The tag key.
+        key: crate::tag::TagKey,
+    },
     /// An error occurred while formatting a template string.
     #[error("Template formatting failed: {0}")]
     TemplateFormattingFailed(#[from] handlebars::RenderError),
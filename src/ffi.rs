@@ -0,0 +1,419 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Minimal C ABI for driving the matching engine from other languages.
+//!
+//! The shared library is built with `cargo build --release --features ffi`. The exported
+//! functions are:
+//!
+//! ```c
+//! typedef struct HeliconMatch HeliconMatch;
+//!
+//! HeliconMatch *helicon_match_directory(const char *path, const char *config_path);
+//! char *helicon_match_candidates_json(const HeliconMatch *handle);
+//! int helicon_match_apply(HeliconMatch *handle, size_t index);
+//! void helicon_match_free(HeliconMatch *handle);
+//! char *helicon_last_error_message(void);
+//! void helicon_string_free(char *string);
+//! ```
+//!
+//! Functions that return a pointer return `NULL` on failure, `helicon_match_apply` returns a
+//! negative value. The same applies if the library panics, because panics must not unwind into
+//! the caller. In both cases, the error message can be retrieved with
+//! `helicon_last_error_message`. All strings returned by the library must be released with
+//! `helicon_string_free`.
+
+#![allow(unsafe_code)]
+
+use crate::musicbrainz::MusicBrainzRelease;
+use crate::release::ReleaseLike;
+use crate::release_candidate::ReleaseCandidateCollection;
+use crate::scanner::Scanner;
+use crate::{Cache, Config, TaggedFileCollection, PKG_NAME};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::Duration;
+use xdg::BaseDirectories;
+
+thread_local! {
+    /// The message of the last error that occurred on this thread.
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Remember the error message so that it can be retrieved by the caller.
+fn set_last_error(message: impl Into<String>) {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message.into()));
+}
+
+/// Run `func` and return `on_panic` if it panics, so that the panic does not unwind across the C
+/// boundary.
+fn catch_panic<T>(on_panic: T, func: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(func)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        set_last_error(format!("helicon panicked: {message}"));
+        on_panic
+    })
+}
+
+/// Convert a string into a C string that is owned by the caller.
+fn into_c_string(value: String) -> *mut c_char {
+    match CString::new(value) {
+        Ok(value) => value.into_raw(),
+        Err(err) => {
+            set_last_error(err.to_string());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Result of matching a directory, handed out to the caller as an opaque pointer.
+#[derive(Debug)]
+pub struct HeliconMatch {
+    /// The configuration used for matching.
+    config: Config,
+    /// The matched directory.
+    path: PathBuf,
+    /// The tracks in the directory (`None` after a candidate has been applied).
+    track_collection: Option<TaggedFileCollection>,
+    /// The release candidates, ordered by distance.
+    candidates: ReleaseCandidateCollection<MusicBrainzRelease>,
+}
+
+/// Summary of a release candidate, serialized to JSON for the caller.
+#[derive(Debug, Serialize)]
+struct CandidateInfo<'a> {
+    /// Index to pass to `helicon_match_apply`.
+    index: usize,
+    /// MusicBrainz ID of the release.
+    release_id: Option<Cow<'a, str>>,
+    /// Artist of the release.
+    artist: Option<Cow<'a, str>>,
+    /// Title of the release.
+    title: Option<Cow<'a, str>>,
+    /// Distance between the candidate and the files.
+    distance: f64,
+}
+
+/// Load the configuration and scan the directory for the first album.
+fn match_directory(path: PathBuf, config_path: Option<PathBuf>) -> crate::Result<HeliconMatch> {
+    let config = config_path
+        .into_iter()
+        .fold(Config::builder().with_defaults(), |builder, path| {
+            builder.with_file(path)
+        })
+        .build()?;
+    let cache = BaseDirectories::with_prefix(PKG_NAME)
+        .ok()
+        .map(|base_dirs| {
+            Cache::new(base_dirs).with_max_age(Duration::from_secs(config.lookup.cache_max_age))
+        });
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let mut scanner = Scanner::scan(config.clone(), cache, path);
+    let (path, track_collection, candidates, _local_release) = runtime
+        .block_on(scanner.recv())
+        .ok_or(crate::Error::NoAlbumFound)?
+        .map_err(|err| err.source)?;
+
+    Ok(HeliconMatch {
+        config,
+        path,
+        track_collection: Some(track_collection),
+        candidates,
+    })
+}
+
+/// Assign the tags of the candidate at `index`, then transfer the files into the library.
+fn apply_candidate(handle: &mut HeliconMatch, index: usize) -> crate::Result<()> {
+    let candidate = handle
+        .candidates
+        .iter()
+        .nth(index)
+        .ok_or(crate::Error::InvalidCandidateIndex(index))?;
+    let track_collection = handle
+        .track_collection
+        .take()
+        .ok_or(crate::Error::CandidateAlreadyApplied)?;
+
+    let mut track_collection = track_collection.assign_tags(candidate, &handle.config);
+    track_collection.prepare_cue_tracks(&handle.config)?;
+    track_collection.move_files(&handle.config)?;
+    let _ = track_collection.move_attachments(&handle.path, &handle.config)?;
+    track_collection.write_tags(&handle.config)?;
+    if handle.config.paths.write_release_json {
+        let _ = track_collection.write_release_json()?;
+    }
+    Ok(())
+}
+
+/// Scan the directory at `path`, look up release candidates for the first album that is found
+/// and return a handle to the result.
+///
+/// `config_path` may be `NULL` to use the default configuration. Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string and `config_path` must either be `NULL` or a
+/// valid NUL-terminated string. The returned handle must be released with
+/// `helicon_match_free`.
+#[no_mangle]
+pub unsafe extern "C" fn helicon_match_directory(
+    path: *const c_char,
+    config_path: *const c_char,
+) -> *mut HeliconMatch {
+    if path.is_null() {
+        set_last_error("path must not be NULL");
+        return std::ptr::null_mut();
+    }
+
+    // SAFETY: The caller guarantees that the pointers are valid NUL-terminated strings.
+    let path = PathBuf::from(unsafe { CStr::from_ptr(path) }.to_string_lossy().as_ref());
+    let config_path = (!config_path.is_null()).then(|| {
+        PathBuf::from(
+            unsafe { CStr::from_ptr(config_path) }
+                .to_string_lossy()
+                .as_ref(),
+        )
+    });
+
+    catch_panic(std::ptr::null_mut(), || {
+        match match_directory(path, config_path) {
+            Ok(handle) => Box::into_raw(Box::new(handle)),
+            Err(err) => {
+                set_last_error(err.to_string());
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Return the release candidates of the handle as a JSON array, ordered by distance.
+///
+/// Returns `NULL` on failure. The string must be released with `helicon_string_free`.
+///
+/// # Safety
+///
+/// `handle` must be a pointer returned by `helicon_match_directory` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn helicon_match_candidates_json(handle: *const HeliconMatch) -> *mut c_char {
+    // SAFETY: The caller guarantees that the handle is valid.
+    let Some(handle) = (unsafe { handle.as_ref() }) else {
+        set_last_error("handle must not be NULL");
+        return std::ptr::null_mut();
+    };
+
+    catch_panic(std::ptr::null_mut(), || {
+        let candidates = handle
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(index, candidate)| CandidateInfo {
+                index,
+                release_id: candidate.release().musicbrainz_release_id(),
+                artist: candidate.release().release_artist(),
+                title: candidate.release().release_title(),
+                distance: candidate.distance(&handle.config).as_f64(),
+            })
+            .collect::<Vec<_>>();
+
+        match serde_json::to_string(&candidates) {
+            Ok(json) => into_c_string(json),
+            Err(err) => {
+                set_last_error(err.to_string());
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Apply the candidate at `index` (as listed by `helicon_match_candidates_json`), i.e., write
+/// the tags and move the files into the library.
+///
+/// Returns 0 on success and a negative value on failure. A candidate can only be applied once
+/// per handle.
+///
+/// # Safety
+///
+/// `handle` must be a pointer returned by `helicon_match_directory` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn helicon_match_apply(handle: *mut HeliconMatch, index: usize) -> c_int {
+    // SAFETY: The caller guarantees that the handle is valid.
+    let Some(handle) = (unsafe { handle.as_mut() }) else {
+        set_last_error("handle must not be NULL");
+        return -1;
+    };
+
+    catch_panic(-1, || match apply_candidate(handle, index) {
+        Ok(()) => 0,
+        Err(err) => {
+            set_last_error(err.to_string());
+            -1
+        }
+    })
+}
+
+/// Release a handle returned by `helicon_match_directory`.
+///
+/// # Safety
+///
+/// `handle` must be `NULL` or a pointer returned by `helicon_match_directory` that was not freed
+/// yet.
+#[no_mangle]
+pub unsafe extern "C" fn helicon_match_free(handle: *mut HeliconMatch) {
+    if !handle.is_null() {
+        // SAFETY: The caller guarantees that the handle was created by `Box::into_raw`.
+        let handle = unsafe { Box::from_raw(handle) };
+        catch_panic((), || drop(handle));
+    }
+}
+
+/// Return the message of the last error that occurred on the calling thread, or `NULL` if no
+/// error occurred.
+///
+/// The string must be released with `helicon_string_free`.
+#[no_mangle]
+pub extern "C" fn helicon_last_error_message() -> *mut c_char {
+    catch_panic(std::ptr::null_mut(), || {
+        LAST_ERROR
+            .with(|last_error| last_error.borrow_mut().take())
+            .map_or(std::ptr::null_mut(), into_c_string)
+    })
+}
+
+/// Release a string returned by the library.
+///
+/// # Safety
+///
+/// `string` must be `NULL` or a pointer returned by this library that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn helicon_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: The caller guarantees that the string was created by `CString::into_raw`.
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::ReleaseSimilarity;
+    use crate::release_candidate::ReleaseCandidate;
+    #[cfg(feature = "flac")]
+    use crate::TaggedFile;
+    #[cfg(feature = "flac")]
+    use std::fs;
+
+    /// Take the last error message of this thread.
+    fn last_error_message() -> Option<String> {
+        let message = helicon_last_error_message();
+        (!message.is_null()).then(|| {
+            let value = unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned();
+            unsafe { helicon_string_free(message) };
+            value
+        })
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(-1, || panic!("oops")), -1);
+        assert_eq!(
+            last_error_message().as_deref(),
+            Some("helicon panicked: oops")
+        );
+        assert_eq!(catch_panic(-1, || 0), 0);
+        assert!(last_error_message().is_none());
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_match_apply_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let library_dir = tempfile::tempdir().unwrap();
+        let release: MusicBrainzRelease = serde_json::from_str(include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/musicbrainz/release.json"
+        )))
+        .unwrap();
+        let track_count = release.release_track_count().unwrap();
+        let mut config = Config::default();
+        config.paths.library_path = library_dir.path().to_string_lossy().into_owned();
+        let tracks = (1..=track_count)
+            .map(|i| {
+                let path = dir.path().join(format!("{i:02}.flac"));
+                let _ = fs::copy(
+                    concat!(
+                        env!("CARGO_MANIFEST_DIR"),
+                        "/tests/data/media/untagged/track.flac"
+                    ),
+                    &path,
+                )
+                .unwrap();
+                TaggedFile::read_from_path(path, &config.tags).unwrap()
+            })
+            .collect();
+        let candidate =
+            ReleaseCandidate::with_similarity(release, ReleaseSimilarity::new(track_count));
+        let handle = Box::into_raw(Box::new(HeliconMatch {
+            config,
+            path: dir.path().to_path_buf(),
+            track_collection: Some(TaggedFileCollection::new(tracks)),
+            candidates: ReleaseCandidateCollection::from(vec![candidate]),
+        }));
+
+        let candidates_json = unsafe { helicon_match_candidates_json(handle) };
+        assert!(!candidates_json.is_null());
+        let candidates: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(candidates_json) }.to_str().unwrap())
+                .unwrap();
+        unsafe { helicon_string_free(candidates_json) };
+        assert_eq!(
+            candidates[0]["release_id"],
+            "0008f765-032b-46cd-ab69-2220edab1837"
+        );
+
+        assert_eq!(unsafe { helicon_match_apply(handle, 1) }, -1);
+        assert!(last_error_message().is_some());
+        assert_eq!(unsafe { helicon_match_apply(handle, 0) }, 0);
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+        let imported_files = crate::util::walk_dir(library_dir.path().to_path_buf())
+            .flat_map(|result| result.unwrap().2)
+            .collect::<Vec<_>>();
+        assert_eq!(imported_files.len(), track_count);
+        assert_eq!(unsafe { helicon_match_apply(handle, 0) }, -1);
+        assert!(last_error_message().is_some());
+        unsafe { helicon_match_free(handle) };
+    }
+
+    #[test]
+    fn test_null_arguments() {
+        assert!(unsafe { helicon_match_directory(std::ptr::null(), std::ptr::null()) }.is_null());
+        let message = helicon_last_error_message();
+        assert!(!message.is_null());
+        assert_eq!(
+            unsafe { CStr::from_ptr(message) }.to_str().unwrap(),
+            "path must not be NULL"
+        );
+        unsafe { helicon_string_free(message) };
+        assert!(helicon_last_error_message().is_null());
+
+        assert_eq!(unsafe { helicon_match_apply(std::ptr::null_mut(), 0) }, -1);
+        assert!(unsafe { helicon_match_candidates_json(std::ptr::null()) }.is_null());
+        unsafe { helicon_match_free(std::ptr::null_mut()) };
+    }
+}
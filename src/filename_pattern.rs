@@ -0,0 +1,225 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Patterns for deriving tag values from file paths (e.g., `%artist%/%album%/%track% %title%`).
+//!
+//! These values are used as a fallback for files that do not have tags, so that there is something
+//! to match against.
+
+use crate::tag::TagKey;
+use regex::Regex;
+use std::path::{Component, Path};
+use thiserror::Error;
+
+/// Encountered when a filename pattern is invalid.
+#[derive(Error, Debug)]
+pub enum FilenamePatternError {
+    /// The pattern contains an unterminated `%` variable.
+    #[error("Unterminated variable in filename pattern: {0}")]
+    UnterminatedVariable(String),
+    /// The pattern contains an unknown variable.
+    #[error("Unknown variable in filename pattern: {0}")]
+    UnknownVariable(String),
+    /// The generated regular expression is invalid.
+    #[error("Invalid filename pattern: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
+
+/// A compiled filename pattern.
+#[derive(Debug, Clone)]
+pub struct FilenamePattern {
+    /// The regular expression that matches the last path components.
+    regex: Regex,
+    /// The tag keys of the capture groups, in order.
+    keys: Vec<TagKey>,
+    /// Number of path components that the pattern matches (including the file name).
+    component_count: usize,
+}
+
+/// Map the name of a pattern variable to a tag key.
+///
+/// Besides the tag key names (e.g., `album_artist`), a few short aliases are supported.
+fn variable_to_tag_key(name: &str) -> Result<TagKey, FilenamePatternError> {
+    match name.to_ascii_lowercase().as_str() {
+        "track" | "tracknumber" => Ok(TagKey::TrackNumber),
+        "title" => Ok(TagKey::TrackTitle),
+        "disc" | "discnumber" => Ok(TagKey::DiscNumber),
+        "year" => Ok(TagKey::ReleaseYear),
+        _ => name
+            .parse()
+            .map_err(|_| FilenamePatternError::UnknownVariable(name.to_string())),
+    }
+}
+
+/// Returns the regular expression that matches the value of the tag key.
+fn tag_key_regex(key: &TagKey) -> &'static str {
+    match key {
+        TagKey::TrackNumber | TagKey::DiscNumber => r"(\d+)",
+        TagKey::ReleaseYear => r"(\d{4})",
+        _ => r"(.+?)",
+    }
+}
+
+impl FilenamePattern {
+    /// Compile the filename pattern.
+    ///
+    /// Variables are enclosed in `%` characters (e.g., `%artist%`) and path components are
+    /// separated by `/`. The pattern is matched against the last components of the path, without
+    /// the file extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern contains an unknown or unterminated variable.
+    pub fn new(pattern: &str) -> Result<Self, FilenamePatternError> {
+        if pattern.matches('%').count() % 2 != 0 {
+            return Err(FilenamePatternError::UnterminatedVariable(
+                pattern.to_string(),
+            ));
+        }
+
+        let mut regex = String::from("^");
+        let mut keys = Vec::new();
+        for (i, part) in pattern.split('%').enumerate() {
+            if i % 2 == 0 {
+                regex.push_str(&regex::escape(part));
+            } else {
+                let key = variable_to_tag_key(part)?;
+                regex.push_str(tag_key_regex(&key));
+                keys.push(key);
+            }
+        }
+        regex.push('$');
+
+        Ok(Self {
+            regex: Regex::new(&regex)?,
+            keys,
+            component_count: pattern.split('/').count(),
+        })
+    }
+
+    /// Match the pattern against the path and return the tag values.
+    ///
+    /// Returns `None` if the path does not match.
+    pub fn match_path(&self, path: &Path) -> Option<Vec<(TagKey, String)>> {
+        let mut components = path
+            .with_extension("")
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .rev()
+            .take(self.component_count)
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        if components.len() < self.component_count {
+            return None;
+        }
+        components.reverse();
+        let relative_path = components.join("/");
+
+        let captures = self.regex.captures(&relative_path)?;
+        let values = self
+            .keys
+            .iter()
+            .zip(captures.iter().skip(1))
+            .filter_map(|(key, value)| {
+                value
+                    .map(|value| value.as_str().trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| (key.clone(), value.to_string()))
+            })
+            .collect();
+        Some(values)
+    }
+}
+
+/// Compile the filename patterns from the configuration, skipping invalid patterns.
+pub fn compile_patterns(patterns: &[String]) -> Vec<FilenamePattern> {
+    patterns
+        .iter()
+        .filter_map(|pattern| {
+            FilenamePattern::new(pattern)
+                .inspect_err(|err| log::warn!("Ignoring filename pattern {pattern:?}: {err}"))
+                .ok()
+        })
+        .collect()
+}
+
+/// Returns the tag values from the first pattern that matches the path.
+pub fn match_patterns(patterns: &[FilenamePattern], path: &Path) -> Vec<(TagKey, String)> {
+    patterns
+        .iter()
+        .find_map(|pattern| pattern.match_path(path))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_path() {
+        let pattern = FilenamePattern::new("%artist%/%album%/%track% - %title%").unwrap();
+        let values = pattern
+            .match_path(Path::new(
+                "/music/The Ahmad Jamal Trio/But Not for Me/01 - But Not for Me.flac",
+            ))
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                (TagKey::Artist, "The Ahmad Jamal Trio".to_string()),
+                (TagKey::Album, "But Not for Me".to_string()),
+                (TagKey::TrackNumber, "01".to_string()),
+                (TagKey::TrackTitle, "But Not for Me".to_string()),
+            ]
+        );
+
+        assert!(pattern
+            .match_path(Path::new("/music/But Not for Me/01 But Not for Me.flac"))
+            .is_none());
+        assert!(pattern
+            .match_path(Path::new("01 - But Not for Me.flac"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_match_patterns() {
+        let patterns = compile_patterns(&[
+            "%artist%/%album%/%track% - %title%".to_string(),
+            "%track% %title%".to_string(),
+            "%invalid%".to_string(),
+        ]);
+        assert_eq!(patterns.len(), 2);
+
+        assert_eq!(
+            match_patterns(
+                &patterns,
+                Path::new("/music/02 Surrey With the Fringe on Top.mp3")
+            ),
+            vec![
+                (TagKey::TrackNumber, "02".to_string()),
+                (
+                    TagKey::TrackTitle,
+                    "Surrey With the Fringe on Top".to_string()
+                ),
+            ]
+        );
+        assert!(match_patterns(&patterns, Path::new("/music/cover.jpg")).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(matches!(
+            FilenamePattern::new("%artist/%title%"),
+            Err(FilenamePatternError::UnterminatedVariable(_))
+        ));
+        assert!(matches!(
+            FilenamePattern::new("%foo%"),
+            Err(FilenamePatternError::UnknownVariable(_))
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! External commands that are run on import events.
+//!
+//! Hooks can be used to trigger other tools after an album has been imported (e.g., `mpc update`
+//! or a library refresh of a media server). Each command is run with a shell, and the album is
+//! described by `HELICON_*` environment variables.
+
+use crate::config::HooksConfig;
+use crate::distance::Distance;
+use crate::release::ReleaseLike;
+use crate::TaggedFileCollection;
+use std::borrow::Cow;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Event on which hooks are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A release candidate has been selected for an album, before it is imported.
+    AlbumMatched,
+    /// The files of the album have been moved (or copied) into the library.
+    PostMove,
+    /// The tags of the album have been written.
+    PostWrite,
+}
+
+impl HookEvent {
+    /// Name of the event (as used in the configuration).
+    fn name(self) -> &'static str {
+        match self {
+            Self::AlbumMatched => "on_album_matched",
+            Self::PostMove => "post_move",
+            Self::PostWrite => "post_write",
+        }
+    }
+
+    /// Commands that are configured for this event.
+    fn commands(self, config: &HooksConfig) -> &[String] {
+        match self {
+            Self::AlbumMatched => &config.on_album_matched,
+            Self::PostMove => &config.post_move,
+            Self::PostWrite => &config.post_write,
+        }
+    }
+}
+
+/// Build the environment variables that describe the album.
+fn hook_environment(
+    event: HookEvent,
+    source_path: &Path,
+    track_collection: &TaggedFileCollection,
+    distance: &Distance,
+) -> Vec<(&'static str, String)> {
+    let track_paths = track_collection
+        .tracks()
+        .map(|track| track.path.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let optional_values = [
+        (
+            "HELICON_ALBUM_PATH",
+            track_collection
+                .album_directory()
+                .map(|directory| directory.to_string_lossy().into_owned()),
+        ),
+        (
+            "HELICON_RELEASE_ID",
+            track_collection
+                .musicbrainz_release_id()
+                .map(Cow::into_owned),
+        ),
+        (
+            "HELICON_RELEASE_GROUP_ID",
+            track_collection
+                .musicbrainz_release_group_id()
+                .map(Cow::into_owned),
+        ),
+        (
+            "HELICON_ARTIST",
+            track_collection.release_artist().map(Cow::into_owned),
+        ),
+        (
+            "HELICON_ALBUM",
+            track_collection.release_title().map(Cow::into_owned),
+        ),
+    ];
+
+    [
+        ("HELICON_EVENT", event.name().to_string()),
+        (
+            "HELICON_SOURCE_PATH",
+            source_path.to_string_lossy().into_owned(),
+        ),
+        ("HELICON_TRACK_PATHS", track_paths),
+        ("HELICON_DISTANCE", format!("{:.4}", distance.as_f64())),
+        (
+            "HELICON_SIMILARITY",
+            format!("{:.1}", (1.0 - distance.as_f64()) * 100.0),
+        ),
+    ]
+    .into_iter()
+    .chain(
+        optional_values
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (name, value))),
+    )
+    .collect()
+}
+
+/// Build the shell command for running a hook.
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        let _ = shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        let _ = shell.arg("-c");
+        shell
+    };
+    let _ = shell.arg(command);
+    shell
+}
+
+/// Run the hooks that are configured for the event.
+///
+/// The commands are run one after another. Failing commands are logged, but do not abort the
+/// import. This blocks until all commands have finished, so it must not be called on an async
+/// runtime worker (use [`tokio::task::spawn_blocking`] instead).
+pub fn run_hooks(
+    config: &HooksConfig,
+    event: HookEvent,
+    source_path: &Path,
+    track_collection: &TaggedFileCollection,
+    distance: &Distance,
+) {
+    let commands = event.commands(config);
+    if commands.is_empty() {
+        return;
+    }
+
+    let environment = hook_environment(event, source_path, track_collection, distance);
+    for command in commands {
+        log::info!("Running {} hook {command:?}", event.name());
+        let status = shell_command(command)
+            .envs(environment.iter().map(|(name, value)| (name, value)))
+            .stdin(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => (),
+            Ok(status) => log::warn!("Hook {command:?} exited with {status}"),
+            Err(err) => log::warn!("Failed to run hook {command:?}: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::memory::MemoryTag;
+    use crate::tag::TagKey;
+    use crate::TaggedFile;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_hook_environment() {
+        let track = |path: &str| {
+            let tag = MemoryTag::new()
+                .with_value(TagKey::Album, Some("Album"))
+                .with_value(
+                    TagKey::MusicBrainzReleaseId,
+                    Some("0d3fc2b5-8b3e-4d4b-b0f5-7e5b0d8a7c7d"),
+                );
+            TaggedFile::with_tags(PathBuf::from(path), vec![Box::new(tag)])
+        };
+        let track_collection = TaggedFileCollection::new(vec![
+            track("/music/Artist/Album/01.flac"),
+            track("/music/Artist/Album/02.flac"),
+        ]);
+
+        let environment = hook_environment(
+            HookEvent::PostWrite,
+            Path::new("/import/Album"),
+            &track_collection,
+            &Distance::from(0.25),
+        );
+        let value = |name: &str| {
+            environment
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value("HELICON_EVENT"), Some("post_write"));
+        assert_eq!(value("HELICON_SOURCE_PATH"), Some("/import/Album"));
+        assert_eq!(value("HELICON_ALBUM_PATH"), Some("/music/Artist/Album"));
+        assert_eq!(
+            value("HELICON_TRACK_PATHS"),
+            Some("/music/Artist/Album/01.flac\n/music/Artist/Album/02.flac")
+        );
+        assert_eq!(
+            value("HELICON_RELEASE_ID"),
+            Some("0d3fc2b5-8b3e-4d4b-b0f5-7e5b0d8a7c7d")
+        );
+        assert_eq!(value("HELICON_ALBUM"), Some("Album"));
+        assert_eq!(value("HELICON_ARTIST"), None);
+        assert_eq!(value("HELICON_SIMILARITY"), Some("75.0"));
+    }
+}
@@ -0,0 +1,1080 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Programmatic import API.
+//!
+//! An [`ImportSession`] scans a directory, looks up the release candidates of the albums in it and
+//! imports them into the library. The progress is reported as a stream of [`ImportEvent`]s. In
+//! interactive mode, each album is passed to the consumer of the stream as a [`DecisionRequest`],
+//! and the album is only imported once the consumer has made a decision. The `import` CLI command
+//! is one such consumer.
+
+use crate::acoustid::{AcoustIdClient, Submission};
+use crate::config::{AlbumCollisionPolicy, TransferMode};
+use crate::decisions::DecisionStore;
+use crate::distance::Distance;
+use crate::enrichment::Enricher;
+use crate::hooks::{self, HookEvent};
+use crate::journal::{ImportJournal, JournalEntry, JournalTrack};
+use crate::musicbrainz::MusicBrainzRelease;
+use crate::musicbrainz_collection::MusicBrainzCollectionClient;
+use crate::progress::{self, ImportProgress, ProgressStatus};
+use crate::quarantine::{self, SkipReason};
+use crate::release::ReleaseLike;
+use crate::release_candidate::{ReleaseCandidate, ReleaseCandidateCollection};
+use crate::scanner::Scanner;
+use crate::stats::ImportStats;
+use crate::taggedfilecollection::AlbumDestinations;
+use crate::util;
+use crate::{Cache, Config, TaggedFileCollection};
+use futures::Stream;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Number of albums that may be waiting to be imported while the next albums are selected.
+const IMPORT_QUEUE_SIZE: usize = 20;
+
+/// How release candidates are selected during an import.
+#[derive(Debug, Clone)]
+pub enum SelectionMode {
+    /// Let the consumer of the session decide about each album (see
+    /// [`ImportEvent::NeedsDecision`]).
+    Interactive,
+    /// Automatically apply the best candidate if its distance does not exceed the maximum
+    /// distance, skip the release otherwise.
+    Automatic {
+        /// The maximum distance of candidates that will be applied.
+        max_distance: Distance,
+    },
+}
+
+/// An album that was scanned and for which release candidates were looked up.
+#[derive(Debug)]
+pub struct ScannedAlbum {
+    /// The scanned directory (or the directory that an archive was extracted to).
+    pub path: PathBuf,
+    /// The tracks of the album.
+    pub track_collection: TaggedFileCollection,
+    /// The MusicBrainz release candidates, ordered by their ranking distance.
+    pub candidates: ReleaseCandidateCollection<MusicBrainzRelease>,
+    /// The release from local metadata files (if any).
+    pub local_release: Option<TaggedFileCollection>,
+}
+
+/// Decision about an album of an [`ImportSession`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ImportDecision {
+    /// Import the album with the metadata that has been assigned to the track collection.
+    Import(TaggedFileCollection, Distance),
+    /// Do not import the album (and move it to the quarantine, if configured).
+    Skip(SkipReason),
+    /// Stop the import run. Albums that have already been selected are still imported.
+    Quit,
+}
+
+/// Sends the decision about an album back to the [`ImportSession`].
+#[derive(Debug)]
+pub struct DecisionResponder(oneshot::Sender<ImportDecision>);
+
+impl DecisionResponder {
+    /// Send the decision to the import session.
+    pub fn respond(self, decision: ImportDecision) {
+        if self.0.send(decision).is_err() {
+            log::warn!("Import session has already been stopped");
+        }
+    }
+}
+
+/// An album that needs a decision by the consumer of an [`ImportSession`].
+///
+/// Dropping the request without making a decision stops the import run, like
+/// [`ImportDecision::Quit`].
+#[derive(Debug)]
+pub struct DecisionRequest {
+    /// The album.
+    album: ScannedAlbum,
+    /// The configuration of the import session.
+    config: Arc<Config>,
+    /// Sender for the decision.
+    responder: DecisionResponder,
+}
+
+impl DecisionRequest {
+    /// The album that needs a decision.
+    #[must_use]
+    pub fn album(&self) -> &ScannedAlbum {
+        &self.album
+    }
+
+    /// Apply the release candidate with the given index and import the album.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no candidate with the given index.
+    pub fn apply_candidate(self, index: usize) {
+        let candidate = self.album.candidates.select_index(index);
+        let distance = candidate.distance(&self.config);
+        let track_collection = self
+            .album
+            .track_collection
+            .assign_tags(&candidate, &self.config);
+        self.responder
+            .respond(ImportDecision::Import(track_collection, distance));
+    }
+
+    /// Skip the album.
+    pub fn skip(self) {
+        self.responder
+            .respond(ImportDecision::Skip(SkipReason::SkippedByUser));
+    }
+
+    /// Stop the import run.
+    pub fn quit(self) {
+        self.responder.respond(ImportDecision::Quit);
+    }
+
+    /// Take the album out of the request, e.g., to modify the track collection or to look up
+    /// further candidates before making a decision with the responder.
+    #[must_use]
+    pub fn into_parts(self) -> (ScannedAlbum, DecisionResponder) {
+        (self.album, self.responder)
+    }
+}
+
+/// How a collision of album directories is resolved by the consumer of an [`ImportSession`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollisionResolution {
+    /// Append the suffix to the name of the album directory.
+    AppendSuffix(String),
+    /// Append the first suffix that is not used by a different release to the name of the album
+    /// directory, or skip the release if there is no such suffix.
+    Disambiguate,
+    /// Import the release into the same directory as the other release.
+    SameDirectory,
+    /// Do not import the release.
+    Skip,
+}
+
+/// An album whose album directory is already used by a different release that was imported
+/// during the same run (only with the `ask` album collision policy in interactive mode).
+///
+/// Dropping the request without resolving the collision skips the album.
+#[derive(Debug)]
+pub struct CollisionRequest {
+    /// The album directory that is already used.
+    directory: PathBuf,
+    /// The suffixes that can be appended to the album directory.
+    suffixes: Vec<String>,
+    /// Sender for the resolution.
+    responder: oneshot::Sender<CollisionResolution>,
+}
+
+impl CollisionRequest {
+    /// The album directory that is already used by a different release.
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The suffixes that can be appended to the album directory to disambiguate it (e.g., the
+    /// release year).
+    #[must_use]
+    pub fn suffixes(&self) -> &[String] {
+        &self.suffixes
+    }
+
+    /// Send the resolution to the import session.
+    pub fn respond(self, resolution: CollisionResolution) {
+        if self.responder.send(resolution).is_err() {
+            log::warn!("Import session has already been stopped");
+        }
+    }
+}
+
+/// Event that is emitted by an [`ImportSession`].
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ImportEvent {
+    /// A previous, interrupted import run of the same path is resumed. The albums that were
+    /// already imported or skipped are not scanned again.
+    Resumed {
+        /// Number of albums that were already processed.
+        finished_count: usize,
+    },
+    /// An album was scanned.
+    AlbumDiscovered {
+        /// The scanned directory.
+        path: PathBuf,
+        /// Number of tracks of the album.
+        track_count: usize,
+    },
+    /// The release candidates of an album were looked up.
+    CandidatesReady {
+        /// The scanned directory.
+        path: PathBuf,
+        /// Number of release candidates.
+        candidate_count: usize,
+        /// Distance of the best candidate (if any).
+        best_distance: Option<Distance>,
+    },
+    /// The consumer needs to decide about an album (only in interactive mode).
+    NeedsDecision(DecisionRequest),
+    /// The consumer needs to resolve a collision of album directories (only in interactive mode).
+    AlbumCollision(CollisionRequest),
+    /// The selected release was applied to an album and the files were transferred into the
+    /// library.
+    Applied {
+        /// The scanned directory.
+        path: PathBuf,
+        /// Distance of the applied release.
+        distance: Distance,
+        /// Whether the album was imported successfully.
+        imported: bool,
+    },
+    /// An album was skipped.
+    Skipped {
+        /// The scanned directory.
+        path: PathBuf,
+        /// The reason why the album was skipped.
+        reason: SkipReason,
+    },
+    /// Scanning an album failed.
+    Failed {
+        /// The scanned directory.
+        path: PathBuf,
+        /// The error that occurred.
+        error: crate::Error,
+    },
+}
+
+/// Select the candidate with the lowest distance without user interaction.
+///
+/// The release is skipped if the best candidate's distance exceeds `max_distance`.
+fn auto_select_release(
+    config: &Config,
+    track_collection: TaggedFileCollection,
+    candidates: ReleaseCandidateCollection<MusicBrainzRelease>,
+    local_release: Option<TaggedFileCollection>,
+    max_distance: &Distance,
+) -> ImportDecision {
+    let local_candidate = local_release.map(|local_release| {
+        ReleaseCandidate::with_base_release(local_release, &track_collection, config)
+    });
+    let local_distance = local_candidate
+        .as_ref()
+        .map(|candidate| candidate.distance(config));
+    let musicbrainz_distance = candidates
+        .iter()
+        .next()
+        .map(|candidate| candidate.distance(config));
+
+    match (local_candidate, local_distance, musicbrainz_distance) {
+        (Some(candidate), Some(distance), musicbrainz_distance)
+            if &distance <= max_distance
+                && musicbrainz_distance
+                    .as_ref()
+                    .is_none_or(|other| &distance <= other) =>
+        {
+            log::info!("Automatically applying local metadata (distance: {distance})");
+            ImportDecision::Import(track_collection.assign_tags(&candidate, config), distance)
+        }
+        (_, _, Some(distance)) if &distance <= max_distance => {
+            let candidate = candidates.select_index(0);
+            log::info!(
+                "Automatically applying release {} (distance: {distance})",
+                candidate.release().id
+            );
+            ImportDecision::Import(track_collection.assign_tags(&candidate, config), distance)
+        }
+        (_, local_distance, musicbrainz_distance) => {
+            let best_distance = local_distance.into_iter().chain(musicbrainz_distance).min();
+            let reason = SkipReason::NoMatchingCandidate { best_distance };
+            log::warn!("Skipping release: {reason}");
+            ImportDecision::Skip(reason)
+        }
+    }
+}
+
+/// An import run that can be controlled programmatically.
+///
+/// The session runs in the background and reports its progress as a stream of [`ImportEvent`]s.
+/// The stream ends when all albums have been processed.
+#[derive(Debug)]
+pub struct ImportSession {
+    /// Receiver for the events of the import run.
+    events: mpsc::UnboundedReceiver<ImportEvent>,
+    /// Handle of the task that runs the import.
+    handle: JoinHandle<crate::Result<ImportStats>>,
+}
+
+impl ImportSession {
+    /// Start importing all albums in the given path.
+    ///
+    /// If `resume` is `true`, the progress of the import run is recorded, and albums that were
+    /// already imported or skipped in a previous, interrupted run of the same path are skipped.
+    #[must_use]
+    pub fn start(
+        config: Config,
+        cache: Option<Cache>,
+        import_root: PathBuf,
+        mode: SelectionMode,
+        resume: bool,
+    ) -> Self {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        let handle = tokio::task::spawn(run_session(
+            Arc::new(config),
+            cache,
+            import_root,
+            mode,
+            resume,
+            events_tx,
+        ));
+        Self { events, handle }
+    }
+
+    /// Wait until the import run has finished and return its statistics.
+    ///
+    /// Events that have not been received yet are discarded, and pending decision requests stop
+    /// the import run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if resolving an album collision failed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the import task panicked.
+    pub async fn finish(self) -> crate::Result<ImportStats> {
+        let Self { events, handle } = self;
+        drop(events);
+        handle.await.unwrap()
+    }
+}
+
+impl Stream for ImportSession {
+    type Item = ImportEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().events.poll_recv(cx)
+    }
+}
+
+/// Check if the album directory of the track collection is already used by a different release
+/// that was imported during this run and resolve the collision according to the configured
+/// policy.
+///
+/// In interactive mode, collisions with the `ask` policy are resolved by the consumer of the
+/// session (see [`ImportEvent::AlbumCollision`]). Otherwise, the album directory is
+/// disambiguated instead.
+///
+/// Returns `false` if the release should not be imported.
+async fn resolve_album_collision(
+    config: &Config,
+    mode: &SelectionMode,
+    destinations: &mut AlbumDestinations,
+    track_collection: &mut TaggedFileCollection,
+    events: &mpsc::UnboundedSender<ImportEvent>,
+) -> crate::Result<bool> {
+    let release_key = track_collection.release_key();
+    let Some(directory) = track_collection.album_directory(config)? else {
+        return Ok(true);
+    };
+
+    if destinations.is_taken(&directory, &release_key) {
+        let resolution = match (config.paths.album_collision_policy, mode) {
+            (AlbumCollisionPolicy::Ignore, _) => CollisionResolution::SameDirectory,
+            (AlbumCollisionPolicy::Skip, _) => CollisionResolution::Skip,
+            (AlbumCollisionPolicy::Disambiguate, _)
+            | (AlbumCollisionPolicy::Ask, SelectionMode::Automatic { .. }) => {
+                CollisionResolution::Disambiguate
+            }
+            (AlbumCollisionPolicy::Ask, SelectionMode::Interactive) => {
+                let (responder, response) = oneshot::channel();
+                let request = CollisionRequest {
+                    directory: directory.clone(),
+                    suffixes: track_collection.album_directory_suffixes(),
+                    responder,
+                };
+                if events.send(ImportEvent::AlbumCollision(request)).is_ok() {
+                    response.await.unwrap_or(CollisionResolution::Skip)
+                } else {
+                    CollisionResolution::Skip
+                }
+            }
+        };
+        match resolution {
+            CollisionResolution::AppendSuffix(suffix) => {
+                track_collection.set_album_directory_suffix(Some(suffix));
+            }
+            CollisionResolution::Disambiguate => {
+                if !track_collection.disambiguate_album_directory(config, destinations)? {
+                    log::warn!(
+                        "Failed to find a unique album directory for {}",
+                        directory.display()
+                    );
+                    return Ok(false);
+                }
+            }
+            CollisionResolution::SameDirectory => {
+                log::warn!(
+                    "{} is already used by a different release",
+                    directory.display()
+                );
+            }
+            CollisionResolution::Skip => return Ok(false),
+        }
+    }
+
+    if let Some(directory) = track_collection.album_directory(config)? {
+        destinations.insert(directory, release_key);
+    }
+    Ok(true)
+}
+
+/// Import the albums that have been selected, one after another.
+async fn run_importer(
+    config: Arc<Config>,
+    import_root: PathBuf,
+    mut importer_rx: mpsc::Receiver<(PathBuf, TaggedFileCollection, Distance)>,
+    progress: Arc<Mutex<Option<ImportProgress>>>,
+    stats: Arc<Mutex<ImportStats>>,
+    events: mpsc::UnboundedSender<ImportEvent>,
+) {
+    let journal = Arc::new(
+        ImportJournal::open_default()
+            .inspect_err(|err| log::warn!("Import journal is not available: {err}"))
+            .ok(),
+    );
+    let acoustid = AcoustIdClient::from_config(&config.acoustid, &config.network);
+    let musicbrainz_collection =
+        MusicBrainzCollectionClient::from_config(&config.musicbrainz_collection, &config.network);
+    let enricher = Enricher::from_config(&config.enrichment, &config.network);
+    while let Some((path, track_collection, distance)) = importer_rx.recv().await {
+        // Hooks and file operations block, so they must not run on a runtime worker.
+        let (path, mut track_collection, distance) = tokio::task::spawn_blocking({
+            let config = Arc::clone(&config);
+            move || {
+                hooks::run_hooks(
+                    &config.hooks,
+                    HookEvent::AlbumMatched,
+                    &path,
+                    &track_collection,
+                    &distance,
+                );
+                (path, track_collection, distance)
+            }
+        })
+        .await
+        .unwrap();
+        if let Some(enricher) = &enricher {
+            enricher.enrich(&mut track_collection).await;
+        }
+        let progress_path = album_progress_path(&path, &track_collection);
+        let submissions = acoustid
+            .as_ref()
+            .map(|_| {
+                track_collection
+                    .tracks()
+                    .filter_map(Submission::from_track)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let release_id = track_collection
+            .musicbrainz_release_id()
+            .map(Cow::into_owned);
+        let imported = tokio::task::spawn_blocking({
+            let config = Arc::clone(&config);
+            let import_root = import_root.clone();
+            let path = path.clone();
+            let distance = distance.clone();
+            let journal = Arc::clone(&journal);
+            let stats = Arc::clone(&stats);
+            move || {
+                import_release(
+                    &config,
+                    &import_root,
+                    path,
+                    track_collection,
+                    distance,
+                    journal.as_ref().as_ref(),
+                    &stats,
+                )
+            }
+        })
+        .await
+        .unwrap();
+        let _ = events.send(ImportEvent::Applied {
+            path,
+            distance,
+            imported,
+        });
+        if imported {
+            record_progress(&progress, &progress_path, ProgressStatus::Done);
+        }
+        if let Some(acoustid) = acoustid.as_ref().filter(|_| imported) {
+            match acoustid.submit(&submissions).await {
+                Ok(()) => {
+                    log::info!("Submitted {} fingerprints to AcoustID", submissions.len())
+                }
+                Err(err) => log::warn!("Failed to submit fingerprints to AcoustID: {err}"),
+            }
+        }
+        if let (Some(collection), Some(release_id)) = (
+            musicbrainz_collection.as_ref().filter(|_| imported),
+            release_id.as_deref(),
+        ) {
+            match collection.add_release(release_id).await {
+                Ok(()) => {
+                    log::info!("Added release {release_id} to the MusicBrainz collection")
+                }
+                Err(err) => {
+                    log::warn!("Failed to add release to the MusicBrainz collection: {err}");
+                }
+            }
+        }
+    }
+}
+
+/// Scan the path, select a release for each album and pass the selected albums to the importer.
+async fn run_session(
+    config: Arc<Config>,
+    cache: Option<Cache>,
+    import_root: PathBuf,
+    mode: SelectionMode,
+    resume: bool,
+    events: mpsc::UnboundedSender<ImportEvent>,
+) -> crate::Result<ImportStats> {
+    let mut progress = resume
+        .then(|| {
+            ImportProgress::open_default(&import_root)
+                .inspect_err(|err| log::warn!("Import progress is not available: {err}"))
+                .ok()
+        })
+        .flatten();
+    if let Some(Err(err)) = progress.as_mut().map(ImportProgress::compact) {
+        log::warn!("Failed to compact import progress: {err}");
+    }
+    let excluded_paths = progress
+        .iter()
+        .flat_map(ImportProgress::finished_paths)
+        .collect::<HashSet<_>>();
+    if !excluded_paths.is_empty() {
+        let _ = events.send(ImportEvent::Resumed {
+            finished_count: excluded_paths.len(),
+        });
+    }
+    let progress = Arc::new(Mutex::new(progress));
+    let mut scanner = Scanner::scan_excluding(
+        Config::clone(&config),
+        cache,
+        import_root.clone(),
+        excluded_paths,
+    );
+    let stats = Arc::new(Mutex::new(ImportStats::default()));
+
+    let (importer_tx, importer_rx) = mpsc::channel(IMPORT_QUEUE_SIZE);
+    let importer_handle = tokio::task::spawn(run_importer(
+        Arc::clone(&config),
+        import_root,
+        importer_rx,
+        Arc::clone(&progress),
+        Arc::clone(&stats),
+        events.clone(),
+    ));
+
+    let decisions = config
+        .decisions
+        .enabled
+        .then(|| {
+            DecisionStore::open_default()
+                .inspect_err(|err| log::warn!("Decision store is not available: {err}"))
+                .ok()
+        })
+        .flatten();
+    let mut album_destinations = AlbumDestinations::default();
+    let mut finished = true;
+    while let Some(result) = scanner.recv().await {
+        let (path, track_collection, candidates, local_release) = match result {
+            Ok(res) => res,
+            Err(err) => {
+                log::error!("Scan of {} failed: {}", err.path.display(), err.source);
+                stats.lock().unwrap().record_failed(err.path.clone());
+                let _ = events.send(ImportEvent::Failed {
+                    path: err.path,
+                    error: err.source,
+                });
+                continue;
+            }
+        };
+        let _ = events.send(ImportEvent::AlbumDiscovered {
+            path: path.clone(),
+            track_count: track_collection.tracks().count(),
+        });
+        let progress_path = album_progress_path(&path, &track_collection);
+        let is_directory_group = track_collection.directory_group().is_some();
+        let matched_release_id = progress.lock().unwrap().as_ref().and_then(|progress| {
+            progress
+                .matched_release_id(&progress_path)
+                .map(ToOwned::to_owned)
+        });
+        record_progress(&progress, &progress_path, ProgressStatus::Pending);
+        let candidates = match decisions.as_ref() {
+            Some(decisions) => candidates.with_decisions(decisions, &config),
+            None => candidates,
+        };
+        let _ = events.send(ImportEvent::CandidatesReady {
+            path: path.clone(),
+            candidate_count: candidates.len(),
+            best_distance: candidates
+                .iter()
+                .next()
+                .map(|candidate| candidate.distance(&config)),
+        });
+
+        // Albums that were matched in an interrupted run are imported with the same release.
+        let matched_index = matched_release_id.and_then(|release_id| {
+            candidates
+                .iter()
+                .position(|candidate| candidate.release().id == release_id)
+        });
+        let decision = match (matched_index, &mode) {
+            (Some(index), _) => {
+                let candidate = candidates.select_index(index);
+                log::info!(
+                    "Resuming with release {}, which was selected in the previous run",
+                    candidate.release().id
+                );
+                let distance = candidate.distance(&config);
+                ImportDecision::Import(track_collection.assign_tags(&candidate, &config), distance)
+            }
+            (None, SelectionMode::Interactive) => {
+                let (responder, response) = oneshot::channel();
+                let request = DecisionRequest {
+                    album: ScannedAlbum {
+                        path: path.clone(),
+                        track_collection,
+                        candidates,
+                        local_release,
+                    },
+                    config: Arc::clone(&config),
+                    responder: DecisionResponder(responder),
+                };
+                if events.send(ImportEvent::NeedsDecision(request)).is_ok() {
+                    response.await.unwrap_or(ImportDecision::Quit)
+                } else {
+                    ImportDecision::Quit
+                }
+            }
+            (None, SelectionMode::Automatic { max_distance }) => auto_select_release(
+                &config,
+                track_collection,
+                candidates,
+                local_release,
+                max_distance,
+            ),
+        };
+        let reason = match decision {
+            ImportDecision::Import(mut track_collection, distance) => {
+                if resolve_album_collision(
+                    &config,
+                    &mode,
+                    &mut album_destinations,
+                    &mut track_collection,
+                    &events,
+                )
+                .await?
+                {
+                    if let Some(progress) = progress.lock().unwrap().as_mut() {
+                        if let Err(err) = progress.record_matched(
+                            &progress_path,
+                            track_collection.musicbrainz_release_id().as_deref(),
+                        ) {
+                            log::warn!("Failed to record import progress: {err}");
+                        }
+                    }
+                    if let Err(err) = importer_tx.send((path, track_collection, distance)).await {
+                        log::error!("Failed to send job to importer: {err}");
+                    };
+                    continue;
+                }
+                SkipReason::AlbumCollision
+            }
+            ImportDecision::Skip(reason) => reason,
+            ImportDecision::Quit => {
+                finished = false;
+                break;
+            }
+        };
+        quarantine_album(&config, &path, is_directory_group, &reason);
+        record_progress(&progress, &progress_path, ProgressStatus::Skipped);
+        stats.lock().unwrap().record_skipped(path.clone());
+        let _ = events.send(ImportEvent::Skipped { path, reason });
+    }
+
+    drop(importer_tx);
+    importer_handle.await.unwrap();
+
+    if finished {
+        if let Some(progress) = progress.lock().unwrap().as_mut() {
+            if let Err(err) = progress.clear() {
+                log::warn!("Failed to clear import progress: {err}");
+            }
+        }
+    }
+
+    let stats = std::mem::take(&mut *stats.lock().unwrap());
+    Ok(stats)
+}
+
+/// Transfer the files of a release that has been tagged into the library, write the tags and
+/// record the import in the journal and the statistics.
+///
+/// Returns `true` if the release was imported.
+pub fn import_release(
+    config: &Config,
+    import_root: &Path,
+    path: PathBuf,
+    mut track_collection: TaggedFileCollection,
+    distance: Distance,
+    journal: Option<&ImportJournal>,
+    stats: &Mutex<ImportStats>,
+) -> bool {
+    if let Err(err) = track_collection.prepare_cue_tracks(config) {
+        log::error!("Failed to prepare tracks from cue sheet: {err}");
+        stats.lock().unwrap().record_failed(path);
+        return false;
+    }
+
+    let file_count = track_collection.tracks().count();
+    let byte_count: u64 = track_collection
+        .tracks()
+        .filter_map(|track| fs::metadata(&track.path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let mut journal_entry = JournalEntry::new(path.clone(), config.paths.transfer_mode);
+    // Split tracks did not exist before the import, so there is nothing to restore.
+    journal_entry.tracks = track_collection
+        .tracks()
+        .filter(|_| !track_collection.has_split_tracks())
+        .map(|track| JournalTrack::snapshot(&track.path, &config.tags))
+        .collect();
+
+    if let Err(err) = track_collection.move_files(config) {
+        log::error!("Failed to move files: {err}");
+        stats.lock().unwrap().record_failed(path);
+        return false;
+    };
+    hooks::run_hooks(
+        &config.hooks,
+        HookEvent::PostMove,
+        &path,
+        &track_collection,
+        &distance,
+    );
+    for (journal_track, track) in journal_entry
+        .tracks
+        .iter_mut()
+        .zip(track_collection.tracks())
+    {
+        journal_track.imported_path.clone_from(&track.path);
+    }
+    if track_collection.has_split_tracks() {
+        journal_entry
+            .created_files
+            .extend(track_collection.tracks().map(|track| track.path.clone()));
+    }
+
+    if track_collection.directory_group().is_some() {
+        log::info!(
+            "Not transferring attachments, because {} contains several releases",
+            path.display()
+        );
+    } else {
+        match track_collection.move_attachments(&path, config) {
+            Ok(attachment_paths) => {
+                log::info!("Transferred {} attachments", attachment_paths.len());
+                journal_entry.attachments = attachment_paths;
+            }
+            Err(err) => log::warn!("Failed to transfer attachments: {err}"),
+        }
+    }
+
+    stats
+        .lock()
+        .unwrap()
+        .record_files_moved(file_count, byte_count);
+    if let Err(err) = track_collection.write_tags(config) {
+        log::error!("Failed to write tags: {err}");
+        stats.lock().unwrap().record_failed(path);
+        return false;
+    };
+    for journal_track in &mut journal_entry.tracks {
+        journal_track.checksum = track_collection
+            .checksum(&journal_track.imported_path)
+            .map(str::to_string);
+    }
+    hooks::run_hooks(
+        &config.hooks,
+        HookEvent::PostWrite,
+        &path,
+        &track_collection,
+        &distance,
+    );
+
+    if config.paths.write_release_json {
+        match track_collection.write_release_json() {
+            Ok(Some(json_path)) => {
+                log::info!("Wrote release metadata to {}", json_path.display());
+                journal_entry.created_files.push(json_path);
+            }
+            Ok(None) => (),
+            Err(err) => log::error!("Failed to write release metadata: {err}"),
+        }
+    }
+
+    if let Some(archive_path) = track_collection.source_archive() {
+        journal_entry.set_source_archive(archive_path);
+    }
+    if let Some(journal) = journal {
+        if let Err(err) = journal.append(&journal_entry) {
+            log::warn!("Failed to record import in journal: {err}");
+        }
+    }
+
+    if config.cleanup.remove_empty_dirs && config.paths.transfer_mode == TransferMode::Move {
+        if let Err(err) = util::remove_empty_dirs(&path, import_root, &config.cleanup.junk_patterns)
+        {
+            log::warn!("Failed to clean up {}: {err}", path.display());
+        }
+    }
+    if let Err(err) = quarantine::remove_report(&path) {
+        log::warn!("Failed to remove quarantine report: {err}");
+    }
+    let path = match track_collection.source_archive() {
+        Some(archive_path) => {
+            clean_up_archive(config, archive_path);
+            archive_path.to_path_buf()
+        }
+        None => path,
+    };
+    let mut stats = stats.lock().unwrap();
+    if track_collection.unreadable_files().is_empty() {
+        stats.record_imported(path, distance);
+    } else {
+        stats.record_partially_imported(path, distance);
+    }
+    true
+}
+
+/// Remove the imported archive if configured.
+///
+/// The temporary directory that the archive was extracted to is removed when the track collection
+/// is dropped.
+fn clean_up_archive(config: &Config, archive_path: &Path) {
+    if config.archives.remove_after_import {
+        match fs::remove_file(archive_path) {
+            Ok(()) => log::info!("Removed archive {}", archive_path.display()),
+            Err(err) => log::warn!("Failed to remove {}: {err}", archive_path.display()),
+        }
+    }
+}
+
+/// Record the status of an album if the progress of the import run is tracked.
+fn record_progress(progress: &Mutex<Option<ImportProgress>>, path: &Path, status: ProgressStatus) {
+    if let Some(progress) = progress.lock().unwrap().as_mut() {
+        if let Err(err) = progress.record(path, status) {
+            log::warn!("Failed to record import progress: {err}");
+        }
+    }
+}
+
+/// Returns the path that the progress of the album is recorded for.
+fn album_progress_path(path: &Path, track_collection: &TaggedFileCollection) -> PathBuf {
+    progress::album_path(
+        path,
+        track_collection.source_archive(),
+        track_collection.directory_group(),
+    )
+}
+
+/// Move the album directory into the quarantine directory (if enabled).
+///
+/// Directories that contain several releases are not quarantined, because that would also move
+/// the files of the other releases.
+fn quarantine_album(config: &Config, path: &Path, is_directory_group: bool, reason: &SkipReason) {
+    if is_directory_group {
+        log::info!(
+            "Not quarantining {}, because it contains several releases",
+            path.display()
+        );
+        return;
+    }
+    if let Err(err) = quarantine::quarantine(config, path, reason) {
+        log::error!("Failed to quarantine {}: {err}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::ImportOutcome;
+    use futures::StreamExt;
+
+    /// Create a decision request for an empty album and the receiver for its decision.
+    fn decision_request() -> (DecisionRequest, oneshot::Receiver<ImportDecision>) {
+        let (responder, response) = oneshot::channel();
+        let request = DecisionRequest {
+            album: ScannedAlbum {
+                path: PathBuf::from("/import/Album"),
+                track_collection: TaggedFileCollection::new(vec![]),
+                candidates: ReleaseCandidateCollection::from(vec![]),
+                local_release: None,
+            },
+            config: Arc::new(Config::default()),
+            responder: DecisionResponder(responder),
+        };
+        (request, response)
+    }
+
+    #[test]
+    fn test_decision_request() {
+        let (request, mut response) = decision_request();
+        assert_eq!(request.album().path, PathBuf::from("/import/Album"));
+        request.skip();
+        assert!(matches!(
+            response.try_recv(),
+            Ok(ImportDecision::Skip(SkipReason::SkippedByUser))
+        ));
+
+        let (request, mut response) = decision_request();
+        let (album, responder) = request.into_parts();
+        assert!(album.local_release.is_none());
+        responder.respond(ImportDecision::Quit);
+        assert!(matches!(response.try_recv(), Ok(ImportDecision::Quit)));
+
+        let (request, mut response) = decision_request();
+        drop(request);
+        assert!(response.try_recv().is_err());
+    }
+    #[test]
+    fn test_resolve_album_collision() {
+        use crate::tag::memory::MemoryTag;
+        use crate::tag::TagKey;
+        use crate::TaggedFile;
+
+        let collection = |release_id: &str| {
+            TaggedFileCollection::new(vec![TaggedFile::with_tags(
+                PathBuf::from("/import/01.flac"),
+                vec![Box::new(
+                    MemoryTag::new()
+                        .with_value(TagKey::AlbumArtist, Some("Artist"))
+                        .with_value(TagKey::Album, Some("Album"))
+                        .with_value(TagKey::TrackTitle, Some("Track"))
+                        .with_value(TagKey::MusicBrainzReleaseId, Some(release_id)),
+                )],
+            )])
+        };
+        let config = Config::default();
+        let mode = SelectionMode::Interactive;
+        let mut destinations = AlbumDestinations::default();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        futures::executor::block_on(async {
+            // The first release is registered without asking.
+            let mut first = collection("first");
+            assert!(resolve_album_collision(
+                &config,
+                &mode,
+                &mut destinations,
+                &mut first,
+                &events_tx
+            )
+            .await
+            .unwrap());
+            assert!(events_rx.try_recv().is_err());
+
+            // The collision with the second release is resolved by the consumer.
+            let mut second = collection("second");
+            let (resolved, ()) = futures::join!(
+                resolve_album_collision(&config, &mode, &mut destinations, &mut second, &events_tx),
+                async {
+                    let Some(ImportEvent::AlbumCollision(request)) = events_rx.recv().await else {
+                        panic!("Expected an album collision");
+                    };
+                    assert_eq!(request.suffixes(), [" [second]"]);
+                    request.respond(CollisionResolution::AppendSuffix(" [second]".to_string()));
+                }
+            );
+            assert!(resolved.unwrap());
+            assert!(second
+                .album_directory(&config)
+                .unwrap()
+                .unwrap()
+                .ends_with("Artist/Album [second]"));
+
+            // Dropping the request skips the release.
+            let mut third = collection("third");
+            let (resolved, ()) = futures::join!(
+                resolve_album_collision(&config, &mode, &mut destinations, &mut third, &events_tx),
+                async {
+                    let _ = events_rx.recv().await;
+                }
+            );
+            assert!(!resolved.unwrap());
+        });
+    }
+
+    #[test]
+    fn test_import_session_automatic() {
+        let import_root = tempfile::tempdir().unwrap();
+        let album_dir = import_root.path().join("Album");
+        fs::create_dir(&album_dir).unwrap();
+        fs::write(album_dir.join("01.flac"), b"not a flac file").unwrap();
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (events, stats) = runtime.block_on(async {
+            let mut session = ImportSession::start(
+                Config::default(),
+                None,
+                import_root.path().to_path_buf(),
+                SelectionMode::Automatic {
+                    max_distance: Distance::from(0.1),
+                },
+                false,
+            );
+            let mut events = vec![];
+            while let Some(event) = session.next().await {
+                events.push(event);
+            }
+            (events, session.finish().await.unwrap())
+        });
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0],
+            ImportEvent::Failed {
+                path,
+                error: crate::Error::UnreadableFiles(1),
+            } if path == &album_dir
+        ));
+        assert_eq!(stats.count(ImportOutcome::Failed), 1);
+        assert_eq!(stats.count(ImportOutcome::Imported), 0);
+        assert!(album_dir.join("01.flac").exists());
+    }
+}
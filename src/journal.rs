@@ -0,0 +1,456 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Journal of past imports, which allows reverting them.
+
+use crate::config::{CollisionPolicy, TagsConfig, TransferMode};
+use crate::tag::{read_tags_from_path, Tag, TagKey};
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use xdg::BaseDirectories;
+
+/// File name of the journal in the state directory.
+const JOURNAL_FILE_NAME: &str = "import-journal.jsonl";
+
+/// The original state of a single track before it was imported.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JournalTrack {
+    /// Path of the file before the import.
+    pub original_path: PathBuf,
+    /// Path of the file after the import.
+    pub imported_path: PathBuf,
+    /// Original tag values for each tag in the file (or `None` if the tags could not be read).
+    ///
+    /// Multi-valued tag keys are recorded once for every value.
+    pub original_tags: Option<Vec<Vec<(TagKey, String)>>>,
+    /// SHA-256 checksum of the file after it was transferred (if transfers are verified).
+    #[serde(default)]
+    pub checksum: Option<String>,
+}
+
+impl JournalTrack {
+    /// Record the current tags of the file at the given path.
+    ///
+    /// If the tags cannot be read, a warning is logged and the tags are not restored when the
+    /// import is reverted.
+    pub fn snapshot(path: &Path, config: &TagsConfig) -> Self {
+        let original_tags = read_tags_from_path(path, config)
+            .inspect_err(|err| {
+                log::warn!(
+                    "Failed to record original tags of {}: {err}",
+                    path.display()
+                );
+            })
+            .ok()
+            .map(|tags| tags.iter().map(|tag| tag_values(tag.as_ref())).collect());
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        Self {
+            original_path: path.clone(),
+            imported_path: path,
+            original_tags,
+            checksum: None,
+        }
+    }
+
+    /// Rewrite the original tag values into the imported file.
+    ///
+    /// All values of the imported file are removed first, so that keys which were absent before
+    /// the import (or tags that did not exist at all) are cleared.
+    fn restore_tags(&self, config: &TagsConfig) -> crate::Result<()> {
+        let Some(original_tags) = &self.original_tags else {
+            log::warn!(
+                "Original tags of {} were not recorded, keeping the current tags",
+                self.imported_path.display()
+            );
+            return Ok(());
+        };
+
+        let mut tags = read_tags_from_path(&self.imported_path, config)?;
+        for (index, tag) in tags.iter_mut().enumerate() {
+            for key in tag_keys(tag.as_ref()) {
+                tag.clear(&key);
+            }
+
+            let mut original_values: Vec<(&TagKey, Vec<Cow<'_, str>>)> = vec![];
+            for (key, value) in original_tags.get(index).into_iter().flatten() {
+                let value = Cow::from(value.as_str());
+                match original_values
+                    .iter_mut()
+                    .find(|(original_key, _)| *original_key == key)
+                {
+                    Some((_, values)) => values.push(value),
+                    None => original_values.push((key, vec![value])),
+                }
+            }
+            for (key, values) in original_values {
+                tag.set_multiple(key, &values);
+            }
+            tag.write(&self.imported_path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the keys that have a value in the tag, with a [`TagKey::Performer`] key for each
+/// instrument instead of the combined [`TagKey::Performers`] key.
+fn tag_keys(tag: &dyn Tag) -> Vec<TagKey> {
+    let mut keys = tag
+        .keys()
+        .into_iter()
+        .filter(|key| key != &TagKey::Performers)
+        .collect::<Vec<_>>();
+    for person in tag.performers().into_iter().flatten() {
+        let key = TagKey::Performer(person.involvement.into_owned());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Returns all values of the tag as `(key, value)` pairs.
+fn tag_values(tag: &dyn Tag) -> Vec<(TagKey, String)> {
+    let mut values = vec![];
+    for key in tag.keys() {
+        if key == TagKey::Performers {
+            continue;
+        }
+        for value in tag.get_multiple(&key) {
+            values.push((key.clone(), value.into_owned()));
+        }
+    }
+    for person in tag.performers().into_iter().flatten() {
+        values.push((
+            TagKey::Performer(person.involvement.into_owned()),
+            person.involvee.into_owned(),
+        ));
+    }
+    values
+}
+
+/// A single import in the journal.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JournalEntry {
+    /// The directory that was imported.
+    pub source_path: PathBuf,
+    /// Time of the import (RFC 3339).
+    pub imported_at: String,
+    /// How the files were transferred into the library.
+    pub transfer_mode: TransferMode,
+    /// The imported tracks.
+    pub tracks: Vec<JournalTrack>,
+    /// Attachments that were transferred along with the tracks, as `(original, imported)` paths.
+    pub attachments: Vec<(PathBuf, PathBuf)>,
+    /// Files that were created during the import (e.g., `release.json`).
+    #[serde(default)]
+    pub created_files: Vec<PathBuf>,
+}
+
+impl JournalEntry {
+    /// Create a new journal entry for an import that happened just now.
+    pub fn new(source_path: PathBuf, transfer_mode: TransferMode) -> Self {
+        Self {
+            source_path: std::path::absolute(&source_path).unwrap_or(source_path),
+            imported_at: chrono::Local::now().to_rfc3339(),
+            transfer_mode,
+            tracks: vec![],
+            attachments: vec![],
+            created_files: vec![],
+        }
+    }
+
+    /// Record that the imported files were extracted from the archive at the given path.
+    ///
+    /// The extracted files are removed after the import, so the archive is recorded as their
+    /// original path and reverting the import only removes the imported copies.
+    pub fn set_source_archive(&mut self, archive_path: &Path) {
+        let archive_path =
+            std::path::absolute(archive_path).unwrap_or_else(|_| archive_path.to_path_buf());
+        self.transfer_mode = TransferMode::Copy;
+        for track in &mut self.tracks {
+            track.original_path.clone_from(&archive_path);
+        }
+        for (original_path, _) in &mut self.attachments {
+            original_path.clone_from(&archive_path);
+        }
+        self.source_path = archive_path;
+    }
+
+    /// Returns `true` if the entry belongs to the given path (either the source directory or the
+    /// directory that the files were imported to).
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.source_path == path
+            || self
+                .tracks
+                .iter()
+                .any(|track| track.imported_path.parent() == Some(path))
+    }
+
+    /// Revert the import by restoring the original tags and moving the files back to their
+    /// original location (or removing the imported copies if the files were not moved).
+    ///
+    /// Afterwards, the album directories in the library are removed if they are empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the files cannot be restored.
+    pub fn revert(&self, config: &TagsConfig) -> crate::Result<()> {
+        for track in &self.tracks {
+            if self.transfer_mode == TransferMode::Move {
+                track.restore_tags(config)?;
+            }
+            self.restore_file(&track.imported_path, &track.original_path)?;
+        }
+
+        for (original_path, imported_path) in &self.attachments {
+            self.restore_file(imported_path, original_path)?;
+        }
+
+        for path in &self.created_files {
+            match fs::remove_file(path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => (),
+            }
+        }
+
+        let mut imported_dirs = self
+            .tracks
+            .iter()
+            .filter_map(|track| track.imported_path.parent())
+            .collect::<Vec<_>>();
+        imported_dirs.sort();
+        imported_dirs.dedup();
+        for dir in imported_dirs.into_iter().filter(|dir| dir.is_dir()) {
+            util::remove_empty_dirs(dir, dir.parent().unwrap_or(dir), &[])?;
+        }
+
+        Ok(())
+    }
+
+    /// Move an imported file back or remove it, depending on the transfer mode.
+    fn restore_file(&self, imported_path: &Path, original_path: &Path) -> crate::Result<()> {
+        if self.transfer_mode == TransferMode::Move {
+            let _ = util::transfer_file(
+                imported_path,
+                original_path,
+                TransferMode::Move,
+                CollisionPolicy::Rename,
+            )?;
+        } else {
+            fs::remove_file(imported_path)?;
+            log::info!("Removed file {}", imported_path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Journal of past imports, stored as JSON lines.
+#[derive(Debug, Clone)]
+pub struct ImportJournal {
+    /// Path of the journal file.
+    path: PathBuf,
+}
+
+impl ImportJournal {
+    /// Open the journal at the given path.
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Open the journal in the XDG state directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory cannot be determined or created.
+    pub fn open_default() -> crate::Result<Self> {
+        let path =
+            BaseDirectories::with_prefix(crate::PKG_NAME)?.place_state_file(JOURNAL_FILE_NAME)?;
+        Ok(Self::new(path))
+    }
+
+    /// Append an entry to the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be written.
+    pub fn append(&self, entry: &JournalEntry) -> crate::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Read all entries from the journal, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be read or is malformed.
+    pub fn entries(&self) -> crate::Result<Vec<JournalEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        BufReader::new(file)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+
+    /// Remove the entry and return it. If `path` is `None`, the most recent entry is removed,
+    /// otherwise the most recent entry that matches the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the journal cannot be read or written.
+    pub fn take_entry(&self, path: Option<&Path>) -> crate::Result<Option<JournalEntry>> {
+        let mut entries = self.entries()?;
+        let Some(index) = entries
+            .iter()
+            .rposition(|entry| path.is_none_or(|path| entry.matches_path(path)))
+        else {
+            return Ok(None);
+        };
+
+        let entry = entries.remove(index);
+        let mut content = Vec::new();
+        for entry in &entries {
+            serde_json::to_writer(&mut content, entry)?;
+            content.push(b'\n');
+        }
+        fs::write(&self.path, content)?;
+
+        Ok(Some(entry))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    fn entry(source_path: &str, imported_dir: &str) -> JournalEntry {
+        let mut entry = JournalEntry::new(PathBuf::from(source_path), TransferMode::Copy);
+        entry.tracks.push(JournalTrack {
+            original_path: Path::new(source_path).join("01.flac"),
+            imported_path: Path::new(imported_dir).join("01.flac"),
+            original_tags: Some(vec![vec![(TagKey::TrackTitle, "Title".to_string())]]),
+            checksum: None,
+        });
+        entry
+    }
+
+    #[test]
+    fn test_journal() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let journal = ImportJournal::new(tmp_dir.path().join("journal.jsonl"));
+        assert!(journal.entries().unwrap().is_empty());
+
+        journal.append(&entry("/import/a", "/music/A")).unwrap();
+        journal.append(&entry("/import/b", "/music/B")).unwrap();
+        journal.append(&entry("/import/c", "/music/C")).unwrap();
+        assert_eq!(journal.entries().unwrap().len(), 3);
+
+        let taken = journal
+            .take_entry(Some(Path::new("/music/A")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(taken.source_path, PathBuf::from("/import/a"));
+
+        let taken = journal.take_entry(None).unwrap().unwrap();
+        assert_eq!(taken.source_path, PathBuf::from("/import/c"));
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].source_path, PathBuf::from("/import/b"));
+        assert_eq!(
+            entries[0].tracks[0].original_tags,
+            Some(vec![vec![(TagKey::TrackTitle, "Title".to_string())]])
+        );
+        assert!(journal
+            .take_entry(Some(Path::new("/other")))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_revert_copy() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let imported_path = tmp_dir.path().join("01.flac");
+        fs::write(&imported_path, "").unwrap();
+        let release_json_path = tmp_dir.path().join("release.json");
+        fs::write(&release_json_path, "{}").unwrap();
+        let mut entry = JournalEntry::new(tmp_dir.path().join("source"), TransferMode::Copy);
+        entry.tracks.push(JournalTrack {
+            original_path: tmp_dir.path().join("source").join("01.flac"),
+            imported_path: imported_path.clone(),
+            original_tags: Some(vec![]),
+            checksum: None,
+        });
+        entry.created_files.push(release_json_path.clone());
+
+        entry.revert(&Config::default().tags).unwrap();
+        assert!(!imported_path.exists());
+        assert!(!release_json_path.exists());
+        assert!(!tmp_dir.path().exists());
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_restore_tags() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("track.flac");
+        fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/media/untagged/track.flac"
+            ),
+            &path,
+        )
+        .unwrap();
+        let config = Config::default().tags;
+        let mut tags = read_tags_from_path(&path, &config).unwrap();
+        tags[0].set_multiple(&TagKey::Genre, &[Cow::from("Jazz"), Cow::from("Blues")]);
+        tags[0].set(
+            &TagKey::Performer("piano".to_string()),
+            Cow::from("Ahmad Jamal"),
+        );
+        tags[0].write(&path).unwrap();
+
+        let track = JournalTrack::snapshot(&path, &config);
+        let mut tags = read_tags_from_path(&path, &config).unwrap();
+        tags[0].set(&TagKey::Genre, Cow::from("Rock"));
+        tags[0].set(&TagKey::TrackTitle, Cow::from("Title"));
+        tags[0].clear(&TagKey::Performers);
+        tags[0].write(&path).unwrap();
+        track.restore_tags(&config).unwrap();
+
+        let tags = read_tags_from_path(&path, &config).unwrap();
+        assert_eq!(tags[0].get_multiple(&TagKey::Genre), ["Jazz", "Blues"]);
+        assert!(tags[0].get(&TagKey::TrackTitle).is_none());
+        assert_eq!(
+            tags[0]
+                .performer("piano")
+                .unwrap()
+                .into_iter()
+                .map(|person| person.involvee)
+                .collect::<Vec<_>>(),
+            ["Ahmad Jamal"]
+        );
+    }
+}
@@ -46,18 +46,36 @@
 #![warn(dead_code)]
 #![warn(variant_size_differences)]
 
+mod acoustid;
 mod analyzer;
+mod archive;
 mod cache;
 mod cli;
 mod config;
+mod cue;
+mod decisions;
 mod distance;
+mod enrichment;
 mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod filename_pattern;
+mod hooks;
+mod import_session;
+mod journal;
+mod local_metadata;
 mod media;
 mod musicbrainz;
+mod musicbrainz_collection;
+mod network;
 mod pathformat;
+mod progress;
+mod quarantine;
 mod release;
 mod release_candidate;
+mod release_filter;
 mod scanner;
+mod stats;
 mod tag;
 mod taggedfile;
 mod taggedfilecollection;
@@ -68,9 +86,19 @@ pub use self::cache::Cache;
 pub use self::cli::main;
 pub use self::config::Config;
 pub use self::error::{ErrorType as Error, Result};
+pub use self::import_session::{
+    CollisionRequest, CollisionResolution, DecisionRequest, DecisionResponder, ImportDecision,
+    ImportEvent, ImportSession, ScannedAlbum, SelectionMode,
+};
 pub use self::taggedfile::TaggedFile;
 pub use self::taggedfilecollection::TaggedFileCollection;
 
+/// String distance functions, which are only exported for the benchmarks.
+#[cfg(feature = "bench")]
+pub mod string_distance {
+    pub use crate::distance::{normalize, StringDistanceBuffer, StringMetric};
+}
+
 /// Name of this package.
 const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 
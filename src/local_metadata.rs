@@ -0,0 +1,343 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Release metadata from local files (e.g., `metadata.json` or `album.nfo`) in the album directory.
+//!
+//! This is useful for private recordings or releases that are not (yet) on MusicBrainz, but ship
+//! with their own metadata.
+
+use crate::release::ReleaseLike;
+use crate::tag::memory::MemoryTag;
+use crate::tag::TagKey;
+use crate::{TaggedFile, TaggedFileCollection};
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// File names of JSON metadata files, in order of preference.
+const JSON_FILE_NAMES: [&str; 1] = ["metadata.json"];
+
+/// File names of plain text metadata files, in order of preference.
+const TEXT_FILE_NAMES: [&str; 2] = ["album.nfo", "info.txt"];
+
+/// Regular expression that matches a track line in a plain text file (e.g., `01. Title (3:45)`).
+const TRACK_LINE_PATTERN: &str =
+    r"^\s*(?:(\d+)[-.])?(\d{1,3})\s*[.):-]?\s+(.+?)(?:\s+[\[(]?\d{1,2}:\d{2}[\])]?)?\s*$";
+
+/// Error that occurs when reading a local metadata file.
+#[derive(Error, Debug)]
+pub enum LocalMetadataError {
+    /// The file could not be read.
+    #[error("Failed to read metadata file: {0}")]
+    Io(#[from] io::Error),
+    /// The JSON file is malformed.
+    #[error("Failed to parse JSON metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The file does not contain any tracks.
+    #[error("Metadata file does not contain any tracks")]
+    NoTracks,
+}
+
+/// A track in a local metadata file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LocalTrackMetadata {
+    /// Disc number.
+    pub disc: Option<u32>,
+    /// Track number.
+    pub number: Option<String>,
+    /// Track title.
+    pub title: Option<String>,
+    /// Track artist, if it differs from the release artist.
+    pub artist: Option<String>,
+}
+
+/// Release metadata from a local file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LocalMetadata {
+    /// Release title.
+    pub title: Option<String>,
+    /// Release artist.
+    pub artist: Option<String>,
+    /// Release date.
+    pub date: Option<String>,
+    /// Record label.
+    pub label: Option<String>,
+    /// Catalog number.
+    pub catalog_number: Option<String>,
+    /// Barcode.
+    pub barcode: Option<String>,
+    /// Release country.
+    pub country: Option<String>,
+    /// Genre.
+    pub genre: Option<String>,
+    /// Tracks on the release.
+    pub tracks: Vec<LocalTrackMetadata>,
+}
+
+impl LocalMetadata {
+    /// Look for a supported metadata file in the given directory and read it.
+    ///
+    /// Returns `None` if no metadata file exists or if it could not be parsed.
+    pub fn find_in_dir(dir: &Path) -> Option<Self> {
+        let json_files = JSON_FILE_NAMES
+            .iter()
+            .map(|name| (dir.join(name), Self::read_json as fn(&str) -> _));
+        let text_files = TEXT_FILE_NAMES
+            .iter()
+            .map(|name| (dir.join(name), Self::read_text as fn(&str) -> _));
+
+        json_files
+            .chain(text_files)
+            .filter(|(path, _)| path.is_file())
+            .find_map(|(path, parse)| {
+                let result = fs::read(&path)
+                    .map_err(LocalMetadataError::from)
+                    .and_then(|bytes| parse(&String::from_utf8_lossy(&bytes)));
+                match result {
+                    Ok(metadata) => {
+                        log::info!("Using local metadata from {}", path.display());
+                        Some(metadata)
+                    }
+                    Err(err) => {
+                        log::warn!("Ignoring metadata file {}: {err}", path.display());
+                        None
+                    }
+                }
+            })
+    }
+
+    /// Parse metadata from a JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is malformed or does not contain any tracks.
+    pub fn read_json(content: &str) -> Result<Self, LocalMetadataError> {
+        let metadata: Self = serde_json::from_str(content)?;
+        if metadata.tracks.is_empty() {
+            return Err(LocalMetadataError::NoTracks);
+        }
+        Ok(metadata)
+    }
+
+    /// Parse metadata from a plain text file with `Key: Value` lines followed by a track list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file does not contain any tracks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the track line regular expression is invalid.
+    pub fn read_text(content: &str) -> Result<Self, LocalMetadataError> {
+        let track_line_regex = Regex::new(TRACK_LINE_PATTERN).expect("invalid track line regex");
+        let mut metadata = Self::default();
+        for line in content.lines() {
+            if let Some((key, value)) = line.split_once([':', '=']) {
+                let value = value.trim();
+                let field = match key.trim().to_lowercase().as_str() {
+                    "artist" | "album artist" | "performer" => Some(&mut metadata.artist),
+                    "album" | "title" => Some(&mut metadata.title),
+                    "date" | "year" | "released" | "release date" => Some(&mut metadata.date),
+                    "label" | "record label" => Some(&mut metadata.label),
+                    "catalog" | "catalog number" | "catalog #" | "cat#" | "cat. no." => {
+                        Some(&mut metadata.catalog_number)
+                    }
+                    "barcode" | "upc" | "ean" => Some(&mut metadata.barcode),
+                    "country" => Some(&mut metadata.country),
+                    "genre" => Some(&mut metadata.genre),
+                    _ => None,
+                };
+                if let Some(field) = field {
+                    if field.is_none() && !value.is_empty() {
+                        *field = Some(value.to_string());
+                    }
+                    continue;
+                }
+            }
+
+            if let Some(captures) = track_line_regex.captures(line) {
+                let (title, artist) = match captures[3].split_once(" - ") {
+                    Some((artist, title)) if metadata.artist.as_deref() != Some(artist) => {
+                        (title.to_string(), Some(artist.to_string()))
+                    }
+                    _ => (captures[3].to_string(), None),
+                };
+                metadata.tracks.push(LocalTrackMetadata {
+                    disc: captures.get(1).and_then(|disc| disc.as_str().parse().ok()),
+                    number: Some(captures[2].trim_start_matches('0').to_string())
+                        .filter(|number| !number.is_empty()),
+                    title: Some(title),
+                    artist,
+                });
+            }
+        }
+
+        if metadata.tracks.is_empty() {
+            return Err(LocalMetadataError::NoTracks);
+        }
+        Ok(metadata)
+    }
+
+    /// Returns `true` if the release title of the metadata is the same as the one of the release
+    /// (ignoring case and surrounding whitespace).
+    pub fn matches_release_title(&self, release: &impl ReleaseLike) -> bool {
+        self.title
+            .as_deref()
+            .zip(release.release_title())
+            .is_some_and(|(title, other_title)| {
+                title.trim().to_lowercase() == other_title.trim().to_lowercase()
+            })
+    }
+
+    /// Convert the metadata into a track collection that can be used as a release candidate.
+    ///
+    /// The tracks are not backed by actual files, the `dir` is only used to assign a path to them.
+    pub fn into_release(self, dir: &Path) -> TaggedFileCollection {
+        let tracks = self
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let total_tracks = self
+                    .tracks
+                    .iter()
+                    .filter(|other_track| other_track.disc == track.disc)
+                    .count()
+                    .to_string();
+                let tag = MemoryTag::new()
+                    .with_value(TagKey::Album, self.title.as_deref())
+                    .with_value(TagKey::AlbumArtist, self.artist.as_deref())
+                    .with_value(TagKey::ReleaseDate, self.date.as_deref())
+                    .with_value(TagKey::RecordLabel, self.label.as_deref())
+                    .with_value(TagKey::CatalogNumber, self.catalog_number.as_deref())
+                    .with_value(TagKey::Barcode, self.barcode.as_deref())
+                    .with_value(TagKey::ReleaseCountry, self.country.as_deref())
+                    .with_value(TagKey::Genre, self.genre.as_deref())
+                    .with_value(TagKey::TrackTitle, track.title.as_deref())
+                    .with_value(
+                        TagKey::Artist,
+                        track.artist.as_deref().or(self.artist.as_deref()),
+                    )
+                    .with_value(
+                        TagKey::TrackNumber,
+                        Some(
+                            track
+                                .number
+                                .clone()
+                                .unwrap_or_else(|| (index + 1).to_string()),
+                        ),
+                    )
+                    .with_value(TagKey::DiscNumber, track.disc.map(|disc| disc.to_string()))
+                    .with_value(TagKey::TotalTracks, Some(total_tracks.as_str()));
+                TaggedFile::with_tags(dir.to_path_buf(), vec![Box::new(tag)])
+            })
+            .collect();
+        TaggedFileCollection::new(tracks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_json() {
+        let metadata = LocalMetadata::read_json(
+            r#"{
+                "title": "Live at Home",
+                "artist": "The Band",
+                "date": "2024-05-01",
+                "tracks": [
+                    {"title": "Intro"},
+                    {"title": "Song", "artist": "Guest"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Live at Home"));
+        assert_eq!(metadata.tracks.len(), 2);
+        assert_eq!(metadata.tracks[1].artist.as_deref(), Some("Guest"));
+
+        assert!(matches!(
+            LocalMetadata::read_json(r#"{"title": "Empty"}"#),
+            Err(LocalMetadataError::NoTracks)
+        ));
+    }
+
+    #[test]
+    fn test_read_text() {
+        let metadata = LocalMetadata::read_text(
+            "Artist: The Band\n\
+             Album: Live at Home\n\
+             Year: 2024\n\
+             Label: Self-released\n\
+             \n\
+             Tracklist:\n\
+             01. Intro (1:23)\n\
+             02. Guest - Song [4:56]\n\
+             1-03 Encore\n",
+        )
+        .unwrap();
+        assert_eq!(metadata.artist.as_deref(), Some("The Band"));
+        assert_eq!(metadata.title.as_deref(), Some("Live at Home"));
+        assert_eq!(metadata.date.as_deref(), Some("2024"));
+        assert_eq!(metadata.label.as_deref(), Some("Self-released"));
+        assert_eq!(
+            metadata.tracks,
+            vec![
+                LocalTrackMetadata {
+                    disc: None,
+                    number: Some("1".to_string()),
+                    title: Some("Intro".to_string()),
+                    artist: None,
+                },
+                LocalTrackMetadata {
+                    disc: None,
+                    number: Some("2".to_string()),
+                    title: Some("Song".to_string()),
+                    artist: Some("Guest".to_string()),
+                },
+                LocalTrackMetadata {
+                    disc: Some(1),
+                    number: Some("3".to_string()),
+                    title: Some("Encore".to_string()),
+                    artist: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_release() {
+        let metadata = LocalMetadata::read_text("Artist: The Band\nAlbum: Demo\n1. A\n2. B\n")
+            .unwrap()
+            .into_release(Path::new("/tmp"));
+        assert_eq!(metadata.release_title().as_deref(), Some("Demo"));
+        assert_eq!(metadata.release_artist().as_deref(), Some("The Band"));
+        assert_eq!(metadata.release_track_count(), Some(2));
+        assert_eq!(metadata.total_discs().as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_matches_release_title() {
+        let metadata = LocalMetadata::read_text("Album: Demo\n1. A\n").unwrap();
+        let release = |title: &str| {
+            LocalMetadata::read_text(&format!("Album: {title}\n1. A\n"))
+                .unwrap()
+                .into_release(Path::new("/tmp"))
+        };
+        assert!(metadata.matches_release_title(&release(" demo ")));
+        assert!(!metadata.matches_release_title(&release("Live")));
+        assert!(!LocalMetadata::default().matches_release_title(&release("Demo")));
+    }
+}
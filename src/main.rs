@@ -8,7 +8,6 @@
 
 //! Main module
 
-#[tokio::main]
-async fn main() -> helicon::Result<()> {
-    helicon::main().await
+fn main() -> helicon::Result<()> {
+    helicon::main()
 }
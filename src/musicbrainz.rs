@@ -8,29 +8,53 @@
 
 //! MusicBrainz helper functions.
 
+use crate::config::{SearchQueryConfig, SearchStrategy};
+use crate::distance::{Distance, ReleaseSimilarity};
+use crate::media::MediaLike;
+use crate::network;
 use crate::release::ReleaseLike;
 use crate::release_candidate::ReleaseCandidate;
+use crate::release_filter;
+use crate::track::TrackLike;
 use crate::util::KeyedBinaryHeap;
 use crate::Cache;
 use crate::Config;
-use futures::{
-    future::TryFutureExt,
-    stream::{self, Stream, StreamExt},
+use futures::stream::{self, Stream, StreamExt};
+use musicbrainz_rs_nova::entity::{
+    recording::{
+        Recording as MusicBrainzRecording, RecordingSearchQuery as MusicBrainzRecordingSearchQuery,
+    },
+    release::ReleaseSearchQuery as MusicBrainzReleaseSearchQuery,
+    release_group::ReleaseGroupSearchQuery as MusicBrainzReleaseGroupSearchQuery,
+    search::SearchResult as MusicBrainzSearchResult,
 };
 pub use musicbrainz_rs_nova::entity::{
     release::Release as MusicBrainzRelease, release_group::ReleaseGroup as MusicBrainzReleaseGroup,
 };
-use musicbrainz_rs_nova::{
-    entity::release::ReleaseSearchQuery as MusicBrainzReleaseSearchQuery, Fetch, Search,
-};
 use regex::Regex;
+use serde::de::DeserializeOwned;
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::time::Duration;
 
 /// MusicBrainz Artist ID of the "Various Artist" compilation artist.
 ///
 /// See <https://musicbrainz.org/artist/89ad4ac3-39f7-470e-963a-56509c546377>.
 pub const VARIOUS_ARTISTS_ID: &str = "89ad4ac3-39f7-470e-963a-56509c546377";
 
+/// Base URL of the MusicBrainz web service.
+const MUSICBRAINZ_API_URL: &str = "https://musicbrainz.org/ws/2";
+
+/// Additional information that is requested when fetching a release.
+const RELEASE_INCLUDES: &str = "artists recordings release-groups labels artist-credits aliases recording-level-rels work-rels work-level-rels artist-rels url-rels";
+
+/// Number of times a request is retried if MusicBrainz rejects it because of rate limiting.
+const MAX_RETRIES: u32 = 5;
+
+/// Delay before retrying a request that was rejected because of rate limiting, unless the
+/// response specifies a different delay.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
 /// Configurable MusicBrainz API client with caching support.
 #[derive(Debug)]
 pub struct MusicBrainzClient<'a> {
@@ -38,12 +62,79 @@ pub struct MusicBrainzClient<'a> {
     config: &'a Config,
     /// Cache
     cache: Option<&'a Cache>,
+    /// HTTP client with the configured proxy and CA certificate.
+    client: reqwest::Client,
 }
 
 impl<'a> MusicBrainzClient<'a> {
     /// Create a new MusicBrainz client.
-    pub fn new(config: &'a Config, cache: Option<&'a Cache>) -> Self {
-        Self { config, cache }
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created with the network configuration.
+    pub fn new(config: &'a Config, cache: Option<&'a Cache>) -> crate::Result<Self> {
+        Ok(Self {
+            config,
+            cache,
+            client: network::http_client(&config.network)?,
+        })
+    }
+
+    /// Send a request to the MusicBrainz web service and deserialize the JSON response.
+    ///
+    /// Requests that are rejected because of rate limiting are retried after a delay.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> crate::Result<T> {
+        let url = format!("{MUSICBRAINZ_API_URL}/{path}");
+        let mut retries = 0;
+        loop {
+            let response = self
+                .client
+                .get(&url)
+                .query(query)
+                .query(&[("fmt", "json")])
+                .send()
+                .await?;
+            if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE
+                && retries < MAX_RETRIES
+            {
+                retries += 1;
+                let delay = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+                    .map_or(RETRY_DELAY, Duration::from_secs);
+                log::debug!("MusicBrainz rejected request to {url}, retrying in {delay:?} ({retries}/{MAX_RETRIES})");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            let body = response.error_for_status()?.text().await?;
+            return Ok(serde_json::from_str(&body)?);
+        }
+    }
+
+    /// Search for MusicBrainz entities of the given type (e.g., `release`) with the query and
+    /// return the requested page of search results.
+    async fn search<T: DeserializeOwned>(
+        &self,
+        entity: &str,
+        query: &str,
+        limit: u8,
+        offset: u16,
+    ) -> crate::Result<T> {
+        self.get(
+            entity,
+            &[
+                ("query", query),
+                ("limit", &limit.to_string()),
+                ("offset", &offset.to_string()),
+            ],
+        )
+        .await
     }
 
     /// Find MusicBrainz Release information for the given (generic) Release.
@@ -51,6 +142,13 @@ impl<'a> MusicBrainzClient<'a> {
         &self,
         base_release: &impl ReleaseLike,
     ) -> crate::Result<Vec<ReleaseCandidate<MusicBrainzRelease>>> {
+        if let Some(release_id) = &self.config.lookup.release_id {
+            log::info!("Using release {release_id} as the only candidate");
+            let release = self.find_release_by_id(release_id.clone()).await?;
+            let candidate = ReleaseCandidate::with_base_release(release, base_release, self.config);
+            return Ok(vec![candidate]);
+        }
+
         if let Some(release_id) = base_release.musicbrainz_release_id() {
             match self.find_release_by_id(release_id.into_owned()).await {
                 Ok(release) => {
@@ -66,51 +164,219 @@ impl<'a> MusicBrainzClient<'a> {
             }
         }
 
-        let similar_release_ids = self
-            .find_release_ids_by_similarity(
-                base_release,
-                self.config.lookup.release_candidate_limit,
-                0,
-            )
+        if self.config.lookup.search.identifiers {
+            match self.find_releases_by_identifiers(base_release).await {
+                Ok(candidates) if !candidates.is_empty() => {
+                    log::info!(
+                        "Found {} release candidates by MusicBrainz Recording/Track IDs.",
+                        candidates.len()
+                    );
+                    return Ok(candidates);
+                }
+                Ok(_) => (),
+                Err(err) => {
+                    log::warn!(
+                        "Failed to look up releases by MusicBrainz Recording/Track IDs: {err}"
+                    );
+                }
+            }
+        }
+
+        let mut heap = KeyedBinaryHeap::with_capacity(
+            self.config.lookup.max_candidates(),
+            |candidate: &ReleaseCandidate<MusicBrainzRelease>| {
+                candidate.ranking_distance(self.config)
+            },
+        );
+        let mut seen_release_ids = HashSet::new();
+        let strategies = std::iter::once(SearchStrategy::Release)
+            .chain(self.config.lookup.search.fallbacks.iter().copied());
+        for strategy in strategies {
+            if !heap.is_empty() {
+                break;
+            }
+            if strategy != SearchStrategy::Release {
+                log::info!("No release candidates found, searching with strategy {strategy:?}");
+            }
+            heap = self
+                .find_releases_with_strategy(base_release, strategy, heap, &mut seen_release_ids)
+                .await?;
+        }
+
+        let releases: Vec<ReleaseCandidate<MusicBrainzRelease>> = heap.into_sorted_vec();
+        log::info!("Found {} release candidates.", releases.len());
+        Ok(releases)
+    }
+
+    /// Fetch the releases that contain the tracks with the MusicBrainz Recording or Track IDs of
+    /// the base release, bypassing the search by metadata.
+    async fn find_releases_by_identifiers(
+        &self,
+        base_release: &impl ReleaseLike,
+    ) -> crate::Result<Vec<ReleaseCandidate<MusicBrainzRelease>>> {
+        let Some((search_query, identified_track_count)) =
+            build_identifier_search_query(base_release)
+        else {
+            return Ok(vec![]);
+        };
+        log::debug!("Querying MusicBrainz recordings: {search_query}");
+        let response: MusicBrainzSearchResult<MusicBrainzRecording> = self
+            .search("recording", &search_query, MAX_IDENTIFIER_SEARCH_RESULTS, 0)
             .await?;
+
+        let release_ids = rank_releases_by_recording_count(
+            response
+                .entities
+                .into_iter()
+                .map(|recording| recording.releases.unwrap_or_default()),
+            identified_track_count,
+        )
+        .into_iter()
+        .filter(|release| self.accepts_release(release))
+        .map(|release| release.id)
+        .take(self.config.lookup.max_candidates())
+        .collect::<Vec<_>>();
+
         let heap = KeyedBinaryHeap::with_capacity(
-            similar_release_ids.len(),
-            |candidate: &ReleaseCandidate<MusicBrainzRelease>| candidate.distance(self.config),
+            self.config.lookup.max_candidates(),
+            |candidate: &ReleaseCandidate<MusicBrainzRelease>| {
+                candidate.ranking_distance(self.config)
+            },
         );
-        let heap = stream::iter(similar_release_ids)
+        let heap = stream::iter(release_ids)
             .map(|release_id| self.find_release_by_id(release_id))
             .buffer_unordered(self.config.lookup.connection_limit)
             .fold(heap, |mut heap, result| async {
-                let Ok(release) = result else {
-                    return heap;
-                };
-
-                let candidate =
-                    ReleaseCandidate::with_base_release(release, base_release, self.config);
-
-                log::debug!(
-                    "Release '{}' has distance to track collection: {}",
-                    candidate.release().title,
-                    candidate.distance(self.config),
-                );
-                heap.push(candidate);
+                match result {
+                    Ok(release) => heap.push(ReleaseCandidate::with_base_release(
+                        release,
+                        base_release,
+                        self.config,
+                    )),
+                    Err(err) => log::warn!("Failed to fetch release: {err}"),
+                }
                 heap
             })
             .await;
+        Ok(heap.into_sorted_vec())
+    }
 
-        let releases: Vec<ReleaseCandidate<MusicBrainzRelease>> = heap.into_sorted_vec();
-        log::info!("Found {} release candidates.", releases.len());
-        Ok(releases)
+    /// Search for similar releases with the given strategy and add them to the heap, requesting
+    /// multiple search result pages if necessary.
+    async fn find_releases_with_strategy<F>(
+        &self,
+        base_release: &impl ReleaseLike,
+        strategy: SearchStrategy,
+        mut heap: KeyedBinaryHeap<ReleaseCandidate<MusicBrainzRelease>, F, Distance>,
+        seen_release_ids: &mut HashSet<String>,
+    ) -> crate::Result<KeyedBinaryHeap<ReleaseCandidate<MusicBrainzRelease>, F, Distance>>
+    where
+        F: Fn(&ReleaseCandidate<MusicBrainzRelease>) -> Distance,
+    {
+        let mut offset: u16 = 0;
+        let mut fetched_release_count: usize = 0;
+        while let Some(limit) = next_page_limit(
+            self.config.lookup.release_candidate_limit,
+            self.config.lookup.max_candidates(),
+            seen_release_ids.len(),
+        ) {
+            let (page_releases, page_size) = match strategy {
+                SearchStrategy::Release | SearchStrategy::TitleOnly => {
+                    self.search_releases_by_similarity(base_release, strategy, limit, offset)
+                        .await?
+                }
+                SearchStrategy::ArtistAndTrackTitles => {
+                    self.search_releases_by_recordings(base_release, limit, offset)
+                        .await?
+                }
+            };
+            let is_last_page = page_size < usize::from(limit);
+            let page_releases = page_releases
+                .into_iter()
+                .filter(|release| seen_release_ids.insert(release.id.clone()))
+                .collect::<Vec<_>>();
+            let similar_release_ids = match self.config.lookup.max_full_candidates {
+                Some(max_full_candidates) => select_best_search_results(
+                    self.config,
+                    base_release,
+                    page_releases,
+                    max_full_candidates.saturating_sub(fetched_release_count),
+                ),
+                None => page_releases
+                    .into_iter()
+                    .map(|release| release.id)
+                    .collect(),
+            };
+            fetched_release_count += similar_release_ids.len();
+
+            heap = stream::iter(similar_release_ids)
+                .map(|release_id| self.find_release_by_id(release_id))
+                .buffer_unordered(self.config.lookup.connection_limit)
+                .fold(heap, |mut heap, result| async {
+                    let Ok(release) = result else {
+                        return heap;
+                    };
+
+                    let candidate =
+                        ReleaseCandidate::with_base_release(release, base_release, self.config);
+
+                    log::debug!(
+                        "Release '{}' has distance to track collection: {}",
+                        candidate.release().title,
+                        candidate.distance(self.config),
+                    );
+                    heap.push(candidate);
+                    heap
+                })
+                .await;
+
+            if is_last_page {
+                break;
+            }
+
+            if self
+                .config
+                .lookup
+                .max_full_candidates
+                .is_some_and(|max_full_candidates| fetched_release_count >= max_full_candidates)
+            {
+                log::debug!("Fetched {fetched_release_count} full releases, stopping search");
+                break;
+            }
+
+            if let Some(max_distance) = self.config.lookup.early_termination_distance {
+                if heap
+                    .iter()
+                    .any(|candidate| candidate.distance(self.config).as_f64() <= max_distance)
+                {
+                    log::debug!("Found candidate with distance <= {max_distance}, stopping search");
+                    break;
+                }
+            }
+
+            offset = offset.saturating_add(u16::from(limit));
+        }
+
+        Ok(heap)
     }
 
     /// Search for similar releases based on the metadata of an existing [`ReleaseLike`].
-    async fn find_release_ids_by_similarity(
+    ///
+    /// Returns the search results (without tracklists) that pass the candidate filters and the
+    /// number of releases on the search result page (including the filtered ones).
+    async fn search_releases_by_similarity(
         &self,
         base_release: &impl ReleaseLike,
+        strategy: SearchStrategy,
         limit: u8,
         offset: u16,
-    ) -> crate::Result<Vec<String>> {
-        let search_query = build_search_query(base_release);
+    ) -> crate::Result<(Vec<MusicBrainzRelease>, usize)> {
+        let Some(search_query) =
+            build_search_query(base_release, &self.config.lookup.search, strategy)
+        else {
+            log::debug!("Not enough metadata for a release search with strategy {strategy:?}");
+            return Ok((vec![], 0));
+        };
         log::debug!("Querying MusicBrainz: {search_query}");
         let response = if let Some(cached_response) = self.cache.and_then(|cache| cache.get_item((search_query.as_ref(), limit, offset))
                 .inspect_err(|err| {
@@ -119,10 +385,8 @@ impl<'a> MusicBrainzClient<'a> {
                 .ok()) {
             cached_response
         } else {
-            let response = MusicBrainzRelease::search(search_query.clone())
-                .limit(limit)
-                .offset(offset)
-                .execute()
+            let response: MusicBrainzSearchResult<MusicBrainzRelease> = self
+                .search("release", &search_query, limit, offset)
                 .await?;
             log::debug!(
                 "Found {} releases using query: {}",
@@ -142,12 +406,60 @@ impl<'a> MusicBrainzClient<'a> {
             response
         };
 
-        let ids = response
+        let page_size = response.entities.len();
+        let releases = response
             .entities
             .into_iter()
-            .map(|release| release.id)
+            .filter(|release| self.accepts_release(release))
             .collect();
-        Ok(ids)
+        Ok((releases, page_size))
+    }
+
+    /// Search for recordings by the release artist and the track titles and return the releases
+    /// that contain these recordings.
+    ///
+    /// Returns the releases (without tracklists) that pass the candidate filters and the number of
+    /// recordings on the search result page.
+    async fn search_releases_by_recordings(
+        &self,
+        base_release: &impl ReleaseLike,
+        limit: u8,
+        offset: u16,
+    ) -> crate::Result<(Vec<MusicBrainzRelease>, usize)> {
+        let Some(search_query) =
+            build_recording_search_query(base_release, &self.config.lookup.search)
+        else {
+            log::debug!("Not enough metadata for a recording search");
+            return Ok((vec![], 0));
+        };
+        log::debug!("Querying MusicBrainz recordings: {search_query}");
+        let response: MusicBrainzSearchResult<MusicBrainzRecording> = self
+            .search("recording", &search_query, limit, offset)
+            .await?;
+
+        let page_size = response.entities.len();
+        let mut releases: Vec<MusicBrainzRelease> = vec![];
+        for release in response
+            .entities
+            .into_iter()
+            .flat_map(|recording| recording.releases.unwrap_or_default())
+            .filter(|release| self.accepts_release(release))
+        {
+            if releases.iter().all(|other| other.id != release.id) {
+                releases.push(release);
+            }
+        }
+        Ok((releases, page_size))
+    }
+
+    /// Returns `true` if the release passes the configured candidate filters.
+    fn accepts_release(&self, release: &MusicBrainzRelease) -> bool {
+        let accepted =
+            release_filter::accepts_release(&self.config.lookup.candidate_filter, release);
+        if !accepted {
+            log::debug!("Release {} excluded by candidate filters", release.id);
+        }
+        accepted
     }
 
     /// Fetch a MusicBrainz release group by its ID.
@@ -166,23 +478,25 @@ impl<'a> MusicBrainzClient<'a> {
             return Ok(release_group);
         }
 
-        MusicBrainzReleaseGroup::fetch()
-            .id(&release_group_id)
-            .with_releases()
-            .execute()
-            .map_err(crate::Error::from)
-            .await
-            .inspect(|release_group| {
-                if let Some(cache) = self.cache {
-                    match cache.insert_item(release_group_id.as_ref(), release_group) {
-                        Ok(()) => {
-                            log::debug!("Inserted release group {release_group_id} into cache");
-                        }
-                        Err(err) => {
-                            log::warn!("Failed to insert release group {release_group_id} into cache: {err}");
-                        }
-                    }};
-            })
+        self.get::<MusicBrainzReleaseGroup>(
+            &format!("release-group/{release_group_id}"),
+            &[("inc", "releases")],
+        )
+        .await
+        .inspect(|release_group| {
+            if let Some(cache) = self.cache {
+                match cache.insert_item(release_group_id.as_ref(), release_group) {
+                    Ok(()) => {
+                        log::debug!("Inserted release group {release_group_id} into cache");
+                    }
+                    Err(err) => {
+                        log::warn!(
+                            "Failed to insert release group {release_group_id} into cache: {err}"
+                        );
+                    }
+                }
+            };
+        })
     }
 
     /// Find release IDs by MusicBrainz Release Group ID.
@@ -198,10 +512,38 @@ impl<'a> MusicBrainzClient<'a> {
             ));
         };
 
-        let release_ids = releases.into_iter().map(|release| release.id).collect();
+        let release_ids = releases
+            .into_iter()
+            .filter(|release| self.accepts_release(release))
+            .map(|release| release.id)
+            .collect();
         Ok(release_ids)
     }
 
+    /// Search for MusicBrainz Release Groups that are similar to the given (generic) release, so
+    /// that the release group can be selected before the release.
+    pub async fn find_release_groups_by_similarity(
+        &self,
+        base_release: &impl ReleaseLike,
+    ) -> crate::Result<Vec<MusicBrainzReleaseGroup>> {
+        let Some(search_query) =
+            build_release_group_search_query(base_release, &self.config.lookup.search)
+        else {
+            return Ok(vec![]);
+        };
+        log::debug!("Querying MusicBrainz release groups: {search_query}");
+        let response: MusicBrainzSearchResult<MusicBrainzReleaseGroup> = self
+            .search(
+                "release-group",
+                &search_query,
+                self.config.lookup.release_candidate_limit,
+                0,
+            )
+            .await?;
+        log::info!("Found {} release groups.", response.entities.len());
+        Ok(response.entities)
+    }
+
     /// Find releases by MusicBrainz Release Group ID.
     pub async fn find_releases_by_release_group_id(
         &self,
@@ -221,48 +563,97 @@ impl<'a> MusicBrainzClient<'a> {
         &self,
         release_id: String,
     ) -> crate::Result<MusicBrainzRelease> {
-        if let Some(release) = self.cache.and_then(|cache| {
+        let cached_entry = self.cache.and_then(|cache| {
             cache
-                .get_item(release_id.as_ref())
+                .get_entry::<MusicBrainzRelease>(release_id.as_ref())
                 .inspect_err(|err| {
                     log::debug!("Failed to get release {release_id} from cache: {err}");
                 })
                 .ok()
-        }) {
-            return Ok(release);
-        }
+                .map(|entry| (cache.is_expired(&entry), entry.item))
+        });
+        let stale_release = match cached_entry {
+            Some((false, release)) => return Ok(release),
+            Some((true, release)) => {
+                log::debug!("Cached release {release_id} expired, revalidating it");
+                Some(release)
+            }
+            None => None,
+        };
 
-        MusicBrainzRelease::fetch()
-            .id(&release_id)
-            .with_artists()
-            .with_recordings()
-            .with_release_groups()
-            .with_labels()
-            .with_artist_credits()
-            .with_aliases()
-            .with_recording_level_relations()
-            .with_work_relations()
-            .with_work_level_relations()
-            .with_artist_relations()
-            .with_url_relations()
-            .execute()
-            .map_err(crate::Error::from)
-            .await
-            .inspect(|release| {
-                if let Some(cache) = self.cache {
-                    match cache.insert_item(release_id.as_ref(), release) {
-                        Ok(()) => {
-                            log::debug!("Inserted release {release_id} into cache");
-                        }
-                        Err(err) => {
-                            log::warn!("Failed to insert release {release_id} into cache: {err}");
-                        }
-                    }
-                };
-            })
+        let result = self
+            .get::<MusicBrainzRelease>(
+                &format!("release/{release_id}"),
+                &[("inc", RELEASE_INCLUDES)],
+            )
+            .await;
+
+        let release = match (result, stale_release) {
+            (Ok(release), stale_release) => {
+                if stale_release.is_some_and(|stale_release| stale_release == release) {
+                    log::debug!("Cached release {release_id} was not modified");
+                }
+                release
+            }
+            (Err(err), Some(stale_release)) => {
+                log::warn!(
+                    "Failed to revalidate release {release_id}, using cached version: {err}"
+                );
+                return Ok(stale_release);
+            }
+            (Err(err), None) => return Err(err),
+        };
+
+        if let Some(cache) = self.cache {
+            match cache.insert_item(release_id.as_ref(), &release) {
+                Ok(()) => {
+                    log::debug!("Inserted release {release_id} into cache");
+                }
+                Err(err) => {
+                    log::warn!("Failed to insert release {release_id} into cache: {err}");
+                }
+            }
+        };
+        Ok(release)
     }
 }
 
+/// Rank the search results by their estimated distance to the base release and return the IDs of
+/// the `limit` best ones.
+fn select_best_search_results(
+    config: &Config,
+    base_release: &impl ReleaseLike,
+    search_results: Vec<MusicBrainzRelease>,
+    limit: usize,
+) -> Vec<String> {
+    let mut ranked_results = search_results
+        .into_iter()
+        .map(|release| {
+            (
+                ReleaseSimilarity::estimate_distance(config, base_release, &release),
+                release.id,
+            )
+        })
+        .collect::<Vec<_>>();
+    ranked_results.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    for (distance, release_id) in ranked_results.iter().skip(limit) {
+        log::debug!("Not fetching release {release_id} (estimated distance: {distance})");
+    }
+    ranked_results
+        .into_iter()
+        .take(limit)
+        .map(|(_, release_id)| release_id)
+        .collect()
+}
+
+/// Calculate the number of releases to request on the next search result page, or `None` if no
+/// more pages should be requested because the maximum number of candidates has been reached.
+fn next_page_limit(page_size: u8, max_candidates: usize, fetched_candidates: usize) -> Option<u8> {
+    let remaining = max_candidates.saturating_sub(fetched_candidates);
+    (remaining > 0)
+        .then(|| u8::try_from(remaining).map_or(page_size, |remaining| remaining.min(page_size)))
+}
+
 /// A MusicBrainz Identifier.
 ///
 /// See <https://musicbrainz.org/doc/MusicBrainz_Identifier> for details.
@@ -383,26 +774,51 @@ impl<'a> MusicBrainzId<'a> {
     }
 }
 
-/// Build a MusicBrainz search query from the given release.
-fn build_search_query(release: &impl ReleaseLike) -> String {
+/// Returns the search term for the value, with the fuzzy operator appended to each word if
+/// enabled.
+fn search_term(value: &str, fuzzy: bool) -> String {
+    if fuzzy {
+        value
+            .split_whitespace()
+            .map(|word| format!("{word}~"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        value.trim().to_string()
+    }
+}
+
+/// Build a MusicBrainz release search query from the given release.
+///
+/// Returns `None` if the release does not have the metadata that the strategy needs.
+fn build_search_query(
+    release: &impl ReleaseLike,
+    search_config: &SearchQueryConfig,
+    strategy: SearchStrategy,
+) -> Option<String> {
     let mut query = MusicBrainzReleaseSearchQuery::query_builder();
     let mut is_empty = true;
+    let title_only = strategy == SearchStrategy::TitleOnly;
 
     // Track count
-    if let Some(track_count) = release.release_track_count() {
-        let _ = query.tracks(&track_count.to_string());
-        is_empty = false;
-    };
+    if search_config.track_count {
+        if let Some(track_count) = release.release_track_count() {
+            let _ = query.tracks(&track_count.to_string());
+            is_empty = false;
+        };
+    }
 
     // Artist
-    if release.is_compilation() {
+    if title_only || !search_config.artist {
+        // The artist is not part of the query.
+    } else if release.is_compilation() {
         if !is_empty {
             let _ = query.and();
         };
         if let Some(v) = release.release_artist() {
             let _ = query.expr(
                 MusicBrainzReleaseSearchQuery::query_builder()
-                    .artist(v.as_ref().trim())
+                    .artist(&search_term(&v, search_config.fuzzy))
                     .or()
                     .arid(VARIOUS_ARTISTS_ID),
             );
@@ -414,26 +830,174 @@ fn build_search_query(release: &impl ReleaseLike) -> String {
         if !is_empty {
             let _ = query.and();
         };
-        let _ = query.artist(v.as_ref().trim());
+        let _ = query.artist(&search_term(&v, search_config.fuzzy));
         is_empty = false;
     }
 
     // Title
+    if title_only || search_config.release_title {
+        match release.release_title() {
+            Some(v) => {
+                if !is_empty {
+                    let _ = query.and();
+                };
+                let _ = query.release(&search_term(&v, search_config.fuzzy));
+                is_empty = false;
+            }
+            None if title_only => return None,
+            None => (),
+        }
+    };
+
+    (!is_empty).then(|| query.build())
+}
+
+/// Build a MusicBrainz release group search query from the artist and title of the given release.
+///
+/// Returns `None` if the release has neither an artist nor a title.
+fn build_release_group_search_query(
+    release: &impl ReleaseLike,
+    search_config: &SearchQueryConfig,
+) -> Option<String> {
+    let mut query = MusicBrainzReleaseGroupSearchQuery::query_builder();
+    let mut is_empty = true;
+
+    if let Some(v) = release.release_artist() {
+        let _ = query.artist(&search_term(&v, search_config.fuzzy));
+        is_empty = false;
+    }
+
     if let Some(v) = release.release_title() {
         if !is_empty {
             let _ = query.and();
         };
-        let _ = query.release(v.as_ref().trim());
-    };
+        let _ = query.releasegroup(&search_term(&v, search_config.fuzzy));
+        is_empty = false;
+    }
 
-    query.build()
+    (!is_empty).then(|| query.build())
+}
+
+/// Maximum number of tracks whose MusicBrainz IDs are used in a recording search query.
+const MAX_IDENTIFIER_SEARCH_TRACKS: usize = 12;
+
+/// Maximum number of recordings that are requested when searching by MusicBrainz IDs.
+const MAX_IDENTIFIER_SEARCH_RESULTS: u8 = 100;
+
+/// Build a MusicBrainz recording search query from the MusicBrainz Recording IDs and Track IDs of
+/// the tracks of the given release, and return it together with the number of tracks whose IDs
+/// are used in the query.
+///
+/// Returns `None` unless most of the tracks have such an ID, so that a few stale IDs do not
+/// replace the search by metadata.
+fn build_identifier_search_query(release: &impl ReleaseLike) -> Option<(String, usize)> {
+    let track_count = release.release_tracks().count();
+    let identified_tracks = release
+        .release_tracks()
+        .map(|track| {
+            [
+                track.musicbrainz_recording_id().map(|id| (id, true)),
+                track.musicbrainz_track_id().map(|id| (id, false)),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|(id, _)| !id.trim().is_empty())
+            .collect::<Vec<_>>()
+        })
+        .filter(|ids| !ids.is_empty())
+        .collect::<Vec<_>>();
+    if identified_tracks.len() * 2 <= track_count {
+        return None;
+    }
+
+    let identified_tracks =
+        &identified_tracks[..identified_tracks.len().min(MAX_IDENTIFIER_SEARCH_TRACKS)];
+    let ids = identified_tracks.iter().flatten().collect::<Vec<_>>();
+
+    let mut query = MusicBrainzRecordingSearchQuery::query_builder();
+    for (index, (id, is_recording_id)) in ids.iter().enumerate() {
+        if index > 0 {
+            let _ = query.or();
+        }
+        if *is_recording_id {
+            let _ = query.rid(id.trim());
+        } else {
+            let _ = query.tid(id.trim());
+        }
+    }
+
+    Some((query.build(), identified_tracks.len()))
+}
+
+/// Deduplicate the releases of the found recordings and order them by the number of recordings
+/// that they contain (most first).
+///
+/// Only releases that contain most of the `identified_track_count` searched tracks are returned,
+/// so that the releases are consistent with the IDs of the tracks.
+fn rank_releases_by_recording_count(
+    recording_releases: impl Iterator<Item = Vec<MusicBrainzRelease>>,
+    identified_track_count: usize,
+) -> Vec<MusicBrainzRelease> {
+    let mut ranked_releases: Vec<(usize, MusicBrainzRelease)> = vec![];
+    for release in recording_releases.flatten() {
+        match ranked_releases
+            .iter_mut()
+            .find(|(_, other)| other.id == release.id)
+        {
+            Some((count, _)) => *count += 1,
+            None => ranked_releases.push((1, release)),
+        }
+    }
+    // The sort is stable, so that releases with the same count remain in search result order.
+    ranked_releases.sort_by(|(lhs, _), (rhs, _)| rhs.cmp(lhs));
+    ranked_releases
+        .into_iter()
+        .take_while(|(count, _)| count * 2 > identified_track_count)
+        .map(|(_, release)| release)
+        .collect()
+}
+
+/// Maximum number of track titles that are used in a recording search query.
+const MAX_RECORDING_SEARCH_TITLES: usize = 5;
+
+/// Build a MusicBrainz recording search query from the release artist and the track titles of
+/// the given release.
+///
+/// Returns `None` if the release has no artist or no track titles.
+fn build_recording_search_query(
+    release: &impl ReleaseLike,
+    search_config: &SearchQueryConfig,
+) -> Option<String> {
+    let artist = release.release_artist()?;
+    let titles = release
+        .media()
+        .flat_map(MediaLike::media_tracks)
+        .filter_map(|track| track.track_title().map(Cow::into_owned))
+        .take(MAX_RECORDING_SEARCH_TITLES)
+        .collect::<Vec<_>>();
+    let (first_title, other_titles) = titles.split_first()?;
+
+    let mut titles_query = MusicBrainzRecordingSearchQuery::query_builder();
+    let _ = titles_query.recording(&search_term(first_title, search_config.fuzzy));
+    for title in other_titles {
+        let _ = titles_query
+            .or()
+            .recording(&search_term(title, search_config.fuzzy));
+    }
+
+    Some(
+        MusicBrainzRecordingSearchQuery::query_builder()
+            .artist(&search_term(&artist, search_config.fuzzy))
+            .and()
+            .expr(&mut titles_query)
+            .build(),
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::release::ReleaseLike;
-    use crate::track::TrackLike;
     use musicbrainz_rs_nova::entity::release::Release as MusicBrainzRelease;
 
     const MUSICBRAINZ_RELEASE_JSON: &str = include_str!(concat!(
@@ -485,6 +1049,99 @@ mod tests {
         assert_eq!(MusicBrainzId::find("some random string"), None);
     }
 
+    #[test]
+    fn test_build_identifier_search_query() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let (query, identified_track_count) = build_identifier_search_query(&release).unwrap();
+        assert_eq!(
+            identified_track_count,
+            release
+                .release_tracks()
+                .count()
+                .min(MAX_IDENTIFIER_SEARCH_TRACKS)
+        );
+        let first_track = release.release_tracks().next().unwrap();
+        assert!(query.contains(first_track.musicbrainz_recording_id().unwrap().as_ref()));
+        assert!(query.contains(first_track.musicbrainz_track_id().unwrap().as_ref()));
+
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": "But Not for Me",
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        assert!(build_identifier_search_query(&release).is_none());
+
+        let tracks = [
+            crate::util::FakeTrack::with_title_and_recording_id("But Not for Me", "recording"),
+            crate::util::FakeTrack::with_title("Surrey With the Fringe on Top"),
+            crate::util::FakeTrack::with_title("Moonlight in Vermont"),
+        ];
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": "But Not for Me",
+            "media": [{"media_tracks": tracks}],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        assert!(build_identifier_search_query(&release).is_none());
+    }
+
+    #[test]
+    fn test_rank_releases_by_recording_count() {
+        let release = |id: &str| {
+            let mut release: MusicBrainzRelease =
+                serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+            release.id = id.to_string();
+            release
+        };
+        let recording_releases = || {
+            [
+                vec![release("compilation"), release("album")],
+                vec![release("album")],
+                vec![release("single"), release("album")],
+            ]
+            .into_iter()
+        };
+        let ranked_release_ids = |identified_track_count| {
+            rank_releases_by_recording_count(recording_releases(), identified_track_count)
+                .into_iter()
+                .map(|release| release.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked_release_ids(1),
+            vec!["album", "compilation", "single"]
+        );
+        assert_eq!(ranked_release_ids(3), vec!["album"]);
+    }
+
+    #[test]
+    fn test_select_best_search_results() {
+        let base_release: MusicBrainzRelease =
+            serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let search_result = |id: &str, title: &str| {
+            let mut release = base_release.clone();
+            release.id = id.to_string();
+            release.title = title.to_string();
+            release
+        };
+        let search_results = vec![
+            search_result("unrelated", "Something Completely Different"),
+            search_result("exact", "Ahmad Jamal at the Pershing: But Not for Me"),
+            search_result("similar", "Ahmad Jamal at the Pershing"),
+        ];
+
+        let config = Config::default();
+        assert_eq!(
+            select_best_search_results(&config, &base_release, search_results.clone(), 2),
+            vec!["exact".to_string(), "similar".to_string()]
+        );
+        assert_eq!(
+            select_best_search_results(&config, &base_release, search_results, 0),
+            Vec::<String>::new()
+        );
+    }
+
     #[test]
     fn test_releaselike_impl() {
         let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
@@ -518,4 +1175,291 @@ mod tests {
             chrono::TimeDelta::milliseconds(487_533)
         );
     }
+
+    #[test]
+    fn test_client_network_config() {
+        let mut config = Config::default();
+        assert!(MusicBrainzClient::new(&config, None).is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        config.network.ca_certificate = Some(dir.path().join("ca.pem"));
+        assert!(matches!(
+            MusicBrainzClient::new(&config, None),
+            Err(crate::Error::Network(_))
+        ));
+    }
+
+    #[test]
+    fn test_next_page_limit() {
+        assert_eq!(next_page_limit(5, 5, 0), Some(5));
+        assert_eq!(next_page_limit(5, 5, 5), None);
+        assert_eq!(next_page_limit(25, 60, 0), Some(25));
+        assert_eq!(next_page_limit(25, 60, 50), Some(10));
+        assert_eq!(next_page_limit(100, 1000, 0), Some(100));
+        assert_eq!(next_page_limit(5, 0, 0), None);
+    }
+
+    #[test]
+    fn test_build_search_query() {
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": "But Not for Me",
+            "release_artist": "The Ahmad Jamal Trio",
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        let mut search_config = SearchQueryConfig {
+            track_count: false,
+            artist: true,
+            release_title: true,
+            fuzzy: false,
+            fallbacks: vec![],
+            identifiers: true,
+        };
+
+        let query = build_search_query(&release, &search_config, SearchStrategy::Release).unwrap();
+        assert!(query.contains("Ahmad Jamal"));
+        assert!(query.contains("But Not for Me"));
+        assert!(!query.contains("tracks"));
+
+        let query =
+            build_search_query(&release, &search_config, SearchStrategy::TitleOnly).unwrap();
+        assert!(!query.contains("Ahmad"));
+        assert!(query.contains("But Not for Me"));
+
+        search_config.fuzzy = true;
+        search_config.release_title = false;
+        let query = build_search_query(&release, &search_config, SearchStrategy::Release).unwrap();
+        assert!(query.contains("Ahmad~ Jamal~"));
+        assert!(!query.contains("Not"));
+
+        search_config.artist = false;
+        assert!(build_search_query(&release, &search_config, SearchStrategy::Release).is_none());
+    }
+
+    #[test]
+    fn test_build_release_group_search_query() {
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": "But Not for Me",
+            "release_artist": "The Ahmad Jamal Trio",
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        let mut search_config = SearchQueryConfig {
+            track_count: true,
+            artist: true,
+            release_title: true,
+            fuzzy: false,
+            fallbacks: vec![],
+            identifiers: true,
+        };
+
+        let query = build_release_group_search_query(&release, &search_config).unwrap();
+        assert!(query.contains("artist:"));
+        assert!(query.contains("Ahmad Jamal"));
+        assert!(query.contains("releasegroup:"));
+        assert!(query.contains("But Not for Me"));
+
+        search_config.fuzzy = true;
+        let query = build_release_group_search_query(&release, &search_config).unwrap();
+        assert!(query.contains("Ahmad~ Jamal~"));
+
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "media": [],
+            "is_compilation": false,
+        }))
+        .unwrap();
+        assert!(build_release_group_search_query(&release, &search_config).is_none());
+    }
+
+    #[test]
+    fn test_tracklike_impl_classical_relations() {
+        fn artist_relation(
+            relation_type: &str,
+            name: &str,
+            attributes: &[&str],
+        ) -> serde_json::Value {
+            serde_json::json!({
+                "type": relation_type,
+                "type-id": "00000000-0000-0000-0000-000000000000",
+                "target-type": "artist",
+                "direction": "backward",
+                "attributes": attributes,
+                "attribute-values": {},
+                "attribute-ids": {},
+                "ended": false,
+                "source-credit": "",
+                "target-credit": "",
+                "artist": {
+                    "id": "00000000-0000-0000-0000-000000000000",
+                    "name": name,
+                    "sort-name": name,
+                    "disambiguation": "",
+                },
+            })
+        }
+
+        let mut value: serde_json::Value = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        value["media"][0]["tracks"][0]["recording"]["relations"] = serde_json::json!([
+            artist_relation("conductor", "Herbert von Karajan", &[]),
+            artist_relation("performing orchestra", "Berliner Philharmoniker", &[]),
+            artist_relation("vocal", "Wiener Singverein", &["choir vocals"]),
+            artist_relation("chorus master", "Helmuth Froschauer", &[]),
+            artist_relation("instrument", "Anne-Sophie Mutter", &["solo", "violin"]),
+            artist_relation("vocal", "Jessye Norman", &[]),
+        ]);
+        let release: MusicBrainzRelease = serde_json::from_value(value).unwrap();
+        let track = release.release_tracks().next().unwrap();
+
+        assert_eq!(
+            track.conductor().collect::<Vec<_>>(),
+            vec!["Herbert von Karajan"]
+        );
+        let performers = track
+            .performers()
+            .unwrap()
+            .into_iter()
+            .map(|person| {
+                (
+                    person.involvement.into_owned(),
+                    person.involvee.into_owned(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            performers,
+            vec![
+                (
+                    "orchestra".to_string(),
+                    "Berliner Philharmoniker".to_string()
+                ),
+                ("choir vocals".to_string(), "Wiener Singverein".to_string()),
+                (
+                    "chorus master".to_string(),
+                    "Helmuth Froschauer".to_string()
+                ),
+                ("solo violin".to_string(), "Anne-Sophie Mutter".to_string()),
+                ("vocals".to_string(), "Jessye Norman".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tracklike_impl_work_hierarchy() {
+        let mut value: serde_json::Value = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        value["media"][0]["tracks"][0]["recording"]["relations"] = serde_json::json!([{
+            "type": "performance",
+            "type-id": "a3005666-a872-32c3-ad06-98af558e99b0",
+            "target-type": "work",
+            "direction": "forward",
+            "attributes": [],
+            "attribute-values": {},
+            "attribute-ids": {},
+            "ended": false,
+            "source-credit": "",
+            "target-credit": "",
+            "work": {
+                "id": "00000000-0000-0000-0000-000000000001",
+                "title": "Symphony No. 5 in C minor, op. 67: II. Andante con moto",
+                "disambiguation": "",
+                "attributes": [],
+                "iswcs": [],
+                "languages": [],
+                "relations": [{
+                    "type": "parts",
+                    "type-id": "ca8d3642-ce5f-49f8-91f2-125d72524e6a",
+                    "target-type": "work",
+                    "direction": "backward",
+                    "ordering-key": 2,
+                    "attributes": [],
+                    "attribute-values": {},
+                    "attribute-ids": {},
+                    "ended": false,
+                    "source-credit": "",
+                    "target-credit": "",
+                    "work": {
+                        "id": "00000000-0000-0000-0000-000000000002",
+                        "title": "Symphony No. 5 in C minor, op. 67",
+                        "disambiguation": "",
+                        "attributes": [],
+                        "iswcs": [],
+                        "languages": [],
+                    },
+                }],
+            },
+        }]);
+        let release: MusicBrainzRelease = serde_json::from_value(value).unwrap();
+        let mut tracks = release.release_tracks();
+
+        let track = tracks.next().unwrap();
+        assert_eq!(
+            track.work_title().as_deref(),
+            Some("Symphony No. 5 in C minor, op. 67: II. Andante con moto")
+        );
+        assert_eq!(
+            track.parent_work_title().as_deref(),
+            Some("Symphony No. 5 in C minor, op. 67")
+        );
+        assert_eq!(track.movement().as_deref(), Some("II. Andante con moto"));
+        assert_eq!(track.movement_number().as_deref(), Some("2"));
+
+        let track = tracks.next().unwrap();
+        assert_eq!(track.parent_work_title().as_deref(), Some("Oklahoma!"));
+        assert_eq!(
+            track.movement().as_deref(),
+            Some("The Surrey With the Fringe on Top")
+        );
+        assert_eq!(track.movement_number().as_deref(), Some("3"));
+
+        let track = tracks.next().unwrap();
+        assert!(track.parent_work_title().is_none());
+        assert!(track.movement().is_none());
+        assert!(track.movement_number().is_none());
+    }
+
+    #[test]
+    fn test_tracklike_impl_multiple_artists() {
+        let mut value: serde_json::Value = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        value["media"][0]["tracks"][0]["artist-credit"] = serde_json::json!([
+            {
+                "name": "Miles Davis",
+                "joinphrase": " & ",
+                "artist": {
+                    "id": "561d854a-6a28-4aa7-8c99-323e6ce46c2a",
+                    "name": "Miles Davis",
+                    "sort-name": "Davis, Miles",
+                    "disambiguation": "",
+                },
+            },
+            {
+                "name": "John Coltrane",
+                "joinphrase": "",
+                "artist": {
+                    "id": "b625448e-bf4a-41c3-a421-72ad46cdb831",
+                    "name": "John Coltrane",
+                    "sort-name": "Coltrane, John",
+                    "disambiguation": "",
+                },
+            },
+        ]);
+        let release: MusicBrainzRelease = serde_json::from_value(value).unwrap();
+        let track = release.release_tracks().next().unwrap();
+
+        assert_eq!(
+            track.track_artist().as_deref(),
+            Some("Miles Davis & John Coltrane")
+        );
+        assert_eq!(
+            track.track_artist_sort_order().as_deref(),
+            Some("Davis, Miles & Coltrane, John")
+        );
+        assert_eq!(
+            track.musicbrainz_artist_id().collect::<Vec<_>>(),
+            vec![
+                "561d854a-6a28-4aa7-8c99-323e6ce46c2a",
+                "b625448e-bf4a-41c3-a421-72ad46cdb831"
+            ]
+        );
+    }
 }
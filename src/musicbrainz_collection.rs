@@ -0,0 +1,237 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Adding imported releases to a [MusicBrainz collection][mb-collection].
+//!
+//! After a release has been imported, it can be added to a release collection of the user, so
+//! that the collection mirrors the local library. This requires an OAuth access token with the
+//! `collection` scope.
+//!
+//! Access tokens expire after an hour, so a refresh token (together with the client ID and secret
+//! of the OAuth application) should be configured. It is used to request a new access token when
+//! the current one is missing or has expired.
+//!
+//! [mb-collection]: https://musicbrainz.org/doc/Collections
+
+use crate::config::{MusicBrainzCollectionConfig, NetworkConfig};
+use crate::network;
+use crate::{PKG_NAME, PKG_VERSION};
+use serde::Deserialize;
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Encountered when adding a release to a MusicBrainz collection fails.
+#[derive(Error, Debug)]
+pub enum MusicBrainzCollectionError {
+    /// The HTTP request failed.
+    #[error("MusicBrainz collection request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The response of the token endpoint could not be parsed.
+    #[error("Invalid MusicBrainz OAuth response: {0}")]
+    InvalidResponse(#[from] serde_json::Error),
+    /// There is no valid access token and it cannot be refreshed.
+    #[error("MusicBrainz access token has expired and no refresh token is configured")]
+    TokenExpired,
+}
+
+/// Build the URL for adding a release to the collection.
+fn collection_release_url(server_url: &str, collection_id: &str, release_id: &str) -> String {
+    format!(
+        "{server_url}/ws/2/collection/{collection_id}/releases/{release_id}?client={PKG_NAME}-{PKG_VERSION}",
+        server_url = server_url.trim_end_matches('/'),
+        collection_id = collection_id.trim(),
+        release_id = release_id.trim(),
+    )
+}
+
+/// Response of the OAuth token endpoint.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    /// The new access token.
+    access_token: String,
+}
+
+/// Credentials for requesting a new access token.
+#[derive(Debug)]
+struct RefreshCredentials {
+    /// OAuth refresh token of the user.
+    refresh_token: String,
+    /// Client ID of the OAuth application.
+    client_id: String,
+    /// Client secret of the OAuth application.
+    client_secret: String,
+}
+
+/// Client for adding releases to a MusicBrainz collection.
+#[derive(Debug)]
+pub struct MusicBrainzCollectionClient {
+    /// The HTTP client.
+    client: reqwest::Client,
+    /// Base URL of the MusicBrainz server.
+    server_url: String,
+    /// MusicBrainz ID of the release collection.
+    collection_id: String,
+    /// Current OAuth access token of the user (if any).
+    access_token: Mutex<Option<String>>,
+    /// Credentials for refreshing the access token (if configured).
+    refresh_credentials: Option<RefreshCredentials>,
+}
+
+impl MusicBrainzCollectionClient {
+    /// Create a client from the configuration.
+    ///
+    /// Returns `None` if adding releases is disabled or if the collection or token is missing.
+    pub fn from_config(
+        config: &MusicBrainzCollectionConfig,
+        network: &NetworkConfig,
+    ) -> Option<Self> {
+        if !config.add_releases {
+            return None;
+        }
+
+        let refresh_credentials = match (
+            &config.refresh_token,
+            &config.client_id,
+            &config.client_secret,
+        ) {
+            (Some(refresh_token), Some(client_id), Some(client_secret)) => {
+                Some(RefreshCredentials {
+                    refresh_token: refresh_token.clone(),
+                    client_id: client_id.clone(),
+                    client_secret: client_secret.clone(),
+                })
+            }
+            (None, _, _) => None,
+            _ => {
+                log::warn!(
+                    "MusicBrainz refresh token is configured without the client ID and secret"
+                );
+                None
+            }
+        };
+        let Some(collection_id) = config
+            .collection_id
+            .as_ref()
+            .filter(|_| config.access_token.is_some() || refresh_credentials.is_some())
+        else {
+            log::warn!(
+                "Adding releases to a MusicBrainz collection is enabled, but the collection ID or access token is not configured"
+            );
+            return None;
+        };
+        if refresh_credentials.is_none() {
+            log::info!("No MusicBrainz refresh token is configured, the access token will expire after an hour");
+        }
+
+        Some(Self {
+            client: network::http_client(network)
+                .inspect_err(|err| {
+                    log::warn!("Failed to create HTTP client for MusicBrainz collections: {err}");
+                })
+                .ok()?,
+            server_url: config.server_url.clone(),
+            collection_id: collection_id.clone(),
+            access_token: Mutex::new(config.access_token.clone()),
+            refresh_credentials,
+        })
+    }
+
+    /// Request a new access token with the refresh token and store it.
+    async fn refresh_access_token(&self) -> Result<String, MusicBrainzCollectionError> {
+        let Some(credentials) = &self.refresh_credentials else {
+            return Err(MusicBrainzCollectionError::TokenExpired);
+        };
+
+        log::debug!("Refreshing MusicBrainz access token");
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", credentials.refresh_token.as_str()),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+        ];
+        let body = self
+            .client
+            .post(format!(
+                "{}/oauth2/token",
+                self.server_url.trim_end_matches('/')
+            ))
+            .form(&params)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let response: TokenResponse = serde_json::from_str(&body)?;
+        *self.access_token.lock().unwrap() = Some(response.access_token.clone());
+        Ok(response.access_token)
+    }
+
+    /// Send the request for adding the release with the access token.
+    async fn put_release(
+        &self,
+        release_id: &str,
+        access_token: &str,
+    ) -> Result<reqwest::Response, MusicBrainzCollectionError> {
+        Ok(self
+            .client
+            .put(collection_release_url(
+                &self.server_url,
+                &self.collection_id,
+                release_id,
+            ))
+            .bearer_auth(access_token)
+            .header(reqwest::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?)
+    }
+
+    /// Add the release with the given MusicBrainz ID to the collection.
+    ///
+    /// Adding a release that is already part of the collection is not an error. If the access
+    /// token has expired, it is refreshed (if a refresh token is configured) and the request is
+    /// retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the API returns an error status (e.g., because
+    /// the access token has expired and cannot be refreshed).
+    pub async fn add_release(&self, release_id: &str) -> Result<(), MusicBrainzCollectionError> {
+        let access_token = self.access_token.lock().unwrap().clone();
+        let access_token = match access_token {
+            Some(access_token) => access_token,
+            None => self.refresh_access_token().await?,
+        };
+        let mut response = self.put_release(release_id, &access_token).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && self.refresh_credentials.is_some()
+        {
+            let access_token = self.refresh_access_token().await?;
+            response = self.put_release(release_id, &access_token).await?;
+        }
+        let _response = response.error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_release_url() {
+        let url = collection_release_url(
+            "https://musicbrainz.org/",
+            " f4784850-3844-11e0-9e42-0800200c9a66 ",
+            "0d3fc2b5-8b3e-4d4b-b0f5-7e5b0d8a7c7d",
+        );
+        assert_eq!(
+            url,
+            format!("https://musicbrainz.org/ws/2/collection/f4784850-3844-11e0-9e42-0800200c9a66/releases/0d3fc2b5-8b3e-4d4b-b0f5-7e5b0d8a7c7d?client=helicon-{PKG_VERSION}")
+        );
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! HTTP clients that honor the proxy and TLS settings of the network configuration.
+
+use crate::config::NetworkConfig;
+use crate::USER_AGENT;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Error that occurs when creating an HTTP client.
+#[derive(Error, Debug)]
+pub enum NetworkError {
+    /// The CA certificate file could not be read.
+    #[error("Failed to read CA certificate {path}: {source}")]
+    ReadCertificate {
+        /// Path of the certificate file.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// The proxy URL or a certificate is invalid, or the client could not be built.
+    #[error("HTTP client error: {0}")]
+    Client(#[from] reqwest::Error),
+}
+
+/// Create an HTTP client with the configured proxy and CA certificate.
+///
+/// # Errors
+///
+/// Returns an error if the proxy URL is invalid or if the CA certificate cannot be loaded.
+pub fn http_client(config: &NetworkConfig) -> Result<reqwest::Client, NetworkError> {
+    let mut builder = reqwest::Client::builder().user_agent(USER_AGENT);
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    if let Some(path) = &config.ca_certificate {
+        let pem = std::fs::read(path).map_err(|source| NetworkError::ReadCertificate {
+            path: path.clone(),
+            source,
+        })?;
+        for certificate in reqwest::Certificate::from_pem_bundle(&pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn test_http_client() {
+        let mut config = Config::default().network;
+        assert!(http_client(&config).is_ok());
+
+        config.proxy = Some("socks5h://127.0.0.1:1080".to_string());
+        assert!(http_client(&config).is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        config.ca_certificate = Some(path);
+        assert!(matches!(
+            http_client(&config),
+            Err(NetworkError::ReadCertificate { .. })
+        ));
+    }
+}
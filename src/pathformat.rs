@@ -9,36 +9,170 @@
 //! Path formatting and templating.
 #![allow(dead_code)]
 
-use crate::config::PathTemplateConfig;
+use crate::config::{NumberFormatConfig, PathSanitizationConfig, PathTemplateConfig};
 use crate::media::MediaLike;
 use crate::release::ReleaseLike;
+use crate::tag::TagKey;
 use crate::track::TrackLike;
-use handlebars::{handlebars_helper, Handlebars, RenderError, Template, TemplateError};
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, RenderContext, RenderError,
+    RenderErrorReason, ScopedJson, Template, TemplateError,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 /// Characters that are forbidden in paths on Microsoft Windows (in addition to control characters).
-#[cfg(target_os = "windows")]
-const ILLEGAL_PATH_CHARS: &str = r#"\/:*?"<>|"#;
+const WINDOWS_ILLEGAL_PATH_CHARS: &str = r#"\/:*?"<>|"#;
 
 /// Characters that are forbidden in paths on Unices (in addition to control characters).
-#[cfg(not(target_os = "windows"))]
-const ILLEGAL_PATH_CHARS: &str = "/";
-
-/// Strips control characters and escapes forbidden characters.
-fn escape_path_chars(data: &str) -> String {
-    data.chars()
-        .filter(|c| !c.is_control())
-        .map(|c| if c.is_whitespace() { ' ' } else { c })
-        .map(|c| {
-            if ILLEGAL_PATH_CHARS.contains(c) {
-                '_'
-            } else {
-                c
+const UNIX_ILLEGAL_PATH_CHARS: &str = "/";
+
+/// File names that are reserved on Microsoft Windows (regardless of the extension).
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Returns `true` if the paths need to be compatible with Microsoft Windows.
+fn is_windows_compatible(options: &PathSanitizationConfig) -> bool {
+    options.windows_compatible || cfg!(target_os = "windows")
+}
+
+/// Strips control characters and replaces forbidden characters.
+fn escape_path_chars(data: &str, options: &PathSanitizationConfig) -> String {
+    let data = if options.ascii_only {
+        Cow::from(deunicode::deunicode(data))
+    } else {
+        Cow::from(data)
+    };
+    let illegal_chars = if is_windows_compatible(options) {
+        WINDOWS_ILLEGAL_PATH_CHARS
+    } else {
+        UNIX_ILLEGAL_PATH_CHARS
+    };
+
+    let mut escaped = String::with_capacity(data.len());
+    for c in data.chars().filter(|c| !c.is_control()) {
+        if illegal_chars.contains(c) {
+            escaped.push_str(&options.replacement);
+        } else if c.is_whitespace() {
+            escaped.push(' ');
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Truncate the string to at most `max_len` bytes without splitting a character.
+fn truncate_at_char_boundary(data: &str, max_len: usize) -> &str {
+    if data.len() <= max_len {
+        return data;
+    }
+
+    let end = (0..=max_len)
+        .rev()
+        .find(|&index| data.is_char_boundary(index))
+        .unwrap_or(0);
+    &data[..end]
+}
+
+/// Sanitize a single path component (i.e., a directory or file name).
+fn sanitize_component(component: &str, options: &PathSanitizationConfig) -> String {
+    let windows_compatible = is_windows_compatible(options);
+    let trim = |component: &str| {
+        if windows_compatible {
+            // Windows silently strips trailing dots and spaces from file names.
+            component.trim_end_matches(['.', ' ']).to_string()
+        } else {
+            component.to_string()
+        }
+    };
+
+    let mut component = trim(component);
+    if windows_compatible {
+        let stem = component.split('.').next().unwrap_or_default();
+        if WINDOWS_RESERVED_NAMES
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(stem.trim_end()))
+        {
+            component.insert_str(0, &options.replacement);
+        }
+    }
+    if options.max_component_length > 0 {
+        component = trim(truncate_at_char_boundary(
+            &component,
+            options.max_component_length,
+        ));
+    }
+    component
+}
+
+/// Returns the length of the path that consists of the components in bytes.
+fn path_length(components: &[String]) -> usize {
+    components.iter().map(String::len).sum::<usize>() + components.len().saturating_sub(1)
+}
+
+/// Sanitize all components of a formatted path and append the suffix (i.e., the file extension).
+///
+/// The file name is shortened so that it still fits into the maximum component length after the
+/// suffix has been appended. If the path exceeds the maximum path length, the file name is
+/// shortened first, then the directory names (starting with the innermost one). Returns an error if
+/// the path cannot be shortened enough.
+fn sanitize_path(
+    path: &str,
+    options: &PathSanitizationConfig,
+    suffix: &str,
+) -> Result<String, RenderError> {
+    let mut components = path
+        .split('/')
+        .map(|component| sanitize_component(component, options))
+        .collect::<Vec<_>>();
+
+    // Components must not become empty by shortening them, because that would remove a directory
+    // level (or the file name).
+    let mut truncated_to_empty = false;
+    let mut truncate = |component: &mut String, max_len: usize| {
+        let truncated = truncate_at_char_boundary(component, max_len).trim_end();
+        truncated_to_empty |= truncated.is_empty() && !component.is_empty();
+        *component = truncated.to_string();
+    };
+
+    if options.max_component_length > 0 {
+        if let Some(file_name) = components.last_mut() {
+            truncate(
+                file_name,
+                options.max_component_length.saturating_sub(suffix.len()),
+            );
+        }
+    }
+
+    if options.max_path_length > 0 {
+        for index in (0..components.len()).rev() {
+            let path_len = path_length(&components);
+            if path_len <= options.max_path_length {
+                break;
             }
-        })
-        .collect::<String>()
+
+            let excess = path_len - options.max_path_length;
+            let max_len = components[index].len().saturating_sub(excess).max(1);
+            truncate(&mut components[index], max_len);
+        }
+    }
+
+    let path = components.join("/");
+    if truncated_to_empty
+        || (options.max_path_length > 0 && path_length(&components) > options.max_path_length)
+    {
+        return Err(RenderErrorReason::Other(format!(
+            "Path {path:?} cannot be shortened to the maximum path and component length"
+        ))
+        .into());
+    }
+
+    Ok(path + suffix)
 }
 
 /// Return the width of a base-10 `usize` integer as usize (if possible).
@@ -75,7 +209,7 @@ impl TryFrom<PathTemplateConfig> for PathTemplate {
 
     fn try_from(config: PathTemplateConfig) -> Result<Self, Self::Error> {
         let album_format = Template::compile(&config.album_format)?;
-        let compilation_format = Template::compile(&config.album_format)?;
+        let compilation_format = Template::compile(&config.compilation_format)?;
         Ok(Self {
             config,
             album_format,
@@ -102,26 +236,87 @@ handlebars_helper!(helper_zfill: |value: Value, { width: usize = 0 }| {
     }
 });
 
+/// Helper that returns the value of a tag by its name (e.g., `{{tag "albumartistsort"}}`).
+///
+/// The name is parsed like in the configuration and the `tags` command, so Picard tag names are
+/// accepted as well. The value is empty if the tag is not set.
+#[derive(Debug, Clone, Copy)]
+struct TagHelper;
+
+impl HelperDef for TagHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'rc>, RenderError> {
+        let name = h
+            .param(0)
+            .and_then(|param| param.value().as_str())
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("tag", 0))?;
+        let key = name.parse::<TagKey>().map_err(RenderErrorReason::Other)?;
+        let value = ctx
+            .data()
+            .get("tags")
+            .and_then(|tags| tags.get(key.to_string()))
+            .cloned()
+            .unwrap_or(Value::Null);
+        Ok(ScopedJson::Derived(value))
+    }
+}
+
 /// Formatter for paths.
 #[derive(Debug, Clone)]
-pub struct PathFormatter(Handlebars<'static>);
+pub struct PathFormatter {
+    /// The template engine.
+    handlebars: Handlebars<'static>,
+    /// Options for sanitizing the formatted paths.
+    sanitize: PathSanitizationConfig,
+}
 
 impl From<&PathTemplate> for PathFormatter {
     fn from(template: &PathTemplate) -> Self {
+        let sanitize = template.config.sanitize.clone();
+        let escape_options = sanitize.clone();
         let mut handlebars = Handlebars::new();
         handlebars.set_strict_mode(true);
-        handlebars.register_escape_fn(escape_path_chars);
+        handlebars.register_escape_fn(move |data| escape_path_chars(data, &escape_options));
         handlebars.register_helper("zfill", Box::new(helper_zfill));
+        handlebars.register_helper("tag", Box::new(TagHelper));
         handlebars.register_template("album", template.album_format.clone());
         handlebars.register_template("compilation", template.compilation_format.clone());
-        Self(handlebars)
+        Self {
+            handlebars,
+            sanitize,
+        }
     }
 }
 
 impl PathFormatter {
     /// Format a path with the given values.
-    pub fn format(&self, values: &PathFormatterValues<'_>) -> Result<String, RenderError> {
-        self.0.render("album", values)
+    ///
+    /// The compilation format is used if the release is a compilation of various artists. The file
+    /// extension (if any) is appended to the sanitized path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template cannot be rendered or if the path cannot be shortened to
+    /// the maximum length.
+    pub fn format(
+        &self,
+        values: &PathFormatterValues<'_>,
+        file_extension: Option<&str>,
+    ) -> Result<String, RenderError> {
+        let template_name = if values.compilation == Some(true) {
+            "compilation"
+        } else {
+            "album"
+        };
+        let suffix = file_extension.map_or_else(String::new, |ext| format!(".{ext}"));
+        self.handlebars
+            .render(template_name, values)
+            .and_then(|path| sanitize_path(&path, &self.sanitize, &suffix))
     }
 }
 
@@ -136,6 +331,10 @@ pub struct PathFormatterValues<'a> {
     pub track_number: Option<Cow<'a, str>>,
     /// The track index (relative to the disc).
     pub track_index: Option<usize>,
+    /// MusicBrainz Recording ID.
+    pub musicbrainz_recording_id: Option<Cow<'a, str>>,
+    /// MusicBrainz Track ID.
+    pub musicbrainz_track_id: Option<Cow<'a, str>>,
     /// The number of tracks on the disc.
     pub track_count: Option<usize>,
     /// The width (i.e., decimal digits count) of the number of tracks on the disc.
@@ -148,12 +347,48 @@ pub struct PathFormatterValues<'a> {
     pub date: Option<Cow<'a, str>>,
     /// The album's release year.
     pub year: Option<Cow<'a, str>>,
+    /// The original release date (i.e., the date of the first release in the release group).
+    pub original_date: Option<Cow<'a, str>>,
+    /// The original release year.
+    pub original_year: Option<Cow<'a, str>>,
+    /// The record label of the release.
+    pub record_label: Option<Cow<'a, str>>,
+    /// The catalog number of the release.
+    pub catalog_number: Option<Cow<'a, str>>,
+    /// The country in which the album was released.
+    pub release_country: Option<Cow<'a, str>>,
+    /// The release type (e.g., `album`).
+    pub release_type: Option<Cow<'a, str>>,
+    /// Whether the release is a compilation of various artists.
+    pub compilation: Option<bool>,
+    /// MusicBrainz Release ID.
+    pub musicbrainz_release_id: Option<Cow<'a, str>>,
+    /// MusicBrainz Release Group ID.
+    pub musicbrainz_release_group_id: Option<Cow<'a, str>>,
+    /// MusicBrainz Release Artist ID.
+    pub musicbrainz_release_artist_id: Option<Cow<'a, str>>,
     /// The disc number.
     pub disc_number: Option<u32>,
+    /// The format of the disc (e.g., `CD`).
+    pub media: Option<Cow<'a, str>>,
+    /// The title of the disc.
+    pub media_title: Option<Cow<'a, str>>,
     /// The total number of discs that are part of this release.
     pub disc_count: Option<usize>,
     /// The width (i.e., decimal digits count) of the number of discs of this release.
     pub disc_count_width: Option<usize>,
+    /// Short name of the audio codec (e.g., `flac`).
+    pub codec: Option<Cow<'a, str>>,
+    /// Average bitrate in kbit/s.
+    pub bitrate: Option<u32>,
+    /// Sample rate in Hz.
+    pub sample_rate: Option<u32>,
+    /// Number of bits per sample.
+    pub bit_depth: Option<u32>,
+    /// Number of audio channels.
+    pub channels: Option<u32>,
+    /// The tag values of the file, by tag key name (for the `tag` helper).
+    pub tags: BTreeMap<String, Cow<'a, str>>,
 }
 
 impl<'a> PathFormatterValues<'a> {
@@ -163,25 +398,80 @@ impl<'a> PathFormatterValues<'a> {
         self.album_artist = release.release_artist();
         self.date = release.release_date();
         self.year = release.release_year();
+        self.original_date = release.original_release_date();
+        self.original_year = release.original_release_year();
+        self.record_label = release.record_label();
+        self.catalog_number = release.catalog_number();
+        self.release_country = release.release_country();
+        self.release_type = release.release_type();
+        self.compilation = Some(release.is_compilation());
+        self.musicbrainz_release_id = release.musicbrainz_release_id();
+        self.musicbrainz_release_group_id = release.musicbrainz_release_group_id();
+        self.musicbrainz_release_artist_id = release.musicbrainz_release_artist_id();
         self.disc_count = Some(release.media().count());
         self.disc_count_width = self.disc_count.and_then(usize_width);
         self
     }
 
+    /// Use the given album artist if the release is a compilation of various artists.
+    pub fn with_various_artists(mut self, various_artists: &'a str) -> Self {
+        if self.compilation == Some(true) {
+            self.album_artist = Some(Cow::from(various_artists));
+        }
+        self
+    }
+
     /// Assign fields from a [`MediaLike`] object.
     pub fn with_media(mut self, media: &'a impl MediaLike) -> Self {
         self.disc_number = media.disc_number();
+        self.media = media.media_format();
+        self.media_title = media.media_title();
         self.track_count = Some(media.media_tracks().count());
         self.track_count_width = self.track_count.and_then(usize_width);
         self
     }
 
+    /// Format the track and disc numbers according to the number format configuration.
+    ///
+    /// The disc number and track index are padded by raising the widths that are used by the
+    /// `zfill` helper. The total is never included, because it would contain a path separator.
+    pub fn with_number_format(mut self, number_format: &NumberFormatConfig) -> Self {
+        self.track_number = self
+            .track_number
+            .map(|number| Cow::from(number_format.pad(&number)));
+        for width in [&mut self.disc_count_width, &mut self.track_count_width] {
+            *width = Some(width.unwrap_or_default().max(number_format.pad_width));
+        }
+        self
+    }
+
+    /// Assign the tag values of a file, so that they can be used with the `tag` helper.
+    ///
+    /// Tracks that are not backed by a file (e.g., MusicBrainz tracks) don't have tag values.
+    pub fn with_tags(mut self, track: &'a impl TrackLike) -> Self {
+        self.tags.extend(
+            TagKey::ALL
+                .iter()
+                .filter_map(|key| track.tag_value(key).map(|value| (key.to_string(), value))),
+        );
+        self
+    }
+
     /// Assign fields from a [`TrackLike`] object.
     pub fn with_track(mut self, index: usize, track: &'a impl TrackLike) -> Self {
         self.track_title = track.track_title();
         self.track_artist = track.track_artist();
         self.track_number = track.track_number();
         self.track_index = index.into();
+        self.musicbrainz_recording_id = track.musicbrainz_recording_id();
+        self.musicbrainz_track_id = track.musicbrainz_track_id();
+        // Technical metadata is only available for files, so existing values are not overwritten
+        // when assigning the fields of a release track afterwards.
+        self.codec = track.codec().or(self.codec.take());
+        self.bitrate = track.bitrate().or(self.bitrate);
+        self.sample_rate = track.sample_rate().or(self.sample_rate);
+        self.bit_depth = track.bit_depth().or(self.bit_depth);
+        self.channels = track.channel_count().or(self.channels);
         self
     }
 }
@@ -189,8 +479,11 @@ impl<'a> PathFormatterValues<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Config;
+    use crate::analyzer::{CompoundAnalyzerResult, TechnicalMetadata};
+    use crate::tag::memory::MemoryTag;
+    use crate::{Config, TaggedFile};
     use serde_json::json;
+    use std::path::PathBuf;
 
     use musicbrainz_rs_nova::entity::release::Release as MusicBrainzRelease;
 
@@ -263,10 +556,195 @@ mod tests {
             .with_media(media)
             .with_track(1, track);
 
-        let output = formatter.format(&values).unwrap();
+        let output = formatter.format(&values, None).unwrap();
         assert_eq!(
             output,
             "The Ahmad Jamal Trio/1958 - Ahmad Jamal at the Pershing: But Not for Me/1-1 - But Not for Me"
         );
     }
+
+    #[test]
+    fn test_album_path_with_number_format() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let media = release.media().next().unwrap();
+        let track = media.media_tracks().next().unwrap();
+
+        let mut config = Config::default();
+        config.tags.number_format.pad_width = 2;
+        let formatter = config.paths.format.formatter();
+        let values = PathFormatterValues::default()
+            .with_release(&release)
+            .with_media(media)
+            .with_track(1, track)
+            .with_number_format(&config.tags.number_format);
+
+        let output = formatter.format(&values, None).unwrap();
+        assert_eq!(
+            output,
+            "The Ahmad Jamal Trio/1958 - Ahmad Jamal at the Pershing: But Not for Me/01-01 - But Not for Me"
+        );
+    }
+
+    #[test]
+    fn test_compilation_path() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let media = release.media().next().unwrap();
+        let track = media.media_tracks().next().unwrap();
+
+        let config = Config::default();
+        let formatter = config.paths.format.formatter();
+        let mut values = PathFormatterValues::default()
+            .with_release(&release)
+            .with_media(media)
+            .with_track(1, track);
+        values.compilation = Some(true);
+        let values = values.with_various_artists("Verschiedene Interpreten");
+
+        let output = formatter.format(&values, None).unwrap();
+        assert_eq!(
+            output,
+            "Verschiedene Interpreten/1958 - Ahmad Jamal at the Pershing: But Not for Me/1-1 - The Ahmad Jamal Trio - But Not for Me"
+        );
+    }
+
+    #[test]
+    fn test_release_values() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let media = release.media().next().unwrap();
+        let track = media.media_tracks().next().unwrap();
+
+        let values = PathFormatterValues::default()
+            .with_release(&release)
+            .with_media(media)
+            .with_track(1, track);
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        handlebars.register_escape_fn(|data| {
+            escape_path_chars(data, &Config::default().paths.format.config.sanitize)
+        });
+        handlebars.register_template(
+            "test",
+            Template::compile(
+                "{{record_label}}/[{{catalog_number}}] {{album_title}} ({{original_year}}) [{{media}}]",
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            handlebars.render("test", &values).unwrap(),
+            "Argo/[LP-628] Ahmad Jamal at the Pershing: But Not for Me (1958) [12\" Vinyl]"
+        );
+        assert_eq!(values.compilation, Some(false));
+    }
+
+    #[test]
+    fn test_technical_metadata_values() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let track = release
+            .media()
+            .next()
+            .unwrap()
+            .media_tracks()
+            .next()
+            .unwrap();
+        let tagged_file = TaggedFile::with_tags(PathBuf::from("01 - But Not for Me.flac"), vec![])
+            .with_analysis_results(Some(CompoundAnalyzerResult {
+                technical_metadata: Some(TechnicalMetadata {
+                    codec: Some("flac".to_string()),
+                    bitrate: Some(1012),
+                    sample_rate: Some(96000),
+                    bit_depth: Some(24),
+                    channels: Some(2),
+                }),
+                ..Default::default()
+            }));
+
+        let values = PathFormatterValues::default()
+            .with_track(1, &tagged_file)
+            .with_track(1, track);
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        handlebars.register_template(
+            "test",
+            Template::compile("{{codec}} {{bit_depth}}-{{sample_rate}}").unwrap(),
+        );
+        assert_eq!(handlebars.render("test", &values).unwrap(), "flac 24-96000");
+    }
+
+    #[test]
+    fn test_tag_helper() {
+        let tagged_file = TaggedFile::with_tags(
+            PathBuf::from("01.flac"),
+            vec![Box::new(
+                MemoryTag::new()
+                    .with_value(TagKey::AlbumArtistSortOrder, Some("Jamal, Ahmad"))
+                    .with_value(TagKey::TrackTitle, Some("But Not for Me")),
+            )],
+        );
+        let values = PathFormatterValues::default().with_tags(&tagged_file);
+
+        let mut config = Config::default().paths.format.config;
+        config.album_format =
+            r#"{{tag "albumartistsort"}}/{{#if (tag "label")}}{{tag "label"}}/{{/if}}{{tag "Title"}}"#
+                .to_string();
+        let formatter = PathTemplate::try_from(config.clone()).unwrap().formatter();
+        assert_eq!(
+            formatter.format(&values, None).unwrap(),
+            "Jamal, Ahmad/But Not for Me"
+        );
+
+        config.album_format = r#"{{tag "unknown"}}"#.to_string();
+        let formatter = PathTemplate::try_from(config).unwrap().formatter();
+        assert!(formatter.format(&values, None).is_err());
+    }
+
+    #[test]
+    fn test_escape_path_chars() {
+        let mut options = Config::default().paths.format.config.sanitize;
+        assert_eq!(escape_path_chars("AC/DC", &options), "AC_DC");
+        options.replacement = String::new();
+        assert_eq!(escape_path_chars("AC/DC\n", &options), "ACDC");
+
+        options.windows_compatible = true;
+        options.replacement = "-".to_string();
+        assert_eq!(escape_path_chars("Live: Tokyo?", &options), "Live- Tokyo-");
+
+        options.ascii_only = true;
+        assert_eq!(escape_path_chars("Björk", &options), "Bjork");
+    }
+
+    #[test]
+    fn test_sanitize_path() {
+        let mut options = Config::default().paths.format.config.sanitize;
+        options.windows_compatible = true;
+        assert_eq!(
+            sanitize_path("Some Artist/Vol. 2.../con/01 - Aux.txt", &options, "").unwrap(),
+            "Some Artist/Vol. 2/_con/01 - Aux.txt"
+        );
+
+        options.max_component_length = 8;
+        assert_eq!(
+            sanitize_path("Ahmad Jamal/But Not for Me", &options, "").unwrap(),
+            "Ahmad Ja/But Not"
+        );
+        assert_eq!(
+            sanitize_path("Ahmad Jamal/But Not for Me", &options, ".flac").unwrap(),
+            "Ahmad Ja/But.flac"
+        );
+        assert!(sanitize_path("Ahmad Jamal/But Not for Me", &options, ".aiff.bak").is_err());
+
+        options.max_component_length = 0;
+        options.max_path_length = 20;
+        assert_eq!(
+            sanitize_path("Ahmad Jamal/But Not for Me", &options, ".flac").unwrap(),
+            "Ahmad Jamal/But Not.flac"
+        );
+        options.max_path_length = 10;
+        assert_eq!(
+            sanitize_path("Ahmad Jamal/But Not for Me", &options, "").unwrap(),
+            "Ahmad Ja/B"
+        );
+        options.max_path_length = 2;
+        assert!(sanitize_path("Ahmad Jamal/But Not for Me", &options, "").is_err());
+        assert_eq!(truncate_at_char_boundary("Björk", 3), "Bj");
+    }
 }
@@ -0,0 +1,326 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Persistent progress of import runs.
+//!
+//! The status of each album directory is recorded while importing, so that an import run that
+//! was interrupted (e.g., because the user quit) can be resumed where it left off. Albums that were
+//! matched, but not imported yet, are imported with the same release when resuming. The progress
+//! of an import path is cleared once an import run of that path finishes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use xdg::BaseDirectories;
+
+/// File name of the progress store in the state directory.
+const PROGRESS_FILE_NAME: &str = "import-progress.jsonl";
+
+/// Status of an album directory in an import run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStatus {
+    /// The album was scanned, but no release has been selected yet.
+    Pending,
+    /// A release was selected, but the files have not been imported yet.
+    Matched,
+    /// The album was imported.
+    Done,
+    /// The album was skipped.
+    Skipped,
+}
+
+impl ProgressStatus {
+    /// Returns `true` if the album does not need to be processed again when resuming.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Skipped)
+    }
+}
+
+/// Returns the path that the progress of an album is recorded for.
+///
+/// This is the archive for albums that were extracted from an archive, and the album directory
+/// otherwise. If the directory contains several releases, the name of the release is appended, so
+/// that each release of the directory is recorded separately.
+pub fn album_path(
+    path: &Path,
+    source_archive: Option<&Path>,
+    directory_group: Option<&str>,
+) -> PathBuf {
+    let path = source_archive.unwrap_or(path);
+    match directory_group {
+        Some(directory_group) => path.join(directory_group),
+        None => path.to_path_buf(),
+    }
+}
+
+/// A single progress update.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ProgressEntry {
+    /// The path that is imported.
+    import_root: PathBuf,
+    /// Path of the album, relative to the import root.
+    path: PathBuf,
+    /// New status of the album.
+    status: ProgressStatus,
+    /// MusicBrainz ID of the release that was selected for a matched album.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    release_id: Option<String>,
+}
+
+/// Progress of the import runs of a single import path, persisted as JSON lines.
+#[derive(Debug, Clone)]
+pub struct ImportProgress {
+    /// Path of the store file.
+    store_path: PathBuf,
+    /// The (absolute) path that is imported.
+    import_root: PathBuf,
+    /// Latest entry of each album, by path relative to the import root.
+    entries: HashMap<PathBuf, ProgressEntry>,
+}
+
+impl ImportProgress {
+    /// Load the progress of the given import path from the store at `store_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn load(store_path: PathBuf, import_root: &Path) -> crate::Result<Self> {
+        let import_root = std::path::absolute(import_root)?;
+        let entries = read_entries(&store_path)?
+            .into_iter()
+            .filter(|entry| entry.import_root == import_root)
+            .map(|entry| (entry.path.clone(), entry))
+            .collect();
+        Ok(Self {
+            store_path,
+            import_root,
+            entries,
+        })
+    }
+
+    /// Load the progress of the given import path from the store in the XDG state directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory cannot be determined or the store cannot be read.
+    pub fn open_default(import_root: &Path) -> crate::Result<Self> {
+        let store_path =
+            BaseDirectories::with_prefix(crate::PKG_NAME)?.place_state_file(PROGRESS_FILE_NAME)?;
+        Self::load(store_path, import_root)
+    }
+
+    /// Returns the path relative to the import root, which is used as key in the store.
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        path.strip_prefix(&self.import_root)
+            .map_or_else(|_| path.clone(), Path::to_path_buf)
+    }
+
+    /// Returns the absolute paths of the albums that do not need to be processed again.
+    pub fn finished_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.entries
+            .values()
+            .filter(|entry| entry.status.is_finished())
+            .map(|entry| self.import_root.join(&entry.path))
+    }
+
+    /// Returns the MusicBrainz ID of the release that was selected for the album at the given
+    /// path if the album was matched, but not imported yet.
+    pub fn matched_release_id(&self, path: &Path) -> Option<&str> {
+        self.entries
+            .get(&self.relative_path(path))
+            .filter(|entry| entry.status == ProgressStatus::Matched)
+            .and_then(|entry| entry.release_id.as_deref())
+    }
+
+    /// Record the status of the album at the given path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    pub fn record(&mut self, path: &Path, status: ProgressStatus) -> crate::Result<()> {
+        self.append(path, status, None)
+    }
+
+    /// Record that the release with the given MusicBrainz ID was selected for the album at the
+    /// given path, so that the album can be imported without matching it again when resuming.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be written.
+    pub fn record_matched(&mut self, path: &Path, release_id: Option<&str>) -> crate::Result<()> {
+        self.append(
+            path,
+            ProgressStatus::Matched,
+            release_id.map(ToOwned::to_owned),
+        )
+    }
+
+    /// Append an entry to the store.
+    fn append(
+        &mut self,
+        path: &Path,
+        status: ProgressStatus,
+        release_id: Option<String>,
+    ) -> crate::Result<()> {
+        let entry = ProgressEntry {
+            import_root: self.import_root.clone(),
+            path: self.relative_path(path),
+            status,
+            release_id,
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.store_path)?;
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        let _ = self.entries.insert(entry.path.clone(), entry);
+        Ok(())
+    }
+
+    /// Rewrite the store, so that it only contains the latest entry of each album of the import
+    /// path. Albums that are still pending are removed, because they need to be processed again
+    /// anyway.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be rewritten.
+    pub fn compact(&mut self) -> crate::Result<()> {
+        self.entries
+            .retain(|_, entry| entry.status != ProgressStatus::Pending);
+        let mut entries = self.entries.values().cloned().collect::<Vec<_>>();
+        entries.sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+        self.rewrite(entries)
+    }
+
+    /// Remove the progress of the import path from the store (e.g., after the import run
+    /// finished).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be rewritten.
+    pub fn clear(&mut self) -> crate::Result<()> {
+        self.rewrite(vec![])?;
+        self.entries.clear();
+        Ok(())
+    }
+
+    /// Rewrite the store with the given entries of the import path, keeping the entries of other
+    /// import paths.
+    fn rewrite(&self, entries: Vec<ProgressEntry>) -> crate::Result<()> {
+        let mut lines = vec![];
+        for entry in read_entries(&self.store_path)?
+            .into_iter()
+            .filter(|entry| entry.import_root != self.import_root)
+            .chain(entries)
+        {
+            lines.extend(serde_json::to_vec(&entry)?);
+            lines.push(b'\n');
+        }
+        if lines.is_empty() {
+            match fs::remove_file(&self.store_path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => (),
+            }
+        } else {
+            fs::write(&self.store_path, lines)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read all entries from the store file.
+fn read_entries(store_path: &Path) -> crate::Result<Vec<ProgressEntry>> {
+    match File::open(store_path) {
+        Ok(file) => BufReader::new(file)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_progress() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let store_path = tmp_dir.path().join("import-progress.jsonl");
+        let import_root = tmp_dir.path().join("incoming");
+        let other_root = tmp_dir.path().join("other");
+
+        let mut progress = ImportProgress::load(store_path.clone(), &import_root).unwrap();
+        let mut other_progress = ImportProgress::load(store_path.clone(), &other_root).unwrap();
+        progress
+            .record(&import_root.join("Album 1"), ProgressStatus::Done)
+            .unwrap();
+        progress
+            .record(&import_root.join("Album 2"), ProgressStatus::Pending)
+            .unwrap();
+        progress
+            .record_matched(&import_root.join("Album 2"), Some("release"))
+            .unwrap();
+        progress
+            .record(&import_root.join("Album 3"), ProgressStatus::Pending)
+            .unwrap();
+        progress
+            .record(&import_root.join("Album 3"), ProgressStatus::Skipped)
+            .unwrap();
+        progress
+            .record(&import_root.join("Album 5"), ProgressStatus::Pending)
+            .unwrap();
+        other_progress
+            .record(&other_root.join("Album 4"), ProgressStatus::Done)
+            .unwrap();
+
+        let mut progress = ImportProgress::load(store_path.clone(), &import_root).unwrap();
+        let mut finished_paths = progress.finished_paths().collect::<Vec<_>>();
+        finished_paths.sort();
+        assert_eq!(
+            finished_paths,
+            vec![import_root.join("Album 1"), import_root.join("Album 3")]
+        );
+        assert_eq!(
+            progress.matched_release_id(&import_root.join("Album 2")),
+            Some("release")
+        );
+        assert_eq!(
+            progress.matched_release_id(&import_root.join("Album 3")),
+            None
+        );
+
+        progress.compact().unwrap();
+        let store = fs::read_to_string(&store_path).unwrap();
+        assert_eq!(store.lines().count(), 4);
+        assert!(!store.contains("Album 5"));
+        let mut progress = ImportProgress::load(store_path.clone(), &import_root).unwrap();
+        assert_eq!(progress.finished_paths().count(), 2);
+        assert_eq!(
+            progress.matched_release_id(&import_root.join("Album 2")),
+            Some("release")
+        );
+
+        progress.clear().unwrap();
+        let progress = ImportProgress::load(store_path.clone(), &import_root).unwrap();
+        assert_eq!(progress.finished_paths().count(), 0);
+        let other_progress = ImportProgress::load(store_path, &other_root).unwrap();
+        assert_eq!(
+            other_progress.finished_paths().collect::<Vec<_>>(),
+            vec![other_root.join("Album 4")]
+        );
+    }
+}
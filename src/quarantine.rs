@@ -0,0 +1,317 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Quarantine for albums that were not imported.
+
+use crate::config::QuarantineMode;
+use crate::distance::Distance;
+use crate::scanner::is_supported_audio_file;
+use crate::util;
+use crate::Config;
+use expanduser::expanduser;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the report file that is written into quarantined album directories.
+pub const REPORT_FILE_NAME: &str = "helicon-quarantine.txt";
+
+/// The reason why an album was not imported.
+#[derive(Debug, Clone)]
+pub enum SkipReason {
+    /// The user skipped the album.
+    SkippedByUser,
+    /// No candidate was similar enough to be imported automatically.
+    NoMatchingCandidate {
+        /// Distance of the best candidate, if there was any.
+        best_distance: Option<Distance>,
+    },
+    /// The album directory is already used by a different release.
+    AlbumCollision,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SkippedByUser => write!(f, "Skipped by user"),
+            Self::NoMatchingCandidate {
+                best_distance: Some(distance),
+            } => write!(
+                f,
+                "No candidate was similar enough (best distance: {distance})"
+            ),
+            Self::NoMatchingCandidate {
+                best_distance: None,
+            } => write!(f, "No candidates found"),
+            Self::AlbumCollision => {
+                write!(f, "Album directory is already used by a different release")
+            }
+        }
+    }
+}
+
+/// Returns the configured quarantine directory, or `None` if the quarantine is disabled.
+///
+/// # Errors
+///
+/// Returns an error if the path cannot be expanded.
+pub fn quarantine_dir(config: &Config) -> crate::Result<Option<PathBuf>> {
+    config
+        .quarantine
+        .path
+        .as_deref()
+        .map(|path| expanduser(path).map_err(crate::Error::Io))
+        .transpose()
+}
+
+/// Find a path in the quarantine directory that does not exist yet.
+fn find_destination_path(quarantine_dir: &Path, name: &str) -> PathBuf {
+    (0..)
+        .map(|number| {
+            if number == 0 {
+                quarantine_dir.join(name)
+            } else {
+                quarantine_dir.join(format!("{name} ({number})"))
+            }
+        })
+        .find(|path| !path.exists() && !path.is_symlink())
+        .expect("infinite iterator")
+}
+
+/// Returns `true` if the directory (or one of its subdirectories) contains audio files.
+fn contains_audio_files(dir: &Path) -> io::Result<bool> {
+    for result in util::walk_dir(dir.to_path_buf()) {
+        let (_dir, _subdirs, files) = result?;
+        if files.iter().any(|file| is_supported_audio_file(file)) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the files that belong to the album in the directory, and whether the directory
+/// contains other albums.
+///
+/// Subdirectories that contain audio files are separate albums that may not have been processed
+/// yet, so their files are not included. Other subdirectories (e.g., with scans) belong to the
+/// album.
+fn album_files(dir: &Path) -> io::Result<(Vec<PathBuf>, bool)> {
+    let mut files = vec![];
+    let mut contains_other_albums = false;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            files.push(path);
+        } else if contains_audio_files(&path)? {
+            contains_other_albums = true;
+        } else {
+            for result in util::walk_dir(path) {
+                let (_dir, _subdirs, subdir_files) = result?;
+                files.extend(subdir_files);
+            }
+        }
+    }
+    Ok((files, contains_other_albums))
+}
+
+/// Remove the directory and its subdirectories if they are empty.
+fn remove_empty_tree(dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            remove_empty_tree(&path)?;
+        }
+    }
+    if fs::read_dir(dir)?.next().is_none() {
+        fs::remove_dir(dir)?;
+    }
+    Ok(())
+}
+
+/// Move the files of the album in the directory.
+///
+/// The whole directory is renamed if it does not contain other albums. Otherwise, or if renaming
+/// fails (e.g., because the destination is on a different filesystem), the files are moved
+/// individually. Files that need to be copied are only removed after all files have been
+/// transferred and verified.
+fn move_dir(source: &Path, destination: &Path) -> crate::Result<()> {
+    let (files, contains_other_albums) = album_files(source)?;
+    if !contains_other_albums && fs::rename(source, destination).is_ok() {
+        return Ok(());
+    }
+
+    let mut copied_files = vec![];
+    for file in &files {
+        let relative_path = file.strip_prefix(source).map_err(io::Error::other)?;
+        let destination_file = destination.join(relative_path);
+        if let Some(dest_dir) = destination_file.parent() {
+            fs::create_dir_all(dest_dir)?;
+        }
+        if fs::rename(file, &destination_file).is_ok() {
+            continue;
+        }
+
+        util::copy_file(file, &destination_file)?;
+        if util::file_checksum(file)? != util::file_checksum(&destination_file)? {
+            return Err(io::Error::other(format!(
+                "{} differs from {} after copying",
+                destination_file.display(),
+                file.display()
+            ))
+            .into());
+        }
+        copied_files.push(file);
+    }
+
+    for file in copied_files {
+        fs::remove_file(file)?;
+    }
+    remove_empty_tree(source)?;
+    Ok(())
+}
+
+/// Create a symbolic link to the directory.
+fn symlink_dir(source: &Path, destination: &Path) -> io::Result<()> {
+    let source = source.canonicalize()?;
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(source, destination)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_dir(source, destination)
+    }
+}
+
+/// Write the report file that explains why the album was quarantined.
+fn write_report(dir: &Path, original_path: &Path, reason: &SkipReason) -> io::Result<()> {
+    let report = format!(
+        "Original path: {path}\nQuarantined at: {date}\nReason: {reason}\n",
+        path = original_path.display(),
+        date = chrono::Local::now().to_rfc3339(),
+    );
+    fs::write(dir.join(REPORT_FILE_NAME), report)
+}
+
+/// Move (or symlink) the album directory into the quarantine directory and write a report file.
+///
+/// Returns the path in the quarantine directory, or `None` if the quarantine is disabled.
+/// Albums that are already inside the quarantine directory are not moved again, only their report
+/// is updated.
+///
+/// # Errors
+///
+/// Returns an error if the album cannot be moved or the report cannot be written.
+pub fn quarantine(
+    config: &Config,
+    path: &Path,
+    reason: &SkipReason,
+) -> crate::Result<Option<PathBuf>> {
+    let Some(quarantine_dir) = quarantine_dir(config)? else {
+        return Ok(None);
+    };
+
+    if path.starts_with(&quarantine_dir) {
+        write_report(path, path, reason)?;
+        return Ok(Some(path.to_path_buf()));
+    }
+
+    fs::create_dir_all(&quarantine_dir)?;
+    let name = path
+        .file_name()
+        .map_or_else(|| "album".into(), |name| name.to_string_lossy());
+    let destination = find_destination_path(&quarantine_dir, &name);
+    match config.quarantine.mode {
+        QuarantineMode::Move => move_dir(path, &destination)?,
+        QuarantineMode::Symlink => symlink_dir(path, &destination)?,
+    }
+    write_report(&destination, path, reason)?;
+    log::info!(
+        "Quarantined {} at {} ({reason})",
+        path.display(),
+        destination.display()
+    );
+
+    Ok(Some(destination))
+}
+
+/// Remove the quarantine report from the directory if it exists.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be removed.
+pub fn remove_report(dir: &Path) -> io::Result<()> {
+    match fs::remove_file(dir.join(REPORT_FILE_NAME)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_move() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let album_dir = tmp_dir.path().join("import").join("Album");
+        let quarantine_path = tmp_dir.path().join("quarantine");
+        fs::create_dir_all(&album_dir).unwrap();
+        fs::write(album_dir.join("01.flac"), "").unwrap();
+        fs::create_dir_all(quarantine_path.join("Album")).unwrap();
+
+        let mut config = Config::default();
+        config.quarantine.path = Some(quarantine_path.to_str().unwrap().to_string());
+        let destination = quarantine(&config, &album_dir, &SkipReason::SkippedByUser)
+            .unwrap()
+            .unwrap();
+        assert_eq!(destination, quarantine_path.join("Album (1)"));
+        assert!(!album_dir.exists());
+        assert!(destination.join("01.flac").exists());
+        let report = fs::read_to_string(destination.join(REPORT_FILE_NAME)).unwrap();
+        assert!(report.contains("Reason: Skipped by user"));
+
+        remove_report(&destination).unwrap();
+        assert!(!destination.join(REPORT_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_quarantine_move_nested_albums() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let album_dir = tmp_dir.path().join("import").join("Album");
+        let quarantine_path = tmp_dir.path().join("quarantine");
+        fs::create_dir_all(album_dir.join("Scans")).unwrap();
+        fs::create_dir_all(album_dir.join("Bonus")).unwrap();
+        fs::write(album_dir.join("01.flac"), "").unwrap();
+        fs::write(album_dir.join("Scans").join("front.jpg"), "").unwrap();
+        fs::write(album_dir.join("Bonus").join("01.flac"), "").unwrap();
+
+        let mut config = Config::default();
+        config.quarantine.path = Some(quarantine_path.to_str().unwrap().to_string());
+        let destination = quarantine(&config, &album_dir, &SkipReason::SkippedByUser)
+            .unwrap()
+            .unwrap();
+        assert_eq!(destination, quarantine_path.join("Album"));
+        assert!(destination.join("01.flac").exists());
+        assert!(destination.join("Scans").join("front.jpg").exists());
+        assert!(!destination.join("Bonus").exists());
+        assert!(album_dir.join("Bonus").join("01.flac").exists());
+        assert!(!album_dir.join("01.flac").exists());
+        assert!(!album_dir.join("Scans").exists());
+    }
+
+    #[test]
+    fn test_quarantine_disabled() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        let result = quarantine(&config, tmp_dir.path(), &SkipReason::SkippedByUser).unwrap();
+        assert!(result.is_none());
+        assert!(tmp_dir.path().exists());
+    }
+}
@@ -19,6 +19,15 @@ use musicbrainz_rs_nova::entity::release_group::{
 };
 use std::borrow::Cow;
 
+/// The issue of a release in a certain country on a certain date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseEvent<'a> {
+    /// Country in which the release was issued.
+    pub country: Option<Cow<'a, str>>,
+    /// Date (YYYY-MM-DD) on which the release was issued.
+    pub date: Option<Cow<'a, str>>,
+}
+
 /// Represent a generic release, independent of the underlying source.
 pub trait ReleaseLike {
     /// Number of tracks.
@@ -79,6 +88,46 @@ pub trait ReleaseLike {
     /// Release Year (YYYY) - the year that the release was issued.
     fn release_year(&self) -> Option<Cow<'_, str>>;
 
+    /// All release events, i.e., the countries and dates in which the release was issued.
+    fn release_events(&self) -> Vec<ReleaseEvent<'_>> {
+        let event = ReleaseEvent {
+            country: self.release_country(),
+            date: self.release_date(),
+        };
+        if event.country.is_none() && event.date.is_none() {
+            vec![]
+        } else {
+            vec![event]
+        }
+    }
+
+    /// The release event in the most preferred country (e.g., `XW`, `US`), or the first release
+    /// event if the release was not issued in any of the preferred countries.
+    fn preferred_release_event(&self, preferred_countries: &[String]) -> Option<ReleaseEvent<'_>> {
+        let mut events = self.release_events();
+        let position = preferred_countries.iter().find_map(|preferred| {
+            events.iter().position(|event| {
+                event
+                    .country
+                    .as_deref()
+                    .is_some_and(|country| preferred.trim().eq_ignore_ascii_case(country))
+            })
+        });
+        if events.is_empty() {
+            None
+        } else {
+            Some(events.swap_remove(position.unwrap_or(0)))
+        }
+    }
+
+    /// Original Release Date (YYYY-MM-DD) - the date that the first release of the release group
+    /// was issued.
+    fn original_release_date(&self) -> Option<Cow<'_, str>>;
+
+    /// Original Release Year (YYYY) - the year that the first release of the release group was
+    /// issued.
+    fn original_release_year(&self) -> Option<Cow<'_, str>>;
+
     /// Release Status indicating the “official” status of the release.
     fn release_status(&self) -> Option<Cow<'_, str>>;
 
@@ -115,8 +164,14 @@ pub trait ReleaseLike {
     }
 
     /// Returns true if this release is likely a compilation.
+    ///
+    /// Implementations that derive [`ReleaseLike::compilation`] from this method need to override
+    /// it.
     fn is_compilation(&self) -> bool {
-        self.release_artist().as_deref().is_some_and(is_va_artist)
+        self.compilation().as_deref() == Some("1")
+            || self.musicbrainz_release_artist_id().as_deref()
+                == Some(musicbrainz::VARIOUS_ARTISTS_ID)
+            || self.release_artist().as_deref().is_some_and(is_va_artist)
     }
 
     /// Yields the media contained in the release.
@@ -229,8 +284,7 @@ impl ReleaseLike for MusicBrainzRelease {
     }
 
     fn compilation(&self) -> Option<Cow<'_, str>> {
-        // TODO:: Implement this.
-        None
+        self.is_compilation().then(|| Cow::from("1"))
     }
 
     fn grouping(&self) -> Option<Cow<'_, str>> {
@@ -282,6 +336,48 @@ impl ReleaseLike for MusicBrainzRelease {
             .map(Cow::from)
     }
 
+    fn release_events(&self) -> Vec<ReleaseEvent<'_>> {
+        let mut events = self
+            .release_events
+            .iter()
+            .flatten()
+            .map(|event| ReleaseEvent {
+                country: event
+                    .area
+                    .as_ref()
+                    .and_then(|area| area.iso_3166_1_codes.as_ref())
+                    .and_then(|codes| codes.first())
+                    .map(Cow::from),
+                date: event
+                    .date
+                    .map(|date| Cow::from(date.format("%Y-%m-%d").to_string())),
+            })
+            .collect::<Vec<_>>();
+        if events.is_empty() && (self.country.is_some() || self.date.is_some()) {
+            events.push(ReleaseEvent {
+                country: self.release_country(),
+                date: self.release_date(),
+            });
+        }
+        events
+    }
+
+    fn original_release_date(&self) -> Option<Cow<'_, str>> {
+        self.release_group
+            .as_ref()
+            .and_then(|release_group| release_group.first_release_date)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .map(Cow::from)
+    }
+
+    fn original_release_year(&self) -> Option<Cow<'_, str>> {
+        self.release_group
+            .as_ref()
+            .and_then(|release_group| release_group.first_release_date)
+            .map(|date| date.format("%Y").to_string())
+            .map(Cow::from)
+    }
+
     fn release_status(&self) -> Option<Cow<'_, str>> {
         self.status
             .as_ref()
@@ -379,3 +475,36 @@ fn is_va_artist(value: &str) -> bool {
             | "unknown"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MUSICBRAINZ_RELEASE_JSON: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/musicbrainz/release.json"
+    ));
+
+    #[test]
+    fn test_release_events() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let events = release.release_events();
+        assert_eq!(
+            events,
+            vec![ReleaseEvent {
+                country: Some(Cow::from("US")),
+                date: Some(Cow::from("1958-01-01")),
+            }]
+        );
+
+        let preferred_countries = ["GB".to_string(), "US".to_string()];
+        assert_eq!(
+            release.preferred_release_event(&preferred_countries),
+            events.first().cloned()
+        );
+        assert_eq!(
+            release.preferred_release_event(&[]),
+            events.first().cloned()
+        );
+    }
+}
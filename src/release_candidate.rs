@@ -8,7 +8,8 @@
 
 //! Release Candidate
 
-use crate::distance::{Distance, ReleaseSimilarity};
+use crate::decisions::DecisionStore;
+use crate::distance::{Distance, ReleasePreference, ReleaseSimilarity};
 use crate::release::ReleaseLike;
 use crate::Config;
 
@@ -19,6 +20,8 @@ pub struct ReleaseCandidate<T: ReleaseLike> {
     release: T,
     /// The similarity to the base release.
     similarity: ReleaseSimilarity,
+    /// The distance to the past decisions of the user (if there are any relevant decisions).
+    decision_distance: Option<Distance>,
 }
 
 impl<T: ReleaseLike> ReleaseCandidate<T> {
@@ -28,6 +31,7 @@ impl<T: ReleaseLike> ReleaseCandidate<T> {
         Self {
             release,
             similarity,
+            decision_distance: None,
         }
     }
 
@@ -56,6 +60,23 @@ impl<T: ReleaseLike> ReleaseCandidate<T> {
     pub fn distance(&self, config: &Config) -> Distance {
         self.similarity.total_distance(config)
     }
+
+    /// Get the distance that is used for ranking candidates, which mixes the distance to the base
+    /// release with the configured release preferences and the past decisions of the user.
+    pub fn ranking_distance(&self, config: &Config) -> Distance {
+        let preference = ReleasePreference::detect(config, &self.release).total_distance(config);
+        [
+            Some(self.distance(config).into_weighted(1.0)),
+            preference
+                .map(|preference| preference.into_weighted(config.lookup.preferred_release.weight)),
+            self.decision_distance
+                .as_ref()
+                .map(|distance| distance.to_weighted(config.decisions.weight)),
+        ]
+        .into_iter()
+        .flatten()
+        .sum()
+    }
 }
 
 /// A collection of release candidates.
@@ -65,6 +86,8 @@ impl<T: ReleaseLike> ReleaseCandidate<T> {
 pub struct ReleaseCandidateCollection<T: ReleaseLike> {
     /// Ordered list of candidates.
     candidates: Vec<ReleaseCandidate<T>>,
+    /// Number of candidates that were hidden because of the minimum similarity.
+    hidden_count: usize,
 }
 
 impl<T: ReleaseLike> ReleaseCandidateCollection<T> {
@@ -81,6 +104,37 @@ impl<T: ReleaseLike> ReleaseCandidateCollection<T> {
             .expect("Failed to find selected candidate in candidate collection.")
     }
 
+    /// Remove candidates whose similarity to the base release is below the configured minimum
+    /// similarity.
+    #[must_use]
+    pub fn with_min_similarity(mut self, config: &Config) -> Self {
+        let Some(min_similarity) = config.lookup.min_similarity else {
+            return self;
+        };
+
+        let candidate_count = self.candidates.len();
+        self.candidates
+            .retain(|candidate| 1.0 - candidate.distance(config).as_f64() >= min_similarity);
+        self.hidden_count += candidate_count - self.candidates.len();
+        self
+    }
+
+    /// Rank the candidates according to the past decisions of the user.
+    #[must_use]
+    pub fn with_decisions(mut self, decisions: &DecisionStore, config: &Config) -> Self {
+        for candidate in &mut self.candidates {
+            candidate.decision_distance = decisions.distance(&candidate.release);
+        }
+        self.candidates
+            .sort_by_cached_key(|candidate| candidate.ranking_distance(config));
+        self
+    }
+
+    /// Returns the number of candidates that were hidden because of the minimum similarity.
+    pub fn hidden_count(&self) -> usize {
+        self.hidden_count
+    }
+
     /// Select the candidate by index and discard the other candidates.
     pub fn select_index(mut self, index: usize) -> ReleaseCandidate<T> {
         self.candidates.swap_remove(index)
@@ -88,10 +142,10 @@ impl<T: ReleaseLike> ReleaseCandidateCollection<T> {
 
     /// Add a new candidate to this collection.
     pub fn add_candidate(&mut self, candidate: ReleaseCandidate<T>, config: &Config) {
-        match self
-            .candidates
-            .binary_search_by(|cand| cand.distance(config).cmp(&candidate.distance(config)))
-        {
+        match self.candidates.binary_search_by(|cand| {
+            cand.ranking_distance(config)
+                .cmp(&candidate.ranking_distance(config))
+        }) {
             Ok(pos) => {
                 // There already is a candidate with the same distance in the candidate list.
                 if !self.candidates.iter().skip(pos).any(|c| {
@@ -133,7 +187,10 @@ impl<T: ReleaseLike> From<Vec<ReleaseCandidate<T>>> for ReleaseCandidateCollecti
     ///
     /// The supplied candidates needs to be in the correct order.
     fn from(candidates: Vec<ReleaseCandidate<T>>) -> Self {
-        Self { candidates }
+        Self {
+            candidates,
+            hidden_count: 0,
+        }
     }
 }
 
@@ -173,4 +230,50 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(distances, [Distance::MIN]);
     }
+
+    #[test]
+    fn test_min_similarity() {
+        let release: FakeRelease = serde_json::from_slice(RELEASE_DATA).unwrap();
+        let candidate_0: FakeRelease = serde_json::from_slice(RELEASE_CANDIDATE_0_DATA).unwrap();
+
+        let mut config = Config::default();
+        let candidates = |config: &Config| {
+            ReleaseCandidateCollection::from(vec![
+                ReleaseCandidate::with_base_release(candidate_0.clone(), &release, config),
+                ReleaseCandidate::with_base_release(
+                    candidate_0.clone(),
+                    &FakeRelease::default(),
+                    config,
+                ),
+            ])
+        };
+        let collection = candidates(&config).with_min_similarity(&config);
+        assert_eq!(collection.len(), 2);
+        assert_eq!(collection.hidden_count(), 0);
+
+        config.lookup.min_similarity = Some(0.4);
+        let collection = candidates(&config).with_min_similarity(&config);
+        assert_eq!(collection.len(), 1);
+        assert_eq!(collection.hidden_count(), 1);
+    }
+
+    #[test]
+    fn test_ranking_distance_without_preferences() {
+        let release: FakeRelease = serde_json::from_slice(RELEASE_DATA).unwrap();
+        let candidate_0: FakeRelease = serde_json::from_slice(RELEASE_CANDIDATE_0_DATA).unwrap();
+
+        let config = Config::default();
+        let candidate = ReleaseCandidate::with_base_release(
+            candidate_0.clone(),
+            &FakeRelease::default(),
+            &config,
+        );
+        assert_eq!(
+            candidate.ranking_distance(&config),
+            candidate.distance(&config)
+        );
+
+        let candidate = ReleaseCandidate::with_base_release(candidate_0, &release, &config);
+        assert_eq!(candidate.ranking_distance(&config), Distance::MIN);
+    }
 }
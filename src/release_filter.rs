@@ -0,0 +1,149 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Filters that exclude releases before they are fetched and compared.
+
+use crate::config::CandidateFilterConfig;
+use crate::media::MediaLike;
+use crate::release::ReleaseLike;
+
+/// A filter that decides if a release may be used as a candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReleaseFilter {
+    /// Exclude releases with one of the given statuses (e.g., `bootleg`).
+    ExcludeStatuses(Vec<String>),
+    /// Exclude releases whose media all have one of the given formats (e.g., `Digital Media`).
+    ExcludeMediaFormats(Vec<String>),
+    /// Only include releases from the given range of years (inclusive).
+    ///
+    /// Releases without a release date are not excluded.
+    YearRange {
+        /// Earliest release year.
+        min: Option<i32>,
+        /// Latest release year.
+        max: Option<i32>,
+    },
+}
+
+impl ReleaseFilter {
+    /// Create the list of filters from the configuration.
+    pub fn from_config(config: &CandidateFilterConfig) -> Vec<Self> {
+        let mut filters = vec![];
+        if !config.excluded_statuses.is_empty() {
+            filters.push(Self::ExcludeStatuses(config.excluded_statuses.clone()));
+        }
+        if !config.excluded_media_formats.is_empty() {
+            filters.push(Self::ExcludeMediaFormats(
+                config.excluded_media_formats.clone(),
+            ));
+        }
+        if config.min_year.is_some() || config.max_year.is_some() {
+            filters.push(Self::YearRange {
+                min: config.min_year,
+                max: config.max_year,
+            });
+        }
+        filters
+    }
+
+    /// Returns `true` if the release passes this filter.
+    pub fn accepts<T: ReleaseLike + ?Sized>(&self, release: &T) -> bool {
+        match self {
+            Self::ExcludeStatuses(statuses) => release.release_status().is_none_or(|status| {
+                !statuses
+                    .iter()
+                    .any(|excluded| excluded.trim().eq_ignore_ascii_case(status.trim()))
+            }),
+            Self::ExcludeMediaFormats(formats) => {
+                let media_formats = release
+                    .media()
+                    .map(MediaLike::media_format)
+                    .collect::<Vec<_>>();
+                media_formats.is_empty()
+                    || !media_formats.iter().all(|format| {
+                        format.as_ref().is_some_and(|format| {
+                            formats
+                                .iter()
+                                .any(|excluded| excluded.trim().eq_ignore_ascii_case(format.trim()))
+                        })
+                    })
+            }
+            Self::YearRange { min, max } => release
+                .release_year()
+                .and_then(|year| year.parse::<i32>().ok())
+                .is_none_or(|year| {
+                    min.is_none_or(|min| year >= min) && max.is_none_or(|max| year <= max)
+                }),
+        }
+    }
+}
+
+/// Returns `true` if the release passes all configured candidate filters.
+pub fn accepts_release<T: ReleaseLike + ?Sized>(
+    config: &CandidateFilterConfig,
+    release: &T,
+) -> bool {
+    ReleaseFilter::from_config(config)
+        .iter()
+        .all(|filter| filter.accepts(release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::musicbrainz::MusicBrainzRelease;
+
+    const MUSICBRAINZ_RELEASE_JSON: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/musicbrainz/release.json"
+    ));
+
+    #[test]
+    fn test_release_filters() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+
+        assert!(ReleaseFilter::ExcludeStatuses(vec!["bootleg".to_string()]).accepts(&release));
+        assert!(!ReleaseFilter::ExcludeStatuses(vec!["Official".to_string()]).accepts(&release));
+
+        assert!(
+            ReleaseFilter::ExcludeMediaFormats(vec!["Digital Media".to_string()]).accepts(&release)
+        );
+        assert!(
+            !ReleaseFilter::ExcludeMediaFormats(vec!["12\" Vinyl".to_string()]).accepts(&release)
+        );
+
+        assert!(ReleaseFilter::YearRange {
+            min: Some(1950),
+            max: Some(1959)
+        }
+        .accepts(&release));
+        assert!(!ReleaseFilter::YearRange {
+            min: Some(1960),
+            max: Some(1979)
+        }
+        .accepts(&release));
+        assert!(ReleaseFilter::YearRange {
+            min: None,
+            max: Some(1958)
+        }
+        .accepts(&release));
+    }
+
+    #[test]
+    fn test_accepts_release() {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let mut config = CandidateFilterConfig::default();
+        assert!(ReleaseFilter::from_config(&config).is_empty());
+        assert!(accepts_release(&config, &release));
+
+        config.excluded_statuses = vec!["bootleg".to_string()];
+        config.min_year = Some(1960);
+        assert_eq!(ReleaseFilter::from_config(&config).len(), 2);
+        assert!(!accepts_release(&config, &release));
+    }
+}
@@ -12,18 +12,30 @@
 //! [mb]: https://musicbrainz.org
 
 use crate::analyzer;
+use crate::archive::{self, ExtractedArchive};
+use crate::cue::CueSheet;
+use crate::filename_pattern::{compile_patterns, FilenamePattern};
+use crate::local_metadata::LocalMetadata;
 use crate::musicbrainz::{MusicBrainzClient, MusicBrainzRelease};
+use crate::progress;
 use crate::release_candidate::ReleaseCandidateCollection;
+use crate::tag::TagKey;
+use crate::track::TrackLike;
 use crate::util::walk_dir;
 use crate::Cache;
 use crate::{Config, TaggedFile, TaggedFileCollection};
 use futures::FutureExt;
+use regex::Regex;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::runtime::{Builder, Runtime};
 use tokio::sync::mpsc::Receiver;
 use tokio::task::JoinSet;
 
+/// File extensions of supported audio formats.
+const SUPPORTED_EXTENSIONS: [&str; 5] = ["mp3", "flac", "wav", "aif", "aiff"];
+
 /// An error type that contains the path that was scanned when the error occurred.
 pub struct ScanError {
     /// The path for which the error occurred.
@@ -33,10 +45,15 @@ pub struct ScanError {
 }
 
 /// Convenience Alias for a Scan Result.
+///
+/// Contains the scanned path, the track collection, the MusicBrainz candidates and the release
+/// from local metadata files (if any).
 type ScanResult = Result<
     (
+        PathBuf,
         TaggedFileCollection,
         ReleaseCandidateCollection<MusicBrainzRelease>,
+        Option<TaggedFileCollection>,
     ),
     ScanError,
 >;
@@ -55,6 +72,18 @@ pub struct Scanner {
 impl Scanner {
     /// Create a scanner for the given path.
     pub fn scan(config: Config, cache: Option<Cache>, path: PathBuf) -> Scanner {
+        Self::scan_excluding(config, cache, path, HashSet::new())
+    }
+
+    /// Create a scanner for the given path that skips the album directories, archives and
+    /// releases of split directories in `excluded_paths` (e.g., because they were already
+    /// imported in a previous run).
+    pub fn scan_excluding(
+        config: Config,
+        cache: Option<Cache>,
+        path: PathBuf,
+        excluded_paths: HashSet<PathBuf>,
+    ) -> Scanner {
         log::info!("Starting scan of {}", path.display());
 
         let (results_tx, results_rx) = tokio::sync::mpsc::channel(20);
@@ -74,18 +103,58 @@ impl Scanner {
         let pool_handle = pool.handle().clone();
         let _scanner = pool.spawn(async move {
             // First, search the file system to find track paths.
-            for (path, tracks) in find_track_paths(path) {
+            let filename_patterns = compile_patterns(&config.tags.filename_patterns);
+            for album_files in find_track_paths(path, &config, &filename_patterns, &excluded_paths)
+                .flat_map(|album_files| {
+                    if config.lookup.split_releases {
+                        split_album_files(album_files)
+                    } else {
+                        vec![album_files]
+                    }
+                })
+                .filter(|album_files| !is_excluded_release(album_files, &excluded_paths))
+            {
+                let AlbumFiles {
+                    path,
+                    tracks,
+                    unreadable_files,
+                    source_archive,
+                    directory_group,
+                    cue_source,
+                } = album_files;
+                if !unreadable_files.is_empty()
+                    && (tracks.is_empty() || !config.lookup.skip_unreadable_files)
+                {
+                    let source = crate::Error::UnreadableFiles(unreadable_files.len());
+                    if let Err(err) = cloned_results_tx
+                        .send(Err(ScanError { path, source }))
+                        .await
+                    {
+                        log::error!("Failed to queue results: {err}");
+                    }
+                    continue;
+                }
+
                 let cloned_config = config.clone();
                 let cloned_config2 = config.clone();
 
                 // Some tracks were found, spawn individual tasks for analyzing the tracks in the
                 // threadpool. We keep track of the spawned task handles in a Vec, so that we
                 // combine the results of these tasks in a track collection.
+                // The virtual tracks of a single-file album do not exist as files yet, so they
+                // cannot be analyzed.
                 let mut handles = JoinSet::new();
+                let is_single_file_album = cue_source.is_some();
                 for track in tracks {
                     let config = cloned_config.clone();
                     let _analysis_abort_handle = handles.spawn_blocking_on(
-                        move || analyze_tagged_file(&config, track),
+                        move || {
+                            if is_single_file_album {
+                                track
+                            } else {
+                                analyze_tagged_file(&config, track)
+                            }
+                        },
                         &pool_handle,
                     );
                 }
@@ -95,19 +164,24 @@ impl Scanner {
                 let cloned_cache = cache.clone();
                 let results_tx = cloned_results_tx.clone();
                 let _matching_logic = pool_handle.spawn(async move {
-                    let musicbrainz =
-                        MusicBrainzClient::new(&cloned_config2, cloned_cache.as_ref());
-                    if let Err(err) = results_tx
-                        .send(
-                            join_analysis_tasks_to_collection_and_find_release_candidates(
-                                &musicbrainz,
-                                path,
-                                handles,
-                            )
-                            .await,
-                        )
-                        .await
-                    {
+                    let result =
+                        match MusicBrainzClient::new(&cloned_config2, cloned_cache.as_ref()) {
+                            Ok(musicbrainz) => {
+                                join_analysis_tasks_to_collection_and_find_release_candidates(
+                                    &cloned_config2,
+                                    &musicbrainz,
+                                    path,
+                                    handles,
+                                    unreadable_files,
+                                    source_archive,
+                                    directory_group,
+                                    cue_source,
+                                )
+                                .await
+                            }
+                            Err(source) => Err(ScanError { path, source }),
+                        };
+                    if let Err(err) = results_tx.send(result).await {
                         log::error!("Failed to queue results: {err}");
                     }
                 });
@@ -134,47 +208,373 @@ impl Drop for Scanner {
     }
 }
 
+/// Returns `true` if the path has the given extension (case-insensitive).
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Read the virtual tracks of a single-file album if the directory contains a cue sheet.
+///
+/// The virtual tracks only carry the metadata from the cue sheet. Their paths are the paths that
+/// the tracks will have once the audio file is split (see [`CueSheet::track_file_name`]), so that
+/// each track has a distinct path. Returns `None` if the directory does not contain exactly one
+/// cue sheet or if the cue sheet cannot be read.
+fn read_single_file_album(
+    dir: &Path,
+    files: &[PathBuf],
+) -> Option<(Vec<TaggedFile>, CueSheet, PathBuf)> {
+    let mut cue_paths = files.iter().filter(|path| has_extension(path, "cue"));
+    let cue_path = cue_paths.next()?;
+    if cue_paths.next().is_some() {
+        log::warn!(
+            "Not reading tracks from cue sheets in {} because it contains multiple cue sheets",
+            dir.display()
+        );
+        return None;
+    }
+
+    let result = CueSheet::read_from_path(cue_path)
+        .map_err(crate::Error::from)
+        .and_then(|sheet| {
+            let audio_path = dir.join(sheet.audio_file()?);
+            if !audio_path.is_file() {
+                return Err(crate::Error::from(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{} does not exist", audio_path.display()),
+                )));
+            }
+            let tracks = sheet
+                .tracks
+                .iter()
+                .map(|track| {
+                    TaggedFile::with_tags(
+                        dir.join(CueSheet::track_file_name(&audio_path, track)),
+                        vec![Box::new(sheet.track_tag(track))],
+                    )
+                })
+                .collect::<Vec<_>>();
+            Ok((tracks, sheet, audio_path))
+        });
+
+    match result {
+        Ok(album) => Some(album),
+        Err(err) => {
+            log::warn!("Failed to read {}: {}", cue_path.display(), err);
+            None
+        }
+    }
+}
+
+/// Convenience alias for the files that could not be read, and the reason why.
+type UnreadableFiles = Vec<(PathBuf, String)>;
+
+/// The audio files of an album that was found during the scan.
+struct AlbumFiles {
+    /// Path of the album directory.
+    path: PathBuf,
+    /// The tracks that could be read.
+    tracks: Vec<TaggedFile>,
+    /// The files that could not be read.
+    unreadable_files: UnreadableFiles,
+    /// Archive that the album directory was extracted from (if any).
+    source_archive: Option<Arc<ExtractedArchive>>,
+    /// Name of the release if the album directory contains files of several releases.
+    directory_group: Option<String>,
+    /// Cue sheet and audio file of a single-file album whose tracks are virtual.
+    cue_source: Option<(CueSheet, PathBuf)>,
+}
+
+/// Read the supported audio files of an album.
+///
+/// Supported audio files that cannot be read are returned separately, so that a single corrupt
+/// file does not prevent the rest of the album from being matched. Returns `None` if there are no
+/// supported audio files.
+fn read_album_files(
+    path: PathBuf,
+    files: &[PathBuf],
+    config: &Config,
+    filename_patterns: &[FilenamePattern],
+) -> Option<AlbumFiles> {
+    let mut tracks = vec![];
+    let mut unreadable_files = vec![];
+    for file_path in files.iter().filter(|path| is_supported_audio_file(path)) {
+        match TaggedFile::read_from_path(file_path, &config.tags) {
+            Ok(file) => tracks.push(file.with_path_values(filename_patterns)),
+            Err(err) => {
+                log::warn!("Failed to read {}: {err}", file_path.display());
+                unreadable_files.push((file_path.clone(), err.to_string()));
+            }
+        }
+    }
+
+    if tracks.is_empty() && unreadable_files.is_empty() {
+        return None;
+    }
+
+    log::info!("Found {} tracks in {}", tracks.len(), path.display());
+    if !unreadable_files.is_empty() {
+        log::warn!(
+            "{} files in {} could not be read",
+            unreadable_files.len(),
+            path.display()
+        );
+    }
+
+    Some(AlbumFiles {
+        path,
+        tracks,
+        unreadable_files,
+        source_archive: None,
+        directory_group: None,
+        cue_source: None,
+    })
+}
+
+/// Extract the archive into a temporary directory and read all audio files in it as one album.
+fn read_archive_files(
+    archive_path: &Path,
+    config: &Config,
+    filename_patterns: &[FilenamePattern],
+) -> Option<AlbumFiles> {
+    let extracted_archive = archive::extract_to_temp_dir(archive_path)
+        .inspect_err(|err| log::warn!("Failed to extract {}: {err}", archive_path.display()))
+        .ok()?;
+    let output_dir = extracted_archive.output_dir().to_path_buf();
+    let files = walk_dir(output_dir.clone())
+        .with_ignore_patterns(&config.paths.ignore_patterns)
+        .filter_map(Result::ok)
+        .flat_map(|(_path, _dirs, files)| files)
+        .collect::<Vec<_>>();
+    let Some(album_files) = read_album_files(output_dir, &files, config, filename_patterns) else {
+        log::warn!("No audio files found in {}", archive_path.display());
+        return None;
+    };
+    Some(AlbumFiles {
+        source_archive: Some(Arc::new(extracted_archive)),
+        ..album_files
+    })
+}
+
 /// Find track collections in the given path.
-fn find_track_paths(input_path: PathBuf) -> impl Iterator<Item = (PathBuf, Vec<TaggedFile>)> {
-    let supported_extensions = HashSet::from(["mp3", "flac"]);
+///
+/// Single-file albums with a cue sheet are read as virtual tracks from the cue sheet. If archive extraction is enabled, each archive is treated as a separate
+/// album. Album directories and archives in `excluded_paths` are skipped.
+fn find_track_paths<'a>(
+    input_path: PathBuf,
+    config: &'a Config,
+    filename_patterns: &'a [FilenamePattern],
+    excluded_paths: &'a HashSet<PathBuf>,
+) -> impl Iterator<Item = AlbumFiles> + 'a {
+    let is_excluded = |path: &Path| {
+        let is_excluded = !excluded_paths.is_empty()
+            && excluded_paths
+                .contains(&std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()));
+        if is_excluded {
+            log::info!(
+                "Skipping {} (already processed in a previous run)",
+                path.display()
+            );
+        }
+        is_excluded
+    };
+
     walk_dir(input_path)
+        .with_ignore_patterns(&config.paths.ignore_patterns)
         .filter_map(Result::ok)
-        .filter_map(move |(path, _dirs, files)| {
-            let tagged_files: Vec<TaggedFile> = files
+        .flat_map(move |(path, _dirs, files)| {
+            let archives = files
                 .iter()
-                .filter(|path| {
-                    path.extension()
-                        .map(std::ffi::OsStr::to_ascii_lowercase)
-                        .and_then(|extension| {
-                            extension
-                                .to_str()
-                                .map(|extension| supported_extensions.contains(extension))
-                        })
-                        .unwrap_or(false)
-                })
-                .filter_map(|path| match TaggedFile::read_from_path(path) {
-                    Ok(file) => Some(file),
-                    Err(err) => {
-                        log::warn!("Failed to read {}: {:?}", path.display(), err);
-                        None
-                    }
-                })
-                .collect();
+                .filter(|file_path| config.archives.extract && archive::is_archive(file_path))
+                .filter(|file_path| !is_excluded(file_path))
+                .filter_map(|file_path| read_archive_files(file_path, config, filename_patterns))
+                .collect::<Vec<_>>();
+
+            if is_excluded(&path) {
+                return archives.into_iter().chain(None);
+            }
 
-            if tagged_files.is_empty() {
-                return None;
+            if let Some((tracks, sheet, audio_path)) = read_single_file_album(&path, &files) {
+                log::info!(
+                    "Found {} tracks in cue sheet for {}",
+                    tracks.len(),
+                    audio_path.display()
+                );
+                let album_files = AlbumFiles {
+                    path,
+                    tracks,
+                    unreadable_files: vec![],
+                    source_archive: None,
+                    directory_group: None,
+                    cue_source: Some((sheet, audio_path)),
+                };
+                return archives.into_iter().chain(Some(album_files));
             }
 
-            log::info!("Found {} tracks in {}", tagged_files.len(), path.display());
+            archives
+                .into_iter()
+                .chain(read_album_files(path, &files, config, filename_patterns))
+        })
+}
+
+/// Returns `true` if the album is one of several releases of a directory and was already
+/// processed in a previous run.
+///
+/// Directories that contain a single release are already skipped before reading their files.
+fn is_excluded_release(album_files: &AlbumFiles, excluded_paths: &HashSet<PathBuf>) -> bool {
+    let Some(directory_group) = album_files.directory_group.as_deref() else {
+        return false;
+    };
+    let path = progress::album_path(
+        &album_files.path,
+        album_files
+            .source_archive
+            .as_deref()
+            .map(ExtractedArchive::path),
+        Some(directory_group),
+    );
+    let is_excluded =
+        excluded_paths.contains(&std::path::absolute(&path).unwrap_or_else(|_| path.clone()));
+    if is_excluded {
+        log::info!(
+            "Skipping {directory_group} in {} (already processed in a previous run)",
+            album_files.path.display()
+        );
+    }
+    is_excluded
+}
+
+/// Returns the key that is used to group the tracks of a directory by release, and a name for the
+/// group.
+///
+/// Tracks are grouped by their MusicBrainz release ID, or by album artist and album title
+/// otherwise. Disc suffixes (e.g., `(Disc 2)`) are removed from the album title, so that the discs
+/// of a multi-disc release stay in the same group.
+fn release_group_key(disc_suffix: &Regex, track: &TaggedFile) -> Option<(String, String)> {
+    if let Some(release_id) = track.first_tag_value(&TagKey::MusicBrainzReleaseId) {
+        let name = track
+            .first_tag_value(&TagKey::Album)
+            .map_or_else(|| release_id.to_string(), |album| album.to_string());
+        return Some((format!("release_id:{release_id}"), name));
+    }
+
+    let album = track.first_tag_value(&TagKey::Album)?;
+    let album = disc_suffix.replace(album.trim(), "");
+    let artist = track
+        .first_tag_value(&TagKey::AlbumArtist)
+        .or_else(|| track.first_tag_value(&TagKey::Artist))
+        .unwrap_or_default();
+    let key = format!(
+        "album:{}\0{}",
+        artist.trim().to_lowercase(),
+        album.to_lowercase()
+    );
+    let name = if artist.is_empty() {
+        album.to_string()
+    } else {
+        format!("{artist} - {album}")
+    };
+    Some((key, name))
+}
+
+/// Split the tracks of an album directory that contains files of several releases (e.g., a
+/// directory of singles) into one group per release, so that each release is matched separately.
+///
+/// Tracks without album tag are kept in a separate group. Files that could not be read are
+/// reported with the first group. If all tracks belong to the same release, the album files are
+/// returned unchanged.
+fn split_album_files(album_files: AlbumFiles) -> Vec<AlbumFiles> {
+    if album_files.cue_source.is_some() {
+        return vec![album_files];
+    }
+
+    let disc_suffix = Regex::new(r"(?i)\s*[(\[]?\b(disc|disk|cd)\s*\d+[)\]]?$")
+        .expect("invalid disc suffix regex");
+    let mut groups: Vec<(Option<String>, Option<String>, Vec<TaggedFile>)> = vec![];
+    for track in album_files.tracks {
+        let (key, name) = release_group_key(&disc_suffix, &track).unzip();
+        match groups
+            .iter_mut()
+            .find(|(group_key, _, _)| *group_key == key)
+        {
+            Some((_, _, tracks)) => tracks.push(track),
+            None => groups.push((key, name, vec![track])),
+        }
+    }
 
-            Some((path, tagged_files))
+    if groups.len() < 2 {
+        return vec![AlbumFiles {
+            tracks: groups
+                .into_iter()
+                .flat_map(|(_, _, tracks)| tracks)
+                .collect(),
+            ..album_files
+        }];
+    }
+
+    log::info!(
+        "Splitting {} into {} releases",
+        album_files.path.display(),
+        groups.len()
+    );
+    let mut unreadable_files = Some(album_files.unreadable_files);
+    groups
+        .into_iter()
+        .map(|(_, name, tracks)| AlbumFiles {
+            path: album_files.path.clone(),
+            tracks,
+            unreadable_files: unreadable_files.take().unwrap_or_default(),
+            source_archive: album_files.source_archive.clone(),
+            directory_group: Some(name.unwrap_or_else(|| "[unknown album]".to_string())),
+            cue_source: None,
         })
+        .collect()
+}
+
+/// Count the albums in the given path (like the scanner finds them), but stop counting after
+/// `limit` albums.
+///
+/// The tracks are read, but neither analyzed nor looked up on MusicBrainz.
+pub fn count_albums(config: &Config, path: PathBuf, limit: usize) -> usize {
+    let filename_patterns = compile_patterns(&config.tags.filename_patterns);
+    let excluded_paths = HashSet::new();
+    find_track_paths(path, config, &filename_patterns, &excluded_paths)
+        .flat_map(|album_files| {
+            if config.lookup.split_releases {
+                split_album_files(album_files)
+            } else {
+                vec![album_files]
+            }
+        })
+        .take(limit)
+        .count()
+}
+
+/// Returns `true` if the file has the extension of a supported audio format.
+pub fn is_supported_audio_file(path: &Path) -> bool {
+    path.extension()
+        .map(std::ffi::OsStr::to_ascii_lowercase)
+        .and_then(|extension| {
+            extension
+                .to_str()
+                .map(|extension| SUPPORTED_EXTENSIONS.contains(&extension))
+        })
+        .unwrap_or(false)
 }
 
 /// Analyze a file and assign the analysis results to it.
 fn analyze_tagged_file(config: &Config, tagged_file: TaggedFile) -> TaggedFile {
     let path = tagged_file.path.as_path();
-    let analysis_result = analyzer::analyze(config, path)
+    let existing_fingerprint = config
+        .analyzers
+        .reuse_existing
+        .then(|| tagged_file.acoustid_fingerprint())
+        .flatten();
+    let analysis_result = existing_fingerprint
+        .map_or_else(
+            || analyzer::analyze(config, path),
+            |fingerprint| analyzer::analyze_with_fingerprint(config, path, &fingerprint),
+        )
         .inspect_err(|err| {
             log::warn!("Analysis of {path} failed: {err}", path = path.display());
         })
@@ -183,23 +583,341 @@ fn analyze_tagged_file(config: &Config, tagged_file: TaggedFile) -> TaggedFile {
 }
 
 /// Join all analysis tasks, then create a TaggedFieCollection from it. Then find similar
-/// candidates on MusicBrainz.
+/// candidates on MusicBrainz and in local metadata files.
+#[expect(clippy::too_many_arguments)]
 async fn join_analysis_tasks_to_collection_and_find_release_candidates(
+    config: &Config,
     musicbrainz: &MusicBrainzClient<'_>,
     path: PathBuf,
     handles: JoinSet<TaggedFile>,
+    unreadable_files: UnreadableFiles,
+    source_archive: Option<Arc<ExtractedArchive>>,
+    directory_group: Option<String>,
+    cue_source: Option<(CueSheet, PathBuf)>,
 ) -> ScanResult {
+    let local_metadata = if config.lookup.use_local_metadata {
+        LocalMetadata::find_in_dir(&path)
+    } else {
+        None
+    };
+
     handles
         .join_all()
         .then(|mut tracks| async {
             tracks.sort_unstable_by(|a, b| a.path.as_path().cmp(b.path.as_path()));
-            let track_collection = TaggedFileCollection::new(tracks);
+            let track_collection = TaggedFileCollection::new(tracks)
+                .with_partial_album_gain(config.analyzers.partial_album_gain)
+                .with_unreadable_files(unreadable_files)
+                .with_source_archive(source_archive)
+                .with_directory_group(directory_group)
+                .with_cue_source(cue_source);
             musicbrainz
                 .find_releases_by_similarity(&track_collection)
                 .await
-                .map(ReleaseCandidateCollection::from)
+                .map(|candidates| {
+                    ReleaseCandidateCollection::from(candidates).with_min_similarity(config)
+                })
                 .map(|candidates| (track_collection, candidates))
         })
         .await
+        .map(|(track_collection, candidates)| {
+            // If the directory contains several releases, the metadata file only describes one of
+            // them.
+            let local_release = local_metadata
+                .filter(|metadata| {
+                    track_collection.directory_group().is_none()
+                        || metadata.matches_release_title(&track_collection)
+                })
+                .map(|metadata| metadata.into_release(&path));
+            (path.clone(), track_collection, candidates, local_release)
+        })
         .map_err(|source| ScanError { path, source })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::{Distance, ReleaseSimilarity};
+    use crate::import_session::import_release;
+    use crate::release_candidate::ReleaseCandidate;
+    use crate::stats::{ImportOutcome, ImportStats};
+    use crate::tag::memory::MemoryTag;
+    use crate::tag::TagType;
+    use std::fs;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_split_album_files() {
+        let track = |path: &str, artist: &str, album: Option<&str>| {
+            TaggedFile::with_tags(
+                PathBuf::from(path),
+                vec![Box::new(
+                    MemoryTag::new()
+                        .with_value(TagKey::Artist, Some(artist))
+                        .with_value(TagKey::Album, album),
+                )],
+            )
+        };
+        let album_files = AlbumFiles {
+            path: PathBuf::from("singles"),
+            tracks: vec![
+                track("01.flac", "Artist", Some("Album (Disc 1)")),
+                track("02.flac", "Artist", Some("Album (Disc 2)")),
+                track("03.flac", "Other Artist", Some("Single")),
+                track("04.flac", "Other Artist", None),
+            ],
+            unreadable_files: vec![(PathBuf::from("05.flac"), "corrupt".to_string())],
+            source_archive: None,
+            directory_group: None,
+            cue_source: None,
+        };
+
+        let groups = split_album_files(album_files);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| group.directory_group.as_deref())
+                .collect::<Vec<_>>(),
+            vec![
+                Some("Artist - Album"),
+                Some("Other Artist - Single"),
+                Some("[unknown album]")
+            ]
+        );
+        assert_eq!(groups[0].tracks.len(), 2);
+        assert_eq!(groups[0].unreadable_files.len(), 1);
+        assert!(groups[1].unreadable_files.is_empty());
+        assert!(groups
+            .iter()
+            .all(|group| group.path == Path::new("singles")));
+
+        let groups = split_album_files(AlbumFiles {
+            path: PathBuf::from("album"),
+            tracks: vec![
+                track("01.flac", "Artist", Some("Album CD1")),
+                track("02.flac", "Artist", Some("Album CD2")),
+            ],
+            unreadable_files: vec![],
+            source_archive: None,
+            directory_group: None,
+            cue_source: None,
+        });
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].directory_group, None);
+        assert_eq!(groups[0].tracks.len(), 2);
+    }
+
+    #[test]
+    fn test_is_excluded_release() {
+        let album_files = |directory_group: Option<&str>| AlbumFiles {
+            path: PathBuf::from("/import/singles"),
+            tracks: vec![],
+            unreadable_files: vec![],
+            source_archive: None,
+            directory_group: directory_group.map(ToString::to_string),
+            cue_source: None,
+        };
+        let excluded_paths = HashSet::from([
+            PathBuf::from("/import/singles"),
+            PathBuf::from("/import/singles/Artist - Album"),
+        ]);
+        assert!(is_excluded_release(
+            &album_files(Some("Artist - Album")),
+            &excluded_paths
+        ));
+        assert!(!is_excluded_release(
+            &album_files(Some("Other Artist - Single")),
+            &excluded_paths
+        ));
+        // Directories without several releases are excluded before their files are read.
+        assert!(!is_excluded_release(&album_files(None), &excluded_paths));
+    }
+
+    /// Write a single-file album with two tracks and its cue sheet into `dir`.
+    ///
+    /// Returns the paths of the audio file and the cue sheet.
+    #[cfg(feature = "flac")]
+    fn write_single_file_album(dir: &Path) -> (PathBuf, PathBuf) {
+        let audio_path = dir.join("Album.flac");
+        let _ = fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/media/untagged/track.flac"
+            ),
+            &audio_path,
+        )
+        .unwrap();
+        let cue_path = dir.join("Album.cue");
+        fs::write(
+            &cue_path,
+            concat!(
+                "TITLE \"Album\"\nFILE \"Album.flac\" WAVE\n",
+                "TRACK 01 AUDIO\nTITLE \"One\"\nINDEX 01 00:00:00\n",
+                "TRACK 02 AUDIO\nTITLE \"Two\"\nINDEX 01 00:00:30\n",
+            ),
+        )
+        .unwrap();
+        (audio_path, cue_path)
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_read_single_file_album() {
+        let dir = tempfile::tempdir().unwrap();
+        let (audio_path, cue_path) = write_single_file_album(dir.path());
+
+        let (tracks, sheet, path) =
+            read_single_file_album(dir.path(), &[audio_path.clone(), cue_path]).unwrap();
+        assert_eq!(path, audio_path);
+        assert_eq!(
+            tracks
+                .iter()
+                .map(|track| track.path.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                dir.path().join("Album - 01.flac"),
+                dir.path().join("Album - 02.flac")
+            ]
+        );
+        assert!(tracks.iter().all(|track| !track.path.exists()));
+
+        let mut config = Config::default();
+        config.cue.split_files = true;
+        let mut collection = TaggedFileCollection::new(tracks).with_cue_source(Some((sheet, path)));
+        collection.prepare_cue_tracks(&config).unwrap();
+        assert!(collection.has_split_tracks());
+        let tracks = collection.tracks().collect::<Vec<_>>();
+        assert!(tracks.iter().all(|track| track.path.is_file()));
+        assert_eq!(tracks[1].tags()[0].tag_type(), TagType::Flac);
+        assert_eq!(
+            tracks[1].first_tag_value(&TagKey::TrackTitle).as_deref(),
+            Some("Two")
+        );
+        assert_eq!(
+            tracks[1].first_tag_value(&TagKey::Album).as_deref(),
+            Some("Album")
+        );
+    }
+
+    /// Create a release candidate for a release with the given track titles, whose tracks are
+    /// assigned to the local tracks in order.
+    fn release_candidate(
+        release_title: &str,
+        track_titles: &[&str],
+    ) -> ReleaseCandidate<crate::util::FakeRelease> {
+        let tracks = track_titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                serde_json::json!({"track_title": title, "track_number": format!("{}", i + 1)})
+            })
+            .collect::<Vec<_>>();
+        let release: crate::util::FakeRelease = serde_json::from_value(serde_json::json!({
+            "release_title": release_title,
+            "release_artist": "Artist",
+            "media": [{"media_tracks": tracks}],
+        }))
+        .unwrap();
+        ReleaseCandidate::with_similarity(release, ReleaseSimilarity::new(track_titles.len()))
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_import_single_file_album() {
+        let dir = tempfile::tempdir().unwrap();
+        let library_dir = tempfile::tempdir().unwrap();
+        let (audio_path, cue_path) = write_single_file_album(dir.path());
+        let (tracks, sheet, path) =
+            read_single_file_album(dir.path(), &[audio_path, cue_path]).unwrap();
+
+        let mut config = Config::default();
+        config.cue.split_files = true;
+        config.paths.library_path = library_dir.path().to_string_lossy().into_owned();
+        let collection = TaggedFileCollection::new(tracks)
+            .with_cue_source(Some((sheet, path)))
+            .assign_tags(&release_candidate("Album", &["One", "Two"]), &config);
+
+        let stats = Mutex::new(ImportStats::default());
+        assert!(import_release(
+            &config,
+            dir.path(),
+            dir.path().to_path_buf(),
+            collection,
+            Distance::from(0.0),
+            None,
+            &stats,
+        ));
+        assert_eq!(stats.lock().unwrap().count(ImportOutcome::Imported), 1);
+        let album_dir = library_dir.path().join("Artist").join("Album");
+        assert!(album_dir.join("1-1 - One.flac").is_file());
+        assert!(album_dir.join("1-2 - Two.flac").is_file());
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_import_directory_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let library_dir = tempfile::tempdir().unwrap();
+        for name in ["Single A.flac", "Single B.flac"] {
+            let _ = fs::copy(
+                concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/tests/data/media/untagged/track.flac"
+                ),
+                dir.path().join(name),
+            )
+            .unwrap();
+        }
+        fs::write(dir.path().join("cover.jpg"), b"cover").unwrap();
+
+        let mut config = Config::default();
+        config.paths.library_path = library_dir.path().to_string_lossy().into_owned();
+        let track =
+            TaggedFile::read_from_path(dir.path().join("Single A.flac"), &config.tags).unwrap();
+        let collection = TaggedFileCollection::new(vec![track])
+            .with_directory_group(Some("Artist - Single A".to_string()))
+            .assign_tags(&release_candidate("Single A", &["A"]), &config);
+        assert_eq!(collection.directory_group(), Some("Artist - Single A"));
+
+        let stats = Mutex::new(ImportStats::default());
+        assert!(import_release(
+            &config,
+            dir.path(),
+            dir.path().to_path_buf(),
+            collection,
+            Distance::from(0.0),
+            None,
+            &stats,
+        ));
+        let album_dir = library_dir.path().join("Artist").join("Single A");
+        assert!(album_dir.join("1-1 - A.flac").is_file());
+        // The attachments and the files of the other release stay in the directory.
+        assert!(!album_dir.join("cover.jpg").exists());
+        assert!(dir.path().join("cover.jpg").is_file());
+        assert!(dir.path().join("Single B.flac").is_file());
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_count_albums() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        assert_eq!(count_albums(&config, dir.path().to_path_buf(), 2), 0);
+
+        for album in ["Album 1", "Album 2", "Album 3"] {
+            let album_dir = dir.path().join(album);
+            fs::create_dir(&album_dir).unwrap();
+            let _ = fs::copy(
+                concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/tests/data/media/untagged/track.flac"
+                ),
+                album_dir.join("track.flac"),
+            )
+            .unwrap();
+        }
+        assert_eq!(count_albums(&config, dir.path().join("Album 1"), 2), 1);
+        assert_eq!(count_albums(&config, dir.path().to_path_buf(), 2), 2);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Statistics that are collected during an import run.
+
+use crate::distance::Distance;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The outcome of importing a single directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// The directory was imported.
+    Imported,
+    /// The directory was imported, but some of its files could not be read.
+    PartiallyImported,
+    /// The directory was skipped by the user.
+    Skipped,
+    /// Scanning or importing the directory failed.
+    Failed,
+}
+
+/// Statistics collector for an import run.
+#[derive(Debug)]
+pub struct ImportStats {
+    /// Time when the import run was started.
+    start_time: Instant,
+    /// The outcome for each directory, in the order they were processed.
+    directories: Vec<(PathBuf, ImportOutcome)>,
+    /// Distances of the imported releases to the selected candidates.
+    distances: Vec<Distance>,
+    /// Number of files that were moved.
+    files_moved: usize,
+    /// Total size of the imported files in bytes.
+    bytes_processed: u64,
+}
+
+impl Default for ImportStats {
+    fn default() -> Self {
+        Self {
+            start_time: Instant::now(),
+            directories: Vec::new(),
+            distances: Vec::new(),
+            files_moved: 0,
+            bytes_processed: 0,
+        }
+    }
+}
+
+impl ImportStats {
+    /// Record that the directory was imported with the given distance to the selected candidate.
+    pub fn record_imported(&mut self, path: PathBuf, distance: Distance) {
+        self.directories.push((path, ImportOutcome::Imported));
+        self.distances.push(distance);
+    }
+
+    /// Record that the directory was imported without some of its files, which could not be read.
+    pub fn record_partially_imported(&mut self, path: PathBuf, distance: Distance) {
+        self.directories
+            .push((path, ImportOutcome::PartiallyImported));
+        self.distances.push(distance);
+    }
+
+    /// Record that the directory was skipped.
+    pub fn record_skipped(&mut self, path: PathBuf) {
+        self.directories.push((path, ImportOutcome::Skipped));
+    }
+
+    /// Record that scanning or importing the directory failed.
+    pub fn record_failed(&mut self, path: PathBuf) {
+        self.directories.push((path, ImportOutcome::Failed));
+    }
+
+    /// Record that files were moved.
+    pub fn record_files_moved(&mut self, file_count: usize, byte_count: u64) {
+        self.files_moved += file_count;
+        self.bytes_processed += byte_count;
+    }
+
+    /// Iterate over the processed directories and their outcome.
+    pub fn directories(&self) -> impl Iterator<Item = (&Path, ImportOutcome)> {
+        self.directories
+            .iter()
+            .map(|(path, outcome)| (path.as_path(), *outcome))
+    }
+
+    /// Number of directories with the given outcome.
+    pub fn count(&self, outcome: ImportOutcome) -> usize {
+        self.directories
+            .iter()
+            .filter(|(_, other)| *other == outcome)
+            .count()
+    }
+
+    /// Average distance of all imported releases, or `None` if nothing was imported.
+    pub fn average_distance(&self) -> Option<Distance> {
+        if self.distances.is_empty() {
+            return None;
+        }
+
+        #[expect(clippy::cast_precision_loss)]
+        let count = self.distances.len() as f64;
+        let sum: f64 = self.distances.iter().map(Distance::as_f64).sum();
+        Some(Distance::from(sum / count))
+    }
+
+    /// Number of files that were moved.
+    pub fn files_moved(&self) -> usize {
+        self.files_moved
+    }
+
+    /// Total size of the imported files in bytes.
+    pub fn bytes_processed(&self) -> u64 {
+        self.bytes_processed
+    }
+
+    /// Time elapsed since the import run was started.
+    pub fn elapsed(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_eq::assert_float_eq;
+
+    #[test]
+    fn test_stats() {
+        let mut stats = ImportStats::default();
+        assert!(stats.average_distance().is_none());
+
+        stats.record_imported(PathBuf::from("a"), Distance::from(0.1));
+        stats.record_imported(PathBuf::from("b"), Distance::from(0.3));
+        stats.record_partially_imported(PathBuf::from("e"), Distance::from(0.2));
+        stats.record_skipped(PathBuf::from("c"));
+        stats.record_failed(PathBuf::from("d"));
+        stats.record_files_moved(10, 1000);
+        stats.record_files_moved(5, 500);
+
+        assert_eq!(stats.count(ImportOutcome::Imported), 2);
+        assert_eq!(stats.count(ImportOutcome::PartiallyImported), 1);
+        assert_eq!(stats.count(ImportOutcome::Skipped), 1);
+        assert_eq!(stats.count(ImportOutcome::Failed), 1);
+        assert_eq!(stats.files_moved(), 15);
+        assert_eq!(stats.bytes_processed(), 1500);
+        assert_float_eq!(
+            stats.average_distance().unwrap().as_f64(),
+            0.2,
+            abs <= 0.000_1
+        );
+    }
+}
@@ -0,0 +1,555 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Support for APEv2 tags at the end of MP3 files.
+//!
+//! See the [APEv2 specification][apev2] for details on the format.
+//!
+//! [apev2]: https://wiki.hydrogenaud.io/index.php?title=APEv2_specification
+
+use crate::tag::{Tag, TagKey, TagType};
+use crate::track::InvolvedPerson;
+use crate::util::parse_year_from_str;
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Preamble of the APEv2 header and footer.
+const PREAMBLE: &[u8; 8] = b"APETAGEX";
+
+/// Version number of APEv2 tags.
+const VERSION: u32 = 2000;
+
+/// Size of the APEv2 header and footer.
+const HEADER_SIZE: u64 = 32;
+
+/// Size of an ID3v1 tag, which is located after the APEv2 tag (if present).
+const ID3V1_SIZE: u64 = 128;
+
+/// Header/footer flag that indicates that the tag contains a header.
+const FLAG_HAS_HEADER: u32 = 1 << 31;
+
+/// Header/footer flag that indicates that this is the header (not the footer).
+const FLAG_IS_HEADER: u32 = 1 << 29;
+
+/// Mask of the item flags that contain the item type.
+const ITEM_TYPE_MASK: u32 = 0b110;
+
+/// Error for malformed APEv2 tags.
+fn invalid_data_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed APEv2 tag")
+}
+
+/// Read a little-endian `u32` at the given offset of the buffer.
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// An item of an APEv2 tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApeItem {
+    /// Item key (case-insensitive).
+    key: String,
+    /// Item flags (including the item type).
+    flags: u32,
+    /// Raw item value.
+    value: Vec<u8>,
+}
+
+impl ApeItem {
+    /// Create a UTF-8 text item.
+    fn text(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            flags: 0,
+            value: value.as_bytes().to_vec(),
+        }
+    }
+
+    /// Returns the value if this is a UTF-8 text item.
+    fn text_value(&self) -> Option<&str> {
+        if self.flags & ITEM_TYPE_MASK == 0 {
+            std::str::from_utf8(&self.value).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse the items of an APEv2 tag.
+fn parse_items(mut data: &[u8], item_count: u32) -> io::Result<Vec<ApeItem>> {
+    let mut items = vec![];
+    for _ in 0..item_count {
+        let size = read_u32(data, 0).ok_or_else(invalid_data_error)?;
+        let flags = read_u32(data, 4).ok_or_else(invalid_data_error)?;
+        let size = usize::try_from(size).map_err(|_| invalid_data_error())?;
+        let key_length = data
+            .get(8..)
+            .and_then(|rest| rest.iter().position(|byte| *byte == 0))
+            .ok_or_else(invalid_data_error)?;
+        let key = String::from_utf8_lossy(&data[8..8 + key_length]).into_owned();
+        let value_start = 8 + key_length + 1;
+        let value = data
+            .get(value_start..value_start + size)
+            .ok_or_else(invalid_data_error)?
+            .to_vec();
+        items.push(ApeItem { key, flags, value });
+        data = &data[value_start + size..];
+    }
+    Ok(items)
+}
+
+/// Location of an APEv2 tag in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ApeTagLocation {
+    /// Offset of the first byte of the tag (including the header, if present).
+    start: u64,
+    /// Offset after the last byte of the tag (i.e., before the ID3v1 tag, if present).
+    end: u64,
+}
+
+/// Find the end of the audio data (or of the APEv2 tag) before an optional ID3v1 tag.
+fn find_tag_end(file: &mut File) -> io::Result<u64> {
+    let file_size = file.seek(SeekFrom::End(0))?;
+    if file_size < ID3V1_SIZE {
+        return Ok(file_size);
+    }
+    let _ = file.seek(SeekFrom::Start(file_size - ID3V1_SIZE))?;
+    let mut id3v1_header = [0; 3];
+    file.read_exact(&mut id3v1_header)?;
+    if &id3v1_header == b"TAG" {
+        Ok(file_size - ID3V1_SIZE)
+    } else {
+        Ok(file_size)
+    }
+}
+
+/// Read the APEv2 tag of the file, if present.
+fn read_tag(file: &mut File) -> io::Result<(ApeTagLocation, Option<Vec<ApeItem>>)> {
+    let end = find_tag_end(file)?;
+    let empty_location = ApeTagLocation { start: end, end };
+    if end < HEADER_SIZE {
+        return Ok((empty_location, None));
+    }
+
+    let _ = file.seek(SeekFrom::Start(end - HEADER_SIZE))?;
+    let mut footer = [0; 32];
+    file.read_exact(&mut footer)?;
+    if &footer[0..8] != PREAMBLE {
+        return Ok((empty_location, None));
+    }
+    let tag_size = read_u32(&footer, 12).ok_or_else(invalid_data_error)?;
+    let item_count = read_u32(&footer, 16).ok_or_else(invalid_data_error)?;
+    let flags = read_u32(&footer, 20).ok_or_else(invalid_data_error)?;
+
+    // The tag size includes the footer, but not the header.
+    let tag_size = u64::from(tag_size);
+    if tag_size < HEADER_SIZE || tag_size > end {
+        return Err(invalid_data_error());
+    }
+    let items_start = end - tag_size;
+    let start = if flags & FLAG_HAS_HEADER == 0 {
+        items_start
+    } else {
+        items_start
+            .checked_sub(HEADER_SIZE)
+            .ok_or_else(invalid_data_error)?
+    };
+
+    let _ = file.seek(SeekFrom::Start(items_start))?;
+    let mut data = vec![];
+    let _ = file
+        .by_ref()
+        .take(tag_size - HEADER_SIZE)
+        .read_to_end(&mut data)?;
+    let items = parse_items(&data, item_count)?;
+    Ok((ApeTagLocation { start, end }, Some(items)))
+}
+
+/// APEv2 tag.
+#[derive(Debug, Default)]
+pub struct ApeTag {
+    /// The tag items.
+    items: Vec<ApeItem>,
+    /// Remove the tag from the file when writing instead of updating it.
+    strip: bool,
+}
+
+impl ApeTag {
+    /// Create a new, empty tag.
+    #[cfg(test)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the APEv2 tag from the path. Returns `None` if the file does not contain an APEv2 tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or the tag is malformed.
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Option<Self>> {
+        let mut file = File::open(path)?;
+        let (_, items) = read_tag(&mut file)?;
+        Ok(items.map(|items| Self {
+            items,
+            strip: false,
+        }))
+    }
+
+    /// Remove the tag from the file on the next write (e.g., because its values have been merged
+    /// into the primary tag).
+    pub fn set_strip(&mut self, strip: bool) {
+        self.strip = strip;
+    }
+
+    /// Get the item key for a tag key.
+    ///
+    /// The keys match the ones that MusicBrainz Picard writes. Item keys are case-insensitive.
+    fn tag_key_to_item(key: &TagKey) -> Option<&'static str> {
+        match key {
+            TagKey::AcoustId => "ACOUSTID_ID".into(),
+            TagKey::AcoustIdFingerprint => "ACOUSTID_FINGERPRINT".into(),
+            TagKey::Album => "Album".into(),
+            TagKey::AlbumArtist => "Album Artist".into(),
+            TagKey::AlbumArtistSortOrder => "ALBUMARTISTSORT".into(),
+            TagKey::AlbumSortOrder => "ALBUMSORT".into(),
+            TagKey::Arranger => "Arranger".into(),
+            TagKey::Artist => "Artist".into(),
+            TagKey::ArtistSortOrder => "ARTISTSORT".into(),
+            TagKey::Artists => "Artists".into(),
+            TagKey::Asin => "ASIN".into(),
+            TagKey::Barcode => "Barcode".into(),
+            TagKey::Bpm => "BPM".into(),
+            TagKey::CatalogNumber => "CatalogNumber".into(),
+            TagKey::Comment => "Comment".into(),
+            TagKey::Compilation => "Compilation".into(),
+            TagKey::Composer => "Composer".into(),
+            TagKey::ComposerSortOrder => "COMPOSERSORT".into(),
+            TagKey::Conductor => "Conductor".into(),
+            TagKey::Copyright => "Copyright".into(),
+            TagKey::DiscNumber | TagKey::TotalDiscs => "Disc".into(),
+            TagKey::DiscSubtitle => "DiscSubtitle".into(),
+            TagKey::EncodedBy => "EncodedBy".into(),
+            TagKey::Engineer => "Engineer".into(),
+            TagKey::Genre => "Genre".into(),
+            TagKey::Grouping => "Grouping".into(),
+            TagKey::Isrc => "ISRC".into(),
+            TagKey::Language => "Language".into(),
+            TagKey::Lyricist => "Lyricist".into(),
+            TagKey::Lyrics => "Lyrics".into(),
+            TagKey::Media => "Media".into(),
+            TagKey::DjMixer => "DJMixer".into(),
+            TagKey::Mixer => "Mixer".into(),
+            TagKey::Mood => "Mood".into(),
+            TagKey::MusicBrainzArtistId => "MUSICBRAINZ_ARTISTID".into(),
+            TagKey::MusicBrainzDiscId => "MUSICBRAINZ_DISCID".into(),
+            TagKey::MusicBrainzOriginalArtistId => "MUSICBRAINZ_ORIGINALARTISTID".into(),
+            TagKey::MusicBrainzOriginalReleaseId => "MUSICBRAINZ_ORIGINALALBUMID".into(),
+            TagKey::MusicBrainzRecordingId => "MUSICBRAINZ_TRACKID".into(),
+            TagKey::MusicBrainzReleaseArtistId => "MUSICBRAINZ_ALBUMARTISTID".into(),
+            TagKey::MusicBrainzReleaseGroupId => "MUSICBRAINZ_RELEASEGROUPID".into(),
+            TagKey::MusicBrainzReleaseId => "MUSICBRAINZ_ALBUMID".into(),
+            TagKey::MusicBrainzTrackId => "MUSICBRAINZ_RELEASETRACKID".into(),
+            TagKey::MusicBrainzWorkId => "MUSICBRAINZ_WORKID".into(),
+            TagKey::OriginalReleaseDate => "ORIGINALDATE".into(),
+            TagKey::Producer => "Producer".into(),
+            TagKey::RecordLabel => "Label".into(),
+            TagKey::ReleaseCountry => "RELEASECOUNTRY".into(),
+            TagKey::ReleaseDate => "Year".into(),
+            TagKey::ReleaseStatus => "MUSICBRAINZ_ALBUMSTATUS".into(),
+            TagKey::ReleaseType => "MUSICBRAINZ_ALBUMTYPE".into(),
+            TagKey::Remixer => "MixArtist".into(),
+            TagKey::ReplayGainAlbumGain => "REPLAYGAIN_ALBUM_GAIN".into(),
+            TagKey::ReplayGainAlbumPeak => "REPLAYGAIN_ALBUM_PEAK".into(),
+            TagKey::ReplayGainReferenceLoudness => "REPLAYGAIN_REFERENCE_LOUDNESS".into(),
+            TagKey::ReplayGainTrackGain => "REPLAYGAIN_TRACK_GAIN".into(),
+            TagKey::ReplayGainTrackPeak => "REPLAYGAIN_TRACK_PEAK".into(),
+            TagKey::Script => "Script".into(),
+            TagKey::Subtitle => "Subtitle".into(),
+            TagKey::TrackNumber | TagKey::TotalTracks => "Track".into(),
+            TagKey::TrackTitle => "Title".into(),
+            TagKey::TrackTitleSortOrder => "TITLESORT".into(),
+            TagKey::WorkTitle => "Work".into(),
+            TagKey::Writer => "Writer".into(),
+            _ => None,
+        }
+    }
+
+    /// Returns the index of the `number/total` part for keys that share an item with another
+    /// key (i.e., `Track` and `Disc`).
+    fn combined_part(key: &TagKey) -> Option<usize> {
+        match key {
+            TagKey::TrackNumber | TagKey::DiscNumber => Some(0),
+            TagKey::TotalTracks | TagKey::TotalDiscs => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Get the text value of the item with the given key.
+    fn item_value(&self, item_key: &str) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|item| item.key.eq_ignore_ascii_case(item_key))
+            .and_then(ApeItem::text_value)
+    }
+
+    /// Replace the item with the given key, or remove it if the value is `None`.
+    fn set_item_value(&mut self, item_key: &str, value: Option<&str>) {
+        let position = self
+            .items
+            .iter()
+            .position(|item| item.key.eq_ignore_ascii_case(item_key));
+        match (position, value) {
+            (Some(position), Some(value)) => {
+                self.items[position] = ApeItem::text(&self.items[position].key, value);
+            }
+            (None, Some(value)) => self.items.push(ApeItem::text(item_key, value)),
+            (Some(position), None) => {
+                let _ = self.items.remove(position);
+            }
+            (None, None) => (),
+        }
+    }
+
+    /// Set one part of a `number/total` item.
+    fn set_combined_part(&mut self, item_key: &str, part: usize, value: Option<&str>) {
+        let mut parts = self
+            .item_value(item_key)
+            .map(|value| {
+                let mut parts = value.splitn(2, '/').map(ToString::to_string);
+                [
+                    parts.next().unwrap_or_default(),
+                    parts.next().unwrap_or_default(),
+                ]
+            })
+            .unwrap_or_default();
+        parts[part] = value.unwrap_or_default().to_string();
+        let value = match (parts[0].is_empty(), parts[1].is_empty()) {
+            (true, _) => None,
+            (false, true) => Some(parts[0].clone()),
+            (false, false) => Some(format!("{}/{}", parts[0], parts[1])),
+        };
+        self.set_item_value(item_key, value.as_deref());
+    }
+
+    /// Serialize the tag including header and footer.
+    fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut data = vec![];
+        for item in &self.items {
+            let size = u32::try_from(item.value.len()).map_err(io::Error::other)?;
+            data.extend_from_slice(&size.to_le_bytes());
+            data.extend_from_slice(&item.flags.to_le_bytes());
+            data.extend_from_slice(item.key.as_bytes());
+            data.push(0);
+            data.extend_from_slice(&item.value);
+        }
+        let tag_size = u32::try_from(data.len() + 32).map_err(io::Error::other)?;
+        let item_count = u32::try_from(self.items.len()).map_err(io::Error::other)?;
+        let header_or_footer = |flags: u32| {
+            let mut bytes = PREAMBLE.to_vec();
+            bytes.extend_from_slice(&VERSION.to_le_bytes());
+            bytes.extend_from_slice(&tag_size.to_le_bytes());
+            bytes.extend_from_slice(&item_count.to_le_bytes());
+            bytes.extend_from_slice(&flags.to_le_bytes());
+            bytes.extend_from_slice(&[0; 8]);
+            bytes
+        };
+
+        let mut bytes = header_or_footer(FLAG_HAS_HEADER | FLAG_IS_HEADER);
+        bytes.extend(data);
+        bytes.extend(header_or_footer(FLAG_HAS_HEADER));
+        Ok(bytes)
+    }
+}
+
+impl Tag for ApeTag {
+    fn tag_type(&self) -> TagType {
+        TagType::Ape
+    }
+
+    fn field_name(&self, key: &TagKey) -> Option<String> {
+        Self::tag_key_to_item(key).map(ToString::to_string)
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        self.items
+            .iter()
+            .filter_map(|item| {
+                item.text_value()
+                    .map(|value| (item.key.clone(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
+        if key == &TagKey::ReleaseYear {
+            return self
+                .get(&TagKey::ReleaseDate)
+                .and_then(|value| parse_year_from_str(&value).map(Cow::from));
+        }
+
+        let value = self.item_value(Self::tag_key_to_item(key)?)?;
+        let value = match Self::combined_part(key) {
+            Some(part) => value.split('/').nth(part)?,
+            None => value.split('\0').next()?,
+        };
+        let value = value.trim();
+        (!value.is_empty()).then_some(Cow::from(value))
+    }
+
+    fn get_multiple<'a>(&'a self, key: &'a TagKey) -> Vec<Cow<'a, str>> {
+        if Self::combined_part(key).is_some() {
+            return self.get(key).into_iter().collect();
+        }
+        Self::tag_key_to_item(key)
+            .and_then(|item_key| self.item_value(item_key))
+            .into_iter()
+            .flat_map(|value| value.split('\0'))
+            .filter(|value| !value.is_empty())
+            .map(Cow::from)
+            .collect()
+    }
+
+    fn set(&mut self, key: &TagKey, value: Cow<'_, str>) {
+        let Some(item_key) = Self::tag_key_to_item(key) else {
+            return;
+        };
+        match Self::combined_part(key) {
+            Some(part) => self.set_combined_part(item_key, part, Some(&value)),
+            None => self.set_item_value(item_key, Some(&value)),
+        }
+    }
+
+    fn set_multiple<'a>(&'a mut self, key: &TagKey, values: &[Cow<'a, str>]) {
+        // Multiple values are separated by null characters.
+        if values.is_empty() {
+            self.clear(key);
+        } else if Self::combined_part(key).is_some() {
+            self.set(key, values[0].clone());
+        } else {
+            self.set(key, Cow::from(values.join("\0")));
+        }
+    }
+
+    fn clear(&mut self, key: &TagKey) {
+        let Some(item_key) = Self::tag_key_to_item(key) else {
+            return;
+        };
+        match Self::combined_part(key) {
+            Some(part) => self.set_combined_part(item_key, part, None),
+            None => self.set_item_value(item_key, None),
+        }
+    }
+
+    fn maybe_as_ape_mut(&mut self) -> Option<&mut ApeTag> {
+        Some(self)
+    }
+
+    fn write(&mut self, path: &Path) -> crate::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let (location, _) = read_tag(&mut file)?;
+
+        // Keep the ID3v1 tag (if any) after the APEv2 tag.
+        let _ = file.seek(SeekFrom::Start(location.end))?;
+        let mut trailer = vec![];
+        let _ = file.read_to_end(&mut trailer)?;
+
+        file.set_len(location.start)?;
+        let _ = file.seek(SeekFrom::Start(location.start))?;
+        if !self.strip && !self.items.is_empty() {
+            file.write_all(&self.to_bytes()?)?;
+        }
+        file.write_all(&trailer)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    fn performers(&self) -> Option<Vec<InvolvedPerson<'_>>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a file with some fake audio data and an ID3v1 tag.
+    fn write_test_file(path: &Path) -> Vec<u8> {
+        let mut content = b"fake audio data".to_vec();
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, 0);
+        content.extend(id3v1);
+        std::fs::write(path, &content).unwrap();
+        content
+    }
+
+    #[test]
+    fn test_tag_type() {
+        let tag = ApeTag::new();
+        assert_eq!(tag.tag_type(), TagType::Ape);
+    }
+
+    #[test]
+    fn test_read_without_tag() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("track.mp3");
+        let _ = write_test_file(&path);
+        assert!(ApeTag::read_from_path(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_and_strip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("track.mp3");
+        let original_content = write_test_file(&path);
+
+        let mut tag = ApeTag::new();
+        tag.set(&TagKey::TrackTitle, Cow::from("But Not for Me"));
+        tag.set(&TagKey::TrackNumber, Cow::from("3"));
+        tag.set(&TagKey::TotalTracks, Cow::from("8"));
+        tag.set(&TagKey::ReleaseDate, Cow::from("1958-07-16"));
+        tag.set_multiple(
+            &TagKey::Artists,
+            &[Cow::from("Ahmad Jamal"), Cow::from("Israel Crosby")],
+        );
+        tag.write(&path).unwrap();
+
+        let mut tag = ApeTag::read_from_path(&path).unwrap().unwrap();
+        assert_eq!(
+            tag.get(&TagKey::TrackTitle).as_deref(),
+            Some("But Not for Me")
+        );
+        assert_eq!(tag.get(&TagKey::TrackNumber).as_deref(), Some("3"));
+        assert_eq!(tag.get(&TagKey::TotalTracks).as_deref(), Some("8"));
+        assert_eq!(tag.get(&TagKey::ReleaseYear).as_deref(), Some("1958"));
+        assert_eq!(
+            tag.get_multiple(&TagKey::Artists),
+            vec![Cow::from("Ahmad Jamal"), Cow::from("Israel Crosby")]
+        );
+        assert_eq!(
+            tag.fields()
+                .into_iter()
+                .find(|(key, _)| key == "Track")
+                .map(|(_, value)| value)
+                .as_deref(),
+            Some("3/8")
+        );
+
+        // The ID3v1 tag is kept at the end of the file.
+        let content = std::fs::read(&path).unwrap();
+        assert_eq!(&content[content.len() - 128..content.len() - 125], b"TAG");
+
+        tag.clear(&TagKey::TrackNumber);
+        assert!(tag.get(&TagKey::TotalTracks).is_none());
+        tag.set_strip(true);
+        tag.write(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), original_content);
+    }
+}
@@ -8,7 +8,11 @@
 
 //! Support for FLAC tags.
 
-use crate::tag::{Tag, TagKey, TagType};
+use crate::config::VorbisTotalFields;
+use crate::tag::{
+    parse_rating_stars, EmbeddedCueSheet, EmbeddedCueSheetTrack, StreamInfo, Tag, TagKey, TagType,
+    DEFAULT_RATING_EMAIL,
+};
 use crate::track::InvolvedPerson;
 use crate::util::parse_year_from_str;
 use std::borrow::Cow;
@@ -18,6 +22,37 @@ use std::path::Path;
 pub struct FlacTag {
     /// The underlying tag data.
     data: metaflac::Tag,
+    /// Field names that are written for the total number of tracks and discs.
+    total_fields: VorbisTotalFields,
+    /// Email address of the `RATING:<email>` field that is read and written.
+    rating_email: String,
+    /// Write the field names used by beets in addition to the canonical ones.
+    beets_fields: bool,
+}
+
+/// Parse the value of a Vorbis rating field and convert it to stars.
+///
+/// `FMPS_RATING` and MusicBrainz Picard's `RATING:<email>` fields always contain fractions of the
+/// maximum rating. In other fields, values from 0 to 1 are only fractions if they contain a
+/// decimal point (so that `RATING=1` is 1 star), values up to 5 are stars and values up to 100 are
+/// percentages.
+fn parse_vorbis_rating(field: &str, value: &str) -> Option<u8> {
+    let is_fraction_field = field == "FMPS_RATING" || field.starts_with("RATING:");
+    let value = value.trim();
+    let has_decimal_point = value.contains('.');
+    let value = value.parse::<f64>().ok()?;
+    let stars = match value {
+        value if (0.0..=1.0).contains(&value) && (is_fraction_field || has_decimal_point) => {
+            value * 5.0
+        }
+        _ if is_fraction_field => return None,
+        value if (1.0..=5.0).contains(&value) => value,
+        value if (5.0..=100.0).contains(&value) => value / 20.0,
+        _ => return None,
+    };
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let stars = stars.round() as u8;
+    (stars > 0).then_some(stars)
 }
 
 impl FlacTag {
@@ -25,13 +60,146 @@ impl FlacTag {
     pub fn new() -> Self {
         FlacTag {
             data: metaflac::Tag::new(),
+            total_fields: VorbisTotalFields::default(),
+            rating_email: DEFAULT_RATING_EMAIL.to_string(),
+            beets_fields: false,
         }
     }
 
     /// Read the FLAC tag from the path
     pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
         let data = metaflac::Tag::read_from_path(path)?;
-        Ok(FlacTag { data })
+        Ok(FlacTag {
+            data,
+            total_fields: VorbisTotalFields::default(),
+            rating_email: DEFAULT_RATING_EMAIL.to_string(),
+            beets_fields: false,
+        })
+    }
+
+    /// Set the field names that are written for the total number of tracks and discs.
+    pub fn set_total_fields(&mut self, total_fields: VorbisTotalFields) {
+        self.total_fields = total_fields;
+    }
+
+    /// Set the email address of the `RATING:<email>` field that is used for [`TagKey::Rating`].
+    ///
+    /// If the email address is empty, a plain `RATING` field is used instead.
+    pub fn set_rating_email(&mut self, email: &str) {
+        self.rating_email = email.to_string();
+    }
+
+    /// Set whether the field names used by beets (see [`Self::tag_key_to_beets_frames`]) are
+    /// written in addition to the canonical field names.
+    pub fn set_beets_fields(&mut self, beets_fields: bool) {
+        self.beets_fields = beets_fields;
+    }
+
+    /// Get the vorbis key names of the fields that contain ratings, in the order of precedence.
+    ///
+    /// The first field is the one that ratings are written to (in addition to `FMPS_RATING`).
+    fn rating_frames(&self) -> Vec<String> {
+        let mut frames = vec![];
+        if !self.rating_email.is_empty() {
+            frames.push(format!("RATING:{}", self.rating_email).to_ascii_uppercase());
+        }
+        frames.push("RATING".to_string());
+        frames.push("FMPS_RATING".to_string());
+        frames
+    }
+
+    /// Get the rating (in stars) from the first rating field that contains a valid value.
+    fn get_rating(&self) -> Option<u8> {
+        self.rating_frames().iter().find_map(|frame| {
+            self.data
+                .get_vorbis(frame)
+                .and_then(|mut values| values.next())
+                .and_then(|value| parse_vorbis_rating(frame, value))
+        })
+    }
+
+    /// Write the rating (in stars) to the rating field and `FMPS_RATING`, or remove all rating
+    /// fields.
+    fn set_rating(&mut self, stars: Option<u8>) {
+        let frames = self.rating_frames();
+        for frame in &frames {
+            self.data.remove_vorbis(frame);
+        }
+        if let Some(stars) = stars.filter(|stars| *stars > 0) {
+            // Always write a decimal point, so that the value is read as a fraction from a plain
+            // `RATING` field.
+            let value = format!("{:.1}", f64::from(stars) / 5.0);
+            self.data
+                .set_vorbis(frames[0].as_str(), vec![value.clone()]);
+            if frames[0] != "FMPS_RATING" {
+                self.data.set_vorbis("FMPS_RATING", vec![value]);
+            }
+        }
+    }
+
+    /// Get the alternative vorbis key name for a tag key, which is read if the field returned by
+    /// [`Self::tag_key_to_frame`] is not present.
+    fn tag_key_to_alternative_frame(key: &TagKey) -> Option<&'static str> {
+        match key {
+            TagKey::TotalDiscs => "DISCTOTAL".into(),
+            TagKey::TotalTracks => "TRACKTOTAL".into(),
+            _ => None,
+        }
+    }
+
+    /// Get the vorbis key names that beets (and partly older Picard versions) use for a tag key
+    /// instead of the canonical one.
+    ///
+    /// Most MusicBrainz fields (e.g., `MUSICBRAINZ_ALBUMID`) already use the same names. These
+    /// fields are read if none of the canonical fields are present, and are only written if
+    /// enabled with [`Self::set_beets_fields`].
+    fn tag_key_to_beets_frames(key: &TagKey) -> &'static [&'static str] {
+        match key {
+            TagKey::AlbumArtist => &["ALBUM ARTIST", "ALBUMARTIST_CREDIT"],
+            TagKey::Artist => &["ARTIST_CREDIT"],
+            TagKey::Comment => &["DESCRIPTION"],
+            TagKey::InitialKey => &["INITIALKEY"],
+            TagKey::RecordLabel => &["PUBLISHER"],
+            TagKey::ReleaseStatus => &["MUSICBRAINZ_ALBUMSTATUS"],
+            TagKey::ReleaseType => &["MUSICBRAINZ_ALBUMTYPE"],
+            _ => &[],
+        }
+    }
+
+    /// Get the vorbis key names that are written for a tag key and the ones that are removed
+    /// instead.
+    fn frames_to_write(&self, key: &TagKey) -> (Vec<&'static str>, Vec<&'static str>) {
+        let frame = Self::tag_key_to_frame(key);
+        let (mut frames, removed_frames) = match Self::tag_key_to_alternative_frame(key) {
+            None => (frame.into_iter().collect(), vec![]),
+            Some(alternative_frame) => {
+                let frame = frame.into_iter();
+                match self.total_fields {
+                    VorbisTotalFields::Totaltracks => (frame.collect(), vec![alternative_frame]),
+                    VorbisTotalFields::Tracktotal => (vec![alternative_frame], frame.collect()),
+                    VorbisTotalFields::Both => {
+                        (frame.chain([alternative_frame]).collect::<Vec<_>>(), vec![])
+                    }
+                }
+            }
+        };
+        if self.beets_fields {
+            frames.extend(Self::tag_key_to_beets_frames(key));
+        }
+        (frames, removed_frames)
+    }
+
+    /// Get the vorbis key name of the field that the value for the tag key is read from.
+    fn present_frame(&self, key: &TagKey) -> Option<&'static str> {
+        let frame = Self::tag_key_to_frame(key)?;
+        if self.data.get_vorbis(frame).is_some() {
+            return Some(frame);
+        }
+        Self::tag_key_to_alternative_frame(key)
+            .into_iter()
+            .chain(Self::tag_key_to_beets_frames(key).iter().copied())
+            .find(|alternative_frame| self.data.get_vorbis(alternative_frame).is_some())
+            .or(Some(frame))
     }
 
     /// Get the vorbis key name for a tag key.
@@ -103,7 +271,7 @@ impl FlacTag {
             TagKey::Podcast => None,
             TagKey::PodcastUrl => None,
             TagKey::Producer => "PRODUCER".into(),
-            TagKey::Rating => None, // TODO: Add mapping to "RATING:user@email"
+            TagKey::Rating => None, // Handled separately, see `Self::rating_frames`.
             TagKey::RecordLabel => "LABEL".into(),
             TagKey::ReleaseCountry => "RELEASECOUNTRY".into(),
             TagKey::ReleaseDate => "DATE".into(),
@@ -118,13 +286,15 @@ impl FlacTag {
             TagKey::ReplayGainTrackGain => "REPLAYGAIN_TRACK_GAIN".into(),
             TagKey::ReplayGainTrackPeak => "REPLAYGAIN_TRACK_PEAK".into(),
             TagKey::ReplayGainTrackRange => "REPLAYGAIN_TRACK_RANGE".into(),
+            TagKey::R128AlbumGain => "R128_ALBUM_GAIN".into(),
+            TagKey::R128TrackGain => "R128_TRACK_GAIN".into(),
             TagKey::Script => "SCRIPT".into(),
             TagKey::ShowName => None,
             TagKey::ShowNameSortOrder => None,
             TagKey::ShowMovement => "SHOWMOVEMENT".into(),
             TagKey::Subtitle => "SUBTITLE".into(),
-            TagKey::TotalDiscs => "TOTALDISCS".into(), // TODO: Also map this to DISCTOTAL.
-            TagKey::TotalTracks => "TOTALTRACKS".into(), // TODO: Also map this to TRACKTOTAL.
+            TagKey::TotalDiscs => "TOTALDISCS".into(),
+            TagKey::TotalTracks => "TOTALTRACKS".into(),
             TagKey::TrackNumber => "TRACKNUMBER".into(),
             TagKey::TrackTitle => "TITLE".into(),
             TagKey::TrackTitleSortOrder => "TITLESORT".into(),
@@ -140,8 +310,30 @@ impl Tag for FlacTag {
         TagType::Flac
     }
 
+    fn field_name(&self, key: &TagKey) -> Option<String> {
+        if key == &TagKey::Rating {
+            return self.rating_frames().into_iter().next();
+        }
+        self.present_frame(key).map(ToString::to_string)
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = self
+            .data
+            .vorbis_comments()
+            .into_iter()
+            .flat_map(|vorbis_comments| &vorbis_comments.comments)
+            .flat_map(|(name, values)| values.iter().map(|value| (name.clone(), value.clone())))
+            .collect::<Vec<_>>();
+        fields.sort();
+        fields
+    }
+
     fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
-        Self::tag_key_to_frame(key)
+        if key == &TagKey::Rating {
+            return self.get_rating().map(|stars| Cow::from(stars.to_string()));
+        }
+        self.present_frame(key)
             .and_then(|key| self.data.get_vorbis(key))
             .and_then(|mut iterator| iterator.next())
             .map(Cow::from)
@@ -153,11 +345,35 @@ impl Tag for FlacTag {
             })
     }
 
+    fn get_multiple<'a>(&'a self, key: &'a TagKey) -> Vec<Cow<'a, str>> {
+        let values = self
+            .present_frame(key)
+            .and_then(|key| self.data.get_vorbis(key))
+            .map(|iterator| iterator.map(Cow::from).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if values.is_empty() {
+            self.get(key).into_iter().collect()
+        } else {
+            values
+        }
+    }
+
     fn set(&mut self, key: &TagKey, value: Cow<'_, str>) {
         self.set_multiple(key, &[value]);
     }
 
     fn set_multiple<'a>(&'a mut self, key: &TagKey, values: &[Cow<'a, str>]) {
+        if key == &TagKey::Rating {
+            match values
+                .first()
+                .map(|value| (value, parse_rating_stars(value)))
+            {
+                Some((_, Some(stars))) => self.set_rating(Some(stars)),
+                Some((value, None)) => log::warn!("Ignoring invalid rating {value:?}"),
+                None => self.set_rating(None),
+            }
+            return;
+        }
         if let Some(frame) = Self::tag_key_to_frame(key) {
             match key {
                 TagKey::Performer(instrument) => {
@@ -177,17 +393,44 @@ impl Tag for FlacTag {
                         .collect::<Vec<_>>();
                     self.data.set_vorbis(frame, items);
                 }
-                _ => self.data.set_vorbis(frame, values.to_vec()),
+                _ => {
+                    let (frames, removed_frames) = self.frames_to_write(key);
+                    for frame in frames {
+                        self.data.set_vorbis(frame, values.to_vec());
+                    }
+                    for frame in removed_frames {
+                        self.data.remove_vorbis(frame);
+                    }
+                }
             }
         }
     }
 
     fn clear(&mut self, key: &TagKey) {
-        if let Some(frame) = Self::tag_key_to_frame(key) {
+        if key == &TagKey::Rating {
+            self.set_rating(None);
+            return;
+        }
+        // The beets fields may contain different values (e.g., the credited artist), so they are
+        // only removed if they are written, too.
+        let beets_frames = if self.beets_fields {
+            Self::tag_key_to_beets_frames(key)
+        } else {
+            &[]
+        };
+        for frame in Self::tag_key_to_frame(key)
+            .into_iter()
+            .chain(Self::tag_key_to_alternative_frame(key))
+            .chain(beets_frames.iter().copied())
+        {
             self.data.remove_vorbis(frame);
         }
     }
 
+    fn maybe_as_flac_mut(&mut self) -> Option<&mut FlacTag> {
+        Some(self)
+    }
+
     fn write(&mut self, path: &Path) -> crate::Result<()> {
         self.data.write_to_path(path)?;
         Ok(())
@@ -198,6 +441,43 @@ impl Tag for FlacTag {
             .and_then(|key| self.data.get_vorbis(key))
             .map(|iterator| iterator.map(parse_performer_value).collect())
     }
+
+    fn stream_info(&self) -> Option<StreamInfo> {
+        self.data.get_streaminfo().map(|info| StreamInfo {
+            sample_rate: info.sample_rate,
+            bits_per_sample: info.bits_per_sample,
+            channels: info.num_channels,
+            // A value of zero means that the total number of samples is unknown.
+            total_samples: (info.total_samples > 0).then_some(info.total_samples),
+        })
+    }
+
+    fn embedded_cuesheet(&self) -> Option<EmbeddedCueSheet> {
+        self.data
+            .get_blocks(metaflac::BlockType::CueSheet)
+            .find_map(|block| match block {
+                metaflac::Block::CueSheet(cuesheet) => Some(cuesheet),
+                _ => None,
+            })
+            .map(|cuesheet| EmbeddedCueSheet {
+                catalog_number: Some(cuesheet.catalog_num.trim_end_matches('\0').trim())
+                    .filter(|value| !value.is_empty())
+                    .map(ToString::to_string),
+                tracks: cuesheet
+                    .tracks
+                    .iter()
+                    // The lead-out track has the number 170 on CD-DA and 255 otherwise.
+                    .filter(|track| track.is_audio && track.number != 170 && track.number != 255)
+                    .map(|track| EmbeddedCueSheetTrack {
+                        number: track.number,
+                        offset: track.offset,
+                        isrc: Some(track.isrc.trim_end_matches('\0').trim())
+                            .filter(|value| !value.is_empty())
+                            .map(ToString::to_string),
+                    })
+                    .collect(),
+            })
+    }
 }
 
 /// Parse a performer value in the form `involvee (involvement)`.
@@ -294,6 +574,20 @@ mod tests {
         assert_eq!(tag.tag_type(), TagType::Flac);
     }
 
+    #[test]
+    fn test_stream_info() {
+        let tag = FlacTag::read_from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/picard-2.12.3/track.flac"
+        ))
+        .unwrap();
+        let stream_info = tag.stream_info().unwrap();
+        assert!(stream_info.sample_rate > 0);
+        assert!(stream_info.channels > 0);
+        assert!(stream_info.duration().is_some());
+        assert!(tag.embedded_cuesheet().is_none());
+    }
+
     macro_rules! add_tests {
         ($tagkey:expr, $fnsuffix:ident) => {
             paste! {
@@ -412,7 +706,6 @@ mod tests {
     //add_tests!(&TagKey::Podcast, podcast);
     //add_tests!(&TagKey::PodcastUrl, podcasturl);
     add_tests!(&TagKey::Producer, producer);
-    //add_tests!(&TagKey::Rating, rating);
     add_tests!(&TagKey::RecordLabel, recordlabel);
     add_tests!(&TagKey::ReleaseCountry, releasecountry);
     add_tests!(&TagKey::ReleaseDate, releasedate);
@@ -430,6 +723,8 @@ mod tests {
     add_tests!(&TagKey::ReplayGainTrackGain, replaygaintrackgain);
     add_tests!(&TagKey::ReplayGainTrackPeak, replaygaintrackpeak);
     add_tests!(&TagKey::ReplayGainTrackRange, replaygaintrackrange);
+    add_tests!(&TagKey::R128AlbumGain, r128albumgain);
+    add_tests!(&TagKey::R128TrackGain, r128trackgain);
     add_tests!(&TagKey::Script, script);
     //add_tests!(&TagKey::ShowName, showname);
     //add_tests!(&TagKey::ShowNameSortOrder, shownamesortorder);
@@ -443,4 +738,128 @@ mod tests {
     add_tests!(&TagKey::ArtistWebsite, artistwebsite);
     add_tests!(&TagKey::WorkTitle, worktitle);
     add_tests!(&TagKey::Writer, writer);
+
+    #[test]
+    fn test_total_fields() {
+        let mut tag = FlacTag::new();
+        tag.data.set_vorbis("TRACKTOTAL", vec!["12"]);
+        tag.data.set_vorbis("DISCTOTAL", vec!["2"]);
+        assert_eq!(tag.get(&TagKey::TotalTracks).as_deref(), Some("12"));
+        assert_eq!(tag.get(&TagKey::TotalDiscs).as_deref(), Some("2"));
+        assert_eq!(
+            tag.field_name(&TagKey::TotalTracks).as_deref(),
+            Some("TRACKTOTAL")
+        );
+
+        tag.set(&TagKey::TotalTracks, Cow::from("10"));
+        assert_eq!(
+            tag.data.get_vorbis("TOTALTRACKS").unwrap().next(),
+            Some("10")
+        );
+        assert!(tag.data.get_vorbis("TRACKTOTAL").is_none());
+
+        tag.set_total_fields(VorbisTotalFields::Both);
+        tag.set(&TagKey::TotalDiscs, Cow::from("3"));
+        assert_eq!(tag.data.get_vorbis("TOTALDISCS").unwrap().next(), Some("3"));
+        assert_eq!(tag.data.get_vorbis("DISCTOTAL").unwrap().next(), Some("3"));
+
+        tag.clear(&TagKey::TotalDiscs);
+        assert!(tag.get(&TagKey::TotalDiscs).is_none());
+    }
+
+    #[test]
+    fn test_rating() {
+        let mut tag = FlacTag::new();
+        assert!(tag.get(&TagKey::Rating).is_none());
+
+        tag.data.set_vorbis("FMPS_RATING", vec!["0.8"]);
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("4"));
+
+        tag.set(&TagKey::Rating, Cow::from("3"));
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("3"));
+        assert_eq!(
+            tag.data
+                .get_vorbis("RATING:USERS@MUSICBRAINZ.ORG")
+                .unwrap()
+                .next(),
+            Some("0.6")
+        );
+        assert_eq!(
+            tag.data.get_vorbis("FMPS_RATING").unwrap().next(),
+            Some("0.6")
+        );
+
+        tag.clear(&TagKey::Rating);
+        assert!(tag.get(&TagKey::Rating).is_none());
+
+        tag.set_rating_email("");
+        tag.data.set_vorbis("RATING", vec!["60"]);
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("3"));
+        tag.data.set_vorbis("RATING", vec!["1"]);
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("1"));
+
+        tag.set(&TagKey::Rating, Cow::from("5"));
+        assert_eq!(tag.data.get_vorbis("RATING").unwrap().next(), Some("1.0"));
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("5"));
+    }
+
+    #[test]
+    fn test_parse_vorbis_rating() {
+        assert_eq!(parse_vorbis_rating("RATING", "1"), Some(1));
+        assert_eq!(parse_vorbis_rating("RATING", "1.0"), Some(5));
+        assert_eq!(parse_vorbis_rating("RATING", "0.6"), Some(3));
+        assert_eq!(parse_vorbis_rating("RATING", "4"), Some(4));
+        assert_eq!(parse_vorbis_rating("RATING", "80"), Some(4));
+        assert_eq!(parse_vorbis_rating("FMPS_RATING", "1"), Some(5));
+        assert_eq!(parse_vorbis_rating("FMPS_RATING", "4"), None);
+        assert_eq!(
+            parse_vorbis_rating("RATING:USERS@MUSICBRAINZ.ORG", "0.4"),
+            Some(2)
+        );
+        assert_eq!(parse_vorbis_rating("RATING", "0"), None);
+    }
+
+    #[test]
+    fn test_beets_fields() {
+        let mut tag = FlacTag::new();
+        tag.data
+            .set_vorbis("ALBUMARTIST_CREDIT", vec!["Credited Artist"]);
+        tag.data.set_vorbis("MUSICBRAINZ_ALBUMTYPE", vec!["album"]);
+        assert_eq!(
+            tag.get(&TagKey::AlbumArtist).as_deref(),
+            Some("Credited Artist")
+        );
+        assert_eq!(tag.get(&TagKey::ReleaseType).as_deref(), Some("album"));
+
+        tag.set(&TagKey::AlbumArtist, Cow::from("Album Artist"));
+        assert_eq!(
+            tag.get(&TagKey::AlbumArtist).as_deref(),
+            Some("Album Artist")
+        );
+        assert_eq!(
+            tag.data.get_vorbis("ALBUMARTIST_CREDIT").unwrap().next(),
+            Some("Credited Artist")
+        );
+
+        tag.clear(&TagKey::ReleaseType);
+        assert_eq!(
+            tag.data.get_vorbis("MUSICBRAINZ_ALBUMTYPE").unwrap().next(),
+            Some("album")
+        );
+
+        tag.set_beets_fields(true);
+        tag.set(&TagKey::AlbumArtist, Cow::from("Album Artist"));
+        assert_eq!(
+            tag.data.get_vorbis("ALBUMARTIST_CREDIT").unwrap().next(),
+            Some("Album Artist")
+        );
+        assert_eq!(
+            tag.data.get_vorbis("ALBUM ARTIST").unwrap().next(),
+            Some("Album Artist")
+        );
+
+        tag.clear(&TagKey::AlbumArtist);
+        assert!(tag.get(&TagKey::AlbumArtist).is_none());
+        assert!(tag.data.get_vorbis("ALBUMARTIST_CREDIT").is_none());
+    }
 }
@@ -8,13 +8,14 @@
 
 //! Support for ID3 tags.
 
-use crate::tag::{Tag, TagKey, TagType};
+use crate::config::{RatingConfig, RatingScale};
+use crate::tag::{parse_rating_stars, Tag, TagKey, TagType, DEFAULT_RATING_EMAIL};
 use crate::track::InvolvedPerson;
-use crate::util::parse_year_from_str;
+use crate::util::{normalize_date_str, parse_year_from_str};
 use id3::{
     frame::{
-        Comment, ExtendedText, Frame, InvolvedPeopleList, InvolvedPeopleListItem,
-        UniqueFileIdentifier,
+        Comment, ExtendedText, Frame, InvolvedPeopleList, InvolvedPeopleListItem, Popularimeter,
+        UniqueFileIdentifier, Unknown,
     },
     Content, TagLike,
 };
@@ -41,14 +42,22 @@ enum FrameId<'a> {
     CombinedText(&'a str, CombinedTextPart),
     /// Extended Text frame (`TXXX`).
     ExtendedText(&'a str),
+    /// Extended Text frame (`TXXX`) whose value is also written to a text frame, but only read
+    /// from the extended text frame (e.g., the work title for iTunes in `TIT1`).
+    MirroredExtendedText(&'a str, &'a str),
     /// Unique File Identifier frame (`UFID`).
     UniqueFileIdentifier(&'a str),
-    /// Comment frame (`COMM`).
-    Comment(&'a str),
+    /// Comment frame (`COMM`) with the configured language and description.
+    Comment,
+    /// Popularimeter frame (`POPM`) with the configured email address.
+    Popularimeter,
     /// Involved Person List in a `IPLS`/`TMCL`/`TIPL` frame.
     InvolvedPersonList(&'a str),
     /// Involved Person in a `IPLS`/`TMCL`/`TIPL` frame.
     InvolvedPerson(&'a str, &'a str),
+    /// A date that is split into a year frame (`YYYY`) and a date frame (`DDMM`), as in ID3v2.3
+    /// (`TYER` and `TDAT`).
+    SplitDate(&'a str, &'a str),
     /// A valued derived from another tag.
     DerivedValue(TagKey, fn(&str) -> Option<String>),
 }
@@ -56,11 +65,52 @@ enum FrameId<'a> {
 const IPLS_NON_PERFORMER_INVOLVEMENTS: [&str; 5] =
     ["arranger", "engineer", "DJ-mix", "mix", "producer"];
 
+/// Container format in which the ID3 tag is stored.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Container {
+    /// Plain ID3 tag at the start of the file (e.g., MP3).
+    Plain,
+    /// `id3 ` chunk in a RIFF/WAVE file.
+    Wav,
+    /// `ID3 ` chunk in an AIFF file.
+    Aiff,
+}
+
+impl Container {
+    /// Detect the container format from the file extension.
+    fn from_path(path: &Path) -> Self {
+        let extension = path.extension().map(std::ffi::OsStr::to_ascii_lowercase);
+        match extension.as_ref().and_then(|ext| ext.to_str()) {
+            Some("wav") => Container::Wav,
+            Some("aif" | "aiff") => Container::Aiff,
+            _ => Container::Plain,
+        }
+    }
+}
+
+/// Language of the comment frame if none is configured.
+const DEFAULT_COMMENT_LANGUAGE: &str = "eng";
+
+/// Description of the comment frame if none is configured.
+const DEFAULT_COMMENT_DESCRIPTION: &str = "description";
+
 /// ID3 tag (version 2).
 #[derive(Debug)]
 pub struct ID3v2Tag {
     /// The underlying tag data.
     data: id3::Tag,
+    /// Match the descriptions of user-defined text frames (`TXXX`) case-insensitively.
+    case_insensitive_descriptions: bool,
+    /// Language of the comment frame (`COMM`) that is written.
+    comment_language: String,
+    /// Description of the comment frame (`COMM`) that is read and written.
+    comment_description: String,
+    /// Email address of the popularimeter frame (`POPM`) that is read and written.
+    rating_email: String,
+    /// How popularimeter ratings are mapped to stars.
+    rating_scale: RatingScale,
+    /// Store the grouping in `GRP1` and the work title in `TIT1` (iTunes-compatible).
+    itunes_grouping: bool,
 }
 
 impl ID3v2Tag {
@@ -68,19 +118,162 @@ impl ID3v2Tag {
     pub fn with_version(version: id3::Version) -> Self {
         ID3v2Tag {
             data: id3::Tag::with_version(version),
+            case_insensitive_descriptions: false,
+            comment_language: DEFAULT_COMMENT_LANGUAGE.to_string(),
+            comment_description: DEFAULT_COMMENT_DESCRIPTION.to_string(),
+            rating_email: DEFAULT_RATING_EMAIL.to_string(),
+            rating_scale: RatingScale::Linear,
+            itunes_grouping: false,
         }
     }
 
     /// Read the ID3 tag from the path
+    ///
+    /// For WAV and AIFF files, the tag is read from the ID3 chunk.
     pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
-        let data = id3::Tag::read_from_path(path).or_else(|err| {
+        let path = path.as_ref();
+        let result = match Container::from_path(path) {
+            Container::Plain => id3::Tag::read_from_path(path),
+            Container::Wav => id3::Tag::read_from_wav_path(path),
+            Container::Aiff => id3::Tag::read_from_aiff_path(path),
+        };
+        let data = result.or_else(|err| {
             if matches!(err.kind, id3::ErrorKind::NoTag) {
                 Ok(id3::Tag::new())
             } else {
                 Err(err)
             }
         })?;
-        Ok(ID3v2Tag { data })
+        Ok(ID3v2Tag {
+            data,
+            case_insensitive_descriptions: false,
+            comment_language: DEFAULT_COMMENT_LANGUAGE.to_string(),
+            comment_description: DEFAULT_COMMENT_DESCRIPTION.to_string(),
+            rating_email: DEFAULT_RATING_EMAIL.to_string(),
+            rating_scale: RatingScale::Linear,
+            itunes_grouping: false,
+        })
+    }
+
+    /// Match the descriptions of user-defined text frames (`TXXX`) case-insensitively and ignore
+    /// trailing null characters, so that frames written by other tools (e.g.,
+    /// `MUSICBRAINZ ALBUM ID` instead of `MusicBrainz Album Id`) are found.
+    pub fn set_case_insensitive_descriptions(&mut self, enabled: bool) {
+        self.case_insensitive_descriptions = enabled;
+    }
+
+    /// Set the language and description of the comment frame (`COMM`) that is used for
+    /// [`TagKey::Comment`].
+    ///
+    /// Comments with a different language (e.g., `XXX`) or a different description (e.g., the
+    /// empty description that Picard writes) are read as fallback.
+    pub fn set_comment_frame(&mut self, language: &str, description: &str) {
+        self.comment_language = language.to_string();
+        self.comment_description = description.to_string();
+    }
+
+    /// Get the text of the comment frame, preferring the configured description, then an empty
+    /// description, and then the configured language.
+    fn get_comment(&self) -> Option<&str> {
+        self.data
+            .comments()
+            .min_by_key(|comment| {
+                (
+                    comment.description != self.comment_description,
+                    !comment.description.is_empty(),
+                    comment.lang != self.comment_language,
+                )
+            })
+            .map(|comment| comment.text.as_str())
+    }
+
+    /// Set the email address and scale of the popularimeter frame (`POPM`) that is used for
+    /// [`TagKey::Rating`].
+    ///
+    /// Ratings of other users are neither read nor modified.
+    pub fn set_rating_options(&mut self, config: &RatingConfig) {
+        self.rating_email.clone_from(&config.email);
+        self.rating_scale = config.scale;
+    }
+
+    /// Store the grouping in the `GRP1` frame and also write the work title to the `TIT1` frame
+    /// (like iTunes does) instead of storing the grouping in `TIT1` (like Picard does by default).
+    /// The work title is always stored in `TXXX:WORK`.
+    pub fn set_itunes_grouping(&mut self, enabled: bool) {
+        self.itunes_grouping = enabled;
+    }
+
+    /// Get all popularimeter frames.
+    fn popularimeters(&self) -> impl Iterator<Item = &Popularimeter> {
+        self.data
+            .frames()
+            .filter_map(|frame| match frame.content() {
+                Content::Popularimeter(popularimeter) => Some(popularimeter),
+                _ => None,
+            })
+    }
+
+    /// Get the rating (in stars) from the popularimeter frame of the configured email address.
+    ///
+    /// Popularimeter frames of other email addresses are ignored, because they are kept when the
+    /// rating is cleared.
+    fn get_rating(&self) -> Option<u8> {
+        self.popularimeters()
+            .find(|popularimeter| popularimeter.user == self.rating_email)
+            .and_then(|popularimeter| self.rating_scale.stars_from_popm(popularimeter.rating))
+    }
+
+    /// Replace the popularimeter frame of the configured email address. The play counter is kept.
+    fn set_rating(&mut self, stars: Option<u8>) {
+        let counter = self
+            .popularimeters()
+            .find(|popularimeter| popularimeter.user == self.rating_email)
+            .map_or(0, |popularimeter| popularimeter.counter);
+        let other_frames = self
+            .data
+            .frames()
+            .filter(|frame| {
+                matches!(frame.content(), Content::Popularimeter(popularimeter) if popularimeter.user != self.rating_email)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let _unused = self.data.remove("POPM");
+        for frame in other_frames {
+            let _unused = self.data.add_frame(frame);
+        }
+
+        if let Some(stars) = stars.filter(|stars| *stars > 0) {
+            let _unused = self.data.add_frame(Frame::with_content(
+                "POPM",
+                Content::Popularimeter(Popularimeter {
+                    user: self.rating_email.clone(),
+                    rating: self.rating_scale.popm_from_stars(stars),
+                    counter,
+                }),
+            ));
+        }
+    }
+
+    /// Separator of multiple values in user-defined text frames.
+    ///
+    /// ID3v2.4 separates them by null characters, like in other text frames. Earlier versions do
+    /// not support multiple values, so they are separated by slashes (like Picard does).
+    fn extended_text_separator(&self) -> char {
+        match self.data.version() {
+            id3::Version::Id3v24 => '\0',
+            id3::Version::Id3v22 | id3::Version::Id3v23 => '/',
+        }
+    }
+
+    /// Returns `true` if the description of a user-defined text frame matches the expected one.
+    fn description_matches(&self, description: &str, expected: &str) -> bool {
+        if self.case_insensitive_descriptions {
+            description
+                .trim_end_matches('\0')
+                .eq_ignore_ascii_case(expected)
+        } else {
+            description == expected
+        }
     }
 
     /// Get the ID3 frame for a tag key.
@@ -105,7 +298,7 @@ impl ID3v2Tag {
             TagKey::Barcode => FrameId::ExtendedText("BARCODE").into(),
             TagKey::Bpm => FrameId::Text("TBPM").into(),
             TagKey::CatalogNumber => FrameId::ExtendedText("CATALOGNUMBER").into(),
-            TagKey::Comment => FrameId::Comment("description").into(), // TODO: Check if "description" is meant literally here.
+            TagKey::Comment => FrameId::Comment.into(),
             TagKey::Compilation => FrameId::Text("TCMP").into(),
             TagKey::Composer => FrameId::Text("TCOM").into(),
             TagKey::ComposerSortOrder => FrameId::Text("TSOC").into(),
@@ -126,7 +319,8 @@ impl ID3v2Tag {
             },
             TagKey::GaplessPlayback => None,
             TagKey::Genre => FrameId::Text("TCON").into(),
-            TagKey::Grouping => FrameId::Text("TIT1").into(), // TODO: Add mapping to "GRP1", too?
+            TagKey::Grouping if self.itunes_grouping => FrameId::Text("GRP1").into(),
+            TagKey::Grouping => FrameId::Text("TIT1").into(),
             TagKey::InitialKey => FrameId::Text("TKEY").into(),
             TagKey::Isrc => FrameId::Text("TSRC").into(),
             TagKey::Language => FrameId::Text("TLAN").into(),
@@ -204,14 +398,14 @@ impl ID3v2Tag {
                 id3::Version::Id3v23 => FrameId::InvolvedPerson("IPLS", "producer").into(),
                 id3::Version::Id3v24 => FrameId::InvolvedPerson("TIPL", "producer").into(),
             },
-            TagKey::Rating => FrameId::Text("POPM").into(),
+            TagKey::Rating => FrameId::Popularimeter.into(),
             TagKey::RecordLabel => FrameId::Text("TPUB").into(),
             TagKey::ReleaseCountry => {
                 FrameId::ExtendedText("MusicBrainz Album Release Country").into()
             }
             TagKey::ReleaseDate => match self.data.version() {
                 id3::Version::Id3v22 => None,
-                id3::Version::Id3v23 => FrameId::Text("TDAT").into(),
+                id3::Version::Id3v23 => FrameId::SplitDate("TYER", "TDAT").into(),
                 id3::Version::Id3v24 => FrameId::Text("TDRC").into(),
             },
             TagKey::ReleaseYear => match self.data.version() {
@@ -233,6 +427,7 @@ impl ID3v2Tag {
             TagKey::ReplayGainTrackGain => FrameId::ExtendedText("REPLAYGAIN_TRACK_GAIN").into(),
             TagKey::ReplayGainTrackPeak => FrameId::ExtendedText("REPLAYGAIN_TRACK_PEAK").into(),
             TagKey::ReplayGainTrackRange => FrameId::ExtendedText("REPLAYGAIN_TRACK_RANGE").into(),
+            TagKey::R128AlbumGain | TagKey::R128TrackGain => None,
             TagKey::Script => FrameId::ExtendedText("SCRIPT").into(),
             TagKey::ShowName => None,
             TagKey::ShowNameSortOrder => None,
@@ -244,7 +439,10 @@ impl ID3v2Tag {
             TagKey::TrackTitle => FrameId::Text("TIT2").into(),
             TagKey::TrackTitleSortOrder => FrameId::Text("TSOT").into(),
             TagKey::ArtistWebsite => FrameId::Text("WOAR").into(),
-            TagKey::WorkTitle => FrameId::ExtendedText("WORK").into(), // TODO: Add mapping to "TIT1", too?
+            TagKey::WorkTitle if self.itunes_grouping => {
+                FrameId::MirroredExtendedText("WORK", "TIT1").into()
+            }
+            TagKey::WorkTitle => FrameId::ExtendedText("WORK").into(),
 
             TagKey::Writer => FrameId::ExtendedText("Writer").into(),
         }
@@ -277,10 +475,32 @@ impl ID3v2Tag {
     fn get_extended_texts<'a>(&'a self, description: &'a str) -> impl Iterator<Item = &'a str> {
         self.data
             .extended_texts()
-            .filter(move |extended_text| extended_text.description == description)
+            .filter(move |extended_text| {
+                self.description_matches(&extended_text.description, description)
+            })
             .map(|extended_text| extended_text.value.as_str())
     }
 
+    /// Remove all extended text frames with the given description.
+    fn remove_extended_texts(&mut self, description: &str) {
+        if !self.case_insensitive_descriptions {
+            self.data.remove_extended_text(Some(description), None);
+            return;
+        }
+
+        let descriptions = self
+            .data
+            .extended_texts()
+            .filter(|extended_text| {
+                self.description_matches(&extended_text.description, description)
+            })
+            .map(|extended_text| extended_text.description.clone())
+            .collect::<Vec<_>>();
+        for description in descriptions {
+            self.data.remove_extended_text(Some(&description), None);
+        }
+    }
+
     /// Get the content of unique file identifier frames as byte slices.
     fn get_unique_file_identifiers<'a>(
         &'a self,
@@ -311,6 +531,40 @@ impl ID3v2Tag {
         })
     }
 
+    /// Get a date that is split into a year frame and a `DDMM` date frame.
+    fn get_split_date<'a>(&'a self, year_id: &'a str, date_id: &'a str) -> Option<Cow<'a, str>> {
+        let year = self.get_frames(year_id).next()?;
+        let day_and_month = self
+            .get_frames(date_id)
+            .next()
+            .filter(|value| value.len() == 4 && value.bytes().all(|c| c.is_ascii_digit()));
+        match day_and_month {
+            Some(value) if year.len() == 4 => {
+                let (day, month) = value.split_at(2);
+                Some(Cow::from(format!("{year}-{month}-{day}")))
+            }
+            _ => Some(Cow::from(year)),
+        }
+    }
+
+    /// Set a date that is split into a year frame and a `DDMM` date frame.
+    ///
+    /// Values that are not a valid date are written to the year frame unchanged.
+    fn set_split_date(&mut self, year_id: &str, date_id: &str, value: &str) {
+        let _unused = self.data.remove(date_id);
+        let Some(date) = normalize_date_str(value) else {
+            self.data.set_text(year_id, value);
+            return;
+        };
+
+        let mut parts = date.split('-');
+        let year = parts.next().unwrap_or_default();
+        self.data.set_text(year_id, year);
+        if let (Some(month), Some(day)) = (parts.next(), parts.next()) {
+            self.data.set_text(date_id, format!("{day}{month}"));
+        }
+    }
+
     /// Migrate this tag to the given ID3 version.
     pub fn migrate_to(&mut self, new_version: id3::Version) {
         let version = self.data.version();
@@ -343,12 +597,85 @@ impl ID3v2Tag {
             let _unused = self.data.add_frame(new_frame);
         }
     }
+
+    /// Set the relative volume adjustment (`RVA2`) frame with the given identification (e.g.,
+    /// `track` or `album`) from a ReplayGain gain (in dB) and peak.
+    ///
+    /// `RVA2` frames only exist in ID3v2.4, so nothing is written for older versions. If `gain` is
+    /// `None`, the frame is removed.
+    pub fn set_relative_volume_adjustment(
+        &mut self,
+        identification: &str,
+        gain: Option<f64>,
+        peak: Option<f64>,
+    ) {
+        if self.data.version() != id3::Version::Id3v24 {
+            return;
+        }
+
+        let frames = self.data.frames_vec_mut();
+        frames.retain(|frame| {
+            frame.id() != "RVA2" || rva2_identification(frame) != Some(identification.as_bytes())
+        });
+        if let Some(gain) = gain {
+            frames.push(Frame::with_content(
+                "RVA2",
+                Content::Unknown(Unknown {
+                    data: rva2_frame_data(identification, gain, peak),
+                    version: id3::Version::Id3v24,
+                }),
+            ));
+        }
+    }
+}
+
+/// Returns the identification of an `RVA2` frame.
+fn rva2_identification(frame: &Frame) -> Option<&[u8]> {
+    match frame.content() {
+        Content::Unknown(unknown) => unknown.data.split(|&byte| byte == 0).next(),
+        _ => None,
+    }
+}
+
+/// Encode the content of an `RVA2` frame that adjusts the master volume.
+///
+/// The volume adjustment is stored as a 16-bit fixed point number (in units of 1/512 dB) and the
+/// peak as 16-bit unsigned integer (see the [ID3v2.4 frame specification][id3v24-frames]).
+///
+/// [id3v24-frames]: https://id3.org/id3v2.4.0-frames
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rva2_frame_data(identification: &str, gain: f64, peak: Option<f64>) -> Vec<u8> {
+    /// Channel type of the master volume.
+    const MASTER_VOLUME: u8 = 0x01;
+
+    let adjustment = (gain * 512.0)
+        .round()
+        .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16;
+    let mut data = identification.as_bytes().to_vec();
+    data.push(0);
+    data.push(MASTER_VOLUME);
+    data.extend_from_slice(&adjustment.to_be_bytes());
+    match peak {
+        Some(peak) => {
+            let peak = (peak * 32768.0).round().clamp(0.0, f64::from(u16::MAX)) as u16;
+            data.push(16);
+            data.extend_from_slice(&peak.to_be_bytes());
+        }
+        None => data.push(0),
+    }
+    data
 }
 
 impl Default for ID3v2Tag {
     fn default() -> Self {
         ID3v2Tag {
             data: id3::Tag::with_version(id3::Version::Id3v23),
+            case_insensitive_descriptions: false,
+            comment_language: DEFAULT_COMMENT_LANGUAGE.to_string(),
+            comment_description: DEFAULT_COMMENT_DESCRIPTION.to_string(),
+            rating_email: DEFAULT_RATING_EMAIL.to_string(),
+            rating_scale: RatingScale::Linear,
+            itunes_grouping: false,
         }
     }
 }
@@ -362,26 +689,78 @@ impl Tag for ID3v2Tag {
         }
     }
 
+    fn field_name(&self, key: &TagKey) -> Option<String> {
+        self.tag_key_to_frame(key)
+            .and_then(|frame_id| match frame_id {
+                FrameId::Text(id)
+                | FrameId::CombinedText(id, _)
+                | FrameId::InvolvedPersonList(id) => Some(id.to_string()),
+                FrameId::SplitDate(year_id, date_id) => Some(format!("{year_id}+{date_id}")),
+                FrameId::ExtendedText(description)
+                | FrameId::MirroredExtendedText(description, _) => {
+                    Some(format!("TXXX:{description}"))
+                }
+                FrameId::UniqueFileIdentifier(owner) => Some(format!("UFID:{owner}")),
+                FrameId::Comment => Some(format!("COMM:{}", self.comment_description)),
+                FrameId::Popularimeter => Some(format!("POPM:{}", self.rating_email)),
+                FrameId::InvolvedPerson(id, involvement) => Some(format!("{id}:{involvement}")),
+                FrameId::DerivedValue(original_key, _) => self.field_name(&original_key),
+            })
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        self.data
+            .frames()
+            .flat_map(|frame| {
+                let id = frame.id();
+                match frame.content() {
+                    Content::Text(text) => text
+                        .split('\0')
+                        .map(|value| (id.to_string(), value.to_string()))
+                        .collect(),
+                    Content::Link(link) => vec![(id.to_string(), link.clone())],
+                    Content::ExtendedText(extended_text) => vec![(
+                        format!("{id}:{}", extended_text.description),
+                        extended_text.value.clone(),
+                    )],
+                    Content::ExtendedLink(extended_link) => vec![(
+                        format!("{id}:{}", extended_link.description),
+                        extended_link.link.clone(),
+                    )],
+                    Content::Comment(comment) => vec![(
+                        format!("{id}:{}", comment.description),
+                        comment.text.clone(),
+                    )],
+                    Content::UniqueFileIdentifier(ufid) => vec![(
+                        format!("{id}:{}", ufid.owner_identifier),
+                        String::from_utf8_lossy(&ufid.identifier).into_owned(),
+                    )],
+                    Content::InvolvedPeopleList(list) => list
+                        .items
+                        .iter()
+                        .map(|item| (format!("{id}:{}", item.involvement), item.involvee.clone()))
+                        .collect(),
+                    _ => vec![],
+                }
+            })
+            .collect()
+    }
+
     fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
         self.tag_key_to_frame(key)
             .and_then(|frame_id| match frame_id {
                 FrameId::Text(id) => self.get_frames(id).map(Cow::from).next(),
+                FrameId::SplitDate(year_id, date_id) => self.get_split_date(year_id, date_id),
                 FrameId::CombinedText(id, part) => {
                     self.get_combined_text_part(id, part).map(Cow::from)
                 }
-                FrameId::ExtendedText(id) => self
+                FrameId::ExtendedText(id) | FrameId::MirroredExtendedText(id, _) => self
                     .get_extended_texts(id)
                     .map(|value| {
                         value
-                            .char_indices()
-                            .next_back()
-                            .map_or(value, |(i, character)| {
-                                if character == '\0' {
-                                    &value[..i]
-                                } else {
-                                    value
-                                }
-                            })
+                            .split(self.extended_text_separator())
+                            .next()
+                            .unwrap_or(value)
                     })
                     .map(Cow::from)
                     .next(),
@@ -390,13 +769,10 @@ impl Tag for ID3v2Tag {
                     .map(std::str::from_utf8)
                     .find_map(Result::ok)
                     .map(Cow::from),
-                FrameId::Comment(desc) => self.data.comments().find_map(|comment| {
-                    if comment.lang == "eng" && comment.description == desc {
-                        Some(Cow::from(comment.text.as_str()))
-                    } else {
-                        None
-                    }
-                }),
+                FrameId::Comment => self.get_comment().map(Cow::from),
+                FrameId::Popularimeter => {
+                    self.get_rating().map(|stars| Cow::from(stars.to_string()))
+                }
                 FrameId::InvolvedPersonList(_) => {
                     // Use the dedicated `performers()` method instead.
                     unreachable!();
@@ -420,6 +796,19 @@ impl Tag for ID3v2Tag {
             })
     }
 
+    fn get_multiple<'a>(&'a self, key: &'a TagKey) -> Vec<Cow<'a, str>> {
+        match self.tag_key_to_frame(key) {
+            Some(FrameId::Text(id)) => self.get_frames(id).map(Cow::from).collect(),
+            Some(FrameId::ExtendedText(id) | FrameId::MirroredExtendedText(id, _)) => self
+                .get_extended_texts(id)
+                .flat_map(|value| value.split(self.extended_text_separator()))
+                .filter(|value| !value.is_empty())
+                .map(Cow::from)
+                .collect(),
+            _ => self.get(key).into_iter().collect(),
+        }
+    }
+
     fn clear(&mut self, key: &TagKey) {
         let frame = self.tag_key_to_frame(key);
         if let Some(frame) = frame {
@@ -429,6 +818,10 @@ impl Tag for ID3v2Tag {
                 | FrameId::InvolvedPersonList(id) => {
                     let _unused = self.data.remove(id);
                 }
+                FrameId::SplitDate(year_id, date_id) => {
+                    let _unused = self.data.remove(year_id);
+                    let _unused = self.data.remove(date_id);
+                }
                 FrameId::CombinedText(id, CombinedTextPart::Second) => {
                     if let Some(value) = self
                         .get_combined_text_part(id, CombinedTextPart::First)
@@ -440,15 +833,21 @@ impl Tag for ID3v2Tag {
                     }
                 }
                 FrameId::ExtendedText(description) => {
-                    self.data.remove_extended_text(Some(description), None);
+                    self.remove_extended_texts(description);
+                }
+                FrameId::MirroredExtendedText(description, id) => {
+                    self.remove_extended_texts(description);
+                    let _unused = self.data.remove(id);
                 }
                 FrameId::UniqueFileIdentifier(owner_id) => {
                     self.data
                         .remove_unique_file_identifier_by_owner_identifier(owner_id);
                 }
-                FrameId::Comment(desc) => {
-                    self.data.remove_comment(Some(desc), None);
+                FrameId::Comment => {
+                    self.data
+                        .remove_comment(Some(&self.comment_description), None);
                 }
+                FrameId::Popularimeter => self.set_rating(None),
                 FrameId::InvolvedPerson(id, involvement) => {
                     let remaining_items = self
                         .data
@@ -514,6 +913,10 @@ impl Tag for ID3v2Tag {
                     ));
                 }
             }
+            Some(FrameId::ExtendedText(_) | FrameId::MirroredExtendedText(_, _)) => {
+                let separator = self.extended_text_separator().to_string();
+                self.set(key, values.join(&separator).into());
+            }
             _ => {
                 self.set(key, values.join(" / ").into());
             }
@@ -524,14 +927,24 @@ impl Tag for ID3v2Tag {
         let frame = self.tag_key_to_frame(key);
         if let Some(frame) = frame {
             match frame {
+                // `TORY` only contains the year.
+                FrameId::Text(id @ "TORY") => {
+                    let value = parse_year_from_str(&value).map_or(value, Cow::from);
+                    self.data.set_text(id, value);
+                }
                 FrameId::Text(id) => {
                     self.data.set_text(id, value);
                 }
+                FrameId::SplitDate(year_id, date_id) => {
+                    self.set_split_date(year_id, date_id, &value);
+                }
                 FrameId::CombinedText(id, CombinedTextPart::First) => {
                     let new_value = match self.get_combined_text_part(id, CombinedTextPart::Second)
                     {
-                        Some(second_value) => Cow::from(format!("{value}/{second_value}")),
-                        None => value,
+                        Some(second_value) if !value.contains('/') => {
+                            Cow::from(format!("{value}/{second_value}"))
+                        }
+                        _ => value,
                     };
                     self.data.set_text(id, new_value);
                 }
@@ -548,12 +961,23 @@ impl Tag for ID3v2Tag {
                 }
                 #[expect(unused_results)]
                 FrameId::ExtendedText(description) => {
+                    self.remove_extended_texts(description);
                     self.data.add_frame(ExtendedText {
                         description: description.to_string(),
-                        value: value.into_owned(),
+                        value: value.trim_end_matches('\0').to_string(),
                     });
                 }
                 #[expect(unused_results)]
+                FrameId::MirroredExtendedText(description, id) => {
+                    let value = value.trim_end_matches('\0');
+                    self.remove_extended_texts(description);
+                    self.data.add_frame(ExtendedText {
+                        description: description.to_string(),
+                        value: value.to_string(),
+                    });
+                    self.data.set_text(id, value);
+                }
+                #[expect(unused_results)]
                 FrameId::UniqueFileIdentifier(owner_id) => {
                     self.data.add_frame(UniqueFileIdentifier {
                         owner_identifier: owner_id.to_string(),
@@ -561,13 +985,19 @@ impl Tag for ID3v2Tag {
                     });
                 }
                 #[expect(unused_results)]
-                FrameId::Comment(desc) => {
+                FrameId::Comment => {
+                    self.data
+                        .remove_comment(Some(&self.comment_description), None);
                     self.data.add_frame(Comment {
-                        lang: "eng".to_string(),
-                        description: desc.to_string(),
+                        lang: self.comment_language.clone(),
+                        description: self.comment_description.clone(),
                         text: value.to_string(),
                     });
                 }
+                FrameId::Popularimeter => match parse_rating_stars(&value) {
+                    Some(stars) => self.set_rating(Some(stars)),
+                    None => log::warn!("Ignoring invalid rating {value:?}"),
+                },
                 FrameId::InvolvedPersonList(_) => {
                     unreachable!();
                 }
@@ -603,7 +1033,12 @@ impl Tag for ID3v2Tag {
     }
 
     fn write(&mut self, path: &Path) -> crate::Result<()> {
-        self.data.write_to_path(path, self.data.version())?;
+        let version = self.data.version();
+        match Container::from_path(path) {
+            Container::Plain => self.data.write_to_path(path, version)?,
+            Container::Wav => self.data.write_to_wav_path(path, version)?,
+            Container::Aiff => self.data.write_to_aiff_path(path, version)?,
+        }
         Ok(())
     }
 
@@ -663,6 +1098,61 @@ mod tests {
         assert_eq!(tag.tag_type(), TagType::ID3v24);
     }
 
+    #[test]
+    fn test_split_date_id3v23() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v23);
+        tag.set(&TagKey::ReleaseDate, Cow::from("1958-1-17"));
+        assert_eq!(tag.get_frames("TYER").next(), Some("1958"));
+        assert_eq!(tag.get_frames("TDAT").next(), Some("1701"));
+        assert_eq!(tag.get(&TagKey::ReleaseDate).as_deref(), Some("1958-01-17"));
+        assert_eq!(tag.get(&TagKey::ReleaseYear).as_deref(), Some("1958"));
+
+        tag.set(&TagKey::ReleaseDate, Cow::from("1958"));
+        assert!(tag.get_frames("TDAT").next().is_none());
+        assert_eq!(tag.get(&TagKey::ReleaseDate).as_deref(), Some("1958"));
+
+        tag.set(&TagKey::OriginalReleaseDate, Cow::from("1958-01-17"));
+        assert_eq!(tag.get_frames("TORY").next(), Some("1958"));
+
+        tag.clear(&TagKey::ReleaseDate);
+        assert!(tag.get(&TagKey::ReleaseDate).is_none());
+        assert!(tag.get(&TagKey::ReleaseYear).is_none());
+    }
+
+    #[test]
+    fn test_provenance() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v23);
+        assert!(tag.provenance(&TagKey::CatalogNumber).is_none());
+
+        tag.set(&TagKey::CatalogNumber, Cow::from("CAT-123"));
+        tag.set(&TagKey::TrackTitle, Cow::from("But Not for Me"));
+        let provenance = tag.provenance(&TagKey::CatalogNumber).unwrap();
+        assert_eq!(provenance.to_string(), "ID3v2.3 TXXX:CATALOGNUMBER");
+        let provenance = tag.provenance(&TagKey::TrackTitle).unwrap();
+        assert_eq!(provenance.to_string(), "ID3v2.3 TIT2");
+    }
+
+    #[test]
+    fn test_keys_and_fields() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        assert!(tag.keys().is_empty());
+        assert!(tag.fields().is_empty());
+
+        tag.set(&TagKey::CatalogNumber, Cow::from("CAT-123"));
+        tag.set(&TagKey::TrackTitle, Cow::from("But Not for Me"));
+        let _unused = tag.data.add_frame(ExtendedText {
+            description: "MY_CUSTOM_FIELD".to_string(),
+            value: "foo".to_string(),
+        });
+
+        assert_eq!(tag.keys(), vec![TagKey::TrackTitle, TagKey::CatalogNumber]);
+        assert_eq!(tag.fields().len(), 3);
+        assert_eq!(
+            tag.unknown_fields(),
+            vec![("TXXX:MY_CUSTOM_FIELD".to_string(), "foo".to_string())]
+        );
+    }
+
     #[test]
     fn test_get_set_clear_multivalued_text() {
         let mut tag = ID3v2Tag::default();
@@ -755,6 +1245,238 @@ mod tests {
         assert_eq!(tag.get(&TagKey::Producer).as_deref(), Some("Producer Dude"));
     }
 
+    #[test]
+    fn test_set_multiple_extended_text() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set_multiple(
+            &TagKey::MusicBrainzArtistId,
+            &[
+                Cow::from("9e7ca87b-4e3d-4d14-90f1-a74acb645fe2"),
+                Cow::from("561d854a-6a28-4aa7-8c99-323e6ce46c2a"),
+            ],
+        );
+
+        assert_eq!(
+            tag.get_extended_texts("MusicBrainz Artist Id")
+                .collect::<Vec<_>>(),
+            vec!["9e7ca87b-4e3d-4d14-90f1-a74acb645fe2\0561d854a-6a28-4aa7-8c99-323e6ce46c2a"]
+        );
+        assert_eq!(
+            tag.get(&TagKey::MusicBrainzArtistId).as_deref(),
+            Some("9e7ca87b-4e3d-4d14-90f1-a74acb645fe2")
+        );
+
+        let mut tag = ID3v2Tag::with_version(Version::Id3v23);
+        tag.set_multiple(
+            &TagKey::MusicBrainzArtistId,
+            &[
+                Cow::from("9e7ca87b-4e3d-4d14-90f1-a74acb645fe2"),
+                Cow::from("561d854a-6a28-4aa7-8c99-323e6ce46c2a"),
+            ],
+        );
+        assert_eq!(
+            tag.get_extended_texts("MusicBrainz Artist Id")
+                .collect::<Vec<_>>(),
+            vec!["9e7ca87b-4e3d-4d14-90f1-a74acb645fe2/561d854a-6a28-4aa7-8c99-323e6ce46c2a"]
+        );
+        assert_eq!(tag.get_multiple(&TagKey::MusicBrainzArtistId).len(), 2);
+    }
+
+    #[test]
+    fn test_case_insensitive_descriptions() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        let _unused = tag.data.add_frame(ExtendedText {
+            description: "MUSICBRAINZ ALBUM ID\0".to_string(),
+            value: "0f6a7f8e-6a2b-4b3c-9a5f-8c2d1e0b7a61".to_string(),
+        });
+        assert!(tag.get(&TagKey::MusicBrainzReleaseId).is_none());
+
+        tag.set_case_insensitive_descriptions(true);
+        assert_eq!(
+            tag.get(&TagKey::MusicBrainzReleaseId).as_deref(),
+            Some("0f6a7f8e-6a2b-4b3c-9a5f-8c2d1e0b7a61")
+        );
+
+        tag.set(
+            &TagKey::MusicBrainzReleaseId,
+            Cow::from("d2c5e4a3-1b6f-4e8d-a7c9-3f0b2e1d5c84\0"),
+        );
+        assert_eq!(
+            tag.data
+                .extended_texts()
+                .map(|extended_text| (
+                    extended_text.description.as_str(),
+                    extended_text.value.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(
+                "MusicBrainz Album Id",
+                "d2c5e4a3-1b6f-4e8d-a7c9-3f0b2e1d5c84"
+            )]
+        );
+
+        tag.clear(&TagKey::MusicBrainzReleaseId);
+        assert_eq!(tag.data.extended_texts().count(), 0);
+    }
+
+    #[test]
+    fn test_comment_frame() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set_comment_frame("eng", "");
+        let _unused = tag.data.add_frame(Comment {
+            lang: "XXX".to_string(),
+            description: String::new(),
+            text: "Fallback".to_string(),
+        });
+        assert_eq!(tag.get(&TagKey::Comment).as_deref(), Some("Fallback"));
+
+        let _unused = tag.data.add_frame(Comment {
+            lang: "eng".to_string(),
+            description: String::new(),
+            text: "Preferred".to_string(),
+        });
+        assert_eq!(tag.get(&TagKey::Comment).as_deref(), Some("Preferred"));
+
+        tag.set(&TagKey::Comment, Cow::from("New"));
+        assert_eq!(
+            tag.data
+                .comments()
+                .map(|comment| (comment.lang.as_str(), comment.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("eng", "New")]
+        );
+        assert_eq!(tag.field_name(&TagKey::Comment).as_deref(), Some("COMM:"));
+
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        let _unused = tag.data.add_frame(Comment {
+            lang: "eng".to_string(),
+            description: "iTunNORM".to_string(),
+            text: "Other".to_string(),
+        });
+        assert_eq!(tag.get(&TagKey::Comment).as_deref(), Some("Other"));
+        let _unused = tag.data.add_frame(Comment {
+            lang: "eng".to_string(),
+            description: String::new(),
+            text: "Picard".to_string(),
+        });
+        assert_eq!(tag.get(&TagKey::Comment).as_deref(), Some("Picard"));
+        tag.set(&TagKey::Comment, Cow::from("New"));
+        assert_eq!(tag.get(&TagKey::Comment).as_deref(), Some("New"));
+    }
+
+    #[test]
+    fn test_itunes_grouping() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set(&TagKey::Grouping, Cow::from("Grouping"));
+        tag.set(&TagKey::WorkTitle, Cow::from("Work"));
+        assert_eq!(tag.get_frames("TIT1").next(), Some("Grouping"));
+        assert_eq!(tag.get_extended_texts("WORK").next(), Some("Work"));
+
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set_itunes_grouping(true);
+        tag.set(&TagKey::Grouping, Cow::from("Grouping"));
+        tag.set(&TagKey::WorkTitle, Cow::from("Work"));
+        assert_eq!(tag.get_frames("GRP1").next(), Some("Grouping"));
+        assert_eq!(tag.get_frames("TIT1").next(), Some("Work"));
+        assert_eq!(tag.get_extended_texts("WORK").next(), Some("Work"));
+        assert_eq!(tag.get(&TagKey::Grouping).as_deref(), Some("Grouping"));
+        assert_eq!(tag.get(&TagKey::WorkTitle).as_deref(), Some("Work"));
+        assert_eq!(tag.field_name(&TagKey::Grouping).as_deref(), Some("GRP1"));
+        assert_eq!(
+            tag.field_name(&TagKey::WorkTitle).as_deref(),
+            Some("TXXX:WORK")
+        );
+
+        tag.clear(&TagKey::WorkTitle);
+        assert!(tag.get_frames("TIT1").next().is_none());
+        assert!(tag.get_extended_texts("WORK").next().is_none());
+
+        // An existing grouping in `TIT1` is not read as the work title.
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set(&TagKey::Grouping, Cow::from("Grouping"));
+        tag.set_itunes_grouping(true);
+        assert!(tag.get(&TagKey::WorkTitle).is_none());
+    }
+
+    #[test]
+    fn test_rating() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        assert!(tag.get(&TagKey::Rating).is_none());
+
+        let _unused = tag.data.add_frame(Frame::with_content(
+            "POPM",
+            Content::Popularimeter(Popularimeter {
+                user: "other@example.com".to_string(),
+                rating: 196,
+                counter: 0,
+            }),
+        ));
+        assert!(tag.get(&TagKey::Rating).is_none());
+
+        tag.set(&TagKey::Rating, Cow::from("3"));
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("3"));
+        let ratings = tag
+            .popularimeters()
+            .map(|popularimeter| (popularimeter.user.as_str(), popularimeter.rating))
+            .collect::<Vec<_>>();
+        assert_eq!(ratings.len(), 2);
+        assert!(ratings.contains(&("users@musicbrainz.org", 153)));
+        assert!(ratings.contains(&("other@example.com", 196)));
+
+        tag.set(&TagKey::Rating, Cow::from("not a rating"));
+        assert_eq!(tag.get(&TagKey::Rating).as_deref(), Some("3"));
+
+        tag.set_rating_options(&RatingConfig {
+            email: "users@musicbrainz.org".to_string(),
+            scale: RatingScale::WindowsMediaPlayer,
+        });
+        tag.set(&TagKey::Rating, Cow::from("3"));
+        assert!(tag
+            .popularimeters()
+            .any(|popularimeter| popularimeter.rating == 128));
+
+        tag.clear(&TagKey::Rating);
+        assert!(tag.get(&TagKey::Rating).is_none());
+        assert_eq!(tag.popularimeters().count(), 1);
+    }
+
+    #[test]
+    fn test_set_relative_volume_adjustment() {
+        let mut tag = ID3v2Tag::with_version(Version::Id3v24);
+        tag.set_relative_volume_adjustment("track", Some(-6.5), Some(0.5));
+        tag.set_relative_volume_adjustment("album", Some(-7.0), None);
+        tag.set_relative_volume_adjustment("track", Some(-6.5), Some(0.5));
+        let frames = tag
+            .data
+            .frames()
+            .filter(|frame| frame.id() == "RVA2")
+            .filter_map(|frame| match frame.content() {
+                Content::Unknown(unknown) => Some(unknown.data.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            frames,
+            vec![
+                b"album\0\x01\xf2\x00\x00".to_vec(),
+                b"track\0\x01\xf3\x00\x10\x40\x00".to_vec(),
+            ]
+        );
+
+        tag.set_relative_volume_adjustment("album", None, None);
+        assert_eq!(
+            tag.data
+                .frames()
+                .filter(|frame| frame.id() == "RVA2")
+                .count(),
+            1
+        );
+
+        let mut tag = ID3v2Tag::with_version(Version::Id3v23);
+        tag.set_relative_volume_adjustment("track", Some(-6.5), Some(0.5));
+        assert_eq!(tag.data.frames().count(), 0);
+    }
+
     #[test]
     fn test_id3v23_utf16_read() {
         const MP3_DATA: &[u8] = include_bytes!(concat!(
@@ -942,6 +1664,20 @@ mod tests {
         assert_eq!(tag.get(&TagKey::Producer).as_deref(), Some("Dave Usher"));
     }
 
+    #[test]
+    fn test_wav_read() {
+        let tag = ID3v2Tag::read_from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/picard-2.12.3/track.wav"
+        ))
+        .unwrap();
+        assert_eq!(tag.tag_type(), TagType::ID3v24);
+        assert_eq!(
+            tag.get(&TagKey::TrackTitle).as_deref(),
+            Some("But Not for Me")
+        );
+    }
+
     #[test]
     fn test_id3v24_read() {
         const MP3_DATA: &[u8] = include_bytes!(concat!(
@@ -1342,7 +2078,6 @@ mod tests {
     //add_tests_with_id3_versions_all!(&TagKey::Performer, performers);
     //add_tests_with_id3_versions_all!(&TagKey::Podcast, podcast);
     //add_tests_with_id3_versions_all!(&TagKey::PodcastUrl, podcasturl);
-    add_tests_with_id3_versions_all!(&TagKey::Rating, rating);
     add_tests_with_id3_versions_all!(&TagKey::RecordLabel, recordlabel);
     add_tests_with_id3_versions_all!(&TagKey::ReleaseCountry, releasecountry);
     add_tests_with_id3_version!(&TagKey::ReleaseDate, Version::Id3v23, releasedate_id3v23);
@@ -0,0 +1,153 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! In-memory tag that is not backed by any file (e.g., for tracks from a cue sheet).
+
+use crate::tag::{Tag, TagKey, TagType};
+use crate::track::InvolvedPerson;
+use std::borrow::Cow;
+use std::path::Path;
+
+/// In-memory tag.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTag {
+    /// The tag values.
+    data: Vec<(TagKey, Vec<String>)>,
+}
+
+impl MemoryTag {
+    /// Create a new, empty tag.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value for the tag key if the value is present and return the modified tag.
+    #[must_use]
+    pub fn with_value(mut self, key: TagKey, value: Option<impl Into<String>>) -> Self {
+        if let Some(value) = value {
+            self.set(&key, Cow::from(value.into()));
+        }
+        self
+    }
+}
+
+impl Tag for MemoryTag {
+    fn tag_type(&self) -> TagType {
+        TagType::Memory
+    }
+
+    fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
+        self.data
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, values)| values.first())
+            .map(|value| Cow::from(value.as_str()))
+    }
+
+    fn keys(&self) -> Vec<TagKey> {
+        self.data.iter().map(|(key, _)| key.clone()).collect()
+    }
+
+    fn get_multiple<'a>(&'a self, key: &'a TagKey) -> Vec<Cow<'a, str>> {
+        self.data
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, values)| {
+                values
+                    .iter()
+                    .map(|value| Cow::from(value.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn set(&mut self, key: &TagKey, value: Cow<'_, str>) {
+        self.set_multiple(key, &[value]);
+    }
+
+    fn set_multiple<'a>(&'a mut self, key: &TagKey, values: &[Cow<'a, str>]) {
+        let values = values.iter().map(ToString::to_string).collect();
+        if let Some((_, old_values)) = self.data.iter_mut().find(|(k, _)| k == key) {
+            *old_values = values;
+        } else {
+            self.data.push((key.clone(), values));
+        }
+    }
+
+    fn clear(&mut self, key: &TagKey) {
+        self.data.retain(|(k, _)| {
+            k != key && !(*key == TagKey::Performers && matches!(k, TagKey::Performer(_)))
+        });
+    }
+
+    fn write(&mut self, path: &Path) -> crate::Result<()> {
+        log::warn!(
+            "Writing in-memory tags to {} is not supported",
+            path.display()
+        );
+        Ok(())
+    }
+
+    fn performers(&self) -> Option<Vec<InvolvedPerson<'_>>> {
+        let performers = self
+            .data
+            .iter()
+            .filter_map(|(key, values)| match key {
+                TagKey::Performer(involvement) => Some((involvement, values)),
+                _ => None,
+            })
+            .flat_map(|(involvement, values)| {
+                values.iter().map(|involvee| InvolvedPerson {
+                    involvement: Cow::from(involvement.as_str()),
+                    involvee: Cow::from(involvee.as_str()),
+                })
+            })
+            .collect::<Vec<_>>();
+        (!performers.is_empty()).then_some(performers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_set_clear() {
+        let mut tag = MemoryTag::new().with_value(TagKey::TrackTitle, Some("foo"));
+        assert_eq!(tag.get(&TagKey::TrackTitle).as_deref(), Some("foo"));
+        assert!(tag.get(&TagKey::Artist).is_none());
+
+        tag.set(&TagKey::TrackTitle, Cow::from("bar"));
+        assert_eq!(tag.get(&TagKey::TrackTitle).as_deref(), Some("bar"));
+
+        tag.clear(&TagKey::TrackTitle);
+        assert!(tag.get(&TagKey::TrackTitle).is_none());
+    }
+
+    #[test]
+    fn test_performers() {
+        let mut tag = MemoryTag::new();
+        assert!(tag.performers().is_none());
+
+        tag.set_multiple(
+            &TagKey::Performer("piano".to_string()),
+            &[Cow::from("Ahmad Jamal")],
+        );
+        tag.set_multiple(
+            &TagKey::Performer("drums".to_string()),
+            &[Cow::from("Vernel Fournier")],
+        );
+        let performers = tag.performers().unwrap();
+        assert_eq!(performers.len(), 2);
+        assert_eq!(performers[0].involvement, "piano");
+        assert_eq!(performers[0].involvee, "Ahmad Jamal");
+
+        tag.clear(&TagKey::Performers);
+        assert!(tag.performers().is_none());
+    }
+}
@@ -7,17 +7,24 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! Tags and tag-related functions.
+use crate::config::TagsConfig;
 use crate::track::InvolvedPerson;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 
+pub mod ape;
 #[cfg(feature = "flac")]
 pub mod flac;
 #[cfg(feature = "id3")]
 pub mod id3;
+pub mod memory;
+pub mod riff;
 
 /// A tag key describes the kind of information in a generic, format-independent way.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum TagKey {
     // Track Level
     /// AcoustID associated with the track.
@@ -128,7 +135,7 @@ pub enum TagKey {
     Performer(String),
     /// Producer Name(s).
     Producer,
-    /// Rating of the track.
+    /// Rating of the track (in stars from 0 to 5).
     Rating,
     /// Remixer Name(s).
     Remixer,
@@ -146,6 +153,10 @@ pub enum TagKey {
     ReplayGainTrackPeak,
     /// ReplayGain Track Range.
     ReplayGainTrackRange,
+    /// Opus Album Gain (relative to -23 LUFS, as Q7.8 fixed point number).
+    R128AlbumGain,
+    /// Opus Track Gain (relative to -23 LUFS, as Q7.8 fixed point number).
+    R128TrackGain,
     /// Track number on the disc.
     TrackNumber,
     /// Track Title.
@@ -238,6 +249,211 @@ pub enum TagKey {
     Subtitle,
 }
 
+impl TagKey {
+    /// All tag keys that do not carry additional data (i.e., all except [`TagKey::Performer`]).
+    pub const ALL: &'static [TagKey] = &[
+        TagKey::AcoustId,
+        TagKey::AcoustIdFingerprint,
+        TagKey::Arranger,
+        TagKey::Artist,
+        TagKey::ArtistSortOrder,
+        TagKey::Artists,
+        TagKey::Bpm,
+        TagKey::Comment,
+        TagKey::Composer,
+        TagKey::ComposerSortOrder,
+        TagKey::Conductor,
+        TagKey::Copyright,
+        TagKey::Director,
+        TagKey::DjMixer,
+        TagKey::EncodedBy,
+        TagKey::EncoderSettings,
+        TagKey::Engineer,
+        TagKey::Genre,
+        TagKey::InitialKey,
+        TagKey::Isrc,
+        TagKey::Language,
+        TagKey::License,
+        TagKey::Lyricist,
+        TagKey::Lyrics,
+        TagKey::Mixer,
+        TagKey::Mood,
+        TagKey::Movement,
+        TagKey::MovementCount,
+        TagKey::MovementNumber,
+        TagKey::MusicBrainzArtistId,
+        TagKey::MusicBrainzOriginalArtistId,
+        TagKey::MusicBrainzOriginalReleaseId,
+        TagKey::MusicBrainzRecordingId,
+        TagKey::MusicBrainzTrackId,
+        TagKey::MusicBrainzTrmId,
+        TagKey::MusicBrainzWorkId,
+        TagKey::MusicIpFingerprint,
+        TagKey::MusicIpPuid,
+        TagKey::OriginalAlbum,
+        TagKey::OriginalArtist,
+        TagKey::OriginalFilename,
+        TagKey::OriginalReleaseDate,
+        TagKey::OriginalReleaseYear,
+        TagKey::Performers,
+        TagKey::Producer,
+        TagKey::Rating,
+        TagKey::Remixer,
+        TagKey::ReplayGainAlbumGain,
+        TagKey::ReplayGainAlbumPeak,
+        TagKey::ReplayGainAlbumRange,
+        TagKey::ReplayGainReferenceLoudness,
+        TagKey::ReplayGainTrackGain,
+        TagKey::ReplayGainTrackPeak,
+        TagKey::ReplayGainTrackRange,
+        TagKey::R128AlbumGain,
+        TagKey::R128TrackGain,
+        TagKey::TrackNumber,
+        TagKey::TrackTitle,
+        TagKey::TrackTitleSortOrder,
+        TagKey::ArtistWebsite,
+        TagKey::WorkTitle,
+        TagKey::Writer,
+        TagKey::DiscNumber,
+        TagKey::DiscSubtitle,
+        TagKey::GaplessPlayback,
+        TagKey::Media,
+        TagKey::MusicBrainzDiscId,
+        TagKey::TotalTracks,
+        TagKey::Album,
+        TagKey::AlbumArtist,
+        TagKey::AlbumArtistSortOrder,
+        TagKey::AlbumSortOrder,
+        TagKey::Asin,
+        TagKey::Barcode,
+        TagKey::CatalogNumber,
+        TagKey::Compilation,
+        TagKey::Grouping,
+        TagKey::MusicBrainzReleaseArtistId,
+        TagKey::MusicBrainzReleaseGroupId,
+        TagKey::MusicBrainzReleaseId,
+        TagKey::RecordLabel,
+        TagKey::ReleaseCountry,
+        TagKey::ReleaseDate,
+        TagKey::ReleaseYear,
+        TagKey::ReleaseStatus,
+        TagKey::ReleaseType,
+        TagKey::Script,
+        TagKey::TotalDiscs,
+        TagKey::Podcast,
+        TagKey::PodcastUrl,
+        TagKey::ShowName,
+        TagKey::ShowNameSortOrder,
+        TagKey::ShowMovement,
+        TagKey::Subtitle,
+    ];
+}
+
+/// Alternative names of tag keys, as used by [MusicBrainz Picard][picard].
+///
+/// Names that only differ from the tag key name by case, underscores or dashes (e.g.,
+/// `catalognumber` or `musicbrainz_workid`) don't need to be listed here. Tag key names take
+/// precedence over aliases, so Picard's `musicbrainz_trackid` (which contains the recording ID) is
+/// parsed as [`TagKey::MusicBrainzTrackId`].
+///
+/// [picard]: https://picard-docs.musicbrainz.org/en/appendices/tag_mapping.html
+const TAG_KEY_ALIASES: &[(&str, TagKey)] = &[
+    ("acoustid_id", TagKey::AcoustId),
+    ("albumartistsort", TagKey::AlbumArtistSortOrder),
+    ("albumsort", TagKey::AlbumSortOrder),
+    ("artistsort", TagKey::ArtistSortOrder),
+    ("composersort", TagKey::ComposerSortOrder),
+    ("date", TagKey::ReleaseDate),
+    ("gapless", TagKey::GaplessPlayback),
+    ("key", TagKey::InitialKey),
+    ("label", TagKey::RecordLabel),
+    ("movementtotal", TagKey::MovementCount),
+    (
+        "musicbrainz_albumartistid",
+        TagKey::MusicBrainzReleaseArtistId,
+    ),
+    ("musicbrainz_albumid", TagKey::MusicBrainzReleaseId),
+    (
+        "musicbrainz_originalalbumid",
+        TagKey::MusicBrainzOriginalReleaseId,
+    ),
+    ("musicbrainz_releasetrackid", TagKey::MusicBrainzTrackId),
+    ("originaldate", TagKey::OriginalReleaseDate),
+    ("originalyear", TagKey::OriginalReleaseYear),
+    ("show", TagKey::ShowName),
+    ("showsort", TagKey::ShowNameSortOrder),
+    ("title", TagKey::TrackTitle),
+    ("titlesort", TagKey::TrackTitleSortOrder),
+    ("website", TagKey::ArtistWebsite),
+    ("work", TagKey::WorkTitle),
+    ("year", TagKey::ReleaseYear),
+];
+
+/// Normalize a tag key name for comparison, i.e., remove underscores and dashes and convert it
+/// to lowercase.
+fn normalize_tag_key_name(name: &str) -> String {
+    name.replace(['_', '-'], "").to_ascii_lowercase()
+}
+
+impl FromStr for TagKey {
+    type Err = String;
+
+    /// Parse a tag key from its name.
+    ///
+    /// The comparison is case-insensitive and ignores underscores and dashes, so that `album`,
+    /// `AlbumArtist` and `album_artist` are all accepted. Picard tag names (e.g.,
+    /// `albumartistsort` or `musicbrainz_albumid`) are accepted as well if they don't match a tag
+    /// key name, and performers can be specified as `performer:<instrument>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((name, involvement)) = s.split_once(':') {
+            return if normalize_tag_key_name(name) == "performer" {
+                Ok(TagKey::Performer(involvement.to_string()))
+            } else {
+                Err(format!("unknown tag key: {s}"))
+            };
+        }
+
+        let normalized_name = normalize_tag_key_name(s);
+        TagKey::ALL
+            .iter()
+            .find(|key| format!("{key:?}").eq_ignore_ascii_case(&normalized_name))
+            .or_else(|| {
+                TAG_KEY_ALIASES
+                    .iter()
+                    .find(|(alias, _)| normalize_tag_key_name(alias) == normalized_name)
+                    .map(|(_, key)| key)
+            })
+            .cloned()
+            .ok_or_else(|| format!("unknown tag key: {s}"))
+    }
+}
+
+impl fmt::Display for TagKey {
+    /// Format the tag key as `snake_case` name (e.g., `album_artist`), which can be parsed again
+    /// with [`TagKey::from_str`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let TagKey::Performer(involvement) = self {
+            return write!(f, "performer:{involvement}");
+        }
+
+        let mut name = String::new();
+        let mut previous_char = None;
+        for c in format!("{self:?}").chars() {
+            if c.is_ascii_uppercase()
+                && previous_char.is_some_and(|p: char| p.is_ascii_lowercase() || p.is_ascii_digit())
+            {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            previous_char = Some(c);
+        }
+        let name = name
+            .replace("music_brainz", "musicbrainz")
+            .replace("acoust_id", "acoustid");
+        f.write_str(&name)
+    }
+}
+
 /// The tag type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TagType {
@@ -253,14 +469,177 @@ pub enum TagType {
     /// Vorbis tag from a FLAC file
     #[cfg(feature = "flac")]
     Flac,
+    /// In-memory tag that is not backed by a file.
+    Memory,
+    /// RIFF INFO tag from a WAV file
+    RiffInfo,
+    /// APEv2 tag (e.g., at the end of an MP3 file)
+    Ape,
+}
+
+impl fmt::Display for TagType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            #[cfg(feature = "id3")]
+            Self::ID3v22 => "ID3v2.2",
+            #[cfg(feature = "id3")]
+            Self::ID3v23 => "ID3v2.3",
+            #[cfg(feature = "id3")]
+            Self::ID3v24 => "ID3v2.4",
+            #[cfg(feature = "flac")]
+            Self::Flac => "Vorbis comment",
+            Self::Memory => "memory",
+            Self::RiffInfo => "RIFF INFO",
+            Self::Ape => "APEv2",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where a value of a file was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueProvenance {
+    /// The value was read from a tag.
+    Tag {
+        /// Type of the tag.
+        tag_type: TagType,
+        /// Name of the field in the tag (e.g., `TXXX:CATALOGNUMBER`), if known.
+        field_name: Option<String>,
+    },
+    /// The value was read from the technical properties of the audio stream.
+    StreamInfo,
+    /// The value was derived from the file path using a filename pattern.
+    FilePath,
+    /// The value was determined by an analyzer.
+    Analyzer,
+}
+
+impl fmt::Display for ValueProvenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tag {
+                tag_type,
+                field_name: Some(field_name),
+            } => write!(f, "{tag_type} {field_name}"),
+            Self::Tag {
+                tag_type,
+                field_name: None,
+            } => write!(f, "{tag_type}"),
+            Self::StreamInfo => f.write_str("stream info"),
+            Self::FilePath => f.write_str("file path"),
+            Self::Analyzer => f.write_str("analyzer"),
+        }
+    }
+}
+
+/// Technical properties of the audio stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Number of bits per sample.
+    pub bits_per_sample: u8,
+    /// Number of channels.
+    pub channels: u8,
+    /// Total number of samples per channel (if known).
+    pub total_samples: Option<u64>,
+}
+
+impl StreamInfo {
+    /// Returns the exact duration of the stream (if the total number of samples is known).
+    pub fn duration(&self) -> Option<chrono::TimeDelta> {
+        let total_samples = self.total_samples?;
+        if self.sample_rate == 0 {
+            return None;
+        }
+
+        let milliseconds = total_samples.checked_mul(1000)? / u64::from(self.sample_rate);
+        i64::try_from(milliseconds)
+            .ok()
+            .and_then(chrono::TimeDelta::try_milliseconds)
+    }
+}
+
+/// A track of a cue sheet that is embedded in the audio file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedCueSheetTrack {
+    /// The track number.
+    pub number: u8,
+    /// Offset of the track in samples, relative to the beginning of the stream.
+    pub offset: u64,
+    /// The ISRC of the track (if any).
+    pub isrc: Option<String>,
+}
+
+/// A cue sheet that is embedded in the audio file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedCueSheet {
+    /// The media catalog number (if any).
+    pub catalog_number: Option<String>,
+    /// The audio tracks (excluding the lead-out track).
+    pub tracks: Vec<EmbeddedCueSheetTrack>,
+}
+
+impl EmbeddedCueSheet {
+    /// Returns the number of tracks in the cue sheet.
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
 }
 
 /// A tag tag can be used for reading.
 pub trait Tag: Send + Sync {
     /// Get the tag type.
     fn tag_type(&self) -> TagType;
+    /// Get the name of the field that the tag key is stored in (e.g., `TXXX:CATALOGNUMBER`).
+    fn field_name(&self, _key: &TagKey) -> Option<String> {
+        None
+    }
     /// Get the string value for the tag key.
     fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>>;
+    /// Get the tag keys that have a value in this tag.
+    fn keys(&self) -> Vec<TagKey> {
+        TagKey::ALL
+            .iter()
+            .filter(|key| self.get(key).is_some())
+            .cloned()
+            .collect()
+    }
+    /// Get all raw fields of this tag as `(field name, value)` pairs, including fields that are
+    /// not mapped to a tag key. Binary fields (e.g., pictures) are not included.
+    fn fields(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+    /// Get the raw fields of this tag that are not mapped to any tag key.
+    fn unknown_fields(&self) -> Vec<(String, String)> {
+        let known_field_names = TagKey::ALL
+            .iter()
+            .filter_map(|key| self.field_name(key))
+            .collect::<Vec<_>>();
+        self.fields()
+            .into_iter()
+            .filter(|(name, _)| {
+                !known_field_names.iter().any(|known_name| {
+                    known_name.eq_ignore_ascii_case(name)
+                        || name
+                            .strip_prefix(known_name.as_str())
+                            .is_some_and(|rest| rest.starts_with(':'))
+                })
+            })
+            .collect()
+    }
+    /// Get the provenance of the value for the tag key, if this tag contains a value for it.
+    fn provenance(&self, key: &TagKey) -> Option<ValueProvenance> {
+        self.get(key)?;
+        Some(ValueProvenance::Tag {
+            tag_type: self.tag_type(),
+            field_name: self.field_name(key),
+        })
+    }
+    /// Get all string values for the tag key.
+    fn get_multiple<'a>(&'a self, key: &'a TagKey) -> Vec<Cow<'a, str>> {
+        self.get(key).into_iter().collect()
+    }
     /// Set the value for tag key..
     fn set(&mut self, key: &TagKey, value: Cow<'_, str>);
     /// Set the value for tag key to multiple values.
@@ -284,9 +663,30 @@ pub trait Tag: Send + Sync {
         None
     }
 
+    /// Get mutable reference to the underlying [`ape::ApeTag`] (if this is an APEv2 tag).
+    fn maybe_as_ape_mut(&mut self) -> Option<&mut ape::ApeTag> {
+        None
+    }
+
+    /// Get mutable reference to the underlying [`flac::FlacTag`] (if this is a FLAC tag).
+    #[cfg(feature = "flac")]
+    fn maybe_as_flac_mut(&mut self) -> Option<&mut flac::FlacTag> {
+        None
+    }
+
     /// Get the list of performers and instruments.
     fn performers(&self) -> Option<Vec<InvolvedPerson<'_>>>;
 
+    /// Get the technical properties of the audio stream (if the tag format provides them).
+    fn stream_info(&self) -> Option<StreamInfo> {
+        None
+    }
+
+    /// Get the embedded cue sheet (if any).
+    fn embedded_cuesheet(&self) -> Option<EmbeddedCueSheet> {
+        None
+    }
+
     /// Get the list performers for the given instrument.
     fn performer(&self, instrument: &str) -> Option<Vec<InvolvedPerson<'_>>> {
         self.performers().map(|performers| {
@@ -298,8 +698,64 @@ pub trait Tag: Send + Sync {
     }
 }
 
+/// Email address of the rating owner if none is configured (as used by MusicBrainz Picard).
+pub const DEFAULT_RATING_EMAIL: &str = "users@musicbrainz.org";
+
+/// Parse a rating value (in stars) from a tag value.
+///
+/// Fractional values are rounded to the nearest star. Returns `None` for values that are not
+/// numeric or outside the range from 0 to 5.
+#[must_use]
+pub fn parse_rating_stars(value: &str) -> Option<u8> {
+    let value = value.trim().parse::<f64>().ok()?;
+    if !(0.0..=5.0).contains(&value) {
+        return None;
+    }
+    #[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let stars = value.round() as u8;
+    Some(stars)
+}
+
+/// Apply the format-specific options of the tag configuration (e.g., how ID3 user-defined text
+/// frames and comment frames are matched and where ratings are stored) to the tags.
+#[cfg_attr(not(any(feature = "id3", feature = "flac")), expect(unused_variables))]
+fn apply_format_options(tags: &mut [Box<dyn Tag>], config: &TagsConfig) {
+    for tag in tags {
+        #[cfg(feature = "id3")]
+        if let Some(tag) = tag.maybe_as_id3v2_mut() {
+            tag.set_case_insensitive_descriptions(config.id3_case_insensitive_descriptions);
+            tag.set_comment_frame(
+                &config.id3_comment.language,
+                &config.id3_comment.description,
+            );
+            tag.set_rating_options(&config.rating);
+            tag.set_itunes_grouping(config.id3_itunes_grouping);
+        }
+        #[cfg(feature = "flac")]
+        if let Some(tag) = tag.maybe_as_flac_mut() {
+            tag.set_rating_email(&config.rating.email);
+            tag.set_beets_fields(config.vorbis_beets_fields);
+        }
+    }
+}
+
+/// Return a vector of tags from the file at the given path, with the format-specific options of
+/// the tag configuration applied.
+///
+/// If a file contains several tags (e.g., ID3v2 and APEv2 tags in an MP3 file), the primary tag
+/// comes first, so that its values take precedence when reading. Secondary tags only provide
+/// values for keys that are missing from the primary tag.
+pub fn read_tags_from_path(
+    path: impl AsRef<Path>,
+    config: &TagsConfig,
+) -> crate::Result<Vec<Box<dyn Tag>>> {
+    let mut tags = read_tags_without_options(path)?;
+    apply_format_options(&mut tags, config);
+    Ok(tags)
+}
+
 /// Return a vector of tags from the file at the given path.
-pub fn read_tags_from_path(path: impl AsRef<Path>) -> crate::Result<Vec<Box<dyn Tag>>> {
+fn read_tags_without_options(path: impl AsRef<Path>) -> crate::Result<Vec<Box<dyn Tag>>> {
     path.as_ref()
         .extension()
         .map(std::ffi::OsStr::to_ascii_lowercase)
@@ -308,21 +764,238 @@ pub fn read_tags_from_path(path: impl AsRef<Path>) -> crate::Result<Vec<Box<dyn
             extension
                 .to_str()
                 .ok_or(crate::Error::UnknownFileType)
-                .map(|ext| match ext {
-                    #[cfg(feature = "id3")]
-                    "mp3" => id3::ID3v2Tag::read_from_path(&path)
-                        .map(Box::new)
-                        .map(|tag| Box::<dyn Tag>::from(tag))
-                        .map(|tag| vec![tag]),
-                    #[cfg(feature = "flac")]
-                    "flac" => flac::FlacTag::read_from_path(&path)
-                        .map(Box::new)
-                        .map(|tag| Box::<dyn Tag>::from(tag))
-                        .map(|tag| vec![tag]),
-                    ext => {
-                        log::debug!("Unknown file extension {:?}", ext);
-                        Err(crate::Error::UnknownFileType)
+                .map(|ext| -> crate::Result<Vec<Box<dyn Tag>>> {
+                    match ext {
+                        "mp3" => {
+                            let mut tags: Vec<Box<dyn Tag>> = vec![];
+                            #[cfg(feature = "id3")]
+                            tags.push(Box::new(id3::ID3v2Tag::read_from_path(&path)?));
+                            // A malformed APEv2 tag must not make the ID3v2 tag unreadable.
+                            match ape::ApeTag::read_from_path(&path) {
+                                Ok(Some(tag)) => tags.push(Box::new(tag)),
+                                Ok(None) => (),
+                                Err(err) => {
+                                    log::warn!(
+                                        "Ignoring malformed APEv2 tag in {}: {err}",
+                                        path.as_ref().display()
+                                    );
+                                }
+                            }
+                            Ok(tags)
+                        }
+                        #[cfg(feature = "flac")]
+                        "flac" => flac::FlacTag::read_from_path(&path)
+                            .map(Box::new)
+                            .map(|tag| Box::<dyn Tag>::from(tag))
+                            .map(|tag| vec![tag]),
+                        "wav" => {
+                            let mut tags: Vec<Box<dyn Tag>> = vec![];
+                            #[cfg(feature = "id3")]
+                            tags.push(Box::new(id3::ID3v2Tag::read_from_path(&path)?));
+                            tags.push(Box::new(riff::RiffInfoTag::read_from_path(&path)?));
+                            Ok(tags)
+                        }
+                        #[cfg(feature = "id3")]
+                        "aif" | "aiff" => id3::ID3v2Tag::read_from_path(&path)
+                            .map(Box::new)
+                            .map(|tag| Box::<dyn Tag>::from(tag))
+                            .map(|tag| vec![tag]),
+                        ext => {
+                            log::debug!("Unknown file extension {:?}", ext);
+                            Err(crate::Error::UnknownFileType)
+                        }
                     }
                 })?
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_key_from_str() {
+        assert_eq!("album".parse::<TagKey>(), Ok(TagKey::Album));
+        assert_eq!("AlbumArtist".parse::<TagKey>(), Ok(TagKey::AlbumArtist));
+        assert_eq!(
+            "musicbrainz_release_id".parse::<TagKey>(),
+            Ok(TagKey::MusicBrainzReleaseId)
+        );
+        assert_eq!(
+            "replay-gain-track-gain".parse::<TagKey>(),
+            Ok(TagKey::ReplayGainTrackGain)
+        );
+        assert!("nonexistent".parse::<TagKey>().is_err());
+    }
+
+    #[test]
+    fn test_tag_key_from_str_picard_aliases() {
+        assert_eq!(
+            "albumartistsort".parse::<TagKey>(),
+            Ok(TagKey::AlbumArtistSortOrder)
+        );
+        assert_eq!("CATALOGNUMBER".parse::<TagKey>(), Ok(TagKey::CatalogNumber));
+        assert_eq!(
+            "musicbrainz_trackid".parse::<TagKey>(),
+            Ok(TagKey::MusicBrainzTrackId)
+        );
+        assert_eq!(
+            "musicbrainz_releasetrackid".parse::<TagKey>(),
+            Ok(TagKey::MusicBrainzTrackId)
+        );
+        assert_eq!("title".parse::<TagKey>(), Ok(TagKey::TrackTitle));
+        assert_eq!(
+            "performer:piano".parse::<TagKey>(),
+            Ok(TagKey::Performer("piano".to_string()))
+        );
+        assert!("composer:piano".parse::<TagKey>().is_err());
+    }
+
+    #[test]
+    fn test_tag_key_display() {
+        assert_eq!(TagKey::AlbumArtist.to_string(), "album_artist");
+        assert_eq!(
+            TagKey::MusicBrainzReleaseId.to_string(),
+            "musicbrainz_release_id"
+        );
+        assert_eq!(
+            TagKey::AcoustIdFingerprint.to_string(),
+            "acoustid_fingerprint"
+        );
+        assert_eq!(TagKey::R128TrackGain.to_string(), "r128_track_gain");
+        assert_eq!(
+            TagKey::Performer("piano".to_string()).to_string(),
+            "performer:piano"
+        );
+        for key in TagKey::ALL {
+            assert_eq!(key.to_string().parse::<TagKey>().as_ref(), Ok(key));
+        }
+    }
+
+    #[test]
+    fn test_tag_key_all() {
+        // This match is exhaustive, so a new tag key does not compile until it is added here, with
+        // the index of its entry in `TagKey::ALL`.
+        let index = |key: &TagKey| match key {
+            TagKey::AcoustId => 0,
+            TagKey::AcoustIdFingerprint => 1,
+            TagKey::Arranger => 2,
+            TagKey::Artist => 3,
+            TagKey::ArtistSortOrder => 4,
+            TagKey::Artists => 5,
+            TagKey::Bpm => 6,
+            TagKey::Comment => 7,
+            TagKey::Composer => 8,
+            TagKey::ComposerSortOrder => 9,
+            TagKey::Conductor => 10,
+            TagKey::Copyright => 11,
+            TagKey::Director => 12,
+            TagKey::DjMixer => 13,
+            TagKey::EncodedBy => 14,
+            TagKey::EncoderSettings => 15,
+            TagKey::Engineer => 16,
+            TagKey::Genre => 17,
+            TagKey::InitialKey => 18,
+            TagKey::Isrc => 19,
+            TagKey::Language => 20,
+            TagKey::License => 21,
+            TagKey::Lyricist => 22,
+            TagKey::Lyrics => 23,
+            TagKey::Mixer => 24,
+            TagKey::Mood => 25,
+            TagKey::Movement => 26,
+            TagKey::MovementCount => 27,
+            TagKey::MovementNumber => 28,
+            TagKey::MusicBrainzArtistId => 29,
+            TagKey::MusicBrainzOriginalArtistId => 30,
+            TagKey::MusicBrainzOriginalReleaseId => 31,
+            TagKey::MusicBrainzRecordingId => 32,
+            TagKey::MusicBrainzTrackId => 33,
+            TagKey::MusicBrainzTrmId => 34,
+            TagKey::MusicBrainzWorkId => 35,
+            TagKey::MusicIpFingerprint => 36,
+            TagKey::MusicIpPuid => 37,
+            TagKey::OriginalAlbum => 38,
+            TagKey::OriginalArtist => 39,
+            TagKey::OriginalFilename => 40,
+            TagKey::OriginalReleaseDate => 41,
+            TagKey::OriginalReleaseYear => 42,
+            TagKey::Performers => 43,
+            TagKey::Producer => 44,
+            TagKey::Rating => 45,
+            TagKey::Remixer => 46,
+            TagKey::ReplayGainAlbumGain => 47,
+            TagKey::ReplayGainAlbumPeak => 48,
+            TagKey::ReplayGainAlbumRange => 49,
+            TagKey::ReplayGainReferenceLoudness => 50,
+            TagKey::ReplayGainTrackGain => 51,
+            TagKey::ReplayGainTrackPeak => 52,
+            TagKey::ReplayGainTrackRange => 53,
+            TagKey::R128AlbumGain => 54,
+            TagKey::R128TrackGain => 55,
+            TagKey::TrackNumber => 56,
+            TagKey::TrackTitle => 57,
+            TagKey::TrackTitleSortOrder => 58,
+            TagKey::ArtistWebsite => 59,
+            TagKey::WorkTitle => 60,
+            TagKey::Writer => 61,
+            TagKey::DiscNumber => 62,
+            TagKey::DiscSubtitle => 63,
+            TagKey::GaplessPlayback => 64,
+            TagKey::Media => 65,
+            TagKey::MusicBrainzDiscId => 66,
+            TagKey::TotalTracks => 67,
+            TagKey::Album => 68,
+            TagKey::AlbumArtist => 69,
+            TagKey::AlbumArtistSortOrder => 70,
+            TagKey::AlbumSortOrder => 71,
+            TagKey::Asin => 72,
+            TagKey::Barcode => 73,
+            TagKey::CatalogNumber => 74,
+            TagKey::Compilation => 75,
+            TagKey::Grouping => 76,
+            TagKey::MusicBrainzReleaseArtistId => 77,
+            TagKey::MusicBrainzReleaseGroupId => 78,
+            TagKey::MusicBrainzReleaseId => 79,
+            TagKey::RecordLabel => 80,
+            TagKey::ReleaseCountry => 81,
+            TagKey::ReleaseDate => 82,
+            TagKey::ReleaseYear => 83,
+            TagKey::ReleaseStatus => 84,
+            TagKey::ReleaseType => 85,
+            TagKey::Script => 86,
+            TagKey::TotalDiscs => 87,
+            TagKey::Podcast => 88,
+            TagKey::PodcastUrl => 89,
+            TagKey::ShowName => 90,
+            TagKey::ShowNameSortOrder => 91,
+            TagKey::ShowMovement => 92,
+            TagKey::Subtitle => 93,
+            TagKey::Performer(_) => usize::MAX,
+        };
+        for (position, key) in TagKey::ALL.iter().enumerate() {
+            assert_eq!(
+                index(key),
+                position,
+                "{key:?} is not at its position in TagKey::ALL"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "id3")]
+    fn test_read_tags_from_path_with_malformed_ape_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.mp3");
+        // APEv2 footer whose tag size exceeds the file size.
+        let mut footer = b"APETAGEX".to_vec();
+        footer.extend_from_slice(&2000u32.to_le_bytes());
+        footer.extend_from_slice(&u32::MAX.to_le_bytes());
+        footer.extend_from_slice(&[0; 16]);
+        std::fs::write(&path, footer).unwrap();
+
+        let mut tags = read_tags_from_path(&path, &crate::Config::default().tags).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert!(tags[0].maybe_as_id3v2_mut().is_some());
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Support for RIFF INFO tags in WAV files.
+
+use crate::tag::{Tag, TagKey, TagType};
+use crate::track::InvolvedPerson;
+use crate::util::parse_year_from_str;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Identifier of a chunk or INFO field.
+type ChunkId = [u8; 4];
+
+/// Error for files that are not valid RIFF/WAVE files.
+fn invalid_data_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a RIFF/WAVE file")
+}
+
+/// Read and check the RIFF/WAVE file header.
+fn read_riff_header(reader: &mut impl Read) -> io::Result<()> {
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(invalid_data_error());
+    }
+    Ok(())
+}
+
+/// Read the header of the next chunk, or `None` at the end of the file.
+fn read_chunk_header(reader: &mut impl Read) -> io::Result<Option<(ChunkId, u32)>> {
+    let mut header = [0; 8];
+    match reader.read_exact(&mut header) {
+        Ok(()) => (),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let mut id = [0; 4];
+    id.copy_from_slice(&header[0..4]);
+    let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    Ok(Some((id, size)))
+}
+
+/// Returns the chunk size including the padding byte for odd sizes.
+fn padded_size(size: u32) -> u64 {
+    u64::from(size) + u64::from(size & 1)
+}
+
+/// Parse the fields of a `LIST` chunk with the list type `INFO`.
+fn parse_info_fields(mut data: &[u8]) -> Vec<(ChunkId, String)> {
+    let mut fields = vec![];
+    while data.len() >= 8 {
+        let mut id = [0; 4];
+        id.copy_from_slice(&data[0..4]);
+        let size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        let Ok(size) = usize::try_from(size) else {
+            break;
+        };
+        let Some(value) = data.get(8..8 + size) else {
+            break;
+        };
+        let value = String::from_utf8_lossy(value)
+            .trim_end_matches('\0')
+            .to_string();
+        fields.push((id, value));
+        data = data.get(8 + size + (size & 1)..).unwrap_or_default();
+    }
+    fields
+}
+
+/// RIFF INFO tag.
+#[derive(Debug, Default)]
+pub struct RiffInfoTag {
+    /// The INFO fields.
+    data: Vec<(ChunkId, String)>,
+}
+
+impl RiffInfoTag {
+    /// Create a new, empty tag.
+    #[cfg(test)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the RIFF INFO tag from the path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a RIFF/WAVE file.
+    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        read_riff_header(&mut reader)?;
+
+        let mut data = vec![];
+        while let Some((id, size)) = read_chunk_header(&mut reader)? {
+            if &id == b"LIST" {
+                let mut chunk = vec![];
+                let _ = (&mut reader)
+                    .take(padded_size(size))
+                    .read_to_end(&mut chunk)?;
+                if chunk.get(0..4) == Some(b"INFO") {
+                    data.extend(parse_info_fields(&chunk[4..]));
+                }
+            } else {
+                let offset = i64::try_from(padded_size(size)).map_err(|_| invalid_data_error())?;
+                reader.seek_relative(offset)?;
+            }
+        }
+
+        Ok(Self { data })
+    }
+
+    /// Get the INFO field identifier for a tag key.
+    fn tag_key_to_field(key: &TagKey) -> Option<&'static ChunkId> {
+        match key {
+            TagKey::Album => Some(b"IPRD"),
+            TagKey::Artist => Some(b"IART"),
+            TagKey::Comment => Some(b"ICMT"),
+            TagKey::Composer => Some(b"IMUS"),
+            TagKey::Copyright => Some(b"ICOP"),
+            TagKey::EncodedBy => Some(b"ITCH"),
+            TagKey::EncoderSettings => Some(b"ISFT"),
+            TagKey::Engineer => Some(b"IENG"),
+            TagKey::Genre => Some(b"IGNR"),
+            TagKey::Isrc => Some(b"ISRC"),
+            TagKey::Language => Some(b"ILNG"),
+            TagKey::Media => Some(b"IMED"),
+            TagKey::Producer => Some(b"IPRO"),
+            TagKey::ReleaseCountry => Some(b"ICNT"),
+            TagKey::ReleaseDate => Some(b"ICRD"),
+            TagKey::TrackNumber => Some(b"ITRK"),
+            TagKey::TrackTitle => Some(b"INAM"),
+            TagKey::Writer => Some(b"IWRI"),
+            _ => None,
+        }
+    }
+
+    /// Serialize the INFO fields as `LIST` chunk payload (including the list type).
+    fn info_chunk_payload(&self) -> Vec<u8> {
+        let mut payload = b"INFO".to_vec();
+        for (id, value) in &self.data {
+            let mut value = value.as_bytes().to_vec();
+            value.push(0);
+            let Ok(size) = u32::try_from(value.len()) else {
+                continue;
+            };
+            payload.extend_from_slice(id);
+            payload.extend_from_slice(&size.to_le_bytes());
+            payload.extend_from_slice(&value);
+            if value.len() % 2 == 1 {
+                payload.push(0);
+            }
+        }
+        payload
+    }
+
+    /// Copy all chunks except for the INFO list from the reader to the writer, then append the
+    /// new INFO list. Returns the resulting RIFF size.
+    fn write_chunks(&self, reader: &mut impl Read, writer: &mut impl Write) -> io::Result<u32> {
+        let mut riff_size: u64 = 4;
+        while let Some((id, size)) = read_chunk_header(reader)? {
+            let mut chunk_reader = (&mut *reader).take(padded_size(size));
+            let copied_size = if &id == b"LIST" {
+                // List chunks are small, so we can read them into memory to check the list type.
+                let mut chunk = vec![];
+                let _ = chunk_reader.read_to_end(&mut chunk)?;
+                if chunk.get(0..4) == Some(b"INFO") {
+                    continue;
+                }
+                writer.write_all(&id)?;
+                writer.write_all(&size.to_le_bytes())?;
+                writer.write_all(&chunk)?;
+                u64::try_from(chunk.len()).map_err(io::Error::other)?
+            } else {
+                writer.write_all(&id)?;
+                writer.write_all(&size.to_le_bytes())?;
+                io::copy(&mut chunk_reader, writer)?
+            };
+            riff_size += 8 + copied_size;
+        }
+
+        if !self.data.is_empty() {
+            let payload = self.info_chunk_payload();
+            let size = u32::try_from(payload.len()).map_err(io::Error::other)?;
+            writer.write_all(b"LIST")?;
+            writer.write_all(&size.to_le_bytes())?;
+            writer.write_all(&payload)?;
+            riff_size += 8 + u64::from(size);
+        }
+
+        u32::try_from(riff_size).map_err(io::Error::other)
+    }
+}
+
+impl Tag for RiffInfoTag {
+    fn tag_type(&self) -> TagType {
+        TagType::RiffInfo
+    }
+
+    fn field_name(&self, key: &TagKey) -> Option<String> {
+        Self::tag_key_to_field(key).map(|field| String::from_utf8_lossy(field).into_owned())
+    }
+
+    fn fields(&self) -> Vec<(String, String)> {
+        self.data
+            .iter()
+            .map(|(id, value)| (String::from_utf8_lossy(id).into_owned(), value.clone()))
+            .collect()
+    }
+
+    fn get<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
+        Self::tag_key_to_field(key)
+            .and_then(|field| self.data.iter().find(|(id, _)| id == field))
+            .map(|(_, value)| Cow::from(value.as_str()))
+            .or_else(|| match key {
+                TagKey::ReleaseYear => self
+                    .get(&TagKey::ReleaseDate)
+                    .and_then(|value| parse_year_from_str(&value).map(Cow::from)),
+                _ => None,
+            })
+    }
+
+    fn set(&mut self, key: &TagKey, value: Cow<'_, str>) {
+        if let Some(field) = Self::tag_key_to_field(key) {
+            if let Some((_, old_value)) = self.data.iter_mut().find(|(id, _)| id == field) {
+                *old_value = value.into_owned();
+            } else {
+                self.data.push((*field, value.into_owned()));
+            }
+        }
+    }
+
+    fn set_multiple<'a>(&'a mut self, key: &TagKey, values: &[Cow<'a, str>]) {
+        // RIFF INFO fields only support a single value.
+        if let Some(value) = values.first() {
+            self.set(key, value.clone());
+        } else {
+            self.clear(key);
+        }
+    }
+
+    fn clear(&mut self, key: &TagKey) {
+        if let Some(field) = Self::tag_key_to_field(key) {
+            self.data.retain(|(id, _)| id != field);
+        }
+    }
+
+    fn write(&mut self, path: &Path) -> crate::Result<()> {
+        let dir = path
+            .parent()
+            .ok_or(io::Error::other("cannot determine directory"))?;
+        let mut reader = BufReader::new(File::open(path)?);
+        read_riff_header(&mut reader)?;
+
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(".helicon.")
+            .suffix(".tmp")
+            .tempfile_in(dir)?;
+        {
+            let mut writer = BufWriter::new(temp_file.as_file_mut());
+            writer.write_all(b"RIFF\0\0\0\0WAVE")?;
+            let riff_size = self.write_chunks(&mut reader, &mut writer)?;
+            let _ = writer.seek(SeekFrom::Start(4))?;
+            writer.write_all(&riff_size.to_le_bytes())?;
+            writer.flush()?;
+        }
+        temp_file
+            .into_temp_path()
+            .persist(path)
+            .map_err(io::Error::from)?;
+        Ok(())
+    }
+
+    fn performers(&self) -> Option<Vec<InvolvedPerson<'_>>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path to a WAV file tagged by Picard.
+    const TAGGED_WAV: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/media/picard-2.12.3/track.wav"
+    );
+
+    #[test]
+    fn test_tag_type() {
+        let tag = RiffInfoTag::new();
+        assert_eq!(tag.tag_type(), TagType::RiffInfo);
+    }
+
+    #[test]
+    fn test_read() {
+        let tag = RiffInfoTag::read_from_path(TAGGED_WAV).unwrap();
+        assert_eq!(
+            tag.get(&TagKey::TrackTitle).as_deref(),
+            Some("But Not for Me")
+        );
+        assert_eq!(
+            tag.get(&TagKey::Artist).as_deref(),
+            Some("The Ahmad Jamal Trio")
+        );
+        assert_eq!(tag.get(&TagKey::ReleaseCountry).as_deref(), Some("US"));
+        assert_eq!(tag.get(&TagKey::ReleaseYear).as_deref(), Some("1958"));
+        assert_eq!(
+            tag.get(&TagKey::Composer).as_deref(),
+            Some("George Gershwin")
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("track.wav");
+        let _ = std::fs::copy(TAGGED_WAV, &path).unwrap();
+
+        let mut tag = RiffInfoTag::read_from_path(&path).unwrap();
+        tag.set(&TagKey::TrackTitle, Cow::from("Foo"));
+        tag.clear(&TagKey::Artist);
+        tag.write(&path).unwrap();
+
+        let tag = RiffInfoTag::read_from_path(&path).unwrap();
+        assert_eq!(tag.get(&TagKey::TrackTitle).as_deref(), Some("Foo"));
+        assert!(tag.get(&TagKey::Artist).is_none());
+        assert_eq!(tag.get(&TagKey::Genre).as_deref(), Some("Jazz"));
+
+        // The RIFF size must be updated and all other chunks must be preserved.
+        let mut reader = BufReader::new(File::open(&path).unwrap());
+        let mut header = [0; 8];
+        reader.read_exact(&mut header).unwrap();
+        let riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let file_size = std::fs::metadata(&path).unwrap().len();
+        assert_eq!(u64::from(riff_size) + 8, file_size);
+        let _ = reader.seek(SeekFrom::Start(12)).unwrap();
+        let mut chunk_ids = vec![];
+        while let Some((id, size)) = read_chunk_header(&mut reader).unwrap() {
+            chunk_ids.push(id);
+            reader
+                .seek_relative(i64::try_from(padded_size(size)).unwrap())
+                .unwrap();
+        }
+        assert_eq!(chunk_ids, vec![*b"fmt ", *b"data", *b"id3 ", *b"LIST"]);
+    }
+}
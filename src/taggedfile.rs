@@ -8,18 +8,32 @@
 
 //! The [`TaggedFile`] struct represents a file that contains tags.
 
-use crate::analyzer::CompoundAnalyzerResult;
+use crate::analyzer::{self, CompoundAnalyzerResult, TechnicalMetadata};
+use crate::config::{
+    ClassicalConfig, NumberFormatConfig, ReplayGainTagsConfig, TagMergePolicy, TagsConfig,
+    VorbisTotalFields,
+};
+use crate::filename_pattern::{self, FilenamePattern};
 use crate::media::MediaLike;
-use crate::release::ReleaseLike;
-use crate::tag::{read_tags_from_path, Tag, TagKey, TagType};
+use crate::release::{ReleaseEvent, ReleaseLike};
+use crate::tag::memory::MemoryTag;
+use crate::tag::{
+    read_tags_from_path, EmbeddedCueSheet, StreamInfo, Tag, TagKey, TagType, ValueProvenance,
+};
 use crate::track::{AnalyzedTrackMetadata, InvolvedPerson, TrackLike};
+use crate::util::{
+    normalize_barcode, normalize_catalog_number, normalize_date_str, parse_year_from_str,
+    set_modified_time,
+};
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt;
+use std::fs;
 use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A tagged file that contains zero or more tags.
 pub struct TaggedFile {
@@ -27,8 +41,14 @@ pub struct TaggedFile {
     pub path: PathBuf,
     /// Tags that are present in the file.
     content: Vec<Box<dyn Tag>>,
-    /// Analysis results.
-    pub analysis_results: Option<CompoundAnalyzerResult>,
+    /// Analysis results (shared with copies of this file).
+    pub analysis_results: Option<Arc<CompoundAnalyzerResult>>,
+    /// Tag keys that are never modified by `set_tag_value` and `set_tag_values`.
+    skipped_keys: Vec<TagKey>,
+    /// How `set_tag_value` and `set_tag_values` merge new values with existing ones.
+    merge_policies: Vec<(TagKey, TagMergePolicy)>,
+    /// Tag values derived from the file path, used if the tags do not contain a value.
+    path_values: Vec<(TagKey, String)>,
 }
 
 impl fmt::Debug for TaggedFile {
@@ -51,6 +71,22 @@ impl TaggedFile {
             path: PathBuf::new(),
             content,
             analysis_results: None,
+            skipped_keys: Vec::new(),
+            merge_policies: Vec::new(),
+            path_values: Vec::new(),
+        }
+    }
+
+    /// Create a tagged file for the given path from tags that were not read from the file itself.
+    #[must_use]
+    pub fn with_tags(path: PathBuf, content: Vec<Box<dyn Tag>>) -> Self {
+        TaggedFile {
+            path,
+            content,
+            analysis_results: None,
+            skipped_keys: Vec::new(),
+            merge_policies: Vec::new(),
+            path_values: Vec::new(),
         }
     }
 
@@ -123,16 +159,20 @@ impl TaggedFile {
         }
     }
 
-    /// Creates a [`TaggedFile`] from the path.
+    /// Creates a [`TaggedFile`] from the path, with the format-specific options of the tag
+    /// configuration applied.
     ///
     /// # Errors
     ///
     /// Returns an error in case the file at the given path does not exist or is unsupported.
-    pub fn read_from_path(path: impl AsRef<Path>) -> crate::Result<Self> {
-        read_tags_from_path(path.as_ref()).map(|content| Self {
+    pub fn read_from_path(path: impl AsRef<Path>, config: &TagsConfig) -> crate::Result<Self> {
+        read_tags_from_path(path.as_ref(), config).map(|content| Self {
             path: path.as_ref().to_path_buf(),
             content,
             analysis_results: None,
+            skipped_keys: Vec::new(),
+            merge_policies: Vec::new(),
+            path_values: Vec::new(),
         })
     }
 
@@ -142,34 +182,280 @@ impl TaggedFile {
         mut self,
         analysis_results: Option<CompoundAnalyzerResult>,
     ) -> Self {
-        self.analysis_results = analysis_results;
+        self.analysis_results = analysis_results.map(Arc::new);
         self
     }
 
+    /// Create a copy of this file whose tags are only kept in memory, so that changes to the copy
+    /// are never written to the file (e.g., to preview tag changes).
+    ///
+    /// All values of each tag key (including performers) are copied, but stream information is
+    /// not.
+    #[must_use]
+    pub fn to_memory_copy(&self) -> Self {
+        let content = self
+            .content
+            .iter()
+            .map(|tag| {
+                let mut memory_tag = MemoryTag::new();
+                for key in tag.keys().iter().filter(|key| **key != TagKey::Performers) {
+                    memory_tag.set_multiple(key, &tag.get_multiple(key));
+                }
+                let mut performers: Vec<(Cow<'_, str>, Vec<Cow<'_, str>>)> = Vec::new();
+                for performer in tag.performers().into_iter().flatten() {
+                    match performers
+                        .iter_mut()
+                        .find(|(involvement, _)| *involvement == performer.involvement)
+                    {
+                        Some((_, involvees)) => involvees.push(performer.involvee),
+                        None => performers.push((performer.involvement, vec![performer.involvee])),
+                    }
+                }
+                for (involvement, involvees) in performers {
+                    memory_tag
+                        .set_multiple(&TagKey::Performer(involvement.into_owned()), &involvees);
+                }
+                Box::new(memory_tag) as Box<dyn Tag>
+            })
+            .collect();
+        Self {
+            path: self.path.clone(),
+            content,
+            analysis_results: self.analysis_results.clone(),
+            skipped_keys: self.skipped_keys.clone(),
+            merge_policies: self.merge_policies.clone(),
+            path_values: self.path_values.clone(),
+        }
+    }
+
+    /// Returns `true` if the tags of this file differ from the tags that are currently stored in
+    /// the file (i.e., if writing the tags would modify the file).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored tags cannot be read.
+    pub fn has_tag_changes(&self, config: &TagsConfig) -> crate::Result<bool> {
+        let stored_tags = read_tags_from_path(&self.path, config)?;
+        Ok(stored_tags.len() != self.content.len()
+            || self
+                .content
+                .iter()
+                .zip(&stored_tags)
+                .any(|(tag, stored_tag)| {
+                    tag.tag_type() != stored_tag.tag_type()
+                        || tag.performers() != stored_tag.performers()
+                        || TagKey::ALL
+                            .iter()
+                            .any(|key| tag.get_multiple(key) != stored_tag.get_multiple(key))
+                }))
+    }
+
+    /// Read the tags of the file at the given path and use it for this track from now on.
+    ///
+    /// All values of the current tags are transferred to the tags of the new file. This is used
+    /// for virtual tracks (e.g., from a cue sheet) once their audio has been written to a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tags of the new file cannot be read.
+    pub fn move_tags_to_file(&mut self, path: PathBuf, config: &TagsConfig) -> crate::Result<()> {
+        let mut content = read_tags_from_path(&path, config)?;
+        for old_tag in &self.content {
+            for key in old_tag.keys() {
+                let values = old_tag.get_multiple(&key);
+                for tag in &mut content {
+                    tag.set_multiple(&key, &values);
+                }
+            }
+        }
+        self.content = content;
+        self.path = path;
+        Ok(())
+    }
+
     /// Returns zero or more [`Tag`] objects.
     #[must_use]
     pub fn tags(&self) -> &[Box<dyn Tag>] {
         &self.content
     }
 
+    /// Returns a short description of the audio format (e.g., `FLAC 44.1 kHz/16 bit`).
+    ///
+    /// If the tag format does not provide the stream properties, the average bitrate is estimated
+    /// from the file size and the track length instead.
+    pub fn format_description(&self) -> String {
+        let format = self.path.extension().map_or_else(
+            || "Unknown".to_string(),
+            |extension| extension.to_string_lossy().to_ascii_uppercase(),
+        );
+        if let Some(stream_info) = self.stream_info() {
+            return format!(
+                "{format} {sample_rate} kHz/{bits_per_sample} bit",
+                sample_rate = f64::from(stream_info.sample_rate) / 1000.0,
+                bits_per_sample = stream_info.bits_per_sample,
+            );
+        }
+
+        if let Some(bitrate) = self.bitrate() {
+            return format!("{format} {bitrate} kbps");
+        }
+
+        let file_size = fs::metadata(&self.path).ok().map(|metadata| metadata.len());
+        match file_size
+            .zip(self.track_length())
+            .and_then(|(file_size, track_length)| {
+                analyzer::estimate_bitrate(file_size, track_length)
+            }) {
+            Some(bitrate) => format!("{format} {bitrate} kbps"),
+            None => format,
+        }
+    }
+
+    /// Returns the technical metadata that was determined during the analysis (if any).
+    fn technical_metadata(&self) -> Option<&TechnicalMetadata> {
+        self.analysis_results
+            .as_ref()
+            .and_then(|results| results.technical_metadata.as_ref())
+    }
+
+    /// Derive fallback tag values from the file path using the first matching pattern.
+    #[must_use]
+    pub fn with_path_values(mut self, patterns: &[FilenamePattern]) -> Self {
+        self.path_values = filename_pattern::match_patterns(patterns, &self.path);
+        self
+    }
+
     /// Yields all values for the given [`TagKey`].
+    ///
+    /// If none of the tags contain a value, the value derived from the file path is used instead.
     pub fn tag_values<'a>(&'a self, key: &'a TagKey) -> impl Iterator<Item = Cow<'a, str>> {
-        self.tags().iter().filter_map(move |tag| tag.get(key))
+        let mut values = self
+            .tags()
+            .iter()
+            .filter_map(move |tag| tag.get(key))
+            .peekable();
+        let path_value = if values.peek().is_none() {
+            self.path_values
+                .iter()
+                .find(|(path_key, _)| path_key == key)
+                .map(|(_, value)| Cow::from(value.as_str()))
+        } else {
+            None
+        };
+        values.chain(path_value)
+    }
+
+    /// Set the tag keys that are never modified when assigning metadata (e.g., because they are
+    /// maintained manually).
+    pub fn set_skipped_keys(&mut self, keys: &[TagKey]) {
+        self.skipped_keys = keys.to_vec();
+    }
+
+    /// Set the field names that are used for the total number of tracks and discs in Vorbis
+    /// comments.
+    #[cfg_attr(not(feature = "flac"), expect(unused_variables))]
+    pub fn set_vorbis_total_fields(&mut self, total_fields: VorbisTotalFields) {
+        #[cfg(feature = "flac")]
+        for tag in &mut self.content {
+            if let Some(tag) = tag.maybe_as_flac_mut() {
+                tag.set_total_fields(total_fields);
+            }
+        }
     }
 
-    /// Yields all values for the given [`TagKey`].
+    /// Merge the values of APEv2 tags into the other tags of this file and remove the APEv2 tags
+    /// from the file when the tags are written next.
+    ///
+    /// Values are only copied if the other tags do not contain a value for the key yet. Files that
+    /// only contain APEv2 tags are left unchanged.
+    pub fn strip_ape_tags(&mut self) {
+        let (ape_tags, mut other_tags): (Vec<_>, Vec<_>) = self
+            .content
+            .iter_mut()
+            .partition(|tag| tag.tag_type() == TagType::Ape);
+        if other_tags.is_empty() {
+            return;
+        }
+
+        for ape_tag in ape_tags {
+            for key in ape_tag.keys() {
+                let values = ape_tag
+                    .get_multiple(&key)
+                    .into_iter()
+                    .map(|value| Cow::from(value.into_owned()))
+                    .collect::<Vec<_>>();
+                for tag in &mut other_tags {
+                    if tag.get(&key).is_none() {
+                        tag.set_multiple(&key, &values);
+                    }
+                }
+            }
+            if let Some(ape_tag) = ape_tag.maybe_as_ape_mut() {
+                ape_tag.set_strip(true);
+            }
+        }
+    }
+
+    /// Returns `true` if the given [`TagKey`] is never modified when assigning metadata.
+    #[must_use]
+    pub fn is_skipped_key(&self, key: &TagKey) -> bool {
+        self.skipped_keys.contains(key)
+    }
+
+    /// Set the merge policies for individual tag keys. Keys without a policy are overwritten.
+    pub fn set_merge_policies(&mut self, merge_policies: &[(TagKey, TagMergePolicy)]) {
+        self.merge_policies = merge_policies.to_vec();
+    }
+
+    /// Returns the merge policy for the given [`TagKey`].
+    #[must_use]
+    pub fn merge_policy(&self, key: &TagKey) -> TagMergePolicy {
+        self.merge_policies
+            .iter()
+            .find(|(policy_key, _)| policy_key == key)
+            .map(|(_, policy)| *policy)
+            .unwrap_or_default()
+    }
+
+    /// Set the value for the given [`TagKey`], according to its merge policy.
     #[expect(clippy::needless_pass_by_value)]
     pub fn set_tag_value(&mut self, key: &TagKey, value: Option<Cow<'_, str>>) {
-        self.content
-            .iter_mut()
-            .for_each(|tag| tag.set_or_clear(key, value.clone()));
+        if self.is_skipped_key(key) {
+            return;
+        }
+        let merge_policy = self.merge_policy(key);
+        for tag in &mut self.content {
+            match merge_policy {
+                TagMergePolicy::Overwrite => tag.set_or_clear(key, value.clone()),
+                TagMergePolicy::FillIfEmpty => {
+                    if tag.get(key).is_none() {
+                        tag.set_or_clear(key, value.clone());
+                    }
+                }
+                TagMergePolicy::AppendMultivalue => {
+                    append_tag_values(tag.as_mut(), key, value.as_slice());
+                }
+            }
+        }
     }
 
-    /// Yields all values for the given [`TagKey`].
+    /// Set the values for the given [`TagKey`], according to its merge policy.
     pub fn set_tag_values(&mut self, key: &TagKey, values: &[Cow<'_, str>]) {
-        self.content
-            .iter_mut()
-            .for_each(|tag| tag.set_multiple(key, values));
+        if self.is_skipped_key(key) {
+            return;
+        }
+        let merge_policy = self.merge_policy(key);
+        for tag in &mut self.content {
+            match merge_policy {
+                TagMergePolicy::Overwrite => tag.set_multiple(key, values),
+                TagMergePolicy::FillIfEmpty => {
+                    if tag.get(key).is_none() {
+                        tag.set_multiple(key, values);
+                    }
+                }
+                TagMergePolicy::AppendMultivalue => append_tag_values(tag.as_mut(), key, values),
+            }
+        }
     }
 
     /// Returns the first value for the given [`TagKey`].
@@ -188,8 +474,18 @@ impl TaggedFile {
         );
         self.set_tag_value(&TagKey::AlbumSortOrder, release.release_sort_order());
         self.set_tag_value(&TagKey::Asin, release.asin());
-        self.set_tag_value(&TagKey::Barcode, release.barcode());
-        self.set_tag_value(&TagKey::CatalogNumber, release.catalog_number());
+        self.set_tag_value(
+            &TagKey::Barcode,
+            release
+                .barcode()
+                .map(|value| Cow::from(normalize_barcode(&value))),
+        );
+        self.set_tag_value(
+            &TagKey::CatalogNumber,
+            release
+                .catalog_number()
+                .map(|value| Cow::from(normalize_catalog_number(&value))),
+        );
         self.set_tag_value(&TagKey::Compilation, release.compilation());
         self.set_tag_value(&TagKey::Grouping, release.grouping());
         self.set_tag_value(
@@ -208,10 +504,42 @@ impl TaggedFile {
         self.set_tag_value(&TagKey::ReleaseCountry, release.release_country());
         self.set_tag_value(&TagKey::ReleaseDate, release.release_date());
         self.set_tag_value(&TagKey::ReleaseYear, release.release_year());
+        // The first release of the release group takes precedence over the first release of the
+        // recording, which may have been released earlier on a different album.
+        if let Some(original_release_date) = release.original_release_date() {
+            self.set_tag_value(&TagKey::OriginalReleaseDate, Some(original_release_date));
+            self.set_tag_value(
+                &TagKey::OriginalReleaseYear,
+                release.original_release_year(),
+            );
+        }
         self.set_tag_value(&TagKey::ReleaseStatus, release.release_status());
         self.set_tag_value(&TagKey::ReleaseType, release.release_type());
         self.set_tag_value(&TagKey::Script, release.script());
-        self.set_tag_value(&TagKey::TotalDiscs, release.total_discs());
+        // The number of media is preferred over the reported total, which may be missing.
+        let media_count = release.media().count();
+        self.set_tag_value(
+            &TagKey::TotalDiscs,
+            if media_count > 0 {
+                Some(Cow::from(media_count.to_string()))
+            } else {
+                release.total_discs()
+            },
+        );
+    }
+
+    /// Assign the country and date of a release event (e.g., the one in the preferred country).
+    pub fn assign_tags_from_release_event(&mut self, event: &ReleaseEvent<'_>) {
+        self.set_tag_value(&TagKey::ReleaseCountry, event.country.clone());
+        self.set_tag_value(&TagKey::ReleaseDate, event.date.clone());
+        self.set_tag_value(
+            &TagKey::ReleaseYear,
+            event
+                .date
+                .as_deref()
+                .and_then(parse_year_from_str)
+                .map(Cow::from),
+        );
     }
 
     /// Assign metadata from a `MediaLike` struct (e.g. a disc of a MusicBrainz release).
@@ -226,14 +554,48 @@ impl TaggedFile {
         );
         self.set_tag_value(&TagKey::Media, media.media_format());
         self.set_tag_value(&TagKey::MusicBrainzDiscId, media.musicbrainz_disc_id());
+        // The number of tracks is preferred over the reported track count, which may be missing.
+        let track_count = media.media_tracks().count();
         self.set_tag_value(
             &TagKey::TotalTracks,
-            media
-                .media_track_count()
+            Some(track_count)
+                .filter(|count| *count > 0)
+                .or_else(|| media.media_track_count())
                 .map(|count| Cow::from(format!("{count}"))),
         );
     }
 
+    /// Normalize the release dates to the `YYYY-MM-DD` format (or `YYYY-MM`/`YYYY` if the day or
+    /// month are unknown).
+    ///
+    /// Values that are not a valid date are left unchanged.
+    pub fn normalize_date_tags(&mut self) {
+        for key in [TagKey::ReleaseDate, TagKey::OriginalReleaseDate] {
+            let Some(value) = self.first_tag_value(&key).map(Cow::into_owned) else {
+                continue;
+            };
+            match normalize_date_str(&value) {
+                Some(date) if date != value => self.set_tag_value(&key, Some(Cow::from(date))),
+                Some(_) => (),
+                None => log::warn!("Invalid date {value:?} in {key} of {}", self.path.display()),
+            }
+        }
+    }
+
+    /// Reformat the track and disc number tags according to the number format configuration.
+    pub fn format_number_tags(&mut self, number_format: &NumberFormatConfig) {
+        for (key, total_key) in [
+            (TagKey::TrackNumber, TagKey::TotalTracks),
+            (TagKey::DiscNumber, TagKey::TotalDiscs),
+        ] {
+            let value = self.first_tag_value(&key).map(|number| {
+                let total = self.first_tag_value(&total_key);
+                number_format.format_tag_value(&number, total.as_deref())
+            });
+            self.set_tag_value(&key, value.map(Cow::from));
+        }
+    }
+
     /// Assign metadata from another `TrackLike` struct (e.g. a MusicBrainz track).
     pub fn assign_tags_from_track(&mut self, track: &impl TrackLike) {
         self.set_tag_value(&TagKey::AcoustId, track.acoustid());
@@ -249,7 +611,11 @@ impl TaggedFile {
         self.set_tag_value(&TagKey::Artist, track.track_artist());
         self.set_tag_value(&TagKey::ArtistSortOrder, track.track_artist_sort_order());
         self.set_tag_value(&TagKey::Artists, track.track_artist());
-        self.set_tag_value(&TagKey::Bpm, track.bpm());
+        let analyzed_bpm = self
+            .analyzed_metadata()
+            .bpm()
+            .map(|value| Cow::from(value.to_string()));
+        self.set_tag_value(&TagKey::Bpm, track.bpm().or(analyzed_bpm));
         self.set_tag_value(&TagKey::Comment, track.comment());
         self.set_tag_values(
             &TagKey::Composer,
@@ -287,10 +653,10 @@ impl TaggedFile {
         self.set_tag_value(&TagKey::Lyrics, track.lyrics());
         self.set_tag_values(&TagKey::Mixer, track.mixer().collect::<Vec<_>>().as_slice());
         self.set_tag_value(&TagKey::Mood, track.mood());
-        self.set_tag_value(&TagKey::Movement, track.movement());
-        self.set_tag_value(&TagKey::MovementCount, track.movement_count());
-        self.set_tag_value(&TagKey::MovementNumber, track.movement_number());
-        self.set_tag_value(&TagKey::MusicBrainzArtistId, track.musicbrainz_artist_id());
+        self.set_tag_values(
+            &TagKey::MusicBrainzArtistId,
+            track.musicbrainz_artist_id().collect::<Vec<_>>().as_slice(),
+        );
         self.set_tag_value(
             &TagKey::MusicBrainzOriginalArtistId,
             track.musicbrainz_original_artist_id(),
@@ -342,25 +708,7 @@ impl TaggedFile {
             &TagKey::Remixer,
             track.remixer().collect::<Vec<_>>().as_slice(),
         );
-        self.set_tag_value(
-            &TagKey::ReplayGainReferenceLoudness,
-            track.replay_gain_reference_loudness(),
-        );
-        let replay_gain_track_gain = self
-            .analyzed_metadata()
-            .replay_gain_track_gain()
-            .map(|value| Cow::from(value.to_string()));
-        self.set_tag_value(&TagKey::ReplayGainTrackGain, replay_gain_track_gain);
-        let replay_gain_track_peak = self
-            .analyzed_metadata()
-            .replay_gain_track_peak()
-            .map(|value| Cow::from(value.to_string()));
-        self.set_tag_value(&TagKey::ReplayGainTrackPeak, replay_gain_track_peak);
-        let replay_gain_track_range = self
-            .analyzed_metadata()
-            .replay_gain_track_range()
-            .map(|value| Cow::from(value.to_string()));
-        self.set_tag_value(&TagKey::ReplayGainTrackRange, replay_gain_track_range);
+        self.assign_replay_gain_track_tags();
         self.set_tag_value(&TagKey::TrackNumber, track.track_number());
         self.set_tag_value(&TagKey::TrackTitle, track.track_title());
         self.set_tag_value(&TagKey::TrackTitleSortOrder, track.track_title_sort_order());
@@ -372,6 +720,147 @@ impl TaggedFile {
         );
     }
 
+    /// Assign the work and movement tags for classical music from another `TrackLike` struct.
+    ///
+    /// The movement tags are removed from tracks whose work is not part of a larger work (e.g.,
+    /// the movement of a symphony). The movement count is set by the `TaggedFileCollection`,
+    /// because it depends on the other tracks of the release.
+    pub fn assign_classical_tags_from_track(
+        &mut self,
+        track: &impl TrackLike,
+        config: &ClassicalConfig,
+    ) {
+        let Some((work_title, movement)) = track.parent_work_title().zip(track.movement()) else {
+            self.set_tag_value(&TagKey::Movement, None);
+            self.set_tag_value(&TagKey::MovementCount, None);
+            self.set_tag_value(&TagKey::MovementNumber, None);
+            self.set_tag_value(&TagKey::ShowMovement, None);
+            return;
+        };
+
+        if config.work_movement_titles {
+            self.set_tag_value(
+                &TagKey::TrackTitle,
+                Some(Cow::from(format!("{work_title}: {movement}"))),
+            );
+        }
+        self.set_tag_value(&TagKey::WorkTitle, Some(work_title));
+        self.set_tag_value(&TagKey::Movement, Some(movement));
+        self.set_tag_value(&TagKey::MovementNumber, track.movement_number());
+        self.set_tag_value(&TagKey::ShowMovement, Some(Cow::from("1")));
+    }
+
+    /// Assign the ReplayGain track gain, peak, range and reference loudness from the analysis
+    /// results.
+    ///
+    /// Existing values are kept if the corresponding analysis result is not available.
+    pub fn assign_replay_gain_track_tags(&mut self) {
+        let values = {
+            let analyzed_metadata = self.analyzed_metadata();
+            let values = [
+                (
+                    TagKey::ReplayGainTrackGain,
+                    analyzed_metadata.replay_gain_track_gain(),
+                ),
+                (
+                    TagKey::ReplayGainTrackPeak,
+                    analyzed_metadata.replay_gain_track_peak(),
+                ),
+                (
+                    TagKey::ReplayGainTrackRange,
+                    analyzed_metadata.replay_gain_track_range(),
+                ),
+                (
+                    TagKey::ReplayGainReferenceLoudness,
+                    analyzed_metadata.replay_gain_reference_loudness(),
+                ),
+            ];
+            values.map(|(key, value)| (key, value.map(|value| value.to_string())))
+        };
+        for (key, value) in values {
+            if let Some(value) = value {
+                self.set_tag_value(&key, Some(Cow::from(value)));
+            }
+        }
+    }
+
+    /// Assign the tags that are derived from the analysis results (i.e., the AcoustID fingerprint,
+    /// the BPM and the ReplayGain track values) without touching any other tags.
+    ///
+    /// Existing values are kept if the corresponding analysis result is not available.
+    pub fn assign_analyzed_tags(&mut self, config: &ReplayGainTagsConfig) {
+        let acoustid_fingerprint = self
+            .analyzed_metadata()
+            .acoustid_fingerprint()
+            .map(|value| value.to_string());
+        if let Some(acoustid_fingerprint) = acoustid_fingerprint {
+            self.set_tag_value(
+                &TagKey::AcoustIdFingerprint,
+                Some(Cow::from(acoustid_fingerprint)),
+            );
+        }
+        let bpm = self
+            .analyzed_metadata()
+            .bpm()
+            .map(|value| value.to_string());
+        if let Some(bpm) = bpm {
+            self.set_tag_value(&TagKey::Bpm, Some(Cow::from(bpm)));
+        }
+        if self.analyzed_metadata().replay_gain_track_gain().is_some() {
+            self.assign_replay_gain_track_tags();
+            self.assign_replay_gain_compat_tags(config);
+        }
+    }
+
+    /// Write the ReplayGain track and album gains in the additional formats that are enabled in
+    /// the configuration (i.e., ID3v2.4 `RVA2` frames and Opus `R128_*_GAIN` tags).
+    pub fn assign_replay_gain_compat_tags(&mut self, config: &ReplayGainTagsConfig) {
+        let value = |key: &TagKey| {
+            self.first_tag_value(key)
+                .as_deref()
+                .and_then(analyzer::parse_replaygain_value)
+        };
+        let track_gain = value(&TagKey::ReplayGainTrackGain);
+        let album_gain = value(&TagKey::ReplayGainAlbumGain);
+        let reference_lufs = value(&TagKey::ReplayGainReferenceLoudness);
+
+        if config.r128 && self.is_opus() {
+            for (key, gain) in [
+                (TagKey::R128TrackGain, track_gain),
+                (TagKey::R128AlbumGain, album_gain),
+            ] {
+                let value = gain
+                    .map(|gain| analyzer::r128_gain_from_replaygain(gain, reference_lufs))
+                    .map(|value| Cow::from(value.to_string()));
+                self.set_tag_value(&key, value);
+            }
+        }
+
+        #[cfg(feature = "id3")]
+        if config.rva2 {
+            let peak = |key: &TagKey| {
+                self.first_tag_value(key)
+                    .and_then(|value| value.trim().parse::<f64>().ok())
+            };
+            let track_peak = peak(&TagKey::ReplayGainTrackPeak);
+            let album_peak = peak(&TagKey::ReplayGainAlbumPeak);
+            for tag in &mut self.content {
+                if let Some(tag) = tag.maybe_as_id3v2_mut() {
+                    tag.set_relative_volume_adjustment("track", track_gain, track_peak);
+                    tag.set_relative_volume_adjustment("album", album_gain, album_peak);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if this is an Opus file (the only format that defines the `R128_*_GAIN`
+    /// tags).
+    fn is_opus(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("opus"))
+    }
+
     /// Write tags to file.
     ///
     /// # Errors
@@ -384,6 +873,48 @@ impl TaggedFile {
 
         Ok(())
     }
+
+    /// Write tags to file and restore the previous modification time of the file afterwards if
+    /// `preserve_mtime` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing any underlying tag fails or the modification time cannot be
+    /// read or restored.
+    pub fn write_tags_preserving_mtime(&mut self, preserve_mtime: bool) -> crate::Result<()> {
+        let modified_time = preserve_mtime
+            .then(|| fs::metadata(&self.path).and_then(|metadata| metadata.modified()))
+            .transpose()?;
+        self.write_tags()?;
+        if let Some(modified_time) = modified_time {
+            set_modified_time(&self.path, modified_time)?;
+        }
+        Ok(())
+    }
+}
+
+/// Add values to the existing values of a tag, skipping values that are already present.
+fn append_tag_values(tag: &mut dyn Tag, key: &TagKey, values: &[Cow<'_, str>]) {
+    let mut merged_values = tag
+        .get_multiple(key)
+        .into_iter()
+        .map(Cow::into_owned)
+        .collect::<Vec<_>>();
+    let previous_count = merged_values.len();
+    for value in values {
+        if !merged_values.iter().any(|existing| existing == value) {
+            merged_values.push(value.to_string());
+        }
+    }
+    if merged_values.len() == previous_count {
+        return;
+    }
+
+    let merged_values = merged_values
+        .iter()
+        .map(|value| Cow::from(value.as_str()))
+        .collect::<Vec<_>>();
+    tag.set_multiple(key, &merged_values);
 }
 
 impl PartialEq for TaggedFile {
@@ -521,8 +1052,8 @@ impl TrackLike for TaggedFile {
         self.first_tag_value(&TagKey::MovementNumber)
     }
 
-    fn musicbrainz_artist_id(&self) -> Option<Cow<'_, str>> {
-        self.first_tag_value(&TagKey::MusicBrainzArtistId)
+    fn musicbrainz_artist_id(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.tag_values(&TagKey::MusicBrainzArtistId)
     }
 
     fn musicbrainz_original_artist_id(&self) -> Option<Cow<'_, str>> {
@@ -646,10 +1177,61 @@ impl TrackLike for TaggedFile {
     }
 
     fn track_length(&self) -> Option<chrono::TimeDelta> {
-        self.analysis_results
-            .as_ref()
-            .and_then(|results| results.track_length.as_ref())
-            .and_then(|track_length| track_length.as_ref().ok().copied())
+        // Prefer the exact duration from the stream info over the analyzed length.
+        self.stream_info()
+            .and_then(|stream_info| stream_info.duration())
+            .or_else(|| {
+                self.analysis_results
+                    .as_ref()
+                    .and_then(|results| results.track_length.as_ref())
+                    .and_then(|track_length| track_length.as_ref().ok().copied())
+            })
+    }
+
+    fn stream_info(&self) -> Option<StreamInfo> {
+        self.content.iter().find_map(|tag| tag.stream_info())
+    }
+
+    fn codec(&self) -> Option<Cow<'_, str>> {
+        self.technical_metadata()
+            .and_then(|metadata| metadata.codec.as_deref())
+            .map(Cow::from)
+    }
+
+    fn bitrate(&self) -> Option<u32> {
+        self.technical_metadata()
+            .and_then(|metadata| metadata.bitrate)
+    }
+
+    fn sample_rate(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| stream_info.sample_rate)
+            .or_else(|| {
+                self.technical_metadata()
+                    .and_then(|metadata| metadata.sample_rate)
+            })
+    }
+
+    fn bit_depth(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| u32::from(stream_info.bits_per_sample))
+            .or_else(|| {
+                self.technical_metadata()
+                    .and_then(|metadata| metadata.bit_depth)
+            })
+    }
+
+    fn channel_count(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| u32::from(stream_info.channels))
+            .or_else(|| {
+                self.technical_metadata()
+                    .and_then(|metadata| metadata.channels)
+            })
+    }
+
+    fn embedded_cuesheet(&self) -> Option<EmbeddedCueSheet> {
+        self.content.iter().find_map(|tag| tag.embedded_cuesheet())
     }
 
     fn track_path(&self) -> Option<&Path> {
@@ -665,7 +1247,39 @@ impl TrackLike for TaggedFile {
     }
 
     fn analyzed_metadata(&self) -> impl AnalyzedTrackMetadata {
-        TaggedFileAnalyzedMetadata(self.analysis_results.as_ref())
+        TaggedFileAnalyzedMetadata(self.analysis_results.as_deref())
+    }
+
+    fn tag_value<'a>(&'a self, key: &'a TagKey) -> Option<Cow<'a, str>> {
+        self.first_tag_value(key)
+    }
+
+    fn value_provenance(&self, key: &TagKey) -> Option<ValueProvenance> {
+        self.tags()
+            .iter()
+            .find_map(|tag| tag.provenance(key))
+            .or_else(|| {
+                self.path_values
+                    .iter()
+                    .any(|(path_key, _)| path_key == key)
+                    .then_some(ValueProvenance::FilePath)
+            })
+    }
+
+    fn track_length_provenance(&self) -> Option<ValueProvenance> {
+        if self
+            .stream_info()
+            .and_then(|stream_info| stream_info.duration())
+            .is_some()
+        {
+            Some(ValueProvenance::StreamInfo)
+        } else {
+            self.analysis_results
+                .as_ref()
+                .and_then(|results| results.track_length.as_ref())
+                .is_some_and(Result::is_ok)
+                .then_some(ValueProvenance::Analyzer)
+        }
     }
 }
 
@@ -697,6 +1311,20 @@ impl AnalyzedTrackMetadata for TaggedFileAnalyzedMetadata<'_> {
     fn replay_gain_track_range(&self) -> Option<Cow<'_, str>> {
         None
     }
+
+    fn replay_gain_reference_loudness(&self) -> Option<Cow<'_, str>> {
+        self.0
+            .and_then(|result| result.ebur128.as_ref())
+            .and_then(|res| res.as_ref().ok())
+            .map(|ebur128| Cow::from(ebur128.replaygain_reference_loudness_string()))
+    }
+
+    fn bpm(&self) -> Option<Cow<'_, str>> {
+        self.0
+            .and_then(|result| result.tempo.as_ref())
+            .and_then(|res| res.as_ref().ok())
+            .map(|tempo| Cow::from(tempo.bpm_string()))
+    }
 }
 
 #[cfg(test)]
@@ -760,6 +1388,69 @@ mod tests {
         assert!(tagged_file_collection.musicbrainz_release_id().is_some());
     }
 
+    #[test]
+    fn test_strip_ape_tags() {
+        use crate::tag::ape::ApeTag;
+
+        let mut ape_tag = ApeTag::new();
+        ape_tag.set(&TagKey::TrackTitle, Cow::from("Old Title"));
+        ape_tag.set(&TagKey::RecordLabel, Cow::from("Argo"));
+        let primary_tag = MemoryTag::new().with_value(TagKey::TrackTitle, Some("But Not for Me"));
+        let mut tagged_file = TaggedFile::new(vec![Box::new(primary_tag), Box::new(ape_tag)]);
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::TrackTitle).as_deref(),
+            Some("But Not for Me")
+        );
+
+        tagged_file.strip_ape_tags();
+        let primary_tag = &tagged_file.tags()[0];
+        assert_eq!(
+            primary_tag.get(&TagKey::TrackTitle).as_deref(),
+            Some("But Not for Me")
+        );
+        assert_eq!(
+            primary_tag.get(&TagKey::RecordLabel).as_deref(),
+            Some("Argo")
+        );
+    }
+
+    #[test]
+    fn test_normalize_date_tags() {
+        let tag = MemoryTag::new()
+            .with_value(TagKey::ReleaseDate, Some("01/17/1958"))
+            .with_value(TagKey::OriginalReleaseDate, Some("unknown"));
+        let mut tagged_file = TaggedFile::new(vec![Box::new(tag)]);
+        tagged_file.normalize_date_tags();
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::ReleaseDate).as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(
+            tagged_file
+                .first_tag_value(&TagKey::OriginalReleaseDate)
+                .as_deref(),
+            Some("unknown")
+        );
+    }
+
+    #[test]
+    fn test_format_number_tags() {
+        let tag = MemoryTag::new()
+            .with_value(TagKey::TrackNumber, Some("4"))
+            .with_value(TagKey::TotalTracks, Some("12"))
+            .with_value(TagKey::DiscNumber, Some("1"));
+        let mut tagged_file = TaggedFile::new(vec![Box::new(tag)]);
+        tagged_file.format_number_tags(&NumberFormatConfig {
+            pad_width: 2,
+            include_total: true,
+        });
+        assert_eq!(tagged_file.track_number().as_deref(), Some("04/12"));
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::DiscNumber).as_deref(),
+            Some("01")
+        );
+    }
+
     #[cfg(feature = "flac")]
     #[test]
     fn test_assign_tags_from_track_flac() {
@@ -783,6 +1474,96 @@ mod tests {
         assert!(tagged_file.musicbrainz_recording_id().is_some());
     }
 
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_assign_analyzed_tags() {
+        use crate::analyzer::TempoResult;
+        use crate::config::BpmPrecision;
+        use crate::tag::flac::FlacTag;
+        use crate::Config;
+
+        let mut tagged_file = TaggedFile::new(vec![Box::new(FlacTag::new())]);
+        tagged_file.set_tag_value(&TagKey::TrackTitle, Some(Cow::from("Title")));
+        tagged_file.set_tag_value(&TagKey::ReplayGainTrackGain, Some(Cow::from("-6.00 dB")));
+        let mut tagged_file = tagged_file.with_analysis_results(Some(CompoundAnalyzerResult {
+            tempo: Some(Ok(TempoResult {
+                bpm: 120.4,
+                precision: BpmPrecision::Integer,
+            })),
+            ..Default::default()
+        }));
+
+        tagged_file.assign_analyzed_tags(&Config::default().tags.replay_gain);
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::Bpm).as_deref(),
+            Some("120")
+        );
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::TrackTitle).as_deref(),
+            Some("Title")
+        );
+        assert_eq!(
+            tagged_file
+                .first_tag_value(&TagKey::ReplayGainTrackGain)
+                .as_deref(),
+            Some("-6.00 dB")
+        );
+        assert!(tagged_file
+            .first_tag_value(&TagKey::AcoustIdFingerprint)
+            .is_none());
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_write_tags_preserving_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.flac");
+        let _ = fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/media/untagged/track.flac"
+            ),
+            &path,
+        )
+        .unwrap();
+        let modified_time = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1);
+        set_modified_time(&path, modified_time).unwrap();
+
+        let config = crate::Config::default();
+        let mut tagged_file = TaggedFile::read_from_path(&path, &config.tags).unwrap();
+        tagged_file.set_tag_value(&TagKey::Bpm, Some(Cow::from("120")));
+        tagged_file.write_tags_preserving_mtime(true).unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().modified().unwrap(),
+            modified_time
+        );
+    }
+
+    #[cfg(feature = "flac")]
+    #[test]
+    fn test_has_tag_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("track.flac");
+        let _ = fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/data/media/untagged/track.flac"
+            ),
+            &path,
+        )
+        .unwrap();
+
+        let config = crate::Config::default();
+        let mut tagged_file = TaggedFile::read_from_path(&path, &config.tags).unwrap();
+        assert!(!tagged_file.has_tag_changes(&config.tags).unwrap());
+
+        tagged_file.set_tag_value(&TagKey::Bpm, Some(Cow::from("120")));
+        assert!(tagged_file.has_tag_changes(&config.tags).unwrap());
+
+        tagged_file.write_tags().unwrap();
+        assert!(!tagged_file.has_tag_changes(&config.tags).unwrap());
+    }
+
     #[cfg(feature = "flac")]
     #[test]
     fn test_assign_tags_from_release_flac() {
@@ -807,4 +1588,79 @@ mod tests {
         assert!(tagged_file_collection.release_country().is_some());
         assert!(tagged_file_collection.musicbrainz_release_id().is_some());
     }
+
+    #[test]
+    fn test_set_tag_value_merge_policies() {
+        let mut tagged_file = TaggedFile::new(vec![Box::new(
+            MemoryTag::new()
+                .with_value(TagKey::Comment, Some("My comment"))
+                .with_value(TagKey::Genre, Some("Jazz")),
+        )]);
+        tagged_file.set_merge_policies(&[
+            (TagKey::Comment, TagMergePolicy::FillIfEmpty),
+            (TagKey::Mood, TagMergePolicy::FillIfEmpty),
+            (TagKey::Genre, TagMergePolicy::AppendMultivalue),
+        ]);
+
+        tagged_file.set_tag_value(&TagKey::Comment, Some(Cow::from("New comment")));
+        tagged_file.set_tag_value(&TagKey::Comment, None);
+        assert_eq!(tagged_file.comment().as_deref(), Some("My comment"));
+
+        tagged_file.set_tag_value(&TagKey::Mood, Some(Cow::from("Relaxed")));
+        assert_eq!(tagged_file.mood().as_deref(), Some("Relaxed"));
+
+        tagged_file.set_tag_values(&TagKey::Genre, &[Cow::from("Bebop"), Cow::from("Jazz")]);
+        tagged_file.set_tag_value(&TagKey::Genre, None);
+        assert_eq!(
+            tagged_file.tags()[0].get_multiple(&TagKey::Genre),
+            vec!["Jazz", "Bebop"]
+        );
+
+        tagged_file.set_tag_value(&TagKey::Album, Some(Cow::from("New Album")));
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::Album).as_deref(),
+            Some("New Album")
+        );
+    }
+
+    #[test]
+    fn test_to_memory_copy() {
+        let mut tag = MemoryTag::new();
+        tag.set_multiple(&TagKey::Genre, &[Cow::from("Jazz"), Cow::from("Bebop")]);
+        tag.set_multiple(
+            &TagKey::Performer("piano".to_string()),
+            &[Cow::from("Ahmad Jamal")],
+        );
+        let tagged_file = TaggedFile::new(vec![Box::new(tag)]);
+        let copy = tagged_file.to_memory_copy();
+
+        assert_eq!(
+            copy.tags()[0].get_multiple(&TagKey::Genre),
+            vec!["Jazz", "Bebop"]
+        );
+        let performers = copy.performers().unwrap();
+        assert_eq!(performers.len(), 1);
+        assert_eq!(performers[0].involvement, "piano");
+        assert_eq!(performers[0].involvee, "Ahmad Jamal");
+    }
+
+    #[test]
+    fn test_tag_values_path_fallback() {
+        let patterns = filename_pattern::compile_patterns(&["%track% - %title%".to_string()]);
+        let mut tagged_file = TaggedFile::new(vec![Box::new(
+            MemoryTag::new().with_value(TagKey::TrackTitle, Some("Moonlight in Vermont")),
+        )]);
+        tagged_file.path = PathBuf::from("/music/03 - Unknown Title.flac");
+        let tagged_file = tagged_file.with_path_values(&patterns);
+
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::TrackTitle).as_deref(),
+            Some("Moonlight in Vermont")
+        );
+        assert_eq!(
+            tagged_file.first_tag_value(&TagKey::TrackNumber).as_deref(),
+            Some("03")
+        );
+        assert!(tagged_file.first_tag_value(&TagKey::Album).is_none());
+    }
 }
@@ -8,19 +8,28 @@
 
 //! Utilities for matching and lookup up albums and tracks.
 
-use crate::analyzer::EbuR128AlbumResult;
+use crate::analyzer::{ChromaprintFingerprintResult, EbuR128AlbumResult};
+use crate::archive::ExtractedArchive;
+use crate::config::{ReplayGainTagsConfig, TransferMode};
+use crate::cue::{self, CueSheet};
 use crate::media::MediaLike;
 use crate::pathformat::PathFormatterValues;
 use crate::release::ReleaseLike;
 use crate::release_candidate::ReleaseCandidate;
 use crate::tag::TagKey;
-use crate::track::TrackLike;
-use crate::util;
+use crate::track::{AnalyzedTrackMetadata, TrackLike};
+use crate::util::{self, ReleaseSnapshot};
 use crate::Config;
 use crate::TaggedFile;
+use chrono::TimeDelta;
 use itertools::Itertools;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
 
 /// Represents the the count of a specific item and the first index at which that item was found.
 ///
@@ -123,25 +132,92 @@ pub struct TaggedFileCollection {
     /// List of media in this collection. These are determined by the "disc number" value of each
     /// track.
     media: Vec<TaggedFileMedia>,
-    /// EBU R128 Album Result
+    /// EBU R128 Album Result (calculated from all tracks that have an analysis result).
     ebur128_album_result: Option<EbuR128AlbumResult>,
+    /// Tracks without EBU R128 analysis result, and the reason why it is missing.
+    ebur128_failures: Vec<(PathBuf, String)>,
+    /// Whether the album gain may be used even if some tracks are missing analysis results.
+    allow_partial_album_gain: bool,
+    /// Suffix that is appended to the album directory to avoid collisions with other releases.
+    album_directory_suffix: Option<String>,
+    /// Files in the album directory that could not be read, and the reason why.
+    unreadable_files: Vec<(PathBuf, String)>,
+    /// Archive that the album directory was extracted from. The extracted directory is removed
+    /// when the last collection of the archive is dropped.
+    source_archive: Option<Arc<ExtractedArchive>>,
+    /// Name of the release if the album directory contains files of several releases and this
+    /// collection only contains some of them.
+    directory_group: Option<String>,
+    /// SHA-256 checksums of the transferred files (by destination path), if transfers are
+    /// verified.
+    checksums: HashMap<PathBuf, String>,
+    /// Destination paths of files that are hard links to their source files. Their tags are not
+    /// written, so that the source files are never modified.
+    linked_paths: HashSet<PathBuf>,
+    /// Cue sheet and audio file of a single-file album, if the tracks of this collection are
+    /// virtual tracks from the cue sheet that have not been turned into real files yet.
+    cue_source: Option<(CueSheet, PathBuf)>,
+    /// Temporary directory that contains the split tracks of a single-file album. It is removed
+    /// when the collection is dropped.
+    cue_split_dir: Option<TempDir>,
+    /// Destination of the audio file of a single-file album that is imported without splitting.
+    single_file_destination: Option<PathBuf>,
+}
+
+/// Album directories that were already used during an import run, so that the files of different
+/// releases do not end up in the same directory.
+#[derive(Debug, Default)]
+pub struct AlbumDestinations(HashMap<PathBuf, String>);
+
+impl AlbumDestinations {
+    /// Returns `true` if the directory is already used by a different release.
+    pub fn is_taken(&self, directory: &Path, release_key: &str) -> bool {
+        self.0
+            .get(directory)
+            .is_some_and(|existing_key| existing_key != release_key)
+    }
+
+    /// Remember that the directory is used by the release.
+    pub fn insert(&mut self, directory: PathBuf, release_key: String) {
+        let _ = self.0.insert(directory, release_key);
+    }
+}
+
+/// Returns the deepest directory that contains all of the given paths.
+fn common_directory(paths: &[PathBuf]) -> Option<PathBuf> {
+    let mut parents = paths.iter().filter_map(|path| path.parent());
+    let first = parents.next()?.to_path_buf();
+    parents.try_fold(first, |common, parent| {
+        common
+            .ancestors()
+            .find(|ancestor| parent.starts_with(ancestor))
+            .map(Path::to_path_buf)
+    })
 }
 
 impl TaggedFileCollection {
     /// Creates a new collection from a `Vec` of `TaggedFile` instances.
     #[must_use]
     pub fn new(mut tracks: Vec<TaggedFile>) -> Self {
-        let ebur128_album_result = tracks
-            .iter()
-            .map(|track| {
-                track
-                    .analysis_results
-                    .as_ref()
-                    .and_then(|analysis_result| analysis_result.ebur128.as_ref())
-            })
-            .map(|opt| opt.and_then(|res| res.as_ref().ok()))
-            .collect::<Option<Vec<_>>>()
-            .and_then(|ebur128_results| EbuR128AlbumResult::from_iter(ebur128_results.into_iter()));
+        let mut ebur128_results = vec![];
+        let mut ebur128_failures = vec![];
+        for track in &tracks {
+            match track
+                .analysis_results
+                .as_ref()
+                .map(|analysis_result| analysis_result.ebur128.as_ref())
+            {
+                Some(Some(Ok(result))) => ebur128_results.push(result),
+                Some(Some(Err(err))) => {
+                    ebur128_failures.push((track.path.clone(), err.to_string()))
+                }
+                Some(None) => {
+                    ebur128_failures.push((track.path.clone(), "not analyzed".to_string()))
+                }
+                None => ebur128_failures.push((track.path.clone(), "analysis failed".to_string())),
+            }
+        }
+        let ebur128_album_result = EbuR128AlbumResult::from_iter(ebur128_results.into_iter());
 
         tracks.sort_by_cached_key(|track| {
             (
@@ -167,7 +243,300 @@ impl TaggedFileCollection {
         Self {
             media,
             ebur128_album_result,
+            ebur128_failures,
+            allow_partial_album_gain: false,
+            album_directory_suffix: None,
+            unreadable_files: vec![],
+            source_archive: None,
+            directory_group: None,
+            checksums: HashMap::new(),
+            linked_paths: HashSet::new(),
+            cue_source: None,
+            cue_split_dir: None,
+            single_file_destination: None,
+        }
+    }
+
+    /// Remember the cue sheet and the audio file that the (virtual) tracks were read from.
+    #[must_use]
+    pub fn with_cue_source(mut self, cue_source: Option<(CueSheet, PathBuf)>) -> Self {
+        self.cue_source = cue_source;
+        self
+    }
+
+    /// Returns `true` if the tracks were split from a single-file album into temporary files.
+    pub fn has_split_tracks(&self) -> bool {
+        self.cue_split_dir.is_some()
+    }
+
+    /// Turn the virtual tracks of a single-file album into real files, so that the album can be
+    /// imported.
+    ///
+    /// If cue sheet splitting is enabled, the audio file is split into one file per track in a
+    /// temporary directory and the tag values of the virtual tracks are transferred to the split
+    /// files. Otherwise, the tracks are replaced by the audio file itself, which only receives
+    /// the release-level tags and is transferred into the album directory of the first track.
+    /// Does nothing if the collection does not contain virtual tracks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the audio file cannot be split or read.
+    pub fn prepare_cue_tracks(&mut self, config: &Config) -> crate::Result<()> {
+        let Some((sheet, audio_path)) = self.cue_source.take() else {
+            return Ok(());
+        };
+
+        if !config.cue.split_files {
+            let destination = self
+                .destination_paths(config)?
+                .first()
+                .and_then(|path| path.parent())
+                .map(|dir| dir.join(audio_path.file_name().unwrap_or_default()));
+            let mut file = TaggedFile::read_from_path(&audio_path, &config.tags)?;
+            if let Some(first_track) = self.tracks().next() {
+                for key in &cue::ALBUM_TAG_KEYS {
+                    if let Some(values) = first_track
+                        .tags()
+                        .iter()
+                        .map(|tag| tag.get_multiple(key))
+                        .find(|values| !values.is_empty())
+                    {
+                        file.set_tag_values(key, &values);
+                    }
+                }
+            }
+            self.media = vec![TaggedFileMedia { tracks: vec![file] }];
+            self.single_file_destination = destination;
+            return Ok(());
+        }
+
+        let split_dir = tempfile::Builder::new().prefix("helicon-cue-").tempdir()?;
+        log::info!(
+            "Splitting {} into {} tracks",
+            audio_path.display(),
+            sheet.tracks.len()
+        );
+        let _ = sheet.split_audio_file(&audio_path, split_dir.path())?;
+        for track in self.tracks_mut() {
+            let file_name = track.path.file_name().unwrap_or_default().to_os_string();
+            track.move_tags_to_file(split_dir.path().join(file_name), &config.tags)?;
+        }
+        self.cue_split_dir = Some(split_dir);
+        Ok(())
+    }
+
+    /// Remember the files of the album that could not be read, so that the album can be reported
+    /// as partially imported.
+    #[must_use]
+    pub fn with_unreadable_files(mut self, unreadable_files: Vec<(PathBuf, String)>) -> Self {
+        self.unreadable_files = unreadable_files;
+        self
+    }
+
+    /// Returns the files of the album that could not be read, together with the reason why.
+    pub fn unreadable_files(&self) -> &[(PathBuf, String)] {
+        &self.unreadable_files
+    }
+
+    /// Remember the archive that the tracks were extracted from.
+    #[must_use]
+    pub fn with_source_archive(mut self, source_archive: Option<Arc<ExtractedArchive>>) -> Self {
+        self.source_archive = source_archive;
+        self
+    }
+
+    /// Returns the path of the archive that the tracks were extracted from (if any).
+    pub fn source_archive(&self) -> Option<&Path> {
+        self.source_archive.as_deref().map(ExtractedArchive::path)
+    }
+
+    /// Remember that the tracks are only a part of the files in the album directory, because the
+    /// directory contains several releases.
+    #[must_use]
+    pub fn with_directory_group(mut self, directory_group: Option<String>) -> Self {
+        self.directory_group = directory_group;
+        self
+    }
+
+    /// Returns the name of the release if the album directory contains files of several releases.
+    pub fn directory_group(&self) -> Option<&str> {
+        self.directory_group.as_deref()
+    }
+
+    /// Allow calculating the album gain from the subset of tracks that were analyzed successfully.
+    ///
+    /// By default, no album gain is available if the analysis of any track failed.
+    #[must_use]
+    pub fn with_partial_album_gain(mut self, allow_partial_album_gain: bool) -> Self {
+        self.allow_partial_album_gain = allow_partial_album_gain;
+        if allow_partial_album_gain
+            && self.ebur128_album_result.is_some()
+            && !self.ebur128_failures.is_empty()
+        {
+            log::warn!(
+                "Album gain is calculated without {} tracks that have no loudness analysis result",
+                self.ebur128_failures.len()
+            );
+        }
+        self
+    }
+
+    /// Returns the tracks that have no EBU R128 analysis result, together with the reason why the
+    /// result is missing.
+    pub fn album_gain_failures(&self) -> &[(PathBuf, String)] {
+        &self.ebur128_failures
+    }
+
+    /// Returns `true` if the album gain was calculated without some of the tracks.
+    pub fn is_album_gain_partial(&self) -> bool {
+        self.album_gain_result().is_some() && !self.ebur128_failures.is_empty()
+    }
+
+    /// Returns the EBU R128 album result if it may be used.
+    fn album_gain_result(&self) -> Option<&EbuR128AlbumResult> {
+        self.ebur128_album_result
+            .as_ref()
+            .filter(|_| self.ebur128_failures.is_empty() || self.allow_partial_album_gain)
+    }
+
+    /// Iterate over all tracks in this collection.
+    pub fn tracks(&self) -> impl Iterator<Item = &TaggedFile> {
+        self.media.iter().flat_map(|media| media.tracks.iter())
+    }
+
+    /// Returns the deepest directory that contains all tracks of this collection.
+    pub fn album_directory(&self) -> Option<PathBuf> {
+        common_directory(
+            &self
+                .tracks()
+                .map(|track| track.path.clone())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Iterate mutably over all tracks in this collection.
+    pub fn tracks_mut(&mut self) -> impl Iterator<Item = &mut TaggedFile> {
+        self.media
+            .iter_mut()
+            .flat_map(|media| media.tracks.iter_mut())
+    }
+
+    /// Returns a summary of the audio formats of the tracks (e.g., `12 × FLAC 44.1 kHz/16 bit`).
+    pub fn format_summary(&self) -> String {
+        let mut formats: Vec<(String, usize)> = Vec::new();
+        for description in self.tracks().map(TaggedFile::format_description) {
+            match formats
+                .iter_mut()
+                .find(|(format, _)| *format == description)
+            {
+                Some((_, count)) => *count += 1,
+                None => formats.push((description, 1)),
+            }
+        }
+        formats
+            .into_iter()
+            .map(|(format, count)| format!("{count} × {format}"))
+            .join(", ")
+    }
+
+    /// Returns groups of tracks that are most likely the same file (e.g., `Track (1).flac` copies).
+    ///
+    /// Tracks are considered duplicates if they have the same Chromaprint fingerprint. Tracks
+    /// without fingerprint (or with a fingerprint of silence, which is the same for all silent
+    /// tracks) are compared by the SHA-256 checksum of the file, which is only computed for files
+    /// that have the same size.
+    pub fn find_duplicate_tracks(&self) -> Vec<Vec<PathBuf>> {
+        let mut groups: Vec<(String, Vec<PathBuf>)> = vec![];
+        let mut unfingerprinted: Vec<(u64, &Path)> = vec![];
+        let mut add_to_group = |key: String, path: &Path| match groups
+            .iter_mut()
+            .find(|(group_key, _)| *group_key == key)
+        {
+            Some((_, paths)) => paths.push(path.to_path_buf()),
+            None => groups.push((key, vec![path.to_path_buf()])),
+        };
+
+        for track in self.tracks() {
+            let fingerprint = track
+                .analyzed_metadata()
+                .acoustid_fingerprint()
+                .map(Cow::into_owned)
+                .or_else(|| track.acoustid_fingerprint().map(Cow::into_owned))
+                .filter(|fingerprint| {
+                    ChromaprintFingerprintResult::from_fingerprint_string(fingerprint)
+                        .is_some_and(|result| !result.is_silent())
+                });
+            if let Some(fingerprint) = fingerprint {
+                add_to_group(format!("fingerprint:{fingerprint}"), &track.path);
+            } else if let Ok(metadata) = fs::metadata(&track.path) {
+                unfingerprinted.push((metadata.len(), &track.path));
+            }
+        }
+
+        for (size, path) in &unfingerprinted {
+            if unfingerprinted
+                .iter()
+                .filter(|(other_size, _)| other_size == size)
+                .count()
+                < 2
+            {
+                continue;
+            }
+            match util::file_checksum(path) {
+                Ok(checksum) => add_to_group(format!("checksum:{checksum}"), path),
+                Err(err) => log::warn!("Failed to compute checksum of {}: {err}", path.display()),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(_, paths)| paths)
+            .filter(|paths| paths.len() > 1)
+            .collect()
+    }
+
+    /// Remove the tracks with the given paths from the collection (e.g., duplicates).
+    ///
+    /// The album gain is calculated again from the remaining tracks.
+    #[must_use]
+    pub fn without_tracks(self, paths: &HashSet<PathBuf>) -> Self {
+        let Self {
+            media,
+            allow_partial_album_gain,
+            album_directory_suffix,
+            unreadable_files,
+            source_archive,
+            directory_group,
+            checksums,
+            linked_paths,
+            cue_source,
+            cue_split_dir,
+            single_file_destination,
+            ..
+        } = self;
+        let tracks = media
+            .into_iter()
+            .flat_map(|media| media.tracks)
+            .filter(|track| !paths.contains(&track.path))
+            .collect();
+        Self {
+            album_directory_suffix,
+            unreadable_files,
+            source_archive,
+            directory_group,
+            checksums,
+            linked_paths,
+            cue_source,
+            cue_split_dir,
+            single_file_destination,
+            ..Self::new(tracks)
         }
+        .with_partial_album_gain(allow_partial_album_gain)
+    }
+
+    /// Returns the total length of all tracks (or `None` if the length of any track is unknown).
+    pub fn total_length(&self) -> Option<TimeDelta> {
+        self.tracks().map(TrackLike::track_length).sum()
     }
 
     /// Finds the consensual value for a certain tag in an iterator of tagged files.
@@ -178,9 +547,60 @@ impl TaggedFileCollection {
             .and_then(MostCommonItem::into_concensus)
     }
 
+    /// Returns the distinct values of a tag across all tracks, in the order of their first
+    /// occurrence.
+    fn distinct_tag_values(&self, key: &TagKey) -> Vec<String> {
+        self.tracks()
+            .filter_map(|track| track.first_tag_value(key))
+            .map(Cow::into_owned)
+            .unique()
+            .collect()
+    }
+
+    /// Returns the release tags used for looking up candidates whose values differ between the
+    /// tracks, together with the distinct values.
+    ///
+    /// Conflicting track artists are reported as album artist conflict if no track has an album
+    /// artist, because the album artist is used for the lookup in that case.
+    pub fn lookup_tag_conflicts(&self) -> Vec<(TagKey, Vec<String>)> {
+        let artist_key = if self
+            .tracks()
+            .any(|track| track.first_tag_value(&TagKey::AlbumArtist).is_some())
+        {
+            TagKey::AlbumArtist
+        } else {
+            TagKey::Artist
+        };
+        [
+            (TagKey::Album, TagKey::Album),
+            (TagKey::AlbumArtist, artist_key),
+        ]
+        .into_iter()
+        .filter_map(|(key, source_key)| {
+            let values = self.distinct_tag_values(&source_key);
+            (values.len() > 1).then_some((key, values))
+        })
+        .collect()
+    }
+
+    /// Set the tag to the same value on all tracks (e.g., to resolve a conflict).
+    pub fn set_release_tag_value(&mut self, key: &TagKey, value: &str) {
+        for track in self
+            .media
+            .iter_mut()
+            .flat_map(|media| media.tracks.iter_mut())
+        {
+            track.set_tag_value(key, Some(Cow::from(value)));
+        }
+    }
+
     /// Assign tracks from a release candidate.
     #[must_use]
-    pub fn assign_tags<T: ReleaseLike>(mut self, release_candidate: &ReleaseCandidate<T>) -> Self {
+    pub fn assign_tags<T: ReleaseLike>(
+        self,
+        release_candidate: &ReleaseCandidate<T>,
+        config: &Config,
+    ) -> Self {
         let matched_track_map = release_candidate
             .similarity()
             .track_assignment()
@@ -194,8 +614,21 @@ impl TaggedFileCollection {
         let album_range_analyzed = self
             .replay_gain_album_range_analyzed()
             .map(|value| value.to_string());
-        let tracks = self
-            .media
+        let Self {
+            media,
+            allow_partial_album_gain,
+            album_directory_suffix,
+            unreadable_files,
+            source_archive,
+            directory_group,
+            checksums,
+            linked_paths,
+            cue_source,
+            cue_split_dir,
+            single_file_destination,
+            ..
+        } = self;
+        let tracks = media
             .into_iter()
             .flat_map(|media| media.tracks.into_iter())
             .enumerate()
@@ -217,13 +650,34 @@ impl TaggedFileCollection {
             })
             .map(
                 move |(mut track, (media_index, other_media, other_track))| {
+                    track.set_skipped_keys(&config.tags.skip);
+                    track.set_merge_policies(&config.tags.merge_policies);
+                    track.set_vorbis_total_fields(config.tags.vorbis_total_fields);
+                    if config.tags.strip_ape_tags {
+                        track.strip_ape_tags();
+                    }
                     track.assign_tags_from_track(other_track);
+                    if config.tags.classical.enabled {
+                        track.assign_classical_tags_from_track(other_track, &config.tags.classical);
+                    }
                     track.set_tag_value(
                         &TagKey::DiscNumber,
                         Some(Cow::from(format!("{media_index}"))),
                     );
                     track.assign_tags_from_media(other_media);
                     track.assign_tags_from_release(release_candidate.release());
+                    if release_candidate.release().is_compilation() {
+                        track.set_tag_value(
+                            &TagKey::AlbumArtist,
+                            Some(Cow::from(config.tags.various_artists.as_str())),
+                        );
+                    }
+                    if let Some(event) = release_candidate
+                        .release()
+                        .preferred_release_event(&config.lookup.preferred_release.countries)
+                    {
+                        track.assign_tags_from_release_event(&event);
+                    }
                     track.set_tag_value(
                         &TagKey::ReplayGainAlbumGain,
                         album_gain_analyzed.as_ref().map(Cow::from),
@@ -236,20 +690,136 @@ impl TaggedFileCollection {
                         &TagKey::ReplayGainAlbumRange,
                         album_range_analyzed.as_ref().map(Cow::from),
                     );
+                    track.assign_replay_gain_compat_tags(&config.tags.replay_gain);
+                    track.format_number_tags(&config.tags.number_format);
+                    track.normalize_date_tags();
                     track
                 },
             )
             .collect();
-        self = TaggedFileCollection::new(tracks);
-        self
+        let mut collection = Self {
+            album_directory_suffix,
+            unreadable_files,
+            source_archive,
+            directory_group,
+            checksums,
+            linked_paths,
+            cue_source,
+            cue_split_dir,
+            single_file_destination,
+            ..Self::new(tracks)
+        }
+        .with_partial_album_gain(allow_partial_album_gain);
+        if config.tags.classical.enabled {
+            collection.assign_movement_counts();
+        }
+        collection
     }
 
-    /// Move files for all tracks in this collection.
+    /// Set the movement count on all tracks that are movements of a work.
     ///
-    /// # Errors
+    /// The count is the number of movements of the same work in this collection, or the highest
+    /// movement number if that is larger (e.g., if only some movements are included).
+    fn assign_movement_counts(&mut self) {
+        let mut movement_counts: HashMap<String, usize> = HashMap::new();
+        for track in self
+            .tracks()
+            .filter(|track| track.first_tag_value(&TagKey::ShowMovement).as_deref() == Some("1"))
+        {
+            let Some(work_title) = track.first_tag_value(&TagKey::WorkTitle) else {
+                continue;
+            };
+            let movement_number = track
+                .first_tag_value(&TagKey::MovementNumber)
+                .and_then(|number| number.parse::<usize>().ok())
+                .unwrap_or_default();
+            let count = movement_counts.entry(work_title.into_owned()).or_default();
+            *count = (*count + 1).max(movement_number);
+        }
+
+        for track in self
+            .media
+            .iter_mut()
+            .flat_map(|media| media.tracks.iter_mut())
+        {
+            if track.first_tag_value(&TagKey::ShowMovement).as_deref() != Some("1") {
+                continue;
+            }
+            let movement_count = track
+                .first_tag_value(&TagKey::WorkTitle)
+                .and_then(|work_title| movement_counts.get(work_title.as_ref()))
+                .map(|count| count.to_string());
+            track.set_tag_value(&TagKey::MovementCount, movement_count.map(Cow::from));
+        }
+    }
+
+    /// Assign the tags that are derived from the analysis results (i.e., the AcoustID fingerprints
+    /// and the ReplayGain track and album values) without touching any other metadata.
     ///
-    /// Returns an error if moving any of the files fails.
-    pub fn move_files(&mut self, config: &Config) -> crate::Result<()> {
+    /// Existing values are kept if the corresponding analysis result is not available.
+    pub fn assign_analyzed_tags(&mut self, config: &ReplayGainTagsConfig) {
+        for track in self
+            .media
+            .iter_mut()
+            .flat_map(|media| media.tracks.iter_mut())
+        {
+            track.assign_analyzed_tags(config);
+        }
+        if self.album_gain_result().is_some() {
+            self.assign_replay_gain_tags(config);
+        }
+    }
+
+    /// Assign only the ReplayGain track and album tags from the analysis results, without
+    /// touching any other metadata.
+    pub fn assign_replay_gain_tags(&mut self, config: &ReplayGainTagsConfig) {
+        let album_gain_analyzed = self
+            .replay_gain_album_gain_analyzed()
+            .map(|value| value.to_string());
+        let album_peak_analyzed = self
+            .replay_gain_album_peak_analyzed()
+            .map(|value| value.to_string());
+        let album_range_analyzed = self
+            .replay_gain_album_range_analyzed()
+            .map(|value| value.to_string());
+        for track in self
+            .media
+            .iter_mut()
+            .flat_map(|media| media.tracks.iter_mut())
+        {
+            track.assign_replay_gain_track_tags();
+            for (key, value) in [
+                (TagKey::ReplayGainAlbumGain, &album_gain_analyzed),
+                (TagKey::ReplayGainAlbumPeak, &album_peak_analyzed),
+                (TagKey::ReplayGainAlbumRange, &album_range_analyzed),
+            ] {
+                if let Some(value) = value {
+                    track.set_tag_value(&key, Some(Cow::from(value)));
+                }
+            }
+            track.assign_replay_gain_compat_tags(config);
+        }
+    }
+
+    /// Returns a copy of this collection with the tags from the release candidate assigned, without
+    /// modifying this collection or the files (e.g., to preview the tag changes).
+    #[must_use]
+    pub fn preview_tags<T: ReleaseLike>(
+        &self,
+        release_candidate: &ReleaseCandidate<T>,
+        config: &Config,
+    ) -> Self {
+        let mut preview = Self::new(self.tracks().map(TaggedFile::to_memory_copy).collect());
+        preview.allow_partial_album_gain = self.allow_partial_album_gain;
+        preview.assign_tags(release_candidate, config)
+    }
+
+    /// Returns the destination paths of all tracks in this collection.
+    fn destination_paths(&self, config: &Config) -> crate::Result<Vec<PathBuf>> {
+        if let Some(destination) = &self.single_file_destination {
+            return Ok(vec![destination.clone()]);
+        }
+
         let paths = self
             .media()
             .flat_map(|media| media.media_tracks().map(move |track| (media, track)))
@@ -257,39 +827,307 @@ impl TaggedFileCollection {
             .map(|(i, (media, track))| {
                 let values = PathFormatterValues::default()
                     .with_release(self)
+                    .with_various_artists(&config.tags.various_artists)
                     .with_media(media)
-                    .with_track(i + 1, track);
+                    .with_tags(track)
+                    .with_track(i + 1, track)
+                    .with_number_format(&config.tags.number_format);
                 config
                     .paths
                     .format_path(&values, track.track_file_extension())
             })
             .collect::<crate::Result<Vec<_>>>()?;
 
+        let Some(suffix) = &self.album_directory_suffix else {
+            return Ok(paths);
+        };
+        let Some(directory) = self.album_directory_from_paths(config, &paths)? else {
+            return Ok(paths);
+        };
+        let mut directory_name = directory.file_name().unwrap_or_default().to_os_string();
+        directory_name.push(suffix);
+        let new_directory = directory.with_file_name(directory_name);
+        Ok(paths
+            .into_iter()
+            .map(|path| match path.strip_prefix(&directory) {
+                Ok(relative_path) => new_directory.join(relative_path),
+                Err(_) => path,
+            })
+            .collect())
+    }
+
+    /// Returns the album directory (i.e., the directory that contains all tracks), or `None` if
+    /// the tracks are not inside a common directory below the library path.
+    fn album_directory_from_paths(
+        &self,
+        config: &Config,
+        paths: &[PathBuf],
+    ) -> crate::Result<Option<PathBuf>> {
+        let library_path = config.paths.library_path()?;
+        Ok(common_directory(paths)
+            .filter(|directory| directory.starts_with(&library_path) && directory != &library_path))
+    }
+
+    /// Returns the destination album directory of this collection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the destination paths cannot be formatted.
+    pub fn album_directory(&self, config: &Config) -> crate::Result<Option<PathBuf>> {
+        let paths = self.destination_paths(config)?;
+        self.album_directory_from_paths(config, &paths)
+    }
+
+    /// Returns a key that identifies the release of this collection, used to detect if another
+    /// release uses the same album directory.
+    pub fn release_key(&self) -> String {
+        self.musicbrainz_release_id().map_or_else(
+            || {
+                format!(
+                    "{artist}\0{title}",
+                    artist = self.release_artist().unwrap_or_default(),
+                    title = self.release_title().unwrap_or_default()
+                )
+            },
+            Cow::into_owned,
+        )
+    }
+
+    /// Returns the suffixes that can be appended to the album directory to disambiguate it from
+    /// other releases (i.e., the release year and the MusicBrainz release ID).
+    pub fn album_directory_suffixes(&self) -> Vec<String> {
+        [
+            self.release_year().map(|year| format!(" ({year})")),
+            self.musicbrainz_release_id().map(|id| format!(" [{id}]")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Set the suffix that is appended to the album directory.
+    pub fn set_album_directory_suffix(&mut self, suffix: Option<String>) {
+        self.album_directory_suffix = suffix;
+    }
+
+    /// Append the first suffix to the album directory that is not used by a different release.
+    ///
+    /// Returns `false` if no such suffix exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the destination paths cannot be formatted.
+    pub fn disambiguate_album_directory(
+        &mut self,
+        config: &Config,
+        destinations: &AlbumDestinations,
+    ) -> crate::Result<bool> {
+        let release_key = self.release_key();
+        for suffix in self.album_directory_suffixes() {
+            self.album_directory_suffix = Some(suffix);
+            if self
+                .album_directory(config)?
+                .is_none_or(|directory| !destinations.is_taken(&directory, &release_key))
+            {
+                return Ok(true);
+            }
+        }
+
+        self.album_directory_suffix = None;
+        Ok(false)
+    }
+
+    /// Move files for all tracks in this collection into the library.
+    ///
+    /// Depending on the configured transfer mode, the files are moved, copied or linked. Tracks
+    /// that were not transferred because of the collision policy keep their original path.
+    ///
+    /// Hard links share their content and permissions with the source file, so files are copied
+    /// instead if their tags have changed or if a file mode is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if transferring any of the files fails.
+    pub fn move_files(&mut self, config: &Config) -> crate::Result<()> {
+        let paths = self.destination_paths(config)?;
+        let library_path = config.paths.library_path()?;
+        // Split tracks are temporary files, so they are always moved.
+        let transfer_mode = if self.has_split_tracks() {
+            TransferMode::Move
+        } else {
+            config.paths.transfer_mode
+        };
+
         for (track, dest_path) in self
             .media
             .iter_mut()
             .flat_map(|media| media.tracks.iter_mut())
             .zip(paths)
         {
-            util::move_file(&track.path, &dest_path)?;
-            track.path = dest_path;
+            let transfer_mode = if transfer_mode == TransferMode::Hardlink
+                && (config.paths.file_mode.is_some() || track.has_tag_changes(&config.tags)?)
+            {
+                log::info!(
+                    "Copying {} instead of linking it, because the file will be modified",
+                    track.path.display()
+                );
+                TransferMode::Copy
+            } else {
+                transfer_mode
+            };
+            let source_checksum = config
+                .paths
+                .verify_transfers
+                .then(|| util::file_checksum(&track.path))
+                .transpose()?;
+            let transferred_path = match &source_checksum {
+                Some(checksum) => util::transfer_file_verified(
+                    &track.path,
+                    &dest_path,
+                    transfer_mode,
+                    config.paths.collision_policy,
+                    checksum,
+                )?,
+                None => util::transfer_file(
+                    &track.path,
+                    &dest_path,
+                    transfer_mode,
+                    config.paths.collision_policy,
+                )?,
+            };
+            if let Some(dest_path) = transferred_path {
+                if let Some(source_checksum) = source_checksum {
+                    // The checksum is updated after the tags have been written.
+                    let _ = self.checksums.insert(dest_path.clone(), source_checksum);
+                }
+                if transfer_mode == TransferMode::Hardlink {
+                    let _ = self.linked_paths.insert(dest_path.clone());
+                }
+                if let Some(mode) = config.paths.file_mode {
+                    util::set_mode(&dest_path, mode)?;
+                }
+                if let Some(mode) = config.paths.dir_mode {
+                    for dir in dest_path
+                        .ancestors()
+                        .skip(1)
+                        .take_while(|dir| dir.starts_with(&library_path) && dir != &library_path)
+                    {
+                        util::set_mode(dir, mode)?;
+                    }
+                }
+                track.path = dest_path;
+            }
         }
 
         Ok(())
     }
 
+    /// Returns the SHA-256 checksum of the transferred file at the given path (after the tags have
+    /// been written).
+    ///
+    /// Checksums are only available if transfers are verified.
+    pub fn checksum(&self, path: &Path) -> Option<&str> {
+        self.checksums.get(path).map(String::as_str)
+    }
+
+    /// Transfer attachments (e.g., cover scans, logs or cue sheets) from the source directory into
+    /// the directory of the first track.
+    ///
+    /// Files are considered attachments if their name matches one of the configured attachment
+    /// patterns. Returns the original and new paths of the transferred files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source directory cannot be read or if transferring any of the files
+    /// fails.
+    pub fn move_attachments(
+        &self,
+        source_dir: &Path,
+        config: &Config,
+    ) -> crate::Result<Vec<(PathBuf, PathBuf)>> {
+        let patterns = &config.paths.attachment_patterns;
+        let Some(dest_dir) = self.tracks().next().and_then(|track| track.path.parent()) else {
+            return Ok(vec![]);
+        };
+        if patterns.is_empty() || source_dir == dest_dir {
+            return Ok(vec![]);
+        }
+
+        let track_paths = self
+            .tracks()
+            .map(|track| track.path.as_path())
+            .collect::<Vec<_>>();
+        let mut transferred_paths = vec![];
+        for entry in fs::read_dir(source_dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name() else {
+                continue;
+            };
+            let is_attachment = path.is_file()
+                && !track_paths.contains(&path.as_path())
+                && file_name.to_str().is_some_and(|name| {
+                    patterns
+                        .iter()
+                        .any(|pattern| util::matches_file_pattern(pattern, name))
+                });
+            if !is_attachment {
+                continue;
+            }
+
+            if let Some(dest_path) = util::transfer_file(
+                &path,
+                dest_dir.join(file_name),
+                config.paths.transfer_mode,
+                config.paths.collision_policy,
+            )? {
+                transferred_paths.push((path, dest_path));
+            }
+        }
+
+        Ok(transferred_paths)
+    }
+
+    /// Write the release metadata as `release.json` into the directory of the first track.
+    ///
+    /// Returns the path of the written file, or `None` if the collection does not contain any
+    /// tracks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write_release_json(&self) -> crate::Result<Option<PathBuf>> {
+        let Some(dir) = self.tracks().next().and_then(|track| track.path.parent()) else {
+            return Ok(None);
+        };
+
+        let path = dir.join("release.json");
+        let mut writer = BufWriter::new(File::create(&path)?);
+        serde_json::to_writer_pretty(&mut writer, &ReleaseSnapshot::from(self))?;
+        writer.flush()?;
+        Ok(Some(path))
+    }
+
     /// Write tags for all tracks in this collection.
     ///
+    /// Files that are hard links to their source files are skipped, because their tags have not
+    /// changed.
+    ///
     /// # Errors
     ///
     /// Returns an error if any of the underlying tags fail to write.
-    pub fn write_tags(&mut self) -> crate::Result<()> {
+    pub fn write_tags(&mut self, config: &Config) -> crate::Result<()> {
         for track in &mut self
             .media
             .iter_mut()
             .flat_map(|media| media.tracks.iter_mut())
         {
-            track.write_tags()?;
+            if self.linked_paths.contains(&track.path) {
+                continue;
+            }
+            track.write_tags_preserving_mtime(config.paths.preserve_mtime)?;
+            if let Some(checksum) = self.checksums.get_mut(&track.path) {
+                *checksum = util::file_checksum(&track.path)?;
+            }
         }
 
         Ok(())
@@ -394,6 +1232,16 @@ impl ReleaseLike for TaggedFileCollection {
             .map(Cow::from)
     }
 
+    fn original_release_date(&self) -> Option<Cow<'_, str>> {
+        self.find_consensual_tag_value(&TagKey::OriginalReleaseDate)
+            .map(Cow::from)
+    }
+
+    fn original_release_year(&self) -> Option<Cow<'_, str>> {
+        self.find_consensual_tag_value(&TagKey::OriginalReleaseYear)
+            .map(Cow::from)
+    }
+
     fn release_status(&self) -> Option<Cow<'_, str>> {
         self.find_consensual_tag_value(&TagKey::ReleaseStatus)
             .map(Cow::from)
@@ -409,9 +1257,24 @@ impl ReleaseLike for TaggedFileCollection {
             .map(Cow::from)
     }
 
+    /// Total number of discs.
+    ///
+    /// If the files are not tagged with a consensual total, the total is derived from the disc
+    /// numbers and the number of media in the collection.
     fn total_discs(&self) -> Option<Cow<'_, str>> {
         self.find_consensual_tag_value(&TagKey::TotalDiscs)
             .map(Cow::from)
+            .or_else(|| {
+                let highest_disc_number = self
+                    .media
+                    .iter()
+                    .filter_map(MediaLike::disc_number)
+                    .max()
+                    .and_then(|number| usize::try_from(number).ok())
+                    .unwrap_or_default();
+                let total_discs = self.media.len().max(highest_disc_number);
+                (total_discs > 0).then(|| Cow::from(total_discs.to_string()))
+            })
     }
 
     fn media(&self) -> impl Iterator<Item = &(impl MediaLike + '_)> {
@@ -419,14 +1282,12 @@ impl ReleaseLike for TaggedFileCollection {
     }
 
     fn replay_gain_album_gain_analyzed(&self) -> Option<Cow<'_, str>> {
-        self.ebur128_album_result
-            .as_ref()
+        self.album_gain_result()
             .map(|result| Cow::from(result.replaygain_album_gain_string()))
     }
 
     fn replay_gain_album_peak_analyzed(&self) -> Option<Cow<'_, str>> {
-        self.ebur128_album_result
-            .as_ref()
+        self.album_gain_result()
             .map(|result| Cow::from(result.replaygain_album_peak_string()))
     }
 }
@@ -471,6 +1332,8 @@ impl MediaLike for TaggedFileMedia {
             .map(Cow::from)
     }
 
+    /// Number of tracks, derived from the files on this disc (regardless of the total tracks in
+    /// the tags).
     fn media_track_count(&self) -> Option<usize> {
         self.tracks.len().into()
     }
@@ -583,8 +1446,10 @@ mod tests {
         assert_eq!(None, most_common_item.into_concensus());
     }
 
-    fn make_collection_from_assignment(func: impl Fn() -> Box<dyn Tag>) -> TaggedFileCollection {
-        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+    fn make_collection_and_release_candidate(
+        release: MusicBrainzRelease,
+        func: impl Fn() -> Box<dyn Tag>,
+    ) -> (TaggedFileCollection, ReleaseCandidate<MusicBrainzRelease>) {
         let release_track_count = release.release_track_count().unwrap();
         let release_candidate =
             ReleaseCandidate::with_similarity(release, ReleaseSimilarity::new(release_track_count));
@@ -592,7 +1457,20 @@ mod tests {
         let tracks = (0..release_track_count)
             .map(|_| TaggedFile::new(vec![func()]))
             .collect();
-        TaggedFileCollection::new(tracks).assign_tags(&release_candidate)
+        (TaggedFileCollection::new(tracks), release_candidate)
+    }
+
+    fn make_collection_from_assignment_with_config(
+        config: &Config,
+        func: impl Fn() -> Box<dyn Tag>,
+    ) -> TaggedFileCollection {
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let (collection, release_candidate) = make_collection_and_release_candidate(release, func);
+        collection.assign_tags(&release_candidate, config)
+    }
+
+    fn make_collection_from_assignment(func: impl Fn() -> Box<dyn Tag>) -> TaggedFileCollection {
+        make_collection_from_assignment_with_config(&Config::default(), func)
     }
 
     #[test]
@@ -742,4 +1620,493 @@ mod tests {
         assert_eq!(collection.script().as_deref(), Some("Latn"));
         assert_eq!(collection.total_discs().as_deref(), Some("1"));
     }
+
+    #[test]
+    fn test_write_release_json() {
+        use crate::tag::memory::MemoryTag;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let tracks = (1..=2)
+            .map(|i| {
+                let tag = MemoryTag::new()
+                    .with_value(TagKey::Album, Some("Album"))
+                    .with_value(TagKey::TrackNumber, Some(i.to_string()));
+                TaggedFile::with_tags(
+                    tmp_dir.path().join(format!("{i}.flac")),
+                    vec![Box::new(tag)],
+                )
+            })
+            .collect();
+        let collection = TaggedFileCollection::new(tracks);
+
+        let path = collection.write_release_json().unwrap().unwrap();
+        assert_eq!(path, tmp_dir.path().join("release.json"));
+        let release: ReleaseSnapshot = serde_json::from_reader(File::open(path).unwrap()).unwrap();
+        assert_eq!(release.release_title().as_deref(), Some("Album"));
+        assert_eq!(release.release_track_count(), Some(2));
+
+        assert!(TaggedFileCollection::new(vec![])
+            .write_release_json()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_move_attachments() {
+        use crate::config::TransferMode;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source_dir = tmp_dir.path().join("source");
+        let dest_dir = tmp_dir.path().join("dest");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::create_dir_all(&dest_dir).unwrap();
+        fs::write(source_dir.join("cover.jpg"), "").unwrap();
+        fs::write(source_dir.join("rip.log"), "").unwrap();
+        fs::write(source_dir.join("notes.txt"), "").unwrap();
+
+        let mut config = Config::default();
+        config.paths.transfer_mode = TransferMode::Copy;
+        let collection = TaggedFileCollection::new(vec![TaggedFile::with_tags(
+            dest_dir.join("01.flac"),
+            vec![],
+        )]);
+        let mut paths = collection.move_attachments(&source_dir, &config).unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                (source_dir.join("cover.jpg"), dest_dir.join("cover.jpg")),
+                (source_dir.join("rip.log"), dest_dir.join("rip.log"))
+            ]
+        );
+        assert!(!dest_dir.join("notes.txt").exists());
+        assert!(source_dir.join("cover.jpg").exists());
+    }
+
+    #[test]
+    fn test_partial_album_gain() {
+        use crate::analyzer::{self, AnalyzerError, CompoundAnalyzerResult};
+
+        let config = Config::default();
+        let analyzed_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/picard-2.12.3/track.flac"
+        );
+        let failed_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/untagged/track.flac"
+        );
+        let tracks = || {
+            vec![
+                TaggedFile::with_tags(PathBuf::from(analyzed_path), vec![])
+                    .with_analysis_results(analyzer::analyze(&config, analyzed_path).ok()),
+                TaggedFile::with_tags(PathBuf::from(failed_path), vec![]).with_analysis_results(
+                    Some(CompoundAnalyzerResult {
+                        ebur128: Some(Err(AnalyzerError::Custom("test failure"))),
+                        ..Default::default()
+                    }),
+                ),
+            ]
+        };
+
+        let collection = TaggedFileCollection::new(tracks());
+        assert!(collection.replay_gain_album_gain_analyzed().is_none());
+        assert!(!collection.is_album_gain_partial());
+        assert_eq!(
+            collection.album_gain_failures(),
+            &[(
+                PathBuf::from(failed_path),
+                "analyzer error: test failure".to_string()
+            )]
+        );
+
+        let collection = TaggedFileCollection::new(tracks()).with_partial_album_gain(true);
+        assert!(collection.replay_gain_album_gain_analyzed().is_some());
+        assert!(collection.is_album_gain_partial());
+    }
+
+    #[test]
+    fn test_assign_replay_gain_tags() {
+        use crate::tag::memory::MemoryTag;
+
+        let config = Config::default();
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/media/picard-2.12.3/track.flac"
+        );
+        let tag = MemoryTag::new().with_value(TagKey::TrackTitle, Some("Title"));
+        let track = TaggedFile::with_tags(PathBuf::from(path), vec![Box::new(tag)])
+            .with_analysis_results(crate::analyzer::analyze(&config, path).ok());
+        let mut collection = TaggedFileCollection::new(vec![track]);
+        collection.assign_replay_gain_tags(&config.tags.replay_gain);
+
+        let track = collection.tracks().next().unwrap();
+        assert!(track
+            .first_tag_value(&TagKey::ReplayGainTrackGain)
+            .is_some());
+        assert_eq!(
+            track.first_tag_value(&TagKey::ReplayGainAlbumGain),
+            collection.replay_gain_album_gain_analyzed()
+        );
+        assert_eq!(
+            track.first_tag_value(&TagKey::TrackTitle).as_deref(),
+            Some("Title")
+        );
+    }
+
+    #[test]
+    fn test_assign_replay_gain_compat_tags_r128() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut config = Config::default();
+        config.tags.replay_gain.r128 = true;
+        let tagged_file = |path: &str| {
+            let tag = MemoryTag::new()
+                .with_value(TagKey::ReplayGainTrackGain, Some("-7.00 dB"))
+                .with_value(TagKey::ReplayGainAlbumGain, Some("-6.50 dB"));
+            let mut track = TaggedFile::with_tags(PathBuf::from(path), vec![Box::new(tag)]);
+            track.assign_replay_gain_compat_tags(&config.tags.replay_gain);
+            track
+        };
+
+        let track = tagged_file("track.opus");
+        assert!(track
+            .first_tag_value(&TagKey::R128TrackGain)
+            .is_some_and(|value| value.parse::<i16>().is_ok()));
+        assert!(track
+            .first_tag_value(&TagKey::R128AlbumGain)
+            .is_some_and(|value| value.parse::<i16>().is_ok()));
+
+        let track = tagged_file("track.flac");
+        assert!(track.first_tag_value(&TagKey::R128TrackGain).is_none());
+        assert!(track.first_tag_value(&TagKey::R128AlbumGain).is_none());
+    }
+
+    #[test]
+    fn test_assign_replay_gain_tags_without_analysis() {
+        use crate::tag::memory::MemoryTag;
+
+        let tag = MemoryTag::new()
+            .with_value(TagKey::ReplayGainTrackGain, Some("-7.00 dB"))
+            .with_value(TagKey::ReplayGainAlbumGain, Some("-6.50 dB"));
+        let track = TaggedFile::new(vec![Box::new(tag)]);
+        let mut collection = TaggedFileCollection::new(vec![track]);
+        collection.assign_replay_gain_tags(&Config::default().tags.replay_gain);
+
+        let track = collection.tracks().next().unwrap();
+        assert_eq!(
+            track
+                .first_tag_value(&TagKey::ReplayGainTrackGain)
+                .as_deref(),
+            Some("-7.00 dB")
+        );
+        assert_eq!(
+            track
+                .first_tag_value(&TagKey::ReplayGainAlbumGain)
+                .as_deref(),
+            Some("-6.50 dB")
+        );
+    }
+
+    #[test]
+    fn test_preview_tags() {
+        use crate::tag::memory::MemoryTag;
+
+        let release: MusicBrainzRelease = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        let (collection, release_candidate) =
+            make_collection_and_release_candidate(release, || {
+                Box::new(MemoryTag::new().with_value(TagKey::Album, Some("Old Title")))
+            });
+        let preview = collection.preview_tags(&release_candidate, &Config::default());
+
+        assert_eq!(collection.release_title().as_deref(), Some("Old Title"));
+        assert_eq!(
+            preview.release_title().as_deref(),
+            Some("Ahmad Jamal at the Pershing: But Not for Me")
+        );
+    }
+
+    #[test]
+    fn test_assign_tags_classical() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut config = Config::default();
+        config.tags.classical.enabled = true;
+        config.tags.classical.work_movement_titles = true;
+
+        let collection =
+            make_collection_from_assignment_with_config(&config, || Box::new(MemoryTag::new()));
+        let tracks = collection.tracks().collect::<Vec<_>>();
+
+        let track = tracks[1];
+        assert_eq!(
+            track.track_title().as_deref(),
+            Some("Oklahoma!: The Surrey With the Fringe on Top")
+        );
+        assert_eq!(track.work_title().as_deref(), Some("Oklahoma!"));
+        assert_eq!(
+            track.movement().as_deref(),
+            Some("The Surrey With the Fringe on Top")
+        );
+        assert_eq!(track.movement_number().as_deref(), Some("3"));
+        assert_eq!(track.movement_count().as_deref(), Some("3"));
+        assert_eq!(
+            track.first_tag_value(&TagKey::ShowMovement).as_deref(),
+            Some("1")
+        );
+
+        let track = tracks[2];
+        assert_eq!(track.track_title().as_deref(), Some("Moonlight in Vermont"));
+        assert!(track.movement().is_none());
+        assert!(track.first_tag_value(&TagKey::ShowMovement).is_none());
+    }
+
+    #[test]
+    fn test_assign_tags_original_release_date() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut value: serde_json::Value = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        value["release-group"]["first-release-date"] = serde_json::json!("1957-10-12");
+        let release: MusicBrainzRelease = serde_json::from_value(value).unwrap();
+        let (collection, release_candidate) =
+            make_collection_and_release_candidate(release, || Box::new(MemoryTag::new()));
+        let collection = collection.assign_tags(&release_candidate, &Config::default());
+
+        assert_eq!(collection.release_date().as_deref(), Some("1958-01-01"));
+        assert_eq!(
+            collection.original_release_date().as_deref(),
+            Some("1957-10-12")
+        );
+        assert_eq!(collection.original_release_year().as_deref(), Some("1957"));
+    }
+
+    #[test]
+    fn test_assign_tags_derived_totals() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut value: serde_json::Value = serde_json::from_str(MUSICBRAINZ_RELEASE_JSON).unwrap();
+        value["media"][0]["track-count"] = serde_json::json!(10);
+        let release: MusicBrainzRelease = serde_json::from_value(value).unwrap();
+        let (collection, release_candidate) =
+            make_collection_and_release_candidate(release, || {
+                Box::new(
+                    MemoryTag::new()
+                        .with_value(TagKey::TotalTracks, Some("12"))
+                        .with_value(TagKey::TotalDiscs, Some("2")),
+                )
+            });
+        let collection = collection.assign_tags(&release_candidate, &Config::default());
+
+        for track in collection.tracks() {
+            assert_eq!(
+                track.first_tag_value(&TagKey::TotalTracks).as_deref(),
+                Some("8")
+            );
+            assert_eq!(
+                track.first_tag_value(&TagKey::TotalDiscs).as_deref(),
+                Some("1")
+            );
+        }
+    }
+
+    #[test]
+    fn test_assign_tags_skipped_keys() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut config = Config::default();
+        config.tags.skip = vec![TagKey::Album, TagKey::Comment];
+
+        let collection = make_collection_from_assignment_with_config(&config, || {
+            Box::new(
+                MemoryTag::new()
+                    .with_value(TagKey::Album, Some("Old Title"))
+                    .with_value(TagKey::Comment, Some("My comment")),
+            )
+        });
+
+        assert_eq!(collection.release_title().as_deref(), Some("Old Title"));
+        assert_eq!(
+            collection.release_artist().as_deref(),
+            Some("The Ahmad Jamal Trio")
+        );
+        for track in collection.tracks() {
+            assert_eq!(track.comment().as_deref(), Some("My comment"));
+        }
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let collection = TaggedFileCollection::new(vec![
+            TaggedFile::with_tags(PathBuf::from("01 - Poinciana.mp3"), vec![]),
+            TaggedFile::with_tags(
+                PathBuf::from("02 - Surrey With the Fringe on Top.mp3"),
+                vec![],
+            ),
+            TaggedFile::with_tags(PathBuf::from("03 - Moonlight in Vermont.ogg"), vec![]),
+        ]);
+        assert_eq!(collection.format_summary(), "2 × MP3, 1 × OGG");
+        assert!(collection.total_length().is_none());
+    }
+
+    #[test]
+    fn test_lookup_tag_conflicts() {
+        use crate::tag::memory::MemoryTag;
+
+        let track = |path: &str, album: &str, artist: &str| {
+            TaggedFile::with_tags(
+                PathBuf::from(path),
+                vec![Box::new(
+                    MemoryTag::new()
+                        .with_value(TagKey::Album, Some(album))
+                        .with_value(TagKey::Artist, Some(artist)),
+                )],
+            )
+        };
+        let mut collection = TaggedFileCollection::new(vec![
+            track("01.flac", "But Not for Me", "Ahmad Jamal"),
+            track("02.flac", "Live at the Pershing", "Ahmad Jamal"),
+            track("03.flac", "But Not for Me", "The Ahmad Jamal Trio"),
+        ]);
+        assert!(collection.release_title().is_none());
+        assert_eq!(
+            collection.lookup_tag_conflicts(),
+            vec![
+                (
+                    TagKey::Album,
+                    vec![
+                        "But Not for Me".to_string(),
+                        "Live at the Pershing".to_string()
+                    ]
+                ),
+                (
+                    TagKey::AlbumArtist,
+                    vec![
+                        "Ahmad Jamal".to_string(),
+                        "The Ahmad Jamal Trio".to_string()
+                    ]
+                ),
+            ]
+        );
+
+        collection.set_release_tag_value(&TagKey::Album, "But Not for Me");
+        collection.set_release_tag_value(&TagKey::AlbumArtist, "The Ahmad Jamal Trio");
+        assert!(collection.lookup_tag_conflicts().is_empty());
+        assert_eq!(
+            collection.release_title().as_deref(),
+            Some("But Not for Me")
+        );
+        assert_eq!(
+            collection.release_artist().as_deref(),
+            Some("The Ahmad Jamal Trio")
+        );
+    }
+
+    #[test]
+    fn test_find_duplicate_tracks() {
+        use crate::tag::memory::MemoryTag;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let track = |name: &str, fingerprint: Option<&str>, content: &str| {
+            let path = tmp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            TaggedFile::with_tags(
+                path,
+                vec![Box::new(
+                    MemoryTag::new().with_value(TagKey::AcoustIdFingerprint, fingerprint),
+                )],
+            )
+        };
+        let collection = TaggedFileCollection::new(vec![
+            track("01.flac", Some("AQAAT0mUaEkSRZEG"), "a"),
+            track("01 (1).flac", Some("AQAAT0mUaEkSRZEG"), "b"),
+            track("02.flac", Some("AQAAT0mkSiqiJEqS"), "c"),
+            track("05.flac", Some("AQAAAA"), "d"),
+            track("06.flac", Some("AQAAAA"), "e"),
+            track("07.flac", Some(""), "f"),
+            track("03.flac", None, "same"),
+            track("03 (1).flac", None, "same"),
+            track("04.flac", None, "diff"),
+        ]);
+        let mut duplicates = collection.find_duplicate_tracks();
+        duplicates.iter_mut().for_each(|paths| paths.sort());
+        duplicates.sort();
+        assert_eq!(
+            duplicates,
+            vec![
+                vec![
+                    tmp_dir.path().join("01 (1).flac"),
+                    tmp_dir.path().join("01.flac")
+                ],
+                vec![
+                    tmp_dir.path().join("03 (1).flac"),
+                    tmp_dir.path().join("03.flac")
+                ],
+            ]
+        );
+
+        let collection = collection.without_tracks(&HashSet::from([
+            tmp_dir.path().join("01 (1).flac"),
+            tmp_dir.path().join("03 (1).flac"),
+        ]));
+        assert_eq!(collection.tracks().count(), 7);
+        assert!(collection.find_duplicate_tracks().is_empty());
+    }
+
+    #[test]
+    fn test_disambiguate_album_directory() {
+        use crate::tag::memory::MemoryTag;
+
+        let mut config = Config::default();
+        config.paths.library_path = "/music".to_string();
+        let mut collection = make_collection_from_assignment(|| Box::new(MemoryTag::new()));
+
+        let directory = collection.album_directory(&config).unwrap().unwrap();
+        assert_eq!(
+            directory,
+            PathBuf::from(
+                "/music/The Ahmad Jamal Trio/1958 - Ahmad Jamal at the Pershing: But Not for Me"
+            )
+        );
+
+        let mut destinations = AlbumDestinations::default();
+        destinations.insert(directory.clone(), collection.release_key());
+        assert!(!destinations.is_taken(&directory, &collection.release_key()));
+        destinations.insert(directory.clone(), "another release".to_string());
+        assert!(destinations.is_taken(&directory, &collection.release_key()));
+
+        assert!(collection
+            .disambiguate_album_directory(&config, &destinations)
+            .unwrap());
+        assert_eq!(
+            collection.album_directory(&config).unwrap().unwrap(),
+            PathBuf::from(
+                "/music/The Ahmad Jamal Trio/1958 - Ahmad Jamal at the Pershing: But Not for Me (1958)"
+            )
+        );
+    }
+
+    #[test]
+    fn test_total_counts_without_tags() {
+        use crate::tag::memory::MemoryTag;
+
+        let track = |disc_number: Option<&str>| {
+            let tag = MemoryTag::new().with_value(TagKey::DiscNumber, disc_number);
+            TaggedFile::with_tags(PathBuf::from("track.flac"), vec![Box::new(tag)])
+        };
+
+        let collection = TaggedFileCollection::new(vec![track(None), track(None), track(None)]);
+        assert_eq!(collection.total_discs().as_deref(), Some("1"));
+        assert_eq!(collection.release_track_count(), Some(3));
+
+        let collection =
+            TaggedFileCollection::new(vec![track(Some("1")), track(Some("1")), track(Some("3"))]);
+        assert_eq!(collection.total_discs().as_deref(), Some("3"));
+        assert_eq!(
+            collection
+                .media()
+                .map(MediaLike::media_track_count)
+                .collect::<Vec<_>>(),
+            vec![Some(2), Some(1)]
+        );
+    }
 }
@@ -7,8 +7,10 @@
 // SPDX-License-Identifier: MPL-2.0
 
 //! Generic release implementations.
+use crate::tag::{EmbeddedCueSheet, StreamInfo, TagKey, ValueProvenance};
 use itertools::Itertools;
 use musicbrainz_rs_nova::entity::artist::Artist as MusicBrainzArtist;
+use musicbrainz_rs_nova::entity::artist_credit::ArtistCredit as MusicBrainzArtistCredit;
 use musicbrainz_rs_nova::entity::relations::Relation as MusicBrainzRelation;
 use musicbrainz_rs_nova::entity::relations::RelationContent as MusicBrainzRelationContent;
 use musicbrainz_rs_nova::entity::release::Track as MusicBrainzReleaseTrack;
@@ -27,7 +29,7 @@ pub struct InvolvedPerson<'a> {
 }
 
 /// Represent a generic release, independent of the underlying source.
-pub trait TrackLike {
+pub trait TrackLike: Sync {
     /// AcoustID associated with the track.
     fn acoustid(&self) -> Option<Cow<'_, str>>;
 
@@ -117,8 +119,8 @@ pub trait TrackLike {
     /// Movement Number.
     fn movement_number(&self) -> Option<Cow<'_, str>>;
 
-    /// Track Artist’s MusicBrainz Identifier.
-    fn musicbrainz_artist_id(&self) -> Option<Cow<'_, str>>;
+    /// Track Artists’ MusicBrainz Identifier(s).
+    fn musicbrainz_artist_id(&self) -> impl Iterator<Item = Cow<'_, str>>;
 
     /// Original Track Artist’s MusicBrainz Identifier.
     fn musicbrainz_original_artist_id(&self) -> Option<Cow<'_, str>>;
@@ -218,6 +220,12 @@ pub trait TrackLike {
     /// Title of the work.
     fn work_title(&self) -> Option<Cow<'_, str>>;
 
+    /// Title of the larger work that the work is a part of (e.g., the symphony that a movement
+    /// belongs to).
+    fn parent_work_title(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
     /// Writer Name(s).
     ///
     /// This is used when uncertain whether the artist is the composer or the lyricist.
@@ -226,6 +234,44 @@ pub trait TrackLike {
     /// Track length.
     fn track_length(&self) -> Option<chrono::TimeDelta>;
 
+    /// Technical properties of the audio stream (e.g., sample rate and exact duration).
+    fn stream_info(&self) -> Option<StreamInfo> {
+        None
+    }
+
+    /// Short name of the audio codec (e.g., `flac`).
+    fn codec(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// Average bitrate in kbit/s.
+    fn bitrate(&self) -> Option<u32> {
+        None
+    }
+
+    /// Sample rate in Hz.
+    fn sample_rate(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| stream_info.sample_rate)
+    }
+
+    /// Number of bits per sample.
+    fn bit_depth(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| u32::from(stream_info.bits_per_sample))
+    }
+
+    /// Number of audio channels.
+    fn channel_count(&self) -> Option<u32> {
+        self.stream_info()
+            .map(|stream_info| u32::from(stream_info.channels))
+    }
+
+    /// Cue sheet that is embedded in the audio file.
+    fn embedded_cuesheet(&self) -> Option<EmbeddedCueSheet> {
+        None
+    }
+
     /// Analyzed metadata for this track.
     fn analyzed_metadata(&self) -> impl AnalyzedTrackMetadata {
         NoAnalyzedTrackMetadata
@@ -240,6 +286,21 @@ pub trait TrackLike {
     fn track_file_extension(&self) -> Option<Cow<'_, str>> {
         None
     }
+
+    /// Where the value for the tag key was read from (e.g., the tag type and field).
+    fn value_provenance(&self, _key: &TagKey) -> Option<ValueProvenance> {
+        None
+    }
+
+    /// Value of the tag with the given key (only available for files).
+    fn tag_value<'a>(&'a self, _key: &'a TagKey) -> Option<Cow<'a, str>> {
+        None
+    }
+
+    /// Where the track length was read from.
+    fn track_length_provenance(&self) -> Option<ValueProvenance> {
+        None
+    }
 }
 
 /// Analyzed track metadata.
@@ -255,6 +316,12 @@ pub trait AnalyzedTrackMetadata {
 
     /// ReplayGain Track Range.
     fn replay_gain_track_range(&self) -> Option<Cow<'_, str>>;
+
+    /// ReplayGain Reference Loudness that the gain was calculated for.
+    fn replay_gain_reference_loudness(&self) -> Option<Cow<'_, str>>;
+
+    /// Tempo in beats per minute.
+    fn bpm(&self) -> Option<Cow<'_, str>>;
 }
 
 /// Adds helper methods to the `MusicBrainzReleaseTrack` struct.
@@ -279,6 +346,16 @@ trait MusicBrainzReleaseTrackHelper {
         &self,
         relation_types: &[&str],
     ) -> impl Iterator<Item = Cow<'_, str>>;
+
+    /// Get the artist credit of this track, or the artist credit of the recording if the track
+    /// does not have one.
+    fn track_artist_credit(&self) -> Option<&[MusicBrainzArtistCredit]>;
+
+    /// Get a work of this track that is part of a larger work, together with the `parts` relation
+    /// and the parent work.
+    fn find_parent_work(
+        &self,
+    ) -> Option<(&MusicBrainzWork, &MusicBrainzRelation, &MusicBrainzWork)>;
 }
 
 impl MusicBrainzReleaseTrackHelper for MusicBrainzReleaseTrack {
@@ -324,6 +401,38 @@ impl MusicBrainzReleaseTrackHelper for MusicBrainzReleaseTrack {
             .map(|artist| &artist.name)
             .map(Cow::from)
     }
+
+    fn track_artist_credit(&self) -> Option<&[MusicBrainzArtistCredit]> {
+        self.artist_credit.as_deref().or(self
+            .recording
+            .as_ref()
+            .and_then(|recording| recording.artist_credit.as_deref()))
+    }
+
+    fn find_parent_work(
+        &self,
+    ) -> Option<(&MusicBrainzWork, &MusicBrainzRelation, &MusicBrainzWork)> {
+        // A work can be part of several other works (e.g., a song that appears in multiple
+        // musicals), so parents with an explicit ordering are preferred.
+        self.find_works()
+            .flat_map(|work| {
+                work.relations
+                    .iter()
+                    .flat_map(|relations| relations.iter())
+                    .filter(|relation| {
+                        relation.relation_type.as_str() == "parts"
+                            && relation.direction.as_str() == "backward"
+                    })
+                    .filter_map(move |relation| {
+                        if let MusicBrainzRelationContent::Work(parent) = &relation.content {
+                            Some((work.as_ref(), relation, parent.as_ref()))
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .min_by_key(|(_, relation, _)| relation.ordering_key.is_none())
+    }
 }
 
 /// Helper method to get the artist from a relation.
@@ -335,6 +444,29 @@ fn relation_artist(relation: &MusicBrainzRelation) -> Option<&MusicBrainzArtist>
     }
 }
 
+/// Describe the involvement of the artist in a performance relation (e.g., `orchestra`,
+/// `choir vocals` or `solo violin`).
+fn relation_involvement(relation: &MusicBrainzRelation) -> String {
+    let attributes = relation.attributes.as_deref().unwrap_or_default();
+    let is_solo = attributes.iter().any(|attribute| attribute == "solo");
+    let involvement = attributes
+        .iter()
+        .filter(|attribute| attribute.as_str() != "solo")
+        .join(", ");
+    let involvement = match (relation.relation_type.as_str(), involvement.is_empty()) {
+        ("performing orchestra", _) => "orchestra".to_string(),
+        ("chorus master", _) => "chorus master".to_string(),
+        ("vocal", true) => "vocals".to_string(),
+        _ => involvement,
+    };
+
+    if is_solo {
+        format!("solo {involvement}").trim_end().to_string()
+    } else {
+        involvement
+    }
+}
+
 impl TrackLike for MusicBrainzReleaseTrack {
     fn acoustid(&self) -> Option<Cow<'_, str>> {
         // TODO: Implement this.
@@ -360,12 +492,7 @@ impl TrackLike for MusicBrainzReleaseTrack {
 
     fn track_artist(&self) -> Option<Cow<'_, str>> {
         Cow::from(
-            self.artist_credit
-                .as_deref()
-                .or(self
-                    .recording
-                    .as_ref()
-                    .and_then(|recording| recording.artist_credit.as_deref()))
+            self.track_artist_credit()
                 .iter()
                 .flat_map(|artists| artists.iter())
                 .fold(String::new(), |acc, artist| {
@@ -381,25 +508,21 @@ impl TrackLike for MusicBrainzReleaseTrack {
     }
 
     fn track_artist_sort_order(&self) -> Option<Cow<'_, str>> {
-        Cow::from(
-            self.artist_credit
-                .as_deref()
-                .or(self
-                    .recording
-                    .as_ref()
-                    .and_then(|recording| recording.artist_credit.as_deref()))
-                .iter()
-                .flat_map(|artists| artists.iter())
-                .map(|artist| &artist.artist)
-                .fold(String::new(), |acc, artist| {
-                    if acc.is_empty() {
-                        acc + &artist.sort_name
-                    } else {
-                        acc + ";" + &artist.sort_name
-                    }
-                }),
-        )
-        .into()
+        // Like the artist name, the combined sort name is built from the credited artists and the
+        // join phrases (e.g., "Davis, Miles & Coltrane, John").
+        let value = self
+            .track_artist_credit()
+            .iter()
+            .flat_map(|artists| artists.iter())
+            .fold(String::new(), |acc, artist_credit| {
+                acc + &artist_credit.artist.sort_name
+                    + artist_credit.joinphrase.as_deref().unwrap_or_default()
+            });
+        if value.is_empty() {
+            None
+        } else {
+            Cow::from(value).into()
+        }
     }
 
     fn bpm(&self) -> Option<Cow<'_, str>> {
@@ -531,23 +654,36 @@ impl TrackLike for MusicBrainzReleaseTrack {
     }
 
     fn movement(&self) -> Option<Cow<'_, str>> {
-        // TODO: Implement this.
-        None
+        self.find_parent_work().map(|(work, _, parent)| {
+            // Movement titles on MusicBrainz usually repeat the parent work title as a prefix,
+            // e.g., "Symphony No. 5 in C minor, op. 67: I. Allegro con brio".
+            let title = work
+                .title
+                .strip_prefix(parent.title.as_str())
+                .and_then(|title| title.strip_prefix(':'))
+                .map(str::trim_start)
+                .filter(|title| !title.is_empty())
+                .unwrap_or(work.title.as_str());
+            Cow::from(title)
+        })
     }
 
     fn movement_count(&self) -> Option<Cow<'_, str>> {
-        // TODO: Implement this.
+        // The number of parts is not included in the work relations of the recording.
         None
     }
 
     fn movement_number(&self) -> Option<Cow<'_, str>> {
-        // TODO: Implement this.
-        None
+        self.find_parent_work()
+            .and_then(|(_, relation, _)| relation.ordering_key)
+            .map(|ordering_key| Cow::from(ordering_key.to_string()))
     }
 
-    fn musicbrainz_artist_id(&self) -> Option<Cow<'_, str>> {
-        // TODO: Implement this.
-        None
+    fn musicbrainz_artist_id(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.track_artist_credit()
+            .iter()
+            .flat_map(|artists| artists.iter())
+            .map(|artist_credit| Cow::from(artist_credit.artist.id.as_str()))
     }
 
     fn musicbrainz_original_artist_id(&self) -> Option<Cow<'_, str>> {
@@ -619,23 +755,21 @@ impl TrackLike for MusicBrainzReleaseTrack {
     }
 
     fn performers(&self) -> Option<Vec<InvolvedPerson<'_>>> {
-        self.find_release_relations_by_type(&["performer", "instrument", "vocal"])
-            .filter_map(|relation| {
-                if let MusicBrainzRelationContent::Artist(artist) = &relation.content {
-                    Some((&artist.name, &relation.attributes))
-                } else {
-                    None
-                }
-            })
-            .map(|(artist, attributes)| {
-                let attrs = attributes.iter().flat_map(|vec| vec.iter()).join(", ");
-                InvolvedPerson {
-                    involvement: Cow::from(attrs),
-                    involvee: Cow::from(artist),
-                }
+        self.find_release_relations_by_type(&[
+            "performer",
+            "instrument",
+            "vocal",
+            "performing orchestra",
+            "chorus master",
+        ])
+        .filter_map(|relation| {
+            relation_artist(relation).map(|artist| InvolvedPerson {
+                involvement: Cow::from(relation_involvement(relation)),
+                involvee: Cow::from(&artist.name),
             })
-            .collect::<Vec<_>>()
-            .into()
+        })
+        .collect::<Vec<_>>()
+        .into()
     }
 
     fn producer(&self) -> impl Iterator<Item = Cow<'_, str>> {
@@ -711,6 +845,11 @@ impl TrackLike for MusicBrainzReleaseTrack {
             .next()
     }
 
+    fn parent_work_title(&self) -> Option<Cow<'_, str>> {
+        self.find_parent_work()
+            .map(|(_, _, parent)| Cow::from(parent.title.as_str()))
+    }
+
     fn writer(&self) -> impl Iterator<Item = Cow<'_, str>> {
         self.find_release_relations_by_type(&["writer"])
             .chain(self.find_work_relations_by_type(&["writer"]))
@@ -744,4 +883,12 @@ impl AnalyzedTrackMetadata for NoAnalyzedTrackMetadata {
     fn replay_gain_track_range(&self) -> Option<Cow<'_, str>> {
         None
     }
+
+    fn replay_gain_reference_loudness(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    fn bpm(&self) -> Option<Cow<'_, str>> {
+        None
+    }
 }
@@ -8,50 +8,183 @@
 
 //! Filesystem-related utility functions.
 
+use crate::config::{CollisionPolicy, TransferMode};
+use sha2::{Digest, Sha256};
 use std::collections::BinaryHeap;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// File name of the per-directory ignore file.
+///
+/// Each line contains a pattern that applies to the directory that contains the file and all of its
+/// subdirectories. Empty lines and lines starting with `#` are ignored.
+pub const IGNORE_FILE_NAME: &str = ".heliconignore";
+
+/// A pattern that excludes files and directories from a directory walk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IgnorePattern {
+    /// Directory that relative patterns are matched against.
+    base: PathBuf,
+    /// The pattern with `*` and `?` wildcards.
+    pattern: String,
+}
+
+impl IgnorePattern {
+    /// Check if the path matches the pattern.
+    ///
+    /// Patterns that contain a `/` are matched against the path relative to the base directory
+    /// (e.g., `*/demos/*`), all other patterns are matched against the file name only.
+    fn matches(&self, path: &Path) -> bool {
+        if self.pattern.contains('/') {
+            path.strip_prefix(&self.base)
+                .ok()
+                .and_then(Path::to_str)
+                .is_some_and(|relative_path| {
+                    matches_file_pattern(&self.pattern, &relative_path.replace('\\', "/"))
+                })
+        } else {
+            path.file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|name| matches_file_pattern(&self.pattern, name))
+        }
+    }
+}
+
+/// Read the ignore patterns from the ignore file in the given directory (if any).
+fn read_ignore_file(dir: &Path) -> Vec<IgnorePattern> {
+    let path = dir.join(IGNORE_FILE_NAME);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return vec![],
+        Err(err) => {
+            log::warn!("Failed to read {}: {err}", path.display());
+            return vec![];
+        }
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|pattern| IgnorePattern {
+            base: dir.to_path_buf(),
+            pattern: pattern.to_string(),
+        })
+        .collect()
+}
+
+/// A directory that is queued for visiting, together with the ignore patterns that apply to it.
+#[derive(Debug, PartialEq, Eq)]
+struct QueuedDir {
+    /// Path of the directory.
+    path: PathBuf,
+    /// Ignore patterns inherited from the parent directories.
+    ignore_patterns: Vec<IgnorePattern>,
+}
+
+impl Ord for QueuedDir {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.path.cmp(&other.path)
+    }
+}
+
+impl PartialOrd for QueuedDir {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
 /// An iterator that recursively walks through a directory structure and yields a tuple `(path,
 /// dirs, files)` for each directory it visits.
 ///
+/// Files and directories that match an ignore pattern are skipped. Patterns are either passed to
+/// [`DirWalk::with_ignore_patterns`] or read from an [`IGNORE_FILE_NAME`] file in the directory or
+/// one of its parents.
+///
 /// This struct is created by [`walk_dir`]. See its documentation for more.
 pub struct DirWalk {
     /// Queued paths that will be visited next.
-    queue: BinaryHeap<PathBuf>,
+    queue: BinaryHeap<QueuedDir>,
+}
+
+impl DirWalk {
+    /// Skip files and directories that match one of the patterns (with `*` and `?` wildcards).
+    ///
+    /// Patterns that contain a `/` are matched against the path relative to the walked directory,
+    /// all other patterns are matched against the file name.
+    #[must_use]
+    pub fn with_ignore_patterns(mut self, patterns: &[String]) -> Self {
+        self.queue = self
+            .queue
+            .into_iter()
+            .map(|mut queued_dir| {
+                queued_dir
+                    .ignore_patterns
+                    .extend(patterns.iter().map(|pattern| IgnorePattern {
+                        base: queued_dir.path.clone(),
+                        pattern: pattern.clone(),
+                    }));
+                queued_dir
+            })
+            .collect();
+        self
+    }
+
+    /// Read the entries of the queued directory and queue its subdirectories.
+    fn visit(
+        &mut self,
+        queued_dir: QueuedDir,
+    ) -> io::Result<(PathBuf, Vec<PathBuf>, Vec<PathBuf>)> {
+        let QueuedDir {
+            path,
+            mut ignore_patterns,
+        } = queued_dir;
+        log::debug!("Queued path: {}", path.display());
+        ignore_patterns.extend(read_ignore_file(&path));
+
+        let mut files = vec![];
+        let mut dirs = vec![];
+        for entry in fs::read_dir(&path)? {
+            let entry_path = entry?.path();
+            if entry_path
+                .file_name()
+                .is_some_and(|name| name == IGNORE_FILE_NAME)
+                || ignore_patterns
+                    .iter()
+                    .any(|pattern| pattern.matches(&entry_path))
+            {
+                log::debug!("Ignored path: {}", entry_path.display());
+                continue;
+            }
+
+            if entry_path.is_dir() {
+                dirs.push(entry_path.clone());
+            } else {
+                files.push(entry_path);
+            }
+        }
+
+        files.sort_unstable();
+
+        for dir in dirs.clone() {
+            self.queue.push(QueuedDir {
+                path: dir,
+                ignore_patterns: ignore_patterns.clone(),
+            });
+        }
+
+        Ok((path, dirs, files))
+    }
 }
 
 impl Iterator for DirWalk {
     type Item = io::Result<(PathBuf, Vec<PathBuf>, Vec<PathBuf>)>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let queued_path = self.queue.pop();
-        queued_path.map(move |path| {
-            log::debug!("Queued path: {}", path.display());
-            fs::read_dir(&path).and_then(move |entries| {
-                let mut files = vec![];
-                let mut dirs = vec![];
-                for entry in entries {
-                    let entry_path = entry?.path();
-
-                    if entry_path.is_dir() {
-                        dirs.push(entry_path.clone());
-                    } else {
-                        files.push(entry_path);
-                    }
-                }
-
-                files.sort_unstable();
-
-                for dir in dirs.clone() {
-                    self.queue.push(dir);
-                }
-
-                Ok((path, dirs, files))
-            })
-        })
+        let queued_dir = self.queue.pop()?;
+        Some(self.visit(queued_dir))
     }
 }
 
@@ -59,7 +192,10 @@ impl Iterator for DirWalk {
 /// consisting of the path of current directory and the files and directories in that directory.
 pub fn walk_dir(path: PathBuf) -> DirWalk {
     let mut queue = BinaryHeap::new();
-    queue.push(path);
+    queue.push(QueuedDir {
+        path,
+        ignore_patterns: vec![],
+    });
     DirWalk { queue }
 }
 
@@ -81,6 +217,7 @@ pub fn copy_file<S: AsRef<Path>, D: AsRef<Path>>(source: S, destination: D) -> i
         .tempfile_in(dest_dir)?;
     let mut source_file = fs::File::open(&source)?;
     let _ = io::copy(&mut source_file, &mut temp_destination_file)?;
+    temp_destination_file.as_file().sync_all()?;
 
     // When copying succeeded, persist the temporary file at the actual destination.
     let temp_destination = temp_destination_file.into_temp_path();
@@ -96,24 +233,501 @@ pub fn copy_file<S: AsRef<Path>, D: AsRef<Path>>(source: S, destination: D) -> i
 
 /// Move the file.
 pub fn move_file<S: AsRef<Path>, D: AsRef<Path>>(source: S, destination: D) -> crate::Result<()> {
+    move_file_checked(source.as_ref(), destination.as_ref(), |_| Ok(()))
+}
+
+/// Move the file and call `check` with the destination path before the source file is removed.
+///
+/// If the check fails, the copied destination file is removed and the source file is kept.
+fn move_file_checked(
+    source: &Path,
+    destination: &Path,
+    check: impl FnOnce(&Path) -> crate::Result<()>,
+) -> crate::Result<()> {
+    if let Some(dest_dir) = destination.parent() {
+        fs::create_dir_all(dest_dir)?;
+    }
+
     // First, try renaming.
-    if let Ok(()) = fs::rename(&source, &destination) {
+    if let Ok(()) = fs::rename(source, destination) {
         log::info!(
             "Renamed file {} to {}",
-            source.as_ref().display(),
-            destination.as_ref().display()
+            source.display(),
+            destination.display()
         );
-        return Ok(());
+        return check(destination);
     }
 
     // If that didn't work, try to copy the source file to a temporary file on the destination
     // filesystem and persist the temporary file under the actual destination path if this
     // succeeds.
-    copy_file(&source, destination)?;
+    copy_file(source, destination)?;
+    if let Err(err) = check(destination) {
+        remove_transferred_file(destination);
+        return Err(err);
+    }
 
     // Then remove the source file.
-    fs::remove_file(&source)?;
-    log::info!("Removed file {}", source.as_ref().display());
+    fs::remove_file(source)?;
+    log::info!("Removed file {}", source.display());
+
+    Ok(())
+}
+
+/// Remove a transferred file that failed verification.
+fn remove_transferred_file(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => log::info!("Removed file {}", path.display()),
+        Err(err) => log::warn!("Failed to remove {}: {err}", path.display()),
+    }
+}
+
+/// Remove the file at the given path if it exists.
+fn remove_existing_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Create a hard link to the source file at the destination path.
+fn hard_link_file(source: &Path, destination: &Path) -> io::Result<()> {
+    if let Some(dest_dir) = destination.parent() {
+        fs::create_dir_all(dest_dir)?;
+    }
+    remove_existing_file(destination)?;
+    fs::hard_link(source, destination)?;
+    log::info!(
+        "Hard linked file {} to {}",
+        source.display(),
+        destination.display()
+    );
+    Ok(())
+}
 
+/// Create a reflink of the source file at the destination path, or copy it if the filesystem
+/// does not support reflinks.
+fn reflink_file(source: &Path, destination: &Path) -> io::Result<()> {
+    if let Some(dest_dir) = destination.parent() {
+        fs::create_dir_all(dest_dir)?;
+    }
+    remove_existing_file(destination)?;
+    if let Err(err) = reflink_copy::reflink(source, destination) {
+        log::debug!(
+            "Failed to reflink {}, falling back to copying: {err}",
+            source.display()
+        );
+        return copy_file(source, destination);
+    }
+    log::info!(
+        "Reflinked file {} to {}",
+        source.display(),
+        destination.display()
+    );
     Ok(())
 }
+
+/// Append a numeric suffix to the file stem (e.g., `track (1).flac`).
+fn append_numeric_suffix_to_file_stem(path: &Path, number: usize) -> PathBuf {
+    let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(format!(" ({number})"));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(OsString::from(file_name))
+}
+
+/// Determine the actual destination path according to the collision policy.
+///
+/// Returns `None` if the file should not be transferred.
+fn resolve_collision(destination: &Path, policy: CollisionPolicy) -> Option<PathBuf> {
+    if !destination.exists() {
+        return Some(destination.to_path_buf());
+    }
+
+    match policy {
+        CollisionPolicy::Skip => None,
+        CollisionPolicy::Overwrite => Some(destination.to_path_buf()),
+        CollisionPolicy::Rename => (1..)
+            .map(|number| append_numeric_suffix_to_file_stem(destination, number))
+            .find(|path| !path.exists()),
+    }
+}
+
+/// Transfer the file to the destination using the given transfer mode.
+///
+/// Returns the path that the file was transferred to, or `None` if the file was not transferred
+/// because the destination already exists and the collision policy is [`CollisionPolicy::Skip`].
+///
+/// # Errors
+///
+/// Returns an error if the file could not be transferred.
+pub fn transfer_file<S: AsRef<Path>, D: AsRef<Path>>(
+    source: S,
+    destination: D,
+    mode: TransferMode,
+    policy: CollisionPolicy,
+) -> crate::Result<Option<PathBuf>> {
+    transfer_file_checked(source.as_ref(), destination.as_ref(), mode, policy, |_| {
+        Ok(())
+    })
+}
+
+/// Transfer the file like [`transfer_file`] and verify that the transferred file has the given
+/// SHA-256 checksum.
+///
+/// When moving the file across filesystems, the copy is verified before the source file is
+/// removed, so the source file is kept if verification fails.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be transferred or if the checksum does not match.
+pub fn transfer_file_verified(
+    source: &Path,
+    destination: &Path,
+    mode: TransferMode,
+    policy: CollisionPolicy,
+    checksum: &str,
+) -> crate::Result<Option<PathBuf>> {
+    transfer_file_checked(source, destination, mode, policy, |path| {
+        if file_checksum(path)? != checksum {
+            return Err(crate::Error::ChecksumMismatch(path.to_path_buf()));
+        }
+        log::debug!("Verified {} ({checksum})", path.display());
+        Ok(())
+    })
+}
+
+/// Transfer the file and call `check` with the destination path (before the source file is
+/// removed, if it is moved).
+fn transfer_file_checked(
+    source: &Path,
+    destination: &Path,
+    mode: TransferMode,
+    policy: CollisionPolicy,
+    check: impl FnOnce(&Path) -> crate::Result<()>,
+) -> crate::Result<Option<PathBuf>> {
+    if source == destination {
+        return Ok(Some(destination.to_path_buf()));
+    }
+
+    let Some(destination) = resolve_collision(destination, policy) else {
+        log::warn!(
+            "Not transferring {} because {} already exists",
+            source.display(),
+            destination.display()
+        );
+        return Ok(None);
+    };
+
+    match mode {
+        TransferMode::Move => {
+            return move_file_checked(source, &destination, check).map(|()| Some(destination))
+        }
+        TransferMode::Copy => copy_file(source, &destination)?,
+        TransferMode::Hardlink => hard_link_file(source, &destination)?,
+        TransferMode::Reflink => reflink_file(source, &destination)?,
+    }
+    if let Err(err) = check(&destination) {
+        remove_transferred_file(&destination);
+        return Err(err);
+    }
+
+    Ok(Some(destination))
+}
+
+/// Compute the SHA-256 checksum of the file as a hexadecimal string.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn file_checksum(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    let _ = io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    let hash = hasher.finalize();
+    Ok(format!("{hash:064x}"))
+}
+
+/// Set the modification time of the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or its modification time cannot be set.
+pub fn set_modified_time(path: &Path, time: SystemTime) -> io::Result<()> {
+    fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(time)
+}
+
+/// Set the permission bits (e.g., `0o644`) of the file or directory.
+///
+/// This does nothing on platforms other than Unix.
+///
+/// # Errors
+///
+/// Returns an error if the permissions cannot be changed.
+pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, mode);
+        Ok(())
+    }
+}
+
+/// Check if the characters of a file name match the characters of a wildcard pattern.
+fn matches_file_pattern_chars(pattern: &[char], file_name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => file_name.is_empty(),
+        Some(('*', pattern_rest)) => (0..=file_name.len())
+            .any(|index| matches_file_pattern_chars(pattern_rest, &file_name[index..])),
+        Some(('?', pattern_rest)) => file_name
+            .split_first()
+            .is_some_and(|(_, name_rest)| matches_file_pattern_chars(pattern_rest, name_rest)),
+        Some((pattern_char, pattern_rest)) => {
+            file_name
+                .split_first()
+                .is_some_and(|(name_char, name_rest)| {
+                    pattern_char.to_lowercase().eq(name_char.to_lowercase())
+                        && matches_file_pattern_chars(pattern_rest, name_rest)
+                })
+        }
+    }
+}
+
+/// Check if a file name matches a pattern with `*` and `?` wildcards (case-insensitive).
+pub fn matches_file_pattern(pattern: &str, file_name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let file_name = file_name.chars().collect::<Vec<_>>();
+    matches_file_pattern_chars(&pattern, &file_name)
+}
+
+/// Remove the directory if it is empty or only contains files that match one of the junk patterns,
+/// then do the same for its parent directories up to (but excluding) `root`.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be read or removed.
+pub fn remove_empty_dirs(dir: &Path, root: &Path, junk_patterns: &[String]) -> io::Result<()> {
+    let is_junk = |path: &PathBuf| {
+        path.is_file()
+            && path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|name| {
+                    junk_patterns
+                        .iter()
+                        .any(|pattern| matches_file_pattern(pattern, name))
+                })
+    };
+
+    let mut current_dir = Some(dir);
+    while let Some(dir) = current_dir.filter(|dir| dir.starts_with(root) && *dir != root) {
+        let entries = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        if !entries.iter().all(is_junk) {
+            break;
+        }
+
+        for path in entries {
+            fs::remove_file(&path)?;
+            log::info!("Removed junk file {}", path.display());
+        }
+        fs::remove_dir(dir)?;
+        log::info!("Removed empty directory {}", dir.display());
+
+        current_dir = dir.parent();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_numeric_suffix_to_file_stem() {
+        assert_eq!(
+            append_numeric_suffix_to_file_stem(Path::new("/music/01 - Track.flac"), 2),
+            PathBuf::from("/music/01 - Track (2).flac")
+        );
+        assert_eq!(
+            append_numeric_suffix_to_file_stem(Path::new("/music/cover"), 1),
+            PathBuf::from("/music/cover (1)")
+        );
+    }
+
+    #[test]
+    fn test_file_checksum() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("file.txt");
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            file_checksum(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_set_mode_and_modified_time() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::Duration;
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("file.txt");
+        fs::write(&path, "abc").unwrap();
+
+        set_mode(&path, 0o640).unwrap();
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        set_modified_time(&path, time).unwrap();
+
+        let metadata = fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+        assert_eq!(metadata.modified().unwrap(), time);
+    }
+
+    #[test]
+    fn test_matches_file_pattern() {
+        assert!(matches_file_pattern("Thumbs.db", "thumbs.db"));
+        assert!(matches_file_pattern("*.log", "EAC rip.log"));
+        assert!(matches_file_pattern("._*", "._01 - Track.flac"));
+        assert!(matches_file_pattern("cd?.jpg", "cd1.jpg"));
+        assert!(!matches_file_pattern("*.log", "log.txt"));
+        assert!(!matches_file_pattern("cd?.jpg", "cd.jpg"));
+    }
+
+    #[test]
+    fn test_walk_dir_ignore_patterns() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path().to_path_buf();
+        let album_dir = root.join("Artist").join("Album");
+        let demos_dir = root.join("Artist").join("demos");
+        fs::create_dir_all(&album_dir).unwrap();
+        fs::create_dir_all(&demos_dir).unwrap();
+        fs::write(album_dir.join("01 - Track.flac"), "").unwrap();
+        fs::write(album_dir.join("._01 - Track.flac"), "").unwrap();
+        fs::write(album_dir.join("02 - Track.mp3"), "").unwrap();
+        fs::write(album_dir.join(IGNORE_FILE_NAME), "# No MP3s\n*.mp3\n").unwrap();
+        fs::write(demos_dir.join("demo.flac"), "").unwrap();
+
+        let files = walk_dir(root)
+            .with_ignore_patterns(&["._*".to_string(), "*/demos/*".to_string()])
+            .filter_map(Result::ok)
+            .flat_map(|(_path, _dirs, files)| files)
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![album_dir.join("01 - Track.flac")]);
+    }
+
+    #[test]
+    fn test_remove_empty_dirs() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path().join("import");
+        let album_dir = root.join("Artist").join("Album");
+        let other_dir = root.join("Other");
+        fs::create_dir_all(&album_dir).unwrap();
+        fs::create_dir_all(&other_dir).unwrap();
+        fs::write(album_dir.join("Thumbs.db"), "").unwrap();
+        fs::write(other_dir.join("track.flac"), "").unwrap();
+
+        let junk_patterns = vec!["thumbs.db".to_string()];
+        remove_empty_dirs(&album_dir, &root, &junk_patterns).unwrap();
+        assert!(!album_dir.exists());
+        assert!(!album_dir.parent().unwrap().exists());
+        assert!(root.exists());
+        assert!(other_dir.join("track.flac").exists());
+
+        fs::remove_file(other_dir.join("track.flac")).unwrap();
+        remove_empty_dirs(&other_dir, &root, &junk_patterns).unwrap();
+        assert!(!other_dir.exists());
+        assert!(root.exists());
+    }
+
+    #[test]
+    fn test_transfer_file_verified() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source = tmp_dir.path().join("source.txt");
+        let destination = tmp_dir.path().join("dest").join("file.txt");
+        fs::write(&source, "data").unwrap();
+        let checksum = file_checksum(&source).unwrap();
+
+        let result = transfer_file_verified(
+            &source,
+            &destination,
+            TransferMode::Copy,
+            CollisionPolicy::Overwrite,
+            "invalid",
+        );
+        assert!(matches!(result, Err(crate::Error::ChecksumMismatch(_))));
+        assert!(source.exists());
+        assert!(!destination.exists());
+
+        let result = transfer_file_verified(
+            &source,
+            &destination,
+            TransferMode::Move,
+            CollisionPolicy::Overwrite,
+            &checksum,
+        );
+        assert_eq!(result.unwrap(), Some(destination.clone()));
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "data");
+    }
+
+    #[test]
+    fn test_transfer_file_collision_policies() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let source = tmp_dir.path().join("source.txt");
+        let destination = tmp_dir.path().join("dest").join("file.txt");
+        fs::write(&source, "new").unwrap();
+        fs::create_dir_all(destination.parent().unwrap()).unwrap();
+        fs::write(&destination, "old").unwrap();
+
+        let result = transfer_file(
+            &source,
+            &destination,
+            TransferMode::Copy,
+            CollisionPolicy::Skip,
+        );
+        assert!(result.unwrap().is_none());
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "old");
+
+        let result = transfer_file(
+            &source,
+            &destination,
+            TransferMode::Copy,
+            CollisionPolicy::Rename,
+        );
+        let renamed = tmp_dir.path().join("dest").join("file (1).txt");
+        assert_eq!(result.unwrap(), Some(renamed.clone()));
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "new");
+        assert!(source.exists());
+
+        let result = transfer_file(
+            &source,
+            &destination,
+            TransferMode::Hardlink,
+            CollisionPolicy::Overwrite,
+        );
+        assert_eq!(result.unwrap(), Some(destination.clone()));
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "new");
+
+        let result = transfer_file(
+            &source,
+            &destination,
+            TransferMode::Move,
+            CollisionPolicy::Overwrite,
+        );
+        assert_eq!(result.unwrap(), Some(destination.clone()));
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "new");
+    }
+}
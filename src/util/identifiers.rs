@@ -0,0 +1,70 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Normalization of release identifiers (barcodes and catalog numbers).
+
+/// Normalize a barcode.
+///
+/// Whitespace and dashes are removed. UPC-A and EAN-13 barcodes that only differ by leading zeros
+/// denote the same GTIN, so these are normalized to the 12-digit UPC-A form if possible. Other
+/// values are returned without whitespace and dashes.
+pub fn normalize_barcode(value: &str) -> String {
+    let digits = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>();
+    if digits.len() < 12 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return digits;
+    }
+
+    let significant_digits = digits.trim_start_matches('0');
+    if significant_digits.len() <= 12 {
+        format!("{significant_digits:0>12}")
+    } else {
+        significant_digits.to_string()
+    }
+}
+
+/// Normalize a catalog number.
+///
+/// The value is converted to uppercase, all kinds of dashes are replaced with a hyphen-minus and
+/// consecutive whitespace is collapsed into a single space.
+pub fn normalize_catalog_number(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .map(|c| match c {
+            '\u{2010}'..='\u{2015}' | '\u{2212}' | '\u{FE63}' | '\u{FF0D}' => '-',
+            c => c,
+        })
+        .collect::<String>()
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_barcode() {
+        assert_eq!(normalize_barcode("0 12345 67890 5"), "012345678905");
+        assert_eq!(normalize_barcode("0012345678905"), "012345678905");
+        assert_eq!(normalize_barcode("4006381333931"), "4006381333931");
+        assert_eq!(normalize_barcode("96385074"), "96385074");
+        assert_eq!(normalize_barcode("none"), "none");
+    }
+
+    #[test]
+    fn test_normalize_catalog_number() {
+        assert_eq!(normalize_catalog_number(" lp\u{2013}628 "), "LP-628");
+        assert_eq!(normalize_catalog_number("LP  628"), "LP 628");
+        assert_eq!(normalize_catalog_number("LP-628"), "LP-628");
+    }
+}
@@ -95,6 +95,16 @@ where
         self.heap.push(Item { value, key });
     }
 
+    /// Returns `true` if the binary heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns an iterator visiting all values in the underlying heap, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|item| &item.value)
+    }
+
     /// Consumes the BinaryHeap and returns a vector in sorted (ascending) order.
     pub fn into_sorted_vec(self) -> Vec<T> {
         self.heap
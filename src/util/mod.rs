@@ -9,15 +9,24 @@
 //! Utility functions
 
 mod fs;
+mod identifiers;
 mod keyed_binheap;
+mod release_snapshot;
 #[cfg(any(test, feature = "dev"))]
 mod testing;
 mod time;
 
-pub use fs::{move_file, walk_dir};
+pub use fs::{
+    copy_file, file_checksum, matches_file_pattern, remove_empty_dirs, set_mode, set_modified_time,
+    transfer_file, transfer_file_verified, walk_dir,
+};
+pub use identifiers::{normalize_barcode, normalize_catalog_number};
 pub use keyed_binheap::KeyedBinaryHeap;
+pub use release_snapshot::ReleaseSnapshot;
 #[cfg(any(test, feature = "dev"))]
 pub use testing::FakeRelease;
 #[cfg(test)]
 pub use testing::FakeTrack;
-pub use time::{parse_year_from_str, FormattedDuration};
+pub use time::{
+    normalize_date_str, parse_partial_date_from_str, parse_year_from_str, FormattedDuration,
+};
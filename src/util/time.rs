@@ -36,7 +36,7 @@ impl FormattedDuration for TimeDelta {
 const PARTIAL_DATE_FORMATS: [&str; 5] = ["%Y-%m-%d", "%Y-%m", "%Y%m%d", "%Y%m", "%Y"];
 
 /// Parse a date from a [`str`] slice by trying various common formats.
-fn parse_partial_date_from_str(value: impl AsRef<str>) -> Option<NaiveDate> {
+pub fn parse_partial_date_from_str(value: impl AsRef<str>) -> Option<NaiveDate> {
     for fmt in PARTIAL_DATE_FORMATS {
         let mut parsed = Parsed::new();
         if parse(&mut parsed, value.as_ref(), StrftimeItems::new(fmt)).is_err() {
@@ -64,9 +64,91 @@ pub fn parse_year_from_str(value: &str) -> Option<String> {
     parse_partial_date_from_str(value).map(|date| date.format("%Y").to_string())
 }
 
+/// Normalize a date to the `YYYY-MM-DD`, `YYYY-MM` or `YYYY` format.
+///
+/// Accepts dates with single-digit components (e.g., `1958-1-1`), dates in `DD/MM/YYYY` or
+/// `MM/DD/YYYY` format (if the order cannot be determined and day and month differ, only the
+/// year is kept) and timestamps (e.g., `1958-01-01T00:00:00`). Returns `None` if the value is not
+/// a valid date.
+pub fn normalize_date_str(value: &str) -> Option<String> {
+    let parts = value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let number = |part: &str| part.parse::<u32>().ok();
+    let (year, month, day) = match parts.as_slice() {
+        [date] if date.len() == 8 => (&date[..4], number(&date[4..6]), number(&date[6..])),
+        [date] if date.len() == 6 => (&date[..4], number(&date[4..]), None),
+        [year] => (*year, None, None),
+        [year, month] if year.len() == 4 => (*year, number(month), None),
+        [year, month, day, ..] if year.len() == 4 => (*year, number(month), number(day)),
+        [first, second, year] if year.len() == 4 => {
+            let (first, second) = (number(first)?, number(second)?);
+            if first > 12 || first == second {
+                (*year, Some(second), Some(first))
+            } else if second > 12 {
+                (*year, Some(first), Some(second))
+            } else {
+                (*year, None, None)
+            }
+        }
+        _ => return None,
+    };
+    if year.len() != 4 {
+        return None;
+    }
+    let year = year.parse::<i32>().ok()?;
+    match (month, day) {
+        (Some(month), Some(day)) => NaiveDate::from_ymd_opt(year, month, day)
+            .map(|date| date.format("%Y-%m-%d").to_string()),
+        (Some(month), None) => {
+            NaiveDate::from_ymd_opt(year, month, 1).map(|date| date.format("%Y-%m").to_string())
+        }
+        _ => NaiveDate::from_ymd_opt(year, 1, 1).map(|date| date.format("%Y").to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_normalize_date_str() {
+        assert_eq!(
+            normalize_date_str("1958-01-17").as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(
+            normalize_date_str("1958-1-1").as_deref(),
+            Some("1958-01-01")
+        );
+        assert_eq!(normalize_date_str("1958-3").as_deref(), Some("1958-03"));
+        assert_eq!(normalize_date_str("1958").as_deref(), Some("1958"));
+        assert_eq!(
+            normalize_date_str("19580117").as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(
+            normalize_date_str("1958-01-17T00:00:00").as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(
+            normalize_date_str("01/01/1958").as_deref(),
+            Some("1958-01-01")
+        );
+        assert_eq!(
+            normalize_date_str("17/01/1958").as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(
+            normalize_date_str("01/17/1958").as_deref(),
+            Some("1958-01-17")
+        );
+        assert_eq!(normalize_date_str("02/03/1958").as_deref(), Some("1958"));
+        assert_eq!(normalize_date_str("1958-02-30"), None);
+        assert_eq!(normalize_date_str("58"), None);
+        assert_eq!(normalize_date_str("unknown"), None);
+    }
     #[test]
     fn test_parse_date_from_str() {
         assert_eq!(